@@ -1,10 +1,13 @@
 pub mod merkle_trie;
 pub mod models;
+mod report;
 mod runner;
 
 pub use runner::TestError as Error;
 
-use runner::{find_all_json_tests, run, TestError};
+use models::SpecName;
+use report::{read_expected_failures, Report};
+use runner::{find_all_json_tests, run, RunOptions, TestError};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -23,16 +26,62 @@ pub struct Cmd {
     /// Output results in JSON format.
     #[structopt(long)]
     json: bool,
+    /// Run only tests whose name or path contains the string. Can be repeated.
+    #[structopt(long = "name")]
+    names: Vec<String>,
+    /// Run only tests of the fork, e.g. `Cancun`. Can be repeated.
+    #[structopt(long = "fork")]
+    forks: Vec<SpecName>,
+    /// File with names of tests that are expected to fail, one per line.
+    ///
+    /// Listed tests that fail don't fail the run, listed tests that pass do.
+    #[structopt(long)]
+    expected_failures: Option<PathBuf>,
+    /// Continue after a failed test and report all failures at the end.
+    #[structopt(short = "k", long)]
+    keep_going: bool,
+    /// Write JSON report with outcome and execution time of every test to the file.
+    #[structopt(long)]
+    report: Option<PathBuf>,
 }
 
 impl Cmd {
     /// Run statetest command.
     pub fn run(&self) -> Result<(), TestError> {
+        let expected_failures = match &self.expected_failures {
+            Some(path) => read_expected_failures(path).unwrap_or_else(|e| {
+                panic!("failed to read expected failures {}: {e}", path.display())
+            }),
+            None => Default::default(),
+        };
+        let opts = RunOptions {
+            single_thread: self.single_thread,
+            trace: self.json,
+            names: self.names.clone(),
+            forks: self.forks.clone(),
+            expected_failures,
+            keep_going: self.keep_going,
+        };
+
+        let mut outcomes = Vec::new();
+        let mut result = Ok(());
         for path in &self.path {
             println!("\nRunning tests in {}...", path.display());
             let test_files = find_all_json_tests(path);
-            run(test_files, self.single_thread, self.json)?
+            let run_result = run(test_files, &opts, &mut outcomes);
+            if result.is_ok() {
+                result = run_result;
+            }
+            if result.is_err() && !self.keep_going {
+                break;
+            }
+        }
+
+        if let Some(path) = &self.report {
+            Report::new(&outcomes)
+                .write(path)
+                .unwrap_or_else(|e| panic!("failed to write report {}: {e}", path.display()));
         }
-        Ok(())
+        result
     }
 }
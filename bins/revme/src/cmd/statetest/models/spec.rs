@@ -1,7 +1,8 @@
 use revm::primitives::SpecId;
-use serde::Deserialize;
+use serde::{de::value::StrDeserializer, Deserialize};
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Hash)]
 pub enum SpecName {
     Frontier,
     FrontierToHomesteadAt5,
@@ -51,3 +52,15 @@ impl SpecName {
         }
     }
 }
+
+impl FromStr for SpecName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let deserializer = StrDeserializer::<serde::de::value::Error>::new(s);
+        match Self::deserialize(deserializer) {
+            Ok(Self::Unknown) | Err(_) => Err(format!("unknown fork {s}")),
+            Ok(spec_name) => Ok(spec_name),
+        }
+    }
+}
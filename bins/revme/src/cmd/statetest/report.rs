@@ -0,0 +1,91 @@
+use serde::{Serialize, Serializer};
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Status of a single executed test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    /// Test failed and is listed in the expected failures.
+    ExpectedFailure,
+    /// Test passed but is listed in the expected failures.
+    UnexpectedPass,
+}
+
+impl TestStatus {
+    /// Returns true if the status fails the run.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Failed | Self::UnexpectedPass)
+    }
+}
+
+/// Outcome of a single test, one for each name, fork and post state index.
+#[derive(Debug, Serialize)]
+pub struct TestOutcome {
+    pub path: PathBuf,
+    pub name: String,
+    /// Fork of the test, `None` if the test file could not be parsed.
+    pub fork: Option<String>,
+    /// Index of the post state in the fork.
+    pub index: Option<usize>,
+    pub status: TestStatus,
+    pub error: Option<String>,
+    /// Execution time in seconds.
+    #[serde(serialize_with = "serialize_duration")]
+    pub elapsed: Duration,
+}
+
+fn serialize_duration<S: Serializer>(elapsed: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(elapsed.as_secs_f64())
+}
+
+/// Machine readable report of the whole run.
+#[derive(Debug, Serialize)]
+pub struct Report<'a> {
+    pub passed: usize,
+    pub failed: usize,
+    pub expected_failures: usize,
+    pub unexpected_passes: usize,
+    /// Total execution time in seconds.
+    #[serde(serialize_with = "serialize_duration")]
+    pub elapsed: Duration,
+    pub tests: &'a [TestOutcome],
+}
+
+impl<'a> Report<'a> {
+    pub fn new(tests: &'a [TestOutcome]) -> Self {
+        let count = |status| tests.iter().filter(|t| t.status == status).count();
+        Self {
+            passed: count(TestStatus::Passed),
+            failed: count(TestStatus::Failed),
+            expected_failures: count(TestStatus::ExpectedFailure),
+            unexpected_passes: count(TestStatus::UnexpectedPass),
+            elapsed: tests.iter().map(|t| t.elapsed).sum(),
+            tests,
+        }
+    }
+
+    /// Writes the report as JSON to `path`.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let file = io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(file, self).map_err(Into::into)
+    }
+}
+
+/// Reads list of expected failures, one test name per line.
+///
+/// Empty lines and lines starting with `#` are ignored.
+pub fn read_expected_failures(path: &Path) -> io::Result<HashSet<String>> {
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
@@ -1,6 +1,7 @@
 use super::{
     merkle_trie::{log_rlp_hash, state_merkle_trie_root},
     models::{SpecName, TestSuite},
+    report::{TestOutcome, TestStatus},
 };
 use indicatif::ProgressBar;
 use revm::{
@@ -12,6 +13,7 @@ use revm::{
     },
 };
 use std::{
+    collections::HashSet,
     io::stdout,
     path::{Path, PathBuf},
     sync::atomic::Ordering,
@@ -41,10 +43,64 @@ pub enum TestErrorKind {
         expected_exception: Option<String>,
         got_exception: Option<String>,
     },
+    #[error("test is listed as expected failure but passed")]
+    UnexpectedPass,
     #[error(transparent)]
     SerdeDeserialize(#[from] serde_json::Error),
 }
 
+/// Options of the test run.
+#[derive(Debug, Default)]
+pub struct RunOptions {
+    /// Run tests in a single thread.
+    pub single_thread: bool,
+    /// Print EIP-3155 traces, implies `single_thread`.
+    pub trace: bool,
+    /// Run only tests whose name or path contains one of the strings. Empty runs all tests.
+    pub names: Vec<String>,
+    /// Run only the given forks. Empty runs all forks.
+    pub forks: Vec<SpecName>,
+    /// Names of tests that are expected to fail.
+    pub expected_failures: HashSet<String>,
+    /// Continue running after a failure instead of stopping at the first one.
+    pub keep_going: bool,
+}
+
+/// Results collected by all runner threads.
+#[derive(Debug, Default)]
+pub struct TestResults {
+    pub outcomes: Vec<TestOutcome>,
+    /// First failure, only collected with [RunOptions::keep_going].
+    pub first_error: Option<TestError>,
+}
+
+impl TestResults {
+    fn push(&mut self, outcome: TestOutcome, error: Option<TestError>) {
+        self.outcomes.push(outcome);
+        if self.first_error.is_none() {
+            self.first_error = error;
+        }
+    }
+}
+
+impl RunOptions {
+    fn matches_name(&self, name: &str) -> bool {
+        self.names.is_empty() || self.names.iter().any(|filter| name.contains(filter))
+    }
+
+    fn matches_fork(&self, spec_name: &SpecName) -> bool {
+        self.forks.is_empty() || self.forks.contains(spec_name)
+    }
+
+    fn is_expected_failure(&self, path: &Path, name: &str) -> bool {
+        self.expected_failures.contains(name)
+            || path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| self.expected_failures.contains(stem))
+    }
+}
+
 pub fn find_all_json_tests(path: &Path) -> Vec<PathBuf> {
     WalkDir::new(path)
         .into_iter()
@@ -99,17 +155,39 @@ fn skip_test(path: &Path) -> bool {
 pub fn execute_test_suite(
     path: &Path,
     elapsed: &Arc<Mutex<Duration>>,
-    trace: bool,
+    opts: &RunOptions,
+    results: &Mutex<TestResults>,
 ) -> Result<(), TestError> {
     if skip_test(path) {
         return Ok(());
     }
+    let path_matches = !opts.names.is_empty() && opts.matches_name(&path.to_string_lossy());
 
     let s = std::fs::read_to_string(path).unwrap();
-    let suite: TestSuite = serde_json::from_str(&s).map_err(|e| TestError {
-        name: path.to_string_lossy().into_owned(),
-        kind: e.into(),
-    })?;
+    let suite: TestSuite = match serde_json::from_str(&s) {
+        Ok(suite) => suite,
+        Err(e) => {
+            let error = TestError {
+                name: path.to_string_lossy().into_owned(),
+                kind: e.into(),
+            };
+            let outcome = TestOutcome {
+                path: path.to_path_buf(),
+                name: error.name.clone(),
+                fork: None,
+                index: None,
+                status: TestStatus::Failed,
+                error: Some(error.kind.to_string()),
+                elapsed: Duration::ZERO,
+            };
+            if opts.keep_going {
+                results.lock().unwrap().push(outcome, Some(error));
+                return Ok(());
+            }
+            results.lock().unwrap().push(outcome, None);
+            return Err(error);
+        }
+    };
 
     let map_caller_keys: HashMap<_, _> = [
         (
@@ -140,6 +218,11 @@ pub fn execute_test_suite(
     .into();
 
     for (name, unit) in suite.0 {
+        if !path_matches && !opts.matches_name(&name) {
+            continue;
+        }
+        let expected_failure = opts.is_expected_failure(path, &name);
+
         // Create database and insert cache
         let mut cache_state = revm::CacheState::new(false);
         for (address, info) in unit.pre {
@@ -201,7 +284,8 @@ pub fn execute_test_suite(
                 SpecName::ByzantiumToConstantinopleAt5
                     | SpecName::Constantinople
                     | SpecName::Unknown
-            ) {
+            ) || !opts.matches_fork(&spec_name)
+            {
                 continue;
             }
 
@@ -257,12 +341,13 @@ pub fn execute_test_suite(
 
                 // do the deed
                 let timer = Instant::now();
-                let exec_result = if trace {
+                let exec_result = if opts.trace {
                     evm.inspect_commit(TracerEip3155::new(Box::new(stdout()), false, false))
                 } else {
                     evm.transact_commit()
                 };
-                *elapsed.lock().unwrap() += timer.elapsed();
+                let test_elapsed = timer.elapsed();
+                *elapsed.lock().unwrap() += test_elapsed;
 
                 // validate results
                 // this is in a closure so we can have a common printing routine for errors
@@ -322,8 +407,47 @@ pub fn execute_test_suite(
                     Ok(())
                 };
 
+                let result = check();
+                let status = match (&result, expected_failure) {
+                    (Ok(()), false) => TestStatus::Passed,
+                    (Ok(()), true) => TestStatus::UnexpectedPass,
+                    (Err(_), false) => TestStatus::Failed,
+                    (Err(_), true) => TestStatus::ExpectedFailure,
+                };
+                let error = match result {
+                    Ok(()) if expected_failure => Some(TestError {
+                        name: name.clone(),
+                        kind: TestErrorKind::UnexpectedPass,
+                    }),
+                    Ok(()) => None,
+                    Err(e) => Some(e),
+                };
+                let outcome = TestOutcome {
+                    path: path.to_path_buf(),
+                    name: name.clone(),
+                    fork: Some(format!("{spec_name:?}")),
+                    index: Some(index),
+                    status,
+                    error: error.as_ref().map(|e| e.kind.to_string()),
+                    elapsed: test_elapsed,
+                };
+                let mut results = results.lock().unwrap();
+                if !status.is_failure() {
+                    results.push(outcome, None);
+                    continue;
+                }
+                if opts.keep_going {
+                    results.push(outcome, error);
+                    continue;
+                }
+                results.push(outcome, None);
+                drop(results);
+                let e = error.unwrap();
+                if status == TestStatus::UnexpectedPass {
+                    return Err(e);
+                }
+
                 // dump state and traces if test failed
-                let Err(e) = check() else { continue };
 
                 // print only once
                 static FAILED: AtomicBool = AtomicBool::new(false);
@@ -360,74 +484,92 @@ pub fn execute_test_suite(
     Ok(())
 }
 
+/// Runs the test files and appends outcomes of the executed tests to `outcomes`.
 pub fn run(
     test_files: Vec<PathBuf>,
-    mut single_thread: bool,
-    trace: bool,
+    opts: &RunOptions,
+    outcomes: &mut Vec<TestOutcome>,
 ) -> Result<(), TestError> {
-    if trace {
-        single_thread = true;
-    }
+    let single_thread = opts.single_thread || opts.trace;
     let n_files = test_files.len();
 
     let endjob = Arc::new(AtomicBool::new(false));
     let console_bar = Arc::new(ProgressBar::new(n_files as u64));
     let queue = Arc::new(Mutex::new((0usize, test_files)));
     let elapsed = Arc::new(Mutex::new(std::time::Duration::ZERO));
+    let results = Arc::new(Mutex::new(TestResults::default()));
 
     let num_threads = match (single_thread, std::thread::available_parallelism()) {
         (true, _) | (false, Err(_)) => 1,
         (false, Ok(n)) => n.get(),
     };
     let num_threads = num_threads.min(n_files);
-    let mut handles = Vec::with_capacity(num_threads);
-    for i in 0..num_threads {
-        let queue = queue.clone();
-        let endjob = endjob.clone();
-        let console_bar = console_bar.clone();
-        let elapsed = elapsed.clone();
+    let mut errors = std::thread::scope(|scope| {
+        let mut handles = Vec::with_capacity(num_threads);
+        for i in 0..num_threads {
+            let queue = queue.clone();
+            let endjob = endjob.clone();
+            let console_bar = console_bar.clone();
+            let elapsed = elapsed.clone();
+            let results = results.clone();
+
+            let thread = std::thread::Builder::new().name(format!("runner-{i}"));
+
+            let f = move || loop {
+                if endjob.load(Ordering::SeqCst) {
+                    return Ok(());
+                }
 
-        let thread = std::thread::Builder::new().name(format!("runner-{i}"));
+                let (_index, test_path) = {
+                    let (current_idx, queue) = &mut *queue.lock().unwrap();
+                    let prev_idx = *current_idx;
+                    let Some(test_path) = queue.get(prev_idx).cloned() else {
+                        return Ok(());
+                    };
+                    *current_idx = prev_idx + 1;
+                    (prev_idx, test_path)
+                };
 
-        let f = move || loop {
-            if endjob.load(Ordering::SeqCst) {
-                return Ok(());
-            }
+                if let Err(err) = execute_test_suite(&test_path, &elapsed, opts, &results) {
+                    endjob.store(true, Ordering::SeqCst);
+                    return Err(err);
+                }
 
-            let (_index, test_path) = {
-                let (current_idx, queue) = &mut *queue.lock().unwrap();
-                let prev_idx = *current_idx;
-                let Some(test_path) = queue.get(prev_idx).cloned() else {
-                    return Ok(());
-                };
-                *current_idx = prev_idx + 1;
-                (prev_idx, test_path)
+                console_bar.inc(1);
             };
+            handles.push(thread.spawn_scoped(scope, f).unwrap());
+        }
 
-            if let Err(err) = execute_test_suite(&test_path, &elapsed, trace) {
-                endjob.store(true, Ordering::SeqCst);
-                return Err(err);
+        // join all threads before returning an error
+        let mut errors = Vec::new();
+        for handle in handles {
+            if let Err(e) = handle.join().unwrap() {
+                errors.push(e);
             }
-
-            console_bar.inc(1);
-        };
-        handles.push(thread.spawn(f).unwrap());
-    }
-
-    // join all threads before returning an error
-    let mut errors = Vec::new();
-    for handle in handles {
-        if let Err(e) = handle.join().unwrap() {
-            errors.push(e);
         }
-    }
+        errors
+    });
 
     console_bar.finish();
 
+    let TestResults {
+        outcomes: mut new_outcomes,
+        first_error,
+    } = std::mem::take(&mut *results.lock().unwrap());
+    let n_failures = new_outcomes
+        .iter()
+        .filter(|outcome| outcome.status.is_failure())
+        .count();
+    outcomes.append(&mut new_outcomes);
+
     println!(
         "Finished execution. Total CPU time: {:.6}s",
         elapsed.lock().unwrap().as_secs_f64()
     );
+    if let Some(error) = first_error {
+        println!("{n_failures} tests failed, first failure:");
+        return Err(error);
+    }
     if errors.is_empty() {
         println!("All tests passed!");
         Ok(())
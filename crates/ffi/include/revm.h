@@ -0,0 +1,311 @@
+#ifndef REVM_H
+#define REVM_H
+
+/* Generated with cbindgen from crates/ffi, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Status returned by the functions of the library.
+ */
+typedef enum RevmStatus {
+  REVM_STATUS_OK = 0,
+  /**
+   * A required pointer is null.
+   */
+  REVM_STATUS_NULL_POINTER = 1,
+  /**
+   * The spec id is unknown.
+   */
+  REVM_STATUS_INVALID_SPEC_ID = 2,
+  /**
+   * The transaction is invalid, see [`revm_last_error`].
+   */
+  REVM_STATUS_INVALID_TRANSACTION = 3,
+  /**
+   * The block is invalid, see [`revm_last_error`].
+   */
+  REVM_STATUS_INVALID_HEADER = 4,
+} RevmStatus;
+
+/**
+ * Outcome of an execution.
+ */
+typedef enum RevmResultKind {
+  REVM_RESULT_KIND_SUCCESS = 0,
+  /**
+   * Reverted by `REVERT`, the output is the revert data.
+   */
+  REVM_RESULT_KIND_REVERT = 1,
+  /**
+   * Halted by an exceptional condition, all gas is spent.
+   */
+  REVM_RESULT_KIND_HALT = 2,
+} RevmResultKind;
+
+/**
+ * EVM with an in-memory database.
+ */
+typedef struct RevmEvm RevmEvm;
+
+/**
+ * Result of an execution with the changed accounts, sorted by address and storage key.
+ */
+typedef struct RevmResult RevmResult;
+
+/**
+ * Log emitted by the transaction, borrowed from the result.
+ */
+typedef struct RevmLog {
+  uint8_t address[20];
+  /**
+   * `topics_len` topics of 32 bytes each.
+   */
+  const uint8_t (*topics)[32];
+  size_t topics_len;
+  const uint8_t *data;
+  size_t data_len;
+} RevmLog;
+
+/**
+ * Account changed by the transaction.
+ */
+typedef struct RevmAccount {
+  uint8_t address[20];
+  /**
+   * Big-endian balance.
+   */
+  uint8_t balance[32];
+  uint64_t nonce;
+  uint8_t code_hash[32];
+  /**
+   * Number of storage slots read or written, see [`revm_result_storage`].
+   */
+  size_t storage_len;
+  /**
+   * Whether the account was created by the transaction.
+   */
+  bool created;
+  /**
+   * Whether the account was selfdestructed and must be removed with its storage.
+   */
+  bool selfdestructed;
+} RevmAccount;
+
+/**
+ * Storage slot of a changed account.
+ */
+typedef struct RevmStorageSlot {
+  uint8_t key[32];
+  /**
+   * Value before the transaction.
+   */
+  uint8_t original_value[32];
+  /**
+   * Value after the transaction.
+   */
+  uint8_t present_value[32];
+} RevmStorageSlot;
+
+/**
+ * Creates an EVM with the default environment and an empty database.
+ *
+ * Must be freed with [`revm_evm_free`].
+ */
+struct RevmEvm *revm_evm_new(void);
+
+/**
+ * Frees the EVM.
+ */
+void revm_evm_free(struct RevmEvm *evm);
+
+/**
+ * Returns the message of the last failed execution, or null if there is none.
+ *
+ * The message is valid until the next execution.
+ */
+const char *revm_last_error(const struct RevmEvm *evm);
+
+/**
+ * Sets the spec, see `SpecId` of revm for the values.
+ */
+enum RevmStatus revm_set_spec_id(struct RevmEvm *evm, uint8_t spec_id);
+
+/**
+ * Sets the chain id.
+ */
+enum RevmStatus revm_set_chain_id(struct RevmEvm *evm, uint64_t chain_id);
+
+/**
+ * Sets the block number.
+ */
+enum RevmStatus revm_set_block_number(struct RevmEvm *evm, uint64_t number);
+
+/**
+ * Sets the block timestamp.
+ */
+enum RevmStatus revm_set_block_timestamp(struct RevmEvm *evm, uint64_t timestamp);
+
+/**
+ * Sets the block gas limit.
+ */
+enum RevmStatus revm_set_block_gas_limit(struct RevmEvm *evm, uint64_t gas_limit);
+
+/**
+ * Sets the block base fee.
+ */
+enum RevmStatus revm_set_block_basefee(struct RevmEvm *evm, const uint8_t *basefee);
+
+/**
+ * Sets the block beneficiary.
+ */
+enum RevmStatus revm_set_block_coinbase(struct RevmEvm *evm, const uint8_t *coinbase);
+
+/**
+ * Sets the block prevrandao, required from the Merge.
+ */
+enum RevmStatus revm_set_block_prevrandao(struct RevmEvm *evm, const uint8_t *prevrandao);
+
+/**
+ * Sets the excess blob gas of the block, required from Cancun.
+ */
+enum RevmStatus revm_set_block_excess_blob_gas(struct RevmEvm *evm, uint64_t excess_blob_gas);
+
+/**
+ * Sets the sender of the transaction.
+ */
+enum RevmStatus revm_set_tx_caller(struct RevmEvm *evm, const uint8_t *caller);
+
+/**
+ * Sets the called address of the transaction, null for a contract creation.
+ */
+enum RevmStatus revm_set_tx_to(struct RevmEvm *evm, const uint8_t *to);
+
+/**
+ * Sets the value transferred by the transaction.
+ */
+enum RevmStatus revm_set_tx_value(struct RevmEvm *evm, const uint8_t *value);
+
+/**
+ * Sets the input data of the transaction, the data is copied.
+ */
+enum RevmStatus revm_set_tx_data(struct RevmEvm *evm, const uint8_t *data, size_t len);
+
+/**
+ * Sets the gas limit of the transaction.
+ */
+enum RevmStatus revm_set_tx_gas_limit(struct RevmEvm *evm, uint64_t gas_limit);
+
+/**
+ * Sets the gas price of the transaction, or the maximum fee per gas of EIP-1559 transactions.
+ */
+enum RevmStatus revm_set_tx_gas_price(struct RevmEvm *evm, const uint8_t *gas_price);
+
+/**
+ * Sets the priority fee of EIP-1559 transactions, null for legacy transactions.
+ */
+enum RevmStatus revm_set_tx_gas_priority_fee(struct RevmEvm *evm, const uint8_t *gas_priority_fee);
+
+/**
+ * Sets the nonce of the transaction, it is checked against the nonce of the caller.
+ */
+enum RevmStatus revm_set_tx_nonce(struct RevmEvm *evm, uint64_t nonce);
+
+/**
+ * Inserts an account into the database, replacing the balance, nonce and code of an existing
+ * one. The code can be null if `code_len` is zero.
+ */
+enum RevmStatus revm_insert_account(struct RevmEvm *evm,
+                                    const uint8_t *address,
+                                    const uint8_t *balance,
+                                    uint64_t nonce,
+                                    const uint8_t *code,
+                                    size_t code_len);
+
+/**
+ * Sets a storage slot of an account in the database.
+ */
+enum RevmStatus revm_insert_storage(struct RevmEvm *evm,
+                                    const uint8_t *address,
+                                    const uint8_t *slot,
+                                    const uint8_t *value);
+
+/**
+ * Executes the transaction of the environment and writes the result to `out`.
+ *
+ * The changes are committed to the database if `commit` is true. The result must be freed with
+ * [`revm_result_free`]. If the transaction or block is invalid, nothing is written and the
+ * message is returned by [`revm_last_error`].
+ */
+enum RevmStatus revm_transact(struct RevmEvm *evm, bool commit, struct RevmResult **out);
+
+/**
+ * Frees the result.
+ */
+void revm_result_free(struct RevmResult *result);
+
+/**
+ * Returns the outcome of the execution.
+ */
+enum RevmResultKind revm_result_kind(const struct RevmResult *result);
+
+/**
+ * Returns the gas used by the transaction, with the refund subtracted.
+ */
+uint64_t revm_result_gas_used(const struct RevmResult *result);
+
+/**
+ * Returns the gas refunded for cleared storage.
+ */
+uint64_t revm_result_gas_refunded(const struct RevmResult *result);
+
+/**
+ * Returns the output, or the revert data, and writes its length to `len`.
+ *
+ * Returns null if the execution halted.
+ */
+const uint8_t *revm_result_output(const struct RevmResult *result, size_t *len);
+
+/**
+ * Writes the address of the created contract to `address`.
+ *
+ * Returns false if the transaction didn't create a contract.
+ */
+bool revm_result_created_address(const struct RevmResult *result, uint8_t (*address)[20]);
+
+/**
+ * Returns the number of logs, zero if the execution wasn't successful.
+ */
+size_t revm_result_logs_len(const struct RevmResult *result);
+
+/**
+ * Writes the log at `index` to `log`, returns false if it is out of bounds.
+ */
+bool revm_result_log(const struct RevmResult *result, size_t index, struct RevmLog *log);
+
+/**
+ * Returns the number of accounts changed by the transaction.
+ */
+size_t revm_result_accounts_len(const struct RevmResult *result);
+
+/**
+ * Writes the changed account at `index` to `account`, returns false if it is out of bounds.
+ */
+bool revm_result_account(const struct RevmResult *result,
+                         size_t index,
+                         struct RevmAccount *account);
+
+/**
+ * Writes the storage slot at `slot_index` of the changed account at `account_index` to `slot`,
+ * returns false if either is out of bounds.
+ */
+bool revm_result_storage(const struct RevmResult *result,
+                         size_t account_index,
+                         size_t slot_index,
+                         struct RevmStorageSlot *slot);
+
+#endif  /* REVM_H */
@@ -0,0 +1,395 @@
+//! # revm-ffi
+//!
+//! C bindings of revm, the header is `include/revm.h`.
+//!
+//! An [`RevmEvm`] holds the environment and an in-memory database. Embedders set the
+//! environment fields, insert accounts, execute the transaction with [`revm_transact`] and read
+//! the output, logs and changed accounts from the returned [`RevmResult`].
+//!
+//! # Safety
+//!
+//! Handles must be created by this library and not used after they are freed. Addresses point to
+//! 20 bytes, words to 32 big-endian bytes and data to the given number of bytes. Pointers
+//! returned by a result are valid until the result is freed.
+#![warn(unreachable_pub, unused_crate_dependencies)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![allow(clippy::missing_safety_doc)]
+
+mod result;
+
+pub use result::*;
+
+use revm::{
+    primitives::{
+        AccountInfo, Address, Bytecode, Bytes, EVMError, SpecId, TransactTo, B256, KECCAK_EMPTY,
+        U256,
+    },
+    DatabaseCommit, InMemoryDB, EVM,
+};
+use std::{
+    ffi::{c_char, CString},
+    ptr, slice,
+};
+
+/// Status returned by the functions of the library.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevmStatus {
+    Ok = 0,
+    /// A required pointer is null.
+    NullPointer = 1,
+    /// The spec id is unknown.
+    InvalidSpecId = 2,
+    /// The transaction is invalid, see [`revm_last_error`].
+    InvalidTransaction = 3,
+    /// The block is invalid, see [`revm_last_error`].
+    InvalidHeader = 4,
+}
+
+/// EVM with an in-memory database.
+pub struct RevmEvm {
+    evm: EVM<InMemoryDB>,
+    last_error: Option<CString>,
+}
+
+/// Creates an EVM with the default environment and an empty database.
+///
+/// Must be freed with [`revm_evm_free`].
+#[no_mangle]
+pub extern "C" fn revm_evm_new() -> *mut RevmEvm {
+    let mut evm = EVM::new();
+    evm.database(InMemoryDB::default());
+    Box::into_raw(Box::new(RevmEvm {
+        evm,
+        last_error: None,
+    }))
+}
+
+/// Frees the EVM.
+#[no_mangle]
+pub unsafe extern "C" fn revm_evm_free(evm: *mut RevmEvm) {
+    if !evm.is_null() {
+        drop(Box::from_raw(evm));
+    }
+}
+
+/// Returns the message of the last failed execution, or null if there is none.
+///
+/// The message is valid until the next execution.
+#[no_mangle]
+pub unsafe extern "C" fn revm_last_error(evm: *const RevmEvm) -> *const c_char {
+    evm.as_ref()
+        .and_then(|evm| evm.last_error.as_ref())
+        .map_or(ptr::null(), |error| error.as_ptr())
+}
+
+/// Sets the spec, see `SpecId` of revm for the values.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_spec_id(evm: *mut RevmEvm, spec_id: u8) -> RevmStatus {
+    let Some(spec_id) = SpecId::try_from_u8(spec_id) else {
+        return RevmStatus::InvalidSpecId;
+    };
+    with_evm(evm, |evm| evm.env.cfg.spec_id = spec_id)
+}
+
+/// Sets the chain id.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_chain_id(evm: *mut RevmEvm, chain_id: u64) -> RevmStatus {
+    with_evm(evm, |evm| evm.env.cfg.chain_id = chain_id)
+}
+
+/// Sets the block number.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_block_number(evm: *mut RevmEvm, number: u64) -> RevmStatus {
+    with_evm(evm, |evm| evm.env.block.number = U256::from(number))
+}
+
+/// Sets the block timestamp.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_block_timestamp(evm: *mut RevmEvm, timestamp: u64) -> RevmStatus {
+    with_evm(evm, |evm| evm.env.block.timestamp = U256::from(timestamp))
+}
+
+/// Sets the block gas limit.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_block_gas_limit(evm: *mut RevmEvm, gas_limit: u64) -> RevmStatus {
+    with_evm(evm, |evm| evm.env.block.gas_limit = U256::from(gas_limit))
+}
+
+/// Sets the block base fee.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_block_basefee(
+    evm: *mut RevmEvm,
+    basefee: *const u8,
+) -> RevmStatus {
+    let Some(basefee) = read_word(basefee) else {
+        return RevmStatus::NullPointer;
+    };
+    with_evm(evm, |evm| evm.env.block.basefee = basefee)
+}
+
+/// Sets the block beneficiary.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_block_coinbase(
+    evm: *mut RevmEvm,
+    coinbase: *const u8,
+) -> RevmStatus {
+    let Some(coinbase) = read_address(coinbase) else {
+        return RevmStatus::NullPointer;
+    };
+    with_evm(evm, |evm| evm.env.block.coinbase = coinbase)
+}
+
+/// Sets the block prevrandao, required from the Merge.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_block_prevrandao(
+    evm: *mut RevmEvm,
+    prevrandao: *const u8,
+) -> RevmStatus {
+    let Some(prevrandao) = read_word(prevrandao) else {
+        return RevmStatus::NullPointer;
+    };
+    with_evm(evm, |evm| {
+        evm.env.block.prevrandao = Some(B256::from(prevrandao))
+    })
+}
+
+/// Sets the excess blob gas of the block, required from Cancun.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_block_excess_blob_gas(
+    evm: *mut RevmEvm,
+    excess_blob_gas: u64,
+) -> RevmStatus {
+    with_evm(evm, |evm| {
+        evm.env.block.set_blob_excess_gas_and_price(excess_blob_gas)
+    })
+}
+
+/// Sets the sender of the transaction.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_tx_caller(evm: *mut RevmEvm, caller: *const u8) -> RevmStatus {
+    let Some(caller) = read_address(caller) else {
+        return RevmStatus::NullPointer;
+    };
+    with_evm(evm, |evm| evm.env.tx.caller = caller)
+}
+
+/// Sets the called address of the transaction, null for a contract creation.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_tx_to(evm: *mut RevmEvm, to: *const u8) -> RevmStatus {
+    let transact_to = match read_address(to) {
+        Some(to) => TransactTo::Call(to),
+        None => TransactTo::create(),
+    };
+    with_evm(evm, |evm| evm.env.tx.transact_to = transact_to)
+}
+
+/// Sets the value transferred by the transaction.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_tx_value(evm: *mut RevmEvm, value: *const u8) -> RevmStatus {
+    let Some(value) = read_word(value) else {
+        return RevmStatus::NullPointer;
+    };
+    with_evm(evm, |evm| evm.env.tx.value = value)
+}
+
+/// Sets the input data of the transaction, the data is copied.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_tx_data(
+    evm: *mut RevmEvm,
+    data: *const u8,
+    len: usize,
+) -> RevmStatus {
+    let Some(data) = read_bytes(data, len) else {
+        return RevmStatus::NullPointer;
+    };
+    with_evm(evm, |evm| evm.env.tx.data = data)
+}
+
+/// Sets the gas limit of the transaction.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_tx_gas_limit(evm: *mut RevmEvm, gas_limit: u64) -> RevmStatus {
+    with_evm(evm, |evm| evm.env.tx.gas_limit = gas_limit)
+}
+
+/// Sets the gas price of the transaction, or the maximum fee per gas of EIP-1559 transactions.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_tx_gas_price(
+    evm: *mut RevmEvm,
+    gas_price: *const u8,
+) -> RevmStatus {
+    let Some(gas_price) = read_word(gas_price) else {
+        return RevmStatus::NullPointer;
+    };
+    with_evm(evm, |evm| evm.env.tx.gas_price = gas_price)
+}
+
+/// Sets the priority fee of EIP-1559 transactions, null for legacy transactions.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_tx_gas_priority_fee(
+    evm: *mut RevmEvm,
+    gas_priority_fee: *const u8,
+) -> RevmStatus {
+    let gas_priority_fee = read_word(gas_priority_fee);
+    with_evm(evm, |evm| evm.env.tx.gas_priority_fee = gas_priority_fee)
+}
+
+/// Sets the nonce of the transaction, it is checked against the nonce of the caller.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_tx_nonce(evm: *mut RevmEvm, nonce: u64) -> RevmStatus {
+    with_evm(evm, |evm| evm.env.tx.nonce = Some(nonce))
+}
+
+/// Inserts an account into the database, replacing the balance, nonce and code of an existing
+/// one. The code can be null if `code_len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn revm_insert_account(
+    evm: *mut RevmEvm,
+    address: *const u8,
+    balance: *const u8,
+    nonce: u64,
+    code: *const u8,
+    code_len: usize,
+) -> RevmStatus {
+    let (Some(address), Some(balance), Some(code)) = (
+        read_address(address),
+        read_word(balance),
+        read_bytes(code, code_len),
+    ) else {
+        return RevmStatus::NullPointer;
+    };
+    let info = AccountInfo::new(balance, nonce, KECCAK_EMPTY, Bytecode::new_raw(code));
+    with_evm(evm, |evm| db(evm).insert_account_info(address, info))
+}
+
+/// Sets a storage slot of an account in the database.
+#[no_mangle]
+pub unsafe extern "C" fn revm_insert_storage(
+    evm: *mut RevmEvm,
+    address: *const u8,
+    slot: *const u8,
+    value: *const u8,
+) -> RevmStatus {
+    let (Some(address), Some(slot), Some(value)) =
+        (read_address(address), read_word(slot), read_word(value))
+    else {
+        return RevmStatus::NullPointer;
+    };
+    with_evm(evm, |evm| {
+        let Ok(()) = db(evm).insert_account_storage(address, slot, value);
+    })
+}
+
+/// Executes the transaction of the environment and writes the result to `out`.
+///
+/// The changes are committed to the database if `commit` is true. The result must be freed with
+/// [`revm_result_free`]. If the transaction or block is invalid, nothing is written and the
+/// message is returned by [`revm_last_error`].
+#[no_mangle]
+pub unsafe extern "C" fn revm_transact(
+    evm: *mut RevmEvm,
+    commit: bool,
+    out: *mut *mut RevmResult,
+) -> RevmStatus {
+    let (Some(evm), false) = (evm.as_mut(), out.is_null()) else {
+        return RevmStatus::NullPointer;
+    };
+    evm.last_error = None;
+    let result_and_state = match evm.evm.transact() {
+        Ok(result_and_state) => result_and_state,
+        Err(error) => {
+            let status = match error {
+                EVMError::Header(_) => RevmStatus::InvalidHeader,
+                EVMError::Transaction(_) => RevmStatus::InvalidTransaction,
+                EVMError::Database(error) => match error {},
+            };
+            evm.last_error = CString::new(error.to_string()).ok();
+            return status;
+        }
+    };
+    if commit {
+        db(&mut evm.evm).commit(result_and_state.state.clone());
+    }
+    *out = Box::into_raw(Box::new(RevmResult::new(
+        result_and_state.result,
+        result_and_state.state,
+    )));
+    RevmStatus::Ok
+}
+
+unsafe fn with_evm(evm: *mut RevmEvm, f: impl FnOnce(&mut EVM<InMemoryDB>)) -> RevmStatus {
+    match evm.as_mut() {
+        Some(evm) => {
+            f(&mut evm.evm);
+            RevmStatus::Ok
+        }
+        None => RevmStatus::NullPointer,
+    }
+}
+
+fn db(evm: &mut EVM<InMemoryDB>) -> &mut InMemoryDB {
+    evm.db().expect("database is set in revm_evm_new")
+}
+
+unsafe fn read_address(address: *const u8) -> Option<Address> {
+    (!address.is_null()).then(|| Address::from_slice(slice::from_raw_parts(address, 20)))
+}
+
+unsafe fn read_word(word: *const u8) -> Option<U256> {
+    (!word.is_null()).then(|| U256::from_be_slice(slice::from_raw_parts(word, 32)))
+}
+
+unsafe fn read_bytes(data: *const u8, len: usize) -> Option<Bytes> {
+    match (data.is_null(), len) {
+        (_, 0) => Some(Bytes::new()),
+        (true, _) => None,
+        (false, len) => Some(Bytes::copy_from_slice(slice::from_raw_parts(data, len))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_pointers() {
+        unsafe {
+            assert_eq!(
+                revm_set_chain_id(ptr::null_mut(), 1),
+                RevmStatus::NullPointer
+            );
+            let evm = revm_evm_new();
+            assert_eq!(
+                revm_set_tx_caller(evm, ptr::null()),
+                RevmStatus::NullPointer
+            );
+            assert_eq!(revm_set_tx_data(evm, ptr::null(), 0), RevmStatus::Ok);
+            assert_eq!(revm_set_spec_id(evm, 200), RevmStatus::InvalidSpecId);
+            assert_eq!(
+                revm_transact(evm, false, ptr::null_mut()),
+                RevmStatus::NullPointer
+            );
+            revm_evm_free(evm);
+        }
+    }
+
+    #[test]
+    fn invalid_transaction() {
+        unsafe {
+            let evm = revm_evm_new();
+            revm_set_tx_gas_limit(evm, 1_000);
+            let mut result = ptr::null_mut();
+            assert_eq!(
+                revm_transact(evm, false, &mut result),
+                RevmStatus::InvalidTransaction
+            );
+            assert!(result.is_null());
+            let error = std::ffi::CStr::from_ptr(revm_last_error(evm));
+            assert!(error
+                .to_str()
+                .unwrap()
+                .contains("CallGasCostMoreThanGasLimit"));
+            revm_evm_free(evm);
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Accessors of the execution result and the changed accounts.
+
+use revm::primitives::{Account, Address, ExecutionResult, Output, State, StorageSlot, U256};
+use std::ptr;
+
+/// Result of an execution with the changed accounts, sorted by address and storage key.
+pub struct RevmResult {
+    result: ExecutionResult,
+    accounts: Vec<ChangedAccount>,
+}
+
+/// Changed account with its storage sorted by key, to be accessed by index.
+struct ChangedAccount {
+    address: Address,
+    account: Account,
+    storage: Vec<(U256, StorageSlot)>,
+}
+
+impl RevmResult {
+    pub(crate) fn new(result: ExecutionResult, state: State) -> Self {
+        let mut accounts: Vec<_> = state
+            .into_iter()
+            .filter(|(_, account)| account.is_touched())
+            .map(|(address, account)| {
+                let mut storage: Vec<_> = account
+                    .storage
+                    .iter()
+                    .map(|(key, slot)| (*key, slot.clone()))
+                    .collect();
+                storage.sort_unstable_by_key(|(key, _)| *key);
+                ChangedAccount {
+                    address,
+                    account,
+                    storage,
+                }
+            })
+            .collect();
+        accounts.sort_unstable_by_key(|changed| changed.address);
+        Self { result, accounts }
+    }
+}
+
+/// Outcome of an execution.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevmResultKind {
+    Success = 0,
+    /// Reverted by `REVERT`, the output is the revert data.
+    Revert = 1,
+    /// Halted by an exceptional condition, all gas is spent.
+    Halt = 2,
+}
+
+/// Log emitted by the transaction, borrowed from the result.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RevmLog {
+    pub address: [u8; 20],
+    /// `topics_len` topics of 32 bytes each.
+    pub topics: *const [u8; 32],
+    pub topics_len: usize,
+    pub data: *const u8,
+    pub data_len: usize,
+}
+
+/// Account changed by the transaction.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RevmAccount {
+    pub address: [u8; 20],
+    /// Big-endian balance.
+    pub balance: [u8; 32],
+    pub nonce: u64,
+    pub code_hash: [u8; 32],
+    /// Number of storage slots read or written, see [`revm_result_storage`].
+    pub storage_len: usize,
+    /// Whether the account was created by the transaction.
+    pub created: bool,
+    /// Whether the account was selfdestructed and must be removed with its storage.
+    pub selfdestructed: bool,
+}
+
+/// Storage slot of a changed account.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RevmStorageSlot {
+    pub key: [u8; 32],
+    /// Value before the transaction.
+    pub original_value: [u8; 32],
+    /// Value after the transaction.
+    pub present_value: [u8; 32],
+}
+
+/// Frees the result.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_free(result: *mut RevmResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+/// Returns the outcome of the execution.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_kind(result: *const RevmResult) -> RevmResultKind {
+    match &(*result).result {
+        ExecutionResult::Success { .. } => RevmResultKind::Success,
+        ExecutionResult::Revert { .. } => RevmResultKind::Revert,
+        ExecutionResult::Halt { .. } => RevmResultKind::Halt,
+    }
+}
+
+/// Returns the gas used by the transaction, with the refund subtracted.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_gas_used(result: *const RevmResult) -> u64 {
+    (*result).result.gas_used()
+}
+
+/// Returns the gas refunded for cleared storage.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_gas_refunded(result: *const RevmResult) -> u64 {
+    (*result).result.gas_refunded()
+}
+
+/// Returns the output, or the revert data, and writes its length to `len`.
+///
+/// Returns null if the execution halted.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_output(
+    result: *const RevmResult,
+    len: *mut usize,
+) -> *const u8 {
+    let output = (*result).result.output();
+    if !len.is_null() {
+        *len = output.map_or(0, |output| output.len());
+    }
+    output.map_or(ptr::null(), |output| output.as_ptr())
+}
+
+/// Writes the address of the created contract to `address`.
+///
+/// Returns false if the transaction didn't create a contract.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_created_address(
+    result: *const RevmResult,
+    address: *mut [u8; 20],
+) -> bool {
+    match &(*result).result {
+        ExecutionResult::Success {
+            output: Output::Create(_, Some(created)),
+            ..
+        } => {
+            *address = created.0 .0;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Returns the number of logs, zero if the execution wasn't successful.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_logs_len(result: *const RevmResult) -> usize {
+    match &(*result).result {
+        ExecutionResult::Success { logs, .. } => logs.len(),
+        _ => 0,
+    }
+}
+
+/// Writes the log at `index` to `log`, returns false if it is out of bounds.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_log(
+    result: *const RevmResult,
+    index: usize,
+    log: *mut RevmLog,
+) -> bool {
+    let ExecutionResult::Success { logs, .. } = &(*result).result else {
+        return false;
+    };
+    let Some(entry) = logs.get(index) else {
+        return false;
+    };
+    *log = RevmLog {
+        address: entry.address.0 .0,
+        topics: entry.topics.as_ptr().cast(),
+        topics_len: entry.topics.len(),
+        data: entry.data.as_ptr(),
+        data_len: entry.data.len(),
+    };
+    true
+}
+
+/// Returns the number of accounts changed by the transaction.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_accounts_len(result: *const RevmResult) -> usize {
+    (&*result).accounts.len()
+}
+
+/// Writes the changed account at `index` to `account`, returns false if it is out of bounds.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_account(
+    result: *const RevmResult,
+    index: usize,
+    account: *mut RevmAccount,
+) -> bool {
+    let Some(changed) = (&*result).accounts.get(index) else {
+        return false;
+    };
+    *account = RevmAccount {
+        address: changed.address.0 .0,
+        balance: changed.account.info.balance.to_be_bytes(),
+        nonce: changed.account.info.nonce,
+        code_hash: changed.account.info.code_hash.0,
+        storage_len: changed.storage.len(),
+        created: changed.account.is_created(),
+        selfdestructed: changed.account.is_selfdestructed(),
+    };
+    true
+}
+
+/// Writes the storage slot at `slot_index` of the changed account at `account_index` to `slot`,
+/// returns false if either is out of bounds.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_storage(
+    result: *const RevmResult,
+    account_index: usize,
+    slot_index: usize,
+    slot: *mut RevmStorageSlot,
+) -> bool {
+    let Some((key, entry)) = (&*result)
+        .accounts
+        .get(account_index)
+        .and_then(|changed| changed.storage.get(slot_index))
+    else {
+        return false;
+    };
+    *slot = RevmStorageSlot {
+        key: key.to_be_bytes(),
+        original_value: entry.previous_or_original_value.to_be_bytes(),
+        present_value: entry.present_value.to_be_bytes(),
+    };
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use core::mem::MaybeUninit;
+    use revm::primitives::{address, B256};
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000001");
+    const CONTRACT: Address = address!("2000000000000000000000000000000000000002");
+    const COINBASE: Address = address!("3000000000000000000000000000000000000003");
+
+    unsafe fn transact(evm: *mut RevmEvm) -> *mut RevmResult {
+        let mut result = ptr::null_mut();
+        assert_eq!(revm_transact(evm, true, &mut result), RevmStatus::Ok);
+        result
+    }
+
+    #[test]
+    fn executes_and_reads_results() {
+        // SSTORE(0, 1), LOG1(0, 0, topic 0xaa), RETURN(0, 0)
+        let code = [
+            0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0xaa, 0x60, 0x00, 0x60, 0x00, 0xa1, 0x60, 0x00,
+            0x60, 0x00, 0xf3,
+        ];
+        let balance = U256::from(10).pow(U256::from(18)).to_be_bytes::<32>();
+        unsafe {
+            let evm = revm_evm_new();
+            assert_eq!(revm_set_spec_id(evm, SpecId::CANCUN as u8), RevmStatus::Ok);
+            revm_set_block_coinbase(evm, COINBASE.as_ptr());
+            revm_set_block_prevrandao(evm, B256::ZERO.as_ptr());
+            revm_set_block_excess_blob_gas(evm, 0);
+            revm_insert_account(evm, CALLER.as_ptr(), balance.as_ptr(), 0, ptr::null(), 0);
+            revm_insert_account(
+                evm,
+                CONTRACT.as_ptr(),
+                U256::ZERO.to_be_bytes::<32>().as_ptr(),
+                1,
+                code.as_ptr(),
+                code.len(),
+            );
+            revm_set_tx_caller(evm, CALLER.as_ptr());
+            revm_set_tx_to(evm, CONTRACT.as_ptr());
+            revm_set_tx_gas_limit(evm, 100_000);
+
+            let result = transact(evm);
+            assert_eq!(revm_result_kind(result), RevmResultKind::Success);
+            assert!(revm_result_gas_used(result) > 21_000);
+            let mut len = usize::MAX;
+            assert!(!revm_result_output(result, &mut len).is_null());
+            assert_eq!(len, 0);
+            assert!(!revm_result_created_address(result, &mut [0; 20]));
+
+            assert_eq!(revm_result_logs_len(result), 1);
+            let mut log = MaybeUninit::uninit();
+            assert!(revm_result_log(result, 0, log.as_mut_ptr()));
+            let log = log.assume_init();
+            assert_eq!(log.address, CONTRACT.0 .0);
+            assert_eq!(log.topics_len, 1);
+            assert_eq!((*log.topics)[31], 0xaa);
+            assert!(!revm_result_log(result, 1, &mut log.clone()));
+
+            // The caller, the contract and the coinbase.
+            assert_eq!(revm_result_accounts_len(result), 3);
+            let mut account = MaybeUninit::uninit();
+            assert!(revm_result_account(result, 1, account.as_mut_ptr()));
+            let account = account.assume_init();
+            assert_eq!(account.address, CONTRACT.0 .0);
+            assert_eq!(account.storage_len, 1);
+            let mut slot = MaybeUninit::uninit();
+            assert!(revm_result_storage(result, 1, 0, slot.as_mut_ptr()));
+            let slot = slot.assume_init();
+            assert_eq!(slot.original_value, [0; 32]);
+            assert_eq!(slot.present_value, U256::from(1).to_be_bytes::<32>());
+            revm_result_free(result);
+
+            // The changes were committed, so the caller nonce is checked against the next one.
+            revm_set_tx_nonce(evm, 0);
+            let mut result = ptr::null_mut();
+            assert_eq!(
+                revm_transact(evm, false, &mut result),
+                RevmStatus::InvalidTransaction
+            );
+            revm_evm_free(evm);
+        }
+    }
+}
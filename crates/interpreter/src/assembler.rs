@@ -0,0 +1,171 @@
+//! Minimal assembler to write bytecode in tests.
+//!
+//! Labels are marked with a `JUMPDEST` and jumps to them are resolved when the code is built, e.g.
+//! `Assembler::new().push(1).label("loop").jump("loop").build()`. The [`bytecode!`](crate::bytecode!) macro takes
+//! the same steps with opcodes written by name.
+
+use crate::{
+    opcode,
+    primitives::{Bytecode, Bytes, U256},
+};
+use alloc::{string::String, vec::Vec};
+
+/// Builder of legacy bytecode with labels.
+///
+/// Jumps to labels are pushed with `PUSH2` and resolved in [`Assembler::build`].
+#[derive(Clone, Debug, Default)]
+pub struct Assembler {
+    code: Vec<u8>,
+    labels: Vec<(String, usize)>,
+    label_refs: Vec<(String, usize)>,
+}
+
+impl Assembler {
+    /// Creates an empty assembler.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an opcode.
+    pub fn op(mut self, opcode: u8) -> Self {
+        self.code.push(opcode);
+        self
+    }
+
+    /// Appends raw bytes.
+    pub fn raw(mut self, bytes: &[u8]) -> Self {
+        self.code.extend_from_slice(bytes);
+        self
+    }
+
+    /// Pushes the value with the smallest `PUSH`, zero is pushed with `PUSH1` so that the code
+    /// runs before Shanghai.
+    pub fn push(self, value: u64) -> Self {
+        self.push_word(U256::from(value))
+    }
+
+    /// Pushes the word with the smallest `PUSH`, see [`Assembler::push`].
+    pub fn push_word(self, value: U256) -> Self {
+        let bytes = value.to_be_bytes::<32>();
+        let start = bytes.iter().position(|&b| b != 0).unwrap_or(31);
+        self.push_bytes(&bytes[start..])
+    }
+
+    /// Pushes the bytes with `PUSH1` to `PUSH32`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes are empty or longer than 32 bytes.
+    pub fn push_bytes(mut self, bytes: &[u8]) -> Self {
+        assert!(
+            (1..=32).contains(&bytes.len()),
+            "push of {} bytes",
+            bytes.len()
+        );
+        self.code.push(opcode::PUSH0 + bytes.len() as u8);
+        self.code.extend_from_slice(bytes);
+        self
+    }
+
+    /// Marks the current position with a `JUMPDEST` to jump to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the label is already defined.
+    pub fn label(mut self, name: &str) -> Self {
+        assert!(
+            self.labels.iter().all(|(label, _)| label != name),
+            "label `{name}` defined twice"
+        );
+        self.labels.push((name.into(), self.code.len()));
+        self.op(opcode::JUMPDEST)
+    }
+
+    /// Pushes the position of the label.
+    pub fn push_label(mut self, name: &str) -> Self {
+        self.label_refs.push((name.into(), self.code.len() + 1));
+        self.raw(&[opcode::PUSH2, 0, 0])
+    }
+
+    /// Jumps to the label.
+    pub fn jump(self, name: &str) -> Self {
+        self.push_label(name).op(opcode::JUMP)
+    }
+
+    /// Jumps to the label if the top of the stack is not zero.
+    pub fn jumpi(self, name: &str) -> Self {
+        self.push_label(name).op(opcode::JUMPI)
+    }
+
+    /// Returns the code with the label positions filled in.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pushed label is not defined.
+    pub fn build(mut self) -> Bytes {
+        for (name, offset) in &self.label_refs {
+            let Some((_, pc)) = self.labels.iter().find(|(label, _)| label == name) else {
+                panic!("label `{name}` is not defined");
+            };
+            let pc = u16::try_from(*pc).expect("label position fits in PUSH2");
+            self.code[*offset..*offset + 2].copy_from_slice(&pc.to_be_bytes());
+        }
+        self.code.into()
+    }
+
+    /// Returns the raw [`Bytecode`] of the code, see [`Assembler::build`].
+    pub fn into_bytecode(self) -> Bytecode {
+        Bytecode::new_raw(self.build())
+    }
+}
+
+/// Assembles raw [`Bytecode`] from opcode names and [`Assembler`](crate::assembler::Assembler)
+/// methods, e.g. `bytecode![push(1), push(0), SSTORE, label("end"), STOP]`.
+#[macro_export]
+macro_rules! bytecode {
+    (@item $asm:ident, $op:ident) => {
+        $asm.op($crate::opcode::$op)
+    };
+    (@item $asm:ident, $method:ident($($arg:expr),* $(,)?)) => {
+        $asm.$method($($arg),*)
+    };
+    ($($op:ident $(($($arg:expr),* $(,)?))?),* $(,)?) => {{
+        let asm = $crate::assembler::Assembler::new();
+        $(
+            let asm = $crate::bytecode!(@item asm, $op $(($($arg),*))?);
+        )*
+        asm.into_bytecode()
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_labels() {
+        let code = bytecode![
+            push(0),
+            jumpi("end"),
+            jump("start"),
+            label("start"),
+            push_word(U256::from(0x1234)),
+            push_bytes(&[0; 3]),
+            label("end"),
+            STOP,
+        ];
+        assert_eq!(
+            code.original_bytes().as_ref(),
+            &[
+                0x60, 0x00, 0x61, 0x00, 0x12, 0x57, 0x61, 0x00, 0x0a, 0x56, 0x5b, 0x61, 0x12, 0x34,
+                0x62, 0x00, 0x00, 0x00, 0x5b, 0x00
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic = "label `missing` is not defined"]
+    fn undefined_label() {
+        Assembler::new().jump("missing").build();
+    }
+}
@@ -6,8 +6,11 @@ mod constants;
 pub use calc::*;
 pub use constants::*;
 
+use crate::primitives::GasBreakdown;
+
 /// Represents the state of gas during execution.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Gas {
     /// The initial gas limit.
     limit: u64,
@@ -40,12 +43,18 @@ impl Gas {
         self.limit
     }
 
-    /// Returns the amount of gas that was used.
+    /// Returns the amount of gas that was used for memory expansion.
     #[inline]
     pub const fn memory(&self) -> u64 {
         self.memory
     }
 
+    /// Returns the amount of gas that was used without memory expansion.
+    #[inline]
+    pub const fn used_without_memory(&self) -> u64 {
+        self.used
+    }
+
     /// Returns the amount of gas that was refunded.
     #[inline]
     pub const fn refunded(&self) -> i64 {
@@ -85,6 +94,75 @@ impl Gas {
         self.refunded = refund;
     }
 
+    /// Returns the refund capped to a part of the spent gas.
+    ///
+    /// [EIP-3529] reduced the cap from a half to a fifth of the spent gas in London.
+    ///
+    /// [EIP-3529]: https://eips.ethereum.org/EIPS/eip-3529
+    #[inline]
+    pub const fn capped_refund(&self, is_london: bool) -> u64 {
+        let max_refund_quotient = if is_london { 5 } else { 2 };
+        let refunded = if self.refunded < 0 {
+            0
+        } else {
+            self.refunded as u64
+        };
+        let max_refund = self.all_used_gas / max_refund_quotient;
+        if refunded < max_refund {
+            refunded
+        } else {
+            max_refund
+        }
+    }
+
+    /// Caps the refund to a part of the spent gas, see [`Gas::capped_refund`].
+    #[inline]
+    pub fn set_final_refund(&mut self, is_london: bool) {
+        self.refunded = self.capped_refund(is_london) as i64;
+    }
+
+    /// Erases a gas cost from the totals.
+    ///
+    /// Returns `false` and leaves the gas unchanged if more gas is returned than was used.
+    #[inline]
+    pub fn checked_erase_cost(&mut self, returned: u64) -> bool {
+        if returned > self.used {
+            return false;
+        }
+        self.erase_cost(returned);
+        true
+    }
+
+    /// Records a refund value.
+    ///
+    /// Returns `false` and leaves the gas unchanged if the refund overflows.
+    #[inline]
+    pub fn checked_record_refund(&mut self, refund: i64) -> bool {
+        match self.refunded.checked_add(refund) {
+            Some(refunded) => {
+                self.refunded = refunded;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns how the gas of a finished transaction was spent.
+    ///
+    /// `intrinsic` is the gas charged before execution. Refund is expected to be already
+    /// capped, see [`Gas::set_final_refund`].
+    #[inline]
+    pub fn breakdown(&self, intrinsic: u64) -> GasBreakdown {
+        // Some chains don't charge the intrinsic gas, e.g. system transactions on Optimism.
+        let intrinsic = intrinsic.min(self.all_used_gas);
+        GasBreakdown {
+            intrinsic,
+            execution: self.all_used_gas - intrinsic,
+            refunded: self.refunded.max(0) as u64,
+            returned: self.remaining(),
+        }
+    }
+
     /// Records an explicit cost.
     ///
     /// Returns `false` if the gas limit is exceeded.
@@ -1,6 +1,6 @@
 use super::constants::*;
 use crate::inner_models::SelfDestructResult;
-use crate::primitives::{Address, Spec, SpecId::*, U256};
+use crate::primitives::{Address, InvalidTransaction, Spec, SpecId, SpecId::*, TxEnv, U256};
 use alloc::vec::Vec;
 
 #[allow(clippy::collapsible_else_if)]
@@ -344,6 +344,36 @@ pub fn initial_tx_gas<SPEC: Spec>(
     is_create: bool,
     access_list: &[(Address, Vec<U256>)],
 ) -> u64 {
+    initial_tx_gas_in(SPEC::SPEC_ID, input, is_create, access_list)
+}
+
+/// Calculates the intrinsic gas of the transaction, the gas that is deducted before execution.
+///
+/// It contains the base stipend, the cost of the input data and the access list, and for
+/// contract creations the creation and initcode costs.
+///
+/// Returns [`InvalidTransaction::CallGasCostMoreThanGasLimit`] if the gas limit of the
+/// transaction doesn't cover it, so it can be used to validate transactions without an EVM.
+pub fn calculate_intrinsic_gas(tx: &TxEnv, spec_id: SpecId) -> Result<u64, InvalidTransaction> {
+    let initial_gas = initial_tx_gas_in(
+        spec_id,
+        &tx.data,
+        tx.transact_to.is_create(),
+        &tx.access_list,
+    );
+    if initial_gas > tx.gas_limit {
+        return Err(InvalidTransaction::CallGasCostMoreThanGasLimit);
+    }
+    Ok(initial_gas)
+}
+
+fn initial_tx_gas_in(
+    spec_id: SpecId,
+    input: &[u8],
+    is_create: bool,
+    access_list: &[(Address, Vec<U256>)],
+) -> u64 {
+    let enabled = |fork| SpecId::enabled(spec_id, fork);
     let mut initial_gas = 0;
     let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
     let non_zero_data_len = input.len() as u64 - zero_data_len;
@@ -351,10 +381,10 @@ pub fn initial_tx_gas<SPEC: Spec>(
     // initdate stipend
     initial_gas += zero_data_len * TRANSACTION_ZERO_DATA;
     // EIP-2028: Transaction data gas cost reduction
-    initial_gas += non_zero_data_len * if SPEC::enabled(ISTANBUL) { 16 } else { 68 };
+    initial_gas += non_zero_data_len * if enabled(ISTANBUL) { 16 } else { 68 };
 
     // get number of access list account and storages.
-    if SPEC::enabled(BERLIN) {
+    if enabled(BERLIN) {
         let accessed_slots = access_list
             .iter()
             .fold(0, |slot_count, (_, slots)| slot_count + slots.len() as u64);
@@ -364,7 +394,7 @@ pub fn initial_tx_gas<SPEC: Spec>(
 
     // base stipend
     initial_gas += if is_create {
-        if SPEC::enabled(HOMESTEAD) {
+        if enabled(HOMESTEAD) {
             // EIP-2: Homestead Hard-fork Changes
             53000
         } else {
@@ -376,9 +406,45 @@ pub fn initial_tx_gas<SPEC: Spec>(
 
     // EIP-3860: Limit and meter initcode
     // Initcode stipend for bytecode analysis
-    if SPEC::enabled(SHANGHAI) && is_create {
+    if enabled(SHANGHAI) && is_create {
         initial_gas += initcode_cost(input.len() as u64)
     }
 
     initial_gas
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{Bytes, TransactTo};
+
+    #[test]
+    fn intrinsic_gas() {
+        let mut tx = TxEnv {
+            gas_limit: 100_000,
+            data: Bytes::from_static(&[0, 1, 2]),
+            access_list: vec![(Address::ZERO, vec![U256::ZERO, U256::from(1)])],
+            ..Default::default()
+        };
+        let call = 21_000 + 4 + 2 * 16 + 2_400 + 2 * 1_900;
+        assert_eq!(calculate_intrinsic_gas(&tx, SpecId::LATEST), Ok(call));
+        // Access lists are free and non zero data is more expensive before Istanbul.
+        assert_eq!(
+            calculate_intrinsic_gas(&tx, SpecId::HOMESTEAD),
+            Ok(21_000 + 4 + 2 * 68)
+        );
+
+        tx.transact_to = TransactTo::create();
+        assert_eq!(
+            calculate_intrinsic_gas(&tx, SpecId::LATEST),
+            Ok(call + 32_000 + 2)
+        );
+
+        tx.gas_limit = call - 1;
+        tx.transact_to = TransactTo::Call(Address::ZERO);
+        assert_eq!(
+            calculate_intrinsic_gas(&tx, SpecId::LATEST),
+            Err(InvalidTransaction::CallGasCostMoreThanGasLimit)
+        );
+    }
+}
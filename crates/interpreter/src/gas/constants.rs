@@ -5,6 +5,7 @@ pub const LOW: u64 = 5;
 pub const MID: u64 = 8;
 pub const HIGH: u64 = 10;
 pub const JUMPDEST: u64 = 1;
+pub const CONDJUMP: u64 = 4;
 pub const SELFDESTRUCT: i64 = 24000;
 pub const CREATE: u64 = 32000;
 pub const CALLVALUE: u64 = 9000;
@@ -5,7 +5,9 @@ use crate::{
 use alloc::vec::Vec;
 
 mod dummy;
+mod noop;
 pub use dummy::DummyHost;
+pub use noop::NoopHost;
 
 /// EVM context host.
 pub trait Host {
@@ -53,4 +55,25 @@ pub trait Host {
 
     /// Mark `address` to be deleted, with funds transferred to `target`.
     fn selfdestruct(&mut self, address: Address, target: Address) -> Option<SelfDestructResult>;
+
+    /// Called before JUMP or JUMPI at `pc` of `address` is executed, `condition` is `None` for
+    /// JUMP.
+    ///
+    /// Destination and condition can be modified. Returns false if the jump is forbidden.
+    ///
+    /// By default all jumps are allowed unchanged.
+    #[inline]
+    fn jump(
+        &mut self,
+        address: Address,
+        pc: usize,
+        dest: &mut U256,
+        condition: Option<&mut U256>,
+    ) -> bool {
+        let _ = address;
+        let _ = pc;
+        let _ = dest;
+        let _ = condition;
+        true
+    }
 }
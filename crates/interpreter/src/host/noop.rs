@@ -0,0 +1,85 @@
+use crate::{
+    primitives::{Address, Bytecode, Bytes, Env, B256, KECCAK_EMPTY, U256},
+    Host, SelfDestructResult,
+};
+use alloc::vec::Vec;
+
+/// A [Host] without state, for running the [crate::Interpreter] on its own.
+///
+/// Accounts are empty and warm, storage reads zero and all writes, logs and self-destructs are
+/// discarded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NoopHost {
+    pub env: Env,
+}
+
+impl NoopHost {
+    /// Create a new noop host with the given [`Env`].
+    #[inline]
+    pub fn new(env: Env) -> Self {
+        Self { env }
+    }
+}
+
+impl Host for NoopHost {
+    #[inline]
+    fn env(&mut self) -> &mut Env {
+        &mut self.env
+    }
+
+    #[inline]
+    fn load_account(&mut self, _address: Address) -> Option<(bool, bool)> {
+        Some((false, true))
+    }
+
+    #[inline]
+    fn block_hash(&mut self, _number: U256) -> Option<B256> {
+        Some(B256::ZERO)
+    }
+
+    #[inline]
+    fn balance(&mut self, _address: Address) -> Option<(U256, bool)> {
+        Some((U256::ZERO, false))
+    }
+
+    #[inline]
+    fn code(&mut self, _address: Address) -> Option<(Bytecode, bool)> {
+        Some((Bytecode::default(), false))
+    }
+
+    #[inline]
+    fn code_hash(&mut self, _address: Address) -> Option<(B256, bool)> {
+        Some((KECCAK_EMPTY, false))
+    }
+
+    #[inline]
+    fn sload(&mut self, _address: Address, _index: U256) -> Option<(U256, bool)> {
+        Some((U256::ZERO, false))
+    }
+
+    #[inline]
+    fn sstore(
+        &mut self,
+        _address: Address,
+        _index: U256,
+        value: U256,
+    ) -> Option<(U256, U256, U256, bool)> {
+        Some((U256::ZERO, U256::ZERO, value, false))
+    }
+
+    #[inline]
+    fn tload(&mut self, _address: Address, _index: U256) -> U256 {
+        U256::ZERO
+    }
+
+    #[inline]
+    fn tstore(&mut self, _address: Address, _index: U256, _value: U256) {}
+
+    #[inline]
+    fn log(&mut self, _address: Address, _topics: Vec<B256>, _data: Bytes) {}
+
+    #[inline]
+    fn selfdestruct(&mut self, _address: Address, _target: Address) -> Option<SelfDestructResult> {
+        Some(SelfDestructResult::default())
+    }
+}
@@ -1,5 +1,5 @@
 pub use crate::primitives::CreateScheme;
-use crate::primitives::{Address, Bytes, B256, U256};
+use crate::primitives::{Address, Bytes, Env, B256, U256};
 
 /// Inputs for a call.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -17,6 +17,8 @@ pub struct CallInputs {
     pub context: CallContext,
     /// Whether this is a static call.
     pub is_static: bool,
+    /// Kind of the transaction if this is its first frame, `None` for sub calls.
+    pub tx_kind: Option<TxKind>,
 }
 
 /// Inputs for a create call.
@@ -33,6 +35,36 @@ pub struct CreateInputs {
     pub init_code: Bytes,
     /// The gas limit of the call.
     pub gas_limit: u64,
+    /// Kind of the transaction if this is its first frame, `None` for sub calls.
+    pub tx_kind: Option<TxKind>,
+}
+
+/// Kind of the transaction that started the first frame.
+///
+/// Lets inspectors tell apart transactions that don't come from an Ethereum signer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TxKind {
+    /// Ethereum transaction.
+    Ethereum,
+    /// Optimism deposit transaction.
+    Deposit,
+    /// Chain specific transaction, see `TxEnv::chain_tx_kind`.
+    Chain(u8),
+}
+
+impl TxKind {
+    /// Returns the kind of the transaction of `env`.
+    pub fn from_env(env: &Env) -> Self {
+        if let Some(kind) = env.tx.chain_tx_kind {
+            return Self::Chain(kind);
+        }
+        #[cfg(feature = "optimism")]
+        if env.cfg.optimism && env.tx.optimism.source_hash.is_some() {
+            return Self::Deposit;
+        }
+        Self::Ethereum
+    }
 }
 
 impl CreateInputs {
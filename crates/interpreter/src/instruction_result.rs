@@ -44,9 +44,15 @@ pub enum InstructionResult {
     CreateContractStartingWithEF,
     /// EIP-3860: Limit and meter initcode. Initcode size limit exceeded.
     CreateInitcodeSizeLimit,
+    /// EOF init code is invalid or it returned invalid EOF code.
+    InvalidEOFInitCode,
+    /// EIP-4750: EOF function return stack overflow.
+    EOFFunctionStackOverflow,
 
     /// Fatal external error. Returned by database.
     FatalExternalError,
+    /// Precompile input exceeds the configured limit.
+    PrecompileInputTooLarge,
 }
 
 impl InstructionResult {
@@ -88,7 +94,10 @@ impl InstructionResult {
                 | Self::CreateContractSizeLimit
                 | Self::CreateContractStartingWithEF
                 | Self::CreateInitcodeSizeLimit
+                | Self::InvalidEOFInitCode
+                | Self::EOFFunctionStackOverflow
                 | Self::FatalExternalError
+                | Self::PrecompileInputTooLarge
         )
     }
 }
@@ -191,7 +200,12 @@ impl From<InstructionResult> for SuccessOrHalt {
                 Self::Halt(Halt::CreateContractSizeLimit)
             }
             InstructionResult::CreateInitcodeSizeLimit => Self::Halt(Halt::CreateInitcodeSizeLimit),
+            InstructionResult::InvalidEOFInitCode => Self::Halt(Halt::InvalidEOFInitCode),
+            InstructionResult::EOFFunctionStackOverflow => {
+                Self::Halt(Halt::EOFFunctionStackOverflow)
+            }
             InstructionResult::FatalExternalError => Self::FatalExternalError,
+            InstructionResult::PrecompileInputTooLarge => Self::Halt(Halt::PrecompileInputTooLarge),
         }
     }
 }
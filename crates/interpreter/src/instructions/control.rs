@@ -3,12 +3,25 @@ use revm_primitives::Bytes;
 use crate::{
     gas,
     primitives::{Spec, U256},
-    Host, InstructionResult, Interpreter, InterpreterResult,
+    Host, InstructionResult, Interpreter, InterpreterResult, STACK_LIMIT,
 };
 
-pub fn jump<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+/// Maximum depth of the EOF function return stack.
+pub const RETURN_STACK_LIMIT: usize = 1024;
+
+pub fn jump<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::MID);
     pop!(interpreter, dest);
+    let mut dest = dest;
+    if !host.jump(
+        interpreter.contract.address,
+        interpreter.program_counter() - 1,
+        &mut dest,
+        None,
+    ) {
+        interpreter.instruction_result = InstructionResult::InvalidJump;
+        return;
+    }
     let dest = as_usize_or_fail!(interpreter, dest, InstructionResult::InvalidJump);
     if interpreter.contract.is_valid_jump(dest) {
         // SAFETY: In analysis we are checking create our jump table and we do check above to be
@@ -20,9 +33,19 @@ pub fn jump<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
     }
 }
 
-pub fn jumpi<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn jumpi<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::HIGH);
     pop!(interpreter, dest, value);
+    let (mut dest, mut value) = (dest, value);
+    if !host.jump(
+        interpreter.contract.address,
+        interpreter.program_counter() - 1,
+        &mut dest,
+        Some(&mut value),
+    ) {
+        interpreter.instruction_result = InstructionResult::InvalidJump;
+        return;
+    }
     if value != U256::ZERO {
         let dest = as_usize_or_fail!(interpreter, dest, InstructionResult::InvalidJump);
         if interpreter.contract.is_valid_jump(dest) {
@@ -36,11 +59,117 @@ pub fn jumpi<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
     }
 }
 
-pub fn jumpdest<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+/// Reads the big-endian two byte immediate at the given offset from the instruction pointer.
+///
+/// # Safety
+///
+/// EOF validation guarantees that immediates are not truncated.
+#[inline(always)]
+unsafe fn read_immediate(interpreter: &Interpreter, offset: usize) -> [u8; 2] {
+    let ptr = interpreter.instruction_pointer.add(offset);
+    [*ptr, *ptr.add(1)]
+}
+
+#[inline(always)]
+unsafe fn read_i16(interpreter: &Interpreter, offset: usize) -> isize {
+    i16::from_be_bytes(read_immediate(interpreter, offset)) as isize
+}
+
+/// EIP-4200: RJUMP instruction
+pub fn rjump<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+    check!(interpreter, PRAGUE);
+    require_eof!(interpreter);
+    gas!(interpreter, gas::BASE);
+    // SAFETY: EOF validation checks that relative jumps target an instruction.
+    unsafe {
+        let offset = read_i16(interpreter, 0);
+        interpreter.instruction_pointer = interpreter.instruction_pointer.offset(2 + offset);
+    }
+}
+
+/// EIP-4200: RJUMPI instruction
+pub fn rjumpi<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+    check!(interpreter, PRAGUE);
+    require_eof!(interpreter);
+    gas!(interpreter, gas::CONDJUMP);
+    pop!(interpreter, condition);
+    // SAFETY: EOF validation checks that relative jumps target an instruction.
+    unsafe {
+        let mut offset = 2;
+        if condition != U256::ZERO {
+            offset += read_i16(interpreter, 0);
+        }
+        interpreter.instruction_pointer = interpreter.instruction_pointer.offset(offset);
+    }
+}
+
+/// EIP-4200: RJUMPV instruction
+pub fn rjumpv<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+    check!(interpreter, PRAGUE);
+    require_eof!(interpreter);
+    gas!(interpreter, gas::CONDJUMP);
+    pop!(interpreter, case);
+    let case = as_usize_saturated!(case);
+    // SAFETY: EOF validation checks that the jump table is complete and that relative jumps
+    // target an instruction.
+    unsafe {
+        let max_index = *interpreter.instruction_pointer as usize;
+        let mut offset = 1 + (max_index as isize + 1) * 2;
+        if case <= max_index {
+            offset += read_i16(interpreter, 1 + case * 2);
+        }
+        interpreter.instruction_pointer = interpreter.instruction_pointer.offset(offset);
+    }
+}
+
+/// EIP-4750: CALLF instruction
+pub fn callf<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+    check!(interpreter, PRAGUE);
+    require_eof!(interpreter, eof);
+    gas!(interpreter, gas::LOW);
+    // SAFETY: EOF validation checks that the immediate is a valid code section index.
+    let index = unsafe { u16::from_be_bytes(read_immediate(interpreter, 0)) as usize };
+    let types = eof.types[index];
+    let entry = eof.code_sections[index].start;
+
+    if interpreter.return_stack.len() >= RETURN_STACK_LIMIT {
+        interpreter.instruction_result = InstructionResult::EOFFunctionStackOverflow;
+        return;
+    }
+    // EIP-5450: stack overflow is checked once on entering the function.
+    if interpreter.stack.len() + types.max_stack_height as usize - types.inputs as usize
+        > STACK_LIMIT
+    {
+        interpreter.instruction_result = InstructionResult::StackOverflow;
+        return;
+    }
+
+    interpreter
+        .return_stack
+        .push(interpreter.program_counter() + 2);
+    // SAFETY: `entry` is the start of a code section.
+    interpreter.instruction_pointer = unsafe { interpreter.contract.bytecode.as_ptr().add(entry) };
+}
+
+/// EIP-4750: RETF instruction
+pub fn retf<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+    check!(interpreter, PRAGUE);
+    require_eof!(interpreter);
+    gas!(interpreter, gas::VERYLOW);
+    // EOF validation rejects `RETF` in the first code section, so the return stack is not empty.
+    let Some(pc) = interpreter.return_stack.pop() else {
+        interpreter.instruction_result = InstructionResult::StackUnderflow;
+        return;
+    };
+    // SAFETY: `pc` was pushed by `CALLF` and points to the instruction after it.
+    interpreter.instruction_pointer = unsafe { interpreter.contract.bytecode.as_ptr().add(pc) };
+}
+
+pub fn jumpdest<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::JUMPDEST);
 }
 
-pub fn pc<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn pc<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::BASE);
     // - 1 because we have already advanced the instruction pointer in `Interpreter::step`
     push!(interpreter, U256::from(interpreter.program_counter() - 1));
@@ -58,7 +187,15 @@ fn return_inner(interpreter: &mut Interpreter, instruction_result: InstructionRe
         let offset = as_usize_or_fail!(interpreter, offset);
         shared_memory_resize!(interpreter, offset, len);
 
-        output = interpreter.shared_memory.slice(offset, len).to_vec().into()
+        let memory = interpreter.shared_memory.slice(offset, len);
+        // Contracts forwarding the output of a sub call (proxies) return the memory
+        // they have just filled with RETURNDATACOPY. Share the buffer of the sub call
+        // instead of copying it again.
+        output = if memory == &interpreter.return_data_buffer[..] {
+            interpreter.return_data_buffer.clone()
+        } else {
+            Bytes::copy_from_slice(memory)
+        };
     }
     interpreter.instruction_result = instruction_result;
     interpreter.next_action = Some(crate::InterpreterAction::Return {
@@ -66,31 +203,32 @@ fn return_inner(interpreter: &mut Interpreter, instruction_result: InstructionRe
             output,
             gas: interpreter.gas,
             result: instruction_result,
+            peak_memory: interpreter.shared_memory.len(),
         },
     });
 }
 
-pub fn ret<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn ret<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     return_inner(interpreter, InstructionResult::Return);
 }
 
 /// EIP-140: REVERT instruction
-pub fn revert<H: Host, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn revert<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
     check!(interpreter, BYZANTIUM);
     return_inner(interpreter, InstructionResult::Revert);
 }
 
 /// Stop opcode. This opcode halts the execution.
-pub fn stop<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn stop<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_result = InstructionResult::Stop;
 }
 
 /// Invalid opcode. This opcode halts the execution.
-pub fn invalid<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn invalid<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_result = InstructionResult::InvalidFEOpcode;
 }
 
 /// Unknown opcode. This opcode halts the execution.
-pub fn unknown<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn unknown<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_result = InstructionResult::OpcodeNotFound;
 }
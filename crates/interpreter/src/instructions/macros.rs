@@ -17,6 +17,21 @@ macro_rules! check {
     };
 }
 
+macro_rules! require_eof {
+    ($interp:expr) => {
+        if $interp.contract.bytecode.eof().is_none() {
+            $interp.instruction_result = InstructionResult::OpcodeNotFound;
+            return;
+        }
+    };
+    ($interp:expr, $eof:ident) => {
+        let Some($eof) = $interp.contract.bytecode.eof() else {
+            $interp.instruction_result = InstructionResult::OpcodeNotFound;
+            return;
+        };
+    };
+}
+
 macro_rules! gas {
     ($interp:expr, $gas:expr) => {
         if !$interp.gas.record_cost($gas) {
@@ -51,7 +66,6 @@ macro_rules! shared_memory_resize {
             // We are fine with saturating to usize if size is close to MAX value.
             let rounded_size = crate::interpreter::next_multiple_of_32(size);
 
-            #[cfg(feature = "memory_limit")]
             if $interp.shared_memory.limit_reached(size) {
                 $interp.instruction_result = InstructionResult::MemoryLimitOOG;
                 return;
@@ -60,7 +74,7 @@ macro_rules! shared_memory_resize {
             // Gas is calculated in evm words (256bits).
             let words_num = rounded_size / 32;
             if !$interp.gas.record_memory(crate::gas::memory_gas(words_num)) {
-                $interp.instruction_result = InstructionResult::MemoryLimitOOG;
+                $interp.instruction_result = InstructionResult::MemoryOOG;
                 return;
             }
             $interp.shared_memory.resize(rounded_size);
@@ -4,11 +4,13 @@ use super::*;
 use crate::{
     gas,
     primitives::{Spec, SpecId},
-    Host, Interpreter,
+    Host, Interpreter, InterpreterAction,
 };
 use alloc::boxed::Box;
 use alloc::sync::Arc;
-use core::fmt;
+use core::marker::PhantomData;
+
+pub use crate::primitives::opcode::*;
 
 /// EVM opcode function signature.
 pub type Instruction<H> = fn(&mut Interpreter, &mut H);
@@ -35,9 +37,13 @@ pub type BoxedInstructionTableArc<'a, H> = Arc<BoxedInstructionTable<'a, H>>;
 /// Note that `Plain` variant gives us 10-20% faster Interpreter execution.
 ///
 /// Boxed variant can be used to wrap plain function pointer with closure.
+///
+/// `Const` variant is the unmodified table of a spec that is evaluated at compile time, it
+/// doesn't need to be built or allocated, see [InstructionTables::spec].
 pub enum InstructionTables<'a, H> {
     Plain(InstructionTableArc<H>),
     Boxed(BoxedInstructionTableArc<'a, H>),
+    Const(&'a InstructionTable<H>),
 }
 
 impl<'a, H> Clone for InstructionTables<'a, H> {
@@ -45,34 +51,35 @@ impl<'a, H> Clone for InstructionTables<'a, H> {
         match self {
             Self::Plain(table) => Self::Plain(table.clone()),
             Self::Boxed(table) => Self::Boxed(table.clone()),
+            Self::Const(table) => Self::Const(table),
         }
     }
 }
 
+impl<'a, H: Host + 'a> InstructionTables<'a, H> {
+    /// Returns the instruction table of the spec evaluated at compile time.
+    #[inline]
+    pub fn spec<SPEC: Spec>() -> Self {
+        Self::Const(&SpecInstructionTable::<H, SPEC>::TABLE)
+    }
+}
+
+/// Holder of the instruction table of the spec, generic statics are not supported.
+struct SpecInstructionTable<H: ?Sized, SPEC>(PhantomData<(*const H, SPEC)>);
+
+impl<H: Host + ?Sized, SPEC: Spec> SpecInstructionTable<H, SPEC> {
+    const TABLE: InstructionTable<H> = make_instruction_table::<H, SPEC>();
+}
+
 macro_rules! opcodes {
     ($($val:literal => $name:ident => $f:expr),* $(,)?) => {
-        // Constants for each opcode. This also takes care of duplicate names.
+        // Names of the opcodes are defined in primitives, check that the values match.
         $(
-            #[doc = concat!("The `", stringify!($val), "` (\"", stringify!($name),"\") opcode.")]
-            pub const $name: u8 = $val;
+            const _: () = assert!($name == $val, "opcode value differs from primitives");
         )*
 
-        /// Maps each opcode to its name.
-        pub const OPCODE_JUMPMAP: [Option<&'static str>; 256] = {
-            let mut map = [None; 256];
-            let mut prev: u8 = 0;
-            $(
-                let val: u8 = $val;
-                assert!(val == 0 || val > prev, "opcodes must be sorted in ascending order");
-                prev = val;
-                map[$val] = Some(stringify!($name));
-            )*
-            let _ = prev;
-            map
-        };
-
         /// Returns the instruction function for the given opcode and spec.
-        pub fn instruction<H: Host, SPEC: Spec>(opcode: u8) -> Instruction<H> {
+        pub const fn instruction<H: Host + ?Sized, SPEC: Spec>(opcode: u8) -> Instruction<H> {
             match opcode {
                 $($name => $f,)*
                 _ => control::unknown,
@@ -82,11 +89,16 @@ macro_rules! opcodes {
 }
 
 /// Make instruction table.
-pub fn make_instruction_table<H: Host, SPEC: Spec>() -> InstructionTable<H> {
-    core::array::from_fn(|i| {
-        debug_assert!(i <= u8::MAX as usize);
-        instruction::<H, SPEC>(i as u8)
-    })
+///
+/// It can be evaluated at compile time, see [InstructionTables::spec].
+pub const fn make_instruction_table<H: Host + ?Sized, SPEC: Spec>() -> InstructionTable<H> {
+    let mut table: InstructionTable<H> = [control::unknown; 256];
+    let mut i = 0;
+    while i < table.len() {
+        table[i] = instruction::<H, SPEC>(i as u8);
+        i += 1;
+    }
+    table
 }
 
 /// Make boxed instruction table that calls `outer` closure for every instruction.
@@ -95,15 +107,40 @@ pub fn make_boxed_instruction_table<'a, H, SPEC, FN>(
     outer: FN,
 ) -> BoxedInstructionTable<'a, H>
 where
-    H: Host + 'a,
+    H: Host + ?Sized + 'a,
     SPEC: Spec + 'static,
     FN: Fn(Instruction<H>) -> BoxedInstruction<'a, H>,
 {
     core::array::from_fn(|i| outer(table[i]))
 }
 
+/// Wraps the instruction so that it doesn't consume gas, see
+/// [crate::primitives::CfgEnv::disable_gas_metering].
+///
+/// Gas and refunds are restored after the instruction, except for the gas forwarded to the call
+/// or create that is returned to the frame when it finishes.
+pub fn gas_free_instruction<'a, H: ?Sized + 'a>(
+    instruction: impl Fn(&mut Interpreter, &mut H) + 'a,
+) -> BoxedInstruction<'a, H> {
+    Box::new(move |interpreter: &mut Interpreter, host: &mut H| {
+        let gas = interpreter.gas;
+        instruction(interpreter, host);
+        let forwarded = match &interpreter.next_action {
+            Some(InterpreterAction::SubCall { inputs, .. }) => inputs.gas_limit,
+            Some(InterpreterAction::Create { inputs }) => inputs.gas_limit,
+            _ => 0,
+        };
+        let used = core::mem::replace(&mut interpreter.gas, gas);
+        // Call stipend is not charged, keep the metered gas if it doesn't fit.
+        if !interpreter.gas.record_cost(forwarded) {
+            interpreter.gas = used;
+        }
+    })
+}
+
 // When adding new opcodes:
-// 1. add the opcode to the list below; make sure it's sorted by opcode value
+// 1. add the opcode to the list below and to the names in primitives; make sure it's sorted by
+//    opcode value
 // 2. add its gas info in the `opcode_gas_info` function below
 // 3. implement the opcode in the corresponding module;
 //    the function signature must be the exact same as the others
@@ -338,11 +375,11 @@ opcodes! {
     // 0xDD
     // 0xDE
     // 0xDF
-    // 0xE0
-    // 0xE1
-    // 0xE2
-    // 0xE3
-    // 0xE4
+    0xE0 => RJUMP  => control::rjump::<H, SPEC>,
+    0xE1 => RJUMPI => control::rjumpi::<H, SPEC>,
+    0xE2 => RJUMPV => control::rjumpv::<H, SPEC>,
+    0xE3 => CALLF  => control::callf::<H, SPEC>,
+    0xE4 => RETF   => control::retf::<H, SPEC>,
     // 0xE5
     // 0xE6
     // 0xE7
@@ -372,77 +409,6 @@ opcodes! {
     0xFF => SELFDESTRUCT => host::selfdestruct::<H, SPEC>,
 }
 
-/// An EVM opcode.
-///
-/// This is always a valid opcode, as declared in the [`opcode`][self] module or the
-/// [`OPCODE_JUMPMAP`] constant.
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[repr(transparent)]
-pub struct OpCode(u8);
-
-impl fmt::Display for OpCode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let n = self.get();
-        if let Some(val) = OPCODE_JUMPMAP[n as usize] {
-            f.write_str(val)
-        } else {
-            write!(f, "UNKNOWN(0x{n:02X})")
-        }
-    }
-}
-
-impl OpCode {
-    /// Instantiate a new opcode from a u8.
-    #[inline]
-    pub const fn new(opcode: u8) -> Option<Self> {
-        match OPCODE_JUMPMAP[opcode as usize] {
-            Some(_) => Some(Self(opcode)),
-            None => None,
-        }
-    }
-
-    /// Instantiate a new opcode from a u8 without checking if it is valid.
-    ///
-    /// # Safety
-    ///
-    /// All code using `Opcode` values assume that they are valid opcodes, so providing an invalid
-    /// opcode may cause undefined behavior.
-    #[inline]
-    pub unsafe fn new_unchecked(opcode: u8) -> Self {
-        Self(opcode)
-    }
-
-    /// Returns the opcode as a string.
-    #[inline]
-    pub const fn as_str(self) -> &'static str {
-        if let Some(str) = OPCODE_JUMPMAP[self.0 as usize] {
-            str
-        } else {
-            "unknown"
-        }
-    }
-
-    /// Returns the opcode as a u8.
-    #[inline]
-    pub const fn get(self) -> u8 {
-        self.0
-    }
-
-    #[inline]
-    #[deprecated(note = "use `new` instead")]
-    #[doc(hidden)]
-    pub const fn try_from_u8(opcode: u8) -> Option<Self> {
-        Self::new(opcode)
-    }
-
-    #[inline]
-    #[deprecated(note = "use `get` instead")]
-    #[doc(hidden)]
-    pub const fn u8(self) -> u8 {
-        self.get()
-    }
-}
-
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct OpInfo {
     /// Data contains few information packed inside u32:
@@ -816,11 +782,31 @@ const fn opcode_gas_info(opcode: u8, spec: SpecId) -> OpInfo {
         0xDD => OpInfo::none(),
         0xDE => OpInfo::none(),
         0xDF => OpInfo::none(),
-        0xE0 => OpInfo::none(),
-        0xE1 => OpInfo::none(),
-        0xE2 => OpInfo::none(),
-        0xE3 => OpInfo::none(),
-        0xE4 => OpInfo::none(),
+        RJUMP => OpInfo::gas_block_end(if SpecId::enabled(spec, SpecId::PRAGUE) {
+            gas::BASE
+        } else {
+            0
+        }),
+        RJUMPI => OpInfo::gas_block_end(if SpecId::enabled(spec, SpecId::PRAGUE) {
+            gas::CONDJUMP
+        } else {
+            0
+        }),
+        RJUMPV => OpInfo::gas_block_end(if SpecId::enabled(spec, SpecId::PRAGUE) {
+            gas::CONDJUMP
+        } else {
+            0
+        }),
+        CALLF => OpInfo::gas_block_end(if SpecId::enabled(spec, SpecId::PRAGUE) {
+            gas::LOW
+        } else {
+            0
+        }),
+        RETF => OpInfo::gas_block_end(if SpecId::enabled(spec, SpecId::PRAGUE) {
+            gas::VERYLOW
+        } else {
+            0
+        }),
         0xE5 => OpInfo::none(),
         0xE6 => OpInfo::none(),
         0xE7 => OpInfo::none(),
@@ -888,6 +874,21 @@ pub const fn spec_opcode_gas(spec_id: SpecId) -> &'static [OpInfo; 256] {
                     const TABLE: &[OpInfo;256] = &make_gas_table(SpecId::CANYON);
                     TABLE
                 }
+                #[cfg(feature = "optimism")]
+                SpecId::ECOTONE => {
+                    const TABLE: &[OpInfo;256] = &make_gas_table(SpecId::ECOTONE);
+                    TABLE
+                }
+                #[cfg(feature = "optimism")]
+                SpecId::FJORD => {
+                    const TABLE: &[OpInfo;256] = &make_gas_table(SpecId::FJORD);
+                    TABLE
+                }
+                #[cfg(feature = "optimism")]
+                SpecId::ISTHMUS => {
+                    const TABLE: &[OpInfo;256] = &make_gas_table(SpecId::ISTHMUS);
+                    TABLE
+                }
             }
         };
     }
@@ -911,6 +912,27 @@ pub const fn spec_opcode_gas(spec_id: SpecId) -> &'static [OpInfo; 256] {
         MERGE,
         SHANGHAI,
         CANCUN,
+        PRAGUE,
         LATEST,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opcode_info_matches_gas_table() {
+        let gas_table = spec_opcode_gas(SpecId::LATEST);
+        for op in 0..=u8::MAX {
+            let Some(info) = OpCodeInfo::of(op) else {
+                continue;
+            };
+            let gas = gas_table[op as usize].get_gas() as u64;
+            // Dynamic gas opcodes are charged in the instruction.
+            if gas != 0 {
+                assert_eq!(info.base_gas, gas, "{}", info.name);
+            }
+        }
+    }
+}
@@ -1,18 +1,21 @@
 pub mod analysis;
 mod contract;
+pub mod eof_validation;
+mod outcome;
 mod shared_memory;
 mod stack;
 
-pub use analysis::BytecodeLocked;
+pub use analysis::{AnalysisCache, BytecodeLocked};
 pub use contract::Contract;
-pub use shared_memory::{next_multiple_of_32, SharedMemory};
+pub use outcome::{CallOutcome, CreateOutcome};
+pub use shared_memory::{next_multiple_of_32, SharedMemory, SharedMemoryPool};
 pub use stack::{Stack, STACK_LIMIT};
 
 use crate::{
-    primitives::Bytes, push, push_b256, return_ok, return_revert, CallInputs, CreateInputs, Gas,
-    Host, InstructionResult,
+    opcode::InstructionTable, primitives::Bytes, push, push_b256, return_ok, return_revert,
+    CallInputs, CreateInputs, Gas, Host, InstructionResult,
 };
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 use core::cmp::min;
 use core::ops::Range;
 use revm_primitives::{Address, U256};
@@ -50,13 +53,75 @@ pub struct Interpreter {
     /// Set inside CALL or CREATE instructions and RETURN or REVERT instructions. Additionally those instructions will set
     /// InstructionResult to CallOrCreate/Return/Revert so we know the reason.
     pub next_action: Option<InterpreterAction>,
+    /// EIP-4750: Return stack of EOF functions.
+    ///
+    /// Contains program counters to continue from after `RETF`. It is separate from the
+    /// operand stack and is empty for legacy bytecode.
+    pub return_stack: Vec<usize>,
+    /// Number of executions of every opcode since the counts were taken, indexed by opcode.
+    #[cfg(feature = "instruction-count")]
+    pub instruction_counts: [u64; 256],
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterpreterResult {
     pub result: InstructionResult,
     pub output: Bytes,
     pub gas: Gas,
+    /// Largest size of the frame memory in bytes, zero if no bytecode was executed.
+    ///
+    /// Memory of a frame only grows, so it is the size of its memory when it returned.
+    pub peak_memory: usize,
+}
+
+impl InterpreterResult {
+    /// Creates a new result of a frame that did not execute any bytecode.
+    #[inline]
+    pub fn new(result: InstructionResult, output: Bytes, gas: Gas) -> Self {
+        Self {
+            result,
+            output,
+            gas,
+            peak_memory: 0,
+        }
+    }
+
+    /// Returns whether the instruction result is a success.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Returns whether the instruction result is a revert.
+    #[inline]
+    pub fn is_revert(&self) -> bool {
+        self.result.is_revert()
+    }
+
+    /// Returns whether the instruction result is an error.
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.result.is_error()
+    }
+
+    /// Returns a view of `range` of the output without copying it.
+    ///
+    /// The returned [`Bytes`] shares the allocation of [`Self::output`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds of the output.
+    #[inline]
+    pub fn output_slice(&self, range: Range<usize>) -> Bytes {
+        self.output.slice(range)
+    }
+
+    /// Returns the output, consuming the result.
+    #[inline]
+    pub fn into_output(self) -> Bytes {
+        self.output
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -80,8 +145,14 @@ pub enum InterpreterAction {
 impl Interpreter {
     /// Create new interpreter
     pub fn new(contract: Box<Contract>, gas_limit: u64, is_static: bool) -> Self {
+        // EOF execution starts at the first code section.
+        let entry = contract
+            .bytecode
+            .eof()
+            .map_or(0, |eof| eof.code_sections[0].start);
         Self {
-            instruction_pointer: contract.bytecode.as_ptr(),
+            // SAFETY: `entry` is inside the bytecode.
+            instruction_pointer: unsafe { contract.bytecode.as_ptr().add(entry) },
             contract,
             gas: Gas::new(gas_limit),
             instruction_result: InstructionResult::Continue,
@@ -90,6 +161,9 @@ impl Interpreter {
             shared_memory: EMPTY_SHARED_MEMORY,
             stack: Stack::new(),
             next_action: None,
+            return_stack: Vec::new(),
+            #[cfg(feature = "instruction-count")]
+            instruction_counts: [0; 256],
         }
     }
 
@@ -199,13 +273,21 @@ impl Interpreter {
     /// Executes the instruction at the current instruction pointer.
     ///
     /// Internally it will increment instruction pointer by one.
+    ///
+    /// Used to drive the interpreter one instruction at a time, execution of the frame is done
+    /// when `instruction_result` is no longer [InstructionResult::Continue]. See
+    /// [Interpreter::begin_run] and [Interpreter::end_run].
     #[inline(always)]
-    fn step<FN, H: Host>(&mut self, instruction_table: &[FN; 256], host: &mut H)
+    pub fn step<FN, H: Host + ?Sized>(&mut self, instruction_table: &[FN; 256], host: &mut H)
     where
         FN: Fn(&mut Interpreter, &mut H),
     {
         // Get current opcode.
         let opcode = unsafe { *self.instruction_pointer };
+        #[cfg(feature = "instruction-count")]
+        {
+            self.instruction_counts[opcode as usize] += 1;
+        }
 
         // SAFETY: In analysis we are doing padding of bytecode so that we are sure that last
         // byte instruction is STOP so we are safe to just increment program_counter bcs on last instruction
@@ -221,24 +303,17 @@ impl Interpreter {
         core::mem::replace(&mut self.shared_memory, EMPTY_SHARED_MEMORY)
     }
 
-    /// Executes the interpreter until it returns or stops.
-    pub fn run<FN, H: Host>(
-        &mut self,
-        shared_memory: SharedMemory,
-        instruction_table: &[FN; 256],
-        host: &mut H,
-    ) -> InterpreterAction
-    where
-        FN: Fn(&mut Interpreter, &mut H),
-    {
+    /// Prepares the interpreter to (re)start the execution with given shared memory.
+    #[inline]
+    pub fn begin_run(&mut self, shared_memory: SharedMemory) {
         self.next_action = None;
         self.instruction_result = InstructionResult::Continue;
         self.shared_memory = shared_memory;
-        // main loop
-        while self.instruction_result == InstructionResult::Continue {
-            self.step(instruction_table, host);
-        }
+    }
 
+    /// Returns the action of the stopped interpreter.
+    #[inline]
+    pub fn end_run(&mut self) -> InterpreterAction {
         // Return next action if it is some.
         if let Some(action) = self.next_action.take() {
             return action;
@@ -250,7 +325,86 @@ impl Interpreter {
                 // return empty bytecode
                 output: Bytes::new(),
                 gas: self.gas,
+                peak_memory: self.shared_memory.len(),
             },
         }
     }
+
+    /// Executes the interpreter until it returns or stops.
+    pub fn run<FN, H: Host + ?Sized>(
+        &mut self,
+        shared_memory: SharedMemory,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        self.begin_run(shared_memory);
+        // main loop
+        while self.instruction_result == InstructionResult::Continue {
+            self.step(instruction_table, host);
+        }
+        self.end_run()
+    }
+
+    /// Executes the interpreter with new memory against the host trait object, without the
+    /// EVM. Calls and creates are not executed, they are returned as the action.
+    ///
+    /// The instruction table is made with [crate::opcode::make_instruction_table] for
+    /// `dyn Host`, the host can be a [crate::NoopHost].
+    pub fn run_with_host<'a>(
+        &mut self,
+        host: &mut (dyn Host + 'a),
+        instruction_table: &InstructionTable<dyn Host + 'a>,
+    ) -> InterpreterAction {
+        let mut shared_memory = SharedMemory::new();
+        shared_memory.new_context();
+        self.run(shared_memory, instruction_table, host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        opcode::make_instruction_table,
+        primitives::{Bytecode, CancunSpec},
+        DummyHost, NoopHost,
+    };
+
+    #[test]
+    fn run_with_host_trait_object() {
+        let table = make_instruction_table::<dyn Host, CancunSpec>();
+        // PUSH1 1 PUSH1 0 SSTORE PUSH1 0 SLOAD PUSH1 2 ADD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+        let bytecode = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x54, 0x60, 0x02, 0x01, 0x60, 0x00, 0x52,
+            0x60, 0x20, 0x60, 0x00, 0xf3,
+        ]));
+        let contract = Box::new(Contract::new(
+            Bytes::new(),
+            bytecode,
+            Default::default(),
+            Address::ZERO,
+            Address::ZERO,
+            U256::ZERO,
+        ));
+        let output = |host: &mut (dyn Host + 'static)| match Interpreter::new(
+            contract.clone(),
+            100_000,
+            false,
+        )
+        .run_with_host(host, &table)
+        {
+            InterpreterAction::Return { result } => {
+                assert_eq!(result.result, InstructionResult::Return);
+                U256::from_be_slice(&result.output)
+            }
+            action => panic!("unexpected action {action:?}"),
+        };
+
+        // Storage of the noop host is always empty.
+        assert_eq!(output(&mut NoopHost::default()), U256::from(2));
+        assert_eq!(output(&mut DummyHost::default()), U256::from(3));
+    }
 }
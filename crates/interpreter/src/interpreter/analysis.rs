@@ -1,9 +1,9 @@
 use crate::opcode;
 use crate::primitives::{
     bitvec::prelude::{bitvec, BitVec, Lsb0},
-    keccak256, Bytecode, BytecodeState, Bytes, JumpMap, B256, KECCAK_EMPTY,
+    keccak256, Bytecode, BytecodeState, Bytes, Eof, HashMap, JumpMap, B256, KECCAK_EMPTY,
 };
-use alloc::sync::Arc;
+use alloc::{collections::VecDeque, sync::Arc};
 use core::fmt;
 
 /// Perform bytecode analysis.
@@ -64,6 +64,7 @@ pub struct BytecodeLocked {
     bytecode: Bytes,
     len: usize,
     jump_map: JumpMap,
+    eof: Option<Arc<Eof>>,
 }
 
 impl fmt::Debug for BytecodeLocked {
@@ -75,6 +76,7 @@ impl fmt::Debug for BytecodeLocked {
                 "jump_map",
                 &crate::primitives::hex::encode(self.jump_map.as_slice()),
             )
+            .field("eof", &self.eof.is_some())
             .finish()
     }
 }
@@ -93,14 +95,21 @@ impl TryFrom<Bytecode> for BytecodeLocked {
 
     #[inline]
     fn try_from(bytecode: Bytecode) -> Result<Self, Self::Error> {
-        if let BytecodeState::Analysed { len, jump_map } = bytecode.state {
-            Ok(BytecodeLocked {
+        match bytecode.state {
+            BytecodeState::Analysed { len, jump_map } => Ok(BytecodeLocked {
                 bytecode: bytecode.bytecode,
                 len,
                 jump_map,
-            })
-        } else {
-            Err(())
+                eof: None,
+            }),
+            // EOF code is validated, so it has no jump map and does not need padding.
+            BytecodeState::Eof(eof) => Ok(BytecodeLocked {
+                len: bytecode.bytecode.len(),
+                bytecode: bytecode.bytecode,
+                jump_map: JumpMap::default(),
+                eof: Some(eof),
+            }),
+            _ => Err(()),
         }
     }
 }
@@ -136,6 +145,12 @@ impl BytecodeLocked {
 
     #[inline]
     pub fn unlock(self) -> Bytecode {
+        if let Some(eof) = self.eof {
+            return Bytecode {
+                bytecode: self.bytecode,
+                state: BytecodeState::Eof(eof),
+            };
+        }
         Bytecode {
             bytecode: self.bytecode,
             state: BytecodeState::Analysed {
@@ -169,4 +184,149 @@ impl BytecodeLocked {
     pub fn jump_map(&self) -> &JumpMap {
         &self.jump_map
     }
+
+    /// Returns the EOF container if the bytecode is EOF.
+    #[inline]
+    pub fn eof(&self) -> Option<&Arc<Eof>> {
+        self.eof.as_ref()
+    }
+}
+
+/// Cache of analysed bytecode keyed by code hash, so calls to the same contracts skip the
+/// analysis of the jump destinations and the decoding and validation of EOF containers.
+///
+/// At most `capacity` bytecodes are kept, the oldest one is evicted first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnalysisCache {
+    bytecodes: HashMap<B256, Bytecode>,
+    /// Code hashes in insertion order.
+    order: VecDeque<B256>,
+    capacity: usize,
+}
+
+impl Default for AnalysisCache {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalysisCache {
+    /// Default number of kept bytecodes.
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// Creates an empty cache with [Self::DEFAULT_CAPACITY].
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    /// Creates an empty cache that keeps up to `capacity` bytecodes, zero disables caching.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bytecodes: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the maximum number of kept bytecodes.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Sets the maximum number of kept bytecodes, the oldest ones over the limit are evicted.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.order.len() > capacity {
+            self.evict();
+        }
+    }
+
+    /// Returns the number of cached bytecodes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns `true` if there are no cached bytecodes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Returns the cached bytecode of the code hash.
+    #[inline]
+    pub fn get(&self, code_hash: &B256) -> Option<&Bytecode> {
+        self.bytecodes.get(code_hash)
+    }
+
+    /// Caches the analysed bytecode of the code hash, evicting the oldest one if the cache is
+    /// full. Bytecode that is not analysed or EOF is not cached.
+    pub fn insert(&mut self, code_hash: B256, bytecode: Bytecode) {
+        if self.capacity == 0
+            || !matches!(
+                bytecode.state,
+                BytecodeState::Analysed { .. } | BytecodeState::Eof(_)
+            )
+        {
+            return;
+        }
+        if self.bytecodes.insert(code_hash, bytecode).is_none() {
+            if self.order.len() == self.capacity {
+                self.evict();
+            }
+            self.order.push_back(code_hash);
+        }
+    }
+
+    /// Removes all cached bytecodes.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bytecodes.clear();
+        self.order.clear();
+    }
+
+    fn evict(&mut self) {
+        if let Some(code_hash) = self.order.pop_front() {
+            self.bytecodes.remove(&code_hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analysis_cache_evicts_oldest_bytecode() {
+        let bytecode = to_analysed(Bytecode::new_raw(Bytes::from_static(&[0x5b, 0x00])));
+        let hashes = [
+            B256::with_last_byte(1),
+            B256::with_last_byte(2),
+            B256::with_last_byte(3),
+        ];
+
+        let mut cache = AnalysisCache::with_capacity(2);
+        cache.insert(hashes[0], Bytecode::new_raw(Bytes::from_static(&[0x00])));
+        assert!(cache.is_empty());
+        for hash in hashes {
+            cache.insert(hash, bytecode.clone());
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&hashes[0]).is_none());
+        assert_eq!(cache.get(&hashes[2]), Some(&bytecode));
+
+        // Reinserted bytecode keeps its position.
+        cache.insert(hashes[1], bytecode.clone());
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&hashes[1]).is_none());
+        assert!(cache.get(&hashes[2]).is_some());
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
 }
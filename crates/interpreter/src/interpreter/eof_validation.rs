@@ -0,0 +1,369 @@
+use crate::opcode::*;
+use crate::primitives::{Bytecode, Bytes, Eof, EofDecodeError, EOF_MAX_STACK_HEIGHT};
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+/// Decodes and validates EOF bytecode.
+pub fn to_eof(raw: Bytes) -> Result<Bytecode, EofValidationError> {
+    let eof = Eof::decode(raw)?;
+    validate_eof(&eof)?;
+    Ok(Bytecode::new_eof(eof))
+}
+
+/// Validates all code sections of the decoded EOF container.
+///
+/// Implements code validation of EIP-3670, EIP-4200 and EIP-4750 and the stack validation of
+/// EIP-5450.
+pub fn validate_eof(eof: &Eof) -> Result<(), EofValidationError> {
+    for index in 0..eof.code_sections.len() {
+        validate_code_section(eof, index)?;
+    }
+    Ok(())
+}
+
+/// Returns the number of stack inputs and outputs of the opcode, or `None` if the opcode is not
+/// allowed in EOF code.
+///
+/// `CALLF` and `RETF` depend on the code section types and are handled separately.
+const fn stack_io(opcode: u8) -> Option<(u8, u8)> {
+    let io = match opcode {
+        STOP | JUMPDEST | INVALID | RJUMP | CALLF | RETF => (0, 0),
+        ADD | MUL | SUB | DIV | SDIV | MOD | SMOD | EXP | SIGNEXTEND => (2, 1),
+        ADDMOD | MULMOD => (3, 1),
+        LT | GT | SLT | SGT | EQ | AND | OR | XOR | BYTE | SHL | SHR | SAR => (2, 1),
+        ISZERO | NOT => (1, 1),
+        KECCAK256 => (2, 1),
+        ADDRESS | ORIGIN | CALLER | CALLVALUE | CALLDATASIZE | CODESIZE | GASPRICE
+        | RETURNDATASIZE | COINBASE | TIMESTAMP | NUMBER | DIFFICULTY | GASLIMIT | CHAINID
+        | SELFBALANCE | BASEFEE | BLOBBASEFEE | MSIZE | GAS => (0, 1),
+        BALANCE | CALLDATALOAD | EXTCODESIZE | EXTCODEHASH | BLOCKHASH | BLOBHASH | MLOAD
+        | SLOAD | TLOAD => (1, 1),
+        CALLDATACOPY | CODECOPY | RETURNDATACOPY | MCOPY => (3, 0),
+        EXTCODECOPY => (4, 0),
+        POP | RJUMPI | RJUMPV => (1, 0),
+        MSTORE | MSTORE8 | SSTORE | TSTORE | RETURN | REVERT => (2, 0),
+        PUSH0..=PUSH32 => (0, 1),
+        DUP1..=DUP16 => {
+            let n = opcode - DUP1 + 1;
+            (n, n + 1)
+        }
+        SWAP1..=SWAP16 => {
+            let n = opcode - SWAP1 + 2;
+            (n, n)
+        }
+        LOG0..=LOG4 => (opcode - LOG0 + 2, 0),
+        CREATE => (3, 1),
+        CREATE2 => (4, 1),
+        CALL => (7, 1),
+        DELEGATECALL | STATICCALL => (6, 1),
+        // Undefined opcodes and `JUMP`, `JUMPI`, `PC`, `CALLCODE` and `SELFDESTRUCT`,
+        // which are removed from EOF code.
+        _ => return None,
+    };
+    Some(io)
+}
+
+/// Returns whether the opcode ends the execution of the code section.
+const fn is_terminating(opcode: u8) -> bool {
+    matches!(opcode, STOP | RETURN | REVERT | INVALID | RETF)
+}
+
+#[inline]
+fn read_i16(code: &[u8], pos: usize) -> isize {
+    i16::from_be_bytes([code[pos], code[pos + 1]]) as isize
+}
+
+#[inline]
+fn read_u16(code: &[u8], pos: usize) -> usize {
+    u16::from_be_bytes([code[pos], code[pos + 1]]) as usize
+}
+
+fn validate_code_section(eof: &Eof, index: usize) -> Result<(), EofValidationError> {
+    let code = eof.code_section(index).expect("section index is in bounds");
+    let types = eof.types[index];
+
+    // EIP-3670: only defined opcodes with complete immediates.
+    let mut is_immediate = vec![false; code.len()];
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        if stack_io(opcode).is_none() {
+            return Err(EofValidationError::UnknownOpcode);
+        }
+        let immediate_size = match opcode {
+            PUSH1..=PUSH32 => (opcode - PUSH0) as usize,
+            RJUMP | RJUMPI | CALLF => 2,
+            RJUMPV => match code.get(pc + 1) {
+                Some(&max_index) => 1 + (max_index as usize + 1) * 2,
+                None => return Err(EofValidationError::TruncatedImmediate),
+            },
+            _ => 0,
+        };
+        let next = pc + 1 + immediate_size;
+        if next > code.len() {
+            return Err(EofValidationError::TruncatedImmediate);
+        }
+        is_immediate[pc + 1..next].fill(true);
+        pc = next;
+    }
+
+    // EIP-5450: every instruction is reachable with a single known stack height.
+    let mut heights = vec![None; code.len()];
+    heights[0] = Some(types.inputs as usize);
+    let mut max_height = types.inputs as usize;
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        let Some(height) = heights[pc] else {
+            return Err(EofValidationError::UnreachableCode);
+        };
+
+        let (inputs, outputs) = match opcode {
+            CALLF => {
+                let target = eof
+                    .types
+                    .get(read_u16(code, pc + 1))
+                    .ok_or(EofValidationError::InvalidCallTarget)?;
+                (target.inputs as usize, target.outputs as usize)
+            }
+            RETF => {
+                // first code section is not called so it can't return.
+                if index == 0 {
+                    return Err(EofValidationError::InvalidReturn);
+                }
+                if height != types.outputs as usize {
+                    return Err(EofValidationError::StackHeightMismatch);
+                }
+                (0, 0)
+            }
+            _ => {
+                let (inputs, outputs) = stack_io(opcode).expect("opcode is checked");
+                (inputs as usize, outputs as usize)
+            }
+        };
+        if height < inputs {
+            return Err(EofValidationError::StackUnderflow);
+        }
+        let height = height - inputs + outputs;
+        if height > EOF_MAX_STACK_HEIGHT as usize {
+            return Err(EofValidationError::StackOverflow);
+        }
+        max_height = max_height.max(height);
+
+        let mut next = pc + 1;
+        let mut jumps = vec![];
+        match opcode {
+            PUSH1..=PUSH32 => next += (opcode - PUSH0) as usize,
+            CALLF => next += 2,
+            RJUMP | RJUMPI => {
+                next += 2;
+                jumps.push(read_i16(code, pc + 1));
+            }
+            RJUMPV => {
+                let count = code[pc + 1] as usize + 1;
+                next += 1 + count * 2;
+                jumps.extend((0..count).map(|i| read_i16(code, pc + 2 + i * 2)));
+            }
+            _ => {}
+        }
+
+        let mut successors = jumps
+            .into_iter()
+            .map(|offset| {
+                let target = next as isize + offset;
+                if target < 0 || target as usize >= code.len() || is_immediate[target as usize] {
+                    return Err(EofValidationError::InvalidRelativeJump);
+                }
+                Ok(target as usize)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if opcode != RJUMP && !is_terminating(opcode) {
+            if next >= code.len() {
+                return Err(EofValidationError::MissingTerminatingInstruction);
+            }
+            successors.push(next);
+        }
+
+        for successor in successors {
+            match heights[successor] {
+                Some(expected) if expected != height => {
+                    return Err(EofValidationError::StackHeightMismatch)
+                }
+                Some(_) => {}
+                // backward jumps target only visited instructions.
+                None if successor <= pc => return Err(EofValidationError::UnreachableCode),
+                None => heights[successor] = Some(height),
+            }
+        }
+
+        pc = next;
+    }
+
+    if max_height != types.max_stack_height as usize {
+        return Err(EofValidationError::MaxStackHeightMismatch);
+    }
+    Ok(())
+}
+
+/// Errors that can occur while validating EOF bytecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EofValidationError {
+    /// Container layout is invalid.
+    Decode(EofDecodeError),
+    /// Opcode is undefined or not allowed in EOF code.
+    UnknownOpcode,
+    /// Immediate of the last instruction is truncated.
+    TruncatedImmediate,
+    /// Relative jump is out of the code section or targets an immediate.
+    InvalidRelativeJump,
+    /// `CALLF` targets a non-existing code section.
+    InvalidCallTarget,
+    /// `RETF` is used in the first code section.
+    InvalidReturn,
+    UnreachableCode,
+    StackUnderflow,
+    StackOverflow,
+    /// Instruction is reached with different stack heights.
+    StackHeightMismatch,
+    /// Computed maximum stack height does not match the types section.
+    MaxStackHeightMismatch,
+    /// Code section does not end with a terminating instruction.
+    MissingTerminatingInstruction,
+}
+
+impl From<EofDecodeError> for EofValidationError {
+    fn from(error: EofDecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EofValidationError {}
+
+impl fmt::Display for EofValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(error) => write!(f, "{error}"),
+            Self::UnknownOpcode => write!(f, "Unknown opcode"),
+            Self::TruncatedImmediate => write!(f, "Truncated immediate"),
+            Self::InvalidRelativeJump => write!(f, "Invalid relative jump"),
+            Self::InvalidCallTarget => write!(f, "Invalid CALLF target"),
+            Self::InvalidReturn => write!(f, "RETF in the first code section"),
+            Self::UnreachableCode => write!(f, "Unreachable code"),
+            Self::StackUnderflow => write!(f, "Stack underflow"),
+            Self::StackOverflow => write!(f, "Stack overflow"),
+            Self::StackHeightMismatch => write!(f, "Stack height mismatch"),
+            Self::MaxStackHeightMismatch => write!(f, "Max stack height mismatch"),
+            Self::MissingTerminatingInstruction => write!(f, "Missing terminating instruction"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::hex;
+
+    /// Code section with its `(inputs, outputs, max_stack_height)` type.
+    type Section<'a> = (&'a [u8], (u8, u8, u16));
+
+    /// Builds a container with the given code sections.
+    fn container(sections: &[Section<'_>]) -> Bytes {
+        let mut raw = vec![0xEF, 0x00, 0x01, 0x01];
+        raw.extend_from_slice(&(sections.len() as u16 * 4).to_be_bytes());
+        raw.push(0x02);
+        raw.extend_from_slice(&(sections.len() as u16).to_be_bytes());
+        for (code, _) in sections {
+            raw.extend_from_slice(&(code.len() as u16).to_be_bytes());
+        }
+        raw.extend_from_slice(&[0x03, 0x00, 0x00, 0x00]);
+        for (_, (inputs, outputs, max_stack_height)) in sections {
+            raw.extend_from_slice(&[*inputs, *outputs]);
+            raw.extend_from_slice(&max_stack_height.to_be_bytes());
+        }
+        for (code, _) in sections {
+            raw.extend_from_slice(code);
+        }
+        raw.into()
+    }
+
+    fn validate(sections: &[Section<'_>]) -> Result<(), EofValidationError> {
+        to_eof(container(sections)).map(|_| ())
+    }
+
+    #[test]
+    fn valid_code() {
+        // PUSH1 1, RJUMPI +4, PUSH0, RJUMP +1, PUSH0, POP, STOP
+        assert_eq!(
+            validate(&[(&hex!("6001e100045fe000015f5000"), (0, 0, 1))]),
+            Ok(())
+        );
+        // PUSH0, RJUMPV [0, 2], PUSH0, POP, STOP
+        assert_eq!(
+            validate(&[(&hex!("5fe201000000025f5000"), (0, 0, 1))]),
+            Ok(())
+        );
+        // CALLF 1, POP, STOP | PUSH0, RETF
+        assert_eq!(
+            validate(&[(&hex!("e300015000"), (0, 0, 1)), (&hex!("5fe4"), (0, 1, 1))]),
+            Ok(())
+        );
+        // backward jump with the same stack height: JUMPDEST, RJUMP -4
+        assert_eq!(validate(&[(&hex!("5be0fffc"), (0, 0, 0))]), Ok(()));
+    }
+
+    #[test]
+    fn invalid_code() {
+        // JUMP, PC, SELFDESTRUCT and undefined opcodes are rejected.
+        for opcode in [JUMP, JUMPI, PC, CALLCODE, SELFDESTRUCT, 0x0C] {
+            assert_eq!(
+                validate(&[(&[opcode, STOP], (0, 0, 2))]),
+                Err(EofValidationError::UnknownOpcode)
+            );
+        }
+        assert_eq!(
+            validate(&[(&hex!("61ff"), (0, 0, 1))]),
+            Err(EofValidationError::TruncatedImmediate)
+        );
+        // RJUMP into its own immediate.
+        assert_eq!(
+            validate(&[(&hex!("e0ffff00"), (0, 0, 0))]),
+            Err(EofValidationError::InvalidRelativeJump)
+        );
+        assert_eq!(
+            validate(&[(&hex!("e3000100"), (0, 0, 0))]),
+            Err(EofValidationError::InvalidCallTarget)
+        );
+        assert_eq!(
+            validate(&[(&hex!("e4"), (0, 0, 0))]),
+            Err(EofValidationError::InvalidReturn)
+        );
+        assert_eq!(
+            validate(&[(&hex!("e000010000"), (0, 0, 0))]),
+            Err(EofValidationError::UnreachableCode)
+        );
+        assert_eq!(
+            validate(&[(&hex!("5000"), (0, 0, 0))]),
+            Err(EofValidationError::StackUnderflow)
+        );
+        // PUSH0, RJUMP -4 loops with growing stack.
+        assert_eq!(
+            validate(&[(&hex!("5fe0fffc"), (0, 0, 1))]),
+            Err(EofValidationError::StackHeightMismatch)
+        );
+        assert_eq!(
+            validate(&[(&hex!("5f5000"), (0, 0, 2))]),
+            Err(EofValidationError::MaxStackHeightMismatch)
+        );
+        assert_eq!(
+            validate(&[(&hex!("5f50"), (0, 0, 1))]),
+            Err(EofValidationError::MissingTerminatingInstruction)
+        );
+        assert_eq!(
+            validate(&[(&hex!("00"), (1, 0, 1))]),
+            Err(EofValidationError::Decode(
+                EofDecodeError::InvalidFirstSectionType
+            ))
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{
+    primitives::{Address, Bytes},
+    Gas, InstructionResult, InterpreterResult,
+};
+use core::ops::Range;
+
+/// Outcome of a call, returned by inspectors that override the call.
+#[derive(Debug, Clone)]
+pub struct CallOutcome {
+    /// Result of the call.
+    pub result: InterpreterResult,
+    /// Range of the caller memory the output is written to.
+    pub memory_offset: Range<usize>,
+}
+
+impl CallOutcome {
+    /// Creates a new call outcome.
+    #[inline]
+    pub fn new(result: InterpreterResult, memory_offset: Range<usize>) -> Self {
+        Self {
+            result,
+            memory_offset,
+        }
+    }
+
+    /// Creates a reverted call outcome with the given output and unspent gas.
+    #[inline]
+    pub fn revert(output: Bytes, gas: Gas) -> Self {
+        InterpreterResult::new(InstructionResult::Revert, output, gas).into()
+    }
+
+    /// Creates a call outcome that ran out of gas, spending all of `gas_limit`.
+    #[inline]
+    pub fn out_of_gas(gas_limit: u64) -> Self {
+        (InstructionResult::OutOfGas, spent_gas(gas_limit)).into()
+    }
+
+    /// Returns whether the call succeeded.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Returns whether the call reverted.
+    #[inline]
+    pub fn is_revert(&self) -> bool {
+        self.result.is_revert()
+    }
+
+    /// Returns whether the call halted with an error.
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.result.is_error()
+    }
+
+    /// Returns the instruction result of the call.
+    #[inline]
+    pub fn instruction_result(&self) -> InstructionResult {
+        self.result.result
+    }
+
+    /// Returns the gas of the call.
+    #[inline]
+    pub fn gas(&self) -> Gas {
+        self.result.gas
+    }
+
+    /// Returns the output of the call.
+    #[inline]
+    pub fn output(&self) -> &Bytes {
+        &self.result.output
+    }
+}
+
+/// Outcome with an empty memory range, so no output is written to the caller memory.
+impl From<InterpreterResult> for CallOutcome {
+    #[inline]
+    fn from(result: InterpreterResult) -> Self {
+        Self::new(result, 0..0)
+    }
+}
+
+impl From<(InstructionResult, Gas)> for CallOutcome {
+    #[inline]
+    fn from((result, gas): (InstructionResult, Gas)) -> Self {
+        InterpreterResult::new(result, Bytes::new(), gas).into()
+    }
+}
+
+/// Outcome of a contract creation, returned by inspectors that override the creation.
+#[derive(Debug, Clone)]
+pub struct CreateOutcome {
+    /// Result of the creation.
+    pub result: InterpreterResult,
+    /// Address of the created contract, if any.
+    pub address: Option<Address>,
+}
+
+impl CreateOutcome {
+    /// Creates a new create outcome.
+    #[inline]
+    pub fn new(result: InterpreterResult, address: Option<Address>) -> Self {
+        Self { result, address }
+    }
+
+    /// Creates a reverted create outcome with the given output and unspent gas.
+    #[inline]
+    pub fn revert(output: Bytes, gas: Gas) -> Self {
+        InterpreterResult::new(InstructionResult::Revert, output, gas).into()
+    }
+
+    /// Creates a create outcome that ran out of gas, spending all of `gas_limit`.
+    #[inline]
+    pub fn out_of_gas(gas_limit: u64) -> Self {
+        (InstructionResult::OutOfGas, spent_gas(gas_limit)).into()
+    }
+
+    /// Returns whether the creation succeeded.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Returns whether the creation reverted.
+    #[inline]
+    pub fn is_revert(&self) -> bool {
+        self.result.is_revert()
+    }
+
+    /// Returns whether the creation halted with an error.
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.result.is_error()
+    }
+
+    /// Returns the instruction result of the creation.
+    #[inline]
+    pub fn instruction_result(&self) -> InstructionResult {
+        self.result.result
+    }
+
+    /// Returns the gas of the creation.
+    #[inline]
+    pub fn gas(&self) -> Gas {
+        self.result.gas
+    }
+
+    /// Returns the output of the creation.
+    #[inline]
+    pub fn output(&self) -> &Bytes {
+        &self.result.output
+    }
+}
+
+/// Outcome without a created address.
+impl From<InterpreterResult> for CreateOutcome {
+    #[inline]
+    fn from(result: InterpreterResult) -> Self {
+        Self::new(result, None)
+    }
+}
+
+impl From<(InstructionResult, Gas)> for CreateOutcome {
+    #[inline]
+    fn from((result, gas): (InstructionResult, Gas)) -> Self {
+        InterpreterResult::new(result, Bytes::new(), gas).into()
+    }
+}
+
+/// Returns gas with all of `gas_limit` spent.
+#[inline]
+fn spent_gas(gas_limit: u64) -> Gas {
+    let mut gas = Gas::new(gas_limit);
+    gas.record_cost(gas_limit);
+    gas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome_constructors() {
+        let call = CallOutcome::revert(Bytes::from_static(b"err"), Gas::new(100));
+        assert!(call.is_revert() && !call.is_ok() && !call.is_error());
+        assert_eq!(call.output().as_ref(), b"err");
+        assert_eq!(call.gas().remaining(), 100);
+        assert_eq!(call.memory_offset, 0..0);
+
+        let call = CallOutcome::out_of_gas(100);
+        assert!(call.is_error());
+        assert_eq!(call.instruction_result(), InstructionResult::OutOfGas);
+        assert_eq!(call.gas().remaining(), 0);
+
+        let create = CreateOutcome::from((InstructionResult::Return, Gas::new(10)));
+        assert!(create.is_ok());
+        assert_eq!(create.address, None);
+        assert!(create.output().is_empty());
+    }
+}
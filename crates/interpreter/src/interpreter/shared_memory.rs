@@ -21,8 +21,7 @@ pub struct SharedMemory {
     checkpoints: Vec<usize>,
     /// Invariant: equals `self.checkpoints.last()`
     last_checkpoint: usize,
-    /// Memory limit. See [`crate::CfgEnv`].
-    #[cfg(feature = "memory_limit")]
+    /// Memory limit. See [`crate::primitives::CfgEnv::memory_limit`].
     memory_limit: u64,
 }
 
@@ -33,7 +32,6 @@ pub const EMPTY_SHARED_MEMORY: SharedMemory = SharedMemory {
     buffer: Vec::new(),
     checkpoints: Vec::new(),
     last_checkpoint: 0,
-    #[cfg(feature = "memory_limit")]
     memory_limit: u64::MAX,
 };
 
@@ -72,7 +70,6 @@ impl SharedMemory {
             buffer: Vec::with_capacity(capacity),
             checkpoints: Vec::with_capacity(32),
             last_checkpoint: 0,
-            #[cfg(feature = "memory_limit")]
             memory_limit: u64::MAX,
         }
     }
@@ -81,7 +78,6 @@ impl SharedMemory {
     /// with `memory_limit` as upper bound for allocation size.
     ///
     /// The default initial capacity is 4KiB.
-    #[cfg(feature = "memory_limit")]
     #[inline]
     pub fn new_with_memory_limit(memory_limit: u64) -> Self {
         Self {
@@ -90,12 +86,31 @@ impl SharedMemory {
         }
     }
 
+    /// Sets the upper bound for allocation size.
+    #[inline]
+    pub fn set_memory_limit(&mut self, memory_limit: u64) {
+        self.memory_limit = memory_limit;
+    }
+
+    /// Removes all contexts and data, the allocated capacity is kept for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.checkpoints.clear();
+        self.last_checkpoint = 0;
+    }
+
+    /// Returns the number of bytes the buffer can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
     /// Returns `true` if the `new_size` for the current context memory will
     /// make the shared buffer length exceed the `memory_limit`.
-    #[cfg(feature = "memory_limit")]
     #[inline]
     pub fn limit_reached(&self, new_size: usize) -> bool {
-        (self.last_checkpoint + new_size) as u64 > self.memory_limit
+        self.last_checkpoint.saturating_add(new_size) as u64 > self.memory_limit
     }
 
     /// Prepares the shared memory for a new context.
@@ -303,6 +318,80 @@ impl SharedMemory {
     }
 }
 
+/// Pool of shared memories that are reused between executions instead of reallocated.
+///
+/// Every execution of a call stack takes one memory from the pool and gives it back when it is
+/// done. At most `max_size` memories are kept, the rest are dropped.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SharedMemoryPool {
+    memories: Vec<SharedMemory>,
+    max_size: usize,
+}
+
+impl Default for SharedMemoryPool {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SharedMemoryPool {
+    /// Creates an empty pool that keeps one memory, enough for sequential executions.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::with_max_size(1)
+    }
+
+    /// Creates an empty pool that keeps up to `max_size` memories, zero disables pooling.
+    #[inline]
+    pub const fn with_max_size(max_size: usize) -> Self {
+        Self {
+            memories: Vec::new(),
+            max_size,
+        }
+    }
+
+    /// Returns the maximum number of kept memories.
+    #[inline]
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Sets the maximum number of kept memories, memories over the limit are dropped.
+    #[inline]
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        self.memories.truncate(max_size);
+    }
+
+    /// Returns the number of memories in the pool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.memories.len()
+    }
+
+    /// Returns `true` if there are no memories in the pool.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.memories.is_empty()
+    }
+
+    /// Takes an empty memory out of the pool, or creates a new one if the pool is empty.
+    #[inline]
+    pub fn take(&mut self) -> SharedMemory {
+        self.memories.pop().unwrap_or_default()
+    }
+
+    /// Clears the memory and puts it back into the pool if it is not full.
+    #[inline]
+    pub fn recycle(&mut self, mut memory: SharedMemory) {
+        if self.memories.len() < self.max_size {
+            memory.clear();
+            self.memories.push(memory);
+        }
+    }
+}
+
 /// Rounds up `x` to the closest multiple of 32. If `x % 32 == 0` then `x` is returned.
 #[inline]
 pub fn next_multiple_of_32(x: usize) -> usize {
@@ -404,4 +493,31 @@ mod tests {
         assert_eq!(shared_memory.len(), 64);
         assert_eq!(shared_memory.buffer.get(0..64), Some(&[0_u8; 64] as &[u8]));
     }
+
+    #[test]
+    fn pool_reuses_memory() {
+        let mut pool = SharedMemoryPool::new();
+        let mut shared_memory = pool.take();
+        shared_memory.new_context();
+        shared_memory.resize(10_000);
+        let capacity = shared_memory.capacity();
+        pool.recycle(shared_memory);
+        assert_eq!(pool.len(), 1);
+
+        // Over the limit.
+        pool.recycle(SharedMemory::new());
+        assert_eq!(pool.len(), 1);
+
+        let shared_memory = pool.take();
+        assert!(pool.is_empty());
+        assert_eq!(shared_memory.capacity(), capacity);
+        assert!(shared_memory.is_empty());
+        assert!(shared_memory.checkpoints.is_empty());
+
+        pool.recycle(shared_memory);
+        pool.set_max_size(0);
+        assert!(pool.is_empty());
+        pool.recycle(SharedMemory::new());
+        assert!(pool.is_empty());
+    }
 }
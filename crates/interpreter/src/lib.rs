@@ -11,6 +11,8 @@ extern crate alloc;
 #[macro_use]
 mod macros;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub mod assembler;
 pub mod gas;
 mod host;
 mod inner_models;
@@ -20,13 +22,14 @@ mod interpreter;
 
 // Reexport primary types.
 pub use gas::Gas;
-pub use host::{DummyHost, Host};
+pub use host::{DummyHost, Host, NoopHost};
 pub use inner_models::*;
 pub use instruction_result::*;
 pub use instructions::{opcode, Instruction, OpCode, OPCODE_JUMPMAP};
 pub use interpreter::{
-    analysis, next_multiple_of_32, BytecodeLocked, Contract, Interpreter, InterpreterAction,
-    InterpreterResult, SharedMemory, Stack, EMPTY_SHARED_MEMORY, STACK_LIMIT,
+    analysis, eof_validation, next_multiple_of_32, AnalysisCache, BytecodeLocked, CallOutcome,
+    Contract, CreateOutcome, Interpreter, InterpreterAction, InterpreterResult, SharedMemory,
+    SharedMemoryPool, Stack, EMPTY_SHARED_MEMORY, STACK_LIMIT,
 };
 pub use primitives::{MAX_CODE_SIZE, MAX_INITCODE_SIZE};
 
@@ -0,0 +1,97 @@
+//! Batch execution of the signature and proof verifying precompiles.
+//!
+//! Block validation pipelines can collect the inputs of all ecrecover and point evaluation
+//! calls of a block in a first pass and verify them ahead of execution. With the `rayon`
+//! feature the inputs are verified in parallel, otherwise one after another.
+//!
+//! Results are in the order of the inputs and equal to the results of calling the precompiles
+//! with the same gas limit.
+
+use crate::{kzg_point_evaluation, secp256k1, PrecompileResult};
+use alloc::vec::Vec;
+use revm_primitives::Env;
+
+/// Runs the ecrecover precompile on every input.
+pub fn ecrecover<I: AsRef<[u8]> + Sync>(inputs: &[I], gas_limit: u64) -> Vec<PrecompileResult> {
+    run(inputs, |input| secp256k1::ec_recover_run(input, gas_limit))
+}
+
+/// Runs the point evaluation precompile on every input, with the KZG verifier of the `env`.
+pub fn point_evaluation<I: AsRef<[u8]> + Sync>(
+    inputs: &[I],
+    gas_limit: u64,
+    env: &Env,
+) -> Vec<PrecompileResult> {
+    run(inputs, |input| {
+        kzg_point_evaluation::run(input, gas_limit, env)
+    })
+}
+
+#[cfg(feature = "rayon")]
+fn run<I, F>(inputs: &[I], f: F) -> Vec<PrecompileResult>
+where
+    I: AsRef<[u8]> + Sync,
+    F: Fn(&[u8]) -> PrecompileResult + Sync,
+{
+    use rayon::prelude::*;
+
+    inputs.par_iter().map(|input| f(input.as_ref())).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn run<I, F>(inputs: &[I], f: F) -> Vec<PrecompileResult>
+where
+    I: AsRef<[u8]> + Sync,
+    F: Fn(&[u8]) -> PrecompileResult + Sync,
+{
+    inputs.iter().map(|input| f(input.as_ref())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{primitives::hex, Error};
+
+    #[test]
+    fn ecrecover_batch_matches_single_calls() {
+        // Signature of the ecrecover test of go-ethereum.
+        let valid = hex!(
+            "18c547e4f7b0f325ad1e56f57e26c745b09a3e503d86e00e5255ff7f715d3d1c"
+            "000000000000000000000000000000000000000000000000000000000000001c"
+            "73b1693892219d736caba55bdb67216e485557ea6b6af75f37096c9aa6a5a75f"
+            "eeb940b1d03b21e36b0e47e79769f095fe2ab855bd91e3a38756b7d75a9c4549"
+        );
+        let mut invalid_v = valid;
+        invalid_v[63] = 29;
+        let inputs = [&valid[..], &invalid_v[..], &[]];
+
+        let results = ecrecover(&inputs, 3_000);
+        assert_eq!(results.len(), inputs.len());
+        for (input, result) in inputs.iter().zip(&results) {
+            assert_eq!(*result, secp256k1::ec_recover_run(input, 3_000));
+        }
+        let (gas, out) = results[0].clone().unwrap();
+        assert_eq!(gas, 3_000);
+        assert_eq!(
+            out,
+            hex!("000000000000000000000000a94f5374fce5edbc8e2a8697c15331677e6ebf0b")
+        );
+        assert_eq!(results[1], Ok((3_000, Vec::new())));
+
+        assert_eq!(ecrecover(&inputs, 2_999), vec![Err(Error::OutOfGas); 3]);
+        assert!(ecrecover::<Vec<u8>>(&[], 3_000).is_empty());
+    }
+
+    #[test]
+    fn point_evaluation_batch_checks_every_input() {
+        let env = Env::default();
+        let inputs = [vec![0; 192], vec![0; 191]];
+        assert_eq!(
+            point_evaluation(&inputs, 50_000, &env),
+            vec![
+                Err(Error::BlobMismatchedVersion),
+                Err(Error::BlobInvalidInputLength)
+            ]
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! BLS12-381 precompiles, [EIP-2537](https://eips.ethereum.org/EIPS/eip-2537).
+//!
+//! Curve operations are done by the faster `blst` library if the `blst` feature is enabled,
+//! or by the pure Rust `arkworks` crates otherwise.
+
+use crate::PrecompileWithAddress;
+
+#[cfg(any(test, not(all(feature = "blst", not(target_arch = "wasm32")))))]
+mod arkworks;
+#[cfg(all(feature = "blst", not(target_arch = "wasm32")))]
+mod blst;
+pub mod g1;
+pub mod g2;
+pub mod map;
+pub mod pairing;
+mod utils;
+
+#[cfg(not(all(feature = "blst", not(target_arch = "wasm32"))))]
+use arkworks as backend;
+#[cfg(all(feature = "blst", not(target_arch = "wasm32")))]
+use blst as backend;
+
+// `blst` is used where both backends are enabled.
+#[cfg(all(
+    feature = "arkworks",
+    feature = "blst",
+    not(any(test, target_arch = "wasm32"))
+))]
+use {ark_bls12_381 as _, ark_ec as _, ark_ff as _, ark_serialize as _};
+
+/// Returns the BLS12-381 precompiles.
+pub fn precompiles() -> [PrecompileWithAddress; 7] {
+    [
+        g1::ADD,
+        g1::MSM,
+        g2::ADD,
+        g2::MSM,
+        pairing::PAIRING,
+        map::MAP_FP_TO_G1,
+        map::MAP_FP2_TO_G2,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        g1::G1_LENGTH,
+        g2::G2_LENGTH,
+        utils::{fp_from_padded, fp_to_padded, PADDED_FP_LENGTH},
+        *,
+    };
+    use crate::{primitives::hex, Error, Precompile, PrecompileResult, Precompiles};
+    use alloc::vec::Vec;
+
+    const G1_GENERATOR: [u8; G1_LENGTH] = hex!(
+        "0000000000000000000000000000000017f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        "0000000000000000000000000000000008b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1"
+    );
+    const G2_GENERATOR: [u8; G2_LENGTH] = hex!(
+        "00000000000000000000000000000000024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+        "0000000000000000000000000000000013e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e"
+        "000000000000000000000000000000000ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801"
+        "000000000000000000000000000000000606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be"
+    );
+    /// Group order minus one, multiplying by it negates the point.
+    const MINUS_ONE: [u8; 32] =
+        hex!("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000");
+
+    fn call(precompile: PrecompileWithAddress, input: &[u8]) -> PrecompileResult {
+        let Precompile::Standard(fun) = precompile.1 else {
+            unreachable!()
+        };
+        fun(input, u64::MAX)
+    }
+
+    fn scalar(n: u8) -> [u8; 32] {
+        let mut scalar = [0; 32];
+        scalar[31] = n;
+        scalar
+    }
+
+    #[test]
+    fn g1_operations() {
+        let g = G1_GENERATOR;
+        let infinity = [0; G1_LENGTH];
+
+        let (gas, double) = call(g1::ADD, &[g, g].concat()).unwrap();
+        assert_eq!(gas, 375);
+        assert_eq!(call(g1::ADD, &[g, infinity].concat()).unwrap().1, g);
+
+        let msm = [&g[..], &scalar(1), &g, &scalar(1), &infinity, &scalar(5)].concat();
+        let (gas, sum) = call(g1::MSM, &msm).unwrap();
+        assert_eq!(gas, 3 * 848 * 12_000 / 1000);
+        assert_eq!(sum, double);
+        let (gas, sum) = call(g1::MSM, &[&g[..], &scalar(2)].concat()).unwrap();
+        assert_eq!(gas, 12_000);
+        assert_eq!(sum, double);
+        let (_, zero) = call(g1::MSM, &[&infinity[..], &scalar(2)].concat()).unwrap();
+        assert_eq!(zero, infinity);
+        let (_, negated) = call(g1::MSM, &[&g[..], &MINUS_ONE].concat()).unwrap();
+        let (_, zero) = call(g1::ADD, &[&g[..], &negated].concat()).unwrap();
+        assert_eq!(zero, infinity);
+
+        assert_eq!(call(g1::ADD, &g), Err(Error::Bls12381InputLength));
+        assert_eq!(call(g1::MSM, &[]), Err(Error::Bls12381InputLength));
+        assert_eq!(call(g1::MSM, &g), Err(Error::Bls12381InputLength));
+    }
+
+    #[test]
+    fn g2_operations() {
+        let g = G2_GENERATOR;
+        let infinity = [0; G2_LENGTH];
+
+        let (gas, double) = call(g2::ADD, &[g, g].concat()).unwrap();
+        assert_eq!(gas, 600);
+        assert_eq!(call(g2::ADD, &[g, infinity].concat()).unwrap().1, g);
+        let (gas, sum) = call(g2::MSM, &[&g[..], &scalar(2)].concat()).unwrap();
+        assert_eq!(gas, 22_500);
+        assert_eq!(sum, double);
+
+        let msm = [&g[..], &scalar(3), &g, &scalar(255)].concat();
+        let (gas, sum) = call(g2::MSM, &msm).unwrap();
+        assert_eq!(gas, 2 * 1000 * 22_500 / 1000);
+        let (_, expected) = call(g2::MSM, &[&double[..], &scalar(129)].concat()).unwrap();
+        assert_eq!(sum, expected);
+    }
+
+    #[test]
+    fn pairing_check() {
+        let (g1, g2) = (G1_GENERATOR, G2_GENERATOR);
+        let (_, negated) = call(g1::MSM, &[&g1[..], &MINUS_ONE].concat()).unwrap();
+
+        let (gas, out) = call(pairing::PAIRING, &[&g1[..], &g2, &negated, &g2].concat()).unwrap();
+        assert_eq!(gas, 2 * 32_600 + 37_700);
+        assert_eq!(out, scalar(1));
+        assert_eq!(
+            call(pairing::PAIRING, &[&g1[..], &g2].concat()).unwrap().1,
+            scalar(0)
+        );
+        let infinity = [0; G1_LENGTH];
+        let (_, out) = call(pairing::PAIRING, &[&infinity[..], &g2].concat()).unwrap();
+        assert_eq!(out, scalar(1));
+        assert_eq!(call(pairing::PAIRING, &g1), Err(Error::Bls12381InputLength));
+    }
+
+    #[test]
+    fn map_to_curve() {
+        let fp = [0xab; PADDED_FP_LENGTH];
+        assert_eq!(
+            call(map::MAP_FP_TO_G1, &fp),
+            Err(Error::Bls12381FpPaddingInvalid)
+        );
+
+        let mut fp = [0; PADDED_FP_LENGTH];
+        fp[PADDED_FP_LENGTH - 1] = 1;
+        let (gas, point) = call(map::MAP_FP_TO_G1, &fp).unwrap();
+        assert_eq!(gas, 5_500);
+        // Mapped points are in the subgroup.
+        call(g1::MSM, &[&point[..], &scalar(1)].concat()).unwrap();
+
+        let (gas, point) = call(map::MAP_FP2_TO_G2, &[fp, fp].concat()).unwrap();
+        assert_eq!(gas, 23_800);
+        call(g2::MSM, &[&point[..], &scalar(1)].concat()).unwrap();
+    }
+
+    #[test]
+    fn invalid_points() {
+        // Modulus is not canonical, modulus - 1 is.
+        let modulus = hex!(
+            "000000000000000000000000000000001a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf"
+            "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab"
+        );
+        assert_eq!(fp_from_padded(&modulus), Err(Error::Bls12381FpNotCanonical));
+        let mut max = modulus;
+        max[PADDED_FP_LENGTH - 1] -= 1;
+        let mut encoded = [0; PADDED_FP_LENGTH];
+        fp_to_padded(&mut encoded, &fp_from_padded(&max).unwrap());
+        assert_eq!(encoded, max);
+
+        let mut not_on_curve = G1_GENERATOR;
+        not_on_curve[G1_LENGTH - 1] ^= 1;
+        assert_eq!(
+            call(g1::ADD, &[not_on_curve, not_on_curve].concat()),
+            Err(Error::Bls12381G1NotOnCurve)
+        );
+        let mut not_on_curve = G2_GENERATOR;
+        not_on_curve[G2_LENGTH - 1] ^= 1;
+        assert_eq!(
+            call(g2::ADD, &[not_on_curve, not_on_curve].concat()),
+            Err(Error::Bls12381G2NotOnCurve)
+        );
+
+        // Point on the curve y^2 = x^3 + 4 outside of the subgroup.
+        use ark_ff::{BigInteger, Field, PrimeField};
+        let point = (1u64..)
+            .find_map(|x| {
+                let x = ark_bls12_381::Fq::from(x);
+                let y = (x.square() * x + ark_bls12_381::Fq::from(4)).sqrt()?;
+                let mut encoded = Vec::with_capacity(G1_LENGTH);
+                for fp in [x, y] {
+                    let mut bytes = [0; PADDED_FP_LENGTH];
+                    fp_to_padded(
+                        &mut bytes,
+                        &fp.into_bigint().to_bytes_be().try_into().unwrap(),
+                    );
+                    encoded.extend_from_slice(&bytes);
+                }
+                Some(encoded)
+            })
+            .unwrap();
+        call(g1::ADD, &[&point[..], &point].concat()).unwrap();
+        assert_eq!(
+            call(g1::MSM, &[&point[..], &scalar(1)].concat()),
+            Err(Error::Bls12381G1NotInSubgroup)
+        );
+        assert_eq!(
+            call(pairing::PAIRING, &[&point[..], &G2_GENERATOR].concat()),
+            Err(Error::Bls12381G1NotInSubgroup)
+        );
+    }
+
+    /// Compares the results of the `blst` backend used by the precompiles with `arkworks`.
+    #[cfg(all(feature = "blst", not(target_arch = "wasm32")))]
+    #[test]
+    fn backends_agree() {
+        use super::utils::{fps_from_padded, Fp};
+
+        let g1: [Fp; 2] = fps_from_padded(&G1_GENERATOR).unwrap();
+        let g2: [Fp; 4] = fps_from_padded(&G2_GENERATOR).unwrap();
+        for n in [0u8, 1, 2, 7, 255] {
+            let mut fp = [0; 48];
+            fp[47] = n;
+            fp[0] = n / 16;
+            let p1 = blst::map_fp_to_g1(&fp);
+            assert_eq!(p1, arkworks::map_fp_to_g1(&fp));
+            let p2 = blst::map_fp2_to_g2(&[fp, g1[0]]);
+            assert_eq!(p2, arkworks::map_fp2_to_g2(&[fp, g1[0]]));
+
+            assert_eq!(blst::g1_add(&p1, &g1), arkworks::g1_add(&p1, &g1));
+            assert_eq!(blst::g2_add(&p2, &g2), arkworks::g2_add(&p2, &g2));
+            let scalars = [scalar(n), MINUS_ONE];
+            assert_eq!(
+                blst::g1_msm(&[p1, g1], &scalars),
+                arkworks::g1_msm(&[p1, g1], &scalars)
+            );
+            assert_eq!(
+                blst::g2_msm(&[p2, g2], &scalars),
+                arkworks::g2_msm(&[p2, g2], &scalars)
+            );
+            let pairs = [(p1, g2), (g1, p2)];
+            assert_eq!(blst::pairing_check(&pairs), arkworks::pairing_check(&pairs));
+        }
+    }
+
+    #[test]
+    fn prague_precompiles() {
+        assert!(!Precompiles::cancun().contains(&g1::ADD.0));
+        for precompile in precompiles() {
+            assert!(Precompiles::prague().contains(&precompile.0));
+        }
+        assert_eq!(
+            Precompiles::prague().len(),
+            Precompiles::cancun().len() + precompiles().len()
+        );
+    }
+}
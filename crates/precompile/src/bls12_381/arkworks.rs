@@ -0,0 +1,140 @@
+//! Curve operations of the precompiles with the pure Rust `arkworks` crates.
+
+use super::utils::{Fp, G1Point, G2Point, Scalar, FP_LENGTH};
+use crate::Error;
+use alloc::vec::Vec;
+use ark_bls12_381::{
+    g1, g2, Bls12_381, Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective,
+};
+use ark_ec::{
+    hashing::{curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurve},
+    pairing::Pairing,
+    AffineRepr, CurveGroup, VariableBaseMSM,
+};
+use ark_ff::{BigInteger, One, PrimeField, Zero};
+use ark_serialize::CanonicalDeserialize;
+
+fn read_fp(input: &Fp) -> Result<Fq, Error> {
+    let mut little_endian = *input;
+    little_endian.reverse();
+    Fq::deserialize_uncompressed(&little_endian[..]).map_err(|_| Error::Bls12381FpNotCanonical)
+}
+
+fn write_fp(input: &Fq) -> Fp {
+    let mut out = [0; FP_LENGTH];
+    out.copy_from_slice(&input.into_bigint().to_bytes_be());
+    out
+}
+
+fn read_scalar(input: &Scalar) -> Fr {
+    // Points are in the subgroup, so reducing the scalar doesn't change the products.
+    Fr::from_be_bytes_mod_order(input)
+}
+
+/// Reads the G1 point and checks that it is on the curve and optionally in the subgroup.
+fn read_g1(input: &G1Point, subgroup_check: bool) -> Result<G1Affine, Error> {
+    let (x, y) = (read_fp(&input[0])?, read_fp(&input[1])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(Error::Bls12381G1NotOnCurve);
+    }
+    if subgroup_check && !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::Bls12381G1NotInSubgroup);
+    }
+    Ok(point)
+}
+
+fn write_g1(point: &G1Affine) -> G1Point {
+    match point.xy() {
+        Some((x, y)) => [write_fp(&x), write_fp(&y)],
+        None => [[0; FP_LENGTH]; 2],
+    }
+}
+
+fn read_fp2(c0: &Fp, c1: &Fp) -> Result<Fq2, Error> {
+    Ok(Fq2::new(read_fp(c0)?, read_fp(c1)?))
+}
+
+/// Reads the G2 point and checks that it is on the curve and optionally in the subgroup.
+fn read_g2(input: &G2Point, subgroup_check: bool) -> Result<G2Affine, Error> {
+    let x = read_fp2(&input[0], &input[1])?;
+    let y = read_fp2(&input[2], &input[3])?;
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(Error::Bls12381G2NotOnCurve);
+    }
+    if subgroup_check && !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::Bls12381G2NotInSubgroup);
+    }
+    Ok(point)
+}
+
+fn write_g2(point: &G2Affine) -> G2Point {
+    match point.xy() {
+        Some((x, y)) => [
+            write_fp(&x.c0),
+            write_fp(&x.c1),
+            write_fp(&y.c0),
+            write_fp(&y.c1),
+        ],
+        None => [[0; FP_LENGTH]; 4],
+    }
+}
+
+pub(super) fn g1_add(a: &G1Point, b: &G1Point) -> Result<G1Point, Error> {
+    let sum = read_g1(a, false)? + read_g1(b, false)?;
+    Ok(write_g1(&sum.into_affine()))
+}
+
+pub(super) fn g1_msm(points: &[G1Point], scalars: &[Scalar]) -> Result<G1Point, Error> {
+    let points = points
+        .iter()
+        .map(|point| read_g1(point, true))
+        .collect::<Result<Vec<_>, _>>()?;
+    let scalars: Vec<_> = scalars.iter().map(read_scalar).collect();
+    let sum = G1Projective::msm_unchecked(&points, &scalars);
+    Ok(write_g1(&sum.into_affine()))
+}
+
+pub(super) fn g2_add(a: &G2Point, b: &G2Point) -> Result<G2Point, Error> {
+    let sum = read_g2(a, false)? + read_g2(b, false)?;
+    Ok(write_g2(&sum.into_affine()))
+}
+
+pub(super) fn g2_msm(points: &[G2Point], scalars: &[Scalar]) -> Result<G2Point, Error> {
+    let points = points
+        .iter()
+        .map(|point| read_g2(point, true))
+        .collect::<Result<Vec<_>, _>>()?;
+    let scalars: Vec<_> = scalars.iter().map(read_scalar).collect();
+    let sum = G2Projective::msm_unchecked(&points, &scalars);
+    Ok(write_g2(&sum.into_affine()))
+}
+
+pub(super) fn pairing_check(pairs: &[(G1Point, G2Point)]) -> Result<bool, Error> {
+    let mut ps = Vec::with_capacity(pairs.len());
+    let mut qs = Vec::with_capacity(pairs.len());
+    for (p, q) in pairs {
+        ps.push(read_g1(p, true)?);
+        qs.push(read_g2(q, true)?);
+    }
+    Ok(Bls12_381::multi_pairing(ps, qs).0.is_one())
+}
+
+pub(super) fn map_fp_to_g1(fp: &Fp) -> G1Point {
+    let fp = read_fp(fp).expect("field element is canonical");
+    let point = WBMap::<g1::Config>::map_to_curve(fp).expect("the map is defined for all inputs");
+    write_g1(&point.clear_cofactor())
+}
+
+pub(super) fn map_fp2_to_g2(fp2: &[Fp; 2]) -> G2Point {
+    let fp2 = read_fp2(&fp2[0], &fp2[1]).expect("field elements are canonical");
+    let point = WBMap::<g2::Config>::map_to_curve(fp2).expect("the map is defined for all inputs");
+    write_g2(&point.clear_cofactor())
+}
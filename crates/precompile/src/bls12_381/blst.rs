@@ -0,0 +1,226 @@
+//! Curve operations of the precompiles with the `blst` library.
+
+use super::utils::{Fp, G1Point, G2Point, Scalar};
+use crate::Error;
+use alloc::vec::Vec;
+use blst::{
+    blst_bendian_from_fp, blst_final_exp, blst_fp, blst_fp12, blst_fp12_is_one, blst_fp12_mul,
+    blst_fp12_one, blst_fp2, blst_fp_from_bendian, blst_map_to_g1, blst_map_to_g2,
+    blst_miller_loop, blst_p1, blst_p1_add_or_double_affine, blst_p1_affine, blst_p1_affine_in_g1,
+    blst_p1_affine_is_inf, blst_p1_affine_on_curve, blst_p1_from_affine, blst_p1_mult,
+    blst_p1_to_affine, blst_p2, blst_p2_add_or_double_affine, blst_p2_affine, blst_p2_affine_in_g2,
+    blst_p2_affine_is_inf, blst_p2_affine_on_curve, blst_p2_from_affine, blst_p2_mult,
+    blst_p2_to_affine, blst_scalar, blst_scalar_from_bendian, p1_affines, p2_affines,
+};
+use core::ptr;
+
+/// Bits of the scalar used in multiplications.
+const NBITS: usize = 256;
+
+fn read_fp(input: &Fp) -> blst_fp {
+    let mut fp = blst_fp::default();
+    // SAFETY: `input` is 48 bytes long.
+    unsafe { blst_fp_from_bendian(&mut fp, input.as_ptr()) };
+    fp
+}
+
+fn write_fp(input: &blst_fp) -> Fp {
+    let mut out = [0; 48];
+    // SAFETY: `out` is 48 bytes long.
+    unsafe { blst_bendian_from_fp(out.as_mut_ptr(), input) };
+    out
+}
+
+/// Reads the little endian bytes of the big endian scalar.
+fn read_scalar(input: &Scalar) -> [u8; 32] {
+    let mut scalar = blst_scalar::default();
+    // SAFETY: `input` is 32 bytes long.
+    unsafe { blst_scalar_from_bendian(&mut scalar, input.as_ptr()) };
+    scalar.b
+}
+
+/// Reads the G1 point and checks that it is on the curve and optionally in the subgroup.
+fn read_g1(input: &G1Point, subgroup_check: bool) -> Result<blst_p1_affine, Error> {
+    // Zeros decode to the point at infinity of blst.
+    let point = blst_p1_affine {
+        x: read_fp(&input[0]),
+        y: read_fp(&input[1]),
+    };
+    // SAFETY: `point` is a valid blst value.
+    if unsafe { !blst_p1_affine_on_curve(&point) } {
+        return Err(Error::Bls12381G1NotOnCurve);
+    }
+    if subgroup_check && unsafe { !blst_p1_affine_in_g1(&point) } {
+        return Err(Error::Bls12381G1NotInSubgroup);
+    }
+    Ok(point)
+}
+
+fn write_g1(point: &blst_p1) -> G1Point {
+    let mut affine = blst_p1_affine::default();
+    // SAFETY: pointers are valid blst values.
+    unsafe { blst_p1_to_affine(&mut affine, point) };
+    [write_fp(&affine.x), write_fp(&affine.y)]
+}
+
+/// Reads the G2 point and checks that it is on the curve and optionally in the subgroup.
+fn read_g2(input: &G2Point, subgroup_check: bool) -> Result<blst_p2_affine, Error> {
+    // Zeros decode to the point at infinity of blst.
+    let point = blst_p2_affine {
+        x: blst_fp2 {
+            fp: [read_fp(&input[0]), read_fp(&input[1])],
+        },
+        y: blst_fp2 {
+            fp: [read_fp(&input[2]), read_fp(&input[3])],
+        },
+    };
+    // SAFETY: `point` is a valid blst value.
+    if unsafe { !blst_p2_affine_on_curve(&point) } {
+        return Err(Error::Bls12381G2NotOnCurve);
+    }
+    if subgroup_check && unsafe { !blst_p2_affine_in_g2(&point) } {
+        return Err(Error::Bls12381G2NotInSubgroup);
+    }
+    Ok(point)
+}
+
+fn write_g2(point: &blst_p2) -> G2Point {
+    let mut affine = blst_p2_affine::default();
+    // SAFETY: pointers are valid blst values.
+    unsafe { blst_p2_to_affine(&mut affine, point) };
+    let [x0, x1] = &affine.x.fp;
+    let [y0, y1] = &affine.y.fp;
+    [write_fp(x0), write_fp(x1), write_fp(y0), write_fp(y1)]
+}
+
+fn p1_from_affine(point: &blst_p1_affine) -> blst_p1 {
+    let mut jacobian = blst_p1::default();
+    // SAFETY: pointers are valid blst values.
+    unsafe { blst_p1_from_affine(&mut jacobian, point) };
+    jacobian
+}
+
+fn p2_from_affine(point: &blst_p2_affine) -> blst_p2 {
+    let mut jacobian = blst_p2::default();
+    // SAFETY: pointers are valid blst values.
+    unsafe { blst_p2_from_affine(&mut jacobian, point) };
+    jacobian
+}
+
+pub(super) fn g1_add(a: &G1Point, b: &G1Point) -> Result<G1Point, Error> {
+    let a = read_g1(a, false)?;
+    let b = read_g1(b, false)?;
+    let mut sum = blst_p1::default();
+    // SAFETY: pointers are valid blst values.
+    unsafe { blst_p1_add_or_double_affine(&mut sum, &p1_from_affine(&a), &b) };
+    Ok(write_g1(&sum))
+}
+
+pub(super) fn g1_msm(points: &[G1Point], scalars: &[Scalar]) -> Result<G1Point, Error> {
+    let mut affines = Vec::with_capacity(points.len());
+    let mut bytes = Vec::with_capacity(scalars.len() * 32);
+    for (point, scalar) in points.iter().zip(scalars) {
+        let point = read_g1(point, true)?;
+        // Points at infinity don't change the sum and are not supported by the batch API.
+        // SAFETY: `point` is a valid blst value.
+        if unsafe { blst_p1_affine_is_inf(&point) } {
+            continue;
+        }
+        affines.push(p1_from_affine(&point));
+        bytes.extend_from_slice(&read_scalar(scalar));
+    }
+
+    let sum = match affines.as_slice() {
+        [] => blst_p1::default(),
+        [point] => {
+            let mut product = blst_p1::default();
+            // SAFETY: pointers are valid blst values, `bytes` has `NBITS` bits.
+            unsafe { blst_p1_mult(&mut product, point, bytes.as_ptr(), NBITS) };
+            product
+        }
+        points => p1_affines::from(points).mult(&bytes, NBITS),
+    };
+    Ok(write_g1(&sum))
+}
+
+pub(super) fn g2_add(a: &G2Point, b: &G2Point) -> Result<G2Point, Error> {
+    let a = read_g2(a, false)?;
+    let b = read_g2(b, false)?;
+    let mut sum = blst_p2::default();
+    // SAFETY: pointers are valid blst values.
+    unsafe { blst_p2_add_or_double_affine(&mut sum, &p2_from_affine(&a), &b) };
+    Ok(write_g2(&sum))
+}
+
+pub(super) fn g2_msm(points: &[G2Point], scalars: &[Scalar]) -> Result<G2Point, Error> {
+    let mut affines = Vec::with_capacity(points.len());
+    let mut bytes = Vec::with_capacity(scalars.len() * 32);
+    for (point, scalar) in points.iter().zip(scalars) {
+        let point = read_g2(point, true)?;
+        // Points at infinity don't change the sum and are not supported by the batch API.
+        // SAFETY: `point` is a valid blst value.
+        if unsafe { blst_p2_affine_is_inf(&point) } {
+            continue;
+        }
+        affines.push(p2_from_affine(&point));
+        bytes.extend_from_slice(&read_scalar(scalar));
+    }
+
+    let sum = match affines.as_slice() {
+        [] => blst_p2::default(),
+        [point] => {
+            let mut product = blst_p2::default();
+            // SAFETY: pointers are valid blst values, `bytes` has `NBITS` bits.
+            unsafe { blst_p2_mult(&mut product, point, bytes.as_ptr(), NBITS) };
+            product
+        }
+        points => p2_affines::from(points).mult(&bytes, NBITS),
+    };
+    Ok(write_g2(&sum))
+}
+
+pub(super) fn pairing_check(pairs: &[(G1Point, G2Point)]) -> Result<bool, Error> {
+    // SAFETY: `blst_fp12_one` points to a static value.
+    let mut product: blst_fp12 = unsafe { *blst_fp12_one() };
+    for (p, q) in pairs {
+        let p = read_g1(p, true)?;
+        let q = read_g2(q, true)?;
+        // Pairings with the point at infinity are one.
+        // SAFETY: pointers are valid blst values.
+        if unsafe { blst_p1_affine_is_inf(&p) || blst_p2_affine_is_inf(&q) } {
+            continue;
+        }
+        let mut miller_loop = blst_fp12::default();
+        let mut accumulated = blst_fp12::default();
+        // SAFETY: pointers are valid blst values.
+        unsafe {
+            blst_miller_loop(&mut miller_loop, &q, &p);
+            blst_fp12_mul(&mut accumulated, &product, &miller_loop);
+        }
+        product = accumulated;
+    }
+
+    let mut result = blst_fp12::default();
+    // SAFETY: pointers are valid blst values.
+    Ok(unsafe {
+        blst_final_exp(&mut result, &product);
+        blst_fp12_is_one(&result)
+    })
+}
+
+pub(super) fn map_fp_to_g1(fp: &Fp) -> G1Point {
+    let mut point = blst_p1::default();
+    // SAFETY: pointers are valid blst values, the second element is optional.
+    unsafe { blst_map_to_g1(&mut point, &read_fp(fp), ptr::null()) };
+    write_g1(&point)
+}
+
+pub(super) fn map_fp2_to_g2(fp2: &[Fp; 2]) -> G2Point {
+    let fp2 = blst_fp2 {
+        fp: [read_fp(&fp2[0]), read_fp(&fp2[1])],
+    };
+    let mut point = blst_p2::default();
+    // SAFETY: pointers are valid blst values, the second element is optional.
+    unsafe { blst_map_to_g2(&mut point, &fp2, ptr::null()) };
+    write_g2(&point)
+}
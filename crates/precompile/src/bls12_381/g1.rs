@@ -0,0 +1,70 @@
+use super::{
+    backend,
+    utils::{
+        fps_from_padded, fps_to_padded, msm_required_gas, G1Point, G1_MSM_DISCOUNT_TABLE,
+        PADDED_FP_LENGTH, SCALAR_LENGTH,
+    },
+};
+use crate::{u64_to_address, Error, Precompile, PrecompileResult, PrecompileWithAddress};
+use alloc::vec::Vec;
+
+/// Length of an encoded G1 point.
+pub(super) const G1_LENGTH: usize = 2 * PADDED_FP_LENGTH;
+
+pub const ADD: PrecompileWithAddress =
+    PrecompileWithAddress(u64_to_address(0x0b), Precompile::Standard(g1_add));
+pub const MSM: PrecompileWithAddress =
+    PrecompileWithAddress(u64_to_address(0x0c), Precompile::Standard(g1_msm));
+
+const ADD_GAS: u64 = 375;
+/// Gas of a single multiplication, discounted for larger multi-scalar multiplications.
+const MSM_BASE_GAS: u64 = 12_000;
+/// Length of a point and scalar pair of the multi-scalar multiplication.
+const MSM_INPUT_LENGTH: usize = G1_LENGTH + SCALAR_LENGTH;
+
+/// Encodes the G1 point, the point at infinity is encoded as zeros.
+pub(super) fn encode_g1_point(point: &G1Point) -> Vec<u8> {
+    fps_to_padded(point)
+}
+
+/// Decodes the coordinates of the G1 point. Checks of the point itself are done by the backend.
+pub(super) fn decode_g1_point(input: &[u8]) -> Result<G1Point, Error> {
+    debug_assert_eq!(input.len(), G1_LENGTH);
+    fps_from_padded(input)
+}
+
+fn g1_add(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if ADD_GAS > gas_limit {
+        return Err(Error::OutOfGas);
+    }
+    if input.len() != 2 * G1_LENGTH {
+        return Err(Error::Bls12381InputLength);
+    }
+    let a = decode_g1_point(&input[..G1_LENGTH])?;
+    let b = decode_g1_point(&input[G1_LENGTH..])?;
+    // Addition doesn't require points to be in the subgroup.
+    let sum = backend::g1_add(&a, &b)?;
+    Ok((ADD_GAS, encode_g1_point(&sum)))
+}
+
+fn g1_msm(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let pairs = input.chunks_exact(MSM_INPUT_LENGTH);
+    if input.is_empty() || !pairs.remainder().is_empty() {
+        return Err(Error::Bls12381InputLength);
+    }
+    let k = pairs.len();
+    let gas = msm_required_gas(k, &G1_MSM_DISCOUNT_TABLE, MSM_BASE_GAS);
+    if gas > gas_limit {
+        return Err(Error::OutOfGas);
+    }
+
+    let mut points = Vec::with_capacity(k);
+    let mut scalars = Vec::with_capacity(k);
+    for pair in pairs {
+        let (point, scalar) = pair.split_at(G1_LENGTH);
+        points.push(decode_g1_point(point)?);
+        scalars.push(scalar.try_into().unwrap());
+    }
+    let sum = backend::g1_msm(&points, &scalars)?;
+    Ok((gas, encode_g1_point(&sum)))
+}
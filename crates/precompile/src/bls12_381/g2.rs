@@ -0,0 +1,70 @@
+use super::{
+    backend,
+    utils::{
+        fps_from_padded, fps_to_padded, msm_required_gas, G2Point, G2_MSM_DISCOUNT_TABLE,
+        PADDED_FP_LENGTH, SCALAR_LENGTH,
+    },
+};
+use crate::{u64_to_address, Error, Precompile, PrecompileResult, PrecompileWithAddress};
+use alloc::vec::Vec;
+
+/// Length of an encoded G2 point.
+pub(super) const G2_LENGTH: usize = 4 * PADDED_FP_LENGTH;
+
+pub const ADD: PrecompileWithAddress =
+    PrecompileWithAddress(u64_to_address(0x0d), Precompile::Standard(g2_add));
+pub const MSM: PrecompileWithAddress =
+    PrecompileWithAddress(u64_to_address(0x0e), Precompile::Standard(g2_msm));
+
+const ADD_GAS: u64 = 600;
+/// Gas of a single multiplication, discounted for larger multi-scalar multiplications.
+const MSM_BASE_GAS: u64 = 22_500;
+/// Length of a point and scalar pair of the multi-scalar multiplication.
+const MSM_INPUT_LENGTH: usize = G2_LENGTH + SCALAR_LENGTH;
+
+/// Encodes the G2 point, the point at infinity is encoded as zeros.
+pub(super) fn encode_g2_point(point: &G2Point) -> Vec<u8> {
+    fps_to_padded(point)
+}
+
+/// Decodes the coordinates of the G2 point. Checks of the point itself are done by the backend.
+pub(super) fn decode_g2_point(input: &[u8]) -> Result<G2Point, Error> {
+    debug_assert_eq!(input.len(), G2_LENGTH);
+    fps_from_padded(input)
+}
+
+fn g2_add(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if ADD_GAS > gas_limit {
+        return Err(Error::OutOfGas);
+    }
+    if input.len() != 2 * G2_LENGTH {
+        return Err(Error::Bls12381InputLength);
+    }
+    let a = decode_g2_point(&input[..G2_LENGTH])?;
+    let b = decode_g2_point(&input[G2_LENGTH..])?;
+    // Addition doesn't require points to be in the subgroup.
+    let sum = backend::g2_add(&a, &b)?;
+    Ok((ADD_GAS, encode_g2_point(&sum)))
+}
+
+fn g2_msm(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let pairs = input.chunks_exact(MSM_INPUT_LENGTH);
+    if input.is_empty() || !pairs.remainder().is_empty() {
+        return Err(Error::Bls12381InputLength);
+    }
+    let k = pairs.len();
+    let gas = msm_required_gas(k, &G2_MSM_DISCOUNT_TABLE, MSM_BASE_GAS);
+    if gas > gas_limit {
+        return Err(Error::OutOfGas);
+    }
+
+    let mut points = Vec::with_capacity(k);
+    let mut scalars = Vec::with_capacity(k);
+    for pair in pairs {
+        let (point, scalar) = pair.split_at(G2_LENGTH);
+        points.push(decode_g2_point(point)?);
+        scalars.push(scalar.try_into().unwrap());
+    }
+    let sum = backend::g2_msm(&points, &scalars)?;
+    Ok((gas, encode_g2_point(&sum)))
+}
@@ -0,0 +1,45 @@
+use super::{
+    backend,
+    g1::encode_g1_point,
+    g2::encode_g2_point,
+    utils::{fps_from_padded, PADDED_FP_LENGTH},
+};
+use crate::{u64_to_address, Error, Precompile, PrecompileResult, PrecompileWithAddress};
+
+pub const MAP_FP_TO_G1: PrecompileWithAddress =
+    PrecompileWithAddress(u64_to_address(0x10), Precompile::Standard(map_fp_to_g1));
+pub const MAP_FP2_TO_G2: PrecompileWithAddress =
+    PrecompileWithAddress(u64_to_address(0x11), Precompile::Standard(map_fp2_to_g2));
+
+const MAP_FP_TO_G1_GAS: u64 = 5_500;
+const MAP_FP2_TO_G2_GAS: u64 = 23_800;
+
+/// Maps the field element to a G1 point with the simplified SWU map.
+fn map_fp_to_g1(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if MAP_FP_TO_G1_GAS > gas_limit {
+        return Err(Error::OutOfGas);
+    }
+    if input.len() != PADDED_FP_LENGTH {
+        return Err(Error::Bls12381InputLength);
+    }
+    let [fp] = fps_from_padded(input)?;
+    Ok((
+        MAP_FP_TO_G1_GAS,
+        encode_g1_point(&backend::map_fp_to_g1(&fp)),
+    ))
+}
+
+/// Maps the element of the quadratic extension field to a G2 point with the simplified SWU map.
+fn map_fp2_to_g2(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if MAP_FP2_TO_G2_GAS > gas_limit {
+        return Err(Error::OutOfGas);
+    }
+    if input.len() != 2 * PADDED_FP_LENGTH {
+        return Err(Error::Bls12381InputLength);
+    }
+    let fp2 = fps_from_padded(input)?;
+    Ok((
+        MAP_FP2_TO_G2_GAS,
+        encode_g2_point(&backend::map_fp2_to_g2(&fp2)),
+    ))
+}
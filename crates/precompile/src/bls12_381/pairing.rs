@@ -0,0 +1,39 @@
+use super::{
+    backend,
+    g1::{decode_g1_point, G1_LENGTH},
+    g2::{decode_g2_point, G2_LENGTH},
+};
+use crate::{u64_to_address, Error, Precompile, PrecompileResult, PrecompileWithAddress};
+use alloc::vec::Vec;
+
+pub const PAIRING: PrecompileWithAddress =
+    PrecompileWithAddress(u64_to_address(0x0f), Precompile::Standard(pairing));
+
+const PAIRING_PER_PAIR: u64 = 32_600;
+const PAIRING_BASE: u64 = 37_700;
+const PAIR_LENGTH: usize = G1_LENGTH + G2_LENGTH;
+
+/// Checks that the product of the pairings of the G1 and G2 point pairs is one.
+///
+/// Output is 32 bytes, 1 if the check passes and 0 otherwise.
+fn pairing(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let pairs = input.chunks_exact(PAIR_LENGTH);
+    if input.is_empty() || !pairs.remainder().is_empty() {
+        return Err(Error::Bls12381InputLength);
+    }
+    let k = pairs.len() as u64;
+    let gas = PAIRING_PER_PAIR * k + PAIRING_BASE;
+    if gas > gas_limit {
+        return Err(Error::OutOfGas);
+    }
+
+    let pairs = pairs
+        .map(|pair| {
+            let (p, q) = pair.split_at(G1_LENGTH);
+            Ok((decode_g1_point(p)?, decode_g2_point(q)?))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let mut out = vec![0; 32];
+    out[31] = backend::pairing_check(&pairs)? as u8;
+    Ok((gas, out))
+}
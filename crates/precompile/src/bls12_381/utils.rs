@@ -0,0 +1,110 @@
+use crate::Error;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Length of an encoded field element.
+pub(super) const FP_LENGTH: usize = 48;
+/// Length of a field element padded to 64 bytes.
+pub(super) const PADDED_FP_LENGTH: usize = 64;
+/// Length of the zero padding of a field element.
+const PADDING_LENGTH: usize = PADDED_FP_LENGTH - FP_LENGTH;
+/// Length of an encoded scalar.
+pub(super) const SCALAR_LENGTH: usize = 32;
+
+/// Big endian field element, less than the modulus.
+pub(super) type Fp = [u8; FP_LENGTH];
+/// Affine G1 point as big endian `x ++ y`, the point at infinity is all zeros.
+pub(super) type G1Point = [Fp; 2];
+/// Affine G2 point as big endian `x.c0 ++ x.c1 ++ y.c0 ++ y.c1`, the point at infinity is all
+/// zeros.
+pub(super) type G2Point = [Fp; 4];
+/// Big endian scalar, not required to be less than the group order.
+pub(super) type Scalar = [u8; SCALAR_LENGTH];
+
+/// Big endian encoding of the base field modulus.
+const MODULUS_REPR: Fp = [
+    0x1a, 0x01, 0x11, 0xea, 0x39, 0x7f, 0xe6, 0x9a, 0x4b, 0x1b, 0xa7, 0xb6, 0x43, 0x4b, 0xac, 0xd7,
+    0x64, 0x77, 0x4b, 0x84, 0xf3, 0x85, 0x12, 0xbf, 0x67, 0x30, 0xd2, 0xa0, 0xf6, 0xb0, 0xf6, 0x24,
+    0x1e, 0xab, 0xff, 0xfe, 0xb1, 0x53, 0xff, 0xff, 0xb9, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xaa, 0xab,
+];
+
+/// Encodes the field element as 64 bytes, big endian with 16 bytes of zero padding.
+pub(super) fn fp_to_padded(out: &mut [u8], input: &Fp) {
+    let (padding, rest) = out.split_at_mut(PADDING_LENGTH);
+    padding.fill(0);
+    rest.copy_from_slice(input);
+}
+
+/// Decodes the field element padded to 64 bytes.
+///
+/// Fails if the padding is not zero or the element is not less than the modulus.
+pub(super) fn fp_from_padded(input: &[u8]) -> Result<Fp, Error> {
+    debug_assert_eq!(input.len(), PADDED_FP_LENGTH);
+    let (padding, input) = input.split_at(PADDING_LENGTH);
+    if padding.iter().any(|byte| *byte != 0) {
+        return Err(Error::Bls12381FpPaddingInvalid);
+    }
+    if input.cmp(&MODULUS_REPR[..]) != Ordering::Less {
+        return Err(Error::Bls12381FpNotCanonical);
+    }
+    Ok(input.try_into().unwrap())
+}
+
+/// Decodes the padded field elements of an encoded point.
+pub(super) fn fps_from_padded<const N: usize>(input: &[u8]) -> Result<[Fp; N], Error> {
+    debug_assert_eq!(input.len(), N * PADDED_FP_LENGTH);
+    let mut fps = [[0; FP_LENGTH]; N];
+    for (fp, input) in fps.iter_mut().zip(input.chunks_exact(PADDED_FP_LENGTH)) {
+        *fp = fp_from_padded(input)?;
+    }
+    Ok(fps)
+}
+
+/// Encodes the field elements of a point, each padded to 64 bytes.
+pub(super) fn fps_to_padded(fps: &[Fp]) -> Vec<u8> {
+    let mut out = vec![0; fps.len() * PADDED_FP_LENGTH];
+    for (out, fp) in out.chunks_exact_mut(PADDED_FP_LENGTH).zip(fps) {
+        fp_to_padded(out, fp);
+    }
+    out
+}
+
+/// Discounts of G1 multi-scalar multiplications with `k` pairs, per mille, indexed by `k - 1`.
+/// Discount of the last entry applies to larger `k`.
+pub(super) const G1_MSM_DISCOUNT_TABLE: [u16; 128] = [
+    1000, 949, 848, 797, 764, 750, 738, 728, 719, 712, 705, 698, 692, 687, 682, 677, 673, 669, 665,
+    661, 658, 654, 651, 648, 645, 642, 640, 637, 635, 632, 630, 627, 625, 623, 621, 619, 617, 615,
+    613, 611, 609, 608, 606, 604, 603, 601, 599, 598, 596, 595, 593, 592, 591, 589, 588, 586, 585,
+    584, 582, 581, 580, 579, 577, 576, 575, 574, 573, 572, 570, 569, 568, 567, 566, 565, 564, 563,
+    562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 551, 550, 549, 548, 547, 547, 546, 545,
+    544, 543, 542, 541, 540, 540, 539, 538, 537, 536, 536, 535, 534, 533, 532, 532, 531, 530, 529,
+    528, 528, 527, 526, 525, 525, 524, 523, 522, 522, 521, 520, 520, 519,
+];
+
+/// Discounts of G2 multi-scalar multiplications, see [G1_MSM_DISCOUNT_TABLE].
+pub(super) const G2_MSM_DISCOUNT_TABLE: [u16; 128] = [
+    1000, 1000, 923, 884, 855, 832, 812, 796, 782, 770, 759, 749, 740, 732, 724, 717, 711, 704,
+    699, 693, 688, 683, 679, 674, 670, 666, 663, 659, 655, 652, 649, 646, 643, 640, 637, 634, 632,
+    629, 627, 624, 622, 620, 618, 615, 613, 611, 609, 607, 606, 604, 602, 600, 598, 597, 595, 593,
+    592, 590, 589, 587, 586, 584, 583, 582, 580, 579, 578, 576, 575, 574, 573, 571, 570, 569, 568,
+    567, 566, 565, 563, 562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 552, 551, 550, 549,
+    548, 547, 546, 545, 545, 544, 543, 542, 541, 541, 540, 539, 538, 537, 537, 536, 535, 535, 534,
+    533, 532, 532, 531, 530, 530, 529, 528, 528, 527, 526, 526, 525, 524, 524,
+];
+
+/// Returns gas of a multi-scalar multiplication with `k` pairs, `multiplication_cost` is the
+/// gas of a single multiplication.
+pub(super) fn msm_required_gas(
+    k: usize,
+    discount_table: &[u16; 128],
+    multiplication_cost: u64,
+) -> u64 {
+    if k == 0 {
+        return 0;
+    }
+    let discount = discount_table[(k - 1).min(discount_table.len() - 1)] as u64;
+    (k as u64)
+        .saturating_mul(discount)
+        .saturating_mul(multiplication_cost)
+        / 1000
+}
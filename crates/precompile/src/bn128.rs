@@ -54,7 +54,7 @@ pub mod mul {
 
 pub mod pair {
     use super::*;
-    const ADDRESS: Address = crate::u64_to_address(8);
+    pub(crate) const ADDRESS: Address = crate::u64_to_address(8);
 
     const ISTANBUL_PAIR_PER_POINT: u64 = 34_000;
     const ISTANBUL_PAIR_BASE: u64 = 45_000;
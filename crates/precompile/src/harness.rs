@@ -0,0 +1,297 @@
+//! Harness for testing precompile implementations against test vectors.
+//!
+//! Vectors use the format of go-ethereum and execution-spec-tests precompile vectors, where a
+//! successful call is described as `{"Input", "Expected", "Name", "Gas"}` and a failing one as
+//! `{"Input", "ExpectedError", "Name"}`. Loading them from JSON requires the `test-vectors`
+//! feature.
+
+use crate::{primitives::Env, Error, Precompile, PrecompileResult};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// Vector of a successful precompile call.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "test-vectors", derive(serde::Deserialize))]
+#[cfg_attr(feature = "test-vectors", serde(rename_all = "PascalCase"))]
+pub struct TestVector {
+    #[cfg_attr(feature = "test-vectors", serde(with = "hex_bytes"))]
+    pub input: Vec<u8>,
+    #[cfg_attr(feature = "test-vectors", serde(with = "hex_bytes"))]
+    pub expected: Vec<u8>,
+    pub name: String,
+    /// Expected gas cost, not checked if not set.
+    #[cfg_attr(feature = "test-vectors", serde(default))]
+    pub gas: Option<u64>,
+}
+
+/// Vector of a failing precompile call.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "test-vectors", derive(serde::Deserialize))]
+#[cfg_attr(feature = "test-vectors", serde(rename_all = "PascalCase"))]
+pub struct FailureVector {
+    #[cfg_attr(feature = "test-vectors", serde(with = "hex_bytes"))]
+    pub input: Vec<u8>,
+    /// Error message of the reference implementation, informational only as error messages
+    /// differ between clients.
+    pub expected_error: String,
+    pub name: String,
+}
+
+/// Mismatch between the precompile and a vector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VectorMismatch {
+    /// Name of the vector.
+    pub name: String,
+    pub kind: MismatchKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MismatchKind {
+    /// Call failed while the vector expects success.
+    UnexpectedError(Error),
+    /// Call succeeded while the vector expects failure.
+    UnexpectedSuccess,
+    OutputMismatch {
+        expected: Vec<u8>,
+        got: Vec<u8>,
+    },
+    GasMismatch {
+        expected: u64,
+        got: u64,
+    },
+    /// Call with one gas less than the expected cost didn't run out of gas.
+    NotOutOfGas,
+}
+
+impl fmt::Display for VectorMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "vector {}: ", self.name)?;
+        match &self.kind {
+            MismatchKind::UnexpectedError(e) => write!(f, "unexpected error: {e}"),
+            MismatchKind::UnexpectedSuccess => write!(f, "expected failure but call succeeded"),
+            MismatchKind::OutputMismatch { expected, got } => write!(
+                f,
+                "output mismatch: expected {}, got {}",
+                crate::primitives::hex::encode(expected),
+                crate::primitives::hex::encode(got)
+            ),
+            MismatchKind::GasMismatch { expected, got } => {
+                write!(f, "gas mismatch: expected {expected}, got {got}")
+            }
+            MismatchKind::NotOutOfGas => write!(f, "call below the gas cost didn't run out of gas"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VectorMismatch {}
+
+/// Invariant broken by a precompile for an arbitrary input, see [PrecompileHarness::fuzz_one].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FuzzViolation {
+    /// Reported gas cost is above the gas limit.
+    GasAboveLimit { limit: u64, cost: u64 },
+    /// Two calls with the same input returned different results.
+    NonDeterministic,
+    /// Call with exactly the reported gas cost didn't succeed.
+    FailsWithExactGas(Error),
+}
+
+/// Standalone environment for calling a single precompile, without an EVM or database.
+#[derive(Clone, Debug)]
+pub struct PrecompileHarness {
+    precompile: Precompile,
+    env: Env,
+}
+
+impl PrecompileHarness {
+    /// Creates new harness for the precompile with default [Env].
+    pub fn new(precompile: Precompile) -> Self {
+        Self {
+            precompile,
+            env: Env::default(),
+        }
+    }
+
+    /// Sets the environment passed to [Precompile::Env] precompiles.
+    pub fn with_env(mut self, env: Env) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Calls the precompile.
+    pub fn call(&self, input: &[u8], gas_limit: u64) -> PrecompileResult {
+        match &self.precompile {
+            Precompile::Standard(f) => f(input, gas_limit),
+            Precompile::Env(f) => f(input, gas_limit, &self.env),
+        }
+    }
+
+    /// Checks the precompile against a successful vector.
+    ///
+    /// If the vector has gas set, the cost must match it and a call with one gas less must run
+    /// out of gas.
+    pub fn run_vector(&self, vector: &TestVector) -> Result<(), VectorMismatch> {
+        let mismatch = |kind| VectorMismatch {
+            name: vector.name.clone(),
+            kind,
+        };
+        let (cost, output) = self
+            .call(&vector.input, u64::MAX)
+            .map_err(|e| mismatch(MismatchKind::UnexpectedError(e)))?;
+        if output != vector.expected {
+            return Err(mismatch(MismatchKind::OutputMismatch {
+                expected: vector.expected.clone(),
+                got: output,
+            }));
+        }
+        if let Some(expected) = vector.gas {
+            if cost != expected {
+                return Err(mismatch(MismatchKind::GasMismatch {
+                    expected,
+                    got: cost,
+                }));
+            }
+            if expected > 0 && self.call(&vector.input, expected - 1) != Err(Error::OutOfGas) {
+                return Err(mismatch(MismatchKind::NotOutOfGas));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the precompile fails on the vector input.
+    pub fn run_failure_vector(&self, vector: &FailureVector) -> Result<(), VectorMismatch> {
+        match self.call(&vector.input, u64::MAX) {
+            Ok(_) => Err(VectorMismatch {
+                name: vector.name.clone(),
+                kind: MismatchKind::UnexpectedSuccess,
+            }),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Runs all vectors and returns the mismatches.
+    pub fn run_vectors<'a>(
+        &self,
+        vectors: impl IntoIterator<Item = &'a TestVector>,
+        failure_vectors: impl IntoIterator<Item = &'a FailureVector>,
+    ) -> Vec<VectorMismatch> {
+        vectors
+            .into_iter()
+            .filter_map(|vector| self.run_vector(vector).err())
+            .chain(
+                failure_vectors
+                    .into_iter()
+                    .filter_map(|vector| self.run_failure_vector(vector).err()),
+            )
+            .collect()
+    }
+
+    /// Checks invariants that must hold for any input, meant to be called from fuzz targets.
+    ///
+    /// The precompile must be deterministic, must not report cost above the gas limit and must
+    /// succeed when called with exactly the reported cost.
+    pub fn fuzz_one(&self, input: &[u8], gas_limit: u64) -> Result<(), FuzzViolation> {
+        let result = self.call(input, gas_limit);
+        if self.call(input, gas_limit) != result {
+            return Err(FuzzViolation::NonDeterministic);
+        }
+        let Ok((cost, output)) = result else {
+            return Ok(());
+        };
+        if cost > gas_limit {
+            return Err(FuzzViolation::GasAboveLimit {
+                limit: gas_limit,
+                cost,
+            });
+        }
+        match self.call(input, cost) {
+            Ok((exact_cost, exact_output)) if exact_cost == cost && exact_output == output => {
+                Ok(())
+            }
+            Ok(_) => Err(FuzzViolation::NonDeterministic),
+            Err(e) => Err(FuzzViolation::FailsWithExactGas(e)),
+        }
+    }
+}
+
+/// Parses JSON array of [TestVector]s.
+#[cfg(feature = "test-vectors")]
+pub fn parse_vectors(json: &str) -> serde_json::Result<Vec<TestVector>> {
+    serde_json::from_str(json)
+}
+
+/// Parses JSON array of [FailureVector]s.
+#[cfg(feature = "test-vectors")]
+pub fn parse_failure_vectors(json: &str) -> serde_json::Result<Vec<FailureVector>> {
+    serde_json::from_str(json)
+}
+
+#[cfg(feature = "test-vectors")]
+mod hex_bytes {
+    use alloc::{string::String, vec::Vec};
+    use serde::{de::Error, Deserialize, Deserializer};
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(d)?;
+        crate::primitives::hex::decode(s).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity;
+
+    #[test]
+    fn identity_vectors() {
+        let harness = PrecompileHarness::new(identity::FUN.1);
+        let vector = TestVector {
+            input: vec![1, 2],
+            expected: vec![1, 2],
+            name: "identity".into(),
+            gas: Some(18),
+        };
+        assert_eq!(harness.run_vector(&vector), Ok(()));
+
+        let wrong = TestVector {
+            gas: Some(17),
+            ..vector.clone()
+        };
+        assert_eq!(
+            harness.run_vectors([&wrong], []),
+            vec![VectorMismatch {
+                name: "identity".into(),
+                kind: MismatchKind::GasMismatch {
+                    expected: 17,
+                    got: 18
+                },
+            }]
+        );
+
+        let failure = FailureVector {
+            input: vec![],
+            expected_error: "none".into(),
+            name: "identity_fails".into(),
+        };
+        assert!(harness.run_failure_vector(&failure).is_err());
+        assert_eq!(harness.fuzz_one(&[0xff; 100], 1_000), Ok(()));
+    }
+
+    #[cfg(feature = "test-vectors")]
+    #[test]
+    fn parse_sha256_vectors() {
+        let vectors = parse_vectors(
+            r#"[{
+                "Input": "",
+                "Expected": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+                "Name": "sha256_empty",
+                "Gas": 60,
+                "NoBenchmark": false
+            }]"#,
+        )
+        .unwrap();
+        let harness = PrecompileHarness::new(crate::hash::SHA256.1);
+        assert!(harness.run_vectors(&vectors, []).is_empty());
+    }
+}
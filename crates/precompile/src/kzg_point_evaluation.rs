@@ -1,5 +1,4 @@
 use crate::{Address, Error, Precompile, PrecompileResult, PrecompileWithAddress};
-use c_kzg::{Bytes32, Bytes48, KzgProof, KzgSettings};
 use revm_primitives::{hex_literal::hex, Env};
 use sha2::{Digest, Sha256};
 
@@ -18,13 +17,14 @@ const RETURN_VALUE: &[u8; 64] = &hex!(
 
 /// Run kzg point evaluation precompile.
 ///
-/// The Env has the KZGSettings that is needed for evaluation.
+/// The Env has the KZG verifier that is needed for evaluation, see
+/// [revm_primitives::CfgEnv::kzg_settings].
 ///
 /// The input is encoded as follows:
 /// | versioned_hash |  z  |  y  | commitment | proof |
 /// |     32         | 32  | 32  |     48     |   48  |
 /// with z and y being padded 32 byte big endian values
-fn run(input: &[u8], gas_limit: u64, env: &Env) -> PrecompileResult {
+pub(crate) fn run(input: &[u8], gas_limit: u64, env: &Env) -> PrecompileResult {
     if gas_limit < GAS_COST {
         return Err(Error::OutOfGas);
     }
@@ -42,11 +42,16 @@ fn run(input: &[u8], gas_limit: u64, env: &Env) -> PrecompileResult {
     }
 
     // Verify KZG proof with z and y in big endian format
-    let commitment = as_bytes48(commitment);
-    let z = as_bytes32(&input[32..64]);
-    let y = as_bytes32(&input[64..96]);
-    let proof = as_bytes48(&input[144..192]);
-    if !verify_kzg_proof(commitment, z, y, proof, env.cfg.kzg_settings.get()) {
+    let commitment = as_array(commitment);
+    let z = as_array(&input[32..64]);
+    let y = as_array(&input[64..96]);
+    let proof = as_array(&input[144..192]);
+    if !env
+        .cfg
+        .kzg_settings
+        .verifier()
+        .verify_kzg_proof(commitment, z, y, proof)
+    {
         return Err(Error::BlobVerifyKzgProofFailed);
     }
 
@@ -62,53 +67,20 @@ fn kzg_to_versioned_hash(commitment: &[u8]) -> [u8; 32] {
     hash
 }
 
-#[inline]
-fn verify_kzg_proof(
-    commitment: &Bytes48,
-    z: &Bytes32,
-    y: &Bytes32,
-    proof: &Bytes48,
-    kzg_settings: &KzgSettings,
-) -> bool {
-    match KzgProof::verify_kzg_proof(commitment, z, y, proof, kzg_settings) {
-        Ok(ok) => ok,
-        #[cfg(not(debug_assertions))]
-        Err(_) => false,
-        #[cfg(debug_assertions)]
-        Err(e) => {
-            panic!("verify_kzg_proof returned an error: {e:?}");
-        }
-    }
-}
-
 #[inline]
 #[track_caller]
 fn as_array<const N: usize>(bytes: &[u8]) -> &[u8; N] {
     bytes.try_into().expect("slice with incorrect length")
 }
 
-#[inline]
-#[track_caller]
-fn as_bytes32(bytes: &[u8]) -> &Bytes32 {
-    // SAFETY: `#[repr(C)] Bytes32([u8; 32])`
-    unsafe { &*as_array::<32>(bytes).as_ptr().cast() }
-}
-
-#[inline]
-#[track_caller]
-fn as_bytes48(bytes: &[u8]) -> &Bytes48 {
-    // SAFETY: `#[repr(C)] Bytes48([u8; 48])`
-    unsafe { &*as_array::<48>(bytes).as_ptr().cast() }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::{sync::Arc, vec::Vec};
+    use revm_primitives::{EnvKzgSettings, KzgVerifier};
 
-    #[test]
-    fn basic_test() {
-        // test data from: https://github.com/ethereum/c-kzg-4844/blob/main/tests/verify_kzg_proof/kzg-mainnet/verify_kzg_proof_case_correct_proof_31ebd010e6098750/data.yaml
-
+    // test data from: https://github.com/ethereum/c-kzg-4844/blob/main/tests/verify_kzg_proof/kzg-mainnet/verify_kzg_proof_case_correct_proof_31ebd010e6098750/data.yaml
+    fn input() -> Vec<u8> {
         let commitment = hex!("8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7").to_vec();
         let mut versioned_hash = Sha256::digest(&commitment).to_vec();
         versioned_hash[0] = VERSIONED_HASH_VERSION_KZG;
@@ -116,13 +88,50 @@ mod tests {
         let y = hex!("1522a4a7f34e1ea350ae07c29c96c7e79655aa926122e95fe69fcbd932ca49e9").to_vec();
         let proof = hex!("a62ad71d14c5719385c0686f1871430475bf3a00f0aa3f7b8dd99a9abc2160744faf0070725e00b60ad9a026a15b1a8c").to_vec();
 
-        let input = [versioned_hash, z, y, commitment, proof].concat();
+        [versioned_hash, z, y, commitment, proof].concat()
+    }
 
+    #[test]
+    fn basic_test() {
         let expected_output = hex!("000000000000000000000000000000000000000000000000000000000000100073eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
         let gas = 50000;
         let env = Env::default();
-        let (actual_gas, actual_output) = run(&input, gas, &env).unwrap();
+        let (actual_gas, actual_output) = run(&input(), gas, &env).unwrap();
         assert_eq!(actual_gas, gas);
         assert_eq!(actual_output, expected_output);
     }
+
+    #[test]
+    fn custom_verifier() {
+        /// Accepts proofs equal to the commitment.
+        #[derive(Debug)]
+        struct EchoVerifier;
+
+        impl KzgVerifier for EchoVerifier {
+            fn verify_kzg_proof(
+                &self,
+                commitment: &[u8; 48],
+                _z: &[u8; 32],
+                _y: &[u8; 32],
+                proof: &[u8; 48],
+            ) -> bool {
+                commitment == proof
+            }
+        }
+
+        let mut env = Env::default();
+        env.cfg.kzg_settings = EnvKzgSettings::Verifier(Arc::new(EchoVerifier));
+        let mut input = input();
+        assert_eq!(
+            run(&input, GAS_COST, &env),
+            Err(Error::BlobVerifyKzgProofFailed)
+        );
+
+        let commitment = input[96..144].to_vec();
+        input[144..].copy_from_slice(&commitment);
+        assert_eq!(
+            run(&input, GAS_COST, &env),
+            Ok((GAS_COST, RETURN_VALUE.to_vec()))
+        );
+    }
 }
@@ -9,14 +9,38 @@
 #[macro_use]
 extern crate alloc;
 
+// Only enables the JavaScript backend of `getrandom`, see `Cargo.toml`.
+#[cfg(all(feature = "std", target_arch = "wasm32"))]
+use getrandom as _;
+
+// The module is left out without a backend, so only this error is reported.
+#[cfg(not(any(
+    feature = "arkworks",
+    target_arch = "wasm32",
+    all(feature = "blst", not(target_arch = "wasm32"))
+)))]
+compile_error!(
+    "a BLS12-381 backend is required: enable the `blst` feature, \
+     or `arkworks` on targets where `blst` is not available"
+);
+
+pub mod batch;
 mod blake2;
+#[cfg(any(
+    feature = "arkworks",
+    target_arch = "wasm32",
+    all(feature = "blst", not(target_arch = "wasm32"))
+))]
+pub mod bls12_381;
 mod bn128;
+pub mod harness;
 mod hash;
 mod identity;
-#[cfg(feature = "c-kzg")]
 pub mod kzg_point_evaluation;
 mod modexp;
 mod secp256k1;
+#[cfg(feature = "secp256r1")]
+pub mod secp256r1;
 pub mod utilities;
 
 use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
@@ -33,6 +57,25 @@ pub fn calc_linear_cost_u32(len: usize, base: u64, word: u64) -> u64 {
     (len as u64 + 32 - 1) / 32 * word + base
 }
 
+/// Checks input of the precompile at `address` against the limits.
+///
+/// Returns [Error::InputTooLarge] if the input exceeds the limit of the precompile.
+pub fn check_input_limits(
+    limits: &PrecompileInputLimits,
+    address: &Address,
+    input: &[u8],
+) -> Result<(), Error> {
+    let (limit, size) = match *address {
+        modexp::ADDRESS => (limits.modexp, modexp::declared_input_len(input)),
+        bn128::pair::ADDRESS => (limits.bn128_pair, input.len()),
+        _ => return Ok(()),
+    };
+    match limit {
+        Some(limit) if size > limit => Err(Error::InputTooLarge { limit, size }),
+        _ => Ok(()),
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct PrecompileOutput {
     pub cost: u64,
@@ -70,6 +113,7 @@ impl Precompiles {
             SpecId::ISTANBUL => Self::istanbul(),
             SpecId::BERLIN => Self::berlin(),
             SpecId::CANCUN => Self::cancun(),
+            SpecId::PRAGUE => Self::prague(),
             SpecId::LATEST => Self::latest(),
         }
     }
@@ -139,30 +183,39 @@ impl Precompiles {
 
     /// Returns precompiles for Cancun spec.
     ///
-    /// If `std` feature is not enabled KZG Point Evaluation precompile will not be included.
+    /// KZG Point Evaluation precompile uses the verifier of
+    /// [CfgEnv::kzg_settings][revm_primitives::CfgEnv::kzg_settings].
     pub fn cancun() -> &'static Self {
         static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
         INSTANCE.get_or_init(|| {
-            let precompiles = Self::berlin().clone();
-
-            // Don't include KZG point evaluation precompile in no_std builds.
-            #[cfg(feature = "c-kzg")]
-            let precompiles = {
-                let mut precompiles = precompiles;
-                precompiles.extend([
-                    // EIP-4844: Shard Blob Transactions
-                    kzg_point_evaluation::POINT_EVALUATION,
-                ]);
-                precompiles
-            };
+            let mut precompiles = Self::berlin().clone();
+            precompiles.extend([
+                // EIP-4844: Shard Blob Transactions
+                kzg_point_evaluation::POINT_EVALUATION,
+            ]);
+            Box::new(precompiles)
+        })
+    }
 
+    /// Returns precompiles for Prague spec.
+    pub fn prague() -> &'static Self {
+        static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
+        INSTANCE.get_or_init(|| {
+            let mut precompiles = Self::cancun().clone();
+            // EIP-2537: Precompile for BLS12-381 curve operations
+            #[cfg(any(
+                feature = "arkworks",
+                target_arch = "wasm32",
+                all(feature = "blst", not(target_arch = "wasm32"))
+            ))]
+            precompiles.extend(bls12_381::precompiles());
             Box::new(precompiles)
         })
     }
 
     /// Returns the precompiles for the latest spec.
     pub fn latest() -> &'static Self {
-        Self::cancun()
+        Self::prague()
     }
 
     /// Returns an iterator over the precompiles addresses.
@@ -226,6 +279,56 @@ impl Default for Precompiles {
     }
 }
 
+/// Provider of the precompiles available to the EVM.
+///
+/// [Precompiles] provide the precompiles of a spec. Chains can implement it to add, remove or
+/// replace precompiles at runtime, for example depending on the address or the active spec.
+pub trait PrecompileProvider: fmt::Debug {
+    /// Returns the precompile at the given address.
+    fn get(&self, address: &Address) -> Option<Precompile>;
+
+    /// Returns addresses of the precompiles, they are warm at the start of the transaction.
+    fn warm_addresses(&self) -> Vec<Address>;
+
+    /// Is the given address a precompile.
+    #[inline]
+    fn contains(&self, address: &Address) -> bool {
+        self.get(address).is_some()
+    }
+}
+
+impl PrecompileProvider for Precompiles {
+    #[inline]
+    fn get(&self, address: &Address) -> Option<Precompile> {
+        Precompiles::get(self, address)
+    }
+
+    fn warm_addresses(&self) -> Vec<Address> {
+        self.addresses().copied().collect()
+    }
+
+    #[inline]
+    fn contains(&self, address: &Address) -> bool {
+        Precompiles::contains(self, address)
+    }
+}
+
+impl<P: PrecompileProvider + ?Sized> PrecompileProvider for &P {
+    #[inline]
+    fn get(&self, address: &Address) -> Option<Precompile> {
+        (**self).get(address)
+    }
+
+    fn warm_addresses(&self) -> Vec<Address> {
+        (**self).warm_addresses()
+    }
+
+    #[inline]
+    fn contains(&self, address: &Address) -> bool {
+        (**self).contains(address)
+    }
+}
+
 #[derive(Clone)]
 pub enum Precompile {
     Standard(StandardPrecompileFn),
@@ -263,6 +366,7 @@ pub enum SpecId {
     ISTANBUL,
     BERLIN,
     CANCUN,
+    PRAGUE,
     LATEST,
 }
 
@@ -278,9 +382,14 @@ impl SpecId {
             ISTANBUL | MUIR_GLACIER => Self::ISTANBUL,
             BERLIN | LONDON | ARROW_GLACIER | GRAY_GLACIER | MERGE | SHANGHAI => Self::BERLIN,
             CANCUN => Self::CANCUN,
+            PRAGUE => Self::PRAGUE,
             LATEST => Self::LATEST,
             #[cfg(feature = "optimism")]
             BEDROCK | REGOLITH | CANYON => Self::BERLIN,
+            #[cfg(feature = "optimism")]
+            ECOTONE | FJORD => Self::CANCUN,
+            #[cfg(feature = "optimism")]
+            ISTHMUS => Self::PRAGUE,
         }
     }
 }
@@ -1,19 +1,20 @@
 use crate::{
     primitives::U256,
     utilities::{get_right_padded, get_right_padded_vec, left_padding, left_padding_vec},
-    Error, Precompile, PrecompileResult, PrecompileWithAddress, StandardPrecompileFn,
+    Address, Error, Precompile, PrecompileResult, PrecompileWithAddress, StandardPrecompileFn,
 };
 use alloc::vec::Vec;
-use aurora_engine_modexp::modexp;
 use core::cmp::{max, min};
 
+pub(crate) const ADDRESS: Address = crate::u64_to_address(5);
+
 pub const BYZANTIUM: PrecompileWithAddress = PrecompileWithAddress(
-    crate::u64_to_address(5),
+    ADDRESS,
     Precompile::Standard(byzantium_run as StandardPrecompileFn),
 );
 
 pub const BERLIN: PrecompileWithAddress = PrecompileWithAddress(
-    crate::u64_to_address(5),
+    ADDRESS,
     Precompile::Standard(berlin_run as StandardPrecompileFn),
 );
 
@@ -31,6 +32,14 @@ pub fn berlin_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
     })
 }
 
+/// Returns sum of the base, exponent and modulus lengths declared in the input header.
+pub(crate) fn declared_input_len(input: &[u8]) -> usize {
+    (0..3)
+        .map(|i| U256::from_be_bytes(get_right_padded::<32>(input, i * 32)))
+        .map(|len| usize::try_from(len).unwrap_or(usize::MAX))
+        .fold(0, usize::saturating_add)
+}
+
 fn calculate_iteration_count(exp_length: u64, exp_highp: &U256) -> u64 {
     let mut iteration_count: u64 = 0;
 
@@ -120,6 +129,34 @@ where
     Ok((gas_cost, left_padding_vec(&output, mod_len)))
 }
 
+/// Computes `base ^ exponent % modulus` of the big endian numbers, the result is big endian and
+/// not longer than the modulus.
+#[cfg(not(feature = "num-bigint"))]
+fn modexp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
+    aurora_engine_modexp::modexp(base, exponent, modulus)
+}
+
+/// Computes `base ^ exponent % modulus` of the big endian numbers, the result is big endian and
+/// not longer than the modulus.
+///
+/// Uses Montgomery multiplication of `num-bigint` for odd moduli.
+#[cfg(feature = "num-bigint")]
+fn modexp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
+    use num_bigint::BigUint;
+
+    // Silence the unused crate dependency warning.
+    use aurora_engine_modexp as _;
+
+    let modulus = BigUint::from_bytes_be(modulus);
+    // Zero modulus results in zero.
+    if modulus.bits() == 0 {
+        return Vec::new();
+    }
+    BigUint::from_bytes_be(base)
+        .modpow(&BigUint::from_bytes_be(exponent), &modulus)
+        .to_bytes_be()
+}
+
 fn byzantium_gas_calc(base_len: u64, exp_len: u64, mod_len: u64, exp_highp: &U256) -> u64 {
     // ouput of this function is bounded by 2^128
     fn mul_complexity(x: u64) -> U256 {
@@ -388,4 +425,82 @@ mod tests {
         let expected: Vec<u8> = Vec::new();
         assert_eq!(res.1, expected)
     }
+
+    #[test]
+    fn test_modexp_input_limit() {
+        let mut input = vec![0u8; 96];
+        input[31] = 64;
+        input[63] = 1;
+        input[95] = 64;
+        assert_eq!(declared_input_len(&input), 129);
+        assert_eq!(declared_input_len(&[0xff; 96]), usize::MAX);
+
+        let limits = crate::PrecompileInputLimits {
+            modexp: Some(128),
+            ..Default::default()
+        };
+        assert_eq!(
+            crate::check_input_limits(&limits, &ADDRESS, &input),
+            Err(Error::InputTooLarge {
+                limit: 128,
+                size: 129
+            })
+        );
+        assert_eq!(crate::check_input_limits(&limits, &ADDRESS, &[]), Ok(()));
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn num_bigint_backend_matches_aurora() {
+        fn check(base: &[u8], exponent: &[u8], modulus: &[u8]) {
+            assert_eq!(
+                left_padding_vec(&modexp(base, exponent, modulus), modulus.len()),
+                left_padding_vec(
+                    &aurora_engine_modexp::modexp(base, exponent, modulus),
+                    modulus.len()
+                ),
+                "base: {}, exponent: {}, modulus: {}",
+                hex::encode(base),
+                hex::encode(exponent),
+                hex::encode(modulus),
+            );
+        }
+
+        // Pseudo random bytes of xorshift64.
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut bytes = |len: usize| -> Vec<u8> {
+            (0..len)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state as u8
+                })
+                .collect()
+        };
+
+        for (base_len, exp_len, mod_len) in [
+            (1, 1, 1),
+            (32, 32, 32),
+            (64, 1, 64),
+            (31, 33, 65),
+            (100, 5, 128),
+            (256, 32, 256),
+        ] {
+            let (base, exponent, mut modulus) = (bytes(base_len), bytes(exp_len), bytes(mod_len));
+            check(&base, &exponent, &modulus);
+            // Both odd and even moduli, Montgomery multiplication is only used for odd ones.
+            modulus[mod_len - 1] ^= 1;
+            check(&base, &exponent, &modulus);
+            // Modulus with leading zeros.
+            modulus[0] = 0;
+            check(&base, &exponent, &modulus);
+            check(&base, &[], &modulus);
+            check(&[], &exponent, &modulus);
+        }
+        check(&[2], &[3], &[0; 32]);
+        check(&[2], &[3], &[0, 1]);
+        check(&[2], &[0], &[1]);
+        check(&[], &[], &[]);
+    }
 }
@@ -8,7 +8,7 @@ pub const ECRECOVER: PrecompileWithAddress = PrecompileWithAddress(
     Precompile::Standard(ec_recover_run as StandardPrecompileFn),
 );
 
-#[cfg(not(feature = "secp256k1"))]
+#[cfg(not(all(feature = "secp256k1", not(target_arch = "wasm32"))))]
 #[allow(clippy::module_inception)]
 mod secp256k1 {
     use crate::B256;
@@ -42,7 +42,7 @@ mod secp256k1 {
     }
 }
 
-#[cfg(feature = "secp256k1")]
+#[cfg(all(feature = "secp256k1", not(target_arch = "wasm32")))]
 #[allow(clippy::module_inception)]
 mod secp256k1 {
     use crate::B256;
@@ -68,7 +68,7 @@ mod secp256k1 {
     }
 }
 
-fn ec_recover_run(i: &[u8], target_gas: u64) -> PrecompileResult {
+pub(crate) fn ec_recover_run(i: &[u8], target_gas: u64) -> PrecompileResult {
     const ECRECOVER_BASE: u64 = 3_000;
 
     if ECRECOVER_BASE > target_gas {
@@ -0,0 +1,184 @@
+//! secp256r1 (P-256) signature verification precompile, [RIP-7212](https://github.com/ethereum/RIPs/blob/master/RIPS/rip-7212.md).
+//!
+//! It is not part of any Ethereum spec, rollups add [P256VERIFY] to their precompiles:
+//!
+//! ```
+//! use revm_precompile::{secp256r1, Precompiles};
+//!
+//! let mut precompiles = Precompiles::cancun().clone();
+//! precompiles.extend([secp256r1::P256VERIFY]);
+//! ```
+//!
+//! Signatures are verified with the `p256` crate.
+
+use crate::{u64_to_address, Error, Precompile, PrecompileResult, PrecompileWithAddress};
+use alloc::vec::Vec;
+use p256::{
+    ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey},
+    EncodedPoint,
+};
+
+pub const P256VERIFY: PrecompileWithAddress =
+    PrecompileWithAddress(u64_to_address(0x100), Precompile::Standard(p256_verify));
+
+const P256VERIFY_BASE: u64 = 3_450;
+
+/// Verifies the signature of the message hash with the public key.
+///
+/// The input is encoded as follows:
+/// | hash | r  | s  | x  | y  |
+/// |  32  | 32 | 32 | 32 | 32 |
+/// with x and y being the coordinates of the public key.
+///
+/// Output is 32 bytes 1 if the signature is valid, empty otherwise.
+fn p256_verify(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if P256VERIFY_BASE > gas_limit {
+        return Err(Error::OutOfGas);
+    }
+    let out = if verify(input) {
+        let mut out = vec![0; 32];
+        out[31] = 1;
+        out
+    } else {
+        Vec::new()
+    };
+    Ok((P256VERIFY_BASE, out))
+}
+
+fn verify(input: &[u8]) -> bool {
+    if input.len() != 160 {
+        return false;
+    }
+    let (hash, signature, public_key) = (&input[..32], &input[32..96], &input[96..]);
+
+    // Fails if r or s is zero or not less than the group order.
+    let Ok(signature) = Signature::from_slice(signature) else {
+        return false;
+    };
+    // Fails if the coordinates are not field elements or the point is not on the curve. The
+    // point at infinity has no affine coordinates and can't be encoded.
+    let public_key = EncodedPoint::from_untagged_bytes(public_key.into());
+    let Ok(public_key) = VerifyingKey::from_encoded_point(&public_key) else {
+        return false;
+    };
+    public_key.verify_prehash(hash, &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::hex;
+    use sha2::{Digest, Sha256};
+
+    // Key and signatures of RFC 6979, A.2.5 with SHA-256.
+    const UX: &str = "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6";
+    const UY: &str = "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299";
+    /// Group order.
+    const N: [u8; 32] = hex!("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551");
+    /// Base field modulus.
+    const P: [u8; 32] = hex!("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff");
+
+    fn input(message: &str, r: &str, s: &str) -> Vec<u8> {
+        let hash = Sha256::digest(message.as_bytes());
+        [
+            &hash[..],
+            &hex::decode(r).unwrap(),
+            &hex::decode(s).unwrap(),
+        ]
+        .concat()
+        .into_iter()
+        .chain(hex::decode(UX).unwrap())
+        .chain(hex::decode(UY).unwrap())
+        .collect()
+    }
+
+    fn sample() -> Vec<u8> {
+        input(
+            "sample",
+            "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716",
+            "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8",
+        )
+    }
+
+    #[test]
+    fn rfc6979_signatures() {
+        let sample = sample();
+        let test = input(
+            "test",
+            "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367",
+            "019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083",
+        );
+
+        let (gas, out) = p256_verify(&sample, P256VERIFY_BASE).unwrap();
+        assert_eq!(gas, P256VERIFY_BASE);
+        let mut one = vec![0; 32];
+        one[31] = 1;
+        assert_eq!(out, one);
+        assert!(verify(&test));
+
+        // Signature of another message.
+        let mut swapped = sample.clone();
+        swapped[..32].copy_from_slice(&test[..32]);
+        assert!(!verify(&swapped));
+        assert_eq!(
+            p256_verify(&swapped, P256VERIFY_BASE),
+            Ok((3_450, Vec::new()))
+        );
+
+        assert_eq!(p256_verify(&sample, 3_449), Err(Error::OutOfGas));
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        let sample = sample();
+        assert!(!verify(&sample[..159]));
+        assert!(!verify(&[&sample[..], &[0]].concat()));
+        assert_eq!(p256_verify(&[], P256VERIFY_BASE), Ok((3_450, Vec::new())));
+
+        let with = |range: core::ops::Range<usize>, word: &[u8; 32]| {
+            let mut input = sample.clone();
+            input[range].copy_from_slice(word);
+            input
+        };
+        let mut n_plus_one = N;
+        n_plus_one[31] += 1;
+        // r and s of zero or not less than the group order.
+        for word in [[0; 32], N, n_plus_one, [0xff; 32]] {
+            assert!(!verify(&with(32..64, &word)));
+            assert!(!verify(&with(64..96, &word)));
+        }
+
+        // Public key not on the curve.
+        let mut invalid = sample.clone();
+        invalid[159] ^= 1;
+        assert!(!verify(&invalid));
+        // Point at infinity, encoded as zeros.
+        let mut infinity = sample.clone();
+        infinity[96..].fill(0);
+        assert!(!verify(&infinity));
+        // Coordinates that are not field elements.
+        for word in [P, [0xff; 32]] {
+            assert!(!verify(&with(96..128, &word)));
+            assert!(!verify(&with(128..160, &word)));
+        }
+    }
+
+    /// Vectors of `ecdsa_secp256r1_sha256_test.json` of Wycheproof, as used by RIP-7212. Hashes
+    /// are the SHA-256 of the messages. Vectors with invalid DER encodings of the signature are
+    /// left out as the precompile takes `r` and `s` directly.
+    #[cfg(feature = "test-vectors")]
+    #[test]
+    fn wycheproof_vectors() {
+        use crate::harness::{parse_vectors, PrecompileHarness};
+
+        let vectors = parse_vectors(include_str!("secp256r1/wycheproof.json")).unwrap();
+        assert_eq!(vectors.len(), 218);
+        // Includes valid signatures with high s.
+        assert_eq!(
+            vectors.iter().filter(|v| !v.expected.is_empty()).count(),
+            147
+        );
+        let harness = PrecompileHarness::new(P256VERIFY.1);
+        assert_eq!(harness.run_vectors(&vectors, []), []);
+    }
+}
@@ -1,10 +1,17 @@
-use crate::{hex, keccak256, Bytes, B256, KECCAK_EMPTY};
+use crate::{
+    hex, keccak256,
+    opcode::{self, OpCode},
+    Bytes, Eof, B256, KECCAK_EMPTY,
+};
 use alloc::{sync::Arc, vec::Vec};
 use bitvec::{
     prelude::{bitvec, Lsb0},
     vec::BitVec,
 };
-use core::fmt::Debug;
+use core::{
+    fmt::{self, Debug},
+    ops::Range,
+};
 
 /// A map of valid `jump` destinations.
 #[derive(Clone, Default, PartialEq, Eq, Hash)]
@@ -37,6 +44,11 @@ impl JumpMap {
     pub fn is_valid(&self, pc: usize) -> bool {
         pc < self.0.len() && self.0[pc]
     }
+
+    /// Returns the valid jump destinations in ascending order.
+    pub fn jumpdests(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter_ones()
+    }
 }
 
 /// State of the [`Bytecode`] analysis.
@@ -49,6 +61,8 @@ pub enum BytecodeState {
     Checked { len: usize },
     /// The bytecode has been analyzed for valid jump destinations.
     Analysed { len: usize, jump_map: JumpMap },
+    /// The bytecode is a decoded and validated EOF container.
+    Eof(Arc<Eof>),
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -105,6 +119,15 @@ impl Bytecode {
         }
     }
 
+    /// Creates a new EOF [`Bytecode`] from a validated container.
+    #[inline]
+    pub fn new_eof(eof: Eof) -> Self {
+        Self {
+            bytecode: eof.raw.clone(),
+            state: BytecodeState::Eof(Arc::new(eof)),
+        }
+    }
+
     /// Create new checked bytecode
     ///
     /// # Safety
@@ -128,7 +151,7 @@ impl Bytecode {
     #[inline]
     pub fn original_bytes(&self) -> Bytes {
         match self.state {
-            BytecodeState::Raw => self.bytecode.clone(),
+            BytecodeState::Raw | BytecodeState::Eof(_) => self.bytecode.clone(),
             BytecodeState::Checked { len } | BytecodeState::Analysed { len, .. } => {
                 self.bytecode.slice(0..len)
             }
@@ -139,7 +162,7 @@ impl Bytecode {
     #[inline]
     pub fn len(&self) -> usize {
         match self.state {
-            BytecodeState::Raw => self.bytecode.len(),
+            BytecodeState::Raw | BytecodeState::Eof(_) => self.bytecode.len(),
             BytecodeState::Checked { len, .. } | BytecodeState::Analysed { len, .. } => len,
         }
     }
@@ -156,6 +179,81 @@ impl Bytecode {
         &self.state
     }
 
+    /// Returns the EOF container if the bytecode is EOF.
+    #[inline]
+    pub fn eof(&self) -> Option<&Arc<Eof>> {
+        match &self.state {
+            BytecodeState::Eof(eof) => Some(eof),
+            _ => None,
+        }
+    }
+
+    /// Returns the map of valid jump destinations if the bytecode is analysed.
+    #[inline]
+    pub fn jump_map(&self) -> Option<&JumpMap> {
+        match &self.state {
+            BytecodeState::Analysed { jump_map, .. } => Some(jump_map),
+            _ => None,
+        }
+    }
+
+    /// Returns the valid jump destinations in ascending order, from the jump map if the bytecode
+    /// is analysed or by iterating the opcodes otherwise. EOF bytecode has none.
+    pub fn jumpdests(&self) -> Vec<usize> {
+        if self.eof().is_some() {
+            return Vec::new();
+        }
+        match self.jump_map() {
+            Some(jump_map) => jump_map.jumpdests().filter(|&pc| pc < self.len()).collect(),
+            None => self
+                .iter_opcodes()
+                .filter(|(_, opcode, _)| opcode.get() == opcode::JUMPDEST)
+                .map(|(pc, _, _)| pc)
+                .collect(),
+        }
+    }
+
+    /// Returns whether `pc` is a valid jump destination.
+    #[inline]
+    pub fn is_valid_jump(&self, pc: usize) -> bool {
+        match self.jump_map() {
+            Some(jump_map) => pc < self.len() && jump_map.is_valid(pc),
+            None => self.jumpdests().binary_search(&pc).is_ok(),
+        }
+    }
+
+    /// Returns an iterator over the opcodes with their program counters and immediate bytes.
+    ///
+    /// Legacy bytecode is iterated from the start, the immediates are the bytes pushed by
+    /// `PUSH` and are cut at the end of the code. EOF bytecode is iterated over its code
+    /// sections, program counters are offsets in the container.
+    ///
+    /// Bytes that are not defined opcodes, e.g. metadata appended by compilers, are returned as
+    /// [`opcode::INVALID`].
+    #[inline]
+    pub fn iter_opcodes(&self) -> OpcodeIter<'_> {
+        match self.eof() {
+            Some(eof) => OpcodeIter {
+                code: &eof.raw,
+                current: 0..0,
+                sections: &eof.code_sections,
+                is_eof: true,
+            },
+            None => OpcodeIter {
+                code: &self.bytecode[..self.len()],
+                current: 0..self.len(),
+                sections: &[],
+                is_eof: false,
+            },
+        }
+    }
+
+    /// Returns the disassembly of the bytecode, one opcode per line, e.g. `0002: PUSH1 0x80`.
+    #[inline]
+    pub fn disassemble(&self) -> Disassembly<'_> {
+        Disassembly(self)
+    }
+
     pub fn to_checked(self) -> Self {
         match self.state {
             BytecodeState::Raw => {
@@ -172,3 +270,267 @@ impl Bytecode {
         }
     }
 }
+
+/// Iterator over the opcodes of [`Bytecode`], see [`Bytecode::iter_opcodes`].
+///
+/// Items are the program counter, the opcode and its immediate bytes.
+#[derive(Clone, Debug)]
+pub struct OpcodeIter<'a> {
+    code: &'a [u8],
+    current: Range<usize>,
+    sections: &'a [Range<usize>],
+    is_eof: bool,
+}
+
+impl<'a> Iterator for OpcodeIter<'a> {
+    type Item = (usize, OpCode, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current.is_empty() {
+            let (section, rest) = self.sections.split_first()?;
+            self.current = section.clone();
+            self.sections = rest;
+        }
+        let pc = self.current.start;
+        let byte = self.code[pc];
+        let immediate_size = match byte {
+            opcode::PUSH1..=opcode::PUSH32 => (byte - opcode::PUSH0) as usize,
+            opcode::RJUMP | opcode::RJUMPI | opcode::CALLF if self.is_eof => 2,
+            opcode::RJUMPV if self.is_eof => self
+                .code
+                .get(pc + 1)
+                .filter(|_| pc + 1 < self.current.end)
+                .map_or(1, |&max_index| 1 + (max_index as usize + 1) * 2),
+            _ => 0,
+        };
+        let next = (pc + 1 + immediate_size).min(self.current.end);
+        self.current.start = next;
+        let opcode = OpCode::new(byte)
+            .unwrap_or_else(|| OpCode::new(opcode::INVALID).expect("INVALID is defined"));
+        Some((pc, opcode, &self.code[pc + 1..next]))
+    }
+}
+
+/// Disassembly of [`Bytecode`], see [`Bytecode::disassemble`].
+#[derive(Clone, Copy, Debug)]
+pub struct Disassembly<'a>(&'a Bytecode);
+
+impl fmt::Display for Disassembly<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let iter = self.0.iter_opcodes();
+        let code = iter.code;
+        for (pc, opcode, immediate) in iter {
+            // Undefined bytes are printed as they are instead of `INVALID`.
+            if opcode.get() == code[pc] {
+                write!(f, "{pc:04x}: {opcode}")?;
+            } else {
+                write!(f, "{pc:04x}: UNKNOWN(0x{:02X})", code[pc])?;
+            }
+            if !immediate.is_empty() {
+                write!(f, " 0x{}", hex::encode(immediate))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Version of the bytecode analysis format.
+///
+/// It needs to be bumped when layout of the jump map or padding of analysed bytecode changes,
+/// so that persisted analysis artifacts get invalidated.
+pub const BYTECODE_ANALYSIS_VERSION: u8 = 1;
+
+/// [`Bytecode`] together with the version of the analysis format it was created with.
+///
+/// Used to persist analysed bytecode (padded code and jump map) so that it does not need
+/// to be analysed again after reloading.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionedBytecode {
+    /// Analysis format version.
+    pub version: u8,
+    /// Bytecode with analysis artifacts.
+    pub bytecode: Bytecode,
+}
+
+impl From<Bytecode> for VersionedBytecode {
+    #[inline]
+    fn from(bytecode: Bytecode) -> Self {
+        Self {
+            version: BYTECODE_ANALYSIS_VERSION,
+            bytecode,
+        }
+    }
+}
+
+impl VersionedBytecode {
+    /// Returns the bytecode.
+    ///
+    /// If the analysis format version does not match [`BYTECODE_ANALYSIS_VERSION`], or the
+    /// analysis does not fit the layout of the current version, analysis is dropped and raw
+    /// bytecode is returned.
+    pub fn into_bytecode(self) -> Bytecode {
+        if self.version == BYTECODE_ANALYSIS_VERSION && self.has_valid_layout() {
+            return self.bytecode;
+        }
+        let len = self.bytecode.len().min(self.bytecode.bytecode.len());
+        Bytecode::new_raw(self.bytecode.bytecode.slice(..len))
+    }
+
+    /// Returns whether the analysed bytecode is padded with zeros after its length and the jump
+    /// map covers the padded bytecode, as the interpreter relies on both.
+    fn has_valid_layout(&self) -> bool {
+        let Bytecode { bytecode, state } = &self.bytecode;
+        let padded =
+            |len: usize| bytecode.len() > len && bytecode[len..].iter().all(|&byte| byte == 0);
+        match state {
+            BytecodeState::Raw | BytecodeState::Eof(_) => true,
+            BytecodeState::Checked { len } => padded(*len),
+            BytecodeState::Analysed { len, jump_map } => {
+                padded(*len) && jump_map.0.len() == bytecode.len()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versioned_bytecode_invalidates_analysis() {
+        let bytecode = Bytecode {
+            bytecode: Bytes::from_static(&[0x5b, 0, 0, 0, 0, 0, 0, 0]),
+            state: BytecodeState::Analysed {
+                len: 1,
+                jump_map: JumpMap::from_slice(&[1]),
+            },
+        };
+
+        let versioned = VersionedBytecode::from(bytecode.clone());
+        assert_eq!(versioned.clone().into_bytecode(), bytecode);
+
+        let outdated = VersionedBytecode {
+            version: BYTECODE_ANALYSIS_VERSION.wrapping_add(1),
+            ..versioned
+        };
+        let raw = outdated.into_bytecode();
+        assert_eq!(raw.state, BytecodeState::Raw);
+        assert_eq!(raw.bytes().as_ref(), &[0x5b]);
+    }
+
+    #[test]
+    fn versioned_bytecode_rejects_invalid_layout() {
+        let analysed = |bytecode: &'static [u8], len, jump_map: &[u8]| VersionedBytecode {
+            version: BYTECODE_ANALYSIS_VERSION,
+            bytecode: Bytecode {
+                bytecode: Bytes::from_static(bytecode),
+                state: BytecodeState::Analysed {
+                    len,
+                    jump_map: JumpMap::from_slice(jump_map),
+                },
+            },
+        };
+
+        // Missing padding.
+        let raw = analysed(&[0x5b, 0x5b], 2, &[0b11]).into_bytecode();
+        assert_eq!(raw.state, BytecodeState::Raw);
+        assert_eq!(raw.bytes().as_ref(), &[0x5b, 0x5b]);
+        // Length past the end of the bytecode.
+        let raw = analysed(&[0x5b, 0x00], 4, &[0b01]).into_bytecode();
+        assert_eq!(raw.state, BytecodeState::Raw);
+        assert_eq!(raw.bytes().as_ref(), &[0x5b, 0x00]);
+        // Jump map of different length than the bytecode.
+        let raw = analysed(&[0x5b, 0, 0, 0, 0, 0, 0, 0, 0], 1, &[0b1]).into_bytecode();
+        assert_eq!(raw.state, BytecodeState::Raw);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let raw = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x80, 0x5b]));
+        let json = crate::utilities::assert_serde_round_trip(&raw);
+        assert_eq!(json["bytecode"], "0x60805b");
+
+        let analysed = Bytecode {
+            bytecode: Bytes::from_static(&[0x5b, 0x00]),
+            state: BytecodeState::Analysed {
+                len: 1,
+                jump_map: JumpMap::from_slice(&[1]),
+            },
+        };
+        crate::utilities::assert_serde_round_trip(&analysed);
+        crate::utilities::assert_serde_round_trip(&VersionedBytecode::from(analysed));
+
+        let eof = Eof::decode(Bytes::from(hex!(
+            "ef000101000402000100040300000000000000e0000000"
+        )))
+        .unwrap();
+        crate::utilities::assert_serde_round_trip(&Bytecode::new_eof(eof));
+    }
+
+    #[test]
+    fn iter_opcodes_legacy() {
+        // PUSH1 0x80, JUMPDEST, PUSH2 0x0102, undefined 0x0c, PUSH2 cut at the end.
+        let bytecode = Bytecode::new_raw(Bytes::from(hex!("60805b6101020c61ff")));
+        let opcodes: Vec<_> = bytecode
+            .iter_opcodes()
+            .map(|(pc, opcode, immediate)| (pc, opcode.get(), immediate.to_vec()))
+            .collect();
+        assert_eq!(
+            opcodes,
+            vec![
+                (0, opcode::PUSH1, vec![0x80]),
+                (2, opcode::JUMPDEST, vec![]),
+                (3, opcode::PUSH2, vec![0x01, 0x02]),
+                (6, opcode::INVALID, vec![]),
+                (7, opcode::PUSH2, vec![0xff]),
+            ]
+        );
+        assert_eq!(bytecode.jumpdests(), vec![2]);
+        assert!(bytecode.is_valid_jump(2));
+        assert!(!bytecode.is_valid_jump(1));
+        assert!(bytecode.jump_map().is_none());
+
+        assert_eq!(
+            bytecode.disassemble().to_string(),
+            "0000: PUSH1 0x80\n\
+             0002: JUMPDEST\n\
+             0003: PUSH2 0x0102\n\
+             0006: UNKNOWN(0x0C)\n\
+             0007: PUSH2 0xff\n"
+        );
+    }
+
+    #[test]
+    fn jumpdests_of_analysed_bytecode() {
+        // JUMPDEST, PUSH1 0x5b, JUMPDEST followed by the padding of the analysis.
+        let bytecode = Bytecode {
+            bytecode: Bytes::from_static(&[0x5b, 0x60, 0x5b, 0x5b, 0x00]),
+            state: BytecodeState::Analysed {
+                len: 4,
+                jump_map: JumpMap::from_slice(&[0b0000_1001]),
+            },
+        };
+        assert_eq!(bytecode.jumpdests(), vec![0, 3]);
+        assert!(!bytecode.is_valid_jump(2));
+        assert_eq!(bytecode.iter_opcodes().count(), 3);
+    }
+
+    #[test]
+    fn iter_opcodes_eof() {
+        // one code section with `RJUMP 0x0000`, `STOP`.
+        let raw = Bytes::from(hex!("ef000101000402000100040300000000000000e0000000"));
+        let bytecode = Bytecode::new_eof(Eof::decode(raw).unwrap());
+        let opcodes: Vec<_> = bytecode
+            .iter_opcodes()
+            .map(|(pc, opcode, immediate)| (pc, opcode.get(), immediate.to_vec()))
+            .collect();
+        assert_eq!(
+            opcodes,
+            vec![(19, opcode::RJUMP, vec![0, 0]), (22, opcode::STOP, vec![])]
+        );
+        assert!(bytecode.jumpdests().is_empty());
+    }
+}
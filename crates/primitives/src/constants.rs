@@ -1,4 +1,4 @@
-use crate::Address;
+use crate::{address, Address};
 
 /// EIP-170: Contract code size limit
 /// By default limit is 0x6000 (~25kb)
@@ -32,3 +32,15 @@ pub const MIN_BLOB_GASPRICE: u64 = 1;
 pub const BLOB_GASPRICE_UPDATE_FRACTION: u64 = 3338477;
 /// First version of the blob.
 pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// Address that is used as the caller of block level system calls.
+pub const SYSTEM_ADDRESS: Address = address!("fffffffffffffffffffffffffffffffffffffffe");
+/// Gas limit of block level system calls.
+pub const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+/// EIP-4788: Beacon block root in the EVM, address of the beacon roots contract.
+pub const BEACON_ROOTS_ADDRESS: Address = address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+/// EIP-2935: Serve historical block hashes from state, address of the history storage contract.
+pub const HISTORY_STORAGE_ADDRESS: Address = address!("0000F90827F1C53a10cb7A02335B175320002935");
+/// EIP-2935: Number of block hashes kept in the history storage contract, it is a ring buffer
+/// indexed by `number % HISTORY_SERVE_WINDOW`.
+pub const HISTORY_SERVE_WINDOW: usize = 8191;
@@ -1,6 +1,7 @@
 use crate::{
-    alloc::vec::Vec, calc_blob_gasprice, Account, Address, Bytes, InvalidHeader,
-    InvalidTransaction, Spec, SpecId, B256, GAS_PER_BLOB, KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK,
+    alloc::vec::Vec, calc_blob_gasprice, calc_excess_blob_gas, Account, Address, Bytes,
+    InvalidHeader, InvalidTransaction, Network, PrecompileGasOverrides, PrecompileInputLimits,
+    Spec, SpecId, B256, GAS_PER_BLOB, KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK, MAX_CODE_SIZE,
     MAX_INITCODE_SIZE, U256, VERSIONED_HASH_VERSION_KZG,
 };
 use alloc::boxed::Box;
@@ -19,6 +20,21 @@ pub struct Env {
 }
 
 impl Env {
+    /// Returns a builder of the environment, see [`EnvBuilder`].
+    #[inline]
+    pub fn builder() -> EnvBuilder {
+        EnvBuilder::default()
+    }
+
+    /// Returns the environment of a known chain at its latest spec, or `None` if the chain id is
+    /// not a known [`Network`].
+    pub fn for_chain(chain_id: u64) -> Option<Self> {
+        Network::from_chain_id(chain_id).map(|network| {
+            let spec_id = network.latest_spec_id();
+            Self::builder().network(network).spec_id(spec_id).build()
+        })
+    }
+
     /// Calculates the effective gas price of the transaction.
     #[inline]
     pub fn effective_gas_price(&self) -> U256 {
@@ -29,6 +45,22 @@ impl Env {
         }
     }
 
+    /// Returns true if [EIP-4844] blob transactions are enabled, they are enabled from Cancun
+    /// unless disabled with [`CfgEnv::disable_blobs`].
+    ///
+    /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
+    #[inline]
+    pub fn is_blob_enabled<SPEC: Spec>(&self) -> bool {
+        self.is_blob_enabled_with_spec_id(SPEC::SPEC_ID)
+    }
+
+    /// Returns true if blob transactions are enabled in the given spec, see
+    /// [`Env::is_blob_enabled`].
+    #[inline]
+    pub fn is_blob_enabled_with_spec_id(&self, spec_id: SpecId) -> bool {
+        SpecId::enabled(spec_id, SpecId::CANCUN) && !self.cfg.disable_blobs
+    }
+
     /// Calculates the [EIP-4844] `data_fee` of the transaction.
     ///
     /// Returns `None` if `Cancun` is not enabled. This is enforced in [`Env::validate_block_env`].
@@ -41,30 +73,76 @@ impl Env {
         })
     }
 
+    /// Clamps transaction gas limit to [`CfgEnv::tx_gas_cap`].
+    ///
+    /// Returns gas limit requested by the transaction if it was clamped.
+    #[inline]
+    pub fn clamp_tx_gas_limit(&mut self) -> Option<u64> {
+        let cap = self.cfg.tx_gas_cap?;
+        if self.tx.gas_limit <= cap {
+            return None;
+        }
+        Some(core::mem::replace(&mut self.tx.gas_limit, cap))
+    }
+
     /// Validate the block environment.
     #[inline]
     pub fn validate_block_env<SPEC: Spec>(&self) -> Result<(), InvalidHeader> {
+        self.validate_block_env_with_spec_id(SPEC::SPEC_ID)
+    }
+
+    /// Validate the block environment like [`Env::validate_block_env`] with the spec given at
+    /// runtime.
+    pub fn validate_block_env_with_spec_id(&self, spec_id: SpecId) -> Result<(), InvalidHeader> {
         // `prevrandao` is required for the merge
-        if SPEC::enabled(SpecId::MERGE) && self.block.prevrandao.is_none() {
+        if SpecId::enabled(spec_id, SpecId::MERGE) && self.block.prevrandao.is_none() {
             return Err(InvalidHeader::PrevrandaoNotSet);
         }
         // `excess_blob_gas` is required for Cancun
-        if SPEC::enabled(SpecId::CANCUN) && self.block.blob_excess_gas_and_price.is_none() {
+        if self.is_blob_enabled_with_spec_id(spec_id)
+            && self.block.blob_excess_gas_and_price.is_none()
+        {
             return Err(InvalidHeader::ExcessBlobGasNotSet);
         }
+        self.block_gas_limit()?;
         Ok(())
     }
 
+    /// Returns the block gas limit that transactions are checked against, or `None` if it is
+    /// not checked.
+    ///
+    /// A limit that doesn't fit in `u64` is handled as set in
+    /// [`CfgEnv::block_gas_limit_overflow`].
+    #[inline]
+    pub fn block_gas_limit(&self) -> Result<Option<u64>, InvalidHeader> {
+        if self.cfg.is_block_gas_limit_disabled() {
+            return Ok(None);
+        }
+        match u64::try_from(self.block.gas_limit) {
+            Ok(gas_limit) => Ok(Some(gas_limit)),
+            Err(_) => match self.cfg.block_gas_limit_overflow {
+                GasLimitOverflow::Saturate => Ok(Some(u64::MAX)),
+                GasLimitOverflow::Error => Err(InvalidHeader::GasLimitOverflow),
+                GasLimitOverflow::Unlimited => Ok(None),
+            },
+        }
+    }
+
     /// Validate transaction data that is set inside ENV and return error if something is wrong.
     ///
     /// Return initial spend gas (Gas needed to execute transaction).
     #[inline]
     pub fn validate_tx<SPEC: Spec>(&self) -> Result<(), InvalidTransaction> {
+        self.validate_tx_with_spec_id(SPEC::SPEC_ID)
+    }
+
+    /// Validate the transaction like [`Env::validate_tx`] with the spec given at runtime.
+    pub fn validate_tx_with_spec_id(&self, spec_id: SpecId) -> Result<(), InvalidTransaction> {
         #[cfg(feature = "optimism")]
         if self.cfg.optimism {
             // Do not allow for a system transaction to be processed if Regolith is enabled.
             if self.tx.optimism.is_system_transaction.unwrap_or(false)
-                && SPEC::enabled(SpecId::REGOLITH)
+                && SpecId::enabled(spec_id, SpecId::REGOLITH)
             {
                 return Err(InvalidTransaction::DepositSystemTxPostRegolith);
             }
@@ -80,7 +158,7 @@ impl Env {
         let is_create = self.tx.transact_to.is_create();
 
         // BASEFEE tx check
-        if SPEC::enabled(SpecId::LONDON) {
+        if SpecId::enabled(spec_id, SpecId::LONDON) {
             if let Some(priority_fee) = self.tx.gas_priority_fee {
                 if priority_fee > self.tx.gas_price {
                     // or gas_max_fee for eip1559
@@ -95,20 +173,20 @@ impl Env {
             }
         }
 
-        // Check if gas_limit is more than block_gas_limit
-        if !self.cfg.is_block_gas_limit_disabled() && U256::from(gas_limit) > self.block.gas_limit {
-            return Err(InvalidTransaction::CallerGasLimitMoreThanBlock);
+        // Check if gas_limit is more than block_gas_limit.
+        // Overflowing block gas limit is rejected by `validate_block_env`.
+        if let Ok(Some(block_gas_limit)) = self.block_gas_limit() {
+            if gas_limit > block_gas_limit {
+                return Err(InvalidTransaction::CallerGasLimitMoreThanBlock);
+            }
         }
 
         // EIP-3860: Limit and meter initcode
-        if SPEC::enabled(SpecId::SHANGHAI) && is_create {
-            let max_initcode_size = self
-                .cfg
-                .limit_contract_code_size
-                .map(|limit| limit.saturating_mul(2))
-                .unwrap_or(MAX_INITCODE_SIZE);
-            if self.tx.data.len() > max_initcode_size {
-                return Err(InvalidTransaction::CreateInitcodeSizeLimit);
+        if SpecId::enabled(spec_id, SpecId::SHANGHAI) && is_create {
+            if let Some(max_initcode_size) = self.cfg.max_initcode_size() {
+                if self.tx.data.len() > max_initcode_size {
+                    return Err(InvalidTransaction::CreateInitcodeSizeLimit);
+                }
             }
         }
 
@@ -120,13 +198,14 @@ impl Env {
         }
 
         // Check that access list is empty for transactions before BERLIN
-        if !SPEC::enabled(SpecId::BERLIN) && !self.tx.access_list.is_empty() {
+        if !SpecId::enabled(spec_id, SpecId::BERLIN) && !self.tx.access_list.is_empty() {
             return Err(InvalidTransaction::AccessListNotSupported);
         }
 
         // - For CANCUN and later, check that the gas price is not more than the tx max
-        // - For before CANCUN, check that `blob_hashes` and `max_fee_per_blob_gas` are empty / not set
-        if SPEC::enabled(SpecId::CANCUN) {
+        // - For before CANCUN or disabled blobs, check that `blob_hashes` and
+        //   `max_fee_per_blob_gas` are empty / not set
+        if self.is_blob_enabled_with_spec_id(spec_id) {
             // Presence of max_fee_per_blob_gas means that this is blob transaction.
             if let Some(max) = self.tx.max_fee_per_blob_gas {
                 // ensure that the user was willing to at least pay the current blob gasprice
@@ -161,6 +240,9 @@ impl Env {
                 if self.tx.blob_hashes.len() > MAX_BLOB_NUMBER_PER_BLOCK as usize {
                     return Err(InvalidTransaction::TooManyBlobs);
                 }
+            } else if !self.tx.blob_hashes.is_empty() {
+                // only blob transactions can have versioned hashes
+                return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
             }
         } else {
             if !self.tx.blob_hashes.is_empty() {
@@ -213,7 +295,7 @@ impl Env {
             .and_then(|gas_cost| gas_cost.checked_add(self.tx.value))
             .ok_or(InvalidTransaction::OverflowPaymentInTransaction)?;
 
-        if SpecId::enabled(self.cfg.spec_id, SpecId::CANCUN) {
+        if SpecId::enabled(self.cfg.spec_id, SpecId::CANCUN) && !self.cfg.disable_blobs {
             let data_fee = self.calc_data_fee().expect("already checked");
             balance_check = balance_check
                 .checked_add(U256::from(data_fee))
@@ -238,6 +320,138 @@ impl Env {
     }
 }
 
+/// Builder of [`Env`] starting from the default environment.
+///
+/// If a [`Network`] is set, the chain id is taken from it and the spec is selected by the block
+/// number and timestamp unless set explicitly.
+///
+/// ```
+/// use revm_primitives::{Env, SpecId, U256};
+///
+/// let env = Env::builder()
+///     .mainnet()
+///     .block_number(17_034_870)
+///     .timestamp(1_681_338_455)
+///     .basefee(U256::from(7))
+///     .build();
+/// assert_eq!(env.cfg.chain_id, 1);
+/// assert_eq!(env.cfg.spec_id, SpecId::SHANGHAI);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EnvBuilder {
+    env: Env,
+    network: Option<Network>,
+    spec_id: Option<SpecId>,
+}
+
+impl EnvBuilder {
+    /// Sets the network, see [`Network`].
+    pub fn network(mut self, network: Network) -> Self {
+        self.env.cfg.chain_id = network.chain_id;
+        self.network = Some(network);
+        self
+    }
+
+    /// Sets the network to Ethereum mainnet.
+    pub fn mainnet(self) -> Self {
+        self.network(Network::MAINNET)
+    }
+
+    /// Sets the network to the Sepolia testnet.
+    pub fn sepolia(self) -> Self {
+        self.network(Network::SEPOLIA)
+    }
+
+    /// Sets the network to the Holesky testnet.
+    pub fn holesky(self) -> Self {
+        self.network(Network::HOLESKY)
+    }
+
+    /// Sets the chain id, overriding the one of the network.
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.env.cfg.chain_id = chain_id;
+        self
+    }
+
+    /// Sets the spec, overriding the one selected from the network.
+    pub fn spec_id(mut self, spec_id: SpecId) -> Self {
+        self.spec_id = Some(spec_id);
+        self
+    }
+
+    /// Sets the block number.
+    pub fn block_number(mut self, number: u64) -> Self {
+        self.env.block.number = U256::from(number);
+        self
+    }
+
+    /// Sets the block timestamp.
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.env.block.timestamp = U256::from(timestamp);
+        self
+    }
+
+    /// Sets the block coinbase.
+    pub fn coinbase(mut self, coinbase: Address) -> Self {
+        self.env.block.coinbase = coinbase;
+        self
+    }
+
+    /// Sets the block gas limit.
+    pub fn block_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.env.block.gas_limit = U256::from(gas_limit);
+        self
+    }
+
+    /// Sets the block base fee.
+    pub fn basefee(mut self, basefee: U256) -> Self {
+        self.env.block.basefee = basefee;
+        self
+    }
+
+    /// Sets the block prevrandao.
+    pub fn prevrandao(mut self, prevrandao: B256) -> Self {
+        self.env.block.prevrandao = Some(prevrandao);
+        self
+    }
+
+    /// Sets the excess blob gas of the block, see [`BlockEnv::set_blob_excess_gas_and_price`].
+    pub fn excess_blob_gas(mut self, excess_blob_gas: u64) -> Self {
+        self.env
+            .block
+            .set_blob_excess_gas_and_price(excess_blob_gas);
+        self
+    }
+
+    /// Sets the block environment.
+    pub fn block(mut self, block: BlockEnv) -> Self {
+        self.env.block = block;
+        self
+    }
+
+    /// Sets the transaction environment.
+    pub fn tx(mut self, tx: TxEnv) -> Self {
+        self.env.tx = tx;
+        self
+    }
+
+    /// Modifies the configuration, e.g. to disable checks.
+    pub fn cfg(mut self, f: impl FnOnce(&mut CfgEnv)) -> Self {
+        f(&mut self.env.cfg);
+        self
+    }
+
+    /// Builds the environment.
+    pub fn build(mut self) -> Env {
+        if let Some(spec_id) = self.spec_id {
+            self.env.cfg.spec_id = spec_id;
+        } else if let Some(network) = &self.network {
+            self.env.cfg.spec_id = network.schedule.spec_id_for_block(&self.env.block);
+        }
+        self.env
+    }
+}
+
 /// EVM configuration.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -246,7 +460,8 @@ pub struct CfgEnv {
     pub chain_id: u64,
     pub spec_id: SpecId,
     /// KZG Settings for point evaluation precompile. By default, this is loaded from the ethereum mainnet trusted setup.
-    #[cfg(feature = "c-kzg")]
+    ///
+    /// Set [crate::kzg::EnvKzgSettings::Verifier] to use a backend other than `c-kzg`.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub kzg_settings: crate::kzg::EnvKzgSettings,
     /// Bytecode that is created with CREATE/CREATE2 is by default analysed and jumptable is created.
@@ -257,13 +472,91 @@ pub struct CfgEnv {
     /// If some it will effects EIP-170: Contract code size limit. Useful to increase this because of tests.
     /// By default it is 0x6000 (~25kb).
     pub limit_contract_code_size: Option<usize>,
-    /// A hard memory limit in bytes beyond which [crate::result::OutOfGasError::Memory] cannot be resized.
+    /// If some it overrides EIP-3860: Limit and meter initcode.
+    /// By default it is twice the contract code size limit.
+    pub limit_initcode_size: Option<usize>,
+    /// If some, transactions requesting more gas than the cap are clamped to it instead of being
+    /// rejected, similar to the gas cap RPC providers apply to `eth_call`. Requested gas limit is
+    /// reported in [crate::ResultAndState::requested_gas_limit].
+    ///
+    /// By default it is not set.
+    pub tx_gas_cap: Option<u64>,
+    /// How a block gas limit that doesn't fit in `u64` is handled. Transaction and frame gas
+    /// is `u64`, so the block gas limit is the only one that can overflow it.
+    ///
+    /// Ignored if the block gas limit is disabled. By default, it is saturated to `u64::MAX`.
+    pub block_gas_limit_overflow: GasLimitOverflow,
+    /// Maximum input sizes of expensive precompiles. Calls with larger inputs halt with
+    /// [crate::Halt::PrecompileInputTooLarge].
+    ///
+    /// By default, no limits are set.
+    pub precompile_input_limits: PrecompileInputLimits,
+    /// Gas costs that replace the costs computed by the precompiles at the given addresses.
+    /// Precompiles with an override run without a gas limit and are charged the override.
+    ///
+    /// By default, no costs are overridden.
+    pub precompile_gas_overrides: PrecompileGasOverrides,
+    /// Opcodes that halt the execution like undefined opcodes, for chains that restrict them.
+    /// Applied when the instruction table is built.
+    ///
+    /// By default, no opcodes are disabled.
+    pub disabled_opcodes: OpcodeSet,
+    /// Disables [EIP-4844] blob transactions for chains that don't support them. Blob
+    /// transactions are rejected as before Cancun and `excess_blob_gas` is not required.
+    ///
+    /// By default it is false.
+    ///
+    /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
+    pub disable_blobs: bool,
+    /// Disables gas metering of the instructions, for testing frameworks and pre-runs of zk
+    /// provers where gas is irrelevant. Instructions run from a separate instruction table and
+    /// don't consume gas, gas forwarded to calls and creates is returned when they finish.
+    ///
+    /// Costs of single instructions are still checked against the remaining gas, including the
+    /// expansion cost of the whole memory, so memory stays bounded by the gas limit.
+    ///
+    /// By default it is false.
+    pub disable_gas_metering: bool,
+    /// A hard limit in bytes of the memory shared by all frames of the transaction, resizing
+    /// beyond it halts with [crate::result::OutOfGasError::MemoryLimit].
     ///
     /// In cases where the gas limit may be extraordinarily high, it is recommended to set this to
     /// a sane value to prevent memory allocation panics. Defaults to `2^32 - 1` bytes per
     /// EIP-1985.
-    #[cfg(feature = "memory_limit")]
     pub memory_limit: u64,
+    /// Addresses and storage slots that are warm when the transaction starts, in addition to
+    /// the coinbase, the precompiles and the access list of the transaction.
+    ///
+    /// They are loaded like the access list but are not charged intrinsic gas, so a
+    /// simulation of a bundle can start a transaction with what the previous ones warmed.
+    ///
+    /// By default, it is empty.
+    pub warm_preloaded: Vec<(Address, Vec<U256>)>,
+    /// Keeps touched empty accounts, for chains that never adopted the state clearing of
+    /// [EIP-161]. Empty accounts exist once they are touched, like before Spurious Dragon.
+    ///
+    /// Only the existence of empty accounts changes, nonces of created contracts and the gas of
+    /// calls still follow the spec. See [CfgEnv::is_state_clear_enabled].
+    ///
+    /// By default it is false.
+    ///
+    /// [EIP-161]: https://eips.ethereum.org/EIPS/eip-161
+    pub disable_state_clear: bool,
+    /// Serves BLOCKHASH from the storage of the [EIP-2935] history storage contract instead of
+    /// [crate::db::Database::block_hash], as proposed by earlier drafts of the EIP. The contract
+    /// is filled by the pre-block system call, see `EVM::apply_pre_block_system_calls` of revm.
+    ///
+    /// The range of served blocks is unchanged, only the source of the hashes.
+    ///
+    /// By default it is false.
+    ///
+    /// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+    pub blockhash_from_history_storage: bool,
+    /// If some, stack and memory of the transaction's frame are captured into
+    /// [crate::ResultAndState::halt_snapshot] when the execution halts.
+    /// By default, it is not set.
+    #[cfg(feature = "halt_snapshot")]
+    pub halt_snapshot: Option<HaltSnapshotLimits>,
     /// Skip balance checks if true. Adds transaction cost to balance to ensure execution doesn't fail.
     #[cfg(feature = "optional_balance_check")]
     pub disable_balance_check: bool,
@@ -291,6 +584,11 @@ pub struct CfgEnv {
     /// By default, it is set to `false`.
     #[cfg(feature = "optional_beneficiary_reward")]
     pub disable_beneficiary_reward: bool,
+    /// Disables EIP-170 contract code size limit and EIP-3860 initcode size limit, so oversized
+    /// contracts can be deployed. Initcode is still metered.
+    /// By default, it is set to `false`.
+    #[cfg(feature = "optional_code_size_limit")]
+    pub disable_code_size_limit: bool,
     /// Enables Optimism's execution changes for deposit transactions and fee
     /// collection. Hot toggling the optimism field gives applications built
     /// on revm the ability to switch optimism execution on and off at runtime,
@@ -362,6 +660,57 @@ impl CfgEnv {
         false
     }
 
+    #[cfg(feature = "optional_code_size_limit")]
+    pub fn is_code_size_limit_disabled(&self) -> bool {
+        self.disable_code_size_limit
+    }
+
+    #[cfg(not(feature = "optional_code_size_limit"))]
+    pub fn is_code_size_limit_disabled(&self) -> bool {
+        false
+    }
+
+    /// Returns true if touched empty accounts are removed from the state, as of Spurious Dragon
+    /// unless [CfgEnv::disable_state_clear] is set.
+    ///
+    /// Pass it to `State::set_state_clear_flag` of revm when the block is committed to the
+    /// `State` database.
+    pub fn is_state_clear_enabled(&self) -> bool {
+        SpecId::enabled(self.spec_id, SpecId::SPURIOUS_DRAGON) && !self.disable_state_clear
+    }
+
+    /// Returns EIP-170 contract code size limit, `None` if it is disabled.
+    pub fn max_code_size(&self) -> Option<usize> {
+        if self.is_code_size_limit_disabled() {
+            return None;
+        }
+        Some(self.limit_contract_code_size.unwrap_or(MAX_CODE_SIZE))
+    }
+
+    /// Returns EIP-3860 initcode size limit, `None` if it is disabled.
+    pub fn max_initcode_size(&self) -> Option<usize> {
+        if self.is_code_size_limit_disabled() {
+            return None;
+        }
+        Some(
+            self.limit_initcode_size
+                .or(self
+                    .limit_contract_code_size
+                    .map(|limit| limit.saturating_mul(2)))
+                .unwrap_or(MAX_INITCODE_SIZE),
+        )
+    }
+
+    #[cfg(feature = "halt_snapshot")]
+    pub fn halt_snapshot_limits(&self) -> Option<HaltSnapshotLimits> {
+        self.halt_snapshot
+    }
+
+    #[cfg(not(feature = "halt_snapshot"))]
+    pub fn halt_snapshot_limits(&self) -> Option<HaltSnapshotLimits> {
+        None
+    }
+
     #[cfg(feature = "optimism")]
     pub fn is_optimism(&self) -> bool {
         self.optimism
@@ -380,10 +729,21 @@ impl Default for CfgEnv {
             spec_id: SpecId::LATEST,
             perf_analyse_created_bytecodes: AnalysisKind::default(),
             limit_contract_code_size: None,
-            #[cfg(feature = "c-kzg")]
+            limit_initcode_size: None,
+            tx_gas_cap: None,
+            block_gas_limit_overflow: GasLimitOverflow::default(),
+            precompile_input_limits: PrecompileInputLimits::default(),
+            precompile_gas_overrides: PrecompileGasOverrides::new(),
+            disabled_opcodes: OpcodeSet::default(),
+            disable_blobs: false,
+            disable_gas_metering: false,
             kzg_settings: crate::kzg::EnvKzgSettings::Default,
-            #[cfg(feature = "memory_limit")]
             memory_limit: (1 << 32) - 1,
+            warm_preloaded: Vec::new(),
+            disable_state_clear: false,
+            blockhash_from_history_storage: false,
+            #[cfg(feature = "halt_snapshot")]
+            halt_snapshot: None,
             #[cfg(feature = "optional_balance_check")]
             disable_balance_check: false,
             #[cfg(feature = "optional_block_gas_limit")]
@@ -396,12 +756,78 @@ impl Default for CfgEnv {
             disable_base_fee: false,
             #[cfg(feature = "optional_beneficiary_reward")]
             disable_beneficiary_reward: false,
+            #[cfg(feature = "optional_code_size_limit")]
+            disable_code_size_limit: false,
             #[cfg(feature = "optimism")]
             optimism: false,
         }
     }
 }
 
+/// Bounds of the [crate::HaltSnapshot] captured when execution halts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HaltSnapshotLimits {
+    /// Maximum number of stack items, counted from the top.
+    pub stack: usize,
+    /// Maximum number of memory bytes, counted from the end of the memory.
+    pub memory: usize,
+}
+
+impl Default for HaltSnapshotLimits {
+    fn default() -> Self {
+        Self {
+            stack: 32,
+            memory: 1024,
+        }
+    }
+}
+
+/// Set of opcodes, see [CfgEnv::disabled_opcodes].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpcodeSet([u64; 4]);
+
+impl OpcodeSet {
+    /// Creates set of the given opcodes.
+    pub fn new(opcodes: impl IntoIterator<Item = u8>) -> Self {
+        let mut set = Self::default();
+        for opcode in opcodes {
+            set.insert(opcode);
+        }
+        set
+    }
+
+    /// Adds opcode to the set.
+    #[inline]
+    pub fn insert(&mut self, opcode: u8) {
+        self.0[opcode as usize / 64] |= 1 << (opcode % 64);
+    }
+
+    /// Removes opcode from the set.
+    #[inline]
+    pub fn remove(&mut self, opcode: u8) {
+        self.0[opcode as usize / 64] &= !(1 << (opcode % 64));
+    }
+
+    /// Returns true if the set contains the opcode.
+    #[inline]
+    pub fn contains(&self, opcode: u8) -> bool {
+        self.0[opcode as usize / 64] & (1 << (opcode % 64)) != 0
+    }
+
+    /// Returns true if the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Returns the opcodes of the set in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=u8::MAX).filter(|&opcode| self.contains(opcode))
+    }
+}
+
 /// The block environment.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -441,6 +867,19 @@ pub struct BlockEnv {
     ///
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
     pub blob_excess_gas_and_price: Option<BlobExcessGasAndPrice>,
+    /// Root of the parent beacon block.
+    ///
+    /// It is only used by block execution to apply the [EIP-4788] system call.
+    ///
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    pub parent_beacon_block_root: Option<B256>,
+    /// Hash of the parent block.
+    ///
+    /// It is only used by block execution to apply the [EIP-2935] system call, which is skipped
+    /// if it is not set.
+    ///
+    /// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+    pub parent_hash: Option<B256>,
 }
 
 impl BlockEnv {
@@ -485,6 +924,8 @@ impl Default for BlockEnv {
             difficulty: U256::ZERO,
             prevrandao: Some(B256::ZERO),
             blob_excess_gas_and_price: Some(BlobExcessGasAndPrice::new(0)),
+            parent_beacon_block_root: None,
+            parent_hash: None,
         }
     }
 }
@@ -544,7 +985,14 @@ pub struct TxEnv {
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
     pub max_fee_per_blob_gas: Option<U256>,
 
-    #[cfg_attr(feature = "serde", serde(flatten))]
+    /// Kind of a chain specific transaction, e.g. a system or a retryable transaction of an L2.
+    ///
+    /// `None` for Ethereum transactions. Other kinds are only executed if the EVM handler has a
+    /// chain transaction extension supporting them.
+    pub chain_tx_kind: Option<u8>,
+
+    /// Optimism fields, serialized as a nested object so that the environment can be encoded in
+    /// non self-describing formats.
     #[cfg(feature = "optimism")]
     pub optimism: OptimismFields,
 }
@@ -574,6 +1022,7 @@ impl Default for TxEnv {
             access_list: Vec::new(),
             blob_hashes: Vec::new(),
             max_fee_per_blob_gas: None,
+            chain_tx_kind: None,
             #[cfg(feature = "optimism")]
             optimism: OptimismFields::default(),
         }
@@ -595,6 +1044,15 @@ pub struct BlobExcessGasAndPrice {
 }
 
 impl BlobExcessGasAndPrice {
+    /// Creates instance of the child block from the parent's excess blob gas and blob gas used,
+    /// see [`crate::calc_excess_blob_gas`].
+    pub fn from_parent(parent_excess_blob_gas: u64, parent_blob_gas_used: u64) -> Self {
+        Self::new(calc_excess_blob_gas(
+            parent_excess_blob_gas,
+            parent_blob_gas_used,
+        ))
+    }
+
     /// Creates a new instance by calculating the blob gas price with [`calc_blob_gasprice`].
     pub fn new(excess_blob_gas: u64) -> Self {
         let blob_gasprice = calc_blob_gasprice(excess_blob_gas);
@@ -605,6 +1063,32 @@ impl BlobExcessGasAndPrice {
     }
 }
 
+/// Validator withdrawal from the consensus layer.
+///
+/// Incorporated as part of the Shanghai upgrade via [EIP-4895].
+///
+/// [EIP-4895]: https://eips.ethereum.org/EIPS/eip-4895
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Withdrawal {
+    /// Monotonically increasing identifier of the withdrawal.
+    pub index: u64,
+    /// Index of the validator the withdrawal is for.
+    pub validator_index: u64,
+    /// Recipient of the withdrawn ether.
+    pub address: Address,
+    /// Withdrawn amount in gwei.
+    pub amount: u64,
+}
+
+impl Withdrawal {
+    /// Returns the withdrawn amount in wei.
+    #[inline]
+    pub fn amount_wei(&self) -> U256 {
+        U256::from(self.amount) * U256::from(1_000_000_000u64)
+    }
+}
+
 /// Additional [TxEnv] fields for optimism.
 #[cfg(feature = "optimism")]
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
@@ -694,6 +1178,19 @@ pub enum CreateScheme {
     },
 }
 
+/// How a gas limit that doesn't fit in `u64` is handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GasLimitOverflow {
+    /// Gas limit is saturated to `u64::MAX`.
+    #[default]
+    Saturate,
+    /// Gas limit is rejected with [`InvalidHeader::GasLimitOverflow`].
+    Error,
+    /// Gas limit is not checked.
+    Unlimited,
+}
+
 /// What bytecode analysis to perform.
 #[derive(Clone, Default, Debug, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -710,6 +1207,60 @@ pub enum AnalysisKind {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{MAX_BLOB_GAS_PER_BLOCK, TARGET_BLOB_GAS_PER_BLOCK};
+    use alloc::vec;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut env = Env::builder()
+            .mainnet()
+            .block_number(19_426_587)
+            .timestamp(1_710_338_135)
+            .basefee(U256::from(7))
+            .excess_blob_gas(131_072)
+            .build();
+        env.tx.caller = Address::repeat_byte(0x11);
+        env.tx.transact_to = TransactTo::create2(U256::from(1));
+        env.tx.data = Bytes::from_static(&[0x60, 0x00]);
+        env.tx.nonce = Some(3);
+        env.tx.access_list = vec![(Address::repeat_byte(0x22), vec![U256::from(1)])];
+        env.tx.blob_hashes = vec![B256::repeat_byte(0x01)];
+        env.tx.max_fee_per_blob_gas = Some(U256::from(2));
+        env.cfg.disable_state_clear = true;
+
+        let json = crate::utilities::assert_serde_round_trip(&env);
+        assert_eq!(json["block"]["basefee"], "0x7");
+        assert_eq!(
+            json["tx"]["caller"],
+            format!("{}", env.tx.caller).to_lowercase()
+        );
+        assert_eq!(json["tx"]["data"], "0x6000");
+    }
+
+    #[test]
+    fn builder_selects_spec_of_network() {
+        let env = Env::builder()
+            .sepolia()
+            .block_number(1_735_371)
+            .timestamp(1_677_557_087)
+            .basefee(U256::from(7))
+            .build();
+        assert_eq!(env.cfg.chain_id, 11_155_111);
+        assert_eq!(env.cfg.spec_id, SpecId::MERGE);
+        assert_eq!(env.block.basefee, U256::from(7));
+
+        let env = Env::builder()
+            .mainnet()
+            .spec_id(SpecId::BERLIN)
+            .chain_id(1337)
+            .build();
+        assert_eq!((env.cfg.chain_id, env.cfg.spec_id), (1337, SpecId::BERLIN));
+
+        let env = Env::for_chain(17_000).unwrap();
+        assert_eq!(env.cfg.spec_id, Network::HOLESKY.latest_spec_id());
+        assert_eq!(Env::for_chain(1337), None);
+    }
 
     #[cfg(feature = "optimism")]
     #[test]
@@ -752,6 +1303,116 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    fn test_clamp_tx_gas_limit() {
+        let mut env = Env::default();
+        env.tx.gas_limit = 100;
+        assert_eq!(env.clamp_tx_gas_limit(), None);
+
+        env.cfg.tx_gas_cap = Some(100);
+        assert_eq!(env.clamp_tx_gas_limit(), None);
+
+        env.tx.gas_limit = 150;
+        assert_eq!(env.clamp_tx_gas_limit(), Some(150));
+        assert_eq!(env.tx.gas_limit, 100);
+    }
+
+    #[test]
+    fn test_block_gas_limit_overflow() {
+        let mut env = Env::default();
+        env.block.gas_limit = U256::from(30_000_000);
+        assert_eq!(env.block_gas_limit(), Ok(Some(30_000_000)));
+
+        env.block.gas_limit = U256::from(u64::MAX) + U256::from(1);
+        assert_eq!(env.block_gas_limit(), Ok(Some(u64::MAX)));
+
+        env.cfg.block_gas_limit_overflow = GasLimitOverflow::Unlimited;
+        assert_eq!(env.block_gas_limit(), Ok(None));
+
+        env.cfg.block_gas_limit_overflow = GasLimitOverflow::Error;
+        assert_eq!(env.block_gas_limit(), Err(InvalidHeader::GasLimitOverflow));
+        assert_eq!(
+            env.validate_block_env::<crate::FrontierSpec>(),
+            Err(InvalidHeader::GasLimitOverflow)
+        );
+    }
+
+    #[test]
+    fn test_validate_blobs() {
+        let mut env = Env::default();
+        env.tx.blob_hashes = vec![B256::with_last_byte(1)];
+        // Versioned hashes without max fee per blob gas.
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::BlobVersionedHashesNotSupported)
+        );
+
+        env.tx.max_fee_per_blob_gas = Some(U256::from(1));
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::BlobVersionNotSupported)
+        );
+        env.tx.blob_hashes[0][0] = VERSIONED_HASH_VERSION_KZG;
+        assert_eq!(env.validate_tx::<crate::CancunSpec>(), Ok(()));
+
+        env.cfg.disable_blobs = true;
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::BlobVersionedHashesNotSupported)
+        );
+        env.block.blob_excess_gas_and_price = None;
+        assert_eq!(env.validate_block_env::<crate::CancunSpec>(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_initcode_size() {
+        let mut env = Env::default();
+        env.tx.transact_to = TransactTo::Create(CreateScheme::Create);
+        env.tx.data = Bytes::from(vec![0; MAX_INITCODE_SIZE + 1]);
+        assert_eq!(
+            env.validate_tx::<crate::ShanghaiSpec>(),
+            Err(InvalidTransaction::CreateInitcodeSizeLimit)
+        );
+
+        // Initcode limit follows contract code size limit unless overridden.
+        env.cfg.limit_contract_code_size = Some(MAX_CODE_SIZE * 2);
+        assert_eq!(env.cfg.max_initcode_size(), Some(MAX_INITCODE_SIZE * 2));
+        assert_eq!(env.validate_tx::<crate::ShanghaiSpec>(), Ok(()));
+        env.cfg.limit_initcode_size = Some(10);
+        assert_eq!(
+            env.validate_tx::<crate::ShanghaiSpec>(),
+            Err(InvalidTransaction::CreateInitcodeSizeLimit)
+        );
+
+        #[cfg(feature = "optional_code_size_limit")]
+        {
+            env.cfg.disable_code_size_limit = true;
+            assert_eq!(env.cfg.max_code_size(), None);
+            assert_eq!(env.validate_tx::<crate::ShanghaiSpec>(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_opcode_set() {
+        let mut set = OpcodeSet::new([0x00, 0x5d, 0xff]);
+        assert!(set.contains(0x5d) && set.contains(0xff));
+        assert!(!set.contains(0x5c));
+        set.remove(0x00);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0x5d, 0xff]);
+        set.remove(0x5d);
+        set.remove(0xff);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_blob_excess_gas_from_parent() {
+        let parent = BlobExcessGasAndPrice::new(0);
+        let child =
+            BlobExcessGasAndPrice::from_parent(parent.excess_blob_gas, MAX_BLOB_GAS_PER_BLOCK);
+        assert_eq!(child.excess_blob_gas, TARGET_BLOB_GAS_PER_BLOCK);
+        assert!(child.blob_gasprice >= parent.blob_gasprice);
+    }
+
     #[test]
     fn test_validate_tx_chain_id() {
         let mut env = Env::default();
@@ -0,0 +1,292 @@
+use crate::Bytes;
+use alloc::vec::Vec;
+use core::{fmt, ops::Range};
+
+/// EOF container magic bytes. Prefix `0xEF` is reserved by EIP-3541.
+pub const EOF_MAGIC_BYTES: [u8; 2] = [0xEF, 0x00];
+
+/// EOF container version.
+pub const EOF_VERSION: u8 = 0x01;
+
+/// Kind of the types section header.
+pub const EOF_KIND_TYPES: u8 = 0x01;
+/// Kind of the code sections header.
+pub const EOF_KIND_CODE: u8 = 0x02;
+/// Kind of the data section header.
+pub const EOF_KIND_DATA: u8 = 0x03;
+/// Header terminator.
+pub const EOF_TERMINATOR: u8 = 0x00;
+
+/// Maximum number of code sections.
+pub const EOF_MAX_CODE_SECTIONS: usize = 1024;
+
+/// Maximum number of function inputs and outputs.
+pub const EOF_MAX_FUNCTION_IO: u8 = 0x7F;
+
+/// Maximum stack height of a code section.
+pub const EOF_MAX_STACK_HEIGHT: u16 = 1023;
+
+/// Returns whether the code starts with the EOF magic bytes.
+#[inline]
+pub fn is_eof(code: &[u8]) -> bool {
+    code.starts_with(&EOF_MAGIC_BYTES)
+}
+
+/// Entry of the EOF types section, describing one code section.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypesSection {
+    /// Number of stack items the code section consumes.
+    pub inputs: u8,
+    /// Number of stack items the code section returns.
+    pub outputs: u8,
+    /// Maximum stack height reached while executing the code section.
+    pub max_stack_height: u16,
+}
+
+/// Decoded EOF container (EIP-3540).
+///
+/// Only the container layout is checked here; code validation (EIP-3670, EIP-4200, EIP-4750
+/// and EIP-5450) is done by the interpreter.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Eof {
+    /// Raw container bytes.
+    pub raw: Bytes,
+    /// Types of the code sections.
+    pub types: Vec<TypesSection>,
+    /// Ranges of the code sections inside the raw container.
+    pub code_sections: Vec<Range<usize>>,
+    /// Range of the data section inside the raw container.
+    pub data_section: Range<usize>,
+}
+
+impl Eof {
+    /// Decodes and checks the layout of the EOF container.
+    pub fn decode(raw: Bytes) -> Result<Self, EofDecodeError> {
+        let mut reader = HeaderReader { raw: &raw, pos: 0 };
+
+        if reader.read_bytes(2)? != EOF_MAGIC_BYTES {
+            return Err(EofDecodeError::MissingMagic);
+        }
+        if reader.read_u8()? != EOF_VERSION {
+            return Err(EofDecodeError::UnknownVersion);
+        }
+
+        if reader.read_u8()? != EOF_KIND_TYPES {
+            return Err(EofDecodeError::MissingTypesSection);
+        }
+        let types_size = reader.read_u16()? as usize;
+
+        if reader.read_u8()? != EOF_KIND_CODE {
+            return Err(EofDecodeError::MissingCodeSection);
+        }
+        let num_code_sections = reader.read_u16()? as usize;
+        if num_code_sections == 0 {
+            return Err(EofDecodeError::ZeroCodeSections);
+        }
+        if num_code_sections > EOF_MAX_CODE_SECTIONS {
+            return Err(EofDecodeError::TooManyCodeSections);
+        }
+        let mut code_sizes = Vec::with_capacity(num_code_sections);
+        for _ in 0..num_code_sections {
+            let size = reader.read_u16()? as usize;
+            if size == 0 {
+                return Err(EofDecodeError::ZeroSizeSection);
+            }
+            code_sizes.push(size);
+        }
+        if types_size != num_code_sections * 4 {
+            return Err(EofDecodeError::InvalidTypesSize);
+        }
+
+        if reader.read_u8()? != EOF_KIND_DATA {
+            return Err(EofDecodeError::MissingDataSection);
+        }
+        let data_size = reader.read_u16()? as usize;
+
+        if reader.read_u8()? != EOF_TERMINATOR {
+            return Err(EofDecodeError::MissingTerminator);
+        }
+
+        let header_size = reader.pos;
+        let body_size = types_size + code_sizes.iter().sum::<usize>() + data_size;
+        if raw.len() != header_size + body_size {
+            return Err(EofDecodeError::InvalidContainerSize);
+        }
+
+        let types = raw[header_size..header_size + types_size]
+            .chunks_exact(4)
+            .map(|chunk| TypesSection {
+                inputs: chunk[0],
+                outputs: chunk[1],
+                max_stack_height: u16::from_be_bytes([chunk[2], chunk[3]]),
+            })
+            .collect::<Vec<_>>();
+        for types_section in &types {
+            if types_section.inputs > EOF_MAX_FUNCTION_IO
+                || types_section.outputs > EOF_MAX_FUNCTION_IO
+                || types_section.max_stack_height > EOF_MAX_STACK_HEIGHT
+            {
+                return Err(EofDecodeError::InvalidTypes);
+            }
+        }
+        if types[0].inputs != 0 || types[0].outputs != 0 {
+            return Err(EofDecodeError::InvalidFirstSectionType);
+        }
+
+        let mut offset = header_size + types_size;
+        let code_sections = code_sizes
+            .into_iter()
+            .map(|size| {
+                let range = offset..offset + size;
+                offset += size;
+                range
+            })
+            .collect();
+        let data_section = offset..offset + data_size;
+
+        Ok(Self {
+            raw,
+            types,
+            code_sections,
+            data_section,
+        })
+    }
+
+    /// Returns the code section at the given index.
+    #[inline]
+    pub fn code_section(&self, index: usize) -> Option<&[u8]> {
+        self.code_sections
+            .get(index)
+            .map(|range| &self.raw[range.clone()])
+    }
+
+    /// Returns the data section.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.raw[self.data_section.clone()]
+    }
+}
+
+/// Reads header fields, failing if the container ends early.
+struct HeaderReader<'a> {
+    raw: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> HeaderReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], EofDecodeError> {
+        let bytes = self
+            .raw
+            .get(self.pos..self.pos + len)
+            .ok_or(EofDecodeError::HeaderTruncated)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, EofDecodeError> {
+        self.read_bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, EofDecodeError> {
+        self.read_bytes(2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+}
+
+/// Errors that can occur while decoding an EOF container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EofDecodeError {
+    /// Container does not start with [`EOF_MAGIC_BYTES`].
+    MissingMagic,
+    /// Container version is not [`EOF_VERSION`].
+    UnknownVersion,
+    /// Container ends inside the header.
+    HeaderTruncated,
+    MissingTypesSection,
+    MissingCodeSection,
+    MissingDataSection,
+    MissingTerminator,
+    ZeroCodeSections,
+    TooManyCodeSections,
+    ZeroSizeSection,
+    /// Types section size does not match the number of code sections.
+    InvalidTypesSize,
+    /// Container size does not match the sizes declared in the header.
+    InvalidContainerSize,
+    /// Types section entry exceeds input, output or stack height limits.
+    InvalidTypes,
+    /// First code section must have zero inputs and outputs.
+    InvalidFirstSectionType,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EofDecodeError {}
+
+impl fmt::Display for EofDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingMagic => write!(f, "Missing EOF magic bytes"),
+            Self::UnknownVersion => write!(f, "Unknown EOF version"),
+            Self::HeaderTruncated => write!(f, "EOF header is truncated"),
+            Self::MissingTypesSection => write!(f, "Missing types section"),
+            Self::MissingCodeSection => write!(f, "Missing code section"),
+            Self::MissingDataSection => write!(f, "Missing data section"),
+            Self::MissingTerminator => write!(f, "Missing header terminator"),
+            Self::ZeroCodeSections => write!(f, "No code sections"),
+            Self::TooManyCodeSections => write!(f, "Too many code sections"),
+            Self::ZeroSizeSection => write!(f, "Code section of zero size"),
+            Self::InvalidTypesSize => write!(f, "Invalid types section size"),
+            Self::InvalidContainerSize => write!(f, "Invalid container size"),
+            Self::InvalidTypes => write!(f, "Invalid types section entry"),
+            Self::InvalidFirstSectionType => write!(f, "Invalid type of the first code section"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn decode_container() {
+        // one code section with `STOP`, two bytes of data.
+        let raw = Bytes::from(hex!("ef00010100040200010001030002000000000000aabb"));
+        let eof = Eof::decode(raw).unwrap();
+        assert_eq!(eof.types, vec![TypesSection::default()]);
+        assert_eq!(eof.code_section(0), Some(&[0x00][..]));
+        assert_eq!(eof.code_section(1), None);
+        assert_eq!(eof.data(), &[0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn decode_invalid_containers() {
+        let decode = |raw: &[u8]| Eof::decode(Bytes::copy_from_slice(raw)).unwrap_err();
+        assert_eq!(decode(&hex!("ef01")), EofDecodeError::MissingMagic);
+        assert_eq!(decode(&hex!("ef0002")), EofDecodeError::UnknownVersion);
+        assert_eq!(decode(&hex!("ef000101")), EofDecodeError::HeaderTruncated);
+        assert_eq!(
+            decode(&hex!("ef00010100040200000300000000")),
+            EofDecodeError::ZeroCodeSections
+        );
+        assert_eq!(
+            decode(&hex!("ef000101000402000100000300000000000000")),
+            EofDecodeError::ZeroSizeSection
+        );
+        assert_eq!(
+            decode(&hex!("ef000101000802000100010300000000000000")),
+            EofDecodeError::InvalidTypesSize
+        );
+        assert_eq!(
+            decode(&hex!("ef000101000402000100010300000000000000")),
+            EofDecodeError::InvalidContainerSize
+        );
+        assert_eq!(
+            decode(&hex!("ef00010100040200010001030000000100000000")),
+            EofDecodeError::InvalidFirstSectionType
+        );
+    }
+}
@@ -1,9 +1,27 @@
+#[cfg(not(any(
+    feature = "pure-kzg",
+    all(feature = "c-kzg", not(target_arch = "wasm32"))
+)))]
+compile_error!(
+    "a KZG backend is required: enable the `c-kzg` feature, \
+     or `pure-kzg` on targets where `c-kzg` is not available"
+);
+
 mod env_settings;
+#[cfg(feature = "pure-kzg")]
+mod pure_verifier;
+#[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
 mod trusted_setup_points;
+mod verifier;
 
+#[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
 pub use c_kzg::KzgSettings;
 pub use env_settings::EnvKzgSettings;
+#[cfg(feature = "pure-kzg")]
+pub use pure_verifier::PureKzgVerifier;
+#[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
 pub use trusted_setup_points::{
     parse_kzg_trusted_setup, G1Points, G2Points, KzgErrors, BYTES_PER_G1_POINT, BYTES_PER_G2_POINT,
     G1_POINTS, G2_POINTS, NUM_G1_POINTS, NUM_G2_POINTS,
 };
+pub use verifier::KzgVerifier;
@@ -1,59 +1,96 @@
-use super::{
-    trusted_setup_points::{G1_POINTS, G2_POINTS},
-    KzgSettings,
-};
-use alloc::{boxed::Box, sync::Arc};
+use super::KzgVerifier;
+use alloc::sync::Arc;
 use core::hash::{Hash, Hasher};
-use once_cell::race::OnceBox;
 
-/// KZG Settings that allow us to specify a custom trusted setup.
+/// KZG Settings that allow us to specify a custom trusted setup or verifier,
 /// or use hardcoded default settings.
-#[derive(Debug, Clone, Default, Eq)]
+#[derive(Debug, Clone, Default)]
 pub enum EnvKzgSettings {
-    /// Default mainnet trusted setup
+    /// Default mainnet trusted setup of `c-kzg`, or of [`PureKzgVerifier`](super::PureKzgVerifier)
+    /// where `c-kzg` is not available, e.g. on wasm.
     #[default]
     Default,
     /// Custom trusted setup.
+    #[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
     Custom(Arc<c_kzg::KzgSettings>),
+    /// Custom verifier, for example a pure Rust backend.
+    Verifier(Arc<dyn KzgVerifier>),
 }
 
-// Implement PartialEq and Hash manually because `c_kzg::KzgSettings` does not implement them
+// Implement PartialEq, Eq and Hash manually because `c_kzg::KzgSettings` and verifiers do not
+// implement them
 impl PartialEq for EnvKzgSettings {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Default, Self::Default) => true,
+            #[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
             (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            (Self::Verifier(a), Self::Verifier(b)) => Arc::ptr_eq(a, b),
             _ => false,
         }
     }
 }
 
+impl Eq for EnvKzgSettings {}
+
 impl Hash for EnvKzgSettings {
     fn hash<H: Hasher>(&self, state: &mut H) {
         core::mem::discriminant(self).hash(state);
         match self {
             Self::Default => {}
+            #[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
             Self::Custom(settings) => Arc::as_ptr(settings).hash(state),
+            Self::Verifier(verifier) => Arc::as_ptr(verifier).cast::<()>().hash(state),
         }
     }
 }
 
 impl EnvKzgSettings {
+    /// Return set KZG verifier.
+    ///
+    /// In will initialize the default settings if it is not already loaded.
+    pub fn verifier(&self) -> &dyn KzgVerifier {
+        match self {
+            #[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
+            Self::Default => default_settings(),
+            #[cfg(not(all(feature = "c-kzg", not(target_arch = "wasm32"))))]
+            Self::Default => super::PureKzgVerifier::mainnet(),
+            #[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
+            Self::Custom(settings) => &**settings,
+            Self::Verifier(verifier) => &**verifier,
+        }
+    }
+
     /// Return set KZG settings.
     ///
     /// In will initialize the default settings if it is not already loaded.
-    pub fn get(&self) -> &KzgSettings {
+    ///
+    /// # Panics
+    ///
+    /// Panics if a custom [EnvKzgSettings::Verifier] is set, it has no `c-kzg` settings.
+    #[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
+    #[deprecated = "use `verifier` instead"]
+    pub fn get(&self) -> &c_kzg::KzgSettings {
         match self {
-            Self::Default => {
-                static DEFAULT: OnceBox<KzgSettings> = OnceBox::new();
-                DEFAULT.get_or_init(|| {
-                    let settings =
-                        KzgSettings::load_trusted_setup(G1_POINTS.as_ref(), G2_POINTS.as_ref())
-                            .expect("failed to load default trusted setup");
-                    Box::new(settings)
-                })
-            }
+            Self::Default => default_settings(),
             Self::Custom(settings) => settings,
+            Self::Verifier(_) => panic!("custom KZG verifier has no `c-kzg` settings"),
         }
     }
 }
+
+/// Returns settings of the mainnet trusted setup.
+#[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
+fn default_settings() -> &'static c_kzg::KzgSettings {
+    use super::trusted_setup_points::{G1_POINTS, G2_POINTS};
+    use alloc::boxed::Box;
+    use once_cell::race::OnceBox;
+
+    static DEFAULT: OnceBox<c_kzg::KzgSettings> = OnceBox::new();
+    DEFAULT.get_or_init(|| {
+        let settings =
+            c_kzg::KzgSettings::load_trusted_setup(G1_POINTS.as_ref(), G2_POINTS.as_ref())
+                .expect("failed to load default trusted setup");
+        Box::new(settings)
+    })
+}
@@ -0,0 +1,108 @@
+use super::KzgVerifier;
+use bls12_381::{pairing, G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use core::fmt;
+
+/// `[τ]G2` of the mainnet trusted setup, the second G2 point of `trusted_setup.txt`.
+const MAINNET_TAU_G2: [u8; 96] = crate::hex_literal::hex!(
+    "b5bfd7dd8cdeb128843bc287230af38926187075cbfbefa81009a2ce615ac53d"
+    "2914e5870cb452d2afaaab24f3499f72185cbfee53492714734429b7b38608e2"
+    "3926c911cceceac9a36851477ba4c60b087041de621000edc98edada20c1def2"
+);
+
+/// Pure Rust KZG verifier built on the `bls12_381` crate.
+///
+/// Verifying a single proof only needs `[τ]G2` of the trusted setup, so it works in `no_std` and
+/// wasm builds. It is the default backend when the `c-kzg` feature is disabled or unavailable.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PureKzgVerifier {
+    tau_g2: G2Affine,
+}
+
+impl fmt::Debug for PureKzgVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PureKzgVerifier").finish_non_exhaustive()
+    }
+}
+
+impl PureKzgVerifier {
+    /// Creates a verifier from the compressed `[τ]G2` point of a trusted setup.
+    ///
+    /// Returns `None` if the point is not a valid G2 point.
+    pub fn new(tau_g2: &[u8; 96]) -> Option<Self> {
+        Option::from(G2Affine::from_compressed(tau_g2)).map(|tau_g2| Self { tau_g2 })
+    }
+
+    /// Returns the verifier of the mainnet trusted setup.
+    pub fn mainnet() -> &'static Self {
+        use alloc::boxed::Box;
+        use once_cell::race::OnceBox;
+
+        static MAINNET: OnceBox<PureKzgVerifier> = OnceBox::new();
+        MAINNET.get_or_init(|| {
+            Box::new(Self::new(&MAINNET_TAU_G2).expect("mainnet [τ]G2 is a valid point"))
+        })
+    }
+}
+
+impl KzgVerifier for PureKzgVerifier {
+    fn verify_kzg_proof(
+        &self,
+        commitment: &[u8; 48],
+        z: &[u8; 32],
+        y: &[u8; 32],
+        proof: &[u8; 48],
+    ) -> bool {
+        let (Some(commitment), Some(proof), Some(z), Some(y)) = (
+            Option::<G1Affine>::from(G1Affine::from_compressed(commitment)),
+            Option::<G1Affine>::from(G1Affine::from_compressed(proof)),
+            scalar(z),
+            scalar(y),
+        ) else {
+            return false;
+        };
+        // e(C - [y]G1, G2) == e(π, [τ]G2 - [z]G2)
+        let lhs = G1Affine::from(G1Projective::from(commitment) - G1Affine::generator() * y);
+        let rhs = G2Affine::from(G2Projective::from(self.tau_g2) - G2Affine::generator() * z);
+        pairing(&lhs, &G2Affine::generator()) == pairing(&proof, &rhs)
+    }
+}
+
+/// Parses a big-endian field element, `None` if it is not canonical.
+fn scalar(bytes: &[u8; 32]) -> Option<Scalar> {
+    let mut le = *bytes;
+    le.reverse();
+    Scalar::from_bytes(&le).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex_literal::hex;
+
+    // test data from: https://github.com/ethereum/c-kzg-4844/blob/main/tests/verify_kzg_proof/kzg-mainnet/verify_kzg_proof_case_correct_proof_31ebd010e6098750/data.yaml
+    const COMMITMENT: [u8; 48] = hex!("8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7");
+    const Z: [u8; 32] = hex!("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000");
+    const Y: [u8; 32] = hex!("1522a4a7f34e1ea350ae07c29c96c7e79655aa926122e95fe69fcbd932ca49e9");
+    const PROOF: [u8; 48] = hex!("a62ad71d14c5719385c0686f1871430475bf3a00f0aa3f7b8dd99a9abc2160744faf0070725e00b60ad9a026a15b1a8c");
+
+    #[test]
+    fn verifies_mainnet_proof() {
+        let verifier = PureKzgVerifier::mainnet();
+        assert!(verifier.verify_kzg_proof(&COMMITMENT, &Z, &Y, &PROOF));
+
+        let mut wrong_y = Y;
+        wrong_y[31] ^= 1;
+        assert!(!verifier.verify_kzg_proof(&COMMITMENT, &Z, &wrong_y, &PROOF));
+        // z is not a canonical field element.
+        let mut modulus = Z;
+        modulus[31] = 1;
+        assert!(!verifier.verify_kzg_proof(&COMMITMENT, &modulus, &Y, &PROOF));
+        assert!(!verifier.verify_kzg_proof(&[0; 48], &Z, &Y, &PROOF));
+    }
+
+    #[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
+    #[test]
+    fn tau_g2_matches_trusted_setup() {
+        assert_eq!(super::super::G2_POINTS[1], MAINNET_TAU_G2);
+    }
+}
@@ -0,0 +1,43 @@
+use core::fmt;
+
+/// Backend that verifies KZG proofs of the EIP-4844 point evaluation precompile.
+///
+/// `c-kzg` is used where it is enabled and the pure Rust `PureKzgVerifier` otherwise, see
+/// [super::EnvKzgSettings]. Implementing the trait allows other backends.
+pub trait KzgVerifier: fmt::Debug + Send + Sync {
+    /// Returns true if `proof` proves that the polynomial committed to by `commitment`
+    /// evaluates to `y` at `z`. Points and field elements are in their serialized form.
+    fn verify_kzg_proof(
+        &self,
+        commitment: &[u8; 48],
+        z: &[u8; 32],
+        y: &[u8; 32],
+        proof: &[u8; 48],
+    ) -> bool;
+}
+
+#[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
+impl KzgVerifier for c_kzg::KzgSettings {
+    fn verify_kzg_proof(
+        &self,
+        commitment: &[u8; 48],
+        z: &[u8; 32],
+        y: &[u8; 32],
+        proof: &[u8; 48],
+    ) -> bool {
+        use c_kzg::{Bytes32, Bytes48, KzgProof};
+
+        let commitment = Bytes48::from(*commitment);
+        let (z, y) = (Bytes32::from(*z), Bytes32::from(*y));
+        let proof = Bytes48::from(*proof);
+        match KzgProof::verify_kzg_proof(&commitment, &z, &y, &proof, self) {
+            Ok(ok) => ok,
+            #[cfg(not(debug_assertions))]
+            Err(_) => false,
+            #[cfg(debug_assertions)]
+            Err(e) => {
+                panic!("verify_kzg_proof returned an error: {e:?}");
+            }
+        }
+    }
+}
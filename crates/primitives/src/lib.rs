@@ -12,29 +12,43 @@ mod bytecode;
 mod constants;
 pub mod db;
 pub mod env;
-#[cfg(feature = "c-kzg")]
+mod eof;
 pub mod kzg;
 mod log;
+pub mod network;
+pub mod opcode;
 pub mod precompile;
+mod receipt;
 pub mod result;
+#[cfg(feature = "rpc-types")]
+pub mod rpc_types;
 pub mod specification;
 pub mod state;
 pub mod utilities;
 
 pub use alloy_primitives::{
-    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, Bytes,
-    FixedBytes, B256, I256, U256,
+    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, Bloom,
+    BloomInput, Bytes, FixedBytes, B256, I256, U256,
 };
 pub use bitvec;
 pub use bytecode::*;
 pub use constants::*;
 pub use env::*;
+pub use eof::*;
 pub use hashbrown::{hash_map, hash_set, HashMap, HashSet};
-#[cfg(feature = "c-kzg")]
-pub use kzg::{EnvKzgSettings, KzgSettings};
+#[cfg(all(feature = "c-kzg", not(target_arch = "wasm32")))]
+pub use kzg::KzgSettings;
+#[cfg(feature = "pure-kzg")]
+pub use kzg::PureKzgVerifier;
+pub use kzg::{EnvKzgSettings, KzgVerifier};
 pub use log::*;
+pub use network::{ForkCondition, HardforkSchedule, Network};
+pub use opcode::OpCode;
 pub use precompile::*;
+pub use receipt::*;
 pub use result::*;
+#[cfg(feature = "rpc-types")]
+pub use rpc_types::RpcConversionError;
 pub use specification::*;
 pub use state::*;
 pub use utilities::*;
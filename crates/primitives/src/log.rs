@@ -1,4 +1,4 @@
-use crate::{Address, Bytes, B256};
+use crate::{Address, Bloom, Bytes, B256};
 use alloc::vec::Vec;
 use alloy_rlp::{RlpDecodable, RlpEncodable};
 
@@ -9,3 +9,274 @@ pub struct Log {
     pub topics: Vec<B256>,
     pub data: Bytes,
 }
+
+impl Log {
+    /// Returns the first topic, the event selector of logs emitted by non-anonymous events.
+    #[inline]
+    pub fn topic0(&self) -> Option<&B256> {
+        self.topics.first()
+    }
+
+    /// Returns true if the log was emitted by the event with the given selector.
+    #[inline]
+    pub fn is_event(&self, selector: &B256) -> bool {
+        self.topic0() == Some(selector)
+    }
+
+    /// Converts the log into the RPC log at the given position.
+    pub fn into_rpc(self, position: LogPosition) -> RpcLog {
+        RpcLog {
+            address: self.address,
+            topics: self.topics,
+            data: self.data,
+            block_hash: position.block_hash,
+            block_number: position.block_number,
+            transaction_hash: position.transaction_hash,
+            transaction_index: position.transaction_index,
+            log_index: position.log_index,
+            removed: false,
+        }
+    }
+
+    /// Accrues address and topics of the log into the bloom.
+    pub fn accrue_bloom(&self, bloom: &mut Bloom) {
+        bloom.m3_2048(self.address.as_slice());
+        for topic in &self.topics {
+            bloom.m3_2048(topic.as_slice());
+        }
+    }
+}
+
+/// Filter of logs by address and topics, with the semantics of `eth_getLogs`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogFilter {
+    /// Addresses one of which emitted the log. Empty matches any address.
+    pub addresses: Vec<Address>,
+    /// Topics by position, the log topic has to be one of the given ones. `None` or a missing
+    /// position matches any topic.
+    pub topics: Vec<Option<Vec<B256>>>,
+}
+
+impl LogFilter {
+    /// Creates a filter that matches all logs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches logs emitted by `address`, in addition to the already added addresses.
+    pub fn address(mut self, address: Address) -> Self {
+        self.addresses.push(address);
+        self
+    }
+
+    /// Matches logs with `topic` at `index`, in addition to the already added topics at `index`.
+    pub fn topic(mut self, index: usize, topic: B256) -> Self {
+        if self.topics.len() <= index {
+            self.topics.resize(index + 1, None);
+        }
+        self.topics[index].get_or_insert_with(Vec::new).push(topic);
+        self
+    }
+
+    /// Matches logs emitted by the event with the given selector.
+    pub fn event(self, selector: B256) -> Self {
+        self.topic(0, selector)
+    }
+
+    /// Returns true if the log matches the filter.
+    pub fn matches(&self, log: &Log) -> bool {
+        if !self.addresses.is_empty() && !self.addresses.contains(&log.address) {
+            return false;
+        }
+        self.topics
+            .iter()
+            .enumerate()
+            .all(|(i, topics)| match topics {
+                None => true,
+                Some(topics) => log.topics.get(i).is_some_and(|t| topics.contains(t)),
+            })
+    }
+}
+
+/// Position of a log in the chain, supplied by the caller when converting it to [RpcLog].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LogPosition {
+    /// Hash of the block.
+    pub block_hash: B256,
+    /// Number of the block.
+    pub block_number: u64,
+    /// Hash of the transaction.
+    pub transaction_hash: B256,
+    /// Index of the transaction in the block.
+    pub transaction_index: u64,
+    /// Index of the log in the block.
+    pub log_index: u64,
+}
+
+/// Log as returned by `eth_getLogs` and in `eth_getTransactionReceipt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RpcLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    pub block_hash: B256,
+    pub block_number: u64,
+    pub transaction_hash: B256,
+    pub transaction_index: u64,
+    pub log_index: u64,
+    /// True if the log was removed by a chain reorganization.
+    pub removed: bool,
+}
+
+/// Converts logs of a transaction into RPC logs. `position` is the position of the first log,
+/// following logs get consecutive log indexes.
+pub fn rpc_logs(logs: impl IntoIterator<Item = Log>, position: LogPosition) -> Vec<RpcLog> {
+    logs.into_iter()
+        .zip(position.log_index..)
+        .map(|(log, log_index)| {
+            log.into_rpc(LogPosition {
+                log_index,
+                ..position
+            })
+        })
+        .collect()
+}
+
+/// Computes logs bloom of a transaction receipt.
+///
+/// Block logs bloom is the union of the blooms of its receipts, see [Bloom::accrue_bloom].
+pub fn logs_bloom<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Bloom {
+    let mut bloom = Bloom::ZERO;
+    for log in logs {
+        log.accrue_bloom(&mut bloom);
+    }
+    bloom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockSummary, BloomInput, Eval, ExecutionResult, GasBreakdown, Output};
+    use alloc::vec;
+
+    #[test]
+    fn filter_and_convert_logs() {
+        let (transfer, approval) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let log = |address, topics: &[B256]| Log {
+            address: Address::with_last_byte(address),
+            topics: topics.to_vec(),
+            data: Bytes::new(),
+        };
+        let logs = vec![
+            log(0x10, &[transfer, B256::with_last_byte(0xa)]),
+            log(0x10, &[approval]),
+            log(0x20, &[transfer, B256::with_last_byte(0xb)]),
+            log(0x20, &[]),
+        ];
+        assert!(logs[0].is_event(&transfer) && !logs[1].is_event(&transfer));
+        assert_eq!(logs[3].topic0(), None);
+
+        let result = ExecutionResult::Success {
+            reason: Eval::Stop,
+            gas: GasBreakdown::default(),
+            logs: logs.clone(),
+            output: Output::Call(Bytes::new()),
+        };
+        let matching = |filter: LogFilter| result.filter_logs(&filter).count();
+        assert_eq!(matching(LogFilter::new()), 4);
+        assert_eq!(matching(LogFilter::new().event(transfer)), 2);
+        assert_eq!(
+            matching(
+                LogFilter::new()
+                    .address(Address::with_last_byte(0x10))
+                    .event(transfer)
+                    .event(approval)
+            ),
+            2
+        );
+        assert_eq!(
+            matching(LogFilter::new().topic(1, B256::with_last_byte(0xb))),
+            1
+        );
+
+        let position = LogPosition {
+            block_number: 7,
+            transaction_index: 1,
+            log_index: 5,
+            ..Default::default()
+        };
+        let rpc = rpc_logs(logs, position);
+        assert_eq!(rpc.len(), 4);
+        assert_eq!(rpc[3].log_index, 8);
+        assert_eq!(rpc[3].block_number, 7);
+        assert_eq!(rpc[0].topics, vec![transfer, B256::with_last_byte(0xa)]);
+    }
+
+    #[test]
+    fn logs_bloom_contains_addresses_and_topics() {
+        let log = Log {
+            address: Address::with_last_byte(0x10),
+            topics: vec![B256::with_last_byte(1), B256::with_last_byte(2)],
+            data: Bytes::from_static(&[0xff]),
+        };
+        let bloom = logs_bloom([&log]);
+        assert!(bloom.contains_input(BloomInput::Raw(log.address.as_slice())));
+        for topic in &log.topics {
+            assert!(bloom.contains_input(BloomInput::Raw(topic.as_slice())));
+        }
+        // Data is not part of the bloom.
+        assert!(!bloom.contains_input(BloomInput::Raw(&[0xff])));
+        assert_eq!(
+            bloom,
+            Bloom::logs_bloom([(log.address.0 .0, log.topics.iter().map(|t| t.0))])
+        );
+        assert_eq!(logs_bloom([]), Bloom::ZERO);
+    }
+
+    #[test]
+    fn block_bloom_is_union_of_receipt_blooms() {
+        let success = |address| ExecutionResult::Success {
+            reason: Eval::Stop,
+            gas: GasBreakdown {
+                intrinsic: 21_000,
+                ..Default::default()
+            },
+            logs: vec![Log {
+                address,
+                ..Default::default()
+            }],
+            output: Output::Call(Bytes::new()),
+        };
+        let results = [
+            success(Address::with_last_byte(0x10)),
+            ExecutionResult::revert(
+                GasBreakdown {
+                    intrinsic: 21_000,
+                    ..Default::default()
+                },
+                Bytes::new(),
+            ),
+            success(Address::with_last_byte(0x20)),
+        ];
+
+        let mut summary = BlockSummary::default();
+        for result in &results {
+            summary.push(result);
+        }
+        assert_eq!(results[1].logs_bloom(), Bloom::ZERO);
+        assert_eq!(
+            summary.logs_bloom,
+            results[0].logs_bloom() | results[2].logs_bloom()
+        );
+        assert_eq!(
+            summary.logs_bloom,
+            logs_bloom(results.iter().flat_map(|result| match result {
+                ExecutionResult::Success { logs, .. } => logs.as_slice(),
+                _ => &[],
+            }))
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Hardfork schedules and chain ids of public networks.
+
+use crate::{
+    BlockEnv,
+    SpecId::{self, *},
+};
+use alloc::{borrow::Cow, vec::Vec};
+
+/// Condition activating a hardfork.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ForkCondition {
+    /// Activated at the block with this number.
+    Block(u64),
+    /// Activated at the first block with at least this timestamp.
+    Timestamp(u64),
+}
+
+impl ForkCondition {
+    /// Returns whether the hardfork is active in the block with the given number and timestamp.
+    #[inline]
+    pub const fn is_active(&self, block_number: u64, timestamp: u64) -> bool {
+        match *self {
+            Self::Block(number) => block_number >= number,
+            Self::Timestamp(time) => timestamp >= time,
+        }
+    }
+}
+
+/// Activation of the specs of a chain by block number or timestamp.
+///
+/// The spec of a block is the latest one whose condition holds, or [`SpecId::FRONTIER`] if none
+/// does. Schedules of the public networks are constants, others are built with
+/// [`HardforkSchedule::new`] and [`HardforkSchedule::with_fork`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HardforkSchedule {
+    forks: Cow<'static, [(ForkCondition, SpecId)]>,
+}
+
+impl HardforkSchedule {
+    /// Ethereum mainnet.
+    pub const MAINNET: Self = Self::from_static(&[
+        (ForkCondition::Block(0), FRONTIER),
+        (ForkCondition::Block(200_000), FRONTIER_THAWING),
+        (ForkCondition::Block(1_150_000), HOMESTEAD),
+        (ForkCondition::Block(1_920_000), DAO_FORK),
+        (ForkCondition::Block(2_463_000), TANGERINE),
+        (ForkCondition::Block(2_675_000), SPURIOUS_DRAGON),
+        (ForkCondition::Block(4_370_000), BYZANTIUM),
+        (ForkCondition::Block(7_280_000), PETERSBURG),
+        (ForkCondition::Block(9_069_000), ISTANBUL),
+        (ForkCondition::Block(9_200_000), MUIR_GLACIER),
+        (ForkCondition::Block(12_244_000), BERLIN),
+        (ForkCondition::Block(12_965_000), LONDON),
+        (ForkCondition::Block(13_773_000), ARROW_GLACIER),
+        (ForkCondition::Block(15_050_000), GRAY_GLACIER),
+        (ForkCondition::Block(15_537_394), MERGE),
+        (ForkCondition::Timestamp(1_681_338_455), SHANGHAI),
+        (ForkCondition::Timestamp(1_710_338_135), CANCUN),
+    ]);
+
+    /// Sepolia testnet.
+    pub const SEPOLIA: Self = Self::from_static(&[
+        (ForkCondition::Block(0), LONDON),
+        (ForkCondition::Block(1_735_371), MERGE),
+        (ForkCondition::Timestamp(1_677_557_088), SHANGHAI),
+        (ForkCondition::Timestamp(1_706_655_072), CANCUN),
+    ]);
+
+    /// Holesky testnet.
+    pub const HOLESKY: Self = Self::from_static(&[
+        (ForkCondition::Block(0), MERGE),
+        (ForkCondition::Timestamp(1_696_000_704), SHANGHAI),
+        (ForkCondition::Timestamp(1_707_305_664), CANCUN),
+    ]);
+
+    /// Creates a schedule from a static list of activations.
+    pub const fn from_static(forks: &'static [(ForkCondition, SpecId)]) -> Self {
+        Self {
+            forks: Cow::Borrowed(forks),
+        }
+    }
+
+    /// Creates a schedule from a list of activations.
+    pub fn new(forks: Vec<(ForkCondition, SpecId)>) -> Self {
+        Self {
+            forks: Cow::Owned(forks),
+        }
+    }
+
+    /// Adds the activation of a spec, e.g. to schedule an upcoming hardfork.
+    pub fn with_fork(mut self, condition: ForkCondition, spec_id: SpecId) -> Self {
+        self.forks.to_mut().push((condition, spec_id));
+        self
+    }
+
+    /// Returns the activations of the schedule.
+    #[inline]
+    pub fn forks(&self) -> &[(ForkCondition, SpecId)] {
+        &self.forks
+    }
+
+    /// Returns the condition activating the spec, if it is scheduled.
+    pub fn activation(&self, spec_id: SpecId) -> Option<ForkCondition> {
+        self.forks
+            .iter()
+            .find(|(_, id)| *id == spec_id)
+            .map(|(condition, _)| *condition)
+    }
+
+    /// Returns the spec active in the block with the given number and timestamp.
+    pub fn spec_id_at(&self, block_number: u64, timestamp: u64) -> SpecId {
+        self.forks
+            .iter()
+            .filter(|(condition, _)| condition.is_active(block_number, timestamp))
+            .map(|(_, spec_id)| *spec_id)
+            .max()
+            .unwrap_or(FRONTIER)
+    }
+
+    /// Returns the spec active in the block.
+    pub fn spec_id_for_block(&self, block: &BlockEnv) -> SpecId {
+        self.spec_id_at(
+            block.number.saturating_to(),
+            block.timestamp.saturating_to(),
+        )
+    }
+
+    /// Returns the latest scheduled spec.
+    pub fn latest_spec_id(&self) -> SpecId {
+        self.spec_id_at(u64::MAX, u64::MAX)
+    }
+}
+
+/// Chain id and hardfork schedule of a network.
+///
+/// Used by [`Env::for_chain`](crate::Env::for_chain) and
+/// [`EnvBuilder::network`](crate::EnvBuilder::network) to select the spec of a block.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Network {
+    /// Chain id of the network.
+    pub chain_id: u64,
+    /// Hardfork schedule of the network.
+    pub schedule: HardforkSchedule,
+}
+
+impl Network {
+    /// Ethereum mainnet.
+    pub const MAINNET: Self = Self {
+        chain_id: 1,
+        schedule: HardforkSchedule::MAINNET,
+    };
+
+    /// Sepolia testnet.
+    pub const SEPOLIA: Self = Self {
+        chain_id: 11_155_111,
+        schedule: HardforkSchedule::SEPOLIA,
+    };
+
+    /// Holesky testnet.
+    pub const HOLESKY: Self = Self {
+        chain_id: 17_000,
+        schedule: HardforkSchedule::HOLESKY,
+    };
+
+    /// Returns the known network with the given chain id.
+    pub fn from_chain_id(chain_id: u64) -> Option<Self> {
+        [Self::MAINNET, Self::SEPOLIA, Self::HOLESKY]
+            .into_iter()
+            .find(|network| network.chain_id == chain_id)
+    }
+
+    /// Returns the spec active in the block with the given number and timestamp.
+    #[inline]
+    pub fn spec_id_at(&self, block_number: u64, timestamp: u64) -> SpecId {
+        self.schedule.spec_id_at(block_number, timestamp)
+    }
+
+    /// Returns the latest scheduled spec of the network.
+    #[inline]
+    pub fn latest_spec_id(&self) -> SpecId {
+        self.schedule.latest_spec_id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn mainnet_schedule() {
+        let mainnet = Network::from_chain_id(1).unwrap();
+        assert_eq!(mainnet.spec_id_at(0, 0), FRONTIER);
+        assert_eq!(mainnet.spec_id_at(4_369_999, 0), SPURIOUS_DRAGON);
+        assert_eq!(mainnet.spec_id_at(7_280_000, 0), PETERSBURG);
+        assert_eq!(mainnet.spec_id_at(15_537_394, 1_681_338_454), MERGE);
+        assert_eq!(mainnet.spec_id_at(17_034_870, 1_681_338_455), SHANGHAI);
+        assert_eq!(mainnet.latest_spec_id(), CANCUN);
+        assert_eq!(Network::from_chain_id(1337), None);
+    }
+
+    #[test]
+    fn custom_schedule() {
+        let schedule = HardforkSchedule::new(vec![(ForkCondition::Block(0), SHANGHAI)])
+            .with_fork(ForkCondition::Timestamp(100), CANCUN);
+        assert_eq!(schedule.spec_id_at(5, 99), SHANGHAI);
+        assert_eq!(schedule.spec_id_at(5, 100), CANCUN);
+        assert_eq!(
+            schedule.activation(CANCUN),
+            Some(ForkCondition::Timestamp(100))
+        );
+
+        let mainnet =
+            HardforkSchedule::MAINNET.with_fork(ForkCondition::Timestamp(u64::MAX), PRAGUE);
+        assert_eq!(mainnet.latest_spec_id(), PRAGUE);
+        assert_eq!(mainnet.spec_id_at(20_000_000, 1_710_338_135), CANCUN);
+        assert_eq!(HardforkSchedule::default().spec_id_at(1, 1), FRONTIER);
+    }
+}
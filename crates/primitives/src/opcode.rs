@@ -0,0 +1,593 @@
+//! EVM opcode names and the [`OpCode`] type.
+
+use crate::SpecId;
+use core::fmt;
+
+macro_rules! opcodes {
+    ($($val:literal => $name:ident),* $(,)?) => {
+        // Constants for each opcode. This also takes care of duplicate names.
+        $(
+            #[doc = concat!("The `", stringify!($val), "` (\"", stringify!($name),"\") opcode.")]
+            pub const $name: u8 = $val;
+        )*
+
+        /// Maps each opcode to its name.
+        pub const OPCODE_JUMPMAP: [Option<&'static str>; 256] = {
+            let mut map = [None; 256];
+            let mut prev: u8 = 0;
+            $(
+                let val: u8 = $val;
+                assert!(val == 0 || val > prev, "opcodes must be sorted in ascending order");
+                prev = val;
+                map[$val] = Some(stringify!($name));
+            )*
+            let _ = prev;
+            map
+        };
+    };
+}
+
+// When adding new opcodes, add them to the list below and to the instruction list of the
+// interpreter; make sure it's sorted by opcode value.
+opcodes! {
+    0x00 => STOP,
+
+    0x01 => ADD,
+    0x02 => MUL,
+    0x03 => SUB,
+    0x04 => DIV,
+    0x05 => SDIV,
+    0x06 => MOD,
+    0x07 => SMOD,
+    0x08 => ADDMOD,
+    0x09 => MULMOD,
+    0x0A => EXP,
+    0x0B => SIGNEXTEND,
+    // 0x0C
+    // 0x0D
+    // 0x0E
+    // 0x0F
+    0x10 => LT,
+    0x11 => GT,
+    0x12 => SLT,
+    0x13 => SGT,
+    0x14 => EQ,
+    0x15 => ISZERO,
+    0x16 => AND,
+    0x17 => OR,
+    0x18 => XOR,
+    0x19 => NOT,
+    0x1A => BYTE,
+    0x1B => SHL,
+    0x1C => SHR,
+    0x1D => SAR,
+    // 0x1E
+    // 0x1F
+    0x20 => KECCAK256,
+    // 0x21
+    // 0x22
+    // 0x23
+    // 0x24
+    // 0x25
+    // 0x26
+    // 0x27
+    // 0x28
+    // 0x29
+    // 0x2A
+    // 0x2B
+    // 0x2C
+    // 0x2D
+    // 0x2E
+    // 0x2F
+    0x30 => ADDRESS,
+    0x31 => BALANCE,
+    0x32 => ORIGIN,
+    0x33 => CALLER,
+    0x34 => CALLVALUE,
+    0x35 => CALLDATALOAD,
+    0x36 => CALLDATASIZE,
+    0x37 => CALLDATACOPY,
+    0x38 => CODESIZE,
+    0x39 => CODECOPY,
+
+    0x3A => GASPRICE,
+    0x3B => EXTCODESIZE,
+    0x3C => EXTCODECOPY,
+    0x3D => RETURNDATASIZE,
+    0x3E => RETURNDATACOPY,
+    0x3F => EXTCODEHASH,
+    0x40 => BLOCKHASH,
+    0x41 => COINBASE,
+    0x42 => TIMESTAMP,
+    0x43 => NUMBER,
+    0x44 => DIFFICULTY,
+    0x45 => GASLIMIT,
+    0x46 => CHAINID,
+    0x47 => SELFBALANCE,
+    0x48 => BASEFEE,
+    0x49 => BLOBHASH,
+    0x4A => BLOBBASEFEE,
+    // 0x4B
+    // 0x4C
+    // 0x4D
+    // 0x4E
+    // 0x4F
+    0x50 => POP,
+    0x51 => MLOAD,
+    0x52 => MSTORE,
+    0x53 => MSTORE8,
+    0x54 => SLOAD,
+    0x55 => SSTORE,
+    0x56 => JUMP,
+    0x57 => JUMPI,
+    0x58 => PC,
+    0x59 => MSIZE,
+    0x5A => GAS,
+    0x5B => JUMPDEST,
+    0x5C => TLOAD,
+    0x5D => TSTORE,
+    0x5E => MCOPY,
+
+    0x5F => PUSH0,
+    0x60 => PUSH1,
+    0x61 => PUSH2,
+    0x62 => PUSH3,
+    0x63 => PUSH4,
+    0x64 => PUSH5,
+    0x65 => PUSH6,
+    0x66 => PUSH7,
+    0x67 => PUSH8,
+    0x68 => PUSH9,
+    0x69 => PUSH10,
+    0x6A => PUSH11,
+    0x6B => PUSH12,
+    0x6C => PUSH13,
+    0x6D => PUSH14,
+    0x6E => PUSH15,
+    0x6F => PUSH16,
+    0x70 => PUSH17,
+    0x71 => PUSH18,
+    0x72 => PUSH19,
+    0x73 => PUSH20,
+    0x74 => PUSH21,
+    0x75 => PUSH22,
+    0x76 => PUSH23,
+    0x77 => PUSH24,
+    0x78 => PUSH25,
+    0x79 => PUSH26,
+    0x7A => PUSH27,
+    0x7B => PUSH28,
+    0x7C => PUSH29,
+    0x7D => PUSH30,
+    0x7E => PUSH31,
+    0x7F => PUSH32,
+
+    0x80 => DUP1,
+    0x81 => DUP2,
+    0x82 => DUP3,
+    0x83 => DUP4,
+    0x84 => DUP5,
+    0x85 => DUP6,
+    0x86 => DUP7,
+    0x87 => DUP8,
+    0x88 => DUP9,
+    0x89 => DUP10,
+    0x8A => DUP11,
+    0x8B => DUP12,
+    0x8C => DUP13,
+    0x8D => DUP14,
+    0x8E => DUP15,
+    0x8F => DUP16,
+
+    0x90 => SWAP1,
+    0x91 => SWAP2,
+    0x92 => SWAP3,
+    0x93 => SWAP4,
+    0x94 => SWAP5,
+    0x95 => SWAP6,
+    0x96 => SWAP7,
+    0x97 => SWAP8,
+    0x98 => SWAP9,
+    0x99 => SWAP10,
+    0x9A => SWAP11,
+    0x9B => SWAP12,
+    0x9C => SWAP13,
+    0x9D => SWAP14,
+    0x9E => SWAP15,
+    0x9F => SWAP16,
+
+    0xA0 => LOG0,
+    0xA1 => LOG1,
+    0xA2 => LOG2,
+    0xA3 => LOG3,
+    0xA4 => LOG4,
+    // 0xA5
+    // 0xA6
+    // 0xA7
+    // 0xA8
+    // 0xA9
+    // 0xAA
+    // 0xAB
+    // 0xAC
+    // 0xAD
+    // 0xAE
+    // 0xAF
+    // 0xB0
+    // 0xB1
+    // 0xB2
+    // 0xB3
+    // 0xB4
+    // 0xB5
+    // 0xB6
+    // 0xB7
+    // 0xB8
+    // 0xB9
+    // 0xBA
+    // 0xBB
+    // 0xBC
+    // 0xBD
+    // 0xBE
+    // 0xBF
+    // 0xC0
+    // 0xC1
+    // 0xC2
+    // 0xC3
+    // 0xC4
+    // 0xC5
+    // 0xC6
+    // 0xC7
+    // 0xC8
+    // 0xC9
+    // 0xCA
+    // 0xCB
+    // 0xCC
+    // 0xCD
+    // 0xCE
+    // 0xCF
+    // 0xD0
+    // 0xD1
+    // 0xD2
+    // 0xD3
+    // 0xD4
+    // 0xD5
+    // 0xD6
+    // 0xD7
+    // 0xD8
+    // 0xD9
+    // 0xDA
+    // 0xDB
+    // 0xDC
+    // 0xDD
+    // 0xDE
+    // 0xDF
+    0xE0 => RJUMP,
+    0xE1 => RJUMPI,
+    0xE2 => RJUMPV,
+    0xE3 => CALLF,
+    0xE4 => RETF,
+    // 0xE5
+    // 0xE6
+    // 0xE7
+    // 0xE8
+    // 0xE9
+    // 0xEA
+    // 0xEB
+    // 0xEC
+    // 0xED
+    // 0xEE
+    // 0xEF
+    0xF0 => CREATE,
+    0xF1 => CALL,
+    0xF2 => CALLCODE,
+    0xF3 => RETURN,
+    0xF4 => DELEGATECALL,
+    0xF5 => CREATE2,
+    // 0xF6
+    // 0xF7
+    // 0xF8
+    // 0xF9
+    0xFA => STATICCALL,
+    // 0xFB
+    // 0xFC
+    0xFD => REVERT,
+    0xFE => INVALID,
+    0xFF => SELFDESTRUCT,
+}
+
+/// An EVM opcode.
+///
+/// This is always a valid opcode, as declared in the [`opcode`][self] module or the
+/// [`OPCODE_JUMPMAP`] constant.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct OpCode(u8);
+
+impl fmt::Display for OpCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = self.get();
+        if let Some(val) = OPCODE_JUMPMAP[n as usize] {
+            f.write_str(val)
+        } else {
+            write!(f, "UNKNOWN(0x{n:02X})")
+        }
+    }
+}
+
+impl OpCode {
+    /// Instantiate a new opcode from a u8.
+    #[inline]
+    pub const fn new(opcode: u8) -> Option<Self> {
+        match OPCODE_JUMPMAP[opcode as usize] {
+            Some(_) => Some(Self(opcode)),
+            None => None,
+        }
+    }
+
+    /// Instantiate a new opcode from a u8 without checking if it is valid.
+    ///
+    /// # Safety
+    ///
+    /// All code using `Opcode` values assume that they are valid opcodes, so providing an invalid
+    /// opcode may cause undefined behavior.
+    #[inline]
+    pub unsafe fn new_unchecked(opcode: u8) -> Self {
+        Self(opcode)
+    }
+
+    /// Returns the opcode as a string.
+    #[inline]
+    pub const fn as_str(self) -> &'static str {
+        if let Some(str) = OPCODE_JUMPMAP[self.0 as usize] {
+            str
+        } else {
+            "unknown"
+        }
+    }
+
+    /// Returns the opcode as a u8.
+    #[inline]
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+
+    /// Parses an opcode from its mnemonic, ignoring ASCII case.
+    pub fn parse(name: &str) -> Option<Self> {
+        OPCODE_JUMPMAP
+            .iter()
+            .position(|n| n.is_some_and(|n| n.eq_ignore_ascii_case(name)))
+            .map(|op| Self(op as u8))
+    }
+
+    /// Returns the static information of the opcode.
+    #[inline]
+    pub const fn info(self) -> &'static OpCodeInfo {
+        match OpCodeInfo::of(self.0) {
+            Some(info) => info,
+            None => unreachable!(),
+        }
+    }
+
+    #[inline]
+    #[deprecated(note = "use `new` instead")]
+    #[doc(hidden)]
+    pub const fn try_from_u8(opcode: u8) -> Option<Self> {
+        Self::new(opcode)
+    }
+
+    #[inline]
+    #[deprecated(note = "use `get` instead")]
+    #[doc(hidden)]
+    pub const fn u8(self) -> u8 {
+        self.get()
+    }
+}
+
+/// Static information about an opcode, see [`OpCode::info`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OpCodeInfo {
+    /// Mnemonic of the opcode.
+    pub name: &'static str,
+    /// Number of stack items the opcode pops.
+    pub inputs: u8,
+    /// Number of stack items the opcode pushes.
+    pub outputs: u8,
+    /// Number of immediate bytes following the opcode in the code.
+    ///
+    /// `RJUMPV` has a variable size, this is the size of its count byte.
+    pub immediate_size: u8,
+    /// Static gas charged by the opcode in the latest spec.
+    ///
+    /// Dynamic costs such as memory expansion, cold account or storage access and value transfers
+    /// are charged on top of it.
+    pub base_gas: u64,
+    /// Spec in which the opcode was introduced.
+    pub introduced_in: SpecId,
+    /// Whether the opcode ends the execution of the current frame or, for `RETF`, function.
+    pub terminating: bool,
+}
+
+impl OpCodeInfo {
+    const fn new(op: u8, inputs: u8, outputs: u8, base_gas: u64, introduced_in: SpecId) -> Self {
+        Self {
+            name: match OPCODE_JUMPMAP[op as usize] {
+                Some(name) => name,
+                None => panic!("opcode info for an undefined opcode"),
+            },
+            inputs,
+            outputs,
+            immediate_size: 0,
+            base_gas,
+            introduced_in,
+            terminating: false,
+        }
+    }
+
+    const fn terminating(
+        op: u8,
+        inputs: u8,
+        outputs: u8,
+        base_gas: u64,
+        introduced_in: SpecId,
+    ) -> Self {
+        let mut info = Self::new(op, inputs, outputs, base_gas, introduced_in);
+        info.terminating = true;
+        info
+    }
+
+    const fn immediate(mut self, size: u8) -> Self {
+        self.immediate_size = size;
+        self
+    }
+
+    /// Returns the information of the opcode, or `None` if the byte is not a defined opcode.
+    #[inline]
+    pub const fn of(opcode: u8) -> Option<&'static Self> {
+        OPCODE_INFO_JUMPTABLE[opcode as usize].as_ref()
+    }
+
+    /// Returns whether the opcode is enabled in the given spec.
+    #[inline]
+    pub const fn is_enabled_in(&self, spec_id: SpecId) -> bool {
+        SpecId::enabled(spec_id, self.introduced_in)
+    }
+}
+
+const fn opcode_info(op: u8) -> Option<OpCodeInfo> {
+    use SpecId::*;
+
+    let info = match op {
+        STOP => OpCodeInfo::terminating(op, 0, 0, 0, FRONTIER),
+        ADD => OpCodeInfo::new(op, 2, 1, 3, FRONTIER),
+        SUB => OpCodeInfo::new(op, 2, 1, 3, FRONTIER),
+        MUL => OpCodeInfo::new(op, 2, 1, 5, FRONTIER),
+        DIV => OpCodeInfo::new(op, 2, 1, 5, FRONTIER),
+        SDIV => OpCodeInfo::new(op, 2, 1, 5, FRONTIER),
+        MOD => OpCodeInfo::new(op, 2, 1, 5, FRONTIER),
+        SMOD => OpCodeInfo::new(op, 2, 1, 5, FRONTIER),
+        SIGNEXTEND => OpCodeInfo::new(op, 2, 1, 5, FRONTIER),
+        ADDMOD | MULMOD => OpCodeInfo::new(op, 3, 1, 8, FRONTIER),
+        EXP => OpCodeInfo::new(op, 2, 1, 10, FRONTIER),
+        LT | GT | SLT | SGT | EQ => OpCodeInfo::new(op, 2, 1, 3, FRONTIER),
+        ISZERO => OpCodeInfo::new(op, 1, 1, 3, FRONTIER),
+        AND | OR | XOR => OpCodeInfo::new(op, 2, 1, 3, FRONTIER),
+        NOT => OpCodeInfo::new(op, 1, 1, 3, FRONTIER),
+        BYTE => OpCodeInfo::new(op, 2, 1, 3, FRONTIER),
+        SHL | SHR | SAR => OpCodeInfo::new(op, 2, 1, 3, CONSTANTINOPLE),
+        KECCAK256 => OpCodeInfo::new(op, 2, 1, 30, FRONTIER),
+        ADDRESS => OpCodeInfo::new(op, 0, 1, 2, FRONTIER),
+        BALANCE => OpCodeInfo::new(op, 1, 1, 100, FRONTIER),
+        ORIGIN | CALLER | CALLVALUE => OpCodeInfo::new(op, 0, 1, 2, FRONTIER),
+        CALLDATALOAD => OpCodeInfo::new(op, 1, 1, 3, FRONTIER),
+        CALLDATASIZE => OpCodeInfo::new(op, 0, 1, 2, FRONTIER),
+        CALLDATACOPY => OpCodeInfo::new(op, 3, 0, 3, FRONTIER),
+        CODESIZE => OpCodeInfo::new(op, 0, 1, 2, FRONTIER),
+        CODECOPY => OpCodeInfo::new(op, 3, 0, 3, FRONTIER),
+        GASPRICE => OpCodeInfo::new(op, 0, 1, 2, FRONTIER),
+        EXTCODESIZE => OpCodeInfo::new(op, 1, 1, 100, FRONTIER),
+        EXTCODECOPY => OpCodeInfo::new(op, 4, 0, 100, FRONTIER),
+        RETURNDATASIZE => OpCodeInfo::new(op, 0, 1, 2, BYZANTIUM),
+        RETURNDATACOPY => OpCodeInfo::new(op, 3, 0, 3, BYZANTIUM),
+        EXTCODEHASH => OpCodeInfo::new(op, 1, 1, 100, CONSTANTINOPLE),
+        BLOCKHASH => OpCodeInfo::new(op, 1, 1, 20, FRONTIER),
+        COINBASE | TIMESTAMP | NUMBER | DIFFICULTY | GASLIMIT => {
+            OpCodeInfo::new(op, 0, 1, 2, FRONTIER)
+        }
+        CHAINID => OpCodeInfo::new(op, 0, 1, 2, ISTANBUL),
+        SELFBALANCE => OpCodeInfo::new(op, 0, 1, 5, ISTANBUL),
+        BASEFEE => OpCodeInfo::new(op, 0, 1, 2, LONDON),
+        BLOBHASH => OpCodeInfo::new(op, 1, 1, 3, CANCUN),
+        BLOBBASEFEE => OpCodeInfo::new(op, 0, 1, 2, CANCUN),
+        POP => OpCodeInfo::new(op, 1, 0, 2, FRONTIER),
+        MLOAD => OpCodeInfo::new(op, 1, 1, 3, FRONTIER),
+        MSTORE | MSTORE8 => OpCodeInfo::new(op, 2, 0, 3, FRONTIER),
+        SLOAD => OpCodeInfo::new(op, 1, 1, 100, FRONTIER),
+        SSTORE => OpCodeInfo::new(op, 2, 0, 100, FRONTIER),
+        JUMP => OpCodeInfo::new(op, 1, 0, 8, FRONTIER),
+        JUMPI => OpCodeInfo::new(op, 2, 0, 10, FRONTIER),
+        PC | MSIZE | GAS => OpCodeInfo::new(op, 0, 1, 2, FRONTIER),
+        JUMPDEST => OpCodeInfo::new(op, 0, 0, 1, FRONTIER),
+        TLOAD => OpCodeInfo::new(op, 1, 1, 100, CANCUN),
+        TSTORE => OpCodeInfo::new(op, 2, 0, 100, CANCUN),
+        MCOPY => OpCodeInfo::new(op, 3, 0, 3, CANCUN),
+        PUSH0 => OpCodeInfo::new(op, 0, 1, 2, SHANGHAI),
+        PUSH1..=PUSH32 => OpCodeInfo::new(op, 0, 1, 3, FRONTIER).immediate(op - PUSH0),
+        DUP1..=DUP16 => OpCodeInfo::new(op, op - DUP1 + 1, op - DUP1 + 2, 3, FRONTIER),
+        SWAP1..=SWAP16 => OpCodeInfo::new(op, op - SWAP1 + 2, op - SWAP1 + 2, 3, FRONTIER),
+        LOG0..=LOG4 => OpCodeInfo::new(
+            op,
+            op - LOG0 + 2,
+            0,
+            375 + 375 * (op - LOG0) as u64,
+            FRONTIER,
+        ),
+        RJUMP => OpCodeInfo::new(op, 0, 0, 2, PRAGUE).immediate(2),
+        RJUMPI => OpCodeInfo::new(op, 1, 0, 4, PRAGUE).immediate(2),
+        RJUMPV => OpCodeInfo::new(op, 1, 0, 4, PRAGUE).immediate(1),
+        CALLF => OpCodeInfo::new(op, 0, 0, 5, PRAGUE).immediate(2),
+        RETF => OpCodeInfo::terminating(op, 0, 0, 3, PRAGUE),
+        CREATE => OpCodeInfo::new(op, 3, 1, 32000, FRONTIER),
+        CALL | CALLCODE => OpCodeInfo::new(op, 7, 1, 100, FRONTIER),
+        RETURN => OpCodeInfo::terminating(op, 2, 0, 0, FRONTIER),
+        DELEGATECALL => OpCodeInfo::new(op, 6, 1, 100, HOMESTEAD),
+        CREATE2 => OpCodeInfo::new(op, 4, 1, 32000, PETERSBURG),
+        STATICCALL => OpCodeInfo::new(op, 6, 1, 100, BYZANTIUM),
+        REVERT => OpCodeInfo::terminating(op, 2, 0, 0, BYZANTIUM),
+        INVALID => OpCodeInfo::terminating(op, 0, 0, 0, FRONTIER),
+        SELFDESTRUCT => OpCodeInfo::terminating(op, 1, 0, 5000, FRONTIER),
+        _ => return None,
+    };
+    Some(info)
+}
+
+/// Maps each opcode to its [`OpCodeInfo`].
+pub const OPCODE_INFO_JUMPTABLE: [Option<OpCodeInfo>; 256] = {
+    let mut map = [None; 256];
+    let mut i = 0;
+    while i < 256 {
+        map[i] = opcode_info(i as u8);
+        assert!(
+            map[i].is_some() == OPCODE_JUMPMAP[i].is_some(),
+            "opcode info must be defined for every opcode"
+        );
+        i += 1;
+    }
+    map
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mnemonics() {
+        assert_eq!(OpCode::parse("PUSH1").map(OpCode::get), Some(PUSH1));
+        assert_eq!(OpCode::parse("keccak256").map(OpCode::get), Some(KECCAK256));
+        assert_eq!(OpCode::parse("PUSH33"), None);
+        for op in 0..=u8::MAX {
+            if let Some(opcode) = OpCode::new(op) {
+                assert_eq!(OpCode::parse(opcode.as_str()), Some(opcode));
+            }
+        }
+    }
+
+    #[test]
+    fn opcode_info() {
+        let info = OpCode::new(SWAP3).unwrap().info();
+        assert_eq!((info.name, info.inputs, info.outputs), ("SWAP3", 4, 4));
+
+        let info = OpCodeInfo::of(PUSH32).unwrap();
+        assert_eq!(info.immediate_size, 32);
+        assert_eq!(info.base_gas, 3);
+
+        let info = OpCodeInfo::of(LOG2).unwrap();
+        assert_eq!((info.inputs, info.base_gas), (4, 1125));
+
+        let info = OpCodeInfo::of(MCOPY).unwrap();
+        assert!(info.is_enabled_in(SpecId::CANCUN));
+        assert!(!info.is_enabled_in(SpecId::SHANGHAI));
+
+        assert!(OpCodeInfo::of(REVERT).unwrap().terminating);
+        assert!(!OpCodeInfo::of(JUMP).unwrap().terminating);
+        assert_eq!(OpCodeInfo::of(0x0C), None);
+    }
+}
@@ -1,5 +1,5 @@
-use crate::Env;
-use alloc::vec::Vec;
+use crate::{Address, Env};
+use alloc::{collections::BTreeMap, vec::Vec};
 use core::fmt;
 
 /// A precompile operation result.
@@ -32,8 +32,66 @@ pub enum PrecompileError {
     BlobMismatchedVersion,
     /// The proof verification failed.
     BlobVerifyKzgProofFailed,
+    // BLS12-381 errors
+    /// The input length is not valid for the precompile.
+    Bls12381InputLength,
+    /// The 16 bytes padding of a field element are not zero.
+    Bls12381FpPaddingInvalid,
+    /// The field element is not less than the modulus.
+    Bls12381FpNotCanonical,
+    /// The point is not on the G1 curve.
+    Bls12381G1NotOnCurve,
+    /// The point is not in the G1 subgroup.
+    Bls12381G1NotInSubgroup,
+    /// The point is not on the G2 curve.
+    Bls12381G2NotOnCurve,
+    /// The point is not in the G2 subgroup.
+    Bls12381G2NotInSubgroup,
+    /// Input is larger than the configured limit, see [PrecompileInputLimits].
+    InputTooLarge {
+        limit: usize,
+        size: usize,
+    },
 }
 
+/// Maximum input sizes of expensive precompiles, checked before the gas cost is computed.
+///
+/// Limits are not part of the consensus rules, they are meant for sandboxed execution where
+/// pathological inputs would take long to compute even if their gas is paid for. By default
+/// no limits are set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrecompileInputLimits {
+    /// Maximum sum of the base, exponent and modulus lengths declared in the modexp input.
+    pub modexp: Option<usize>,
+    /// Maximum input length of the bn128 pairing check.
+    pub bn128_pair: Option<usize>,
+}
+
+/// Gas cost of a precompile replacing the cost computed by the precompile, for chains that
+/// reprice precompiles.
+///
+/// Cost is `base + per_word * words`, where `words` is the input length in 32 byte words
+/// rounded up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrecompileGasCost {
+    pub base: u64,
+    pub per_word: u64,
+}
+
+impl PrecompileGasCost {
+    /// Returns the cost of the call with the given input length.
+    pub fn cost(&self, input_len: usize) -> u64 {
+        let words = (input_len as u64).div_ceil(32);
+        self.base
+            .saturating_add(self.per_word.saturating_mul(words))
+    }
+}
+
+/// Gas costs of the precompiles at the given addresses, see [PrecompileGasCost].
+pub type PrecompileGasOverrides = BTreeMap<Address, PrecompileGasCost>;
+
 #[cfg(feature = "std")]
 impl std::error::Error for PrecompileError {}
 
@@ -60,6 +118,24 @@ impl fmt::Display for PrecompileError {
             PrecompileError::BlobVerifyKzgProofFailed => {
                 write!(f, "verifying blob kzg proof failed")
             }
+            PrecompileError::Bls12381InputLength => write!(f, "invalid bls12-381 input length"),
+            PrecompileError::Bls12381FpPaddingInvalid => {
+                write!(f, "invalid padding of bls12-381 field element")
+            }
+            PrecompileError::Bls12381FpNotCanonical => {
+                write!(f, "bls12-381 field element not less than modulus")
+            }
+            PrecompileError::Bls12381G1NotOnCurve => write!(f, "point not on bls12-381 g1 curve"),
+            PrecompileError::Bls12381G1NotInSubgroup => {
+                write!(f, "point not in bls12-381 g1 subgroup")
+            }
+            PrecompileError::Bls12381G2NotOnCurve => write!(f, "point not on bls12-381 g2 curve"),
+            PrecompileError::Bls12381G2NotInSubgroup => {
+                write!(f, "point not in bls12-381 g2 subgroup")
+            }
+            PrecompileError::InputTooLarge { limit, size } => {
+                write!(f, "input size {size} exceeds the limit of {limit}")
+            }
         }
     }
 }
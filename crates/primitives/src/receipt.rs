@@ -0,0 +1,116 @@
+use crate::{logs_bloom, Bloom, ExecutionResult, Log};
+use alloc::vec::Vec;
+use alloy_rlp::{Encodable, RlpDecodable, RlpEncodable};
+
+/// Consensus receipt of a transaction.
+///
+/// RLP encoding is the receipt of legacy transactions, see [Receipt::encoded] for typed ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, RlpDecodable, RlpEncodable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    /// [EIP-658] status, true if the transaction succeeded.
+    ///
+    /// [EIP-658]: https://eips.ethereum.org/EIPS/eip-658
+    pub success: bool,
+    /// Gas used by this and the preceding transactions of the block.
+    pub cumulative_gas_used: u64,
+    /// Bloom of the logs addresses and topics.
+    pub logs_bloom: Bloom,
+    /// Logs emitted by the transaction.
+    pub logs: Vec<Log>,
+}
+
+impl Receipt {
+    /// Creates the receipt of the transaction with the given result.
+    ///
+    /// `cumulative_gas_used` includes the gas used by the transaction, see
+    /// [crate::BlockSummary::cumulative_gas_used].
+    pub fn new(result: ExecutionResult, cumulative_gas_used: u64) -> Self {
+        let success = result.is_success();
+        let logs = result.into_logs();
+        Self {
+            success,
+            cumulative_gas_used,
+            logs_bloom: logs_bloom(&logs),
+            logs,
+        }
+    }
+
+    /// Returns the [EIP-2718] encoding of the receipt of a transaction with the given type,
+    /// without the type prefix for legacy transactions.
+    ///
+    /// [EIP-2718]: https://eips.ethereum.org/EIPS/eip-2718
+    pub fn encoded(&self, tx_type: u8) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.length() + 1);
+        if tx_type != 0 {
+            out.push(tx_type);
+        }
+        self.encode(&mut out);
+        out
+    }
+}
+
+/// Creates receipts of the transactions of a block from their results, in order.
+pub fn receipts(results: impl IntoIterator<Item = ExecutionResult>) -> Vec<Receipt> {
+    let mut cumulative_gas_used = 0;
+    results
+        .into_iter()
+        .map(|result| {
+            cumulative_gas_used += result.gas_used();
+            Receipt::new(result, cumulative_gas_used)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Address, BloomInput, Bytes, Eval, GasBreakdown, Output, B256};
+    use alloc::vec;
+    use alloy_rlp::Decodable;
+
+    #[test]
+    fn receipts_of_block() {
+        let gas = GasBreakdown {
+            intrinsic: 21_000,
+            ..Default::default()
+        };
+        let log = Log {
+            address: Address::with_last_byte(0x10),
+            topics: vec![B256::with_last_byte(1)],
+            data: Bytes::from_static(&[1]),
+        };
+        let receipts = receipts([
+            ExecutionResult::Success {
+                reason: Eval::Stop,
+                gas,
+                logs: vec![log.clone()],
+                output: Output::Call(Bytes::new()),
+            },
+            ExecutionResult::revert(gas, Bytes::new()),
+        ]);
+
+        assert!(receipts[0].success);
+        assert_eq!(receipts[0].cumulative_gas_used, 21_000);
+        assert!(receipts[0]
+            .logs_bloom
+            .contains_input(BloomInput::Raw(log.address.as_slice())));
+        assert_eq!(receipts[0].logs, vec![log]);
+
+        assert!(!receipts[1].success);
+        assert_eq!(receipts[1].cumulative_gas_used, 42_000);
+        assert_eq!(receipts[1].logs_bloom, Bloom::ZERO);
+
+        // Failed status is encoded as an empty string.
+        let encoded = receipts[1].encoded(0);
+        assert_eq!(&encoded[..6], &[0xf9, 0x01, 0x08, 0x80, 0x82, 0xa4]);
+        let typed = receipts[1].encoded(2);
+        assert_eq!((typed[0], &typed[1..]), (2, &encoded[..]));
+        for receipt in receipts {
+            assert_eq!(
+                Receipt::decode(&mut receipt.encoded(0).as_slice()),
+                Ok(receipt)
+            );
+        }
+    }
+}
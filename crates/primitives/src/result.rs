@@ -1,5 +1,5 @@
-use crate::{Address, Bytes, Log, State, U256};
-use alloc::{boxed::Box, vec::Vec};
+use crate::{logs_bloom, Address, Bloom, Bytes, HashSet, Log, LogFilter, State, U256};
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
 use core::fmt;
 
 /// Result of EVM execution.
@@ -15,6 +15,189 @@ pub struct ResultAndState {
     pub result: ExecutionResult,
     /// State that got updated
     pub state: State,
+    /// Gas limit requested by the transaction, set only if it was clamped to
+    /// [crate::CfgEnv::tx_gas_cap].
+    pub requested_gas_limit: Option<u64>,
+    /// Stack and memory at the halt, set only if execution halted and capturing is enabled with
+    /// `CfgEnv::halt_snapshot`.
+    pub halt_snapshot: Option<HaltSnapshot>,
+    /// Fees charged to the caller.
+    pub fees: TxFees,
+    /// Numbers of executed instructions, set only with the `instruction-count` feature of the
+    /// interpreter.
+    pub instruction_counts: Option<InstructionCounts>,
+    /// Accounts and storage slots read and written by the transaction.
+    pub access: AccessSummary,
+    /// Counters of the journaled state, set only with the `journal-metrics` feature of revm.
+    pub journal_metrics: Option<JournalMetrics>,
+}
+
+/// Accounts and storage slots read and written by a transaction.
+///
+/// Reads include the accesses of reverted frames, unlike [ResultAndState::state]. Writes are
+/// the accounts and slots whose values differ at the end of the transaction from the loaded
+/// ones, a value changed and then restored is not a write.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccessSummary {
+    /// Accounts whose balance, nonce or code was read.
+    pub accounts_read: HashSet<Address>,
+    /// Storage slots that were read, including the slots read by `SSTORE`.
+    pub slots_read: HashSet<(Address, U256)>,
+    /// Accounts whose balance, nonce or code changed, or that were created or destroyed.
+    pub accounts_written: HashSet<Address>,
+    /// Storage slots whose value changed.
+    pub slots_written: HashSet<(Address, U256)>,
+}
+
+impl AccessSummary {
+    /// Returns true if the transaction of `later` read anything written by this transaction,
+    /// so `later` depends on it.
+    pub fn is_read_by(&self, later: &AccessSummary) -> bool {
+        self.accounts_written
+            .iter()
+            .any(|address| later.accounts_read.contains(address))
+            || self
+                .slots_written
+                .iter()
+                .any(|slot| later.slots_read.contains(slot))
+    }
+}
+
+/// Numbers of instructions executed by the transaction, over all its frames.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstructionCounts {
+    /// Number of executed instructions.
+    pub steps: u64,
+    /// Number of executions of every opcode, indexed by opcode.
+    pub opcodes: Vec<u64>,
+}
+
+impl Default for InstructionCounts {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            steps: 0,
+            opcodes: vec![0; 256],
+        }
+    }
+}
+
+impl InstructionCounts {
+    /// Returns the number of executions of the opcode.
+    #[inline]
+    pub fn count(&self, opcode: u8) -> u64 {
+        self.opcodes
+            .get(opcode as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Adds the numbers of executions of every opcode, indexed by opcode.
+    pub fn add(&mut self, opcodes: &[u64; 256]) {
+        self.opcodes.resize(256, 0);
+        for (total, count) in self.opcodes.iter_mut().zip(opcodes) {
+            *total += count;
+            self.steps += count;
+        }
+    }
+}
+
+/// Counters of the accesses to the state made by a transaction, including the ones of
+/// reverted frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JournalMetrics {
+    /// Accounts loaded from the database.
+    pub cold_account_loads: u64,
+    /// Accounts that were already loaded, including precompiles.
+    pub warm_account_loads: u64,
+    /// Storage slots loaded from the database, including the loads of `SSTORE`.
+    pub cold_storage_loads: u64,
+    /// Storage slots that were already loaded, including the loads of `SSTORE`.
+    pub warm_storage_loads: u64,
+    /// `SSTORE`s changing a zero slot to a non-zero value.
+    pub sstore_sets: u64,
+    /// `SSTORE`s changing a non-zero slot to another non-zero value.
+    pub sstore_resets: u64,
+    /// `SSTORE`s changing a non-zero slot to zero.
+    pub sstore_clears: u64,
+    /// Accounts created by `CREATE`, `CREATE2` or a create transaction.
+    pub account_creations: u64,
+}
+
+/// Fees charged to the caller of the transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TxFees {
+    /// Price paid per unit of execution gas.
+    pub effective_gas_price: U256,
+    /// [EIP-4844] blob gas used by the transaction, zero before Cancun.
+    ///
+    /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
+    pub blob_gas_used: u64,
+    /// Price paid per unit of blob gas, zero before Cancun.
+    pub blob_gas_price: u128,
+    /// L1 gas of the transaction data, zero for deposit transactions.
+    #[cfg(feature = "optimism")]
+    pub l1_gas_used: U256,
+    /// L1 data fee charged to the caller, zero for deposit transactions.
+    #[cfg(feature = "optimism")]
+    pub l1_fee: U256,
+    /// Operator fee charged to the caller for the used gas, zero for deposit transactions and
+    /// before Isthmus.
+    #[cfg(feature = "optimism")]
+    pub operator_fee: U256,
+}
+
+impl TxFees {
+    /// Returns the fee paid for the blob gas.
+    #[inline]
+    pub fn blob_fee(&self) -> U256 {
+        U256::from(self.blob_gas_price).saturating_mul(U256::from(self.blob_gas_used))
+    }
+
+    /// Returns the fee paid for the used execution gas, without the blob fee.
+    #[inline]
+    pub fn gas_fee(&self, gas_used: u64) -> U256 {
+        self.effective_gas_price
+            .saturating_mul(U256::from(gas_used))
+    }
+
+    /// Returns the total fee paid by the caller for the used execution gas, including the blob
+    /// fee and, on Optimism, the L1 data fee and the operator fee.
+    #[inline]
+    pub fn total_fee(&self, gas_used: u64) -> U256 {
+        let fee = self.gas_fee(gas_used).saturating_add(self.blob_fee());
+        #[cfg(feature = "optimism")]
+        let fee = fee
+            .saturating_add(self.l1_fee)
+            .saturating_add(self.operator_fee);
+        fee
+    }
+}
+
+/// Bounded snapshot of the transaction's frame at the moment it halted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HaltSnapshot {
+    /// Address of the executed contract.
+    pub address: Address,
+    /// Program counter of the instruction that halted.
+    pub pc: usize,
+    /// Opcode of the instruction that halted.
+    pub opcode: u8,
+    /// Topmost stack items, last item is the top of the stack.
+    pub stack: Vec<U256>,
+    /// Length of the whole stack.
+    pub stack_len: usize,
+    /// Offset of `memory` in the frame memory.
+    pub memory_offset: usize,
+    /// Window at the end of the frame memory, where the most recently written data is.
+    pub memory: Bytes,
+    /// Length of the whole frame memory.
+    pub memory_len: usize,
 }
 
 /// Result of a transaction execution.
@@ -24,22 +207,37 @@ pub enum ExecutionResult {
     /// Returned successfully
     Success {
         reason: Eval,
-        gas_used: u64,
-        gas_refunded: u64,
+        gas: GasBreakdown,
         logs: Vec<Log>,
         output: Output,
     },
     /// Reverted by `REVERT` opcode that doesn't spend all gas.
-    Revert { gas_used: u64, output: Bytes },
+    Revert {
+        gas: GasBreakdown,
+        output: Bytes,
+        /// Reason decoded from the output, `None` if the output is shorter than a selector.
+        #[cfg(feature = "revert_reason")]
+        reason: Option<RevertReason>,
+    },
     /// Reverted for various reasons and spend all gas.
     Halt {
         reason: Halt,
-        /// Halting will spend all the gas, and will be equal to gas_limit.
-        gas_used: u64,
+        /// Halting will spend all the gas, used gas will be equal to gas_limit.
+        gas: GasBreakdown,
     },
 }
 
 impl ExecutionResult {
+    /// Creates a reverted result with the given output.
+    pub fn revert(gas: GasBreakdown, output: Bytes) -> Self {
+        Self::Revert {
+            gas,
+            #[cfg(feature = "revert_reason")]
+            reason: RevertReason::decode(&output),
+            output,
+        }
+    }
+
     /// Returns if transaction execution is successful.
     /// 1 indicates success, 0 indicates revert.
     /// <https://eips.ethereum.org/EIPS/eip-658>
@@ -82,6 +280,15 @@ impl ExecutionResult {
         }
     }
 
+    /// Returns logs that match the filter, if execution is not successful there are none.
+    pub fn filter_logs<'a>(&'a self, filter: &'a LogFilter) -> impl Iterator<Item = &'a Log> {
+        let logs = match self {
+            Self::Success { logs, .. } => logs.as_slice(),
+            _ => &[],
+        };
+        logs.iter().filter(|log| filter.matches(log))
+    }
+
     /// Consumes the type and returns logs, if execution is not successful, function will return empty vec.
     pub fn into_logs(self) -> Vec<Log> {
         match self {
@@ -90,12 +297,169 @@ impl ExecutionResult {
         }
     }
 
+    /// Returns logs bloom of the transaction receipt.
+    ///
+    /// Bloom is empty if execution is not successful, as there are no logs.
+    pub fn logs_bloom(&self) -> Bloom {
+        match self {
+            Self::Success { logs, .. } => logs_bloom(logs),
+            _ => Bloom::ZERO,
+        }
+    }
+
+    /// Returns the reason of the revert, `None` if execution didn't revert or the output is
+    /// shorter than a selector.
+    #[cfg(feature = "revert_reason")]
+    pub fn revert_reason(&self) -> Option<&RevertReason> {
+        match self {
+            Self::Revert { reason, .. } => reason.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the gas breakdown of the transaction.
+    pub fn gas(&self) -> &GasBreakdown {
+        let (Self::Success { gas, .. } | Self::Revert { gas, .. } | Self::Halt { gas, .. }) = self;
+        gas
+    }
+
+    /// Returns the gas used by the transaction, with refund subtracted.
     pub fn gas_used(&self) -> u64 {
-        let (Self::Success { gas_used, .. }
-        | Self::Revert { gas_used, .. }
-        | Self::Halt { gas_used, .. }) = self;
+        self.gas().used()
+    }
 
-        *gas_used
+    /// Returns the gas refunded to the caller for cleared storage.
+    pub fn gas_refunded(&self) -> u64 {
+        self.gas().refunded
+    }
+}
+
+/// Reason of a revert decoded from the revert output.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RevertReason {
+    /// `Error(string)` with its message.
+    Error(String),
+    /// `Panic(uint256)` with its code, for example `0x11` for an arithmetic overflow.
+    Panic(U256),
+    /// Custom error that is not decoded, or a standard one with invalid ABI encoding.
+    Custom {
+        /// Selector of the error.
+        selector: [u8; 4],
+        /// ABI encoded arguments of the error.
+        data: Bytes,
+    },
+}
+
+impl RevertReason {
+    /// Selector of `Error(string)`.
+    pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+    /// Selector of `Panic(uint256)`.
+    pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+    /// Decodes the revert output, `None` if it is shorter than a selector.
+    pub fn decode(output: &Bytes) -> Option<Self> {
+        let selector: [u8; 4] = output.get(..4)?.try_into().ok()?;
+        let data = &output[4..];
+        let reason = match selector {
+            Self::ERROR_SELECTOR => decode_error_message(data).map(|m| Self::Error(m.into())),
+            Self::PANIC_SELECTOR if data.len() == 32 => {
+                Some(Self::Panic(U256::from_be_slice(data)))
+            }
+            _ => None,
+        };
+        Some(reason.unwrap_or(Self::Custom {
+            selector,
+            data: output.slice(4..),
+        }))
+    }
+}
+
+/// Decodes the ABI encoded string argument of `Error(string)`.
+fn decode_error_message(data: &[u8]) -> Option<&str> {
+    let read_usize = |offset: usize| -> Option<usize> {
+        let word = U256::try_from_be_slice(data.get(offset..offset.checked_add(32)?)?)?;
+        usize::try_from(word).ok()
+    };
+    let offset = read_usize(0)?;
+    let len = read_usize(offset)?;
+    let start = offset.checked_add(32)?;
+    let message = data.get(start..start.checked_add(len)?)?;
+    core::str::from_utf8(message).ok()
+}
+
+/// How the gas limit of a transaction was spent.
+///
+/// The parts add up to the gas limit: `intrinsic + execution + returned`. Refund is a part of
+/// the intrinsic and execution gas that is given back to the caller.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasBreakdown {
+    /// Gas charged before execution, for the transaction data, access list and creation.
+    pub intrinsic: u64,
+    /// Gas spent by the execution of the transaction.
+    pub execution: u64,
+    /// Gas refunded for cleared storage, after the refund cap was applied.
+    pub refunded: u64,
+    /// Gas left unspent by the execution.
+    pub returned: u64,
+}
+
+impl GasBreakdown {
+    /// Returns the gas used by the transaction, with refund subtracted.
+    ///
+    /// Saturates at zero if the refund is larger than the spent gas, which the EVM never reports.
+    #[inline]
+    pub const fn used(&self) -> u64 {
+        self.spent().saturating_sub(self.refunded)
+    }
+
+    /// Returns the gas spent before the refund.
+    #[inline]
+    pub const fn spent(&self) -> u64 {
+        self.intrinsic.saturating_add(self.execution)
+    }
+
+    /// Returns the gas limit of the transaction.
+    #[inline]
+    pub const fn limit(&self) -> u64 {
+        self.spent().saturating_add(self.returned)
+    }
+}
+
+/// Aggregated outcome of multiple transactions executed in the same block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockSummary {
+    /// Gas used by all transactions.
+    pub gas_used: u64,
+    /// Cumulative gas used after each transaction, as it is found in receipts.
+    pub cumulative_gas_used: Vec<u64>,
+    /// Number of successful transactions.
+    pub successful: usize,
+    /// Logs bloom of the block, union of the transactions logs blooms.
+    pub logs_bloom: Bloom,
+}
+
+impl BlockSummary {
+    /// Adds transaction result to the summary.
+    pub fn push(&mut self, result: &ExecutionResult) {
+        self.gas_used += result.gas_used();
+        self.cumulative_gas_used.push(self.gas_used);
+        self.logs_bloom.accrue_bloom(&result.logs_bloom());
+        if result.is_success() {
+            self.successful += 1;
+        }
+    }
+
+    /// Returns number of transactions in the summary.
+    pub fn len(&self) -> usize {
+        self.cumulative_gas_used.len()
+    }
+
+    /// Returns true if there are no transactions in the summary.
+    pub fn is_empty(&self) -> bool {
+        self.cumulative_gas_used.is_empty()
     }
 }
 
@@ -224,6 +588,8 @@ pub enum InvalidTransaction {
     /// wiping state and only increasing the nonce + persisting the mint value.
     #[cfg(feature = "optimism")]
     HaltedDepositPostRegolith,
+    /// Chain specific transaction kind is not supported by the EVM handler.
+    UnsupportedChainTxKind(u8),
 }
 
 #[cfg(feature = "std")]
@@ -296,6 +662,9 @@ impl fmt::Display for InvalidTransaction {
                     "Deposit transaction halted post-regolith. Error will be bubbled up to main return handler."
                 )
             }
+            InvalidTransaction::UnsupportedChainTxKind(kind) => {
+                write!(f, "Unsupported chain transaction kind {}", kind)
+            }
         }
     }
 }
@@ -314,6 +683,10 @@ pub enum InvalidHeader {
     PrevrandaoNotSet,
     /// `excess_blob_gas` is not set for Cancun and above.
     ExcessBlobGasNotSet,
+    /// `parent_beacon_block_root` is not set for Cancun and above.
+    ParentBeaconBlockRootNotSet,
+    /// Block gas limit doesn't fit in `u64`, see [crate::CfgEnv::block_gas_limit_overflow].
+    GasLimitOverflow,
 }
 
 #[cfg(feature = "std")]
@@ -324,6 +697,10 @@ impl fmt::Display for InvalidHeader {
         match self {
             InvalidHeader::PrevrandaoNotSet => write!(f, "Prevrandao not set"),
             InvalidHeader::ExcessBlobGasNotSet => write!(f, "Excess blob gas not set"),
+            InvalidHeader::ParentBeaconBlockRootNotSet => {
+                write!(f, "Parent beacon block root not set")
+            }
+            InvalidHeader::GasLimitOverflow => write!(f, "Block gas limit overflows u64"),
         }
     }
 }
@@ -359,6 +736,10 @@ pub enum Halt {
     CreateContractStartingWithEF,
     /// EIP-3860: Limit and meter initcode. Initcode size limit exceeded.
     CreateInitcodeSizeLimit,
+    /// EOF init code is invalid or it returned invalid EOF code.
+    InvalidEOFInitCode,
+    /// EIP-4750: EOF function return stack overflow.
+    EOFFunctionStackOverflow,
 
     /* Internal Halts that can be only found inside Inspector */
     OverflowPayment,
@@ -370,6 +751,9 @@ pub enum Halt {
     /* Optimism errors */
     #[cfg(feature = "optimism")]
     FailedDeposit,
+
+    /// Precompile input exceeds [crate::CfgEnv::precompile_input_limits].
+    PrecompileInputTooLarge,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -387,3 +771,151 @@ pub enum OutOfGasError {
     // i.e. in `as_usize_or_fail`
     InvalidOperand,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn total_fee_includes_all_fees() {
+        let fees = TxFees {
+            effective_gas_price: U256::from(2),
+            blob_gas_used: 3,
+            blob_gas_price: 5,
+            #[cfg(feature = "optimism")]
+            l1_gas_used: U256::from(48),
+            #[cfg(feature = "optimism")]
+            l1_fee: U256::from(100),
+            #[cfg(feature = "optimism")]
+            operator_fee: U256::from(1_000),
+        };
+        let expected = 2 * 21_000 + 3 * 5;
+        #[cfg(feature = "optimism")]
+        let expected = expected + 100 + 1_000;
+        assert_eq!(fees.total_fee(21_000), U256::from(expected));
+    }
+
+    #[test]
+    fn gas_breakdown_saturates() {
+        let gas = GasBreakdown {
+            intrinsic: 21_000,
+            execution: 5_000,
+            refunded: 4_800,
+            returned: 4_000,
+        };
+        assert_eq!(
+            (gas.used(), gas.spent(), gas.limit()),
+            (21_200, 26_000, 30_000)
+        );
+
+        let gas = GasBreakdown {
+            intrinsic: 1,
+            execution: u64::MAX,
+            refunded: u64::MAX,
+            returned: 1,
+        };
+        assert_eq!(
+            (gas.used(), gas.spent(), gas.limit()),
+            (0, u64::MAX, u64::MAX)
+        );
+        let gas = GasBreakdown {
+            refunded: 1,
+            ..Default::default()
+        };
+        assert_eq!(gas.used(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use crate::{Account, B256};
+
+        let address = Address::repeat_byte(0x11);
+        let gas = GasBreakdown {
+            intrinsic: 21_000,
+            execution: 100,
+            refunded: 0,
+            returned: 0,
+        };
+        let mut result = ResultAndState {
+            result: ExecutionResult::Success {
+                reason: Eval::Return,
+                gas,
+                logs: vec![Log {
+                    address,
+                    topics: vec![B256::repeat_byte(0x22)],
+                    data: Bytes::from_static(&[1, 2]),
+                }],
+                output: Output::Create(Bytes::from_static(&[0x00]), Some(address)),
+            },
+            state: State::from_iter([(address, Account::new_not_existing())]),
+            requested_gas_limit: Some(30_000_000),
+            halt_snapshot: None,
+            fees: TxFees::default(),
+            instruction_counts: Some(InstructionCounts::default()),
+            access: AccessSummary::default(),
+            journal_metrics: Some(JournalMetrics::default()),
+        };
+        result.access.accounts_read.insert(address);
+        result.access.slots_written.insert((address, U256::from(1)));
+        crate::utilities::assert_serde_round_trip(&result);
+
+        for result in [
+            ExecutionResult::revert(gas, Bytes::from_static(&[0xde, 0xad])),
+            ExecutionResult::Halt {
+                reason: Halt::OutOfGas(OutOfGasError::Memory),
+                gas,
+            },
+        ] {
+            crate::utilities::assert_serde_round_trip(&result);
+        }
+    }
+
+    #[test]
+    fn revert_reason_decoding() {
+        let error = Bytes::from(hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000004"
+            "6f6f707300000000000000000000000000000000000000000000000000000000"
+        ));
+        assert_eq!(
+            RevertReason::decode(&error),
+            Some(RevertReason::Error("oops".into()))
+        );
+
+        let panic = Bytes::from(hex!(
+            "4e487b71"
+            "0000000000000000000000000000000000000000000000000000000000000011"
+        ));
+        assert_eq!(
+            RevertReason::decode(&panic),
+            Some(RevertReason::Panic(U256::from(0x11)))
+        );
+
+        // Invalid encodings of standard errors are kept raw, like custom errors.
+        for output in [&hex!("4e487b7111")[..], &hex!("deadbeef01")[..]] {
+            let output = Bytes::copy_from_slice(output);
+            assert_eq!(
+                RevertReason::decode(&output),
+                Some(RevertReason::Custom {
+                    selector: output[..4].try_into().unwrap(),
+                    data: output.slice(4..),
+                })
+            );
+        }
+        assert_eq!(RevertReason::decode(&Bytes::from_static(&[1, 2, 3])), None);
+    }
+
+    #[cfg(feature = "revert_reason")]
+    #[test]
+    fn revert_carries_reason() {
+        let output = Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]);
+        let result = ExecutionResult::revert(GasBreakdown::default(), output);
+        assert!(matches!(
+            result.revert_reason(),
+            Some(RevertReason::Custom { selector, .. }) if *selector == [0xde, 0xad, 0xbe, 0xef]
+        ));
+    }
+}
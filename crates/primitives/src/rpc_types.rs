@@ -0,0 +1,353 @@
+//! Conversions between the environment and results of the EVM and the RPC types of
+//! [`alloy_rpc_types`].
+//!
+//! Transactions of all Ethereum types are supported, including the blob fields of EIP-4844
+//! transactions.
+
+use crate::{
+    Address, BlobExcessGasAndPrice, BlockEnv, Bytes, ExecutionResult, Log, Output, TransactTo,
+    TxEnv, B256, U256,
+};
+use alloy_rpc_types as rpc;
+use core::fmt;
+
+/// Errors converting an RPC type to the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpcConversionError {
+    /// The value of the field doesn't fit in `u64`.
+    Overflow(&'static str),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RpcConversionError {}
+
+impl fmt::Display for RpcConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow(field) => write!(f, "Field `{field}` overflows u64"),
+        }
+    }
+}
+
+impl TryFrom<&rpc::Transaction> for TxEnv {
+    type Error = RpcConversionError;
+
+    fn try_from(tx: &rpc::Transaction) -> Result<Self, Self::Error> {
+        // EIP-1559 transactions pay at most the fee cap, others the gas price.
+        let (gas_price, gas_priority_fee) = match tx.max_fee_per_gas {
+            Some(max_fee) => (
+                U256::from(max_fee),
+                Some(U256::from(tx.max_priority_fee_per_gas.unwrap_or_default())),
+            ),
+            None => (U256::from(tx.gas_price.unwrap_or_default()), None),
+        };
+        let access_list = tx
+            .access_list
+            .as_ref()
+            .map(|list| {
+                list.0
+                    .iter()
+                    .map(|item| {
+                        let keys = item
+                            .storage_keys
+                            .iter()
+                            .map(|key| U256::from_be_bytes(key.0))
+                            .collect();
+                        (address(item.address), keys)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let blob_hashes = tx
+            .blob_versioned_hashes
+            .iter()
+            .flatten()
+            .map(|hash| B256::from(hash.0))
+            .collect();
+
+        Ok(TxEnv {
+            caller: address(tx.from),
+            gas_limit: to_u64(tx.gas, "gas")?,
+            gas_price,
+            transact_to: match tx.to {
+                Some(to) => TransactTo::Call(address(to)),
+                None => TransactTo::create(),
+            },
+            value: U256::from_limbs(tx.value.into_limbs()),
+            data: Bytes(tx.input.0.clone()),
+            nonce: Some(tx.nonce),
+            chain_id: tx.chain_id,
+            access_list,
+            gas_priority_fee,
+            blob_hashes,
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas.map(U256::from),
+            ..Default::default()
+        })
+    }
+}
+
+impl<T> TryFrom<&rpc::Block<T>> for BlockEnv {
+    type Error = RpcConversionError;
+
+    fn try_from(block: &rpc::Block<T>) -> Result<Self, Self::Error> {
+        let header = &block.header;
+        let blob_excess_gas_and_price = header
+            .excess_blob_gas
+            .map(|excess| to_u64(excess, "excessBlobGas").map(BlobExcessGasAndPrice::new))
+            .transpose()?;
+        Ok(BlockEnv {
+            number: U256::from(header.number.unwrap_or_default()),
+            coinbase: address(header.miner),
+            timestamp: U256::from(header.timestamp),
+            gas_limit: U256::from(header.gas_limit),
+            basefee: U256::from(header.base_fee_per_gas.unwrap_or_default()),
+            difficulty: U256::from_limbs(header.difficulty.into_limbs()),
+            prevrandao: header.mix_hash.map(|hash| B256::from(hash.0)),
+            blob_excess_gas_and_price,
+            parent_beacon_block_root: header
+                .parent_beacon_block_root
+                .map(|root| B256::from(root.0)),
+            parent_hash: Some(B256::from(header.parent_hash.0)),
+        })
+    }
+}
+
+impl From<&ExecutionResult> for rpc::TransactionReceipt {
+    /// Fills the fields known from the execution into a legacy receipt. The type of the
+    /// transaction, the transaction itself and its position in the block, and
+    /// `cumulative_gas_used` of the previous transactions are left for the caller.
+    fn from(result: &ExecutionResult) -> Self {
+        let contract_address = match result {
+            ExecutionResult::Success {
+                output: Output::Create(_, address),
+                ..
+            } => address.map(|address| address.0 .0.into()),
+            _ => None,
+        };
+        let gas_used = u128::from(result.gas_used());
+        rpc::TransactionReceipt {
+            inner: rpc::ReceiptEnvelope::Legacy(rpc::ReceiptWithBloom {
+                receipt: rpc::Receipt {
+                    status: result.is_success().into(),
+                    cumulative_gas_used: gas_used,
+                    logs: result.logs().iter().map(rpc_log).collect(),
+                },
+                logs_bloom: result.logs_bloom().0 .0.into(),
+            }),
+            transaction_hash: Default::default(),
+            transaction_index: None,
+            block_hash: None,
+            block_number: None,
+            gas_used,
+            effective_gas_price: 0,
+            blob_gas_used: None,
+            blob_gas_price: None,
+            from: Default::default(),
+            to: None,
+            contract_address,
+            state_root: None,
+        }
+    }
+}
+
+impl From<&Log> for rpc::Log {
+    fn from(log: &Log) -> Self {
+        rpc_log(log)
+    }
+}
+
+fn rpc_log(log: &Log) -> rpc::Log {
+    rpc::Log {
+        inner: rpc_primitives::Log::new_unchecked(
+            log.address.0 .0.into(),
+            log.topics.iter().map(|topic| topic.0.into()).collect(),
+            log.data.0.clone().into(),
+        ),
+        ..Default::default()
+    }
+}
+
+#[inline]
+fn address(address: rpc_primitives::Address) -> Address {
+    Address::from(address.0 .0)
+}
+
+#[inline]
+fn to_u64(value: u128, field: &'static str) -> Result<u64, RpcConversionError> {
+    u64::try_from(value).map_err(|_| RpcConversionError::Overflow(field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calc_blob_gasprice, CreateScheme, Eval, GasBreakdown, Halt, OutOfGasError,
+        BLOB_GASPRICE_UPDATE_FRACTION,
+    };
+    use alloc::vec;
+
+    fn transaction() -> rpc::Transaction {
+        rpc::Transaction {
+            from: rpc_primitives::Address::repeat_byte(0x11),
+            to: Some(rpc_primitives::Address::repeat_byte(0x22)),
+            gas: 21_000,
+            gas_price: Some(10),
+            value: rpc_primitives::U256::from(5),
+            input: vec![0xde, 0xad].into(),
+            nonce: 7,
+            chain_id: Some(1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn legacy_transaction() {
+        let tx = TxEnv::try_from(&transaction()).unwrap();
+        assert_eq!(tx.caller, Address::repeat_byte(0x11));
+        assert_eq!(tx.transact_to, TransactTo::Call(Address::repeat_byte(0x22)));
+        assert_eq!(tx.gas_limit, 21_000);
+        assert_eq!(tx.gas_price, U256::from(10));
+        assert_eq!(tx.gas_priority_fee, None);
+        assert_eq!(tx.value, U256::from(5));
+        assert_eq!(tx.data, Bytes::from_static(&[0xde, 0xad]));
+        assert_eq!(tx.nonce, Some(7));
+        assert_eq!(tx.chain_id, Some(1));
+
+        let create = rpc::Transaction {
+            to: None,
+            ..transaction()
+        };
+        assert_eq!(
+            TxEnv::try_from(&create).unwrap().transact_to,
+            TransactTo::Create(CreateScheme::Create)
+        );
+
+        let overflow = rpc::Transaction {
+            gas: u128::MAX,
+            ..transaction()
+        };
+        assert_eq!(
+            TxEnv::try_from(&overflow),
+            Err(RpcConversionError::Overflow("gas"))
+        );
+    }
+
+    #[test]
+    fn access_list_and_fee_market_transaction() {
+        let rpc_tx = rpc::Transaction {
+            transaction_type: Some(2),
+            max_fee_per_gas: Some(20),
+            max_priority_fee_per_gas: Some(2),
+            access_list: Some(rpc::AccessList(vec![rpc::AccessListItem {
+                address: rpc_primitives::Address::repeat_byte(0x33),
+                storage_keys: vec![rpc_primitives::B256::with_last_byte(3)],
+            }])),
+            ..transaction()
+        };
+        let tx = TxEnv::try_from(&rpc_tx).unwrap();
+        assert_eq!(tx.gas_price, U256::from(20));
+        assert_eq!(tx.gas_priority_fee, Some(U256::from(2)));
+        assert_eq!(
+            tx.access_list,
+            vec![(Address::repeat_byte(0x33), vec![U256::from(3)])]
+        );
+    }
+
+    #[test]
+    fn blob_transaction() {
+        let rpc_tx = rpc::Transaction {
+            transaction_type: Some(3),
+            max_fee_per_blob_gas: Some(7),
+            blob_versioned_hashes: Some(vec![rpc_primitives::B256::repeat_byte(0x01)]),
+            ..transaction()
+        };
+        let tx = TxEnv::try_from(&rpc_tx).unwrap();
+        assert_eq!(tx.max_fee_per_blob_gas, Some(U256::from(7)));
+        assert_eq!(tx.blob_hashes, vec![B256::repeat_byte(0x01)]);
+    }
+
+    #[test]
+    fn block() {
+        let rpc_block = rpc::Block::<rpc::Transaction> {
+            header: rpc::Header {
+                number: Some(100),
+                miner: rpc_primitives::Address::repeat_byte(0x44),
+                timestamp: 1_000,
+                gas_limit: 30_000_000,
+                base_fee_per_gas: Some(7),
+                mix_hash: Some(rpc_primitives::B256::repeat_byte(0x55)),
+                excess_blob_gas: Some(BLOB_GASPRICE_UPDATE_FRACTION.into()),
+                parent_beacon_block_root: Some(rpc_primitives::B256::repeat_byte(0x66)),
+                parent_hash: rpc_primitives::B256::repeat_byte(0x77),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let block = BlockEnv::try_from(&rpc_block).unwrap();
+        assert_eq!(block.number, U256::from(100));
+        assert_eq!(block.coinbase, Address::repeat_byte(0x44));
+        assert_eq!(block.timestamp, U256::from(1_000));
+        assert_eq!(block.gas_limit, U256::from(30_000_000));
+        assert_eq!(block.basefee, U256::from(7));
+        assert_eq!(block.prevrandao, Some(B256::repeat_byte(0x55)));
+        assert_eq!(
+            block.get_blob_gasprice(),
+            Some(calc_blob_gasprice(BLOB_GASPRICE_UPDATE_FRACTION))
+        );
+        assert_eq!(
+            block.parent_beacon_block_root,
+            Some(B256::repeat_byte(0x66))
+        );
+        assert_eq!(block.parent_hash, Some(B256::repeat_byte(0x77)));
+    }
+
+    #[test]
+    fn receipt() {
+        let log = Log {
+            address: Address::repeat_byte(0x88),
+            topics: vec![B256::repeat_byte(0x99)],
+            data: Bytes::from_static(&[1]),
+        };
+        let created = Address::repeat_byte(0xaa);
+        let result = ExecutionResult::Success {
+            reason: Eval::Stop,
+            gas: GasBreakdown {
+                intrinsic: 53_000,
+                execution: 7_000,
+                refunded: 0,
+                returned: 40_000,
+            },
+            logs: vec![log.clone()],
+            output: Output::Create(Bytes::new(), Some(created)),
+        };
+        let receipt = rpc::TransactionReceipt::from(&result);
+        assert!(receipt.status());
+        assert_eq!(receipt.gas_used, u128::from(result.gas_used()));
+        assert_eq!(
+            receipt.contract_address,
+            Some(rpc_primitives::Address::repeat_byte(0xaa))
+        );
+        let rpc::ReceiptEnvelope::Legacy(inner) = &receipt.inner else {
+            panic!("expected legacy receipt");
+        };
+        assert_eq!(inner.receipt.logs, vec![rpc::Log::from(&log)]);
+        assert_eq!(inner.logs_bloom.0 .0, result.logs_bloom().0 .0);
+        assert_eq!(
+            inner.receipt.logs[0].topics(),
+            [rpc_primitives::B256::repeat_byte(0x99)]
+        );
+
+        let halt = ExecutionResult::Halt {
+            reason: Halt::OutOfGas(OutOfGasError::BasicOutOfGas),
+            gas: GasBreakdown {
+                intrinsic: 21_000,
+                execution: 79_000,
+                ..Default::default()
+            },
+        };
+        let receipt = rpc::TransactionReceipt::from(&halt);
+        assert!(!receipt.status());
+        assert_eq!(receipt.gas_used, 100_000);
+        assert!(receipt.inner.logs().is_empty());
+    }
+}
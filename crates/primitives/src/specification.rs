@@ -28,6 +28,7 @@ pub enum SpecId {
     MERGE = 15,           // Paris/Merge	        15537394 (TTD: 58750000000000000000000)
     SHANGHAI = 16,        // Shanghai	            17034870 (TS: 1681338455)
     CANCUN = 17,          // Cancun	                TBD
+    PRAGUE = 18,          // Prague	                TBD
     LATEST = u8::MAX,
 }
 
@@ -60,6 +61,10 @@ pub enum SpecId {
     SHANGHAI = 18,
     CANYON = 19,
     CANCUN = 20,
+    ECOTONE = 21,
+    FJORD = 22,
+    PRAGUE = 23,
+    ISTHMUS = 24,
     LATEST = u8::MAX,
 }
 
@@ -92,12 +97,19 @@ impl From<&str> for SpecId {
             "Merge" => Self::MERGE,
             "Shanghai" => Self::SHANGHAI,
             "Cancun" => Self::CANCUN,
+            "Prague" => Self::PRAGUE,
             #[cfg(feature = "optimism")]
             "Bedrock" => SpecId::BEDROCK,
             #[cfg(feature = "optimism")]
             "Regolith" => SpecId::REGOLITH,
             #[cfg(feature = "optimism")]
             "Canyon" => SpecId::CANYON,
+            #[cfg(feature = "optimism")]
+            "Ecotone" => SpecId::ECOTONE,
+            #[cfg(feature = "optimism")]
+            "Fjord" => SpecId::FJORD,
+            #[cfg(feature = "optimism")]
+            "Isthmus" => SpecId::ISTHMUS,
             _ => Self::LATEST,
         }
     }
@@ -143,6 +155,7 @@ spec!(LONDON, LondonSpec);
 spec!(MERGE, MergeSpec);
 spec!(SHANGHAI, ShanghaiSpec);
 spec!(CANCUN, CancunSpec);
+spec!(PRAGUE, PragueSpec);
 
 spec!(LATEST, LatestSpec);
 
@@ -153,6 +166,12 @@ spec!(BEDROCK, BedrockSpec);
 spec!(REGOLITH, RegolithSpec);
 #[cfg(feature = "optimism")]
 spec!(CANYON, CanyonSpec);
+#[cfg(feature = "optimism")]
+spec!(ECOTONE, EcotoneSpec);
+#[cfg(feature = "optimism")]
+spec!(FJORD, FjordSpec);
+#[cfg(feature = "optimism")]
+spec!(ISTHMUS, IsthmusSpec);
 
 #[cfg(feature = "optimism")]
 #[cfg(test)]
@@ -220,4 +239,33 @@ mod tests {
         assert!(SpecId::enabled(SpecId::CANYON, SpecId::REGOLITH));
         assert!(SpecId::enabled(SpecId::CANYON, SpecId::CANYON));
     }
+
+    #[test]
+    fn test_ecotone_post_merge_hardforks() {
+        assert!(EcotoneSpec::enabled(SpecId::MERGE));
+        assert!(EcotoneSpec::enabled(SpecId::SHANGHAI));
+        assert!(EcotoneSpec::enabled(SpecId::CANCUN));
+        assert!(!EcotoneSpec::enabled(SpecId::LATEST));
+        assert!(EcotoneSpec::enabled(SpecId::CANYON));
+        assert!(EcotoneSpec::enabled(SpecId::ECOTONE));
+        assert!(!EcotoneSpec::enabled(SpecId::FJORD));
+    }
+
+    #[test]
+    fn test_fjord_post_merge_hardforks() {
+        assert!(FjordSpec::enabled(SpecId::CANCUN));
+        assert!(!FjordSpec::enabled(SpecId::PRAGUE));
+        assert!(!FjordSpec::enabled(SpecId::LATEST));
+        assert!(FjordSpec::enabled(SpecId::ECOTONE));
+        assert!(FjordSpec::enabled(SpecId::FJORD));
+        assert!(!FjordSpec::enabled(SpecId::ISTHMUS));
+    }
+
+    #[test]
+    fn test_isthmus_post_merge_hardforks() {
+        assert!(IsthmusSpec::enabled(SpecId::PRAGUE));
+        assert!(!IsthmusSpec::enabled(SpecId::LATEST));
+        assert!(IsthmusSpec::enabled(SpecId::FJORD));
+        assert!(IsthmusSpec::enabled(SpecId::ISTHMUS));
+    }
 }
@@ -281,6 +281,31 @@ impl AccountInfo {
 mod tests {
     use crate::{Account, KECCAK_EMPTY, U256};
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use crate::{AccountInfo, Address, Bytecode, Bytes, State, StorageSlot};
+
+        let mut account = Account::from(AccountInfo::new(
+            U256::from(10),
+            1,
+            KECCAK_EMPTY,
+            Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00])),
+        ));
+        account.storage.insert(
+            U256::from(1),
+            StorageSlot::new_changed(U256::ZERO, U256::from(2)),
+        );
+        account.mark_touch();
+        account.mark_created();
+
+        let json = crate::utilities::assert_serde_round_trip(&account);
+        assert_eq!(json["info"]["balance"], "0xa");
+
+        let state = State::from_iter([(Address::repeat_byte(0x11), account)]);
+        crate::utilities::assert_serde_round_trip(&state);
+    }
+
     #[test]
     fn account_is_empty_balance() {
         let mut account = Account::default();
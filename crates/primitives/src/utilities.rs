@@ -1,8 +1,21 @@
 use crate::{
     b256, B256, BLOB_GASPRICE_UPDATE_FRACTION, MIN_BLOB_GASPRICE, TARGET_BLOB_GAS_PER_BLOCK,
 };
+#[cfg(not(feature = "asm-keccak"))]
 pub use alloy_primitives::keccak256;
 
+/// Simple interface to the [`Keccak-256`] hash function, with the permutation accelerated by
+/// the SHA-3 instructions of ARMv8 CPUs that support them.
+///
+/// [`Keccak-256`]: https://en.wikipedia.org/wiki/SHA-3
+#[cfg(feature = "asm-keccak")]
+#[inline]
+pub fn keccak256<T: AsRef<[u8]>>(bytes: T) -> B256 {
+    use sha3::{Digest, Keccak256};
+
+    B256::new(Keccak256::digest(bytes).into())
+}
+
 /// The Keccak-256 hash of the empty string `""`.
 pub const KECCAK_EMPTY: B256 =
     b256!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
@@ -59,11 +72,39 @@ pub fn fake_exponential(factor: u64, numerator: u64, denominator: u64) -> u128 {
     output / denominator
 }
 
+/// Asserts that the value is the same after a round trip through JSON and bincode, and returns
+/// its JSON.
+#[cfg(all(test, feature = "serde"))]
+pub(crate) fn assert_serde_round_trip<T>(value: &T) -> serde_json::Value
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + core::fmt::Debug,
+{
+    let json = serde_json::to_value(value).unwrap();
+    assert_eq!(&serde_json::from_value::<T>(json.clone()).unwrap(), value);
+    let binary = bincode::serialize(value).unwrap();
+    assert_eq!(&bincode::deserialize::<T>(&binary).unwrap(), value);
+    json
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::GAS_PER_BLOB;
 
+    #[test]
+    fn keccak256_known_hashes() {
+        assert_eq!(keccak256([]), KECCAK_EMPTY);
+        assert_eq!(
+            keccak256(b"hello world"),
+            b256!("47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad")
+        );
+        // More than one block of the sponge.
+        assert_eq!(
+            keccak256([0u8; 200]),
+            alloy_primitives::keccak256([0u8; 200])
+        );
+    }
+
     // https://github.com/ethereum/go-ethereum/blob/28857080d732857030eda80c69b9ba2c8926f221/consensus/misc/eip4844/eip4844_test.go#L27
     #[test]
     fn test_calc_excess_blob_gas() {
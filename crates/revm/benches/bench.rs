@@ -76,11 +76,47 @@ fn transfer(c: &mut Criterion) {
     g.finish();
 }
 
+/// Loop of cheap instructions, dominated by the dispatch of the interpreter loop.
+fn dispatch(c: &mut Criterion) {
+    let mut evm = revm::new();
+    // PUSH2 0x2000 JUMPDEST PUSH1 1 SWAP1 SUB DUP1 PUSH1 3 JUMPI STOP
+    evm.database(BenchmarkDB::new_bytecode(to_analysed(Bytecode::new_raw(
+        bytes!("6120005b600190038060035700"),
+    ))));
+
+    evm.env.tx.caller = address!("1000000000000000000000000000000000000000");
+    evm.env.tx.transact_to = TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+
+    let mut g = c.benchmark_group("dispatch");
+    g.noise_threshold(0.03).warm_up_time(Duration::from_secs(1));
+    bench_transact(&mut g, &mut evm);
+    bench_eval(&mut g, &mut evm);
+    g.finish();
+}
+
+/// Loop of calls, dominated by the setup of the call frames.
+fn calls(c: &mut Criterion) {
+    let mut evm = revm::new();
+    // Without input: 256 times STATICCALL(GAS, ADDRESS, 0, 1, 0, 0), with input: STOP.
+    evm.database(BenchmarkDB::new_bytecode(to_analysed(Bytecode::new_raw(
+        bytes!("36601d576101005b6000600060016000305afa506001900380600757005b00"),
+    ))));
+
+    evm.env.tx.caller = address!("1000000000000000000000000000000000000000");
+    evm.env.tx.transact_to = TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+
+    let mut g = c.benchmark_group("calls");
+    g.noise_threshold(0.03).warm_up_time(Duration::from_secs(1));
+    bench_transact(&mut g, &mut evm);
+    g.finish();
+}
+
 fn bench_transact(g: &mut BenchmarkGroup<'_, WallTime>, evm: &mut Evm) {
     let state = match evm.db.as_mut().unwrap().0.state {
         BytecodeState::Raw => "raw",
         BytecodeState::Checked { .. } => "checked",
         BytecodeState::Analysed { .. } => "analysed",
+        BytecodeState::Eof(_) => "eof",
     };
     let id = format!("transact/{state}");
     g.bench_function(id, |b| b.iter(|| evm.transact().unwrap()));
@@ -119,6 +155,8 @@ criterion_group!(
     analysis,
     snailtracer,
     transfer,
+    dispatch,
+    calls,
 );
 criterion_main!(benches);
 
@@ -0,0 +1,201 @@
+//! Code generation of chain-specific EVMs.
+
+/// Generates a module with an EVM dedicated to a chain.
+///
+/// Specification, precompiles, instructions and handler of the chain are fixed at compile time,
+/// so the generated EVM has no runtime branching on the spec id, unlike [crate::new_evm].
+/// Custom gas rules are applied by replacing instructions or handler functions.
+///
+/// Generated module contains:
+/// * `ChainSpec`: [Spec][crate::primitives::Spec] of the chain.
+/// * `Evm<'a, DB>`: [EVMImpl][crate::EVMImpl] of the chain.
+/// * `precompiles()`: [PrecompileProvider][crate::precompile::PrecompileProvider] of the chain,
+///   precompiles of `spec` by default.
+/// * `instruction_table::<H>()`: instructions of `spec` with `instructions` replaced.
+/// * `new_evm(db, env, inspector)`: creates the EVM, `env.cfg.spec_id` is set to `spec`.
+///
+/// Items of the parent module are in scope of the arguments. Handler is a function that
+/// modifies the [Handler][crate::Handler], instructions are `opcode => instruction` pairs.
+///
+/// # Example
+///
+/// ```
+/// use revm::{
+///     interpreter::{opcode, Host, InstructionResult, Interpreter},
+///     Database, Handler,
+/// };
+///
+/// fn banned<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+///     interpreter.instruction_result = InstructionResult::NotActivated;
+/// }
+///
+/// fn no_refund<DB: Database>(handler: &mut Handler<DB>) {
+///     handler.calculate_gas_refund = |_, _| 0;
+/// }
+///
+/// revm::chain_evm! {
+///     /// EVM of the appchain.
+///     pub mod appchain {
+///         spec: SHANGHAI,
+///         handler: no_refund,
+///         instructions: {
+///             opcode::SELFDESTRUCT => banned,
+///         },
+///     }
+/// }
+///
+/// fn main() {
+///     let mut db = revm::InMemoryDB::default();
+///     let mut env = revm::primitives::Env::default();
+///     let _evm = appchain::new_evm(&mut db, &mut env, None);
+/// }
+/// ```
+#[macro_export]
+macro_rules! chain_evm {
+    (
+        $(#[$attr:meta])*
+        $vis:vis mod $name:ident {
+            spec: $spec_id:ident,
+            $(precompiles: $precompiles:expr,)?
+            $(handler: $handler:expr,)?
+            $(instructions: { $($opcode:expr => $instruction:expr),* $(,)? },)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[allow(unreachable_pub)]
+        $vis mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// Specification of the chain.
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct ChainSpec;
+
+            impl $crate::primitives::Spec for ChainSpec {
+                const SPEC_ID: $crate::primitives::SpecId = $crate::primitives::SpecId::$spec_id;
+            }
+
+            /// EVM of the chain.
+            pub type Evm<'a, DB> = $crate::EVMImpl<'a, ChainSpec, DB>;
+
+            /// Returns precompiles of the chain.
+            pub fn precompiles() -> impl $crate::precompile::PrecompileProvider + 'static {
+                $crate::chain_evm!(@precompiles $spec_id $(, $precompiles)?)
+            }
+
+            /// Returns instruction table of the chain.
+            pub fn instruction_table<H: $crate::interpreter::Host>(
+            ) -> $crate::interpreter::opcode::InstructionTable<H> {
+                #[allow(unused_mut)]
+                let mut table =
+                    $crate::interpreter::opcode::make_instruction_table::<H, ChainSpec>();
+                $($(table[$opcode as usize] = $instruction;)*)?
+                table
+            }
+
+            /// Creates EVM of the chain.
+            pub fn new_evm<'a, DB: $crate::Database>(
+                db: &'a mut DB,
+                env: &'a mut $crate::primitives::Env,
+                inspector: Option<&'a mut dyn $crate::Inspector<DB>>,
+            ) -> Evm<'a, DB> {
+                env.cfg.spec_id = <ChainSpec as $crate::primitives::Spec>::SPEC_ID;
+                #[allow(unused_mut)]
+                let mut evm = Evm::new_with_instruction_table(db, env, inspector, instruction_table());
+                // Handler has precompiles of the spec by default.
+                $(
+                    let _ = stringify!($precompiles);
+                    evm.set_precompiles(precompiles());
+                )?
+                $(($handler)(&mut evm.handler);)?
+                evm
+            }
+        }
+    };
+    (@precompiles $spec_id:ident) => {
+        $crate::precompile::Precompiles::new($crate::precompile::SpecId::from_spec_id(
+            $crate::primitives::SpecId::$spec_id,
+        ))
+    };
+    (@precompiles $spec_id:ident, $precompiles:expr) => {
+        $precompiles
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::{opcode, Host, InstructionResult, Interpreter},
+        precompile::{Precompile, PrecompileResult, PrecompileWithAddress, Precompiles},
+        primitives::{
+            AccountInfo, Address, Bytecode, Bytes, Env, ExecutionResult, Halt, Spec, SpecId,
+            TransactTo,
+        },
+        Database, Handler, Transact,
+    };
+
+    const ANSWER: Address = Address::with_last_byte(0x42);
+
+    fn answer(_input: &[u8], _gas_limit: u64) -> PrecompileResult {
+        Ok((10, vec![42]))
+    }
+
+    fn with_answer() -> Precompiles {
+        let mut precompiles = Precompiles::cancun().clone();
+        precompiles.extend([PrecompileWithAddress(ANSWER, Precompile::Standard(answer))]);
+        precompiles
+    }
+
+    fn banned<H: Host>(interpreter: &mut Interpreter, _host: &mut H) {
+        interpreter.instruction_result = InstructionResult::NotActivated;
+    }
+
+    fn no_refund<DB: Database>(handler: &mut Handler<DB>) {
+        handler.calculate_gas_refund = |_, _| 0;
+    }
+
+    chain_evm! {
+        mod appchain {
+            spec: CANCUN,
+            precompiles: with_answer(),
+            handler: no_refund,
+            instructions: {
+                opcode::TSTORE => banned,
+            },
+        }
+    }
+
+    fn call(db: &mut CacheDB<EmptyDB>, to: Address) -> ExecutionResult {
+        let mut env = Env::default();
+        env.tx.caller = Address::with_last_byte(0x10);
+        env.tx.transact_to = TransactTo::Call(to);
+        env.tx.gas_limit = 100_000;
+        let mut evm = appchain::new_evm(db, &mut env, None);
+        evm.transact().unwrap().result
+    }
+
+    #[test]
+    fn chain_evm_uses_chain_rules() {
+        assert_eq!(appchain::ChainSpec::SPEC_ID, SpecId::CANCUN);
+        let mut db = CacheDB::new(EmptyDB::default());
+
+        let result = call(&mut db, ANSWER);
+        assert_eq!(result.output(), Some(&Bytes::from_static(&[42])));
+
+        let contract = Address::with_last_byte(0x20);
+        // PUSH1 1 PUSH1 0 TSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x5d]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(Default::default(), 1, code.hash_slow(), code),
+        );
+        assert!(matches!(
+            call(&mut db, contract),
+            ExecutionResult::Halt {
+                reason: Halt::NotActivated,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,137 @@
+//! Hardforks of chains built on top of the Ethereum hardforks.
+
+use crate::{
+    primitives::{db::Database, Env, Spec, SpecId},
+    Handler, Journal,
+};
+
+/// Hardfork specification of a chain.
+///
+/// Chains define their own fork enum and map each fork onto the Ethereum [SpecId] it is based
+/// on. The base spec selects the instruction table, gas costs and precompiles, rules of the chain
+/// are applied by the handler of the fork. See [crate::new_evm_with_chain_spec].
+///
+/// [SpecId] is the specification of Ethereum mainnet (and Optimism if `cfg.optimism` is set).
+pub trait ChainSpec: Copy + PartialOrd {
+    /// Ethereum hardfork the fork is based on.
+    fn base_spec_id(&self) -> SpecId;
+
+    /// Returns `true` if `fork` is active, i.e. this fork is `fork` or a later one.
+    #[inline]
+    fn is_enabled(&self, fork: Self) -> bool {
+        *self >= fork
+    }
+
+    /// Builds the handler of the fork. `SPEC` is the [Spec] of [ChainSpec::base_spec_id].
+    fn handler<SPEC: Spec, DB: Database, J: Journal, EXT>(&self, env: &Env) -> Handler<DB, J, EXT> {
+        let _ = env;
+        Handler::mainnet::<SPEC>()
+    }
+}
+
+impl ChainSpec for SpecId {
+    #[inline]
+    fn base_spec_id(&self) -> SpecId {
+        *self
+    }
+
+    fn handler<SPEC: Spec, DB: Database, J: Journal, EXT>(&self, env: &Env) -> Handler<DB, J, EXT> {
+        #[cfg(feature = "optimism")]
+        if env.cfg.optimism {
+            return Handler::optimism::<SPEC>();
+        }
+        let _ = env;
+        Handler::mainnet::<SPEC>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        new_evm_with_chain_spec,
+        primitives::{
+            AccountInfo, Address, Bytecode, Bytes, ExecutionResult, Halt, ResultAndState,
+            TransactTo, U256,
+        },
+    };
+
+    #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+    enum AppFork {
+        Genesis,
+        Transient,
+    }
+
+    impl ChainSpec for AppFork {
+        fn base_spec_id(&self) -> SpecId {
+            match self {
+                AppFork::Genesis => SpecId::SHANGHAI,
+                AppFork::Transient => SpecId::CANCUN,
+            }
+        }
+
+        fn handler<SPEC: Spec, DB: Database, J: Journal, EXT>(
+            &self,
+            _env: &Env,
+        ) -> Handler<DB, J, EXT> {
+            let mut handler = Handler::mainnet::<SPEC>();
+            // Fees are burned as of the Transient fork.
+            if self.is_enabled(AppFork::Transient) {
+                handler.reward_beneficiary = |_, _| Ok(());
+            }
+            handler
+        }
+    }
+
+    #[test]
+    fn chain_spec_selects_base_spec_and_handler() {
+        let (caller, contract, coinbase) = (
+            Address::with_last_byte(0x10),
+            Address::with_last_byte(0x20),
+            Address::with_last_byte(0x30),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(10_000_000)));
+        // PUSH1 1 PUSH1 0 TSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x5d]));
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+
+        let transact = |db: &mut CacheDB<EmptyDB>, fork: AppFork| {
+            let mut env = Env::default();
+            env.block.coinbase = coinbase;
+            env.tx.caller = caller;
+            env.tx.transact_to = TransactTo::Call(contract);
+            env.tx.gas_limit = 100_000;
+            env.tx.gas_price = U256::from(1);
+            let result = new_evm_with_chain_spec(fork, &mut env, db, None, ())
+                .transact()
+                .unwrap();
+            assert_eq!(env.cfg.spec_id, fork.base_spec_id());
+            result
+        };
+
+        let ResultAndState { result, state, .. } = transact(&mut db, AppFork::Genesis);
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: Halt::NotActivated,
+                ..
+            }
+        ));
+        assert_eq!(state[&coinbase].info.balance, U256::from(100_000));
+
+        let ResultAndState { result, state, .. } = transact(&mut db, AppFork::Transient);
+        assert!(result.is_success());
+        let coinbase_balance = state
+            .get(&coinbase)
+            .map_or(U256::ZERO, |acc| acc.info.balance);
+        assert_eq!(coinbase_balance, U256::ZERO);
+    }
+}
@@ -0,0 +1,53 @@
+//! Precompiles with access to the EVM context.
+
+use crate::{
+    db::Database,
+    precompile::{Precompile, PrecompileResult},
+    primitives::Bytes,
+    EvmContext, Journal, JournaledState,
+};
+use alloc::sync::Arc;
+use core::fmt;
+
+/// Precompile that reads and writes the state, for example a bridge or a system contract
+/// implemented natively.
+///
+/// It is called inside the checkpoint of the call, so changes made through
+/// [EvmContext::journaled_state] are reverted if the precompile fails or the caller reverts.
+/// Database errors are stored in [EvmContext::error] before returning an error, they abort the
+/// transaction.
+pub trait ContextStatefulPrecompile<DB: Database, J: Journal = JournaledState, EXT = ()>:
+    fmt::Debug
+{
+    /// Executes the precompile with the input and gas limit of the call.
+    fn call(
+        &self,
+        input: &Bytes,
+        gas_limit: u64,
+        context: &mut EvmContext<'_, DB, J, EXT>,
+    ) -> PrecompileResult;
+}
+
+/// Precompile registered in the [EvmContext], see [crate::EVMImpl::set_context_precompile].
+#[derive(Debug)]
+pub enum ContextPrecompile<DB: Database, J: Journal = JournaledState, EXT = ()> {
+    /// Precompile without access to the context.
+    Ordinary(Precompile),
+    /// Precompile with access to the journaled state, environment and database.
+    ContextStateful(Arc<dyn ContextStatefulPrecompile<DB, J, EXT>>),
+}
+
+impl<DB: Database, J: Journal, EXT> Clone for ContextPrecompile<DB, J, EXT> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Ordinary(precompile) => Self::Ordinary(precompile.clone()),
+            Self::ContextStateful(precompile) => Self::ContextStateful(precompile.clone()),
+        }
+    }
+}
+
+impl<DB: Database, J: Journal, EXT> From<Precompile> for ContextPrecompile<DB, J, EXT> {
+    fn from(precompile: Precompile) -> Self {
+        Self::Ordinary(precompile)
+    }
+}
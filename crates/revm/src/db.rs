@@ -1,21 +1,50 @@
 //! [Database] implementations.
 
+#[cfg(feature = "asyncdb")]
+pub mod asyncdb;
 pub mod emptydb;
 #[cfg(feature = "ethersdb")]
 pub mod ethersdb;
+#[cfg(feature = "std")]
+pub mod forkdb;
 pub mod in_memory_db;
+pub mod middleware;
+pub mod mockdb;
+pub mod native_decimals;
+pub mod overlay;
 pub mod states;
+#[cfg(feature = "std")]
+pub mod sync_db;
+#[cfg(feature = "triedb")]
+pub mod triedb;
+pub mod witness;
 
 pub use crate::primitives::db::*;
+#[cfg(feature = "asyncdb")]
+pub use asyncdb::{AsyncDatabase, AsyncDatabaseCache, MissingData};
 pub use emptydb::{EmptyDB, EmptyDBTyped};
 #[cfg(feature = "ethersdb")]
 pub use ethersdb::EthersDB;
+#[cfg(feature = "std")]
+pub use forkdb::{BlockchainBackend, ForkDB, ForkDBError};
 pub use in_memory_db::*;
+pub use middleware::{
+    DatabaseLayerExt, DatabaseMiddleware, DatabaseQuery, DatabaseResponse, Layered, QueryStats,
+};
+pub use mockdb::MockDB;
+pub use native_decimals::NativeDecimalsDB;
+pub use overlay::{AccountOverlay, OverlayDB, StateOverlay};
 pub use states::{
-    AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
-    OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
-    StorageWithOriginalValues, TransitionAccount, TransitionState,
+    AccountDiff, AccountLifecycle, AccountLifecycleHook, AccountRevert, AccountStatus,
+    BundleAccount, BundleState, CacheState, DBBox, OriginalValuesKnown, PlainAccount, RevertToSlot,
+    State, StateBuilder, StateDBBox, StateDiff, StorageDiff, StorageWithOriginalValues,
+    TransitionAccount, TransitionState,
 };
+#[cfg(feature = "std")]
+pub use sync_db::{SharedCacheDB, SyncDatabase};
+#[cfg(feature = "triedb")]
+pub use triedb::TrieDB;
+pub use witness::{DatabaseWitness, ExecutionWitness, WitnessRecordingDB};
 
 #[cfg(all(not(feature = "ethersdb"), feature = "web3db"))]
 compile_error!(
@@ -0,0 +1,319 @@
+use crate::primitives::{AccountInfo, Address, Bytecode, EVMError, HashMap, B256, U256};
+use crate::Database;
+use core::{convert::Infallible, future::Future};
+
+/// Asynchronous EVM database interface.
+///
+/// Contains the same methods as [Database], but they return futures, so backends that fetch
+/// data over the network (e.g. forked RPC state) don't need to block a thread per query.
+/// Executed with [crate::EVM::transact_async].
+pub trait AsyncDatabase {
+    /// The database error type.
+    type Error;
+
+    /// Get basic account information.
+    fn basic(
+        &mut self,
+        address: Address,
+    ) -> impl Future<Output = Result<Option<AccountInfo>, Self::Error>>;
+
+    /// Get account code by its hash.
+    fn code_by_hash(
+        &mut self,
+        code_hash: B256,
+    ) -> impl Future<Output = Result<Bytecode, Self::Error>>;
+
+    /// Get storage value of address at index.
+    fn storage(
+        &mut self,
+        address: Address,
+        index: U256,
+    ) -> impl Future<Output = Result<U256, Self::Error>>;
+
+    /// Get block hash by block number.
+    fn block_hash(&mut self, number: U256) -> impl Future<Output = Result<B256, Self::Error>>;
+}
+
+/// Data that was requested by the execution but is not yet fetched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MissingData {
+    /// Basic account information.
+    Account(Address),
+    /// Code with the given hash.
+    Code(B256),
+    /// Storage slot of the account.
+    Storage(Address, U256),
+    /// Hash of the block with the given number.
+    BlockHash(U256),
+}
+
+/// Synchronous view over data fetched from [AsyncDatabase].
+///
+/// Execution is driven as a state machine: transaction is executed against the fetched data and
+/// every item that is not fetched yet is recorded as [MissingData] and served as empty: not
+/// existing account, empty code, zero storage value and block hash. A single execution so
+/// discovers all missing items on its path, they are fetched together with
+/// [AsyncDatabaseCache::fetch_missing] and execution is restarted. Only the output of an
+/// execution that didn't miss any item is valid.
+#[derive(Clone, Debug, Default)]
+pub struct AsyncDatabaseCache {
+    accounts: HashMap<Address, Option<AccountInfo>>,
+    contracts: HashMap<B256, Bytecode>,
+    storage: HashMap<(Address, U256), U256>,
+    block_hashes: HashMap<U256, B256>,
+    missing: Vec<MissingData>,
+}
+
+impl AsyncDatabaseCache {
+    /// Returns the items that were requested by the executions since the last
+    /// [AsyncDatabaseCache::fetch_missing] and are not fetched yet.
+    pub fn missing(&self) -> &[MissingData] {
+        &self.missing
+    }
+
+    /// Await all missing items from the database and store them in the cache.
+    pub async fn fetch_missing<DB: AsyncDatabase>(&mut self, db: &mut DB) -> Result<(), DB::Error> {
+        for missing in core::mem::take(&mut self.missing) {
+            self.fetch(db, missing).await?;
+        }
+        Ok(())
+    }
+
+    /// Await missing item from the database and store it in the cache.
+    ///
+    /// Code of a fetched account is fetched with it, so it doesn't need another execution to
+    /// be discovered.
+    pub async fn fetch<DB: AsyncDatabase>(
+        &mut self,
+        db: &mut DB,
+        missing: MissingData,
+    ) -> Result<(), DB::Error> {
+        match missing {
+            MissingData::Account(address) => {
+                let info = db.basic(address).await?;
+                if let Some(info) = info.as_ref().filter(|info| info.code.is_none()) {
+                    if !info.is_empty_code_hash() && !self.contracts.contains_key(&info.code_hash) {
+                        let code = db.code_by_hash(info.code_hash).await?;
+                        self.contracts.insert(info.code_hash, code);
+                    }
+                }
+                self.accounts.insert(address, info);
+            }
+            MissingData::Code(code_hash) => {
+                let code = db.code_by_hash(code_hash).await?;
+                self.contracts.insert(code_hash, code);
+            }
+            MissingData::Storage(address, index) => {
+                let value = db.storage(address, index).await?;
+                self.storage.insert((address, index), value);
+            }
+            MissingData::BlockHash(number) => {
+                let hash = db.block_hash(number).await?;
+                self.block_hashes.insert(number, hash);
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the missing item, once per execution.
+    fn record(&mut self, missing: MissingData) {
+        if !self.missing.contains(&missing) {
+            self.missing.push(missing);
+        }
+    }
+}
+
+impl Database for AsyncDatabaseCache {
+    type Error = Infallible;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.accounts.get(&address) {
+            Some(info) => Ok(info.clone()),
+            None => {
+                self.record(MissingData::Account(address));
+                Ok(None)
+            }
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.contracts.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => {
+                self.record(MissingData::Code(code_hash));
+                Ok(Bytecode::new())
+            }
+        }
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self.storage.get(&(address, index)) {
+            Some(value) => Ok(*value),
+            None => {
+                self.record(MissingData::Storage(address, index));
+                Ok(U256::ZERO)
+            }
+        }
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        match self.block_hashes.get(&number) {
+            Some(hash) => Ok(*hash),
+            None => {
+                self.record(MissingData::BlockHash(number));
+                Ok(B256::ZERO)
+            }
+        }
+    }
+}
+
+/// Maps error of the execution over the cache, which never fails on database access.
+pub(crate) fn map_cache_error<E>(error: EVMError<Infallible>) -> EVMError<E> {
+    match error {
+        EVMError::Database(never) => match never {},
+        EVMError::Transaction(e) => EVMError::Transaction(e),
+        EVMError::Header(e) => EVMError::Header(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        new_evm,
+        primitives::{Bytes, Env, TransactTo, TxEnv},
+        EVM,
+    };
+    use core::{
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    /// Async wrapper that counts the queries.
+    struct CountingDB {
+        db: CacheDB<EmptyDB>,
+        queries: usize,
+    }
+
+    impl AsyncDatabase for CountingDB {
+        type Error = Infallible;
+
+        async fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Infallible> {
+            self.queries += 1;
+            self.db.basic(address)
+        }
+
+        async fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Infallible> {
+            self.queries += 1;
+            self.db.code_by_hash(code_hash)
+        }
+
+        async fn storage(&mut self, address: Address, index: U256) -> Result<U256, Infallible> {
+            self.queries += 1;
+            self.db.storage(address, index)
+        }
+
+        async fn block_hash(&mut self, number: U256) -> Result<B256, Infallible> {
+            self.queries += 1;
+            self.db.block_hash(number)
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn transact_async_fetches_missing_data() {
+        let caller = Address::with_last_byte(0x10);
+        let callee = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        // PUSH1 1 SLOAD PUSH1 0 SSTORE STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x54, 0x60, 0x00, 0x55, 0x00,
+        ]));
+        db.insert_account_info(
+            callee,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        db.insert_account_storage(callee, U256::from(1), U256::from(42))
+            .unwrap();
+
+        let mut evm = EVM::new();
+        evm.database(CountingDB { db, queries: 0 });
+        evm.env.tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(callee),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+
+        let output = block_on(evm.transact_async()).unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(
+            output.state[&callee].storage[&U256::ZERO].present_value,
+            U256::from(42)
+        );
+        // Every item is fetched only once.
+        let queries = evm.db.as_ref().unwrap().queries;
+        assert_eq!(queries, 5);
+    }
+
+    #[test]
+    fn missing_data_is_fetched_in_batches() {
+        let caller = Address::with_last_byte(0x10);
+        let callee = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        // SLOAD(1) SLOAD(2) SLOAD(3) ADD ADD PUSH1 0 SSTORE STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x54, 0x60, 0x02, 0x54, 0x60, 0x03, 0x54, 0x01, 0x01, 0x60, 0x00, 0x55,
+            0x00,
+        ]));
+        db.insert_account_info(
+            callee,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        for slot in 1..=3 {
+            db.insert_account_storage(callee, U256::from(slot), U256::from(slot))
+                .unwrap();
+        }
+        let mut db = CountingDB { db, queries: 0 };
+
+        let env = Env {
+            tx: TxEnv {
+                caller,
+                transact_to: TransactTo::Call(callee),
+                gas_limit: 100_000,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut cache = AsyncDatabaseCache::default();
+        let mut executions = 0;
+        let output = loop {
+            let output = new_evm(&mut env.clone(), &mut cache, None).transact();
+            executions += 1;
+            if cache.missing().is_empty() {
+                break output.unwrap();
+            }
+            block_on(cache.fetch_missing(&mut db)).unwrap();
+        };
+
+        // Accounts with the code, then all storage slots, then the final execution.
+        assert_eq!(executions, 3);
+        assert_eq!(
+            output.state[&callee].storage[&U256::ZERO].present_value,
+            U256::from(6)
+        );
+        assert_eq!(db.queries, 7);
+    }
+}
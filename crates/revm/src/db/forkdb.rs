@@ -0,0 +1,276 @@
+//! [DatabaseRef] that lazily fetches state of a remote chain.
+
+#[cfg(feature = "rpcdb")]
+mod rpc;
+
+#[cfg(feature = "rpcdb")]
+pub use rpc::{JsonRpcBackend, JsonRpcError};
+
+use super::DatabaseRef;
+use crate::primitives::{AccountInfo, Address, Bytecode, HashMap, B256, KECCAK_EMPTY, U256};
+use core::fmt;
+use std::sync::RwLock;
+
+/// Source of the chain state used by [ForkDB].
+///
+/// All queries are made against the state after the given block.
+pub trait BlockchainBackend {
+    /// The backend error type.
+    type Error;
+
+    /// Get balance, nonce and code of the account.
+    ///
+    /// Returns `None` if the account does not exist.
+    fn account(&self, address: Address, block: u64) -> Result<Option<AccountInfo>, Self::Error>;
+
+    /// Get storage value of address at index.
+    fn storage(&self, address: Address, index: U256, block: u64) -> Result<U256, Self::Error>;
+
+    /// Get hash of the block with the given number.
+    fn block_hash(&self, number: u64) -> Result<B256, Self::Error>;
+}
+
+/// Error of [ForkDB].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForkDBError<E> {
+    /// Error of the backend.
+    Backend(E),
+    /// Code was requested by hash before any account with it was fetched.
+    CodeNotFound(B256),
+}
+
+impl<E: fmt::Display> fmt::Display for ForkDBError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForkDBError::Backend(e) => write!(f, "backend error: {e}"),
+            ForkDBError::CodeNotFound(hash) => write!(f, "code with hash {hash} not found"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for ForkDBError<E> {}
+
+/// State fetched at a single block.
+#[derive(Debug, Default)]
+struct BlockCache {
+    accounts: HashMap<Address, Option<AccountInfo>>,
+    storage: HashMap<(Address, U256), U256>,
+}
+
+/// [DatabaseRef] that lazily fetches state from a [BlockchainBackend], pinned to a fork block.
+///
+/// Fetched data is cached per block, so moving the fork block with [ForkDB::set_block] and back
+/// does not fetch it again. Contracts and block hashes don't depend on the block and are shared.
+/// The database is read-only, wrap it in [crate::db::CacheDB] to execute transactions on top of
+/// it.
+#[derive(Debug)]
+pub struct ForkDB<B> {
+    backend: B,
+    block: u64,
+    blocks: RwLock<HashMap<u64, BlockCache>>,
+    contracts: RwLock<HashMap<B256, Bytecode>>,
+    block_hashes: RwLock<HashMap<u64, B256>>,
+}
+
+impl<B: BlockchainBackend> ForkDB<B> {
+    /// Creates new database pinned to `block`.
+    pub fn new(backend: B, block: u64) -> Self {
+        Self {
+            backend,
+            block,
+            blocks: Default::default(),
+            contracts: Default::default(),
+            block_hashes: Default::default(),
+        }
+    }
+
+    /// Returns the block the database is pinned to.
+    pub fn block(&self) -> u64 {
+        self.block
+    }
+
+    /// Pins the database to another block.
+    pub fn set_block(&mut self, block: u64) {
+        self.block = block;
+    }
+
+    /// Returns the backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+}
+
+impl<B: BlockchainBackend> DatabaseRef for ForkDB<B> {
+    type Error = ForkDBError<B::Error>;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(info) = self
+            .blocks
+            .read()
+            .unwrap()
+            .get(&self.block)
+            .and_then(|cache| cache.accounts.get(&address))
+        {
+            return Ok(info.clone());
+        }
+
+        let mut info = self
+            .backend
+            .account(address, self.block)
+            .map_err(ForkDBError::Backend)?;
+        if let Some(info) = info.as_mut() {
+            let code = info.code.take().unwrap_or_default();
+            info.code_hash = if code.is_empty() {
+                KECCAK_EMPTY
+            } else {
+                code.hash_slow()
+            };
+            let code = code.to_checked();
+            self.contracts
+                .write()
+                .unwrap()
+                .insert(info.code_hash, code.clone());
+            info.code = Some(code);
+        }
+
+        self.blocks
+            .write()
+            .unwrap()
+            .entry(self.block)
+            .or_default()
+            .accounts
+            .insert(address, info.clone());
+        Ok(info)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::new());
+        }
+        self.contracts
+            .read()
+            .unwrap()
+            .get(&code_hash)
+            .cloned()
+            .ok_or(ForkDBError::CodeNotFound(code_hash))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self
+            .blocks
+            .read()
+            .unwrap()
+            .get(&self.block)
+            .and_then(|cache| cache.storage.get(&(address, index)))
+        {
+            return Ok(*value);
+        }
+
+        let value = self
+            .backend
+            .storage(address, index, self.block)
+            .map_err(ForkDBError::Backend)?;
+        self.blocks
+            .write()
+            .unwrap()
+            .entry(self.block)
+            .or_default()
+            .storage
+            .insert((address, index), value);
+        Ok(value)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        // Blocks after the fork block are not known to the backend.
+        let number = match u64::try_from(number) {
+            Ok(number) if number <= self.block => number,
+            _ => return Ok(B256::ZERO),
+        };
+        if let Some(hash) = self.block_hashes.read().unwrap().get(&number) {
+            return Ok(*hash);
+        }
+
+        let hash = self
+            .backend
+            .block_hash(number)
+            .map_err(ForkDBError::Backend)?;
+        self.block_hashes.write().unwrap().insert(number, hash);
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::Bytes;
+    use std::sync::Mutex;
+
+    /// Backend where balance of every account is the block number.
+    #[derive(Default)]
+    struct CountingBackend {
+        queries: Mutex<usize>,
+    }
+
+    impl BlockchainBackend for CountingBackend {
+        type Error = ();
+
+        fn account(&self, _address: Address, block: u64) -> Result<Option<AccountInfo>, ()> {
+            *self.queries.lock().unwrap() += 1;
+            let code = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+            Ok(Some(AccountInfo {
+                balance: U256::from(block),
+                code: Some(code),
+                ..Default::default()
+            }))
+        }
+
+        fn storage(&self, _address: Address, index: U256, _block: u64) -> Result<U256, ()> {
+            *self.queries.lock().unwrap() += 1;
+            Ok(index)
+        }
+
+        fn block_hash(&self, number: u64) -> Result<B256, ()> {
+            *self.queries.lock().unwrap() += 1;
+            Ok(B256::with_last_byte(number as u8))
+        }
+    }
+
+    #[test]
+    fn caches_per_block() {
+        let address = Address::with_last_byte(0x10);
+        let mut db = ForkDB::new(CountingBackend::default(), 10);
+
+        let info = db.basic_ref(address).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(10));
+        assert_eq!(
+            db.code_by_hash_ref(info.code_hash).unwrap(),
+            info.code.unwrap()
+        );
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(1)));
+        assert_eq!(
+            db.block_hash_ref(U256::from(9)),
+            Ok(B256::with_last_byte(9))
+        );
+        // Blocks after the fork block are unknown.
+        assert_eq!(db.block_hash_ref(U256::from(11)), Ok(B256::ZERO));
+        assert_eq!(*db.backend().queries.lock().unwrap(), 3);
+
+        // Cached values are not fetched again.
+        db.basic_ref(address).unwrap();
+        db.storage_ref(address, U256::from(1)).unwrap();
+        db.block_hash_ref(U256::from(9)).unwrap();
+        assert_eq!(*db.backend().queries.lock().unwrap(), 3);
+
+        db.set_block(20);
+        assert_eq!(
+            db.basic_ref(address).unwrap().unwrap().balance,
+            U256::from(20)
+        );
+        db.set_block(10);
+        assert_eq!(
+            db.basic_ref(address).unwrap().unwrap().balance,
+            U256::from(10)
+        );
+        assert_eq!(*db.backend().queries.lock().unwrap(), 4);
+    }
+}
@@ -0,0 +1,171 @@
+use super::BlockchainBackend;
+use crate::primitives::{AccountInfo, Address, Bytecode, Bytes, B256, U256};
+use core::{fmt, str::FromStr};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Error of [JsonRpcBackend].
+#[derive(Debug)]
+pub enum JsonRpcError {
+    /// Request failed to be sent or its response failed to be read.
+    Transport(reqwest::Error),
+    /// Node returned an error.
+    Rpc { code: i64, message: String },
+    /// Response could not be parsed.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonRpcError::Transport(e) => write!(f, "transport error: {e}"),
+            JsonRpcError::Rpc { code, message } => write!(f, "rpc error {code}: {message}"),
+            JsonRpcError::InvalidResponse(e) => write!(f, "invalid response: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
+impl From<reqwest::Error> for JsonRpcError {
+    fn from(e: reqwest::Error) -> Self {
+        JsonRpcError::Transport(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcErrorObject {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<Value>,
+    error: Option<RpcErrorObject>,
+}
+
+/// [BlockchainBackend] that queries an Ethereum JSON-RPC node over HTTP.
+///
+/// Requests are blocking, so it must not be used from within an async runtime.
+#[derive(Debug)]
+pub struct JsonRpcBackend {
+    client: reqwest::blocking::Client,
+    url: String,
+    id: AtomicU64,
+}
+
+impl JsonRpcBackend {
+    /// Creates new backend for the node at `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::blocking::Client::new(),
+            url: url.into(),
+            id: AtomicU64::new(0),
+        }
+    }
+
+    /// Calls `method` with `params` and returns the result.
+    pub fn request(&self, method: &str, params: Value) -> Result<Value, JsonRpcError> {
+        let id = self.id.fetch_add(1, Ordering::Relaxed);
+        let response: RpcResponse = self
+            .client
+            .post(&self.url)
+            .json(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .send()?
+            .json()?;
+        if let Some(error) = response.error {
+            return Err(JsonRpcError::Rpc {
+                code: error.code,
+                message: error.message,
+            });
+        }
+        response
+            .result
+            .ok_or_else(|| JsonRpcError::InvalidResponse(format!("{method} returned no result")))
+    }
+
+    fn request_parsed<T: FromStr>(&self, method: &str, params: Value) -> Result<T, JsonRpcError> {
+        let result = self.request(method, params)?;
+        let value = result
+            .as_str()
+            .ok_or_else(|| JsonRpcError::InvalidResponse(format!("{method} returned {result}")))?;
+        parse(value)
+    }
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T, JsonRpcError> {
+    value
+        .parse()
+        .map_err(|_| JsonRpcError::InvalidResponse(format!("can't parse {value}")))
+}
+
+fn parse_quantity(value: &str) -> Result<U256, JsonRpcError> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    U256::from_str_radix(digits, 16)
+        .map_err(|_| JsonRpcError::InvalidResponse(format!("can't parse {value}")))
+}
+
+fn block_tag(block: u64) -> String {
+    format!("{block:#x}")
+}
+
+impl BlockchainBackend for JsonRpcBackend {
+    type Error = JsonRpcError;
+
+    fn account(&self, address: Address, block: u64) -> Result<Option<AccountInfo>, Self::Error> {
+        let params = json!([address.to_string(), block_tag(block)]);
+        let balance: String = self.request_parsed("eth_getBalance", params.clone())?;
+        let nonce: String = self.request_parsed("eth_getTransactionCount", params.clone())?;
+        let code: Bytes = self.request_parsed("eth_getCode", params)?;
+
+        let balance = parse_quantity(&balance)?;
+        let nonce = parse_quantity(&nonce)?
+            .try_into()
+            .map_err(|_| JsonRpcError::InvalidResponse(format!("nonce {nonce} is too large")))?;
+        // JSON-RPC has no notion of non-existing accounts, empty ones are reported as such.
+        if balance == U256::ZERO && nonce == 0 && code.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(AccountInfo {
+            balance,
+            nonce,
+            code: Some(Bytecode::new_raw(code)),
+            ..Default::default()
+        }))
+    }
+
+    fn storage(&self, address: Address, index: U256, block: u64) -> Result<U256, Self::Error> {
+        let value: String = self.request_parsed(
+            "eth_getStorageAt",
+            json!([address.to_string(), format!("{index:#x}"), block_tag(block)]),
+        )?;
+        parse_quantity(&value)
+    }
+
+    fn block_hash(&self, number: u64) -> Result<B256, Self::Error> {
+        let block = self.request("eth_getBlockByNumber", json!([block_tag(number), false]))?;
+        let hash = block
+            .get("hash")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsonRpcError::InvalidResponse(format!("block {number} not found")))?;
+        parse(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quantities() {
+        assert_eq!(parse_quantity("0x0").unwrap(), U256::ZERO);
+        assert_eq!(
+            parse_quantity("0x1bc16d674ec80000").unwrap(),
+            U256::from(2e18 as u64)
+        );
+        assert!(parse_quantity("0xzz").is_err());
+        assert_eq!(block_tag(255), "0xff");
+    }
+}
@@ -17,12 +17,18 @@ pub type InMemoryDB = CacheDB<EmptyDB>;
 /// Accounts and code are stored in two separate maps, the `accounts` map maps addresses to [DbAccount],
 /// whereas contracts are identified by their code hash, and are stored in the `contracts` map.
 /// The [DbAccount] holds the code hash of the contract, which is used to look up the contract in the `contracts` map.
+///
+/// With `serde` feature the cache can be persisted. Contracts are stored together with their analysis
+/// artifacts and [BYTECODE_ANALYSIS_VERSION](crate::primitives::BYTECODE_ANALYSIS_VERSION), so reloaded
+/// contracts don't need to be analysed again.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CacheDB<ExtDB: DatabaseRef> {
     /// Account info where None means it is not existing. Not existing state is needed for Pre TANGERINE forks.
     /// `code` is always `None`, and bytecode can be found in `contracts`.
     pub accounts: HashMap<Address, DbAccount>,
     /// Tracks all contracts by their code hash.
+    #[cfg_attr(feature = "serde", serde(with = "serde_contracts"))]
     pub contracts: HashMap<B256, Bytecode>,
     /// All logs that were committed via [DatabaseCommit::commit].
     pub logs: Vec<Log>,
@@ -31,7 +37,87 @@ pub struct CacheDB<ExtDB: DatabaseRef> {
     /// The underlying database ([DatabaseRef]) that is used to load data.
     ///
     /// Note: this is read-only, data is never written to this database.
+    /// It is not serialized, default value is used when deserializing.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub db: ExtDB,
+    /// Undo layers of the open snapshots, see [CacheDB::snapshot].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    snapshots: Vec<SnapshotLayer>,
+    /// Identifier of the next snapshot.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    next_snapshot_id: u64,
+}
+
+/// Cached state of a [CacheDB], see [CacheDB::export].
+///
+/// With `serde` feature it can be written to disk and imported into a cache over another
+/// instance of the underlying database.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheDBState {
+    pub accounts: HashMap<Address, DbAccount>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_contracts"))]
+    pub contracts: HashMap<B256, Bytecode>,
+    pub logs: Vec<Log>,
+    pub block_hashes: HashMap<U256, B256>,
+}
+
+#[cfg(all(feature = "serde", feature = "std"))]
+impl CacheDBState {
+    /// Writes the state as JSON.
+    pub fn write_json(&self, writer: impl std::io::Write) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Reads the state from JSON written by [CacheDBState::write_json].
+    pub fn read_json(reader: impl std::io::Read) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+}
+
+/// Identifier of a [CacheDB] snapshot.
+///
+/// Identifiers are never reused, so the identifier of a discarded snapshot stays invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SnapshotId(u64);
+
+/// Values that accounts had when the snapshot was taken.
+///
+/// Only accounts changed after the snapshot are recorded, the first time they are changed.
+#[derive(Debug, Clone, Default)]
+struct SnapshotLayer {
+    id: u64,
+    /// Previous accounts, `None` if the account was not cached.
+    accounts: HashMap<Address, Option<DbAccount>>,
+    logs_len: usize,
+}
+
+/// Serializes contracts as [VersionedBytecode](crate::primitives::VersionedBytecode).
+#[cfg(feature = "serde")]
+mod serde_contracts {
+    use crate::primitives::{Bytecode, HashMap, VersionedBytecode, B256};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        contracts: &HashMap<B256, Bytecode>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            contracts
+                .iter()
+                .map(|(hash, code)| (hash, VersionedBytecode::from(code.clone()))),
+        )
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<B256, Bytecode>, D::Error> {
+        let contracts = HashMap::<B256, VersionedBytecode>::deserialize(deserializer)?;
+        Ok(contracts
+            .into_iter()
+            .map(|(hash, code)| (hash, code.into_bytecode()))
+            .collect())
+    }
 }
 
 impl<ExtDB: DatabaseRef + Default> Default for CacheDB<ExtDB> {
@@ -51,6 +137,65 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
             logs: Vec::default(),
             block_hashes: HashMap::new(),
             db,
+            snapshots: Vec::new(),
+            next_snapshot_id: 0,
+        }
+    }
+
+    /// Takes a snapshot of the cached state that can be restored with [CacheDB::revert_to].
+    ///
+    /// Snapshots are cheap, only accounts that are changed afterwards are copied, once per
+    /// snapshot. Contracts and block hashes are never reverted as they don't change.
+    pub fn snapshot(&mut self) -> SnapshotId {
+        let id = self.next_snapshot_id;
+        self.next_snapshot_id += 1;
+        self.snapshots.push(SnapshotLayer {
+            id,
+            logs_len: self.logs.len(),
+            ..Default::default()
+        });
+        SnapshotId(id)
+    }
+
+    /// Reverts all changes made after the snapshot was taken.
+    ///
+    /// The snapshot and all snapshots taken after it are discarded. Returns false if the
+    /// snapshot doesn't exist anymore.
+    pub fn revert_to(&mut self, id: SnapshotId) -> bool {
+        // Layers are ordered by their increasing identifiers.
+        let Ok(index) = self.snapshots.binary_search_by_key(&id.0, |layer| layer.id) else {
+            return false;
+        };
+        for layer in self.snapshots.drain(index..).rev() {
+            for (address, account) in layer.accounts {
+                match account {
+                    Some(account) => self.accounts.insert(address, account),
+                    None => self.accounts.remove(&address),
+                };
+            }
+            self.logs.truncate(layer.logs_len);
+        }
+        true
+    }
+
+    /// Discards all snapshots and keeps the current state.
+    pub fn flatten(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Returns the number of open snapshots.
+    pub fn snapshot_depth(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Records value of the account in the latest snapshot before it is changed.
+    fn record_account(&mut self, address: Address) {
+        if let Some(layer) = self.snapshots.last_mut() {
+            let accounts = &self.accounts;
+            layer
+                .accounts
+                .entry(address)
+                .or_insert_with(|| accounts.get(&address).cloned());
         }
     }
 
@@ -78,6 +223,7 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
     /// Insert account info but not override storage
     pub fn insert_account_info(&mut self, address: Address, mut info: AccountInfo) {
         self.insert_contract(&mut info);
+        self.record_account(address);
         self.accounts.entry(address).or_default().info = info;
     }
 
@@ -85,6 +231,7 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
     ///
     /// If the account was not found in the cache, it will be loaded from the underlying database.
     pub fn load_account(&mut self, address: Address) -> Result<&mut DbAccount, ExtDB::Error> {
+        self.record_account(address);
         let db = &self.db;
         match self.accounts.entry(address) {
             Entry::Occupied(entry) => Ok(entry.into_mut()),
@@ -111,6 +258,58 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
         Ok(())
     }
 
+    /// Inserts account infos, like [CacheDB::insert_account_info] but with capacity reserved
+    /// once for all of them.
+    pub fn extend_accounts(&mut self, accounts: impl IntoIterator<Item = (Address, AccountInfo)>) {
+        let accounts = accounts.into_iter();
+        self.accounts.reserve(accounts.size_hint().0);
+        for (address, info) in accounts {
+            self.insert_account_info(address, info);
+        }
+    }
+
+    /// Inserts storage slots of the account without overriding account info.
+    ///
+    /// The account is loaded once for all slots.
+    pub fn extend_storage(
+        &mut self,
+        address: Address,
+        storage: impl IntoIterator<Item = (U256, U256)>,
+    ) -> Result<(), ExtDB::Error> {
+        self.load_account(address)?.storage.extend(storage);
+        Ok(())
+    }
+
+    /// Inserts contracts with their known code hashes.
+    ///
+    /// Hashes are trusted and are not recomputed, so they must be the keccak hashes of the
+    /// original bytecodes.
+    pub fn insert_contracts(&mut self, contracts: impl IntoIterator<Item = (B256, Bytecode)>) {
+        self.contracts.extend(contracts);
+    }
+
+    /// Returns copy of the cached state, without the underlying database and snapshots.
+    pub fn export(&self) -> CacheDBState {
+        CacheDBState {
+            accounts: self.accounts.clone(),
+            contracts: self.contracts.clone(),
+            logs: self.logs.clone(),
+            block_hashes: self.block_hashes.clone(),
+        }
+    }
+
+    /// Merges exported state into the cache, overriding accounts present in both.
+    pub fn import(&mut self, state: CacheDBState) {
+        self.accounts.reserve(state.accounts.len());
+        for (address, account) in state.accounts {
+            self.record_account(address);
+            self.accounts.insert(address, account);
+        }
+        self.contracts.extend(state.contracts);
+        self.logs.extend(state.logs);
+        self.block_hashes.extend(state.block_hashes);
+    }
+
     /// replace account storage without overriding account info
     pub fn replace_account_storage(
         &mut self,
@@ -130,6 +329,7 @@ impl<ExtDB: DatabaseRef> DatabaseCommit for CacheDB<ExtDB> {
             if !account.is_touched() {
                 continue;
             }
+            self.record_account(address);
             if account.is_selfdestructed() {
                 let db_account = self.accounts.entry(address).or_default();
                 db_account.storage.clear();
@@ -288,6 +488,7 @@ impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DbAccount {
     pub info: AccountInfo,
     /// If account is selfdestructed or newly created, storage will be cleared.
@@ -330,6 +531,7 @@ impl From<AccountInfo> for DbAccount {
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AccountState {
     /// Before Spurious Dragon hardfork there was a difference between empty and not existing.
     /// And we are flagging it here.
@@ -453,4 +655,153 @@ mod tests {
         assert_eq!(new_state.storage(account, key0), Ok(U256::ZERO));
         assert_eq!(new_state.storage(account, key1), Ok(value1));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_analysed_contracts() {
+        use crate::{
+            interpreter::analysis::to_analysed,
+            primitives::{Bytecode, BytecodeState, Bytes},
+        };
+
+        let account = Address::with_last_byte(42);
+        let bytecode = to_analysed(Bytecode::new_raw(Bytes::from_static(&[0x5b, 0x00])));
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        init_state.insert_account_info(
+            account,
+            AccountInfo {
+                code_hash: bytecode.hash_slow(),
+                code: Some(bytecode.clone()),
+                ..Default::default()
+            },
+        );
+        let _ = init_state.insert_account_storage(account, U256::from(1), U256::from(2));
+
+        let serialized = serde_json::to_string(&init_state).unwrap();
+        let mut deserialized: CacheDB<EmptyDB> = serde_json::from_str(&serialized).unwrap();
+
+        let code_hash = bytecode.hash_slow();
+        let code = deserialized.code_by_hash(code_hash).unwrap();
+        assert!(matches!(code.state, BytecodeState::Analysed { .. }));
+        assert_eq!(code, bytecode);
+        assert_eq!(
+            deserialized.storage(account, U256::from(1)),
+            Ok(U256::from(2))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_outdated_analysis() {
+        use crate::{
+            interpreter::analysis::to_analysed,
+            primitives::{Bytecode, BytecodeState, Bytes, BYTECODE_ANALYSIS_VERSION},
+        };
+
+        let raw = Bytecode::new_raw(Bytes::from_static(&[0x5b, 0x00]));
+        let code_hash = raw.hash_slow();
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        init_state.insert_account_info(
+            Address::with_last_byte(42),
+            AccountInfo {
+                code_hash,
+                code: Some(to_analysed(raw.clone())),
+                ..Default::default()
+            },
+        );
+
+        // Contracts written by a version with another analysis format.
+        let mut serialized = serde_json::to_value(&init_state).unwrap();
+        for contract in serialized["contracts"]
+            .as_object_mut()
+            .unwrap()
+            .values_mut()
+        {
+            contract["version"] = BYTECODE_ANALYSIS_VERSION.wrapping_add(1).into();
+        }
+        let mut deserialized: CacheDB<EmptyDB> = serde_json::from_value(serialized).unwrap();
+
+        let code = deserialized.code_by_hash(code_hash).unwrap();
+        assert_eq!(code.state, BytecodeState::Raw);
+        assert_eq!(code, raw);
+    }
+
+    #[test]
+    fn test_snapshot_revert() {
+        let (a, b) = (Address::with_last_byte(0x10), Address::with_last_byte(0x20));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(a, AccountInfo::from_balance(U256::from(1)));
+
+        let first = db.snapshot();
+        db.insert_account_storage(a, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_info(b, AccountInfo::from_balance(U256::from(2)));
+
+        let second = db.snapshot();
+        db.insert_account_info(a, AccountInfo::from_balance(U256::from(3)));
+        assert_eq!(db.snapshot_depth(), 2);
+
+        assert!(db.revert_to(second));
+        assert_eq!(db.accounts[&a].info.balance, U256::from(1));
+        assert_eq!(db.accounts[&a].storage[&U256::from(1)], U256::from(1));
+        assert_eq!(db.snapshot_depth(), 1);
+        assert!(!db.revert_to(second));
+
+        // Snapshot taken after the revert doesn't reuse the identifier of the reverted one.
+        let after_revert = db.snapshot();
+        assert_ne!(after_revert, second);
+        assert!(!db.revert_to(second));
+        assert_eq!(db.snapshot_depth(), 2);
+
+        assert!(db.revert_to(first));
+        assert!(db.accounts[&a].storage.is_empty());
+        assert!(!db.accounts.contains_key(&b));
+        assert_eq!(db.snapshot_depth(), 0);
+        assert!(!db.revert_to(after_revert));
+
+        let third = db.snapshot();
+        db.insert_account_info(b, AccountInfo::from_balance(U256::from(2)));
+        db.flatten();
+        assert!(!db.revert_to(third));
+        assert!(db.accounts.contains_key(&b));
+    }
+
+    #[test]
+    fn test_bulk_insert_and_export() {
+        use crate::primitives::{Bytecode, Bytes};
+
+        let (a, b) = (Address::with_last_byte(0x10), Address::with_last_byte(0x20));
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_contracts([(code.hash_slow(), code.clone())]);
+        db.extend_accounts([
+            (a, AccountInfo::from_balance(U256::from(1))),
+            (b, AccountInfo::from_balance(U256::from(2))),
+        ]);
+        db.extend_storage(a, (0..3).map(|i| (U256::from(i), U256::from(i + 1))))
+            .unwrap();
+
+        let mut imported = CacheDB::new(EmptyDB::default());
+        imported.import(db.export());
+        assert_eq!(imported.code_by_hash(code.hash_slow()), Ok(code));
+        assert_eq!(imported.basic(b).unwrap().unwrap().balance, U256::from(2));
+        assert_eq!(imported.storage(a, U256::from(2)), Ok(U256::from(3)));
+    }
+
+    #[cfg(all(feature = "serde", feature = "std"))]
+    #[test]
+    fn test_state_json_roundtrip() {
+        use super::CacheDBState;
+
+        let account = Address::with_last_byte(0x10);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.extend_storage(account, [(U256::from(1), U256::from(2))])
+            .unwrap();
+
+        let mut json = Vec::new();
+        db.export().write_json(&mut json).unwrap();
+        let mut imported = CacheDB::new(EmptyDB::default());
+        imported.import(CacheDBState::read_json(json.as_slice()).unwrap());
+        assert_eq!(imported.storage(account, U256::from(1)), Ok(U256::from(2)));
+    }
 }
@@ -0,0 +1,210 @@
+//! Composable hooks around [Database] queries.
+//!
+//! Middleware observes or answers queries without implementing [Database] itself, and any
+//! number of middleware can be stacked over a database with [DatabaseLayerExt::layer]:
+//!
+//! ```
+//! use revm::db::{DatabaseLayerExt, EmptyDB, QueryStats};
+//!
+//! let db = EmptyDB::default().layer(QueryStats::default());
+//! ```
+
+use super::{Database, DatabaseCommit};
+use crate::primitives::{Account, AccountInfo, Address, Bytecode, HashMap, B256, U256};
+
+/// Query made to a [Database].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DatabaseQuery {
+    Basic(Address),
+    CodeByHash(B256),
+    Storage(Address, U256),
+    BlockHash(U256),
+}
+
+/// Response to a [DatabaseQuery] of the same kind.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DatabaseResponse {
+    Basic(Option<AccountInfo>),
+    CodeByHash(Bytecode),
+    Storage(U256),
+    BlockHash(B256),
+}
+
+/// Hooks called around every query of the database it is layered over, see [Layered].
+///
+/// `E` is the error type of the database.
+pub trait DatabaseMiddleware<E> {
+    /// Called before the query reaches the database.
+    ///
+    /// Returning some result answers the query without calling the database, the response must
+    /// be of the same kind as the query.
+    fn before(&mut self, query: &DatabaseQuery) -> Option<Result<DatabaseResponse, E>> {
+        let _ = query;
+        None
+    }
+
+    /// Called with the result of the query, including results returned by [Self::before] of
+    /// this middleware.
+    fn after(&mut self, query: &DatabaseQuery, result: &Result<DatabaseResponse, E>) {
+        let _ = (query, result);
+    }
+}
+
+/// [Database] with middleware applied to its queries.
+///
+/// Commits are forwarded to the database without calling the middleware.
+#[derive(Clone, Debug, Default)]
+pub struct Layered<M, DB> {
+    pub middleware: M,
+    pub db: DB,
+}
+
+impl<M, DB> Layered<M, DB> {
+    pub fn new(middleware: M, db: DB) -> Self {
+        Self { middleware, db }
+    }
+}
+
+impl<M: DatabaseMiddleware<DB::Error>, DB: Database> Layered<M, DB> {
+    fn query(&mut self, query: DatabaseQuery) -> Result<DatabaseResponse, DB::Error> {
+        let result = match self.middleware.before(&query) {
+            Some(result) => result,
+            None => match query {
+                DatabaseQuery::Basic(address) => {
+                    self.db.basic(address).map(DatabaseResponse::Basic)
+                }
+                DatabaseQuery::CodeByHash(code_hash) => self
+                    .db
+                    .code_by_hash(code_hash)
+                    .map(DatabaseResponse::CodeByHash),
+                DatabaseQuery::Storage(address, index) => self
+                    .db
+                    .storage(address, index)
+                    .map(DatabaseResponse::Storage),
+                DatabaseQuery::BlockHash(number) => {
+                    self.db.block_hash(number).map(DatabaseResponse::BlockHash)
+                }
+            },
+        };
+        self.middleware.after(&query, &result);
+        result
+    }
+}
+
+const MISMATCHED_RESPONSE: &str = "middleware answered query with response of a different kind";
+
+impl<M: DatabaseMiddleware<DB::Error>, DB: Database> Database for Layered<M, DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.query(DatabaseQuery::Basic(address))? {
+            DatabaseResponse::Basic(info) => Ok(info),
+            _ => panic!("{MISMATCHED_RESPONSE}"),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.query(DatabaseQuery::CodeByHash(code_hash))? {
+            DatabaseResponse::CodeByHash(code) => Ok(code),
+            _ => panic!("{MISMATCHED_RESPONSE}"),
+        }
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self.query(DatabaseQuery::Storage(address, index))? {
+            DatabaseResponse::Storage(value) => Ok(value),
+            _ => panic!("{MISMATCHED_RESPONSE}"),
+        }
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        match self.query(DatabaseQuery::BlockHash(number))? {
+            DatabaseResponse::BlockHash(hash) => Ok(hash),
+            _ => panic!("{MISMATCHED_RESPONSE}"),
+        }
+    }
+}
+
+impl<M, DB: DatabaseCommit> DatabaseCommit for Layered<M, DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes)
+    }
+}
+
+/// Extension trait for stacking middleware over a [Database].
+pub trait DatabaseLayerExt: Database + Sized {
+    /// Applies the middleware to the queries of this database.
+    ///
+    /// Middleware applied last sees the queries first.
+    fn layer<M: DatabaseMiddleware<Self::Error>>(self, middleware: M) -> Layered<M, Self> {
+        Layered::new(middleware, self)
+    }
+}
+
+impl<DB: Database> DatabaseLayerExt for DB {}
+
+/// Middleware that counts queries and failed queries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    pub basic: u64,
+    pub code_by_hash: u64,
+    pub storage: u64,
+    pub block_hash: u64,
+    pub errors: u64,
+}
+
+impl<E> DatabaseMiddleware<E> for QueryStats {
+    fn after(&mut self, query: &DatabaseQuery, result: &Result<DatabaseResponse, E>) {
+        match query {
+            DatabaseQuery::Basic(_) => self.basic += 1,
+            DatabaseQuery::CodeByHash(_) => self.code_by_hash += 1,
+            DatabaseQuery::Storage(..) => self.storage += 1,
+            DatabaseQuery::BlockHash(_) => self.block_hash += 1,
+        }
+        if result.is_err() {
+            self.errors += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{CacheDB, EmptyDB};
+
+    /// Answers storage queries of one address without reaching the database.
+    struct FixedStorage(Address);
+
+    impl<E> DatabaseMiddleware<E> for FixedStorage {
+        fn before(&mut self, query: &DatabaseQuery) -> Option<Result<DatabaseResponse, E>> {
+            match query {
+                DatabaseQuery::Storage(address, _) if *address == self.0 => {
+                    Some(Ok(DatabaseResponse::Storage(U256::from(7))))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn stacked_middleware() {
+        let (a, b) = (Address::with_last_byte(0x10), Address::with_last_byte(0x20));
+        let mut cache = CacheDB::new(EmptyDB::default());
+        cache
+            .insert_account_storage(b, U256::from(1), U256::from(1))
+            .unwrap();
+
+        let mut db = cache
+            .layer(QueryStats::default())
+            .layer(FixedStorage(a))
+            .layer(QueryStats::default());
+        assert_eq!(db.storage(a, U256::from(1)), Ok(U256::from(7)));
+        assert_eq!(db.storage(b, U256::from(1)), Ok(U256::from(1)));
+        db.basic(a).unwrap();
+
+        // Outer stats see all queries, inner only the ones that were not answered.
+        assert_eq!((db.middleware.storage, db.middleware.basic), (2, 1));
+        let inner = &db.db.db.middleware;
+        assert_eq!((inner.storage, inner.basic), (1, 1));
+    }
+}
@@ -0,0 +1,149 @@
+use core::convert::Infallible;
+use revm_interpreter::primitives::{
+    db::DatabaseRef, keccak256, AccountInfo, Address, Bytecode, Env, HashMap, B256, U256,
+};
+
+/// Read-only database for unit tests of contracts that depend on the chain environment.
+///
+/// Like [crate::db::EmptyDB] it has no accounts or storage, but block hashes, chain defaults
+/// and the balance of every account can be configured. Wrap it in [crate::db::CacheDB] to
+/// execute transactions on top of it.
+///
+/// ```
+/// use revm::db::MockDB;
+/// use revm::primitives::{Env, B256, U256};
+///
+/// let db = MockDB::new()
+///     .with_chain_id(10)
+///     .with_block_number(100)
+///     .with_block_hash(99, B256::repeat_byte(0x99))
+///     .with_default_balance(U256::from(1_000_000));
+/// let mut env = Env::default();
+/// db.configure_env(&mut env);
+/// assert_eq!(env.cfg.chain_id, 10);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MockDB {
+    block_hashes: HashMap<U256, B256>,
+    default_balance: Option<U256>,
+    chain_id: Option<u64>,
+    block_number: Option<u64>,
+    basefee: Option<U256>,
+}
+
+impl MockDB {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets hash of the block, hashes of other blocks are `keccak256(number)` like in
+    /// [crate::db::EmptyDB].
+    pub fn with_block_hash(mut self, number: u64, hash: B256) -> Self {
+        self.block_hashes.insert(U256::from(number), hash);
+        self
+    }
+
+    /// Makes every account exist with the balance.
+    pub fn with_default_balance(mut self, balance: U256) -> Self {
+        self.default_balance = Some(balance);
+        self
+    }
+
+    /// Sets chain id applied by [MockDB::configure_env].
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Sets block number applied by [MockDB::configure_env].
+    pub fn with_block_number(mut self, number: u64) -> Self {
+        self.block_number = Some(number);
+        self
+    }
+
+    /// Sets base fee applied by [MockDB::configure_env].
+    pub fn with_basefee(mut self, basefee: U256) -> Self {
+        self.basefee = Some(basefee);
+        self
+    }
+
+    /// Applies the configured chain defaults to the environment, values that are not set are
+    /// left unchanged.
+    pub fn configure_env(&self, env: &mut Env) {
+        if let Some(chain_id) = self.chain_id {
+            env.cfg.chain_id = chain_id;
+        }
+        if let Some(number) = self.block_number {
+            env.block.number = U256::from(number);
+        }
+        if let Some(basefee) = self.basefee {
+            env.block.basefee = basefee;
+        }
+    }
+}
+
+impl DatabaseRef for MockDB {
+    type Error = Infallible;
+
+    fn basic_ref(&self, _address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        Ok(self.default_balance.map(AccountInfo::from_balance))
+    }
+
+    fn code_by_hash_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+        Ok(Bytecode::new())
+    }
+
+    fn storage_ref(&self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
+        Ok(U256::ZERO)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        Ok(match self.block_hashes.get(&number) {
+            Some(hash) => *hash,
+            None => keccak256(number.to_be_bytes::<{ U256::BYTES }>()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::CacheDB,
+        primitives::{Bytes, TransactTo},
+        EVM,
+    };
+
+    #[test]
+    fn blockhash_from_mock() {
+        let contract = Address::with_last_byte(0x20);
+        // PUSH1 99 BLOCKHASH PUSH1 0 SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x63, 0x40, 0x60, 0x00, 0x55]));
+        let mock = MockDB::new()
+            .with_block_number(100)
+            .with_block_hash(99, B256::repeat_byte(0x99))
+            .with_default_balance(U256::from(1_000_000_000));
+
+        let mut evm = EVM::new();
+        mock.configure_env(&mut evm.env);
+        let mut db = CacheDB::new(mock);
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+
+        let output = evm.transact().unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(
+            output.state[&contract].storage[&U256::ZERO].present_value,
+            U256::from_be_bytes(B256::repeat_byte(0x99).0)
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use super::{DatabaseCommit, DatabaseRef};
+use crate::primitives::{Account, AccountInfo, Address, Bytecode, HashMap, B256, U256};
+use crate::Database;
+
+/// Decimals of the native token as seen by the EVM.
+pub const EVM_NATIVE_DECIMALS: u8 = 18;
+
+/// Database adapter for chains whose native token doesn't have 18 decimals.
+///
+/// Balances in the wrapped database are in the smallest unit of the native token, while the EVM
+/// sees them scaled to 18 decimals. This way gas prices, fees and value transfers are all
+/// accounted in the same unit as on Ethereum.
+///
+/// Amounts smaller than one native unit that result from the execution are kept in
+/// `remainders` and added back when the account is loaded, so no value is lost or created.
+#[derive(Debug, Clone)]
+pub struct NativeDecimalsDB<DB> {
+    /// The underlying database with balances in native units.
+    pub db: DB,
+    /// Amounts below one native unit, in EVM units.
+    pub remainders: HashMap<Address, U256>,
+    scale: U256,
+}
+
+impl<DB> NativeDecimalsDB<DB> {
+    /// Creates new adapter for a native token with `decimals`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `decimals` is above [EVM_NATIVE_DECIMALS].
+    pub fn new(db: DB, decimals: u8) -> Self {
+        assert!(
+            decimals <= EVM_NATIVE_DECIMALS,
+            "native token can't have more than {EVM_NATIVE_DECIMALS} decimals"
+        );
+        Self {
+            db,
+            remainders: HashMap::new(),
+            scale: U256::from(10).pow(U256::from(EVM_NATIVE_DECIMALS - decimals)),
+        }
+    }
+
+    /// Converts balance in native units to the balance seen by the EVM.
+    pub fn to_evm_balance(&self, address: &Address, balance: U256) -> U256 {
+        let remainder = self.remainders.get(address).copied().unwrap_or_default();
+        balance.saturating_mul(self.scale).saturating_add(remainder)
+    }
+
+    /// Splits balance seen by the EVM into native units and remainder.
+    pub fn to_native_balance(&self, balance: U256) -> (U256, U256) {
+        (balance / self.scale, balance % self.scale)
+    }
+
+    /// Scales the balance of the account. An account that is missing from the wrapped database
+    /// but has a remainder, e.g. because it was cleared as empty, is created with it.
+    fn map_info(&self, address: &Address, info: Option<AccountInfo>) -> Option<AccountInfo> {
+        match info {
+            Some(mut info) => {
+                info.balance = self.to_evm_balance(address, info.balance);
+                Some(info)
+            }
+            None => self
+                .remainders
+                .get(address)
+                .map(|remainder| AccountInfo::from_balance(*remainder)),
+        }
+    }
+}
+
+impl<DB: Database> Database for NativeDecimalsDB<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        Ok(self.map_info(&address, info))
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.db.storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.db.block_hash(number)
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for NativeDecimalsDB<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_ref(address)?;
+        Ok(self.map_info(&address, info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for NativeDecimalsDB<DB> {
+    fn commit(&mut self, mut changes: HashMap<Address, Account>) {
+        for (address, account) in changes.iter_mut() {
+            if !account.is_touched() {
+                continue;
+            }
+            let (balance, remainder) = self.to_native_balance(account.info.balance);
+            account.info.balance = balance;
+            if remainder == U256::ZERO || account.is_selfdestructed() {
+                self.remainders.remove(address);
+            } else {
+                self.remainders.insert(*address, remainder);
+            }
+        }
+        self.db.commit(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{TransactTo, TxEnv},
+        EVM,
+    };
+
+    #[test]
+    fn value_transfer_keeps_remainder() {
+        let caller = Address::with_last_byte(0x10);
+        let callee = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // 5 units of a native token with 6 decimals.
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(5_000_000)));
+
+        let mut evm = EVM::new();
+        evm.database(NativeDecimalsDB::new(db, 6));
+        evm.env.tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(callee),
+            // 1.5 micro units.
+            value: U256::from(1_500_000_000_000u64),
+            gas_price: U256::from(1_000_000_000),
+            gas_limit: 21_000,
+            ..Default::default()
+        };
+        assert!(evm.transact_commit().unwrap().is_success());
+
+        let db = evm.db.as_mut().unwrap();
+        // Callee got 1 native unit and 0.5 is kept as remainder.
+        assert_eq!(db.db.accounts[&callee].info.balance, U256::from(1));
+        assert_eq!(db.remainders[&callee], U256::from(500_000_000_000u64));
+        assert_eq!(
+            db.basic(callee).unwrap().unwrap().balance,
+            U256::from(1_500_000_000_000u64)
+        );
+
+        // Fee of 21_000 gwei is charged in native units as well.
+        let spent = U256::from(1_500_000_000_000u64 + 21_000 * 1_000_000_000u64);
+        let caller_balance = db.basic(caller).unwrap().unwrap().balance;
+        assert_eq!(
+            caller_balance,
+            U256::from(5_000_000) * U256::from(1_000_000_000_000u64) - spent
+        );
+    }
+
+    #[test]
+    fn remainder_of_missing_account_is_kept() {
+        let caller = Address::with_last_byte(0x10);
+        let callee = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(5_000_000)));
+
+        let mut evm = EVM::new();
+        evm.database(NativeDecimalsDB::new(db, 6));
+        evm.env.tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(callee),
+            // Half of a native unit, the callee stays empty in the wrapped database.
+            value: U256::from(500_000_000_000u64),
+            gas_limit: 21_000,
+            ..Default::default()
+        };
+        assert!(evm.transact_commit().unwrap().is_success());
+
+        let db = evm.db.as_mut().unwrap();
+        db.db.accounts.remove(&callee);
+        assert_eq!(
+            db.basic(callee).unwrap(),
+            Some(AccountInfo::from_balance(U256::from(500_000_000_000u64)))
+        );
+        assert_eq!(
+            db.basic_ref(callee).unwrap(),
+            Some(AccountInfo::from_balance(U256::from(500_000_000_000u64)))
+        );
+    }
+}
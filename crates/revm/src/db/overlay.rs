@@ -0,0 +1,120 @@
+use super::DatabaseRef;
+use crate::primitives::{AccountInfo, Address, Bytecode, HashMap, B256, U256};
+
+/// Overrides of a single account, see [StateOverlay].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountOverlay {
+    /// Replaces account info if set. Code should be included in the info.
+    pub info: Option<AccountInfo>,
+    /// Replaces storage slots.
+    pub storage: HashMap<U256, U256>,
+    /// If true, slots that are not in `storage` are zero instead of being read from database.
+    pub replace_storage: bool,
+}
+
+/// State overrides applied on top of a read-only database, similar to state overrides of
+/// `eth_call`.
+pub type StateOverlay = HashMap<Address, AccountOverlay>;
+
+/// [DatabaseRef] that reads [StateOverlay] before the underlying database.
+///
+/// Both the overlay and the database are only borrowed, all changes of the execution stay in
+/// the journaled state, so one database can be shared by concurrent executions.
+#[derive(Debug, Clone, Copy)]
+pub struct OverlayDB<'a, ExtDB: DatabaseRef> {
+    /// The underlying database.
+    pub db: ExtDB,
+    /// Overrides that take precedence over the database.
+    pub overlay: &'a StateOverlay,
+}
+
+impl<'a, ExtDB: DatabaseRef> OverlayDB<'a, ExtDB> {
+    pub fn new(db: ExtDB, overlay: &'a StateOverlay) -> Self {
+        Self { db, overlay }
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for OverlayDB<'_, ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.overlay.get(&address).and_then(|acc| acc.info.as_ref()) {
+            Some(info) => Ok(Some(info.clone())),
+            None => self.db.basic_ref(address),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self
+            .overlay
+            .values()
+            .filter_map(|acc| acc.info.as_ref())
+            .find(|info| info.code_hash == code_hash)
+            .and_then(|info| info.code.clone());
+        match code {
+            Some(code) => Ok(code),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self.overlay.get(&address) {
+            Some(acc) => match acc.storage.get(&index) {
+                Some(value) => Ok(*value),
+                None if acc.replace_storage => Ok(U256::ZERO),
+                None => self.db.storage_ref(address, index),
+            },
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{CacheDB, EmptyDB};
+
+    #[test]
+    fn overlay_takes_precedence() {
+        let address = Address::with_last_byte(0x10);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(address, AccountInfo::from_balance(U256::from(1)));
+        db.insert_account_storage(address, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_storage(address, U256::from(2), U256::from(2))
+            .unwrap();
+
+        let mut overlay = StateOverlay::default();
+        overlay.insert(
+            address,
+            AccountOverlay {
+                info: Some(AccountInfo::from_balance(U256::from(10))),
+                storage: HashMap::from_iter([(U256::from(1), U256::from(10))]),
+                replace_storage: false,
+            },
+        );
+
+        let overlay_db = OverlayDB::new(&db, &overlay);
+        let info = overlay_db.basic_ref(address).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(10));
+        assert_eq!(
+            overlay_db.storage_ref(address, U256::from(1)),
+            Ok(U256::from(10))
+        );
+        assert_eq!(
+            overlay_db.storage_ref(address, U256::from(2)),
+            Ok(U256::from(2))
+        );
+
+        overlay.get_mut(&address).unwrap().replace_storage = true;
+        let overlay_db = OverlayDB::new(&db, &overlay);
+        assert_eq!(
+            overlay_db.storage_ref(address, U256::from(2)),
+            Ok(U256::ZERO)
+        );
+    }
+}
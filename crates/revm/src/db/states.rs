@@ -1,9 +1,11 @@
+pub mod account_lifecycle;
 pub mod account_status;
 pub mod bundle_account;
 pub mod bundle_state;
 pub mod cache;
 pub mod cache_account;
 pub mod changes;
+pub mod diff;
 pub mod plain_account;
 pub mod reverts;
 pub mod state;
@@ -11,13 +13,15 @@ pub mod state_builder;
 pub mod transition_account;
 pub mod transition_state;
 
+pub use account_lifecycle::{AccountLifecycle, AccountLifecycleHook};
 /// Account status for Block and Bundle states.
 pub use account_status::AccountStatus;
 pub use bundle_account::BundleAccount;
-pub use bundle_state::{BundleBuilder, BundleState, OriginalValuesKnown};
+pub use bundle_state::{BundleBuilder, BundleState, OriginalValuesKnown, RevertsLimit};
 pub use cache::CacheState;
 pub use cache_account::CacheAccount;
 pub use changes::{PlainStateReverts, PlainStorageChangeset, PlainStorageRevert, StateChangeset};
+pub use diff::{AccountDiff, StateDiff, StorageDiff};
 pub use plain_account::{PlainAccount, StorageWithOriginalValues};
 pub use reverts::{AccountRevert, RevertToSlot};
 pub use state::{DBBox, State, StateDBBox};
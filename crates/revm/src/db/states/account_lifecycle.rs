@@ -0,0 +1,49 @@
+use alloc::boxed::Box;
+use core::fmt;
+use revm_interpreter::primitives::{Account, Address};
+
+/// Lifecycle event of an account committed to the [super::State].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AccountLifecycle {
+    /// Account was created by `CREATE` or `CREATE2`.
+    Created,
+    /// Account that existed before the transaction was selfdestructed.
+    Destroyed,
+    /// Account was created and selfdestructed in the same transaction.
+    CreatedAndDestroyed,
+}
+
+impl AccountLifecycle {
+    /// Returns lifecycle event of the account changed by the transaction.
+    pub fn of(account: &Account) -> Option<Self> {
+        if !account.is_touched() {
+            return None;
+        }
+        match (account.is_created(), account.is_selfdestructed()) {
+            (true, true) => Some(Self::CreatedAndDestroyed),
+            (true, false) => Some(Self::Created),
+            (false, true) => Some(Self::Destroyed),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Callback that [super::State] calls on commit for every account lifecycle event.
+pub struct AccountLifecycleHook(Box<dyn FnMut(Address, AccountLifecycle) + Send>);
+
+impl AccountLifecycleHook {
+    pub fn new(hook: impl FnMut(Address, AccountLifecycle) + Send + 'static) -> Self {
+        Self(Box::new(hook))
+    }
+
+    pub fn call(&mut self, address: Address, event: AccountLifecycle) {
+        (self.0)(address, event)
+    }
+}
+
+impl fmt::Debug for AccountLifecycleHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountLifecycleHook")
+            .finish_non_exhaustive()
+    }
+}
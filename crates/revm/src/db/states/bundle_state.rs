@@ -247,6 +247,33 @@ impl BundleRetention {
     }
 }
 
+/// Limits of the reverts retained in [BundleState], see [BundleState::prune_reverts].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RevertsLimit {
+    /// Maximum number of retained transitions, usually one transition is one block.
+    pub max_transitions: Option<usize>,
+    /// Maximum approximate size of retained reverts, see [BundleState::reverts_size].
+    pub max_size: Option<usize>,
+}
+
+impl RevertsLimit {
+    /// Limits reverts to the last `max_transitions` transitions.
+    pub fn transitions(max_transitions: usize) -> Self {
+        Self {
+            max_transitions: Some(max_transitions),
+            max_size: None,
+        }
+    }
+
+    /// Limits reverts to the approximate `max_size`.
+    pub fn size(max_size: usize) -> Self {
+        Self {
+            max_transitions: None,
+            max_size: Some(max_size),
+        }
+    }
+}
+
 /// Bundle state contain only values that got changed
 ///
 /// For every account it contains both original and present state.
@@ -255,6 +282,7 @@ impl BundleRetention {
 /// Reverts and created when TransitionState is applied to BundleState.
 /// And can be used to revert BundleState to the state before transition.
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BundleState {
     /// Account state.
     pub state: HashMap<Address, BundleAccount>,
@@ -608,6 +636,37 @@ impl BundleState {
         detached_reverts
     }
 
+    /// Drains the oldest reverts until the retained reverts are within the `limit`.
+    ///
+    /// Drained reverts are passed to `on_pruned` one transition at a time, oldest first, so they
+    /// can be persisted before they are dropped. Returns the number of drained transitions.
+    pub fn prune_reverts(
+        &mut self,
+        limit: RevertsLimit,
+        mut on_pruned: impl FnMut(Vec<(Address, AccountRevert)>),
+    ) -> usize {
+        let mut to_prune = limit
+            .max_transitions
+            .map_or(0, |max| self.reverts.len().saturating_sub(max));
+        let mut size = self.reverts_size;
+        for (index, reverts) in self.reverts.iter().enumerate() {
+            if index >= to_prune && limit.max_size.is_none_or(|max| size <= max) {
+                break;
+            }
+            size -= reverts
+                .iter()
+                .map(|(_, revert)| revert.size_hint())
+                .sum::<usize>();
+            to_prune = to_prune.max(index + 1);
+        }
+
+        self.reverts_size = size;
+        for reverts in self.reverts.drain(..to_prune) {
+            on_pruned(reverts);
+        }
+        to_prune
+    }
+
     /// Return and clear all reverts from [BundleState]
     pub fn take_all_reverts(&mut self) -> Reverts {
         self.reverts_size = 0;
@@ -697,6 +756,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn prune_reverts_to_limit() {
+        let address = Address::new([0x01; 20]);
+        let block_reverts = |slots: u64| {
+            vec![(
+                address,
+                None,
+                (0..slots).map(|slot| (U256::from(slot), U256::ZERO)),
+            )]
+        };
+        // Sizes of the reverts are 2, 3, 4 and 5.
+        let new_bundle = || {
+            BundleState::new(
+                [],
+                [
+                    block_reverts(1),
+                    block_reverts(2),
+                    block_reverts(3),
+                    block_reverts(4),
+                ],
+                [],
+            )
+        };
+
+        let mut bundle = new_bundle();
+        let mut pruned = Vec::new();
+        let count = bundle.prune_reverts(RevertsLimit::transitions(3), |reverts| {
+            pruned.push(reverts[0].1.storage.len())
+        });
+        assert_eq!((count, pruned), (1, vec![1]));
+        assert_eq!((bundle.reverts.len(), bundle.reverts_size), (3, 12));
+
+        let mut bundle = new_bundle();
+        assert_eq!(bundle.prune_reverts(RevertsLimit::size(9), |_| {}), 2);
+        assert_eq!((bundle.reverts.len(), bundle.reverts_size), (2, 9));
+
+        let mut bundle = new_bundle();
+        let limit = RevertsLimit {
+            max_transitions: Some(3),
+            max_size: Some(100),
+        };
+        assert_eq!(bundle.prune_reverts(limit, |_| {}), 1);
+        assert_eq!(bundle.prune_reverts(limit, |_| {}), 0);
+    }
+
     const fn account1() -> Address {
         Address::new([0x60; 20])
     }
@@ -0,0 +1,214 @@
+use super::{BundleState, StorageWithOriginalValues, TransitionState};
+use revm_interpreter::primitives::{AccountInfo, Address, Bytecode, HashMap, B256, U256};
+
+/// Changes of accounts, independent of the state representation they were taken from.
+pub type StateDiff = HashMap<Address, AccountDiff>;
+
+/// Original and present value of a changed storage slot.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageDiff {
+    pub original: U256,
+    pub present: U256,
+}
+
+/// Changes of a single account.
+///
+/// Account infos never contain code, changed code is in [AccountDiff::code].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountDiff {
+    /// Account before the changes, `None` if it didn't exist.
+    pub original_info: Option<AccountInfo>,
+    /// Account after the changes, `None` if it doesn't exist.
+    pub info: Option<AccountInfo>,
+    /// Changed storage slots.
+    pub storage: HashMap<U256, StorageDiff>,
+    /// If true, storage was cleared and slots that are not in `storage` are zero.
+    pub storage_cleared: bool,
+    /// New code of the account, set only if the code hash changed.
+    pub code: Option<Bytecode>,
+}
+
+impl AccountDiff {
+    fn new(
+        original_info: Option<&AccountInfo>,
+        info: Option<&AccountInfo>,
+        storage: &StorageWithOriginalValues,
+        storage_cleared: bool,
+        contracts: &HashMap<B256, Bytecode>,
+    ) -> Self {
+        let original_hash = original_info.map(AccountInfo::code_hash);
+        let code = info
+            .filter(|info| Some(info.code_hash) != original_hash)
+            .and_then(|info| {
+                info.code
+                    .clone()
+                    .or_else(|| contracts.get(&info.code_hash).cloned())
+            });
+        Self {
+            original_info: original_info.cloned().map(AccountInfo::without_code),
+            info: info.cloned().map(AccountInfo::without_code),
+            storage: storage
+                .iter()
+                .filter(|(_, slot)| storage_cleared || slot.is_changed())
+                .map(|(index, slot)| {
+                    let diff = StorageDiff {
+                        original: slot.original_value(),
+                        present: slot.present_value(),
+                    };
+                    (*index, diff)
+                })
+                .collect(),
+            storage_cleared,
+            code,
+        }
+    }
+
+    /// Returns true if the account didn't change.
+    pub fn is_empty(&self) -> bool {
+        self.original_info == self.info
+            && self.storage.is_empty()
+            && !self.storage_cleared
+            && self.code.is_none()
+    }
+}
+
+impl BundleState {
+    /// Returns changes of all accounts in the bundle, unchanged accounts are skipped.
+    pub fn to_diff(&self) -> StateDiff {
+        self.state
+            .iter()
+            .map(|(address, account)| {
+                let diff = AccountDiff::new(
+                    account.original_info.as_ref(),
+                    account.info.as_ref(),
+                    &account.storage,
+                    account.status.was_destroyed(),
+                    &self.contracts,
+                );
+                (*address, diff)
+            })
+            .filter(|(_, diff)| !diff.is_empty())
+            .collect()
+    }
+}
+
+impl TransitionState {
+    /// Returns changes of all accounts in the transitions, unchanged accounts are skipped.
+    pub fn to_diff(&self) -> StateDiff {
+        self.transitions
+            .iter()
+            .map(|(address, account)| {
+                let diff = AccountDiff::new(
+                    account.previous_info.as_ref(),
+                    account.info.as_ref(),
+                    &account.storage,
+                    account.storage_was_destroyed,
+                    &HashMap::new(),
+                );
+                (*address, diff)
+            })
+            .filter(|(_, diff)| !diff.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{AccountStatus, BundleAccount, TransitionAccount};
+    use revm_interpreter::primitives::{Bytes, StorageSlot};
+
+    #[test]
+    fn diff_of_transitions_and_bundle() {
+        let (a, b) = (Address::with_last_byte(0x10), Address::with_last_byte(0x20));
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+        let info = AccountInfo {
+            nonce: 1,
+            code_hash: code.hash_slow(),
+            code: Some(code.clone()),
+            ..Default::default()
+        };
+        let storage = HashMap::from_iter([
+            (
+                U256::from(1),
+                StorageSlot::new_changed(U256::ZERO, U256::from(2)),
+            ),
+            (U256::from(2), StorageSlot::new(U256::from(3))),
+        ]);
+
+        let transitions = TransitionState::single(
+            a,
+            TransitionAccount {
+                info: Some(info.clone()),
+                status: AccountStatus::InMemoryChange,
+                previous_status: AccountStatus::LoadedNotExisting,
+                storage,
+                ..Default::default()
+            },
+        );
+        let diff = transitions.to_diff();
+        let account = &diff[&a];
+        assert_eq!(account.original_info, None);
+        assert_eq!(account.info.as_ref().unwrap().code, None);
+        assert_eq!(account.code, Some(code));
+        assert_eq!(account.storage.len(), 1);
+        assert_eq!(account.storage[&U256::from(1)].present, U256::from(2));
+
+        let mut bundle = BundleState::default();
+        bundle.state.insert(
+            b,
+            BundleAccount::new(
+                Some(info.clone()),
+                Some(info),
+                HashMap::new(),
+                AccountStatus::Loaded,
+            ),
+        );
+        assert!(bundle.to_diff().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use crate::db::states::bundle_state::BundleRetention;
+
+        let address = Address::with_last_byte(0x10);
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x00]));
+        let info = AccountInfo {
+            balance: U256::from(10),
+            nonce: 1,
+            code_hash: code.hash_slow(),
+            code: Some(code),
+        };
+        let transitions = TransitionState::single(
+            address,
+            TransitionAccount {
+                info: Some(info),
+                status: AccountStatus::InMemoryChange,
+                previous_status: AccountStatus::LoadedNotExisting,
+                storage: HashMap::from_iter([(
+                    U256::from(1),
+                    StorageSlot::new_changed(U256::ZERO, U256::from(2)),
+                )]),
+                ..Default::default()
+            },
+        );
+        let json = serde_json::to_string(&transitions).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TransitionState>(&json).unwrap(),
+            transitions
+        );
+
+        let diff = transitions.to_diff();
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(serde_json::from_str::<StateDiff>(&json).unwrap(), diff);
+
+        let mut bundle = BundleState::default();
+        bundle.apply_transitions_and_create_reverts(transitions, BundleRetention::Reverts);
+        assert_eq!(bundle.reverts.len(), 1);
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert_eq!(serde_json::from_str::<BundleState>(&json).unwrap(), bundle);
+    }
+}
@@ -1,6 +1,10 @@
 use super::{
-    bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, BundleState,
-    CacheAccount, StateBuilder, TransitionAccount, TransitionState,
+    account_lifecycle::{AccountLifecycle, AccountLifecycleHook},
+    bundle_state::BundleRetention,
+    cache::CacheState,
+    plain_account::PlainStorage,
+    AccountRevert, BundleState, CacheAccount, RevertsLimit, StateBuilder, TransitionAccount,
+    TransitionState,
 };
 use crate::db::EmptyDB;
 use alloc::{
@@ -56,6 +60,14 @@ pub struct State<DB> {
     /// This map can be used to give different values for block hashes if in case
     /// The fork block is different or some blocks are not saved inside database.
     pub block_hashes: BTreeMap<u64, B256>,
+    /// If set, reverts of the bundle state are pruned to the limit when transitions are merged.
+    ///
+    /// Use [State::merge_transitions_with_pruned] to receive the pruned reverts.
+    pub reverts_limit: Option<RevertsLimit>,
+    /// If set, it is called on commit for created and destroyed accounts.
+    ///
+    /// Use [State::set_account_hook] to set it.
+    pub account_hook: Option<AccountLifecycleHook>,
 }
 
 // Have ability to call State::builder without having to specify the type.
@@ -130,6 +142,9 @@ impl<DB: Database> State<DB> {
     }
 
     /// State clear EIP-161 is enabled in Spurious Dragon hardfork.
+    ///
+    /// Chains that never adopted it keep touched empty accounts, set it with
+    /// [crate::primitives::CfgEnv::is_state_clear_enabled] to follow the config of the EVM.
     pub fn set_state_clear_flag(&mut self, has_state_clear: bool) {
         self.cache.set_state_clear_flag(has_state_clear);
     }
@@ -160,15 +175,37 @@ impl<DB: Database> State<DB> {
         }
     }
 
+    /// Sets the hook that is called on commit for every account that was created, destroyed or
+    /// created and destroyed in the same transaction.
+    pub fn set_account_hook(
+        &mut self,
+        hook: impl FnMut(Address, AccountLifecycle) + Send + 'static,
+    ) {
+        self.account_hook = Some(AccountLifecycleHook::new(hook));
+    }
+
     /// Take all transitions and merge them inside bundle state.
     /// This action will create final post state and all reverts so that
     /// we at any time revert state of bundle to the state before transition
     /// is applied.
     pub fn merge_transitions(&mut self, retention: BundleRetention) {
+        self.merge_transitions_with_pruned(retention, |_| {});
+    }
+
+    /// Merges transitions like [State::merge_transitions] and passes reverts pruned because of
+    /// [State::reverts_limit] to `on_pruned`, oldest first.
+    pub fn merge_transitions_with_pruned(
+        &mut self,
+        retention: BundleRetention,
+        on_pruned: impl FnMut(Vec<(Address, AccountRevert)>),
+    ) {
         if let Some(transition_state) = self.transition_state.as_mut().map(TransitionState::take) {
             self.bundle_state
                 .apply_transitions_and_create_reverts(transition_state, retention);
         }
+        if let Some(limit) = self.reverts_limit {
+            self.bundle_state.prune_reverts(limit, on_pruned);
+        }
     }
 
     pub fn load_cache_account(&mut self, address: Address) -> Result<&mut CacheAccount, DB::Error> {
@@ -293,6 +330,13 @@ impl<DB: Database> Database for State<DB> {
 
 impl<DB: Database> DatabaseCommit for State<DB> {
     fn commit(&mut self, evm_state: HashMap<Address, Account>) {
+        if let Some(hook) = self.account_hook.as_mut() {
+            for (address, account) in evm_state.iter() {
+                if let Some(event) = AccountLifecycle::of(account) {
+                    hook.call(*address, event);
+                }
+            }
+        }
         let transitions = self.cache.apply_evm_state(evm_state);
         self.apply_transition(transitions);
     }
@@ -839,4 +883,48 @@ mod tests {
             )])])
         )
     }
+
+    #[test]
+    fn account_hook_reports_lifecycle() {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut state = State::builder().build();
+        let hook_events = events.clone();
+        state.set_account_hook(move |address, event| {
+            hook_events.lock().unwrap().push((address, event))
+        });
+
+        let [created, destroyed, ephemeral, changed] = [1, 2, 3, 4].map(Address::with_last_byte);
+        let mut evm_state = HashMap::new();
+        for (address, created, selfdestructed) in [
+            (created, true, false),
+            (destroyed, false, true),
+            (ephemeral, true, true),
+            (changed, false, false),
+        ] {
+            state.basic(address).unwrap();
+            let mut account = Account::from(AccountInfo::from_balance(U256::from(1)));
+            account.mark_touch();
+            if created {
+                account.mark_created();
+            }
+            if selfdestructed {
+                account.mark_selfdestruct();
+            }
+            evm_state.insert(address, account);
+        }
+        state.commit(evm_state);
+
+        let mut events = events.lock().unwrap().clone();
+        events.sort_by_key(|(address, _)| *address);
+        assert_eq!(
+            events,
+            [
+                (created, AccountLifecycle::Created),
+                (destroyed, AccountLifecycle::Destroyed),
+                (ephemeral, AccountLifecycle::CreatedAndDestroyed),
+            ]
+        );
+    }
 }
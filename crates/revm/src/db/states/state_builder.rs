@@ -1,4 +1,4 @@
-use super::{cache::CacheState, state::DBBox, BundleState, State, TransitionState};
+use super::{cache::CacheState, state::DBBox, BundleState, RevertsLimit, State, TransitionState};
 use crate::db::EmptyDB;
 use alloc::collections::BTreeMap;
 use revm_interpreter::primitives::{
@@ -28,6 +28,9 @@ pub struct StateBuilder<DB> {
     with_background_transition_merge: bool,
     /// If we want to set different block hashes
     with_block_hashes: BTreeMap<u64, B256>,
+    /// Limit of the reverts retained in the bundle state.
+    /// Default is unlimited.
+    with_reverts_limit: Option<RevertsLimit>,
 }
 
 impl StateBuilder<EmptyDB> {
@@ -57,6 +60,7 @@ impl<DB: Database> StateBuilder<DB> {
             with_bundle_update: false,
             with_background_transition_merge: false,
             with_block_hashes: BTreeMap::new(),
+            with_reverts_limit: None,
         }
     }
 
@@ -72,6 +76,7 @@ impl<DB: Database> StateBuilder<DB> {
             with_bundle_update: self.with_bundle_update,
             with_background_transition_merge: self.with_background_transition_merge,
             with_block_hashes: self.with_block_hashes,
+            with_reverts_limit: self.with_reverts_limit,
         }
     }
 
@@ -150,6 +155,15 @@ impl<DB: Database> StateBuilder<DB> {
         }
     }
 
+    /// Prunes the oldest reverts of the bundle state to the limit every time transitions are
+    /// merged, so long running executors don't accumulate unbounded revert history.
+    pub fn with_reverts_limit(self, limit: RevertsLimit) -> Self {
+        Self {
+            with_reverts_limit: Some(limit),
+            ..self
+        }
+    }
+
     pub fn build(mut self) -> State<DB> {
         let use_preloaded_bundle = if self.with_cache_prestate.is_some() {
             self.with_bundle_prestate = None;
@@ -166,6 +180,8 @@ impl<DB: Database> StateBuilder<DB> {
             bundle_state: self.with_bundle_prestate.unwrap_or_default(),
             use_preloaded_bundle,
             block_hashes: self.with_block_hashes,
+            reverts_limit: self.with_reverts_limit,
+            account_hook: None,
         }
     }
 }
@@ -3,6 +3,7 @@ use alloc::vec::Vec;
 use revm_interpreter::primitives::{hash_map::Entry, Address, HashMap};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TransitionState {
     /// Block state account with account state
     pub transitions: HashMap<Address, TransitionAccount>,
@@ -0,0 +1,215 @@
+//! [DatabaseRef] cache that can be shared between threads.
+//!
+//! [SyncDatabase] caches everything loaded from the underlying database behind locks, so one
+//! warmed cache can serve many [crate::EVM] instances executing in parallel. Each thread
+//! executes on its own [SharedCacheDB], created with [SyncDatabase::fork], which keeps changes
+//! of its transactions local while reads of untouched state hit the shared cache:
+//!
+//! ```
+//! use revm::db::{EmptyDB, SyncDatabase};
+//! use revm::primitives::Address;
+//! use revm::Database;
+//! use std::{sync::Arc, thread};
+//!
+//! let shared = Arc::new(SyncDatabase::new(EmptyDB::default()));
+//! thread::scope(|s| {
+//!     for _ in 0..2 {
+//!         let mut db = SyncDatabase::fork(&shared);
+//!         s.spawn(move || db.basic(Address::ZERO));
+//!     }
+//! });
+//! ```
+
+use super::{CacheDB, DatabaseRef};
+use crate::primitives::{AccountInfo, Address, Bytecode, HashMap, B256, KECCAK_EMPTY, U256};
+use std::sync::{Arc, RwLock};
+
+/// Per-thread view of a shared [SyncDatabase].
+///
+/// Changes committed to it are only visible to this view.
+pub type SharedCacheDB<ExtDB> = CacheDB<Arc<SyncDatabase<ExtDB>>>;
+
+/// Read-only [DatabaseRef] that caches data loaded from the underlying database.
+///
+/// It is `Send + Sync` if the underlying database is, the cache is never committed to so it
+/// always contains the state of the underlying database.
+#[derive(Debug, Default)]
+pub struct SyncDatabase<ExtDB> {
+    db: ExtDB,
+    accounts: RwLock<HashMap<Address, Option<AccountInfo>>>,
+    storage: RwLock<HashMap<(Address, U256), U256>>,
+    contracts: RwLock<HashMap<B256, Bytecode>>,
+    block_hashes: RwLock<HashMap<U256, B256>>,
+}
+
+impl<ExtDB: DatabaseRef> SyncDatabase<ExtDB> {
+    /// Creates new empty cache over the database.
+    pub fn new(db: ExtDB) -> Self {
+        Self {
+            db,
+            accounts: Default::default(),
+            storage: Default::default(),
+            contracts: Default::default(),
+            block_hashes: Default::default(),
+        }
+    }
+
+    /// Returns the underlying database.
+    pub fn db(&self) -> &ExtDB {
+        &self.db
+    }
+
+    /// Returns number of cached accounts and storage slots.
+    pub fn cached(&self) -> (usize, usize) {
+        (
+            self.accounts.read().unwrap().len(),
+            self.storage.read().unwrap().len(),
+        )
+    }
+
+    /// Loads accounts and their storage slots into the cache, e.g. the access lists of the
+    /// transactions about to be executed.
+    pub fn warm<'a>(
+        &self,
+        accounts: impl IntoIterator<Item = (Address, &'a [U256])>,
+    ) -> Result<(), ExtDB::Error> {
+        for (address, slots) in accounts {
+            self.basic_ref(address)?;
+            for &index in slots {
+                self.storage_ref(address, index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates new view for executing transactions on a single thread.
+    pub fn fork(this: &Arc<Self>) -> SharedCacheDB<ExtDB> {
+        CacheDB::new(Arc::clone(this))
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for SyncDatabase<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(info) = self.accounts.read().unwrap().get(&address) {
+            return Ok(info.clone());
+        }
+        let mut info = self.db.basic_ref(address)?;
+        if let Some(info) = info.as_mut() {
+            // Code is stored once by hash, like in CacheDB.
+            if let Some(code) = info.code.take() {
+                if info.code_hash != KECCAK_EMPTY {
+                    self.contracts
+                        .write()
+                        .unwrap()
+                        .entry(info.code_hash)
+                        .or_insert(code);
+                }
+            }
+        }
+        // Another thread may have loaded it meanwhile, keep the first value.
+        Ok(self
+            .accounts
+            .write()
+            .unwrap()
+            .entry(address)
+            .or_insert(info)
+            .clone())
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if let Some(code) = self.contracts.read().unwrap().get(&code_hash) {
+            return Ok(code.clone());
+        }
+        let code = self.db.code_by_hash_ref(code_hash)?;
+        Ok(self
+            .contracts
+            .write()
+            .unwrap()
+            .entry(code_hash)
+            .or_insert(code)
+            .clone())
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self.storage.read().unwrap().get(&(address, index)) {
+            return Ok(*value);
+        }
+        let value = self.db.storage_ref(address, index)?;
+        Ok(*self
+            .storage
+            .write()
+            .unwrap()
+            .entry((address, index))
+            .or_insert(value))
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        if let Some(hash) = self.block_hashes.read().unwrap().get(&number) {
+            return Ok(*hash);
+        }
+        let hash = self.db.block_hash_ref(number)?;
+        Ok(*self
+            .block_hashes
+            .write()
+            .unwrap()
+            .entry(number)
+            .or_insert(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{DatabaseCommit, EmptyDB};
+    use crate::primitives::Bytes;
+    use crate::Database;
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn shared_between_threads() {
+        assert_send_sync::<SyncDatabase<CacheDB<EmptyDB>>>();
+
+        let address = Address::with_last_byte(0x10);
+        let mut base = CacheDB::new(EmptyDB::default());
+        base.insert_account_info(
+            address,
+            AccountInfo {
+                balance: U256::from(100),
+                code: Some(Bytecode::new_raw(Bytes::from_static(&[0x00]))),
+                ..Default::default()
+            },
+        );
+        base.insert_account_storage(address, U256::from(1), U256::from(2))
+            .unwrap();
+
+        let shared = Arc::new(SyncDatabase::new(base));
+        shared.warm([(address, &[U256::from(1)][..])]).unwrap();
+        assert_eq!(shared.cached(), (1, 1));
+
+        thread::scope(|s| {
+            for i in 0..4u64 {
+                let mut db = SyncDatabase::fork(&shared);
+                s.spawn(move || {
+                    let info = db.basic(address).unwrap().unwrap();
+                    assert_eq!(db.storage(address, U256::from(1)), Ok(U256::from(2)));
+                    assert!(!db.code_by_hash(info.code_hash).unwrap().is_empty());
+
+                    // Local changes stay in the view.
+                    let mut account = crate::primitives::Account::from(info);
+                    account.info.balance = U256::from(i);
+                    account.mark_touch();
+                    db.commit([(address, account)].into_iter().collect());
+                    assert_eq!(db.basic(address).unwrap().unwrap().balance, U256::from(i));
+                });
+            }
+        });
+        assert_eq!(
+            shared.basic_ref(address).unwrap().unwrap().balance,
+            U256::from(100)
+        );
+    }
+}
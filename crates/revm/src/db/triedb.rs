@@ -0,0 +1,399 @@
+use super::{Database, DatabaseCommit, DatabaseRef};
+use crate::primitives::{
+    b256, keccak256, Account, AccountInfo, Address, Bytecode, HashMap, B256, KECCAK_EMPTY, U256,
+};
+use alloc::{vec, vec::Vec};
+use core::convert::Infallible;
+
+/// Root of the trie without entries.
+pub const EMPTY_ROOT: B256 =
+    b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// In-memory database that keeps the whole state and computes its Merkle-Patricia trie root.
+///
+/// It is meant for standalone block validation tests: load the pre-state, commit executed
+/// transactions and compare [TrieDB::state_root] with the expected post-state root.
+///
+/// Empty accounts are not part of the trie, as they can't exist after EIP-161.
+#[derive(Clone, Debug, Default)]
+pub struct TrieDB {
+    accounts: HashMap<Address, TrieAccount>,
+    contracts: HashMap<B256, Bytecode>,
+    block_hashes: HashMap<U256, B256>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct TrieAccount {
+    /// `code` is always `None`, bytecode is in [TrieDB::contracts].
+    info: AccountInfo,
+    /// Non-zero storage slots.
+    storage: HashMap<U256, U256>,
+    /// Cached storage root, cleared when storage changes.
+    storage_root: Option<B256>,
+}
+
+impl TrieAccount {
+    fn set_storage(&mut self, index: U256, value: U256) {
+        if value == U256::ZERO {
+            self.storage.remove(&index);
+        } else {
+            self.storage.insert(index, value);
+        }
+        self.storage_root = None;
+    }
+
+    fn storage_root(&mut self) -> B256 {
+        *self.storage_root.get_or_insert_with(|| {
+            trie_root(self.storage.iter().map(|(index, value)| {
+                let key = keccak256(index.to_be_bytes::<{ U256::BYTES }>());
+                (key.to_vec(), encode_u256(*value))
+            }))
+        })
+    }
+}
+
+impl TrieDB {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts account info, storage of the account is kept.
+    pub fn insert_account_info(&mut self, address: Address, mut info: AccountInfo) {
+        self.insert_contract(&mut info);
+        self.accounts.entry(address).or_default().info = info;
+    }
+
+    /// Inserts account storage slot, zero value removes the slot.
+    pub fn insert_account_storage(&mut self, address: Address, index: U256, value: U256) {
+        self.accounts
+            .entry(address)
+            .or_default()
+            .set_storage(index, value);
+    }
+
+    /// Inserts hash of the block, hashes of other blocks are `keccak256(number)`.
+    pub fn insert_block_hash(&mut self, number: U256, hash: B256) {
+        self.block_hashes.insert(number, hash);
+    }
+
+    /// Returns storage root of the account, [EMPTY_ROOT] if account doesn't exist.
+    pub fn storage_root(&mut self, address: Address) -> B256 {
+        self.accounts
+            .get_mut(&address)
+            .map_or(EMPTY_ROOT, TrieAccount::storage_root)
+    }
+
+    /// Returns root of the state trie.
+    ///
+    /// Storage roots are cached and recomputed only for accounts with changed storage.
+    pub fn state_root(&mut self) -> B256 {
+        let leaves = self
+            .accounts
+            .iter_mut()
+            .filter(|(_, account)| !account.info.is_empty())
+            .map(|(address, account)| {
+                let storage_root = account.storage_root();
+                let info = &account.info;
+                let value = encode_list(&[
+                    encode_u256(U256::from(info.nonce)),
+                    encode_u256(info.balance),
+                    encode_bytes(storage_root.as_slice()),
+                    encode_bytes(info.code_hash.as_slice()),
+                ]);
+                (keccak256(address).to_vec(), value)
+            })
+            .collect::<Vec<_>>();
+        trie_root(leaves)
+    }
+
+    fn insert_contract(&mut self, info: &mut AccountInfo) {
+        if let Some(code) = info.code.take() {
+            if !code.is_empty() {
+                if info.code_hash == KECCAK_EMPTY {
+                    info.code_hash = code.hash_slow();
+                }
+                self.contracts.entry(info.code_hash).or_insert(code);
+            }
+        }
+        if info.code_hash == B256::ZERO {
+            info.code_hash = KECCAK_EMPTY;
+        }
+    }
+}
+
+impl DatabaseCommit for TrieDB {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        for (address, mut account) in changes {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                self.accounts.remove(&address);
+                continue;
+            }
+            let is_newly_created = account.is_created();
+            self.insert_contract(&mut account.info);
+
+            let trie_account = self.accounts.entry(address).or_default();
+            trie_account.info = account.info;
+            if is_newly_created {
+                trie_account.storage.clear();
+                trie_account.storage_root = None;
+            }
+            for (index, slot) in account.storage {
+                trie_account.set_storage(index, slot.present_value());
+            }
+        }
+    }
+}
+
+impl DatabaseRef for TrieDB {
+    type Error = Infallible;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        Ok(self
+            .accounts
+            .get(&address)
+            .map(|account| account.info.clone()))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        Ok(self.contracts.get(&code_hash).cloned().unwrap_or_default())
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        Ok(self
+            .accounts
+            .get(&address)
+            .and_then(|account| account.storage.get(&index))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        Ok(match self.block_hashes.get(&number) {
+            Some(hash) => *hash,
+            None => keccak256(number.to_be_bytes::<{ U256::BYTES }>()),
+        })
+    }
+}
+
+impl Database for TrieDB {
+    type Error = Infallible;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+/// Computes root of the trie with the given keys and values.
+fn trie_root(entries: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>) -> B256 {
+    let mut entries = entries
+        .into_iter()
+        .map(|(key, value)| (to_nibbles(&key), value))
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        return EMPTY_ROOT;
+    }
+    entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    keccak256(encode_node(&entries, 0))
+}
+
+/// Returns RLP of the node containing sorted `entries` that share first `depth` nibbles.
+fn encode_node(entries: &[(Vec<u8>, Vec<u8>)], depth: usize) -> Vec<u8> {
+    if let [(key, value)] = entries {
+        let path = hex_prefix(&key[depth..], true);
+        return encode_list(&[encode_bytes(&path), encode_bytes(value)]);
+    }
+
+    // Entries are sorted so the common prefix of all is the common prefix of first and last.
+    let (first, last) = (&entries[0].0, &entries[entries.len() - 1].0);
+    let shared = first[depth..]
+        .iter()
+        .zip(&last[depth..])
+        .take_while(|(a, b)| a == b)
+        .count();
+    if shared > 0 {
+        let path = hex_prefix(&first[depth..depth + shared], false);
+        let child = encode_node(entries, depth + shared);
+        return encode_list(&[encode_bytes(&path), node_reference(child)]);
+    }
+
+    // Branch node, key that ends here is the value of the branch.
+    let mut items = Vec::with_capacity(17);
+    let (value, mut rest) = match entries[0].0.len() == depth {
+        true => (encode_bytes(&entries[0].1), &entries[1..]),
+        false => (encode_bytes(&[]), entries),
+    };
+    for nibble in 0..16 {
+        let len = rest
+            .iter()
+            .take_while(|(key, _)| key[depth] == nibble)
+            .count();
+        let (children, next) = rest.split_at(len);
+        items.push(match children.is_empty() {
+            true => encode_bytes(&[]),
+            false => node_reference(encode_node(children, depth + 1)),
+        });
+        rest = next;
+    }
+    items.push(value);
+    encode_list(&items)
+}
+
+/// Nodes shorter than hash are embedded into their parent.
+fn node_reference(node: Vec<u8>) -> Vec<u8> {
+    if node.len() < 32 {
+        node
+    } else {
+        encode_bytes(keccak256(&node).as_slice())
+    }
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Hex-prefix encoding of the node path.
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        out.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        out.push(flag << 4);
+        nibbles
+    };
+    out.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    out
+}
+
+fn encode_u256(value: U256) -> Vec<u8> {
+    let bytes = value.to_be_bytes::<{ U256::BYTES }>();
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len());
+    encode_bytes(&bytes[start..])
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    if let [byte] = bytes {
+        if *byte < 0x80 {
+            return vec![*byte];
+        }
+    }
+    let mut out = encode_length(bytes.len(), 0x80);
+    out.extend_from_slice(bytes);
+    out
+}
+
+fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = encode_length(items.iter().map(Vec::len).sum(), 0xc0);
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+fn encode_length(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        return vec![offset + len as u8];
+    }
+    let bytes = len.to_be_bytes();
+    let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(0);
+    let mut out = vec![offset + 55 + (bytes.len() - start) as u8];
+    out.extend_from_slice(&bytes[start..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{address, Bytes, TransactTo},
+        EVM,
+    };
+
+    fn root_of(entries: &[(&str, &str)]) -> B256 {
+        trie_root(
+            entries
+                .iter()
+                .map(|(key, value)| (key.as_bytes().to_vec(), value.as_bytes().to_vec())),
+        )
+    }
+
+    #[test]
+    fn trie_root_vectors() {
+        assert_eq!(trie_root(Vec::new()), EMPTY_ROOT);
+        assert_eq!(keccak256([0x80]), EMPTY_ROOT);
+        assert_eq!(
+            root_of(&[
+                ("doe", "reindeer"),
+                ("dog", "puppy"),
+                ("dogglesworth", "cat"),
+            ]),
+            b256!("8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3")
+        );
+        assert_eq!(
+            root_of(&[
+                ("do", "verb"),
+                ("dog", "puppy"),
+                ("doge", "coin"),
+                ("horse", "stallion"),
+            ]),
+            b256!("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+        );
+    }
+
+    #[test]
+    fn state_root_follows_commits() {
+        let caller = address!("a94f5374fce5edbf8e2a8697c15331677e6ebf0b");
+        let contract = Address::with_last_byte(0x20);
+        let mut db = TrieDB::new();
+        assert_eq!(db.state_root(), EMPTY_ROOT);
+
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        // PUSH1 1 PUSH1 0 SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55]));
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+        let pre_root = db.state_root();
+        assert_eq!(db.storage_root(contract), EMPTY_ROOT);
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = caller;
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+        evm.transact_commit().unwrap();
+
+        let db = evm.db.as_mut().unwrap();
+        assert_ne!(db.state_root(), pre_root);
+        assert_ne!(db.storage_root(contract), EMPTY_ROOT);
+
+        // Clearing the slot restores the empty storage root.
+        db.insert_account_storage(contract, U256::ZERO, U256::ZERO);
+        assert_eq!(db.storage_root(contract), EMPTY_ROOT);
+    }
+}
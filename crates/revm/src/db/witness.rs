@@ -0,0 +1,174 @@
+//! Capture of the state read during execution.
+
+use super::{Database, DatabaseCommit};
+use crate::primitives::{
+    Account, AccountInfo, Address, Bytecode, HashMap, B256, KECCAK_EMPTY, U256,
+};
+
+/// State read from a database, enough to re-execute the same transactions without it.
+///
+/// Every item is recorded with the value of its first read, contracts are stored once by their
+/// code hash and are not repeated in the account infos.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionWitness {
+    /// Accounts where `None` means the account does not exist.
+    pub accounts: HashMap<Address, Option<AccountInfo>>,
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+    pub contracts: HashMap<B256, Bytecode>,
+    pub block_hashes: HashMap<U256, B256>,
+}
+
+impl ExecutionWitness {
+    /// Returns true if nothing was read.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+            && self.storage.is_empty()
+            && self.contracts.is_empty()
+            && self.block_hashes.is_empty()
+    }
+
+    /// Returns number of recorded storage slots.
+    pub fn storage_len(&self) -> usize {
+        self.storage.values().map(HashMap::len).sum()
+    }
+}
+
+/// Database that records the state read through it.
+pub trait DatabaseWitness {
+    /// Returns state read so far.
+    fn witness(&self) -> &ExecutionWitness;
+
+    /// Returns state read so far and starts recording a new witness.
+    fn take_witness(&mut self) -> ExecutionWitness;
+}
+
+/// [Database] that records every query of the underlying database into an [ExecutionWitness].
+///
+/// Only the first read of every item is recorded. Commits are forwarded to the database, so
+/// items first read after a commit are recorded with their committed value.
+#[derive(Clone, Debug, Default)]
+pub struct WitnessRecordingDB<DB> {
+    pub db: DB,
+    witness: ExecutionWitness,
+}
+
+impl<DB> WitnessRecordingDB<DB> {
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            witness: ExecutionWitness::default(),
+        }
+    }
+
+    /// Returns the database and the recorded witness.
+    pub fn into_parts(self) -> (DB, ExecutionWitness) {
+        (self.db, self.witness)
+    }
+}
+
+impl<DB> DatabaseWitness for WitnessRecordingDB<DB> {
+    fn witness(&self) -> &ExecutionWitness {
+        &self.witness
+    }
+
+    fn take_witness(&mut self) -> ExecutionWitness {
+        core::mem::take(&mut self.witness)
+    }
+}
+
+impl<DB: Database> Database for WitnessRecordingDB<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        if !self.witness.accounts.contains_key(&address) {
+            let recorded = info.clone().map(|mut info| {
+                if let Some(code) = info.code.take() {
+                    if info.code_hash != KECCAK_EMPTY {
+                        self.witness.contracts.entry(info.code_hash).or_insert(code);
+                    }
+                }
+                info
+            });
+            self.witness.accounts.insert(address, recorded);
+        }
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        self.witness
+            .contracts
+            .entry(code_hash)
+            .or_insert_with(|| code.clone());
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.witness
+            .storage
+            .entry(address)
+            .or_default()
+            .entry(index)
+            .or_insert(value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.witness.block_hashes.entry(number).or_insert(hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for WitnessRecordingDB<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{CacheDB, EmptyDB};
+    use crate::primitives::Bytes;
+
+    #[test]
+    fn records_first_reads() {
+        let (a, b) = (Address::with_last_byte(0x10), Address::with_last_byte(0x20));
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+        let mut cache = CacheDB::new(EmptyDB::default());
+        cache.insert_account_info(
+            a,
+            AccountInfo {
+                code: Some(code.clone()),
+                ..Default::default()
+            },
+        );
+        cache
+            .insert_account_storage(a, U256::from(1), U256::from(2))
+            .unwrap();
+
+        let mut db = WitnessRecordingDB::new(cache);
+        let info = db.basic(a).unwrap().unwrap();
+        assert!(db.basic(b).unwrap().is_none());
+        db.storage(a, U256::from(1)).unwrap();
+        db.db
+            .insert_account_storage(a, U256::from(1), U256::from(3))
+            .unwrap();
+        assert_eq!(db.storage(a, U256::from(1)), Ok(U256::from(3)));
+        db.block_hash(U256::from(1)).unwrap();
+
+        let witness = db.take_witness();
+        assert_eq!(witness.accounts.len(), 2);
+        assert_eq!(witness.accounts[&a].as_ref().unwrap().code, None);
+        assert_eq!(witness.accounts[&b], None);
+        assert_eq!(witness.contracts[&info.code_hash], code);
+        assert_eq!(witness.storage[&a][&U256::from(1)], U256::from(2));
+        assert_eq!(witness.storage_len(), 1);
+        assert_eq!(witness.block_hashes.len(), 1);
+        assert!(db.witness().is_empty());
+    }
+}
@@ -1,9 +1,42 @@
 use crate::{
-    db::{Database, DatabaseCommit, DatabaseRef},
-    evm_impl::{new_evm, Transact},
-    primitives::{db::WrapDatabaseRef, EVMError, EVMResult, Env, ExecutionResult, ResultAndState},
-    Inspector,
+    db::{CacheDB, Database, DatabaseCommit, DatabaseRef, OverlayDB, StateOverlay},
+    evm_impl::{new_evm, new_evm_with_external, Transact, TransactExt},
+    inspectors::NoOpInspector,
+    interpreter::{gas::CALL_STIPEND, AnalysisCache, SharedMemoryPool},
+    orderings::{InvalidOrder, OrderingOutcome, SharedPreState},
+    primitives::{
+        db::WrapDatabaseRef, hash_map::Entry, Account, Address, BlockEnv, BlockSummary, Bytes,
+        EVMError, EVMResult, Env, ExecutionResult, HardforkSchedule, HashMap, InvalidHeader,
+        InvalidTransaction, ResultAndState, SpecId, TransactTo, TxEnv, Withdrawal,
+        BEACON_ROOTS_ADDRESS, HISTORY_STORAGE_ADDRESS, SYSTEM_ADDRESS, SYSTEM_CALL_GAS_LIMIT, U256,
+    },
+    ExecutionHandle, ExecutionSnapshot, Inspector, JournaledState, RpcError,
 };
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+
+#[cfg(feature = "asyncdb")]
+use crate::db::asyncdb::{map_cache_error, AsyncDatabase, AsyncDatabaseCache};
+
+/// Result of executing multiple transactions, see [EVM::transact_multiple].
+///
+/// On error the results of the transactions committed before the failing one are returned
+/// together with the error. State of every transaction is moved into the database when it is
+/// committed, so [ResultAndState::state] of the results is empty.
+pub type BatchResult<DBError> =
+    Result<(Vec<ResultAndState>, BlockSummary), (Vec<ResultAndState>, EVMError<DBError>)>;
+
+/// Outputs of the plain and inspected executions that differ, see [EVM::transact_shadowed].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowMismatch<DBError> {
+    /// Output of the execution with the plain instruction table.
+    pub plain: EVMResult<DBError>,
+    /// Output of the execution with the instruction table wrapped by the inspector.
+    pub inspected: EVMResult<DBError>,
+}
+
+/// Results of the executed transactions, with the results executed before the error if any.
+type TxsResult<DBError> = Result<Vec<ResultAndState>, (Vec<ResultAndState>, EVMError<DBError>)>;
 
 /// Struct that takes Database and enabled transact to update state directly to database.
 /// additionally it allows user to set all environment parameters.
@@ -37,6 +70,12 @@ use crate::{
 pub struct EVM<DB> {
     pub env: Env,
     pub db: Option<DB>,
+    /// Shared memories reused between transactions instead of reallocated. Its size can be
+    /// capped with [SharedMemoryPool::set_max_size].
+    pub memory_pool: SharedMemoryPool,
+    /// Analysed bytecode of the called contracts reused between transactions. Its capacity can
+    /// be set with [AnalysisCache::set_capacity].
+    pub analysis_cache: AnalysisCache,
 }
 
 pub fn new<DB>() -> EVM<DB> {
@@ -52,7 +91,7 @@ impl<DB> Default for EVM<DB> {
 impl<DB: Database + DatabaseCommit> EVM<DB> {
     /// Execute transaction and apply result to database
     pub fn transact_commit(&mut self) -> Result<ExecutionResult, EVMError<DB::Error>> {
-        let ResultAndState { result, state } = self.transact()?;
+        let ResultAndState { result, state, .. } = self.transact()?;
         self.db.as_mut().unwrap().commit(state);
         Ok(result)
     }
@@ -62,10 +101,268 @@ impl<DB: Database + DatabaseCommit> EVM<DB> {
         &mut self,
         inspector: INSP,
     ) -> Result<ExecutionResult, EVMError<DB::Error>> {
-        let ResultAndState { result, state } = self.inspect(inspector)?;
+        let ResultAndState { result, state, .. } = self.inspect(inspector)?;
         self.db.as_mut().unwrap().commit(state);
         Ok(result)
     }
+
+    /// Execute multiple transactions of the same block and commit each of them to database.
+    ///
+    /// Block and config are taken from `env`, while `env.tx` is set to each transaction in turn.
+    /// Returns result of every transaction together with the aggregated [BlockSummary].
+    ///
+    /// Execution stops at the first transaction that errors, as such transaction can't be
+    /// included in the block. Transactions executed before it stay committed and their results
+    /// are returned together with the error. Transaction with gas limit above the remaining
+    /// block gas fails with [InvalidTransaction::CallerGasLimitMoreThanBlock].
+    pub fn transact_multiple(&mut self, txs: &[TxEnv]) -> BatchResult<DB::Error> {
+        let mut summary = BlockSummary::default();
+        self.execute_multiple(txs, &mut summary, None)
+            .map(|results| (results, summary))
+    }
+
+    /// Inspect multiple transactions of the same block and commit each of them to database.
+    ///
+    /// Same as [EVM::transact_multiple] with block and transaction hooks of the inspector
+    /// called around the execution.
+    pub fn inspect_multiple<INSP: Inspector<DB>>(
+        &mut self,
+        txs: &[TxEnv],
+        mut inspector: INSP,
+    ) -> BatchResult<DB::Error> {
+        let mut summary = BlockSummary::default();
+        inspector.begin_block(&self.env);
+        let results = self.execute_multiple(txs, &mut summary, Some(&mut inspector));
+        inspector.end_block(&self.env, &summary);
+        results.map(|results| (results, summary))
+    }
+
+    /// Execute whole block and commit it to database.
+    ///
+    /// `block` replaces the block environment of `env`, after which operations are applied
+    /// in the order defined by the specification:
+    /// 1. System calls, as applied by [EVM::apply_pre_block_system_calls].
+    /// 2. Transactions, as executed by [EVM::transact_multiple].
+    /// 3. [EIP-4895] (Shanghai): withdrawals are credited to their recipients.
+    ///
+    /// System calls are not part of the returned results and don't count toward [BlockSummary].
+    ///
+    /// [EIP-4895]: https://eips.ethereum.org/EIPS/eip-4895
+    pub fn transact_block(
+        &mut self,
+        block: BlockEnv,
+        txs: &[TxEnv],
+        withdrawals: &[Withdrawal],
+    ) -> BatchResult<DB::Error> {
+        self.execute_block(block, txs, withdrawals, None)
+    }
+
+    /// Inspect whole block and commit it to database.
+    ///
+    /// Same as [EVM::transact_block] with block and transaction hooks of the inspector called
+    /// around the execution. System calls are not inspected.
+    pub fn inspect_block<INSP: Inspector<DB>>(
+        &mut self,
+        block: BlockEnv,
+        txs: &[TxEnv],
+        withdrawals: &[Withdrawal],
+        mut inspector: INSP,
+    ) -> BatchResult<DB::Error> {
+        self.execute_block(block, txs, withdrawals, Some(&mut inspector))
+    }
+
+    fn execute_block(
+        &mut self,
+        block: BlockEnv,
+        txs: &[TxEnv],
+        withdrawals: &[Withdrawal],
+        mut inspector: Option<&mut dyn Inspector<DB>>,
+    ) -> BatchResult<DB::Error> {
+        self.env.block = block;
+        let mut summary = BlockSummary::default();
+        if let Some(inspector) = inspector.as_mut() {
+            inspector.begin_block(&self.env);
+        }
+        let results = self.apply_block(
+            txs,
+            withdrawals,
+            &mut summary,
+            inspector
+                .as_mut()
+                .map(|i| &mut **i as &mut dyn Inspector<DB>),
+        );
+        // Block end is reported even if the block failed, with the transactions executed so far.
+        if let Some(inspector) = inspector {
+            inspector.end_block(&self.env, &summary);
+        }
+        results.map(|results| (results, summary))
+    }
+
+    fn apply_block(
+        &mut self,
+        txs: &[TxEnv],
+        withdrawals: &[Withdrawal],
+        summary: &mut BlockSummary,
+        inspector: Option<&mut dyn Inspector<DB>>,
+    ) -> TxsResult<DB::Error> {
+        self.apply_pre_block_system_calls()
+            .map_err(|error| (Vec::new(), error))?;
+
+        let results = self.execute_multiple(txs, summary, inspector)?;
+
+        if SpecId::enabled(self.env.cfg.spec_id, SpecId::SHANGHAI) {
+            if let Err(error) = self.apply_withdrawals(withdrawals) {
+                return Err((results, error));
+            }
+        }
+        Ok(results)
+    }
+
+    fn execute_multiple(
+        &mut self,
+        txs: &[TxEnv],
+        summary: &mut BlockSummary,
+        mut inspector: Option<&mut dyn Inspector<DB>>,
+    ) -> TxsResult<DB::Error> {
+        let mut results = Vec::with_capacity(txs.len());
+        for tx in txs {
+            match self.execute_in_block(
+                tx,
+                summary,
+                inspector
+                    .as_mut()
+                    .map(|i| &mut **i as &mut dyn Inspector<DB>),
+            ) {
+                Ok(result_and_state) => results.push(result_and_state),
+                Err(error) => return Err((results, error)),
+            }
+        }
+        Ok(results)
+    }
+
+    /// Execute transaction as part of the block summarized by `summary` and commit it.
+    fn execute_in_block(
+        &mut self,
+        tx: &TxEnv,
+        summary: &mut BlockSummary,
+        mut inspector: Option<&mut dyn Inspector<DB>>,
+    ) -> EVMResult<DB::Error> {
+        if let Some(block_gas_limit) = self.env.block_gas_limit()? {
+            if summary.gas_used.saturating_add(tx.gas_limit) > block_gas_limit {
+                return Err(InvalidTransaction::CallerGasLimitMoreThanBlock.into());
+            }
+        }
+
+        let db = self.db.as_mut().expect("Database needs to be set");
+        self.env.tx = tx.clone();
+        if let Some(inspector) = inspector.as_mut() {
+            inspector.begin_tx(&self.env);
+        }
+        let evm = new_evm::<DB>(
+            &mut self.env,
+            db,
+            inspector
+                .as_mut()
+                .map(|i| &mut **i as &mut dyn Inspector<DB>),
+        );
+        let mut result_and_state = with_caches(
+            evm,
+            &mut self.memory_pool,
+            &mut self.analysis_cache,
+            |evm| evm.transact(),
+        )?;
+        summary.push(&result_and_state.result);
+        if let Some(inspector) = inspector {
+            inspector.end_tx(&self.env, &result_and_state, summary);
+        }
+        db.commit(core::mem::take(&mut result_and_state.state));
+        Ok(result_and_state)
+    }
+
+    /// Apply system calls that precede transactions of the block set in the environment and
+    /// commit them to database:
+    /// 1. [EIP-4788] (Cancun): parent beacon block root is stored inside beacon roots contract.
+    /// 2. [EIP-2935] (Prague): parent block hash is stored inside history storage contract.
+    ///    It is opt-in, the call is only made if [BlockEnv::parent_hash] is set.
+    ///
+    /// Calls are skipped for the genesis block. Useful when transactions of the block are
+    /// executed one by one instead of with [EVM::transact_block].
+    ///
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    /// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+    pub fn apply_pre_block_system_calls(&mut self) -> Result<(), EVMError<DB::Error>> {
+        let spec_id = self.env.cfg.spec_id;
+        if SpecId::enabled(spec_id, SpecId::CANCUN) {
+            let root = self
+                .env
+                .block
+                .parent_beacon_block_root
+                .ok_or(InvalidHeader::ParentBeaconBlockRootNotSet)?;
+            // Genesis block has no parent, system call is skipped.
+            if self.env.block.number != U256::ZERO {
+                self.system_call(BEACON_ROOTS_ADDRESS, root.0.into())?;
+            }
+        }
+        if let Some(parent_hash) = self.env.block.parent_hash {
+            if SpecId::enabled(spec_id, SpecId::PRAGUE) && self.env.block.number != U256::ZERO {
+                self.system_call(HISTORY_STORAGE_ADDRESS, parent_hash.0.into())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Call system contract at `address` with `data` and commit changes to database.
+    ///
+    /// Call is done from [SYSTEM_ADDRESS] without fees and without block gas accounting.
+    /// System address and coinbase are not touched by it. If there is no code on `address`
+    /// call is skipped.
+    fn system_call(&mut self, address: Address, data: Bytes) -> Result<(), EVMError<DB::Error>> {
+        let db = self.db.as_mut().expect("Database needs to be set");
+        match db.basic(address).map_err(EVMError::Database)? {
+            Some(info) if !info.is_empty_code_hash() => (),
+            _ => return Ok(()),
+        }
+
+        let mut env = self.env.clone();
+        env.tx = TxEnv {
+            caller: SYSTEM_ADDRESS,
+            transact_to: TransactTo::Call(address),
+            data,
+            gas_limit: SYSTEM_CALL_GAS_LIMIT,
+            ..Default::default()
+        };
+        // System call is not bound by block gas limit and does not pay for gas.
+        env.block.gas_limit = U256::from(SYSTEM_CALL_GAS_LIMIT);
+        env.block.basefee = U256::ZERO;
+
+        let ResultAndState { mut state, .. } = new_evm::<DB>(&mut env, db, None).transact()?;
+        state.remove(&SYSTEM_ADDRESS);
+        state.remove(&env.block.coinbase);
+        db.commit(state);
+        Ok(())
+    }
+
+    /// Credit withdrawals to their recipients and commit changes to database.
+    fn apply_withdrawals(&mut self, withdrawals: &[Withdrawal]) -> Result<(), EVMError<DB::Error>> {
+        let db = self.db.as_mut().expect("Database needs to be set");
+        let mut state: HashMap<Address, Account> = HashMap::new();
+        for withdrawal in withdrawals.iter().filter(|w| w.amount != 0) {
+            let account = match state.entry(withdrawal.address) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let info = db
+                        .basic(withdrawal.address)
+                        .map_err(EVMError::Database)?
+                        .unwrap_or_default();
+                    entry.insert(Account::from(info))
+                }
+            };
+            account.info.balance = account.info.balance.saturating_add(withdrawal.amount_wei());
+            account.mark_touch();
+        }
+        db.commit(state);
+        Ok(())
+    }
 }
 
 impl<DB: Database> EVM<DB> {
@@ -82,7 +379,13 @@ impl<DB: Database> EVM<DB> {
     /// state.
     pub fn transact_preverified(&mut self) -> EVMResult<DB::Error> {
         if let Some(db) = self.db.as_mut() {
-            new_evm::<DB>(&mut self.env, db, None).transact_preverified()
+            let evm = new_evm::<DB>(&mut self.env, db, None);
+            with_caches(
+                evm,
+                &mut self.memory_pool,
+                &mut self.analysis_cache,
+                |evm| evm.transact_preverified(),
+            )
         } else {
             panic!("Database needs to be set");
         }
@@ -91,7 +394,122 @@ impl<DB: Database> EVM<DB> {
     /// Execute transaction without writing to DB, return change state.
     pub fn transact(&mut self) -> EVMResult<DB::Error> {
         if let Some(db) = self.db.as_mut() {
-            new_evm::<DB>(&mut self.env, db, None).transact()
+            let evm = new_evm::<DB>(&mut self.env, db, None);
+            with_caches(
+                evm,
+                &mut self.memory_pool,
+                &mut self.analysis_cache,
+                |evm| evm.transact(),
+            )
+        } else {
+            panic!("Database needs to be set");
+        }
+    }
+
+    /// Estimate the lowest gas limit the transaction succeeds with, like `eth_estimateGas`.
+    ///
+    /// Transaction gas limit is the upper bound of the search. The transaction is executed
+    /// without writing to DB with the upper bound, then with a limit derived from the gas it
+    /// spent, and a binary search finds the lowest limit in between. Needed limit is higher than
+    /// the used gas as refunds are given only at the end, and calls withhold 1/64 of the gas.
+    ///
+    /// Errors are the ones geth returns: revert error if the transaction reverts with the upper
+    /// bound, `gas required exceeds allowance` if it halts. Transaction gas limit is restored
+    /// afterwards.
+    pub fn estimate_gas(&mut self) -> Result<u64, RpcError>
+    where
+        DB::Error: fmt::Display,
+    {
+        let gas_limit = self.env.tx.gas_limit;
+        let estimate = self.estimate_gas_inner();
+        self.env.tx.gas_limit = gas_limit;
+        estimate
+    }
+
+    fn estimate_gas_inner(&mut self) -> Result<u64, RpcError>
+    where
+        DB::Error: fmt::Display,
+    {
+        self.env.clamp_tx_gas_limit();
+        let cap = self.env.tx.gas_limit;
+        let output = self.transact().map_err(|e| RpcError::from_evm_error(&e))?;
+        let spent = match output.result {
+            ExecutionResult::Success { gas, .. } => gas.spent(),
+            ExecutionResult::Revert { output, .. } => return Err(RpcError::reverted(output)),
+            ExecutionResult::Halt { .. } => {
+                return Err(RpcError::gas_required_exceeds_allowance(cap))
+            }
+        };
+
+        // Transaction fails with `lo` and succeeds with `hi`. It needs at least the gas it spent.
+        let (mut lo, mut hi) = (spent.saturating_sub(1), cap);
+        // Most transactions succeed with the spent gas and the gas withheld from calls.
+        let optimistic = spent.saturating_add(CALL_STIPEND).saturating_mul(64) / 63;
+        if lo < optimistic && optimistic < hi {
+            if self.succeeds_with_gas_limit(optimistic)? {
+                hi = optimistic;
+            } else {
+                lo = optimistic;
+            }
+        }
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if self.succeeds_with_gas_limit(mid)? {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Ok(hi)
+    }
+
+    fn succeeds_with_gas_limit(&mut self, gas_limit: u64) -> Result<bool, RpcError>
+    where
+        DB::Error: fmt::Display,
+    {
+        self.env.tx.gas_limit = gas_limit;
+        match self.transact() {
+            Ok(output) => Ok(output.result.is_success()),
+            // Gas limit is too low to be a valid transaction, e.g. it doesn't cover the
+            // intrinsic gas.
+            Err(EVMError::Transaction(_)) => Ok(false),
+            Err(e) => Err(RpcError::from_evm_error(&e)),
+        }
+    }
+
+    /// Start transaction that is executed step by step, without writing to DB.
+    ///
+    /// Returned [ExecutionHandle] drives the execution, [ExecutionHandle::resume] finishes it
+    /// and returns change state.
+    pub fn start_transact(
+        &mut self,
+    ) -> Result<ExecutionHandle<'_, DB::Error>, EVMError<DB::Error>> {
+        if let Some(db) = self.db.as_mut() {
+            let mut evm = new_evm::<DB>(&mut self.env, db, None);
+            let frames = evm.start_transact()?;
+            Ok(ExecutionHandle::new(evm, frames))
+        } else {
+            panic!("Database needs to be set");
+        }
+    }
+
+    /// Continue the transaction from the snapshot taken by [ExecutionHandle::snapshot], without
+    /// writing to DB.
+    ///
+    /// Environment of the EVM is replaced with the environment of the snapshot. Database is
+    /// expected to be in the same state as when the transaction was started.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot contains invalid bytecode or program counter.
+    pub fn restore_transact(
+        &mut self,
+        snapshot: ExecutionSnapshot,
+    ) -> ExecutionHandle<'_, DB::Error> {
+        if let Some(db) = self.db.as_mut() {
+            let mut evm = new_evm::<DB>(&mut self.env, db, None);
+            let frames = evm.restore_transact(snapshot);
+            ExecutionHandle::new(evm, frames)
         } else {
             panic!("Database needs to be set");
         }
@@ -100,11 +518,88 @@ impl<DB: Database> EVM<DB> {
     /// Execute transaction with given inspector, without wring to DB. Return change state.
     pub fn inspect<INSP: Inspector<DB>>(&mut self, mut inspector: INSP) -> EVMResult<DB::Error> {
         if let Some(db) = self.db.as_mut() {
-            new_evm::<DB>(&mut self.env, db, Some(&mut inspector)).transact()
+            let evm = new_evm::<DB>(&mut self.env, db, Some(&mut inspector));
+            with_caches(
+                evm,
+                &mut self.memory_pool,
+                &mut self.analysis_cache,
+                |evm| evm.transact(),
+            )
         } else {
             panic!("Database needs to be set");
         }
     }
+
+    /// Execute transaction twice without writing to DB, with the plain instruction table and
+    /// with the table wrapped by an inspector that does nothing. Return change state.
+    ///
+    /// Debug mode that catches differences introduced by the inspector wrapping, for example
+    /// after handlers change. Output of the plain execution is returned if results, gas and
+    /// state of the two executions are the same, otherwise both outputs are returned in
+    /// [ShadowMismatch].
+    pub fn transact_shadowed(
+        &mut self,
+    ) -> Result<EVMResult<DB::Error>, Box<ShadowMismatch<DB::Error>>>
+    where
+        DB::Error: PartialEq,
+    {
+        let db = self.db.as_mut().expect("Database needs to be set");
+        // Execution can modify the environment, e.g. clamp the gas limit.
+        let mut env = self.env.clone();
+        let mut inspector = NoOpInspector;
+        let inspected = new_evm::<DB>(&mut env, db, Some(&mut inspector)).transact();
+        let plain = new_evm::<DB>(&mut self.env, db, None).transact();
+        if plain != inspected {
+            return Err(Box::new(ShadowMismatch { plain, inspected }));
+        }
+        Ok(plain)
+    }
+
+    /// Execute transaction with given inspector and the context of the embedding, without
+    /// writing to DB. Return change state.
+    ///
+    /// The inspector can access `external` with [crate::EvmContext::external], `EXT` is usually
+    /// a mutable reference to a ledger kept next to the state.
+    pub fn inspect_with_external<EXT, INSP: Inspector<DB, JournaledState, EXT>>(
+        &mut self,
+        mut inspector: INSP,
+        external: EXT,
+    ) -> EVMResult<DB::Error> {
+        if let Some(db) = self.db.as_mut() {
+            let evm = new_evm_with_external(&mut self.env, db, Some(&mut inspector), external);
+            with_caches(
+                evm,
+                &mut self.memory_pool,
+                &mut self.analysis_cache,
+                |evm| evm.transact(),
+            )
+        } else {
+            panic!("Database needs to be set");
+        }
+    }
+}
+
+#[cfg(feature = "asyncdb")]
+impl<DB: AsyncDatabase> EVM<DB> {
+    /// Execute transaction without writing to DB, awaiting the data it needs from the
+    /// [AsyncDatabase]. Return change state.
+    ///
+    /// Execution is restarted after all data it needed and was not yet fetched is awaited, see
+    /// [AsyncDatabaseCache].
+    pub async fn transact_async(&mut self) -> EVMResult<DB::Error> {
+        let db = self.db.as_mut().expect("Database needs to be set");
+        let mut cache = AsyncDatabaseCache::default();
+        loop {
+            let mut env = self.env.clone();
+            let result = new_evm(&mut env, &mut cache, None).transact();
+            if cache.missing().is_empty() {
+                let output = result.map_err(map_cache_error)?;
+                self.env = env;
+                return Ok(output);
+            }
+            cache.fetch_missing(db).await.map_err(EVMError::Database)?;
+        }
+    }
 }
 
 impl<'a, DB: DatabaseRef> EVM<DB> {
@@ -138,6 +633,42 @@ impl<'a, DB: DatabaseRef> EVM<DB> {
         }
     }
 
+    /// Execute transaction with [StateOverlay] applied on top of the DB, without writing to
+    /// either of them. Return change state.
+    ///
+    /// All changes stay in the journaled state, so the same DB can serve concurrent calls.
+    pub fn transact_ref_with_overlay(&self, overlay: &StateOverlay) -> EVMResult<DB::Error> {
+        if let Some(db) = self.db.as_ref() {
+            new_evm::<_>(
+                &mut self.env.clone(),
+                &mut WrapDatabaseRef(OverlayDB::new(db, overlay)),
+                None,
+            )
+            .transact()
+        } else {
+            panic!("Database needs to be set");
+        }
+    }
+
+    /// Execute transaction with given inspector and [StateOverlay] applied on top of the DB,
+    /// without writing to either of them. Return change state.
+    pub fn inspect_ref_with_overlay<I: Inspector<WrapDatabaseRef<OverlayDB<'a, &'a DB>>>>(
+        &'a self,
+        overlay: &'a StateOverlay,
+        mut inspector: I,
+    ) -> EVMResult<DB::Error> {
+        if let Some(db) = self.db.as_ref() {
+            new_evm(
+                &mut self.env.clone(),
+                &mut WrapDatabaseRef(OverlayDB::new(db, overlay)),
+                Some(&mut inspector),
+            )
+            .transact()
+        } else {
+            panic!("Database needs to be set");
+        }
+    }
+
     /// Execute transaction with given inspector, without wring to DB. Return change state.
     pub fn inspect_ref<I: Inspector<WrapDatabaseRef<&'a DB>>>(
         &'a self,
@@ -156,6 +687,74 @@ impl<'a, DB: DatabaseRef> EVM<DB> {
     }
 }
 
+impl<DB: DatabaseRef> EVM<DB> {
+    /// Execute the same transactions in multiple orders, without writing to DB.
+    ///
+    /// Every order starts from the same pre-state and transactions are committed between each
+    /// other as in [EVM::transact_multiple]. Data loaded from the database is shared between
+    /// orders, so each item is fetched only once. Execution of an order stops at the first
+    /// failing transaction.
+    ///
+    /// Results are compared against the first order, transactions with different results are
+    /// reported as [OrderingOutcome::conflicts]. See [crate::shuffled_orders] to generate orders.
+    ///
+    /// Returns [InvalidOrder] without executing anything if an order refers to a transaction
+    /// that is not in `txs`.
+    pub fn transact_orderings(
+        &self,
+        txs: &[TxEnv],
+        orders: &[Vec<usize>],
+    ) -> Result<Vec<OrderingOutcome<DB::Error>>, InvalidOrder> {
+        InvalidOrder::check(orders, txs.len())?;
+        let db = self.db.as_ref().expect("Database needs to be set");
+        let pre_state = SharedPreState::new(db);
+
+        let mut outcomes: Vec<OrderingOutcome<DB::Error>> = Vec::with_capacity(orders.len());
+        for order in orders {
+            let mut evm = EVM::with_env(self.env.clone());
+            evm.database(CacheDB::new(&pre_state));
+
+            let mut outcome = OrderingOutcome {
+                order: order.clone(),
+                results: Vec::with_capacity(order.len()),
+                summary: BlockSummary::default(),
+                error: None,
+                conflicts: Vec::new(),
+            };
+            for &index in order {
+                match evm.execute_in_block(&txs[index], &mut outcome.summary, None) {
+                    Ok(result_and_state) => outcome.results.push(result_and_state),
+                    Err(e) => {
+                        outcome.error = Some(e);
+                        break;
+                    }
+                }
+            }
+
+            if let Some(first) = outcomes.first() {
+                outcome.mark_conflicts(first);
+            }
+            outcomes.push(outcome);
+        }
+        Ok(outcomes)
+    }
+}
+
+/// Lends the memory pool and the analysis cache to the EVM for the duration of `f`.
+fn with_caches<'a, E, T>(
+    mut evm: Box<dyn TransactExt<E> + 'a>,
+    memory_pool: &mut SharedMemoryPool,
+    analysis_cache: &mut AnalysisCache,
+    f: impl FnOnce(&mut dyn TransactExt<E>) -> T,
+) -> T {
+    core::mem::swap(evm.memory_pool(), memory_pool);
+    core::mem::swap(evm.analysis_cache(), analysis_cache);
+    let output = f(&mut *evm);
+    core::mem::swap(evm.memory_pool(), memory_pool);
+    core::mem::swap(evm.analysis_cache(), analysis_cache);
+    output
+}
+
 impl<DB> EVM<DB> {
     /// Creates a new [EVM] instance with the default environment,
     pub fn new() -> Self {
@@ -164,7 +763,12 @@ impl<DB> EVM<DB> {
 
     /// Creates a new [EVM] instance with the given environment.
     pub fn with_env(env: Env) -> Self {
-        Self { env, db: None }
+        Self {
+            env,
+            db: None,
+            memory_pool: SharedMemoryPool::new(),
+            analysis_cache: AnalysisCache::new(),
+        }
     }
 
     pub fn database(&mut self, db: DB) {
@@ -178,4 +782,2125 @@ impl<DB> EVM<DB> {
     pub fn take_db(&mut self) -> DB {
         core::mem::take(&mut self.db).unwrap()
     }
+
+    /// Sets the spec to the one the schedule activates in the current block, and returns it.
+    ///
+    /// Call it after setting the block number and timestamp.
+    pub fn set_spec_from_schedule(&mut self, schedule: &HardforkSchedule) -> SpecId {
+        self.env.cfg.spec_id = schedule.spec_id_for_block(&self.env.block);
+        self.env.cfg.spec_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{AccountOverlay, AccountState, CacheDB, EmptyDB, State},
+        interpreter::opcode,
+        primitives::{
+            hex, AccountInfo, Bytecode, BytecodeState, CreateScheme, GasBreakdown, Halt, B256,
+        },
+        Breakpoint,
+    };
+
+    #[test]
+    fn transact_block_applies_system_call_and_withdrawals() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 0 CALLDATALOAD PUSH1 0 SSTORE STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x00, 0x35, 0x60, 0x00, 0x55, 0x00,
+        ]));
+        db.insert_account_info(
+            BEACON_ROOTS_ADDRESS,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.env.cfg.spec_id = SpecId::CANCUN;
+        evm.database(db);
+
+        let recipient = Address::with_last_byte(0x10);
+        let withdrawals = [5, 7].map(|amount| Withdrawal {
+            address: recipient,
+            amount,
+            ..Default::default()
+        });
+
+        // Beacon root is required from Cancun.
+        let block = BlockEnv {
+            number: U256::from(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            evm.transact_block(block.clone(), &[], &withdrawals),
+            Err((
+                Vec::new(),
+                InvalidHeader::ParentBeaconBlockRootNotSet.into()
+            ))
+        );
+
+        let root = B256::with_last_byte(0x42);
+        let block = BlockEnv {
+            parent_beacon_block_root: Some(root),
+            ..block
+        };
+        let (results, summary) = evm.transact_block(block, &[], &withdrawals).unwrap();
+        assert!(results.is_empty());
+        assert_eq!(summary.gas_used, 0);
+
+        let db = evm.db.as_ref().unwrap();
+        assert_eq!(
+            db.accounts[&BEACON_ROOTS_ADDRESS].storage[&U256::ZERO],
+            U256::from_be_bytes(root.0)
+        );
+        // System address is loaded by the call but its changes are discarded.
+        assert_eq!(
+            db.accounts[&SYSTEM_ADDRESS].account_state,
+            AccountState::NotExisting
+        );
+        assert_eq!(
+            db.accounts[&recipient].info.balance,
+            U256::from(12_000_000_000u64)
+        );
+    }
+
+    #[test]
+    fn blockhash_is_served_from_history_storage_if_enabled() {
+        let caller = Address::with_last_byte(0x10);
+        let reader = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 0 CALLDATALOAD PUSH1 1 NUMBER SUB SSTORE STOP
+        let history = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x00, 0x35, 0x60, 0x01, 0x43, 0x03, 0x55, 0x00,
+        ]));
+        db.insert_account_info(
+            HISTORY_STORAGE_ADDRESS,
+            AccountInfo::new(U256::ZERO, 1, history.hash_slow(), history),
+        );
+        // PUSH1 1 NUMBER SUB BLOCKHASH PUSH1 0 SSTORE STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x43, 0x03, 0x40, 0x60, 0x00, 0x55, 0x00,
+        ]));
+        db.insert_account_info(
+            reader,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        let legacy_hash = B256::with_last_byte(0x01);
+        db.block_hashes.insert(U256::from(4), legacy_hash);
+
+        let tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(reader),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        let block = BlockEnv {
+            number: U256::from(5),
+            parent_beacon_block_root: Some(B256::ZERO),
+            ..Default::default()
+        };
+        let parent_hash = B256::with_last_byte(0x42);
+        let block_with_parent = BlockEnv {
+            parent_hash: Some(parent_hash),
+            ..block.clone()
+        };
+        let stored = |db: &CacheDB<EmptyDB>, address, index: u64| {
+            db.accounts[&address]
+                .storage
+                .get(&U256::from(index))
+                .copied()
+        };
+
+        // Without parent hash the history system call is skipped.
+        let mut evm = EVM::new();
+        evm.env.cfg.spec_id = SpecId::PRAGUE;
+        evm.database(db.clone());
+        evm.transact_block(block, core::slice::from_ref(&tx), &[])
+            .unwrap();
+        let db_skipped = evm.take_db();
+        assert_eq!(stored(&db_skipped, HISTORY_STORAGE_ADDRESS, 4), None);
+        assert_eq!(
+            stored(&db_skipped, reader, 0),
+            Some(U256::from_be_bytes(legacy_hash.0))
+        );
+
+        // History is stored, but BLOCKHASH is still read from the database by default.
+        let mut evm = EVM::new();
+        evm.env.cfg.spec_id = SpecId::PRAGUE;
+        evm.database(db.clone());
+        evm.transact_block(block_with_parent.clone(), core::slice::from_ref(&tx), &[])
+            .unwrap();
+        let db_default = evm.take_db();
+        assert_eq!(
+            stored(&db_default, HISTORY_STORAGE_ADDRESS, 4),
+            Some(U256::from_be_bytes(parent_hash.0))
+        );
+        assert_eq!(
+            stored(&db_default, reader, 0),
+            Some(U256::from_be_bytes(legacy_hash.0))
+        );
+
+        let mut evm = EVM::new();
+        evm.env.cfg.spec_id = SpecId::PRAGUE;
+        evm.env.cfg.blockhash_from_history_storage = true;
+        evm.database(db);
+        evm.transact_block(block_with_parent, &[tx], &[]).unwrap();
+        let db = evm.db.as_ref().unwrap();
+        assert_eq!(
+            stored(db, reader, 0),
+            Some(U256::from_be_bytes(parent_hash.0))
+        );
+    }
+
+    #[test]
+    fn memory_is_reused_between_transactions() {
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 0 PUSH3 0x010000 MSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x00, 0x62, 0x01, 0x00, 0x00, 0x52,
+        ]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(Default::default(), 0, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 1_000_000;
+        assert!(evm.memory_pool.is_empty());
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(evm.memory_pool.len(), 1);
+
+        let memory = evm.memory_pool.take();
+        assert!(memory.capacity() >= 0x010020);
+        assert!(memory.is_empty());
+        evm.memory_pool.recycle(memory);
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(evm.memory_pool.len(), 1);
+
+        evm.memory_pool.set_max_size(0);
+        assert!(evm.transact().unwrap().result.is_success());
+        assert!(evm.memory_pool.is_empty());
+    }
+
+    #[test]
+    fn analysed_bytecode_is_cached_between_transactions() {
+        use crate::interpreter::analysis::to_analysed;
+
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 1 PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ]));
+        let code_hash = code.hash_slow();
+        db.insert_account_info(contract, AccountInfo::new(U256::ZERO, 0, code_hash, code));
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        let output = |evm: &mut EVM<_>| evm.transact().unwrap().result.output().cloned();
+        assert!(evm.analysis_cache.is_empty());
+        assert_eq!(
+            output(&mut evm),
+            Some(U256::from(1).to_be_bytes_vec().into())
+        );
+        assert_eq!(evm.analysis_cache.len(), 1);
+        assert!(matches!(
+            evm.analysis_cache.get(&code_hash).unwrap().state,
+            BytecodeState::Analysed { .. }
+        ));
+
+        // Cached bytecode is executed instead of the one of the database.
+        // PUSH1 2 PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+        evm.analysis_cache.insert(
+            code_hash,
+            to_analysed(Bytecode::new_raw(Bytes::from_static(&[
+                0x60, 0x02, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+            ]))),
+        );
+        assert_eq!(
+            output(&mut evm),
+            Some(U256::from(2).to_be_bytes_vec().into())
+        );
+
+        evm.analysis_cache.set_capacity(0);
+        assert!(evm.analysis_cache.is_empty());
+        assert_eq!(
+            output(&mut evm),
+            Some(U256::from(1).to_be_bytes_vec().into())
+        );
+        assert!(evm.analysis_cache.is_empty());
+    }
+
+    #[test]
+    fn tx_gas_limit_is_clamped_to_cap() {
+        let caller = Address::with_last_byte(0x10);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.block.gas_limit = U256::from(30_000_000);
+        evm.env.tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(Address::with_last_byte(0x20)),
+            gas_limit: u64::MAX,
+            ..Default::default()
+        };
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            InvalidTransaction::CallerGasLimitMoreThanBlock.into()
+        );
+
+        evm.env.cfg.tx_gas_cap = Some(50_000);
+        let output = evm.transact().unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(output.requested_gas_limit, Some(u64::MAX));
+        // Transaction is left as requested.
+        assert_eq!(evm.env.tx.gas_limit, u64::MAX);
+
+        evm.preverify_transaction().unwrap();
+        assert_eq!(evm.env.tx.gas_limit, u64::MAX);
+        let output = evm.transact_preverified().unwrap();
+        assert_eq!(output.requested_gas_limit, Some(u64::MAX));
+        assert_eq!(evm.env.tx.gas_limit, u64::MAX);
+    }
+
+    #[test]
+    fn handler_aliases_first_frame_caller() {
+        use crate::{primitives::LatestSpec, EVMImpl, Transact};
+
+        let (caller, contract) = (Address::with_last_byte(0x10), Address::with_last_byte(0x20));
+        let mut db = CacheDB::new(EmptyDB::default());
+        // CALLER PUSH1 0 SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x33, 0x60, 0x00, 0x55]));
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+
+        let mut env = Env::default();
+        env.tx.caller = caller;
+        env.tx.transact_to = TransactTo::Call(contract);
+        env.tx.gas_limit = 100_000;
+        let mut evm = EVMImpl::<LatestSpec, _>::new_with_spec(&mut db, &mut env, None);
+        evm.handler.alias_caller = |_, context| Address::with_last_byte(context.caller[19] + 1);
+
+        let output = evm.transact().unwrap();
+        assert!(output.result.is_success());
+        let stored = output.state[&contract].storage[&U256::ZERO].present_value;
+        assert_eq!(stored, U256::from(0x11));
+        // Nonce is still bumped on the transaction caller.
+        assert_eq!(output.state[&caller].info.nonce, 1);
+    }
+
+    #[test]
+    fn handler_precompiles_can_be_replaced() {
+        use crate::{
+            precompile::{Precompile, PrecompileProvider, PrecompileResult, Precompiles},
+            primitives::LatestSpec,
+            EVMImpl, Transact,
+        };
+        use alloc::sync::Arc;
+
+        const ANSWER: Address = Address::with_last_byte(0x42);
+        const IDENTITY: Address = Address::with_last_byte(0x04);
+
+        fn answer(_input: &[u8], _gas_limit: u64) -> PrecompileResult {
+            Ok((10, vec![42]))
+        }
+
+        /// Latest precompiles with identity replaced by answer.
+        #[derive(Debug)]
+        struct AnswerPrecompiles;
+
+        impl PrecompileProvider for AnswerPrecompiles {
+            fn get(&self, address: &Address) -> Option<Precompile> {
+                match *address {
+                    ANSWER => Some(Precompile::Standard(answer)),
+                    IDENTITY => None,
+                    _ => Precompiles::latest().get(address),
+                }
+            }
+
+            fn warm_addresses(&self) -> Vec<Address> {
+                let mut addresses = Precompiles::latest().warm_addresses();
+                addresses.retain(|address| *address != IDENTITY);
+                addresses.push(ANSWER);
+                addresses
+            }
+        }
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        let mut env = Env::default();
+        env.tx.caller = Address::with_last_byte(0x10);
+        env.tx.data = Bytes::from_static(&[1, 2, 3]);
+        env.tx.gas_limit = 100_000;
+        let call = |env: &mut Env, db: &mut CacheDB<EmptyDB>, to, replace: bool| {
+            env.tx.transact_to = TransactTo::Call(to);
+            let mut evm = EVMImpl::<LatestSpec, _>::new_with_spec(db, env, None);
+            if replace {
+                evm.handler.precompiles = Arc::new(AnswerPrecompiles);
+            }
+            let output = evm.transact().unwrap();
+            output.result.into_output().unwrap()
+        };
+
+        assert_eq!(call(&mut env, &mut db, IDENTITY, false), env.tx.data);
+        assert!(call(&mut env, &mut db, ANSWER, false).is_empty());
+        assert!(call(&mut env, &mut db, IDENTITY, true).is_empty());
+        assert_eq!(
+            call(&mut env, &mut db, ANSWER, true),
+            Bytes::from_static(&[42])
+        );
+    }
+
+    #[test]
+    fn handler_transfer_takes_fee_on_value_transfers() {
+        use crate::{
+            handler::mainnet,
+            interpreter::{InstructionResult, Transfer},
+            primitives::LatestSpec,
+            EVMImpl, EvmContext, Journal, Transact,
+        };
+
+        const TREASURY: Address = Address::repeat_byte(0xfe);
+
+        /// Native token that sends a tenth of every transfer to the treasury.
+        fn fee_on_transfer<DB: Database, J: Journal>(
+            context: &mut EvmContext<'_, DB, J>,
+            transfer: &Transfer,
+        ) -> Result<(), InstructionResult> {
+            mainnet::transfer(context, transfer)?;
+            let fee = Transfer {
+                source: transfer.target,
+                target: TREASURY,
+                value: transfer.value / U256::from(10),
+            };
+            mainnet::transfer(context, &fee)
+        }
+
+        let caller = Address::repeat_byte(0x10);
+        let contract = Address::repeat_byte(0x20);
+        let receiver = Address::repeat_byte(0x40);
+        // CALL(GAS, receiver, 100, 0, 0, 0, 0) STOP
+        let mut code = vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 100, 0x73,
+        ];
+        code.extend_from_slice(receiver.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x00]);
+        let code = Bytecode::new_raw(code.into());
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000)));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut env = Env::default();
+        env.tx.caller = caller;
+        env.tx.transact_to = TransactTo::Call(contract);
+        env.tx.value = U256::from(200);
+        env.tx.gas_limit = 100_000;
+        let mut evm = EVMImpl::<LatestSpec, _>::new_with_spec(&mut db, &mut env, None);
+        evm.handler.transfer = fee_on_transfer;
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+
+        assert!(result.is_success());
+        let balance = |address| state[&address].info.balance;
+        assert_eq!(balance(caller), U256::from(800));
+        assert_eq!(balance(contract), U256::from(200 - 20 - 100));
+        assert_eq!(balance(receiver), U256::from(100 - 10));
+        assert_eq!(balance(TREASURY), U256::from(30));
+    }
+
+    #[test]
+    fn handler_create_address_derives_created_contracts() {
+        use crate::{
+            handler::mainnet,
+            interpreter::CreateInputs,
+            primitives::{keccak256, LatestSpec, Output},
+            EVMImpl, Transact,
+        };
+
+        /// Chain whose contract addresses start with `0xaa`.
+        fn prefixed(env: &Env, inputs: &CreateInputs, nonce: u64, hash: &B256) -> Address {
+            let mut address = mainnet::create_address(env, inputs, nonce, hash);
+            address[0] = 0xaa;
+            address
+        }
+
+        let caller = Address::repeat_byte(0x10);
+        let mut db = CacheDB::new(EmptyDB::default());
+        let mut env = Env::default();
+        env.tx.caller = caller;
+        env.tx.transact_to = TransactTo::Create(CreateScheme::Create);
+        // CREATE2(0, 0, 0, 0) PUSH1 0 SSTORE STOP
+        env.tx.data = Bytes::from_static(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0xf5, 0x60, 0x00, 0x55, 0x00,
+        ]);
+        env.tx.gas_limit = 200_000;
+        let mut evm = EVMImpl::<LatestSpec, _>::new_with_spec(&mut db, &mut env, None);
+        evm.handler.create_address = prefixed;
+        let ResultAndState { result, state, .. } = evm.transact().unwrap();
+
+        let mut created = caller.create(0);
+        created[0] = 0xaa;
+        let mut created2 = created.create2(B256::ZERO, keccak256([]));
+        created2[0] = 0xaa;
+        let ExecutionResult::Success {
+            output: Output::Create(_, address),
+            ..
+        } = result
+        else {
+            panic!("create failed: {result:?}");
+        };
+        assert_eq!(address, Some(created));
+        assert_eq!(
+            state[&created].storage[&U256::ZERO].present_value,
+            U256::from_be_slice(created2.as_slice())
+        );
+        assert!(state[&created2].is_created());
+    }
+
+    #[test]
+    fn context_precompile_changes_state_through_journal() {
+        use crate::{
+            precompile::{Error, PrecompileResult},
+            primitives::LatestSpec,
+            ContextStatefulPrecompile, EVMImpl, EvmContext, Transact,
+        };
+        use alloc::sync::Arc;
+
+        const COUNTER: Address = Address::with_last_byte(0x42);
+
+        /// Increments slot 0 of its account, fails after the increment on empty input.
+        #[derive(Debug)]
+        struct Counter;
+
+        fn increment<DB: Database>(context: &mut EvmContext<'_, DB>) -> Result<(), DB::Error> {
+            let journal = &mut context.journaled_state;
+            journal.load_account(COUNTER, context.db)?;
+            let (count, _) = journal.sload(COUNTER, U256::ZERO, context.db)?;
+            journal.sstore(COUNTER, U256::ZERO, count + U256::from(1), context.db)?;
+            Ok(())
+        }
+
+        impl<DB: Database> ContextStatefulPrecompile<DB> for Counter {
+            fn call(
+                &self,
+                input: &Bytes,
+                _gas_limit: u64,
+                context: &mut EvmContext<'_, DB>,
+            ) -> PrecompileResult {
+                if let Err(e) = increment(context) {
+                    context.error = Some(e);
+                    return Err(Error::OutOfGas);
+                }
+                if input.is_empty() {
+                    return Err(Error::OutOfGas);
+                }
+                Ok((100, Vec::new()))
+            }
+        }
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        let mut env = Env::default();
+        env.tx.caller = Address::with_last_byte(0x10);
+        env.tx.transact_to = TransactTo::Call(COUNTER);
+        env.tx.gas_limit = 100_000;
+        let mut call = |input: &'static [u8]| {
+            env.tx.data = Bytes::from_static(input);
+            let mut evm = EVMImpl::<LatestSpec, _>::new_with_spec(&mut db, &mut env, None);
+            evm.set_context_precompile(
+                COUNTER,
+                crate::ContextPrecompile::ContextStateful(Arc::new(Counter)),
+            );
+            let ResultAndState { result, state, .. } = evm.transact().unwrap();
+            let count = state
+                .get(&COUNTER)
+                .and_then(|account| account.storage.get(&U256::ZERO))
+                .map(|slot| slot.present_value);
+            (result, count)
+        };
+
+        let (result, count) = call(&[1]);
+        assert!(result.is_success());
+        assert_eq!(count, Some(U256::from(1)));
+
+        // Increment is reverted with the failed call.
+        let (result, count) = call(&[]);
+        assert!(!result.is_success());
+        assert!(count.unwrap_or_default().is_zero());
+    }
+
+    #[test]
+    fn precompile_gas_overrides_replace_precompile_cost() {
+        use crate::primitives::PrecompileGasCost;
+
+        const IDENTITY: Address = Address::with_last_byte(0x04);
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        let mut env = Env::default();
+        env.tx.caller = Address::with_last_byte(0x10);
+        env.tx.transact_to = TransactTo::Call(IDENTITY);
+        env.tx.data = Bytes::from(vec![1; 33]);
+        env.tx.gas_limit = 100_000;
+        let mut call = |env: &mut Env| {
+            let result = new_evm(env, &mut db, None).transact().unwrap().result;
+            (result.gas_used(), result.output().cloned())
+        };
+
+        let (gas_used, output) = call(&mut env);
+        assert_eq!(output, Some(env.tx.data.clone()));
+
+        let cost = PrecompileGasCost {
+            base: 1_000,
+            per_word: 10,
+        };
+        assert_eq!(cost.cost(33), 1_020);
+        env.cfg.precompile_gas_overrides.insert(IDENTITY, cost);
+        let (overridden_gas_used, output) = call(&mut env);
+        assert_eq!(output, Some(env.tx.data.clone()));
+        // Identity costs 15 gas and 3 gas per word.
+        assert_eq!(overridden_gas_used, gas_used - 21 + 1_020);
+
+        // Calls that can't pay the overridden cost run out of gas.
+        env.cfg.precompile_gas_overrides.insert(
+            IDENTITY,
+            PrecompileGasCost {
+                base: 1_000_000,
+                per_word: 0,
+            },
+        );
+        let (gas_used, output) = call(&mut env);
+        assert_eq!(gas_used, 100_000);
+        assert_eq!(output, None);
+    }
+
+    #[test]
+    fn handler_overrides_first_frame_stages() {
+        use crate::{
+            handler::{mainnet, FirstFrameInputs},
+            interpreter::{CallInputs, CallOutcome, Gas},
+            primitives::LatestSpec,
+            EVMImpl, EvmContext, Transact,
+        };
+
+        struct RevertFirstCall;
+
+        impl<DB: Database> Inspector<DB> for RevertFirstCall {
+            fn call(
+                &mut self,
+                _context: &mut EvmContext<'_, DB>,
+                inputs: &mut CallInputs,
+            ) -> Option<CallOutcome> {
+                Some(CallOutcome::revert(
+                    inputs.input.clone(),
+                    Gas::new(inputs.gas_limit),
+                ))
+            }
+        }
+
+        let (caller, contract) = (Address::with_last_byte(0x10), Address::with_last_byte(0x20));
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 0 CALLDATALOAD PUSH1 0 SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x35, 0x60, 0x00, 0x55]));
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+
+        let mut env = Env::default();
+        env.tx.caller = caller;
+        env.tx.transact_to = TransactTo::Call(contract);
+        env.tx.gas_limit = 100_000;
+        let mut inspector = RevertFirstCall;
+        let mut evm = EVMImpl::<LatestSpec, _>::new_with_spec(&mut db, &mut env, None);
+        evm.handler.first_frame_inputs = |env, gas_limit| {
+            let mut inputs = mainnet::first_frame_inputs(env, gas_limit);
+            if let FirstFrameInputs::Call(inputs) = &mut inputs {
+                inputs.input = Bytes::copy_from_slice(B256::with_last_byte(7).as_slice());
+            }
+            inputs
+        };
+
+        let output = evm.transact().unwrap();
+        let stored = output.state[&contract].storage[&U256::ZERO].present_value;
+        assert_eq!(stored, U256::from(7));
+
+        // Inspector sees the overridden inputs and skips the frame.
+        evm.inspector = Some(&mut inspector);
+        let output = evm.transact().unwrap();
+        assert_eq!(
+            output.result,
+            ExecutionResult::revert(
+                GasBreakdown {
+                    intrinsic: 21_000,
+                    execution: 0,
+                    refunded: 0,
+                    returned: 79_000,
+                },
+                Bytes::copy_from_slice(B256::with_last_byte(7).as_slice()),
+            )
+        );
+    }
+
+    #[test]
+    fn handler_executes_chain_specific_tx_kinds() {
+        use crate::{
+            handler::{mainnet, ChainTx, FirstFrameInputs},
+            primitives::{Account, InvalidTransaction, LatestSpec},
+            EVMImpl, Transact,
+        };
+        use alloc::sync::Arc;
+
+        const SYSTEM_TX: u8 = 0x7E;
+        let system = Address::with_last_byte(0xFE);
+
+        /// System transactions are free and are sent by the system address.
+        struct SystemTx;
+
+        impl ChainTx for SystemTx {
+            fn supports(&self, kind: u8) -> bool {
+                kind == SYSTEM_TX
+            }
+
+            fn validate_tx(&self, env: &Env, _kind: u8) -> Result<(), InvalidTransaction> {
+                if env.tx.gas_price.is_zero() {
+                    Ok(())
+                } else {
+                    Err(InvalidTransaction::GasPriceLessThanBasefee)
+                }
+            }
+
+            fn validate_tx_against_state(
+                &self,
+                _env: &Env,
+                _kind: u8,
+                _caller: &mut Account,
+            ) -> Result<(), InvalidTransaction> {
+                Ok(())
+            }
+
+            fn first_frame_inputs(&self, env: &Env, _kind: u8, gas_limit: u64) -> FirstFrameInputs {
+                let mut inputs = mainnet::first_frame_inputs(env, gas_limit);
+                if let FirstFrameInputs::Call(inputs) = &mut inputs {
+                    inputs.context.caller = Address::with_last_byte(0xFE);
+                }
+                inputs
+            }
+        }
+
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // CALLER PUSH1 0 SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x33, 0x60, 0x00, 0x55]));
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+
+        let mut env = Env::default();
+        env.tx.caller = Address::with_last_byte(0x10);
+        env.tx.transact_to = TransactTo::Call(contract);
+        env.tx.gas_limit = 100_000;
+        env.tx.nonce = Some(5);
+        env.tx.chain_tx_kind = Some(SYSTEM_TX);
+        let mut evm = EVMImpl::<LatestSpec, _>::new_with_spec(&mut db, &mut env, None);
+
+        assert_eq!(
+            evm.transact(),
+            Err(InvalidTransaction::UnsupportedChainTxKind(SYSTEM_TX).into())
+        );
+
+        // Nonce check is skipped and the caller is replaced by the system address.
+        evm.handler.chain_tx = Some(Arc::new(SystemTx));
+        let output = evm.transact().unwrap();
+        assert!(output.result.is_success());
+        let stored = output.state[&contract].storage[&U256::ZERO].present_value;
+        assert_eq!(stored, U256::from_be_slice(system.as_slice()));
+    }
+
+    #[test]
+    fn inspector_sees_tx_kind_of_first_frame() {
+        use crate::{
+            handler::ChainTx,
+            interpreter::{CallInputs, CallOutcome, TxKind},
+            primitives::{InvalidTransaction, LatestSpec},
+            EVMImpl, EvmContext, Transact,
+        };
+        use alloc::sync::Arc;
+
+        #[derive(Default)]
+        struct TxKinds(Vec<Option<TxKind>>);
+
+        impl<DB: Database> Inspector<DB> for TxKinds {
+            fn call(
+                &mut self,
+                _context: &mut EvmContext<'_, DB>,
+                inputs: &mut CallInputs,
+            ) -> Option<CallOutcome> {
+                self.0.push(inputs.tx_kind);
+                None
+            }
+        }
+
+        struct AnyKind;
+
+        impl ChainTx for AnyKind {
+            fn supports(&self, _kind: u8) -> bool {
+                true
+            }
+
+            fn validate_tx(&self, _env: &Env, _kind: u8) -> Result<(), InvalidTransaction> {
+                Ok(())
+            }
+        }
+
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 0 DUP1 DUP1 DUP1 DUP1 PUSH1 0x30 GAS CALL
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x00, 0x80, 0x80, 0x80, 0x80, 0x60, 0x30, 0x5a, 0xf1,
+        ]));
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+
+        let mut env = Env::default();
+        env.tx.caller = Address::with_last_byte(0x10);
+        env.tx.transact_to = TransactTo::Call(contract);
+        env.tx.gas_limit = 100_000;
+        let mut inspector = TxKinds::default();
+        let mut evm =
+            EVMImpl::<LatestSpec, _>::new_with_spec(&mut db, &mut env, Some(&mut inspector));
+        evm.handler.chain_tx = Some(Arc::new(AnyKind));
+        evm.transact().unwrap();
+        evm.context.env.tx.chain_tx_kind = Some(0x7E);
+        evm.transact().unwrap();
+        drop(evm);
+
+        assert_eq!(
+            inspector.0,
+            [
+                Some(TxKind::Ethereum),
+                None,
+                Some(TxKind::Chain(0x7E)),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn inspector_updates_external_context() {
+        use crate::{
+            interpreter::{CallInputs, CallOutcome},
+            EvmContext,
+        };
+
+        #[derive(Default)]
+        struct Ledger {
+            calls: Vec<Address>,
+        }
+
+        struct LedgerInspector;
+
+        impl<DB: Database> Inspector<DB, JournaledState, &mut Ledger> for LedgerInspector {
+            fn call(
+                &mut self,
+                context: &mut EvmContext<'_, DB, JournaledState, &mut Ledger>,
+                inputs: &mut CallInputs,
+            ) -> Option<CallOutcome> {
+                context.external.calls.push(inputs.contract);
+                None
+            }
+        }
+
+        let contract = Address::with_last_byte(0x20);
+        let mut evm = EVM::new();
+        evm.database(CacheDB::new(EmptyDB::default()));
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 21_000;
+
+        let mut ledger = Ledger::default();
+        let output = evm
+            .inspect_with_external(LedgerInspector, &mut ledger)
+            .unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(ledger.calls, [contract]);
+    }
+
+    #[test]
+    fn handler_updates_external_context() {
+        use crate::{primitives::LatestSpec, EVMImpl, Handler, Transact};
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        let mut env = Env::default();
+        env.tx.caller = Address::with_last_byte(0x10);
+        env.tx.transact_to = TransactTo::Call(Address::with_last_byte(0x20));
+        env.tx.gas_limit = 21_000;
+
+        // Counts the rewarded transactions instead of paying the beneficiary.
+        let mut handler = Handler::<_, JournaledState, u64>::mainnet::<LatestSpec>();
+        handler.reward_beneficiary = |context, _| {
+            context.external += 1;
+            Ok(())
+        };
+        let mut evm =
+            EVMImpl::<LatestSpec, _, _, _>::new_with_external(&mut db, &mut env, None, handler, 0);
+        assert!(evm.transact().unwrap().result.is_success());
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(evm.context.external, 2);
+    }
+
+    #[test]
+    fn inspector_records_and_forbids_jumps() {
+        use crate::EvmContext;
+
+        #[derive(Default)]
+        struct JumpInspector {
+            edges: Vec<(usize, U256, Option<U256>)>,
+            forbidden: Option<U256>,
+        }
+
+        impl<DB: Database> Inspector<DB> for JumpInspector {
+            fn jump(
+                &mut self,
+                _context: &mut EvmContext<'_, DB>,
+                _address: Address,
+                pc: usize,
+                dest: &mut U256,
+                condition: Option<&mut U256>,
+            ) -> bool {
+                self.edges.push((pc, *dest, condition.map(|c| *c)));
+                self.forbidden != Some(*dest)
+            }
+        }
+
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 1 PUSH1 7 JUMPI STOP STOP JUMPDEST PUSH1 12 JUMP INVALID JUMPDEST STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x60, 0x07, 0x57, 0x00, 0x00, 0x5b, 0x60, 0x0c, 0x56, 0xfe, 0x5b, 0x00,
+        ]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+
+        let mut inspector = JumpInspector::default();
+        let output = evm.inspect(&mut inspector).unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(
+            inspector.edges,
+            [
+                (4, U256::from(7), Some(U256::from(1))),
+                (10, U256::from(12), None)
+            ]
+        );
+
+        let mut inspector = JumpInspector {
+            forbidden: Some(U256::from(12)),
+            ..Default::default()
+        };
+        let output = evm.inspect(&mut inspector).unwrap();
+        assert!(matches!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: Halt::InvalidJump,
+                ..
+            }
+        ));
+        assert_eq!(inspector.edges.len(), 2);
+    }
+
+    #[test]
+    fn disabled_opcodes_halt_execution() {
+        use crate::primitives::OpcodeSet;
+
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 1 PUSH1 0 TSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x5d]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+        assert!(evm.transact().unwrap().result.is_success());
+
+        evm.env.cfg.disabled_opcodes = OpcodeSet::new([opcode::TSTORE]);
+        let halted = |result: ExecutionResult| {
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: Halt::OpcodeNotFound,
+                    ..
+                }
+            )
+        };
+        assert!(halted(evm.transact().unwrap().result));
+        assert!(halted(evm.inspect(NoOpInspector).unwrap().result));
+    }
+
+    #[test]
+    fn unmodified_instruction_table_is_const() {
+        use crate::{
+            interpreter::opcode::InstructionTables,
+            primitives::{LatestSpec, OpcodeSet},
+            EVMImpl,
+        };
+
+        let table_of = |mut env: Env| {
+            let mut db = EmptyDB::default();
+            let evm = EVMImpl::<LatestSpec, _>::new_with_spec(&mut db, &mut env, None);
+            match evm.instruction_table {
+                InstructionTables::Plain(_) => "plain",
+                InstructionTables::Boxed(_) => "boxed",
+                InstructionTables::Const(_) => "const",
+            }
+        };
+        assert_eq!(table_of(Env::default()), "const");
+
+        let mut env = Env::default();
+        env.cfg.disabled_opcodes = OpcodeSet::new([opcode::TSTORE]);
+        assert_eq!(table_of(env), "plain");
+
+        let mut env = Env::default();
+        env.cfg.disable_gas_metering = true;
+        assert_eq!(table_of(env), "boxed");
+    }
+
+    #[cfg(feature = "instruction-count")]
+    #[test]
+    fn instructions_are_counted_over_frames() {
+        let (caller, callee) = (Address::with_last_byte(0x20), Address::with_last_byte(0x21));
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in [
+            // CALL(GAS, callee, 0, 0, 0, 0, 0) POP STOP
+            (
+                caller,
+                &[
+                    0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x21, 0x5a,
+                    0xf1, 0x50, 0x00,
+                ][..],
+            ),
+            // PUSH1 1 PUSH1 2 ADD POP STOP
+            (callee, &[0x60, 0x01, 0x60, 0x02, 0x01, 0x50, 0x00][..]),
+        ] {
+            let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+        }
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(caller);
+        for _ in 0..2 {
+            let counts = evm.transact().unwrap().instruction_counts.unwrap();
+            assert_eq!(counts.steps, 10 + 5);
+            assert_eq!(counts.count(opcode::PUSH1), 6 + 2);
+            assert_eq!(counts.count(opcode::CALL), 1);
+            assert_eq!(counts.count(opcode::POP), 2);
+            assert_eq!(counts.count(opcode::STOP), 2);
+            assert_eq!(counts.count(opcode::SSTORE), 0);
+        }
+        let counts = evm
+            .inspect(NoOpInspector)
+            .unwrap()
+            .instruction_counts
+            .unwrap();
+        assert_eq!(counts.steps, 15);
+    }
+
+    #[test]
+    fn proxy_forwards_sub_call_output() {
+        let (proxy, callee) = (Address::with_last_byte(0x20), Address::with_last_byte(0x21));
+        // CALL(GAS, callee, 0, 0, 0, 0, 0) POP RETURNDATACOPY(0, 0, RETURNDATASIZE)
+        let forward = [
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x21, 0x5a, 0xf1,
+            0x50, 0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e,
+        ];
+        // RETURN(0, RETURNDATASIZE)
+        let ret = [0x3d, 0x60, 0x00, 0xf3];
+        // MSTORE8(0, 0xff)
+        let overwrite = [0x60, 0xff, 0x60, 0x00, 0x53];
+
+        for (tail, first_byte) in [(&ret[..], 0x00), (&[&overwrite[..], &ret].concat(), 0xff)] {
+            let mut db = CacheDB::new(EmptyDB::default());
+            for (address, code) in [
+                (proxy, [&forward[..], tail].concat()),
+                // MSTORE(0, 0x2a) RETURN(0, 32)
+                (
+                    callee,
+                    vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3],
+                ),
+            ] {
+                let code = Bytecode::new_raw(code.into());
+                db.insert_account_info(
+                    address,
+                    AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+                );
+            }
+
+            let mut evm = EVM::new();
+            evm.database(db);
+            evm.env.tx.caller = Address::with_last_byte(0x10);
+            evm.env.tx.transact_to = TransactTo::Call(proxy);
+            let output = evm.transact().unwrap().result.into_output().unwrap();
+            let mut expected = U256::from(0x2a).to_be_bytes::<32>();
+            expected[0] = first_byte;
+            assert_eq!(output[..], expected[..]);
+        }
+    }
+
+    #[test]
+    fn estimate_gas_finds_lowest_gas_limit() {
+        let (forwarder, clear, write, revert, invalid) = (
+            Address::with_last_byte(0x20),
+            Address::with_last_byte(0x21),
+            Address::with_last_byte(0x22),
+            Address::with_last_byte(0x23),
+            Address::with_last_byte(0x24),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in [
+            // CALL(GAS, write, 0, 0, 0, 0, 0) and REVERT(0, 0) if it failed
+            (
+                forwarder,
+                &[
+                    0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x22, 0x5a,
+                    0xf1, 0x15, 0x60, 0x13, 0x57, 0x00, 0x5b, 0x60, 0x00, 0x60, 0x00, 0xfd,
+                ][..],
+            ),
+            // SSTORE(0, 0) of the set slot
+            (clear, &[0x60, 0x00, 0x60, 0x00, 0x55, 0x00][..]),
+            // SSTORE(0, 1)
+            (write, &[0x60, 0x01, 0x60, 0x00, 0x55, 0x00][..]),
+            // REVERT(0, 0)
+            (revert, &[0x60, 0x00, 0x60, 0x00, 0xfd][..]),
+            (invalid, &[0xfe][..]),
+        ] {
+            let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+        }
+        db.insert_account_storage(clear, U256::ZERO, U256::from(1))
+            .unwrap();
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        for contract in [forwarder, clear] {
+            evm.env.tx.transact_to = TransactTo::Call(contract);
+            evm.env.tx.gas_limit = 100_000;
+            let gas_used = evm.transact().unwrap().result.gas_used();
+            let estimate = evm.estimate_gas().unwrap();
+            assert_eq!(evm.env.tx.gas_limit, 100_000);
+            assert!(estimate > gas_used);
+
+            evm.env.tx.gas_limit = estimate;
+            assert!(evm.transact().unwrap().result.is_success());
+            evm.env.tx.gas_limit = estimate - 1;
+            assert!(!evm.transact().unwrap().result.is_success());
+        }
+
+        evm.env.tx.gas_limit = 100_000;
+        evm.env.tx.transact_to = TransactTo::Call(revert);
+        assert_eq!(
+            evm.estimate_gas().unwrap_err().code,
+            RpcError::EXECUTION_REVERTED
+        );
+        evm.env.tx.transact_to = TransactTo::Call(invalid);
+        assert_eq!(
+            evm.estimate_gas().unwrap_err().message,
+            "gas required exceeds allowance (100000)"
+        );
+    }
+
+    #[test]
+    fn gas_breakdown_reports_refund() {
+        use crate::{primitives::LatestSpec, EVMImpl, Transact};
+
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // SSTORE(0, 0) STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0x55, 0x00]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        db.insert_account_storage(contract, U256::ZERO, U256::from(1))
+            .unwrap();
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+        let result = evm.transact().unwrap().result;
+        let gas = *result.gas();
+        assert_eq!(
+            gas,
+            GasBreakdown {
+                intrinsic: 21_000,
+                // two pushes and a cold SSTORE clearing the slot
+                execution: 3 + 3 + 2_100 + 2_900,
+                refunded: 4_800,
+                returned: 100_000 - 26_006,
+            }
+        );
+        assert_eq!(result.gas_used(), 26_006 - 4_800);
+        assert_eq!(gas.limit(), 100_000);
+
+        // Chains can replace the refund policy.
+        let mut evm =
+            EVMImpl::<LatestSpec, _>::new_with_spec(evm.db.as_mut().unwrap(), &mut evm.env, None);
+        evm.handler.calculate_gas_refund = |_, gas| gas.refunded() as u64 / 2;
+        let gas = *evm.transact().unwrap().result.gas();
+        assert_eq!(gas.refunded, 2_400);
+        assert_eq!(gas.used(), 26_006 - 2_400);
+    }
+
+    #[test]
+    fn gas_metering_can_be_disabled() {
+        let (looping, caller, expanding) = (
+            Address::with_last_byte(0x20),
+            Address::with_last_byte(0x21),
+            Address::with_last_byte(0x22),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in [
+            // PUSH2 0x1000 JUMPDEST PUSH1 1 SWAP1 SUB DUP1 PUSH1 3 JUMPI STOP
+            (
+                looping,
+                &[
+                    0x61, 0x10, 0x00, 0x5b, 0x60, 0x01, 0x90, 0x03, 0x80, 0x60, 0x03, 0x57, 0x00,
+                ][..],
+            ),
+            // CALL(GAS, looping, 0, 0, 0, 0, 0) and return the success flag.
+            (
+                caller,
+                &[
+                    0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x20, 0x5a,
+                    0xf1, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+                ][..],
+            ),
+            // PUSH1 0 PUSH4 0x10000000 MSTORE
+            (
+                expanding,
+                &[0x60, 0x00, 0x63, 0x10, 0x00, 0x00, 0x00, 0x52][..],
+            ),
+        ] {
+            let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+        }
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(looping);
+        evm.env.tx.gas_limit = 50_000;
+        assert!(evm.transact().unwrap().result.is_halt());
+
+        evm.env.cfg.disable_gas_metering = true;
+        let result = evm.transact().unwrap().result;
+        assert!(result.is_success());
+        assert_eq!(result.gas_used(), 21_000);
+        assert_eq!(evm.inspect(NoOpInspector).unwrap().result, result);
+
+        // Gas forwarded to the call is returned.
+        evm.env.tx.transact_to = TransactTo::Call(caller);
+        let result = evm.transact().unwrap().result;
+        assert_eq!(result.gas_used(), 21_000);
+        assert_eq!(
+            result.output(),
+            Some(&Bytes::from(U256::from(1).to_be_bytes_vec()))
+        );
+
+        // Memory is still bounded by the gas limit.
+        evm.env.tx.transact_to = TransactTo::Call(expanding);
+        assert!(evm.transact().unwrap().result.is_halt());
+    }
+
+    #[test]
+    fn memory_limit_halts_and_peak_memory_is_reported() {
+        use crate::{interpreter::InterpreterResult, primitives::OutOfGasError};
+
+        /// Records peak memory of the calls.
+        #[derive(Default)]
+        struct PeakMemory(Vec<usize>);
+
+        impl<DB: Database> Inspector<DB> for PeakMemory {
+            fn call_end(
+                &mut self,
+                _context: &mut crate::EvmContext<'_, DB>,
+                result: InterpreterResult,
+            ) -> InterpreterResult {
+                self.0.push(result.peak_memory);
+                result
+            }
+        }
+
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 1 PUSH2 0x1000 MSTORE8
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x61, 0x10, 0x00, 0x53]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+        let mut inspector = PeakMemory::default();
+        assert!(evm.inspect(&mut inspector).unwrap().result.is_success());
+        assert_eq!(inspector.0, [0x1020]);
+
+        evm.env.cfg.memory_limit = 0x1000;
+        assert_eq!(
+            evm.transact().unwrap().result,
+            ExecutionResult::Halt {
+                reason: Halt::OutOfGas(OutOfGasError::MemoryLimit),
+                gas: GasBreakdown {
+                    intrinsic: 21_000,
+                    execution: 79_000,
+                    refunded: 0,
+                    returned: 0,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn shadowed_execution_matches_inspected() {
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 42 PUSH1 0 SSTORE PUSH1 0 PUSH1 0 LOG0 PUSH1 14 JUMP INVALID JUMPDEST GAS STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x2a, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0xa0, 0x60, 0x0e, 0x56, 0xfe,
+            0x5b, 0x5a, 0x00,
+        ]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.cfg.tx_gas_cap = Some(100_000);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = u64::MAX;
+
+        let output = evm.transact_shadowed().unwrap().unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(output.result.logs().len(), 1);
+        assert_eq!(output.requested_gas_limit, Some(u64::MAX));
+    }
+
+    #[test]
+    fn shadowed_execution_reports_mismatch() {
+        /// Database whose balances grow on every read, so no two executions are the same.
+        struct GrowingBalances(u64);
+
+        impl Database for GrowingBalances {
+            type Error = core::convert::Infallible;
+
+            fn basic(&mut self, _address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+                self.0 += 1;
+                Ok(Some(AccountInfo::from_balance(U256::from(self.0))))
+            }
+
+            fn code_by_hash(&mut self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+                Ok(Bytecode::new())
+            }
+
+            fn storage(&mut self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
+                Ok(U256::ZERO)
+            }
+
+            fn block_hash(&mut self, _number: U256) -> Result<B256, Self::Error> {
+                Ok(B256::ZERO)
+            }
+        }
+
+        let mut evm = EVM::new();
+        evm.database(GrowingBalances(0));
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(Address::with_last_byte(0x20));
+        evm.env.tx.gas_limit = 21_000;
+
+        let mismatch = evm.transact_shadowed().unwrap_err();
+        let (plain, inspected) = (mismatch.plain.unwrap(), mismatch.inspected.unwrap());
+        assert_eq!(plain.result, inspected.result);
+        assert_ne!(plain.state, inspected.state);
+    }
+
+    #[test]
+    fn output_reports_charged_fees() {
+        let caller = Address::with_last_byte(0x10);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.block.basefee = U256::from(7);
+        evm.env.block.set_blob_excess_gas_and_price(0);
+        evm.env.tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(Address::with_last_byte(0x20)),
+            gas_limit: 100_000,
+            gas_price: U256::from(10),
+            gas_priority_fee: Some(U256::from(1)),
+            max_fee_per_blob_gas: Some(U256::from(1)),
+            blob_hashes: vec![B256::with_last_byte(1); 2]
+                .into_iter()
+                .map(|mut hash| {
+                    hash[0] = crate::primitives::VERSIONED_HASH_VERSION_KZG;
+                    hash
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let output = evm.transact().unwrap();
+        let fees = output.fees;
+        assert_eq!(fees.effective_gas_price, U256::from(8));
+        assert_eq!(fees.blob_gas_used, 2 * crate::primitives::GAS_PER_BLOB);
+        assert_eq!(fees.blob_gas_price, 1);
+
+        let paid = output.state[&caller].info.balance;
+        let charged = fees.gas_fee(output.result.gas_used()) + fees.blob_fee();
+        assert_eq!(U256::from(10u64.pow(18)) - paid, charged);
+    }
+
+    #[cfg(feature = "halt_snapshot")]
+    #[test]
+    fn halt_captures_stack_and_memory() {
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 0x2a PUSH1 0 MSTORE PUSH1 0x07 PUSH1 0x01 JUMP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x07, 0x60, 0x01, 0x56,
+        ]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.cfg.halt_snapshot = Some(crate::primitives::HaltSnapshotLimits {
+            stack: 16,
+            memory: 8,
+        });
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+        let output = evm.transact().unwrap();
+        assert!(output.result.is_halt());
+
+        let snapshot = output.halt_snapshot.unwrap();
+        assert_eq!(snapshot.address, contract);
+        assert_eq!((snapshot.pc, snapshot.opcode), (9, opcode::JUMP));
+        // Jump destination was popped before the jump failed.
+        assert_eq!(snapshot.stack, vec![U256::from(7)]);
+        assert_eq!((snapshot.memory_offset, snapshot.memory_len), (24, 32));
+        assert_eq!(snapshot.memory[..], [0, 0, 0, 0, 0, 0, 0, 0x2a]);
+
+        evm.env.cfg.halt_snapshot = None;
+        assert_eq!(evm.transact().unwrap().halt_snapshot, None);
+    }
+
+    #[derive(Default)]
+    struct BlockHooks {
+        calls: Vec<&'static str>,
+        cumulative_gas_used: Vec<u64>,
+        block_gas_used: Option<u64>,
+    }
+
+    impl<DB: Database> Inspector<DB> for BlockHooks {
+        fn begin_block(&mut self, _env: &Env) {
+            self.calls.push("begin_block");
+        }
+
+        fn begin_tx(&mut self, _env: &Env) {
+            self.calls.push("begin_tx");
+        }
+
+        fn end_tx(&mut self, _env: &Env, _result: &ResultAndState, summary: &BlockSummary) {
+            self.calls.push("end_tx");
+            self.cumulative_gas_used.push(summary.gas_used);
+        }
+
+        fn end_block(&mut self, _env: &Env, summary: &BlockSummary) {
+            self.calls.push("end_block");
+            self.block_gas_used = Some(summary.gas_used);
+        }
+    }
+
+    #[test]
+    fn inspect_multiple_calls_block_hooks() {
+        let caller = Address::with_last_byte(0x10);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+
+        let mut evm = EVM::new();
+        evm.database(db);
+
+        let txs = (0..2)
+            .map(|nonce| TxEnv {
+                caller,
+                transact_to: TransactTo::Call(Address::with_last_byte(0x20)),
+                gas_limit: 21_000,
+                nonce: Some(nonce),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut hooks = BlockHooks::default();
+        evm.inspect_multiple(&txs, &mut hooks).unwrap();
+        assert_eq!(
+            hooks.calls,
+            [
+                "begin_block",
+                "begin_tx",
+                "end_tx",
+                "begin_tx",
+                "end_tx",
+                "end_block"
+            ]
+        );
+        assert_eq!(hooks.cumulative_gas_used, [21_000, 42_000]);
+        assert_eq!(hooks.block_gas_used, Some(42_000));
+
+        // Block end is reported for a failed batch with the transactions executed before it.
+        let txs = [2, 2].map(|nonce| TxEnv {
+            nonce: Some(nonce),
+            ..txs[0].clone()
+        });
+        let mut hooks = BlockHooks::default();
+        let (results, _) = evm.inspect_multiple(&txs, &mut hooks).unwrap_err();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            hooks.calls,
+            ["begin_block", "begin_tx", "end_tx", "begin_tx", "end_block"]
+        );
+        assert_eq!(hooks.block_gas_used, Some(21_000));
+    }
+
+    #[test]
+    fn inspect_block_ends_failed_block() {
+        let mut evm = EVM::new();
+        evm.env.cfg.spec_id = SpecId::CANCUN;
+        evm.database(CacheDB::new(EmptyDB::default()));
+
+        // Beacon root is missing, block fails before its transactions.
+        let block = BlockEnv {
+            number: U256::from(1),
+            ..Default::default()
+        };
+        let mut hooks = BlockHooks::default();
+        evm.inspect_block(block.clone(), &[], &[], &mut hooks)
+            .unwrap_err();
+        assert_eq!(hooks.calls, ["begin_block", "end_block"]);
+
+        // Transaction fails as caller can't pay for it.
+        let block = BlockEnv {
+            parent_beacon_block_root: Some(B256::ZERO),
+            ..block
+        };
+        let tx = TxEnv {
+            caller: Address::with_last_byte(0x10),
+            gas_limit: 21_000,
+            gas_price: U256::from(1),
+            ..Default::default()
+        };
+        let mut hooks = BlockHooks::default();
+        let (results, _) = evm
+            .inspect_block(block, &[tx], &[], &mut hooks)
+            .unwrap_err();
+        assert!(results.is_empty());
+        assert_eq!(hooks.calls, ["begin_block", "begin_tx", "end_block"]);
+        assert_eq!(hooks.block_gas_used, Some(0));
+    }
+
+    #[test]
+    fn start_transact_steps_through_frames() {
+        let caller = Address::with_last_byte(0x10);
+        let callee = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0x30 GAS CALL STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x30, 0x5a, 0xf1,
+            0x00,
+        ]));
+        db.insert_account_info(
+            callee,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        // PUSH1 1 POP STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x50, 0x00]));
+        db.insert_account_info(
+            Address::with_last_byte(0x30),
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(callee),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+
+        let mut handle = evm.start_transact().unwrap();
+        assert_eq!(handle.depth(), 1);
+        assert_eq!(handle.step_n(2), 2);
+        assert_eq!(handle.interpreter().unwrap().program_counter(), 4);
+
+        assert!(handle.run_until(Breakpoint::Depth(2)));
+        assert_eq!(handle.interpreter().unwrap().program_counter(), 0);
+        assert!(handle.run_until(Breakpoint::Opcode(opcode::POP)));
+        assert_eq!(handle.depth(), 2);
+
+        assert!(!handle.run_until(Breakpoint::Pc(100)));
+        assert!(handle.is_finished());
+        let output = handle.resume().unwrap();
+        assert!(output.result.is_success());
+
+        // Stepping gives the same output as regular execution.
+        assert_eq!(evm.transact().unwrap(), output);
+    }
+
+    #[test]
+    fn restored_snapshot_continues_execution() {
+        let caller = Address::with_last_byte(0x10);
+        let callee = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 0x2a PUSH1 0 MSTORE PUSH1 1 PUSH1 0 SSTORE
+        // PUSH1 0 PUSH1 0 PUSH1 0x20 PUSH1 0 PUSH1 0 PUSH1 0x30 GAS CALL STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00,
+            0x60, 0x20, 0x60, 0x00, 0x60, 0x00, 0x60, 0x30, 0x5a, 0xf1, 0x00,
+        ]));
+        db.insert_account_info(
+            callee,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        // PUSH1 0 CALLDATALOAD PUSH1 1 SSTORE STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x00, 0x35, 0x60, 0x01, 0x55, 0x00,
+        ]));
+        db.insert_account_info(
+            Address::with_last_byte(0x30),
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(callee),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        let expected = evm.transact().unwrap();
+
+        let mut handle = evm.start_transact().unwrap();
+        assert!(handle.run_until(Breakpoint::Depth(2)));
+        handle.step_n(2);
+        let snapshot = handle.snapshot();
+        assert_eq!(snapshot.depth(), 2);
+        assert_eq!(snapshot.program_counter(), Some(3));
+        assert_eq!(handle.resume().unwrap(), expected);
+
+        // Snapshot can be restored into a running execution and into a new one.
+        let mut handle = evm.start_transact().unwrap();
+        handle.step();
+        handle.restore(snapshot.clone());
+        assert_eq!(handle.depth(), 2);
+        assert_eq!(handle.resume().unwrap(), expected);
+
+        #[cfg(feature = "serde")]
+        let snapshot: ExecutionSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        evm.env = Env::default();
+        let handle = evm.restore_transact(snapshot);
+        assert_eq!(handle.resume().unwrap(), expected);
+    }
+
+    #[test]
+    fn warm_preloaded_accounts_and_slots_are_warm() {
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 0x30 BALANCE POP PUSH1 1 SLOAD POP STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x30, 0x31, 0x50, 0x60, 0x01, 0x54, 0x50, 0x00,
+        ]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx = TxEnv {
+            caller: Address::with_last_byte(0x10),
+            transact_to: TransactTo::Call(contract),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        let cold = evm.transact().unwrap().result.gas_used();
+
+        evm.env.cfg.warm_preloaded = vec![
+            (Address::with_last_byte(0x30), Vec::new()),
+            (contract, vec![U256::from(1)]),
+        ];
+        let warm = evm.transact().unwrap().result.gas_used();
+        // Preloads are not charged intrinsic gas like the access list.
+        assert_eq!(cold - warm, 2_500 + 2_000);
+    }
+
+    #[test]
+    fn access_summary_includes_reverted_reads() {
+        let caller = Address::with_last_byte(0x10);
+        let contract = Address::with_last_byte(0x20);
+        let reverting = Address::with_last_byte(0x30);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 1 SLOAD POP PUSH1 7 PUSH1 2 SSTORE
+        // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0x30 GAS CALL STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x54, 0x50, 0x60, 0x07, 0x60, 0x02, 0x55, 0x60, 0x00, 0x60, 0x00, 0x60,
+            0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x30, 0x5a, 0xf1, 0x00,
+        ]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        // PUSH1 1 PUSH1 5 SSTORE PUSH1 0 PUSH1 0 REVERT
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x60, 0x05, 0x55, 0x60, 0x00, 0x60, 0x00, 0xfd,
+        ]));
+        db.insert_account_info(
+            reverting,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(contract),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        let ResultAndState { state, access, .. } = evm.transact().unwrap();
+
+        assert!(!state[&reverting].storage.contains_key(&U256::from(5)));
+        assert!([caller, contract, reverting]
+            .iter()
+            .all(|address| access.accounts_read.contains(address)));
+        assert_eq!(
+            access.slots_read,
+            [
+                (contract, U256::from(1)),
+                (contract, U256::from(2)),
+                (reverting, U256::from(5))
+            ]
+            .into_iter()
+            .collect()
+        );
+        // Only the nonce of the caller changed, gas price is zero.
+        assert_eq!(access.accounts_written, [caller].into_iter().collect());
+        assert_eq!(
+            access.slots_written,
+            [(contract, U256::from(2))].into_iter().collect()
+        );
+    }
+
+    #[cfg(feature = "journal-metrics")]
+    #[test]
+    fn journal_metrics_count_loads_and_stores() {
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // PUSH1 7 PUSH1 2 SSTORE PUSH1 9 PUSH1 0 SSTORE PUSH1 0 PUSH1 1 SSTORE
+        // PUSH1 0 SLOAD POP PUSH1 0 PUSH1 0 PUSH1 0 CREATE POP STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x07, 0x60, 0x02, 0x55, 0x60, 0x09, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x01,
+            0x55, 0x60, 0x00, 0x54, 0x50, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x50, 0x00,
+        ]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        for (key, value) in [(0, 5), (1, 3)] {
+            db.insert_account_storage(contract, U256::from(key), U256::from(value))
+                .unwrap();
+        }
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::repeat_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 200_000;
+        let ResultAndState {
+            result,
+            journal_metrics,
+            ..
+        } = evm.transact().unwrap();
+
+        assert!(result.is_success());
+        let metrics = journal_metrics.unwrap();
+        assert_eq!(metrics.cold_storage_loads, 3);
+        assert_eq!(metrics.warm_storage_loads, 1);
+        assert_eq!(metrics.sstore_sets, 1);
+        assert_eq!(metrics.sstore_resets, 1);
+        assert_eq!(metrics.sstore_clears, 1);
+        assert_eq!(metrics.account_creations, 1);
+        assert!(metrics.cold_account_loads >= 3);
+        assert!(metrics.warm_account_loads > 0);
+    }
+
+    #[test]
+    fn disabled_state_clear_treats_touched_empty_accounts_as_existing() {
+        let contract = Address::with_last_byte(0x20);
+        let empty = Address::with_last_byte(0x40);
+        // CALL(GAS, 0x40, 1, 0, 0, 0, 0) STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x01, 0x60, 0x40, 0x5a, 0xf1,
+            0x00,
+        ]));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::from(1), 1, code.hash_slow(), code),
+        );
+        db.insert_account_info(empty, AccountInfo::default());
+
+        let gas_used = |disable_state_clear| {
+            let mut evm = EVM::new();
+            evm.database(db.clone());
+            evm.env.cfg.disable_state_clear = disable_state_clear;
+            evm.env.tx.caller = Address::with_last_byte(0x10);
+            evm.env.tx.transact_to = TransactTo::Call(contract);
+            evm.env.tx.gas_limit = 100_000;
+            let result = evm.transact().unwrap().result;
+            assert!(result.is_success());
+            result.gas_used()
+        };
+
+        // The empty account doesn't exist with state clearing, the value transfer pays for
+        // its creation.
+        assert_eq!(gas_used(false) - gas_used(true), 25_000);
+    }
+
+    #[test]
+    fn disabled_state_clear_keeps_touched_empty_accounts_in_state() {
+        let empty = Address::with_last_byte(0x40);
+        for (spec_id, disable_state_clear, kept) in [
+            (SpecId::LATEST, false, false),
+            (SpecId::LATEST, true, true),
+            (SpecId::HOMESTEAD, false, true),
+        ] {
+            let mut evm = EVM::new();
+            evm.database(State::builder().build());
+            evm.env.cfg.spec_id = spec_id;
+            evm.env.cfg.disable_state_clear = disable_state_clear;
+            evm.env.tx.caller = Address::with_last_byte(0x10);
+            evm.env.tx.transact_to = TransactTo::Call(empty);
+            let state_clear = evm.env.cfg.is_state_clear_enabled();
+            evm.db.as_mut().unwrap().set_state_clear_flag(state_clear);
+            assert!(evm.transact_commit().unwrap().is_success());
+
+            let account = &evm.db.as_ref().unwrap().cache.accounts[&empty];
+            assert_eq!(account.account.is_some(), kept);
+        }
+    }
+
+    #[test]
+    fn resume_profiled_samples_locations() {
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // JUMPDEST PUSH1 0 JUMP
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x5b, 0x60, 0x00, 0x56]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx = TxEnv {
+            caller: Address::with_last_byte(0x10),
+            transact_to: TransactTo::Call(contract),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+
+        let (output, profile) = evm.start_transact().unwrap().resume_profiled(7).unwrap();
+        assert!(output.result.is_halt());
+        assert_eq!(evm.transact().unwrap(), output);
+
+        // Loop costs 12 gas per 3 instructions.
+        assert_eq!(profile.total, (100_000 - 21_000) / 12 * 3 / 7);
+        assert_eq!(profile.samples.values().sum::<u64>(), profile.total);
+        let hottest = profile.hottest(3);
+        assert_eq!(hottest.len(), 3);
+        assert!(hottest
+            .iter()
+            .all(|((address, pc), _)| *address == contract && [0, 1, 3].contains(pc)));
+    }
+
+    #[test]
+    fn spec_is_set_from_schedule() {
+        let mut evm = EVM::<EmptyDB>::new();
+        evm.env.block.number = U256::from(19_426_587);
+        evm.env.block.timestamp = U256::from(1_710_338_123);
+        assert_eq!(
+            evm.set_spec_from_schedule(&HardforkSchedule::MAINNET),
+            SpecId::SHANGHAI
+        );
+
+        evm.env.block.timestamp = U256::from(1_710_338_135);
+        evm.set_spec_from_schedule(&HardforkSchedule::MAINNET);
+        assert_eq!(evm.env.cfg.spec_id, SpecId::CANCUN);
+    }
+
+    #[test]
+    fn transact_orderings_reports_conflicts() {
+        let contract = Address::with_last_byte(0x30);
+        let mut db = CacheDB::new(EmptyDB::default());
+        // Only the first caller succeeds:
+        // PUSH1 0 SLOAD ISZERO PUSH1 0x0b JUMPI PUSH1 0 DUP1 REVERT
+        // JUMPDEST PUSH1 1 PUSH1 0 SSTORE STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x00, 0x54, 0x15, 0x60, 0x0b, 0x57, 0x60, 0x00, 0x80, 0xfd, 0x5b, 0x60, 0x01,
+            0x60, 0x00, 0x55, 0x00,
+        ]));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let txs = [0x10, 0x11].map(|caller| {
+            let caller = Address::with_last_byte(caller);
+            db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+            TxEnv {
+                caller,
+                transact_to: TransactTo::Call(contract),
+                gas_limit: 100_000,
+                ..Default::default()
+            }
+        });
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        let outcomes = evm
+            .transact_orderings(&txs, &[vec![0, 1], vec![1, 0], vec![0, 1]])
+            .unwrap();
+
+        assert!(outcomes.iter().all(|outcome| outcome.error.is_none()));
+        assert!(outcomes[0].result_of(0).unwrap().is_success());
+        assert!(!outcomes[0].result_of(1).unwrap().is_success());
+        assert!(outcomes[1].result_of(1).unwrap().is_success());
+        assert!(outcomes[0].conflicts.is_empty());
+        assert_eq!(outcomes[1].conflicts, [1, 0]);
+        assert!(outcomes[2].conflicts.is_empty());
+
+        // Database is not changed.
+        let db = evm.db.as_ref().unwrap();
+        assert!(db.accounts[&contract].storage.is_empty());
+
+        assert_eq!(
+            evm.transact_orderings(&txs, &[vec![0, 1], vec![1, 2]]),
+            Err(InvalidOrder {
+                order: 1,
+                index: 2,
+                len: 2
+            })
+        );
+    }
+
+    #[test]
+    fn transact_ref_with_overlay_shares_db() {
+        let caller = Address::with_last_byte(0x10);
+        let callee = Address::with_last_byte(0x20);
+        let db = CacheDB::new(EmptyDB::default());
+
+        // Caller exists only in the overlay.
+        let mut overlay = StateOverlay::default();
+        overlay.insert(
+            caller,
+            AccountOverlay {
+                info: Some(AccountInfo::from_balance(U256::from(1_000_000))),
+                ..Default::default()
+            },
+        );
+
+        std::thread::scope(|scope| {
+            for value in [1, 2] {
+                let (db, overlay) = (&db, &overlay);
+                scope.spawn(move || {
+                    let mut evm = EVM::new();
+                    evm.database(db);
+                    evm.env.tx = TxEnv {
+                        caller,
+                        transact_to: TransactTo::Call(callee),
+                        value: U256::from(value),
+                        gas_limit: 21_000,
+                        ..Default::default()
+                    };
+                    let output = evm.transact_ref_with_overlay(overlay).unwrap();
+                    assert!(output.result.is_success());
+                    assert_eq!(output.state[&callee].info.balance, U256::from(value));
+                });
+            }
+        });
+
+        assert!(db.accounts.is_empty());
+    }
+
+    #[test]
+    fn transact_multiple_commits_between_txs() {
+        let caller = Address::with_last_byte(0x10);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+
+        let mut evm = EVM::new();
+        evm.database(db);
+
+        let txs = (0..2)
+            .map(|nonce| TxEnv {
+                caller,
+                transact_to: TransactTo::Call(Address::with_last_byte(0x20)),
+                value: U256::from(10),
+                gas_limit: 21_000,
+                nonce: Some(nonce),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let (results, summary) = evm.transact_multiple(&txs).unwrap();
+        assert_eq!(results.len(), 2);
+        // State is moved into the database instead of being returned.
+        assert!(results.iter().all(|r| r.state.is_empty()));
+        assert_eq!(summary.gas_used, 42_000);
+        assert_eq!(summary.cumulative_gas_used, vec![21_000, 42_000]);
+        assert_eq!(summary.successful, 2);
+
+        let db = evm.db.as_ref().unwrap();
+        assert_eq!(db.accounts[&caller].info.nonce, 2);
+
+        // Block gas limit is checked against the cumulative gas.
+        evm.env.block.gas_limit = U256::from(30_000);
+        let txs = [2, 3].map(|nonce| TxEnv {
+            nonce: Some(nonce),
+            ..txs[0].clone()
+        });
+        let (results, error) = evm.transact_multiple(&txs).unwrap_err();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].result.is_success());
+        assert_eq!(
+            error,
+            InvalidTransaction::CallerGasLimitMoreThanBlock.into()
+        );
+        assert_eq!(evm.db.as_ref().unwrap().accounts[&caller].info.nonce, 3);
+    }
+
+    #[test]
+    fn eof_code_is_executed_from_prague() {
+        // PUSH1 1, RJUMPI +1, STOP, CALLF 1, PUSH0, SSTORE, STOP | PUSH1 0x2a, RETF
+        let code = Bytecode::new_raw(Bytes::from_static(&hex!(
+            "ef0001010008020002000c00030300000000000002000100016001e1000100e300015f5500602ae4"
+        )));
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = Address::with_last_byte(0x10);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+
+        // Before Prague the container is executed as legacy code.
+        evm.env.cfg.spec_id = SpecId::CANCUN;
+        let result = evm.transact_ref().unwrap().result;
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: Halt::OpcodeNotFound,
+                ..
+            }
+        ));
+
+        evm.env.cfg.spec_id = SpecId::PRAGUE;
+        let ResultAndState { result, state, .. } = evm.transact_ref().unwrap();
+        assert!(result.is_success());
+        assert_eq!(
+            state[&contract].storage[&U256::ZERO].present_value(),
+            U256::from(0x2a)
+        );
+
+        // Init code with `PC`, which is not allowed in EOF.
+        evm.env.tx.transact_to = TransactTo::Create(CreateScheme::Create);
+        evm.env.tx.data = Bytes::from_static(&hex!("ef0001010004020001000203000000000000015800"));
+        let result = evm.transact_ref().unwrap().result;
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: Halt::InvalidEOFInitCode,
+                ..
+            }
+        ));
+    }
 }
@@ -1,51 +1,68 @@
 use crate::{
     db::Database,
+    handler::{CreateAddressHandle, TransferHandle},
     interpreter::{
-        analysis::to_analysed, gas, return_ok, CallInputs, Contract, CreateInputs, Gas,
-        InstructionResult, Interpreter, InterpreterResult, MAX_CODE_SIZE,
+        analysis::to_analysed, eof_validation::to_eof, gas, return_ok, AnalysisCache, CallInputs,
+        Contract, CreateInputs, Gas, InstructionResult, Interpreter, InterpreterResult, Transfer,
     },
-    journaled_state::JournaledState,
-    precompile::{Precompile, Precompiles},
+    precompile::{check_input_limits, Precompile, PrecompileProvider},
     primitives::{
-        keccak256, Address, AnalysisKind, Bytecode, Bytes, EVMError, Env, Spec, SpecId::*, B256,
-        U256,
+        is_eof, keccak256, Address, AnalysisKind, Bytecode, Bytes, EVMError, Env, HashMap, Spec,
+        SpecId, SpecId::*, TxFees, B256, HISTORY_SERVE_WINDOW, HISTORY_STORAGE_ADDRESS, U256,
     },
-    CallStackFrame, CALL_STACK_LIMIT,
+    CallStackFrame, ContextPrecompile, Journal, JournalCheckpoint, JournaledState,
+    CALL_STACK_LIMIT,
 };
-use alloc::boxed::Box;
+use alloc::{boxed::Box, sync::Arc};
 use core::ops::Range;
 
 /// EVM Data contains all the data that EVM needs to execute.
 #[derive(Debug)]
-pub struct EvmContext<'a, DB: Database> {
+pub struct EvmContext<'a, DB: Database, J: Journal = JournaledState, EXT = ()> {
     /// EVM Environment contains all the information about config, block and transaction that
     /// evm needs.
     pub env: &'a mut Env,
     /// EVM State with journaling support.
-    pub journaled_state: JournaledState,
+    pub journaled_state: J,
     /// Database to load data from.
     pub db: &'a mut DB,
     /// Error that happened during execution.
     pub error: Option<DB::Error>,
-    /// Precompiles that are available for evm.
-    pub precompiles: Precompiles,
+    /// Precompiles that are available for evm, see [crate::Handler::precompiles].
+    pub precompiles: Arc<dyn PrecompileProvider>,
+    /// Applies the value transfers of calls and creates, see [crate::Handler::transfer].
+    pub transfer: TransferHandle<DB, J, EXT>,
+    /// Derives the addresses of created contracts, see [crate::Handler::create_address].
+    pub create_address: CreateAddressHandle,
+    /// Precompiles with access to the context, they take precedence over [Self::precompiles].
+    /// See [crate::EVMImpl::set_context_precompile].
+    pub context_precompiles: HashMap<Address, ContextPrecompile<DB, J, EXT>>,
+    /// Fees charged to the caller of the current transaction, reported in
+    /// [ResultAndState::fees](crate::primitives::ResultAndState::fees). Chain specific fees are
+    /// added by [crate::Handler::deduct_caller].
+    pub fees: TxFees,
     /// Used as temporary value holder to store L1 block info.
     #[cfg(feature = "optimism")]
     pub l1_block_info: Option<crate::optimism::L1BlockInfo>,
+    /// Context of the embedding, e.g. ledgers kept next to the state. It is accessible to
+    /// handlers, inspectors and context precompiles, see [crate::EVMImpl::new_with_external].
+    pub external: EXT,
+    /// Analysed bytecode of the called contracts, reused between calls and transactions.
+    pub analysis_cache: AnalysisCache,
 }
 
-impl<'a, DB: Database> EvmContext<'a, DB> {
-    /// Load access list for berlin hard fork.
+impl<'a, DB: Database, J: Journal, EXT> EvmContext<'a, DB, J, EXT> {
+    /// Load access list for berlin hard fork, together with [crate::primitives::CfgEnv::warm_preloaded].
     ///
     /// Loading of accounts/storages is needed to make them warm.
     #[inline]
     pub fn load_access_list(&mut self) -> Result<(), EVMError<DB::Error>> {
-        for (address, slots) in self.env.tx.access_list.iter() {
-            self.journaled_state
-                .initial_account_load(*address, slots, self.db)
-                .map_err(EVMError::Database)?;
-        }
-        Ok(())
+        self.journaled_state
+            .load_warm(&self.env.cfg.warm_preloaded, self.db)
+            .map_err(EVMError::Database)?;
+        self.journaled_state
+            .load_warm(&self.env.tx.access_list, self.db)
+            .map_err(EVMError::Database)
     }
 
     /// Return environment.
@@ -53,14 +70,92 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
         self.env
     }
 
+    /// Returns the current journal depth.
+    ///
+    /// Depth is increased by every call, create and [`EvmContext::checkpoint`].
+    #[inline]
+    pub fn depth(&self) -> u64 {
+        self.journaled_state.depth()
+    }
+
+    /// Creates a journal checkpoint that changes made after it can be reverted to.
+    ///
+    /// This is intended for custom instructions that create sub-scopes. Invariants that
+    /// need to hold:
+    /// * Every checkpoint needs to be either committed with [`EvmContext::checkpoint_commit`]
+    ///   or reverted with [`EvmContext::checkpoint_revert`] before the instruction returns.
+    /// * Checkpoints are resolved in LIFO order, the last created checkpoint is resolved first.
+    /// * Reverting a checkpoint discards all checkpoints that were created after it.
+    ///
+    /// Prefer [`EvmContext::with_checkpoint`] which upholds these invariants.
+    #[inline]
+    pub fn checkpoint(&mut self) -> JournalCheckpoint {
+        self.journaled_state.checkpoint()
+    }
+
+    /// Commits the last checkpoint, changes made after it are kept.
+    #[inline]
+    pub fn checkpoint_commit(&mut self) {
+        self.journaled_state.checkpoint_commit()
+    }
+
+    /// Reverts all changes made after the given checkpoint.
+    #[inline]
+    pub fn checkpoint_revert(&mut self, checkpoint: JournalCheckpoint) {
+        self.journaled_state.checkpoint_revert(checkpoint)
+    }
+
+    /// Runs `f` inside a new checkpoint.
+    ///
+    /// Changes are committed if `f` returns `Ok` and reverted if it returns `Err`.
+    /// Returns [`InstructionResult::CallTooDeep`] without calling `f` if the depth limit is reached.
+    pub fn with_checkpoint<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, InstructionResult>,
+    ) -> Result<T, InstructionResult> {
+        if self.depth() > CALL_STACK_LIMIT {
+            return Err(InstructionResult::CallTooDeep);
+        }
+        let checkpoint = self.checkpoint();
+        let depth = self.depth();
+        let result = f(self);
+        debug_assert_eq!(depth, self.depth(), "unresolved checkpoint inside of scope");
+        match result {
+            Ok(_) => self.checkpoint_commit(),
+            Err(_) => self.checkpoint_revert(checkpoint),
+        }
+        result
+    }
+
     /// Fetch block hash from database.
+    ///
+    /// If [crate::primitives::CfgEnv::blockhash_from_history_storage] is set, block hashes are
+    /// read through the journal from the storage of the EIP-2935 history contract instead of
+    /// [Database::block_hash].
     pub fn block_hash(&mut self, number: U256) -> Option<B256> {
+        if self.env.cfg.blockhash_from_history_storage {
+            let index = number % U256::from(HISTORY_SERVE_WINDOW);
+            return self
+                .history_storage(index)
+                .map(|value| B256::from(value.to_be_bytes()))
+                .map_err(|e| self.error = Some(e))
+                .ok();
+        }
         self.db
             .block_hash(number)
             .map_err(|e| self.error = Some(e))
             .ok()
     }
 
+    /// Loads the slot of the history storage contract at `index`.
+    fn history_storage(&mut self, index: U256) -> Result<U256, DB::Error> {
+        self.journaled_state
+            .load_account(HISTORY_STORAGE_ADDRESS, self.db)?;
+        self.journaled_state
+            .sload(HISTORY_STORAGE_ADDRESS, index, self.db)
+            .map(|(value, _)| value)
+    }
+
     /// Load account and return flags (is_cold, exists)
     pub fn load_account(&mut self, address: Address) -> Option<(bool, bool)> {
         self.journaled_state
@@ -78,6 +173,22 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
             .map(|(acc, is_cold)| (acc.info.balance, is_cold))
     }
 
+    /// Mints `amount` to the account, see [JournaledState::mint].
+    pub fn mint(&mut self, address: Address, amount: U256) -> Option<bool> {
+        self.journaled_state
+            .mint(address, amount, self.db)
+            .map_err(|e| self.error = Some(e))
+            .ok()
+    }
+
+    /// Burns `amount` from the account, see [JournaledState::burn].
+    pub fn burn(&mut self, address: Address, amount: U256) -> Option<bool> {
+        self.journaled_state
+            .burn(address, amount, self.db)
+            .map_err(|e| self.error = Some(e))
+            .ok()
+    }
+
     /// Return account code and if address is cold loaded.
     pub fn code(&mut self, address: Address) -> Option<(Bytecode, bool)> {
         let (acc, is_cold) = self
@@ -147,6 +258,7 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
                 result: e,
                 gas,
                 output: Bytes::new(),
+                peak_memory: 0,
             })
         };
 
@@ -175,7 +287,7 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
 
         // Create address
         let code_hash = keccak256(&inputs.init_code);
-        let created_address = inputs.created_address_with_hash(old_nonce, &code_hash);
+        let created_address = (self.create_address)(self.env, inputs, old_nonce, &code_hash);
 
         // Load account so it needs to be marked as warm for access list.
         if self
@@ -187,11 +299,11 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
             return return_error(InstructionResult::FatalExternalError);
         }
 
-        // create account, transfer funds and make the journal checkpoint.
+        // create account and make the journal checkpoint.
         let checkpoint = match self.journaled_state.create_account_checkpoint::<SPEC>(
             inputs.caller,
             created_address,
-            inputs.value,
+            U256::ZERO,
         ) {
             Ok(checkpoint) => checkpoint,
             Err(e) => {
@@ -199,7 +311,30 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
             }
         };
 
-        let bytecode = Bytecode::new_raw(inputs.init_code.clone());
+        // transfer funds to the created account.
+        let transfer = self.transfer;
+        let value_transfer = Transfer {
+            source: inputs.caller,
+            target: created_address,
+            value: inputs.value,
+        };
+        if let Err(e) = transfer(self, &value_transfer) {
+            self.journaled_state.checkpoint_revert(checkpoint);
+            return return_error(e);
+        }
+
+        let bytecode = if SPEC::enabled(PRAGUE) && is_eof(&inputs.init_code) {
+            // EIP-3540: EOF init code must be valid.
+            match to_eof(inputs.init_code.clone()) {
+                Ok(bytecode) => bytecode,
+                Err(_) => {
+                    self.journaled_state.checkpoint_revert(checkpoint);
+                    return return_error(InstructionResult::InvalidEOFInitCode);
+                }
+            }
+        } else {
+            Bytecode::new_raw(inputs.init_code.clone())
+        };
 
         let contract = Box::new(Contract::new(
             Bytes::new(),
@@ -219,6 +354,33 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
         }))
     }
 
+    /// Returns the analysed bytecode from [Self::analysis_cache] or analyses and caches it.
+    fn analysed_bytecode(&mut self, code_hash: B256, mut bytecode: Bytecode) -> Bytecode {
+        if bytecode.is_empty() {
+            return bytecode;
+        }
+        let eof_enabled = SpecId::enabled(self.journaled_state.spec(), PRAGUE);
+        if let Some(cached) = self.analysis_cache.get(&code_hash) {
+            // Cache is shared by specs, EOF code is decoded only from Prague.
+            let is_valid = match cached.eof() {
+                Some(_) => eof_enabled,
+                None => !eof_enabled || !is_eof(cached.original_bytes().as_ref()),
+            };
+            if is_valid {
+                return cached.clone();
+            }
+        }
+        // EOF code is stored raw and is decoded when called.
+        if eof_enabled && bytecode.eof().is_none() && is_eof(bytecode.original_bytes().as_ref()) {
+            if let Ok(eof) = to_eof(bytecode.original_bytes()) {
+                bytecode = eof;
+            }
+        }
+        let bytecode = to_analysed(bytecode);
+        self.analysis_cache.insert(code_hash, bytecode.clone());
+        bytecode
+    }
+
     /// Make call frame
     pub fn make_call_frame(
         &mut self,
@@ -232,6 +394,7 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
                 result: instruction_result,
                 gas,
                 output: Bytes::new(),
+                peak_memory: 0,
             })
         };
 
@@ -260,18 +423,14 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
         }
 
         // Transfer value from caller to called account
-        if let Err(e) = self.journaled_state.transfer(
-            &inputs.transfer.source,
-            &inputs.transfer.target,
-            inputs.transfer.value,
-            self.db,
-        ) {
+        let transfer = self.transfer;
+        if let Err(e) = transfer(self, &inputs.transfer) {
             //println!("transfer error");
             self.journaled_state.checkpoint_revert(checkpoint);
             return return_result(e);
         }
 
-        if let Some(precompile) = self.precompiles.get(&inputs.contract) {
+        if let Some(precompile) = self.precompile(&inputs.contract) {
             //println!("Call precompile");
             let result = self.call_precompile(precompile, inputs, gas);
             if matches!(result.result, return_ok!()) {
@@ -281,6 +440,8 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
             }
             Err(result)
         } else if !bytecode.is_empty() {
+            // Plain value transfers don't need analysed bytecode nor interpreter.
+            let bytecode = self.analysed_bytecode(code_hash, bytecode);
             let contract = Box::new(Contract::new_with_context(
                 inputs.input.clone(),
                 bytecode,
@@ -301,29 +462,79 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
         }
     }
 
+    /// Returns the precompile at the given address, context precompiles first.
+    fn precompile(&self, address: &Address) -> Option<ContextPrecompile<DB, J, EXT>> {
+        match self.context_precompiles.get(address) {
+            Some(precompile) => Some(precompile.clone()),
+            None => self
+                .precompiles
+                .get(address)
+                .map(ContextPrecompile::Ordinary),
+        }
+    }
+
     /// Call precompile contract
     fn call_precompile(
         &mut self,
-        precompile: Precompile,
+        precompile: ContextPrecompile<DB, J, EXT>,
         inputs: &CallInputs,
         gas: Gas,
     ) -> InterpreterResult {
         let input_data = &inputs.input;
 
+        // Oversized inputs are rejected before the precompile computes their gas cost.
+        let limits = &self.env.cfg.precompile_input_limits;
+        if check_input_limits(limits, &inputs.contract, input_data).is_err() {
+            return InterpreterResult {
+                result: InstructionResult::PrecompileInputTooLarge,
+                gas,
+                output: Bytes::new(),
+                peak_memory: 0,
+            };
+        }
+
+        // Overridden cost is charged instead of the cost computed by the precompile.
+        let gas_override = self
+            .env
+            .cfg
+            .precompile_gas_overrides
+            .get(&inputs.contract)
+            .map(|cost| cost.cost(input_data.len()));
+        let gas_limit = match gas_override {
+            Some(cost) if cost > gas.limit() => {
+                return InterpreterResult {
+                    result: InstructionResult::PrecompileOOG,
+                    gas,
+                    output: Bytes::new(),
+                    peak_memory: 0,
+                };
+            }
+            Some(_) => u64::MAX,
+            None => gas.limit(),
+        };
+
+        // Context precompiles are called inside the checkpoint of the call, their changes are
+        // reverted with it.
         let out = match precompile {
-            Precompile::Standard(fun) => fun(input_data, gas.limit()),
-            Precompile::Env(fun) => fun(input_data, gas.limit(), self.env()),
+            ContextPrecompile::Ordinary(Precompile::Standard(fun)) => fun(input_data, gas_limit),
+            ContextPrecompile::Ordinary(Precompile::Env(fun)) => {
+                fun(input_data, gas_limit, self.env())
+            }
+            ContextPrecompile::ContextStateful(precompile) => {
+                precompile.call(input_data, gas_limit, self)
+            }
         };
 
         let mut result = InterpreterResult {
             result: InstructionResult::Return,
             gas,
             output: Bytes::new(),
+            peak_memory: 0,
         };
 
         match out {
             Ok((gas_used, data)) => {
-                if result.gas.record_cost(gas_used) {
+                if result.gas.record_cost(gas_override.unwrap_or(gas_used)) {
                     result.result = InstructionResult::Return;
                     result.output = Bytes::from(data);
                 } else {
@@ -331,7 +542,10 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
                 }
             }
             Err(e) => {
-                result.result = if crate::precompile::Error::OutOfGas == e {
+                result.result = if self.error.is_some() {
+                    // Database error of a context precompile.
+                    InstructionResult::FatalExternalError
+                } else if crate::precompile::Error::OutOfGas == e {
                     InstructionResult::PrecompileOOG
                 } else {
                     InstructionResult::PrecompileError
@@ -373,8 +587,23 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
         // Host error if present on execution
         // if ok, check contract creation limit and calculate gas deduction on output len.
         //
+        // EIP-3540: EOF init code must deploy valid EOF code.
+        let eof_bytecode = if frame.interpreter.contract.bytecode.eof().is_some() {
+            match to_eof(interpreter_result.output.clone()) {
+                Ok(bytecode) => Some(bytecode),
+                Err(_) => {
+                    self.journaled_state.checkpoint_revert(frame.checkpoint);
+                    interpreter_result.result = InstructionResult::InvalidEOFInitCode;
+                    return (interpreter_result, address);
+                }
+            }
+        } else {
+            None
+        };
+
         // EIP-3541: Reject new contract code starting with the 0xEF byte
-        if SPEC::enabled(LONDON)
+        if eof_bytecode.is_none()
+            && SPEC::enabled(LONDON)
             && !interpreter_result.output.is_empty()
             && interpreter_result.output.first() == Some(&0xEF)
         {
@@ -386,12 +615,11 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
         // EIP-170: Contract code size limit
         // By default limit is 0x6000 (~25kb)
         if SPEC::enabled(SPURIOUS_DRAGON)
-            && interpreter_result.output.len()
-                > self
-                    .env
-                    .cfg
-                    .limit_contract_code_size
-                    .unwrap_or(MAX_CODE_SIZE)
+            && self
+                .env
+                .cfg
+                .max_code_size()
+                .is_some_and(|limit| interpreter_result.output.len() > limit)
         {
             self.journaled_state.checkpoint_revert(frame.checkpoint);
             interpreter_result.result = InstructionResult::CreateContractSizeLimit;
@@ -414,13 +642,14 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
         // if we have enough gas we can commit changes.
         self.journaled_state.checkpoint_commit();
 
-        // Do analysis of bytecode straight away.
-        let bytecode = match self.env.cfg.perf_analyse_created_bytecodes {
-            AnalysisKind::Raw => Bytecode::new_raw(interpreter_result.output.clone()),
-            AnalysisKind::Check => {
+        // Do analysis of bytecode straight away. EOF code is already validated.
+        let bytecode = match (eof_bytecode, &self.env.cfg.perf_analyse_created_bytecodes) {
+            (Some(bytecode), _) => bytecode,
+            (None, AnalysisKind::Raw) => Bytecode::new_raw(interpreter_result.output.clone()),
+            (None, AnalysisKind::Check) => {
                 Bytecode::new_raw(interpreter_result.output.clone()).to_checked()
             }
-            AnalysisKind::Analyse => {
+            (None, AnalysisKind::Analyse) => {
                 to_analysed(Bytecode::new_raw(interpreter_result.output.clone()))
             }
         };
@@ -432,3 +661,148 @@ impl<'a, DB: Database> EvmContext<'a, DB> {
         (interpreter_result, address)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{AccountInfo, ExecutionResult, TransactTo},
+        Inspector, EVM,
+    };
+
+    #[test]
+    fn with_checkpoint_reverts_on_error() {
+        let address = Address::with_last_byte(0x10);
+        let mut env = Env::default();
+        let mut db = EmptyDB::default();
+        let mut context = EvmContext {
+            env: &mut env,
+            journaled_state: JournaledState::new(LATEST, vec![]),
+            db: &mut db,
+            error: None,
+            precompiles: Arc::new(crate::precompile::Precompiles::default()),
+            transfer: crate::handler::mainnet::transfer,
+            create_address: crate::handler::mainnet::create_address,
+            context_precompiles: HashMap::new(),
+            fees: TxFees::default(),
+            #[cfg(feature = "optimism")]
+            l1_block_info: None,
+            external: (),
+            analysis_cache: AnalysisCache::new(),
+        };
+        context.load_account(address);
+
+        let result = context.with_checkpoint(|context| {
+            context.sstore(address, U256::from(1), U256::from(1));
+            Err::<(), _>(InstructionResult::Revert)
+        });
+        assert_eq!(result, Err(InstructionResult::Revert));
+        assert_eq!(context.depth(), 0);
+        assert_eq!(
+            context.sload(address, U256::from(1)),
+            Some((U256::ZERO, true))
+        );
+
+        let result = context.with_checkpoint(|context| {
+            context.sstore(address, U256::from(1), U256::from(2));
+            Ok(context.depth())
+        });
+        assert_eq!(result, Ok(1));
+        assert_eq!(context.depth(), 0);
+        assert_eq!(
+            context.sload(address, U256::from(1)),
+            Some((U256::from(2), false))
+        );
+    }
+
+    #[test]
+    fn mint_and_burn_are_reverted() {
+        let address = Address::with_last_byte(0x10);
+        let mut env = Env::default();
+        let mut db = EmptyDB::default();
+        let mut context = EvmContext {
+            env: &mut env,
+            journaled_state: JournaledState::new(LATEST, vec![]),
+            db: &mut db,
+            error: None,
+            precompiles: Arc::new(crate::precompile::Precompiles::default()),
+            transfer: crate::handler::mainnet::transfer,
+            create_address: crate::handler::mainnet::create_address,
+            context_precompiles: HashMap::new(),
+            fees: TxFees::default(),
+            #[cfg(feature = "optimism")]
+            l1_block_info: None,
+            external: (),
+            analysis_cache: AnalysisCache::new(),
+        };
+        assert_eq!(context.mint(address, U256::from(10)), Some(true));
+
+        let _ = context.with_checkpoint(|context| {
+            assert_eq!(context.burn(address, U256::from(11)), Some(false));
+            assert_eq!(context.burn(address, U256::from(4)), Some(true));
+            assert_eq!(context.mint(address, U256::MAX), Some(false));
+            assert_eq!(context.balance(address), Some((U256::from(6), false)));
+            Err::<(), _>(InstructionResult::Revert)
+        });
+        assert_eq!(context.balance(address), Some((U256::from(10), false)));
+        assert!(context.journaled_state.state[&address].is_touched());
+    }
+
+    #[test]
+    fn value_transfer_skips_interpreter() {
+        #[derive(Default)]
+        struct StepCounter {
+            steps: usize,
+        }
+
+        impl<DB: Database> Inspector<DB> for StepCounter {
+            fn step(&mut self, _interp: &mut Interpreter, _context: &mut EvmContext<'_, DB>) {
+                self.steps += 1;
+            }
+        }
+
+        let caller = Address::with_last_byte(0x10);
+        let target = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = caller;
+        evm.env.tx.transact_to = TransactTo::Call(target);
+        evm.env.tx.value = U256::from(100);
+        evm.env.tx.gas_limit = 21_000;
+
+        let mut inspector = StepCounter::default();
+        let result = evm.inspect_commit(&mut inspector).unwrap();
+        assert!(matches!(result, ExecutionResult::Success { .. }));
+        assert_eq!(result.gas_used(), 21_000);
+        // No interpreter was created and the empty code was not analysed.
+        assert_eq!(inspector.steps, 0);
+        assert!(evm.analysis_cache.is_empty());
+        let db = evm.db.as_mut().unwrap();
+        assert_eq!(db.accounts[&target].info.balance, U256::from(100));
+        assert_eq!(db.accounts[&caller].info.balance, U256::from(999_900));
+        assert_eq!(db.accounts[&caller].info.nonce, 1);
+
+        // Calls to contracts still go through the interpreter.
+        // PUSH1 0 PUSH1 0 REVERT
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xfd]));
+        db.insert_account_info(
+            target,
+            AccountInfo {
+                balance: U256::from(100),
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                nonce: 0,
+            },
+        );
+        evm.env.tx.gas_limit = 30_000;
+        let mut inspector = StepCounter::default();
+        let result = evm.inspect_commit(&mut inspector).unwrap();
+        assert!(matches!(result, ExecutionResult::Revert { .. }));
+        assert_eq!(inspector.steps, 3);
+        assert_eq!(evm.analysis_cache.len(), 1);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    interpreter::{AnalysisCache, SharedMemoryPool},
+    primitives::{db::Database, Env, Spec},
+    EVMImpl, Handler, Inspector, TransactExt,
+};
+use core::{
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+};
+
+/// Reusable setup of the EVMs of a spec.
+///
+/// Holds the handler, and the shared memories and analysed bytecode that are reused between
+/// transactions. [EvmFactory::execution] creates a cheap [Execution] per transaction that borrows
+/// the database and the environment, and gives the memories and bytecode back when dropped.
+///
+/// Instruction table of the spec is a compile time constant and is not rebuilt. Tables wrapped
+/// for an inspector or gas-free execution are still built per execution.
+pub struct EvmFactory<SPEC: Spec, DB: Database> {
+    handler: Handler<DB>,
+    memory_pool: SharedMemoryPool,
+    analysis_cache: AnalysisCache,
+    _spec: PhantomData<SPEC>,
+}
+
+impl<SPEC: Spec + 'static, DB: Database> Default for EvmFactory<SPEC, DB> {
+    fn default() -> Self {
+        Self::new(Handler::mainnet::<SPEC>())
+    }
+}
+
+impl<SPEC: Spec + 'static, DB: Database> EvmFactory<SPEC, DB> {
+    /// Creates factory of EVMs with the given handler.
+    pub fn new(handler: Handler<DB>) -> Self {
+        Self {
+            handler,
+            memory_pool: SharedMemoryPool::new(),
+            analysis_cache: AnalysisCache::new(),
+            _spec: PhantomData,
+        }
+    }
+
+    /// Handler of the created executions.
+    pub fn handler(&mut self) -> &mut Handler<DB> {
+        &mut self.handler
+    }
+
+    /// Shared memories reused by the executions, see [TransactExt::memory_pool].
+    pub fn memory_pool(&mut self) -> &mut SharedMemoryPool {
+        &mut self.memory_pool
+    }
+
+    /// Analysed bytecode reused by the executions, see [TransactExt::analysis_cache].
+    pub fn analysis_cache(&mut self) -> &mut AnalysisCache {
+        &mut self.analysis_cache
+    }
+
+    /// Creates an execution of the transaction in `env` on `db`.
+    pub fn execution<'a>(
+        &'a mut self,
+        db: &'a mut DB,
+        env: &'a mut Env,
+        inspector: Option<&'a mut dyn Inspector<DB>>,
+    ) -> Execution<'a, SPEC, DB> {
+        let mut evm = EVMImpl::new_with_handler(db, env, inspector, self.handler.clone());
+        mem::swap(evm.memory_pool(), &mut self.memory_pool);
+        mem::swap(evm.analysis_cache(), &mut self.analysis_cache);
+        Execution { evm, factory: self }
+    }
+}
+
+/// EVM of a single transaction created by [EvmFactory::execution].
+pub struct Execution<'a, SPEC: Spec + 'static, DB: Database> {
+    evm: EVMImpl<'a, SPEC, DB>,
+    factory: &'a mut EvmFactory<SPEC, DB>,
+}
+
+impl<'a, SPEC: Spec + 'static, DB: Database> Deref for Execution<'a, SPEC, DB> {
+    type Target = EVMImpl<'a, SPEC, DB>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.evm
+    }
+}
+
+impl<SPEC: Spec + 'static, DB: Database> DerefMut for Execution<'_, SPEC, DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.evm
+    }
+}
+
+impl<SPEC: Spec + 'static, DB: Database> Drop for Execution<'_, SPEC, DB> {
+    fn drop(&mut self) {
+        mem::swap(self.evm.memory_pool(), &mut self.factory.memory_pool);
+        mem::swap(self.evm.analysis_cache(), &mut self.factory.analysis_cache);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::bytecode,
+        primitives::{AccountInfo, Address, LatestSpec, TransactTo, U256},
+        Transact,
+    };
+
+    #[test]
+    fn executions_share_factory_setup() {
+        let contract = Address::with_last_byte(0x20);
+        let mut db = CacheDB::new(EmptyDB::default());
+        let code = bytecode![push(0), push(0), SSTORE];
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(contract, U256::ZERO, U256::from(1))
+            .unwrap();
+
+        let mut factory = EvmFactory::<LatestSpec, _>::default();
+        factory.handler().calculate_gas_refund = |_, _| 0;
+
+        for _ in 0..2 {
+            let mut env = Env::default();
+            env.tx.caller = Address::with_last_byte(0x10);
+            env.tx.transact_to = TransactTo::Call(contract);
+            env.tx.gas_limit = 100_000;
+            let mut execution = factory.execution(&mut db, &mut env, None);
+            let output = execution.transact().unwrap();
+            assert_eq!(output.result.gas_refunded(), 0);
+            drop(execution);
+            assert_eq!(factory.analysis_cache().len(), 1);
+        }
+        assert_eq!(factory.memory_pool().len(), 1);
+    }
+}
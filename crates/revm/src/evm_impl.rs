@@ -1,43 +1,59 @@
 use crate::{
     db::Database,
-    handler::Handler,
+    execution_handle::FramesSnapshot,
+    handler::{FirstFrameInputs, GasAccounting, Handler},
     inspector_instruction,
     interpreter::{
-        gas::initial_tx_gas,
-        opcode::{make_boxed_instruction_table, make_instruction_table, InstructionTables},
-        CallContext, CallInputs, CallScheme, CreateInputs, Host, Interpreter, InterpreterAction,
-        InterpreterResult, SelfDestructResult, SharedMemory, Transfer,
+        gas::{calculate_intrinsic_gas, initial_tx_gas},
+        instructions::control,
+        opcode::{
+            gas_free_instruction, make_boxed_instruction_table, make_instruction_table,
+            InstructionTable, InstructionTables,
+        },
+        AnalysisCache, CallInputs, CreateInputs, Host, InstructionResult, Interpreter,
+        InterpreterAction, InterpreterResult, SelfDestructResult, SharedMemory, SharedMemoryPool,
+        EMPTY_SHARED_MEMORY,
     },
     journaled_state::JournaledState,
-    precompile::Precompiles,
+    precompile::PrecompileProvider,
     primitives::{
-        specification, Address, Bytecode, Bytes, EVMError, EVMResult, Env, InvalidTransaction, Log,
-        Output, Spec, SpecId::*, TransactTo, B256, U256,
+        specification, Address, Bytecode, Bytes, EVMError, EVMResult, Env, HaltSnapshot, HashMap,
+        Log, Output, ResultAndState, Spec, SpecId::*, TransactTo, TxFees, B256, U256,
     },
-    CallStackFrame, EvmContext, Inspector,
+    CallStackFrame, ChainSpec, ContextPrecompile, EvmContext, ExecutionFrames, ExecutionSnapshot,
+    Inspector, Journal,
 };
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
 use auto_impl::auto_impl;
 use core::{fmt, marker::PhantomData, ops::Range};
 
-#[cfg(feature = "optimism")]
-use crate::optimism;
+#[cfg(feature = "instruction-count")]
+use crate::primitives::InstructionCounts;
 
 /// EVM call stack limit.
 pub const CALL_STACK_LIMIT: u64 = 1024;
 
-pub struct EVMImpl<'a, SPEC: Spec, DB: Database> {
-    pub context: EvmContext<'a, DB>,
-    pub inspector: Option<&'a mut dyn Inspector<DB>>,
+pub struct EVMImpl<'a, SPEC: Spec, DB: Database, J: Journal = JournaledState, EXT = ()> {
+    pub context: EvmContext<'a, DB, J, EXT>,
+    pub inspector: Option<&'a mut dyn Inspector<DB, J, EXT>>,
     pub instruction_table: InstructionTables<'a, Self>,
-    pub handler: Handler<DB>,
+    pub handler: Handler<DB, J, EXT>,
+    /// Snapshot of the first frame if it halted, see [crate::primitives::CfgEnv::halt_snapshot].
+    halt_snapshot: Option<HaltSnapshot>,
+    /// Instructions executed by the frames of the transaction.
+    #[cfg(feature = "instruction-count")]
+    instruction_counts: InstructionCounts,
+    /// Shared memories reused by the executions, see [TransactExt::memory_pool].
+    memory_pool: SharedMemoryPool,
     _phantomdata: PhantomData<SPEC>,
 }
 
-impl<SPEC, DB> fmt::Debug for EVMImpl<'_, SPEC, DB>
+impl<SPEC, DB, J, EXT> fmt::Debug for EVMImpl<'_, SPEC, DB, J, EXT>
 where
     SPEC: Spec,
     DB: Database + fmt::Debug,
+    J: Journal,
+    EXT: fmt::Debug,
     DB::Error: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -47,91 +63,148 @@ where
     }
 }
 
-#[cfg(feature = "optimism")]
-impl<'a, SPEC: Spec, DB: Database> EVMImpl<'a, SPEC, DB> {
-    /// If the transaction is not a deposit transaction, subtract the L1 data fee from the
-    /// caller's balance directly after minting the requested amount of ETH.
-    fn remove_l1_cost(
-        is_deposit: bool,
-        tx_caller: Address,
-        l1_cost: U256,
-        db: &mut DB,
-        journal: &mut JournaledState,
-    ) -> Result<(), EVMError<DB::Error>> {
-        if is_deposit {
-            return Ok(());
-        }
-        let acc = journal
-            .load_account(tx_caller, db)
-            .map_err(EVMError::Database)?
-            .0;
-        if l1_cost.gt(&acc.info.balance) {
-            return Err(EVMError::Transaction(
-                InvalidTransaction::LackOfFundForMaxFee {
-                    fee: Box::new(l1_cost),
-                    balance: Box::new(acc.info.balance),
-                },
-            ));
-        }
-        acc.info.balance = acc.info.balance.saturating_sub(l1_cost);
-        Ok(())
-    }
-
-    /// If the transaction is a deposit with a `mint` value, add the mint value
-    /// in wei to the caller's balance. This should be persisted to the database
-    /// prior to the rest of execution.
-    fn commit_mint_value(
-        tx_caller: Address,
-        tx_mint: Option<u128>,
-        db: &mut DB,
-        journal: &mut JournaledState,
-    ) -> Result<(), EVMError<DB::Error>> {
-        if let Some(mint) = tx_mint {
-            journal
-                .load_account(tx_caller, db)
-                .map_err(EVMError::Database)?
-                .0
-                .info
-                .balance += U256::from(mint);
-            journal.checkpoint();
-        }
-        Ok(())
+impl<'a, SPEC: Spec + 'static, DB: Database, J: Journal> EVMImpl<'a, SPEC, DB, J> {
+    pub fn new_with_spec(
+        db: &'a mut DB,
+        env: &'a mut Env,
+        inspector: Option<&'a mut dyn Inspector<DB, J>>,
+    ) -> Self {
+        let spec_id = env.cfg.spec_id;
+        Self::new_with_chain_spec(db, env, inspector, spec_id)
+    }
+
+    /// Creates EVM like [EVMImpl::new_with_spec] with the handler of the chain's fork. `SPEC` is
+    /// expected to be the spec of [ChainSpec::base_spec_id].
+    pub fn new_with_chain_spec<C: ChainSpec>(
+        db: &'a mut DB,
+        env: &'a mut Env,
+        inspector: Option<&'a mut dyn Inspector<DB, J>>,
+        chain_spec: C,
+    ) -> Self {
+        let handler = chain_spec.handler::<SPEC, DB, J, ()>(env);
+        Self::new_with_handler(db, env, inspector, handler)
+    }
+
+    /// Creates EVM like [EVMImpl::new_with_spec] with the given handler.
+    pub fn new_with_handler(
+        db: &'a mut DB,
+        env: &'a mut Env,
+        inspector: Option<&'a mut dyn Inspector<DB, J>>,
+        handler: Handler<DB, J>,
+    ) -> Self {
+        Self::new_with_external(db, env, inspector, handler, ())
+    }
+
+    /// Creates EVM like [EVMImpl::new_with_spec] with the given instruction table, for example
+    /// with some instructions replaced. [CfgEnv::disabled_opcodes] are applied to the table,
+    /// its instructions don't consume gas if [CfgEnv::disable_gas_metering] is set and it is
+    /// wrapped by the inspector if it is set.
+    ///
+    /// [CfgEnv::disabled_opcodes]: crate::primitives::CfgEnv::disabled_opcodes
+    /// [CfgEnv::disable_gas_metering]: crate::primitives::CfgEnv::disable_gas_metering
+    pub fn new_with_instruction_table(
+        db: &'a mut DB,
+        env: &'a mut Env,
+        inspector: Option<&'a mut dyn Inspector<DB, J>>,
+        instruction_table: InstructionTable<Self>,
+    ) -> Self {
+        let handler = env.cfg.spec_id.handler::<SPEC, DB, J, ()>(env);
+        Self::new_with_table_and_handler(db, env, inspector, instruction_table, handler, ())
     }
 }
 
-impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
-    pub fn new_with_spec(
+impl<'a, SPEC: Spec + 'static, DB: Database, J: Journal, EXT> EVMImpl<'a, SPEC, DB, J, EXT> {
+    /// Creates EVM like [EVMImpl::new_with_handler] with the context of the embedding, see
+    /// [EvmContext::external].
+    ///
+    /// Handlers, inspectors and context precompiles of the EVM are generic over the type of the
+    /// context, which lets them access it without downcasting.
+    pub fn new_with_external(
         db: &'a mut DB,
         env: &'a mut Env,
-        inspector: Option<&'a mut dyn Inspector<DB>>,
-        precompiles: Precompiles,
+        inspector: Option<&'a mut dyn Inspector<DB, J, EXT>>,
+        handler: Handler<DB, J, EXT>,
+        external: EXT,
     ) -> Self {
-        let journaled_state =
-            JournaledState::new(SPEC::SPEC_ID, precompiles.addresses().copied().collect());
-        // If T is present it should be a generic T that modifies handler.
-        let instruction_table = if inspector.is_some() {
-            let instruction_table = make_boxed_instruction_table::<Self, SPEC, _>(
-                make_instruction_table::<Self, SPEC>(),
-                inspector_instruction,
+        // Unmodified table of the spec is evaluated at compile time.
+        if inspector.is_none()
+            && env.cfg.disabled_opcodes.is_empty()
+            && !env.cfg.disable_gas_metering
+        {
+            return Self::new_with_instruction_tables(
+                db,
+                env,
+                inspector,
+                InstructionTables::spec::<SPEC>(),
+                handler,
+                external,
             );
-            InstructionTables::Boxed(Arc::new(instruction_table))
-        } else {
-            InstructionTables::Plain(Arc::new(make_instruction_table::<Self, SPEC>()))
-        };
-        #[cfg(feature = "optimism")]
-        let mut handler = if env.cfg.optimism {
-            Handler::optimism::<SPEC>()
-        } else {
-            Handler::mainnet::<SPEC>()
+        }
+        Self::new_with_table_and_handler(
+            db,
+            env,
+            inspector,
+            make_instruction_table::<Self, SPEC>(),
+            handler,
+            external,
+        )
+    }
+
+    fn new_with_table_and_handler(
+        db: &'a mut DB,
+        env: &'a mut Env,
+        inspector: Option<&'a mut dyn Inspector<DB, J, EXT>>,
+        mut instruction_table: InstructionTable<Self>,
+        handler: Handler<DB, J, EXT>,
+        external: EXT,
+    ) -> Self {
+        for opcode in env.cfg.disabled_opcodes.iter() {
+            instruction_table[opcode as usize] = control::unknown;
+        }
+        // If T is present it should be a generic T that modifies handler.
+        let instruction_table = match (inspector.is_some(), env.cfg.disable_gas_metering) {
+            (false, false) => InstructionTables::Plain(Arc::new(instruction_table)),
+            (true, false) => {
+                InstructionTables::Boxed(Arc::new(make_boxed_instruction_table::<Self, SPEC, _>(
+                    instruction_table,
+                    inspector_instruction,
+                )))
+            }
+            (false, true) => {
+                InstructionTables::Boxed(Arc::new(make_boxed_instruction_table::<Self, SPEC, _>(
+                    instruction_table,
+                    gas_free_instruction,
+                )))
+            }
+            // Inspector sees the gas that is not consumed.
+            (true, true) => {
+                InstructionTables::Boxed(Arc::new(make_boxed_instruction_table::<Self, SPEC, _>(
+                    instruction_table,
+                    |instruction| inspector_instruction(gas_free_instruction(instruction)),
+                )))
+            }
         };
-        #[cfg(not(feature = "optimism"))]
-        let mut handler = Handler::mainnet::<SPEC>();
+        Self::new_with_instruction_tables(db, env, inspector, instruction_table, handler, external)
+    }
 
+    fn new_with_instruction_tables(
+        db: &'a mut DB,
+        env: &'a mut Env,
+        inspector: Option<&'a mut dyn Inspector<DB, J, EXT>>,
+        instruction_table: InstructionTables<'a, Self>,
+        mut handler: Handler<DB, J, EXT>,
+        external: EXT,
+    ) -> Self {
         if env.cfg.is_beneficiary_reward_disabled() {
             // do nothing
             handler.reward_beneficiary = |_, _| Ok(());
         }
 
+        let precompiles = handler.precompiles.clone();
+        let mut journaled_state = J::new(SPEC::SPEC_ID, sorted_warm_addresses(&*precompiles));
+        if env.cfg.disable_state_clear {
+            journaled_state.set_state_clear(false);
+        }
         Self {
             context: EvmContext {
                 env,
@@ -139,16 +212,70 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
                 db,
                 error: None,
                 precompiles,
+                transfer: handler.transfer,
+                create_address: handler.create_address,
+                context_precompiles: HashMap::new(),
+                fees: TxFees::default(),
                 #[cfg(feature = "optimism")]
                 l1_block_info: None,
+                external,
+                analysis_cache: AnalysisCache::new(),
             },
             inspector,
             instruction_table,
             handler,
+            halt_snapshot: None,
+            #[cfg(feature = "instruction-count")]
+            instruction_counts: InstructionCounts::default(),
+            memory_pool: SharedMemoryPool::new(),
             _phantomdata: PhantomData {},
         }
     }
 
+    /// Sets precompiles of the handler and the context, see [Handler::precompiles].
+    pub fn set_precompiles(&mut self, precompiles: impl PrecompileProvider + 'static) {
+        self.use_precompiles(Arc::new(precompiles));
+    }
+
+    fn use_precompiles(&mut self, precompiles: Arc<dyn PrecompileProvider>) {
+        self.context.precompiles = precompiles.clone();
+        self.handler.precompiles = precompiles;
+        self.warm_precompiles();
+    }
+
+    /// Sets the precompile with access to the context at the given address, it takes precedence
+    /// over the precompiles of the handler. See [EvmContext::context_precompiles].
+    pub fn set_context_precompile(
+        &mut self,
+        address: Address,
+        precompile: impl Into<ContextPrecompile<DB, J, EXT>>,
+    ) {
+        self.context
+            .context_precompiles
+            .insert(address, precompile.into());
+        self.warm_precompiles();
+    }
+
+    /// Sets addresses of all precompiles as warm in the journal.
+    fn warm_precompiles(&mut self) {
+        let mut addresses = self.context.precompiles.warm_addresses();
+        addresses.extend(self.context.context_precompiles.keys().copied());
+        addresses.sort_unstable();
+        addresses.dedup();
+        self.context
+            .journaled_state
+            .set_precompile_addresses(addresses);
+    }
+
+    /// Takes shared memory for the call stack out of the memory pool.
+    fn new_shared_memory(&mut self) -> SharedMemory {
+        let mut shared_memory = self.memory_pool.take();
+        shared_memory.set_memory_limit(self.context.env.cfg.memory_limit);
+
+        shared_memory.new_context();
+        shared_memory
+    }
+
     #[inline]
     pub fn run<FN>(
         &mut self,
@@ -161,17 +288,10 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
         let mut call_stack: Vec<Box<CallStackFrame>> = Vec::with_capacity(1025);
         call_stack.push(first_frame);
 
-        #[cfg(feature = "memory_limit")]
-        let mut shared_memory =
-            SharedMemory::new_with_memory_limit(self.context.env.cfg.memory_limit);
-        #[cfg(not(feature = "memory_limit"))]
-        let mut shared_memory = SharedMemory::new();
-
-        shared_memory.new_context();
-
-        let mut stack_frame = call_stack.first_mut().unwrap();
+        let mut shared_memory = self.new_shared_memory();
 
         loop {
+            let stack_frame = call_stack.last_mut().unwrap();
             // run interpreter
             let action = stack_frame
                 .interpreter
@@ -179,38 +299,170 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
             // take shared memory back.
             shared_memory = stack_frame.interpreter.take_memory();
 
-            let new_frame = match action {
-                InterpreterAction::SubCall {
-                    inputs,
-                    return_memory_offset,
-                } => self.handle_sub_call(
-                    inputs,
-                    stack_frame,
-                    return_memory_offset,
-                    &mut shared_memory,
-                ),
-                InterpreterAction::Create { inputs } => self.handle_sub_create(inputs, stack_frame),
-                InterpreterAction::Return { result } => {
-                    // free memory context.
-                    shared_memory.free_context();
-
-                    let child = call_stack.pop().unwrap();
-                    let parent = call_stack.last_mut();
-
-                    if let Some(result) =
-                        self.handle_frame_return(child, parent, &mut shared_memory, result)
-                    {
-                        return result;
-                    }
-                    stack_frame = call_stack.last_mut().unwrap();
-                    continue;
+            if let Some(result) = self.handle_action(&mut call_stack, &mut shared_memory, action) {
+                self.memory_pool.recycle(shared_memory);
+                return result;
+            }
+        }
+    }
+
+    /// Handles action of the last frame on the call stack.
+    ///
+    /// Returns result of the execution if the first frame returned.
+    #[inline]
+    #[allow(clippy::vec_box)]
+    fn handle_action(
+        &mut self,
+        call_stack: &mut Vec<Box<CallStackFrame>>,
+        shared_memory: &mut SharedMemory,
+        action: InterpreterAction,
+    ) -> Option<InterpreterResult> {
+        let is_first_frame = call_stack.len() == 1;
+        let stack_frame = call_stack.last_mut().unwrap();
+        #[cfg(feature = "instruction-count")]
+        self.instruction_counts.add(&core::mem::replace(
+            &mut stack_frame.interpreter.instruction_counts,
+            [0; 256],
+        ));
+        let new_frame = match action {
+            InterpreterAction::SubCall {
+                inputs,
+                return_memory_offset,
+            } => self.handle_sub_call(inputs, stack_frame, return_memory_offset, shared_memory),
+            InterpreterAction::Create { inputs } => self.handle_sub_create(inputs, stack_frame),
+            InterpreterAction::Return { result } => {
+                if is_first_frame && result.result.is_error() {
+                    self.capture_halt_snapshot(stack_frame, shared_memory);
                 }
-            };
-            if let Some(new_frame) = new_frame {
-                shared_memory.new_context();
-                call_stack.push(new_frame);
+                // free memory context.
+                shared_memory.free_context();
+
+                let child = call_stack.pop().unwrap();
+                let parent = call_stack.last_mut();
+
+                return self.handle_frame_return(child, parent, shared_memory, result);
+            }
+        };
+        if let Some(new_frame) = new_frame {
+            shared_memory.new_context();
+            call_stack.push(new_frame);
+        }
+        None
+    }
+
+    /// Captures stack and memory of the halted frame if enabled in the config.
+    fn capture_halt_snapshot(
+        &mut self,
+        stack_frame: &CallStackFrame,
+        shared_memory: &SharedMemory,
+    ) {
+        let Some(limits) = self.context.env.cfg.halt_snapshot_limits() else {
+            return;
+        };
+        let interpreter = &stack_frame.interpreter;
+        // Instruction pointer is already past the instruction that halted.
+        let pc = interpreter.program_counter().saturating_sub(1);
+        let stack = interpreter.stack().data();
+        let memory = shared_memory.context_memory();
+        let memory_offset = memory.len().saturating_sub(limits.memory);
+        self.halt_snapshot = Some(HaltSnapshot {
+            address: interpreter.contract().address,
+            pc,
+            opcode: interpreter
+                .contract()
+                .bytecode
+                .bytecode()
+                .get(pc)
+                .copied()
+                .unwrap_or_default(),
+            stack: stack[stack.len().saturating_sub(limits.stack)..].to_vec(),
+            stack_len: stack.len(),
+            memory_offset,
+            memory: Bytes::copy_from_slice(&memory[memory_offset..]),
+            memory_len: memory.len(),
+        });
+    }
+
+    /// Restores the gas limit requested by the transaction after it was clamped to
+    /// [CfgEnv::tx_gas_cap](crate::primitives::CfgEnv::tx_gas_cap) for the execution.
+    #[inline]
+    fn restore_gas_limit(&mut self, requested_gas_limit: Option<u64>) {
+        if let Some(gas_limit) = requested_gas_limit {
+            self.context.env.tx.gas_limit = gas_limit;
+        }
+    }
+
+    /// Sets the output fields that are not set by the handler.
+    fn finalize_output(&mut self, output: &mut ResultAndState, requested_gas_limit: Option<u64>) {
+        output.requested_gas_limit = requested_gas_limit;
+        output.fees = core::mem::take(&mut self.context.fees);
+        output.halt_snapshot = self
+            .halt_snapshot
+            .take()
+            .filter(|_| output.result.is_halt());
+        #[cfg(feature = "instruction-count")]
+        {
+            output.instruction_counts = Some(core::mem::take(&mut self.instruction_counts));
+        }
+    }
+
+    /// Executes up to `steps` instructions of the call stack and returns the number of executed
+    /// ones. Instruction table is resolved once for all of them.
+    fn run_frames<FN>(
+        &mut self,
+        instruction_table: &[FN; 256],
+        frames: &mut ExecutionFrames,
+        steps: usize,
+    ) -> usize
+    where
+        FN: Fn(&mut Interpreter, &mut Self),
+    {
+        let mut executed = 0;
+        while executed < steps && !frames.is_finished() {
+            let stack_frame = frames.call_stack.last_mut().unwrap();
+            if !frames.running {
+                let shared_memory =
+                    core::mem::replace(&mut frames.shared_memory, EMPTY_SHARED_MEMORY);
+                stack_frame.interpreter.begin_run(shared_memory);
+                frames.running = true;
+            }
+
+            let interpreter = &mut stack_frame.interpreter;
+            while executed < steps && interpreter.instruction_result == InstructionResult::Continue
+            {
+                interpreter.step(instruction_table, self);
+                executed += 1;
             }
-            stack_frame = call_stack.last_mut().unwrap();
+            if interpreter.instruction_result != InstructionResult::Continue {
+                let action = interpreter.end_run();
+                frames.shared_memory = interpreter.take_memory();
+                frames.running = false;
+                frames.result =
+                    self.handle_action(&mut frames.call_stack, &mut frames.shared_memory, action);
+            }
+        }
+        executed
+    }
+
+    /// Executes a single instruction of the call stack.
+    fn step_frames<FN>(&mut self, instruction_table: &[FN; 256], frames: &mut ExecutionFrames)
+    where
+        FN: Fn(&mut Interpreter, &mut Self),
+    {
+        let stack_frame = frames.call_stack.last_mut().unwrap();
+        if !frames.running {
+            let shared_memory = core::mem::replace(&mut frames.shared_memory, EMPTY_SHARED_MEMORY);
+            stack_frame.interpreter.begin_run(shared_memory);
+            frames.running = true;
+        }
+
+        stack_frame.interpreter.step(instruction_table, self);
+        if stack_frame.interpreter.instruction_result != InstructionResult::Continue {
+            let action = stack_frame.interpreter.end_run();
+            frames.shared_memory = stack_frame.interpreter.take_memory();
+            frames.running = false;
+            frames.result =
+                self.handle_action(&mut frames.call_stack, &mut frames.shared_memory, action);
         }
     }
 
@@ -279,10 +531,10 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
     ) -> Option<Box<CallStackFrame>> {
         // Call inspector if it is some.
         if let Some(inspector) = self.inspector.as_mut() {
-            if let Some((result, address)) = inspector.create(&mut self.context, &mut inputs) {
+            if let Some(outcome) = inspector.create(&mut self.context, &mut inputs) {
                 curent_stack_frame
                     .interpreter
-                    .insert_create_output(result, address);
+                    .insert_create_output(outcome.result, outcome.address);
                 return None;
             }
         }
@@ -321,13 +573,18 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
     ) -> Option<Box<CallStackFrame>> {
         // Call inspector if it is some.
         if let Some(inspector) = self.inspector.as_mut() {
-            if let Some((result, range)) = inspector.call(&mut self.context, &mut inputs) {
-                curent_stake_frame
-                    .interpreter
-                    .insert_call_output(shared_memory, result, range);
+            if let Some(outcome) = inspector.call(&mut self.context, &mut inputs) {
+                curent_stake_frame.interpreter.insert_call_output(
+                    shared_memory,
+                    outcome.result,
+                    outcome.memory_offset,
+                );
                 return None;
             }
         }
+        if let Some(alias_call_caller) = self.handler.alias_call_caller {
+            inputs.context.caller = alias_call_caller(self.context.env, &inputs.context);
+        }
         match self
             .context
             .make_call_frame(&inputs, return_memory_offset.clone())
@@ -349,22 +606,18 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
 
     /// Pre verify transaction.
     pub fn preverify_transaction_inner(&mut self) -> Result<(), EVMError<DB::Error>> {
-        let env = self.env();
+        let env = &*self.context.env;
 
         // Important: validate block before tx.
         env.validate_block_env::<SPEC>()?;
-        env.validate_tx::<SPEC>()?;
-
-        let initial_gas_spend = initial_tx_gas::<SPEC>(
-            &env.tx.data,
-            env.tx.transact_to.is_create(),
-            &env.tx.access_list,
-        );
+        let chain_tx = self.handler.chain_tx(env)?;
+        match chain_tx {
+            Some((chain_tx, kind)) => chain_tx.validate_tx(env, kind)?,
+            None => env.validate_tx::<SPEC>()?,
+        }
 
         // Additional check to see if limit is big enough to cover initial gas.
-        if initial_gas_spend > env.tx.gas_limit {
-            return Err(InvalidTransaction::CallGasCostMoreThanGasLimit.into());
-        }
+        calculate_intrinsic_gas(&env.tx, SPEC::SPEC_ID)?;
 
         // load acc
         let tx_caller = env.tx.caller;
@@ -374,41 +627,54 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
             .load_account(tx_caller, self.context.db)
             .map_err(EVMError::Database)?;
 
-        self.context
-            .env
-            .validate_tx_against_state(caller_account)
-            .map_err(Into::into)
+        match chain_tx {
+            Some((chain_tx, kind)) => {
+                chain_tx.validate_tx_against_state(self.context.env, kind, caller_account)
+            }
+            None => self.context.env.validate_tx_against_state(caller_account),
+        }
+        .map_err(Into::into)
     }
 
     /// Transact preverified transaction.
     pub fn transact_preverified_inner(&mut self) -> EVMResult<DB::Error> {
+        // start main loop if CallStackFrame is created correctly
+        let (interpreter_result, created_address) = match self.first_frame()? {
+            Ok(first_stack_frame) => {
+                let created_address = first_stack_frame.created_address;
+                let table = self.instruction_table.clone();
+                let result = match table {
+                    InstructionTables::Plain(table) => self.run(&table, first_stack_frame),
+                    InstructionTables::Boxed(table) => self.run(&table, first_stack_frame),
+                    InstructionTables::Const(table) => self.run(table, first_stack_frame),
+                };
+                (result, created_address)
+            }
+            Err(interpreter_result) => (interpreter_result, None),
+        };
+        self.finish_preverified(interpreter_result, created_address)
+    }
+
+    /// Prepares preverified transaction for execution.
+    ///
+    /// Returns the first frame, or result of the execution if frame was not needed.
+    fn first_frame(
+        &mut self,
+    ) -> Result<Result<Box<CallStackFrame>, InterpreterResult>, EVMError<DB::Error>> {
+        // Precompiles of the handler could have been replaced after the EVM was created.
+        if !Arc::ptr_eq(&self.context.precompiles, &self.handler.precompiles) {
+            self.use_precompiles(self.handler.precompiles.clone());
+        }
+        // So could the hooks that frames are made with.
+        self.context.transfer = self.handler.transfer;
+        self.context.create_address = self.handler.create_address;
+
         let env = &self.context.env;
         let tx_caller = env.tx.caller;
-        let tx_value = env.tx.value;
-        let tx_data = env.tx.data.clone();
         let tx_gas_limit = env.tx.gas_limit;
 
-        // the L1-cost fee is only computed for Optimism non-deposit transactions.
-        #[cfg(feature = "optimism")]
-        let tx_l1_cost = if env.cfg.optimism && env.tx.optimism.source_hash.is_none() {
-            let l1_block_info =
-                optimism::L1BlockInfo::try_fetch(self.context.db).map_err(EVMError::Database)?;
-
-            let Some(enveloped_tx) = &env.tx.optimism.enveloped_tx else {
-                panic!("[OPTIMISM] Failed to load enveloped transaction.");
-            };
-            let tx_l1_cost = l1_block_info.calculate_tx_l1_cost::<SPEC>(enveloped_tx);
-
-            // storage l1 block info for later use.
-            self.context.l1_block_info = Some(l1_block_info);
-
-            tx_l1_cost
-        } else {
-            U256::ZERO
-        };
-
         let initial_gas_spend = initial_tx_gas::<SPEC>(
-            &tx_data,
+            &env.tx.data,
             env.tx.transact_to.is_create(),
             &env.tx.access_list,
         );
@@ -424,102 +690,66 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
 
         self.context.load_access_list()?;
 
-        // load acc
-        let journal = &mut self.context.journaled_state;
-
-        #[cfg(feature = "optimism")]
-        if self.context.env.cfg.optimism {
-            EVMImpl::<SPEC, DB>::commit_mint_value(
-                tx_caller,
-                self.context.env.tx.optimism.mint,
-                self.context.db,
-                journal,
-            )?;
-
-            let is_deposit = self.context.env.tx.optimism.source_hash.is_some();
-            EVMImpl::<SPEC, DB>::remove_l1_cost(
-                is_deposit,
-                tx_caller,
-                tx_l1_cost,
-                self.context.db,
-                journal,
-            )?;
-        }
-
-        let (caller_account, _) = journal
-            .load_account(tx_caller, self.context.db)
-            .map_err(EVMError::Database)?;
-
         // Subtract gas costs from the caller's account.
-        // We need to saturate the gas cost to prevent underflow in case that `disable_balance_check` is enabled.
-        let mut gas_cost =
-            U256::from(tx_gas_limit).saturating_mul(self.context.env.effective_gas_price());
+        let fees = &mut self.context.fees;
+        *fees = TxFees {
+            effective_gas_price: self.context.env.effective_gas_price(),
+            ..Default::default()
+        };
+        let mut gas_cost = U256::from(tx_gas_limit).saturating_mul(fees.effective_gas_price);
 
         // EIP-4844
-        if SPEC::enabled(CANCUN) {
-            let data_fee = self.context.env.calc_data_fee().expect("already checked");
-            gas_cost = gas_cost.saturating_add(data_fee);
+        if self.context.env.is_blob_enabled::<SPEC>() {
+            fees.blob_gas_used = self.context.env.tx.get_total_blob_gas();
+            fees.blob_gas_price = self
+                .context
+                .env
+                .block
+                .get_blob_gasprice()
+                .expect("already checked");
+            gas_cost = gas_cost.saturating_add(fees.blob_fee());
         }
 
-        caller_account.info.balance = caller_account.info.balance.saturating_sub(gas_cost);
+        self.handler.deduct_caller(&mut self.context, gas_cost)?;
 
-        // touch account so we know it is changed.
-        caller_account.mark_touch();
+        if self.context.env.tx.transact_to.is_call() {
+            let (caller_account, _) = self
+                .context
+                .journaled_state
+                .load_account(tx_caller, self.context.db)
+                .map_err(EVMError::Database)?;
+            // Nonce is already checked
+            caller_account.info.nonce = caller_account.info.nonce.saturating_add(1);
+            caller_account.mark_touch();
+        }
 
         let transact_gas_limit = tx_gas_limit - initial_gas_spend;
 
-        // call inner handling of call/create
-        let first_stack_frame = match self.context.env.tx.transact_to {
-            TransactTo::Call(address) => {
-                // Nonce is already checked
-                caller_account.info.nonce = caller_account.info.nonce.saturating_add(1);
-
-                self.context.make_call_frame(
-                    &CallInputs {
-                        contract: address,
-                        transfer: Transfer {
-                            source: tx_caller,
-                            target: address,
-                            value: tx_value,
-                        },
-                        input: tx_data,
-                        gas_limit: transact_gas_limit,
-                        context: CallContext {
-                            caller: tx_caller,
-                            address,
-                            code_address: address,
-                            apparent_value: tx_value,
-                            scheme: CallScheme::Call,
-                        },
-                        is_static: false,
-                    },
-                    0..0,
-                )
-            }
-            TransactTo::Create(scheme) => self.context.make_create_frame::<SPEC>(&CreateInputs {
-                caller: tx_caller,
-                scheme,
-                value: tx_value,
-                init_code: tx_data,
-                gas_limit: transact_gas_limit,
-            }),
-        };
-        // Some only if it is create.
-        let mut created_address = None;
+        let mut inputs = self
+            .handler
+            .first_frame_inputs(self.context.env, transact_gas_limit);
+        if let FirstFrameInputs::Call(inputs) = &mut inputs {
+            inputs.context.caller = self.handler.alias_caller(self.context.env, &inputs.context);
+        }
 
-        // start main loop if CallStackFrame is created correctly
-        let interpreter_result = match first_stack_frame {
-            Ok(first_stack_frame) => {
-                created_address = first_stack_frame.created_address;
-                let table = self.instruction_table.clone();
-                match table {
-                    InstructionTables::Plain(table) => self.run(&table, first_stack_frame),
-                    InstructionTables::Boxed(table) => self.run(&table, first_stack_frame),
-                }
+        if let Some(inspector) = self.inspector.as_mut() {
+            if let Some(result) =
+                self.handler
+                    .inspect_first_frame(&mut self.context, &mut **inspector, &mut inputs)
+            {
+                return Ok(Err(result));
             }
-            Err(interpreter_result) => interpreter_result,
-        };
+        }
 
+        Ok(self.handler.make_first_frame(&mut self.context, &inputs))
+    }
+
+    /// Finishes the transaction with the result of the first frame.
+    fn finish_preverified(
+        &mut self,
+        interpreter_result: InterpreterResult,
+        created_address: Option<Address>,
+    ) -> EVMResult<DB::Error> {
         let handler = &self.handler;
         let data = &mut self.context;
 
@@ -530,11 +760,13 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
         // set refund. Refund amount depends on hardfork.
         gas.set_refund(handler.calculate_gas_refund(data.env, &gas) as i64);
 
+        let accounting = GasAccounting::new::<SPEC>(data.env, &gas);
+
         // Reimburse the caller
-        handler.reimburse_caller(data, &gas)?;
+        handler.reimburse_caller(data, &accounting)?;
 
         // Reward beneficiary
-        handler.reward_beneficiary(data, &gas)?;
+        handler.reward_beneficiary(data, &accounting)?;
 
         // output of execution
         let output = match data.env.tx.transact_to {
@@ -542,14 +774,25 @@ impl<'a, SPEC: Spec + 'static, DB: Database> EVMImpl<'a, SPEC, DB> {
             TransactTo::Create(_) => Output::Create(interpreter_result.output, created_address),
         };
 
+        let initial_gas_spend = initial_tx_gas::<SPEC>(
+            &data.env.tx.data,
+            data.env.tx.transact_to.is_create(),
+            &data.env.tx.access_list,
+        );
+
         // main return
-        handler.main_return(data, interpreter_result.result, output, &gas)
+        handler.main_return(
+            data,
+            interpreter_result.result,
+            output,
+            gas.breakdown(initial_gas_spend),
+        )
     }
 }
 
 /// EVM transaction interface.
 #[auto_impl(&mut, Box)]
-pub trait Transact<DBError> {
+pub trait Transact<DBError, J = JournaledState> {
     /// Run checks that could make transaction fail before call/create.
     fn preverify_transaction(&mut self) -> Result<(), EVMError<DBError>>;
 
@@ -558,30 +801,198 @@ pub trait Transact<DBError> {
 
     /// Execute transaction by running pre-verification steps and then transaction itself.
     fn transact(&mut self) -> EVMResult<DBError>;
+
+    /// Capture the state of the started transaction.
+    fn snapshot_transact(&self, frames: &ExecutionFrames) -> ExecutionSnapshot<J>;
+
+    /// Replace the state of the EVM with the snapshot and return the call stack to continue the
+    /// transaction from.
+    fn restore_transact(&mut self, snapshot: ExecutionSnapshot<J>) -> ExecutionFrames;
+}
+
+/// Extension of [Transact] with the execution internals of [EVMImpl].
+///
+/// It is separate from [Transact] so that implementors of [Transact] don't need to implement it.
+#[auto_impl(&mut, Box)]
+pub trait TransactExt<DBError, J = JournaledState>: Transact<DBError, J> {
+    /// Run pre-verification steps and prepare the transaction for step by step execution.
+    fn start_transact(&mut self) -> Result<ExecutionFrames, EVMError<DBError>>;
+
+    /// Execute a single instruction of the started transaction.
+    ///
+    /// Returns false if there are no more instructions to execute.
+    fn step_transact(&mut self, frames: &mut ExecutionFrames) -> bool;
+
+    /// Execute the rest of the started transaction and return its output.
+    fn finish_transact(&mut self, frames: ExecutionFrames) -> EVMResult<DBError>;
+
+    /// Execute up to `steps` instructions of the started transaction.
+    ///
+    /// Returns the number of executed instructions, it is less than `steps` only if execution
+    /// finished.
+    fn advance_transact(&mut self, frames: &mut ExecutionFrames, steps: usize) -> usize;
+
+    /// Pool of shared memories that executions take their memory from and give it back to.
+    ///
+    /// Memories are reused between transactions executed by this EVM, the pool can be swapped
+    /// with another one to reuse them across EVMs.
+    fn memory_pool(&mut self) -> &mut SharedMemoryPool;
+
+    /// Cache of the analysed bytecode of the called contracts.
+    ///
+    /// Bytecode is reused between transactions executed by this EVM, the cache can be swapped
+    /// with another one to reuse it across EVMs.
+    fn analysis_cache(&mut self) -> &mut AnalysisCache;
 }
 
-impl<'a, SPEC: Spec + 'static, DB: Database> Transact<DB::Error> for EVMImpl<'a, SPEC, DB> {
+impl<'a, SPEC: Spec + 'static, DB: Database, J: Journal, EXT> Transact<DB::Error, J>
+    for EVMImpl<'a, SPEC, DB, J, EXT>
+{
     #[inline]
     fn preverify_transaction(&mut self) -> Result<(), EVMError<DB::Error>> {
-        self.preverify_transaction_inner()
+        let requested_gas_limit = self.context.env.clamp_tx_gas_limit();
+        let output = self.preverify_transaction_inner();
+        self.restore_gas_limit(requested_gas_limit);
+        output
     }
 
     #[inline]
     fn transact_preverified(&mut self) -> EVMResult<DB::Error> {
+        let requested_gas_limit = self.context.env.clamp_tx_gas_limit();
         let output = self.transact_preverified_inner();
-        self.handler.end(&mut self.context, output)
+        let output = self.handler.end(&mut self.context, output);
+        self.restore_gas_limit(requested_gas_limit);
+        let mut output = output?;
+        self.finalize_output(&mut output, requested_gas_limit);
+        Ok(output)
     }
 
     #[inline]
     fn transact(&mut self) -> EVMResult<DB::Error> {
+        let requested_gas_limit = self.context.env.clamp_tx_gas_limit();
         let output = self
             .preverify_transaction_inner()
             .and_then(|()| self.transact_preverified_inner());
-        self.handler.end(&mut self.context, output)
+        let output = self.handler.end(&mut self.context, output);
+        self.restore_gas_limit(requested_gas_limit);
+        let mut output = output?;
+        self.finalize_output(&mut output, requested_gas_limit);
+        Ok(output)
+    }
+
+    fn snapshot_transact(&self, frames: &ExecutionFrames) -> ExecutionSnapshot<J> {
+        ExecutionSnapshot {
+            env: self.context.env.clone(),
+            journaled_state: self.context.journaled_state.clone(),
+            fees: self.context.fees,
+            halt_snapshot: self.halt_snapshot.clone(),
+            #[cfg(feature = "optimism")]
+            l1_block_info: self.context.l1_block_info.clone(),
+            #[cfg(feature = "instruction-count")]
+            instruction_counts: self.instruction_counts.clone(),
+            frames: FramesSnapshot::capture(frames),
+        }
+    }
+
+    fn restore_transact(&mut self, snapshot: ExecutionSnapshot<J>) -> ExecutionFrames {
+        *self.context.env = snapshot.env;
+        self.context.journaled_state = snapshot.journaled_state;
+        self.context.error = None;
+        self.context.fees = snapshot.fees;
+        self.halt_snapshot = snapshot.halt_snapshot;
+        #[cfg(feature = "optimism")]
+        {
+            self.context.l1_block_info = snapshot.l1_block_info;
+        }
+        #[cfg(feature = "instruction-count")]
+        {
+            self.instruction_counts = snapshot.instruction_counts;
+        }
+        snapshot.frames.restore()
     }
 }
 
-impl<'a, SPEC: Spec + 'static, DB: Database> Host for EVMImpl<'a, SPEC, DB> {
+impl<'a, SPEC: Spec + 'static, DB: Database, J: Journal, EXT> TransactExt<DB::Error, J>
+    for EVMImpl<'a, SPEC, DB, J, EXT>
+{
+    fn start_transact(&mut self) -> Result<ExecutionFrames, EVMError<DB::Error>> {
+        let requested_gas_limit = self.context.env.clamp_tx_gas_limit();
+        let mut frames = ExecutionFrames::new(self.new_shared_memory(), requested_gas_limit);
+        match self
+            .preverify_transaction_inner()
+            .and_then(|()| self.first_frame())
+        {
+            Ok(Ok(first_stack_frame)) => {
+                frames.created_address = first_stack_frame.created_address;
+                frames.call_stack.push(first_stack_frame);
+            }
+            Ok(Err(interpreter_result)) => frames.result = Some(interpreter_result),
+            // Error can be turned into output by the end handle.
+            Err(e) => {
+                let output = self.handler.end(&mut self.context, Err(e));
+                if output.is_err() {
+                    self.restore_gas_limit(requested_gas_limit);
+                }
+                frames.output = Some(output?);
+            }
+        }
+        Ok(frames)
+    }
+
+    fn step_transact(&mut self, frames: &mut ExecutionFrames) -> bool {
+        if frames.is_finished() {
+            return false;
+        }
+        let table = self.instruction_table.clone();
+        match table {
+            InstructionTables::Plain(table) => self.step_frames(&table, frames),
+            InstructionTables::Boxed(table) => self.step_frames(&table, frames),
+            InstructionTables::Const(table) => self.step_frames(table, frames),
+        }
+        true
+    }
+
+    fn finish_transact(&mut self, mut frames: ExecutionFrames) -> EVMResult<DB::Error> {
+        self.advance_transact(&mut frames, usize::MAX);
+        self.memory_pool.recycle(core::mem::replace(
+            &mut frames.shared_memory,
+            EMPTY_SHARED_MEMORY,
+        ));
+        let output = match (frames.output, frames.result) {
+            (Some(output), _) => Ok(output),
+            (None, Some(interpreter_result)) => {
+                let output = self.finish_preverified(interpreter_result, frames.created_address);
+                self.handler.end(&mut self.context, output)
+            }
+            (None, None) => unreachable!("finished call stack has a result"),
+        };
+        self.restore_gas_limit(frames.requested_gas_limit);
+        let mut output = output?;
+        self.finalize_output(&mut output, frames.requested_gas_limit);
+        Ok(output)
+    }
+
+    fn advance_transact(&mut self, frames: &mut ExecutionFrames, steps: usize) -> usize {
+        let table = self.instruction_table.clone();
+        match table {
+            InstructionTables::Plain(table) => self.run_frames(&table, frames, steps),
+            InstructionTables::Boxed(table) => self.run_frames(&table, frames, steps),
+            InstructionTables::Const(table) => self.run_frames(table, frames, steps),
+        }
+    }
+
+    fn memory_pool(&mut self) -> &mut SharedMemoryPool {
+        &mut self.memory_pool
+    }
+
+    fn analysis_cache(&mut self) -> &mut AnalysisCache {
+        &mut self.context.analysis_cache
+    }
+}
+
+impl<'a, SPEC: Spec + 'static, DB: Database, J: Journal, EXT> Host
+    for EVMImpl<'a, SPEC, DB, J, EXT>
+{
     fn env(&mut self) -> &mut Env {
         self.context.env()
     }
@@ -642,7 +1053,7 @@ impl<'a, SPEC: Spec + 'static, DB: Database> Host for EVMImpl<'a, SPEC, DB> {
 
     fn selfdestruct(&mut self, address: Address, target: Address) -> Option<SelfDestructResult> {
         if let Some(inspector) = self.inspector.as_mut() {
-            let acc = self.context.journaled_state.state.get(&address).unwrap();
+            let acc = self.context.journaled_state.account(address);
             inspector.selfdestruct(address, target, acc.info.balance);
         }
         self.context
@@ -651,6 +1062,26 @@ impl<'a, SPEC: Spec + 'static, DB: Database> Host for EVMImpl<'a, SPEC, DB> {
             .map_err(|e| self.context.error = Some(e))
             .ok()
     }
+
+    fn jump(
+        &mut self,
+        address: Address,
+        pc: usize,
+        dest: &mut U256,
+        condition: Option<&mut U256>,
+    ) -> bool {
+        match self.inspector.as_mut() {
+            Some(inspector) => inspector.jump(&mut self.context, address, pc, dest, condition),
+            None => true,
+        }
+    }
+}
+
+/// Returns warm addresses of the precompiles, sorted for lookups of the journal.
+fn sorted_warm_addresses(precompiles: &dyn PrecompileProvider) -> Vec<Address> {
+    let mut addresses = precompiles.warm_addresses();
+    addresses.sort_unstable();
+    addresses
 }
 
 /// Creates new EVM instance with erased types.
@@ -658,18 +1089,43 @@ pub fn new_evm<'a, DB: Database>(
     env: &'a mut Env,
     db: &'a mut DB,
     insp: Option<&'a mut dyn Inspector<DB>>,
-) -> Box<dyn Transact<DB::Error> + 'a> {
+) -> Box<dyn TransactExt<DB::Error> + 'a> {
+    new_evm_with_external(env, db, insp, ())
+}
+
+/// Creates EVM like [new_evm] with the context of the embedding, see [EvmContext::external].
+pub fn new_evm_with_external<'a, DB: Database, EXT: 'a>(
+    env: &'a mut Env,
+    db: &'a mut DB,
+    insp: Option<&'a mut dyn Inspector<DB, JournaledState, EXT>>,
+    external: EXT,
+) -> Box<dyn TransactExt<DB::Error> + 'a> {
+    let spec_id = env.cfg.spec_id;
+    new_evm_with_chain_spec(spec_id, env, db, insp, external)
+}
+
+/// Creates EVM like [new_evm_with_external] for the fork of a chain, see [ChainSpec].
+///
+/// `env.cfg.spec_id` is set to the base spec of the fork.
+pub fn new_evm_with_chain_spec<'a, C: ChainSpec, DB: Database, EXT: 'a>(
+    chain_spec: C,
+    env: &'a mut Env,
+    db: &'a mut DB,
+    insp: Option<&'a mut dyn Inspector<DB, JournaledState, EXT>>,
+    external: EXT,
+) -> Box<dyn TransactExt<DB::Error> + 'a> {
     macro_rules! create_evm {
-        ($spec:ident) => {
-            Box::new(EVMImpl::<'a, $spec, DB>::new_with_spec(
-                db,
-                env,
-                insp,
-                Precompiles::new(revm_precompile::SpecId::from_spec_id($spec::SPEC_ID)).clone(),
-            ))
-        };
+        ($spec:ident) => {{
+            let handler = chain_spec.handler::<$spec, DB, JournaledState, EXT>(env);
+            Box::new(
+                EVMImpl::<'a, $spec, DB, JournaledState, EXT>::new_with_external(
+                    db, env, insp, handler, external,
+                ),
+            )
+        }};
     }
 
+    env.cfg.spec_id = chain_spec.base_spec_id();
     use specification::*;
     match env.cfg.spec_id {
         SpecId::FRONTIER | SpecId::FRONTIER_THAWING => create_evm!(FrontierSpec),
@@ -686,6 +1142,7 @@ pub fn new_evm<'a, DB: Database>(
         SpecId::MERGE => create_evm!(MergeSpec),
         SpecId::SHANGHAI => create_evm!(ShanghaiSpec),
         SpecId::CANCUN => create_evm!(CancunSpec),
+        SpecId::PRAGUE => create_evm!(PragueSpec),
         SpecId::LATEST => create_evm!(LatestSpec),
         #[cfg(feature = "optimism")]
         SpecId::BEDROCK => create_evm!(BedrockSpec),
@@ -693,6 +1150,12 @@ pub fn new_evm<'a, DB: Database>(
         SpecId::REGOLITH => create_evm!(RegolithSpec),
         #[cfg(feature = "optimism")]
         SpecId::CANYON => create_evm!(CanyonSpec),
+        #[cfg(feature = "optimism")]
+        SpecId::ECOTONE => create_evm!(EcotoneSpec),
+        #[cfg(feature = "optimism")]
+        SpecId::FJORD => create_evm!(FjordSpec),
+        #[cfg(feature = "optimism")]
+        SpecId::ISTHMUS => create_evm!(IsthmusSpec),
     }
 }
 
@@ -702,131 +1165,88 @@ mod tests {
     use super::*;
 
     use crate::db::InMemoryDB;
-    use crate::primitives::{specification::BedrockSpec, state::AccountInfo, SpecId};
+    use crate::optimism;
+    use crate::primitives::{state::AccountInfo, SpecId};
 
     #[test]
-    fn test_commit_mint_value() {
-        let caller = Address::ZERO;
-        let mint_value = Some(1u128);
-        let mut db = InMemoryDB::default();
-        db.insert_account_info(
-            caller,
-            AccountInfo {
-                nonce: 0,
-                balance: U256::from(100),
-                code_hash: B256::ZERO,
-                code: None,
-            },
-        );
-        let mut journal = JournaledState::new(SpecId::BERLIN, vec![]);
-        journal
-            .initial_account_load(caller, &[U256::from(100)], &mut db)
-            .unwrap();
-        assert!(EVMImpl::<BedrockSpec, InMemoryDB>::commit_mint_value(
-            caller,
-            mint_value,
-            &mut db,
-            &mut journal
-        )
-        .is_ok(),);
-
-        // Check the account balance is updated.
-        let (account, _) = journal.load_account(caller, &mut db).unwrap();
-        assert_eq!(account.info.balance, U256::from(101));
-
-        // No mint value should be a no-op.
-        assert!(EVMImpl::<BedrockSpec, InMemoryDB>::commit_mint_value(
-            caller,
-            None,
-            &mut db,
-            &mut journal
-        )
-        .is_ok(),);
-        let (account, _) = journal.load_account(caller, &mut db).unwrap();
-        assert_eq!(account.info.balance, U256::from(101));
-    }
+    fn test_l1_fee_reported_in_fees() {
+        use crate::primitives::{bytes, specification::RegolithSpec, Env, TransactTo};
 
-    #[test]
-    fn test_remove_l1_cost_non_deposit() {
-        let caller = Address::ZERO;
+        let caller = Address::with_last_byte(0x10);
         let mut db = InMemoryDB::default();
-        let mut journal = JournaledState::new(SpecId::BERLIN, vec![]);
-        let slots = &[U256::from(100)];
-        journal
-            .initial_account_load(caller, slots, &mut db)
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000_000)));
+        for (slot, value) in [(1u64, 1_000u64), (5, 1_000), (6, 1_000)] {
+            db.insert_account_storage(
+                optimism::L1_BLOCK_CONTRACT,
+                U256::from(slot),
+                U256::from(value),
+            )
             .unwrap();
-        assert!(EVMImpl::<BedrockSpec, InMemoryDB>::remove_l1_cost(
-            true,
-            caller,
-            U256::ZERO,
-            &mut db,
-            &mut journal
-        )
-        .is_ok(),);
-    }
+        }
 
-    #[test]
-    fn test_remove_l1_cost() {
-        let caller = Address::ZERO;
-        let mut db = InMemoryDB::default();
-        db.insert_account_info(
-            caller,
-            AccountInfo {
-                nonce: 0,
-                balance: U256::from(100),
-                code_hash: B256::ZERO,
-                code: None,
-            },
+        let mut env = Env::default();
+        env.cfg.optimism = true;
+        env.cfg.spec_id = SpecId::REGOLITH;
+        env.tx.caller = caller;
+        env.tx.transact_to = TransactTo::Call(Address::with_last_byte(0x20));
+        env.tx.gas_limit = 100_000;
+        env.tx.gas_price = U256::from(1);
+        env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+
+        let mut evm = EVMImpl::<RegolithSpec, _>::new_with_spec(&mut db, &mut env, None);
+        let output = evm.transact().unwrap();
+        assert_eq!(output.fees.l1_gas_used, U256::from(48));
+        assert_eq!(output.fees.l1_fee, U256::from(1048));
+        let gas_used = output.result.gas_used();
+        assert_eq!(
+            output.fees.total_fee(gas_used),
+            U256::from(gas_used) + U256::from(1048)
         );
-        let mut journal = JournaledState::new(SpecId::BERLIN, vec![]);
-        journal
-            .initial_account_load(caller, &[U256::from(100)], &mut db)
-            .unwrap();
-        assert!(EVMImpl::<BedrockSpec, InMemoryDB>::remove_l1_cost(
-            false,
-            caller,
-            U256::from(1),
-            &mut db,
-            &mut journal
-        )
-        .is_ok(),);
-
-        // Check the account balance is updated.
-        let (account, _) = journal.load_account(caller, &mut db).unwrap();
-        assert_eq!(account.info.balance, U256::from(99));
+        let spent = U256::from(1_000_000_000) - output.state[&caller].info.balance;
+        assert_eq!(spent, output.fees.total_fee(gas_used));
     }
 
     #[test]
-    fn test_remove_l1_cost_lack_of_funds() {
-        let caller = Address::ZERO;
+    fn test_operator_fee_charged_and_credited() {
+        use crate::primitives::{bytes, specification::IsthmusSpec, Env, TransactTo};
+
+        let caller = Address::with_last_byte(0x10);
         let mut db = InMemoryDB::default();
-        db.insert_account_info(
-            caller,
-            AccountInfo {
-                nonce: 0,
-                balance: U256::from(100),
-                code_hash: B256::ZERO,
-                code: None,
-            },
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000_000)));
+        // Operator fee scalar of 2 per gas and constant of 500, packed into slot 8.
+        let operator_fee_params = (U256::from(2_000_000) << 64) | U256::from(500);
+        for (slot, value) in [
+            (1u64, U256::from(1_000)),
+            (3, U256::from(1) << 96),
+            (8, operator_fee_params),
+        ] {
+            db.insert_account_storage(optimism::L1_BLOCK_CONTRACT, U256::from(slot), value)
+                .unwrap();
+        }
+
+        let mut env = Env::default();
+        env.cfg.optimism = true;
+        env.cfg.spec_id = SpecId::ISTHMUS;
+        env.tx.caller = caller;
+        env.tx.transact_to = TransactTo::Call(Address::with_last_byte(0x20));
+        env.tx.gas_limit = 100_000;
+        env.tx.gas_price = U256::from(1);
+        env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+
+        let mut evm = EVMImpl::<IsthmusSpec, _>::new_with_spec(&mut db, &mut env, None);
+        let output = evm.transact().unwrap();
+        let gas_used = output.result.gas_used();
+        let operator_fee = U256::from(gas_used * 2 + 500);
+        assert_eq!(
+            output.state[&optimism::OPERATOR_FEE_RECIPIENT].info.balance,
+            operator_fee
         );
-        let mut journal = JournaledState::new(SpecId::BERLIN, vec![]);
-        journal
-            .initial_account_load(caller, &[U256::from(100)], &mut db)
-            .unwrap();
+        let spent = U256::from(1_000_000_000) - output.state[&caller].info.balance;
+        assert_eq!(output.fees.operator_fee, operator_fee);
         assert_eq!(
-            EVMImpl::<BedrockSpec, InMemoryDB>::remove_l1_cost(
-                false,
-                caller,
-                U256::from(101),
-                &mut db,
-                &mut journal
-            ),
-            Err(EVMError::Transaction(
-                InvalidTransaction::LackOfFundForMaxFee {
-                    fee: Box::new(U256::from(101)),
-                    balance: Box::new(U256::from(100)),
-                },
-            ))
+            output.fees.total_fee(gas_used),
+            U256::from(gas_used) + output.fees.l1_fee + operator_fee
         );
+        assert_eq!(spent, output.fees.total_fee(gas_used));
     }
 }
@@ -0,0 +1,438 @@
+use crate::{
+    interpreter::{
+        BytecodeLocked, Contract, Gas, InstructionResult, Interpreter, InterpreterResult,
+        SharedMemory, EMPTY_SHARED_MEMORY,
+    },
+    primitives::{
+        Address, Bytecode, Bytes, EVMError, EVMResult, Env, HaltSnapshot, HashMap, ResultAndState,
+        TxFees, B256, U256,
+    },
+    CallStackFrame, JournalCheckpoint, JournaledState, Transact, TransactExt,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::ops::Range;
+
+/// Condition on which [ExecutionHandle::run_until] stops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Breakpoint {
+    /// Program counter of the current frame is equal to the value.
+    Pc(usize),
+    /// Number of frames on the call stack is equal to the value.
+    Depth(usize),
+    /// Next instruction of the current frame is the opcode.
+    Opcode(u8),
+}
+
+/// Call stack of the transaction that is executed step by step.
+///
+/// Created by [TransactExt::start_transact] and advanced by [TransactExt::step_transact].
+#[derive(Debug)]
+pub struct ExecutionFrames {
+    /// Frames that are executing, last one is the current frame.
+    #[allow(clippy::vec_box)]
+    pub(crate) call_stack: Vec<Box<CallStackFrame>>,
+    /// Shared memory, set only if interpreter of the current frame is not running.
+    pub(crate) shared_memory: SharedMemory,
+    /// True if interpreter of the current frame is running.
+    pub(crate) running: bool,
+    /// Address of the contract created by the transaction.
+    pub(crate) created_address: Option<Address>,
+    /// Gas limit requested by the transaction if it was clamped.
+    pub(crate) requested_gas_limit: Option<u64>,
+    /// Result of the first frame, set when execution of the call stack is done.
+    pub(crate) result: Option<InterpreterResult>,
+    /// Output of the transaction if it was decided before the execution started.
+    pub(crate) output: Option<ResultAndState>,
+}
+
+impl ExecutionFrames {
+    pub(crate) fn new(shared_memory: SharedMemory, requested_gas_limit: Option<u64>) -> Self {
+        Self {
+            call_stack: Vec::with_capacity(1025),
+            shared_memory,
+            running: false,
+            created_address: None,
+            requested_gas_limit,
+            result: None,
+            output: None,
+        }
+    }
+
+    /// Returns true if there are no more instructions to execute.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.call_stack.is_empty()
+    }
+
+    /// Returns the number of frames on the call stack.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.call_stack.len()
+    }
+
+    /// Returns the interpreter of the current frame.
+    #[inline]
+    pub fn interpreter(&self) -> Option<&Interpreter> {
+        self.call_stack.last().map(|frame| &frame.interpreter)
+    }
+
+    /// Returns true if the next instruction matches the breakpoint.
+    fn is_hit(&self, breakpoint: Breakpoint) -> bool {
+        let Some(interpreter) = self.interpreter() else {
+            return false;
+        };
+        match breakpoint {
+            Breakpoint::Pc(pc) => interpreter.program_counter() == pc,
+            Breakpoint::Depth(depth) => self.depth() == depth,
+            Breakpoint::Opcode(opcode) => interpreter.current_opcode() == opcode,
+        }
+    }
+}
+
+/// State of a transaction executed step by step, see [ExecutionHandle::snapshot].
+///
+/// Contains the environment, the journaled state and the call stack with the interpreter of
+/// every frame, so the execution can be continued from it with [ExecutionHandle::restore] or
+/// [crate::EVM::restore_transact] any number of times without executing the transaction again.
+/// State of the inspector and the database are not part of the snapshot.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionSnapshot<J = JournaledState> {
+    pub(crate) env: Env,
+    pub(crate) journaled_state: J,
+    pub(crate) fees: TxFees,
+    pub(crate) halt_snapshot: Option<HaltSnapshot>,
+    #[cfg(feature = "optimism")]
+    pub(crate) l1_block_info: Option<crate::optimism::L1BlockInfo>,
+    #[cfg(feature = "instruction-count")]
+    pub(crate) instruction_counts: crate::primitives::InstructionCounts,
+    pub(crate) frames: FramesSnapshot,
+}
+
+impl<J> ExecutionSnapshot<J> {
+    /// Returns the environment of the transaction.
+    #[inline]
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    /// Returns the journaled state at the time of the snapshot.
+    #[inline]
+    pub fn journaled_state(&self) -> &J {
+        &self.journaled_state
+    }
+
+    /// Returns the number of frames on the call stack.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.frames.call_stack.len()
+    }
+
+    /// Returns the program counter of the current frame.
+    #[inline]
+    pub fn program_counter(&self) -> Option<usize> {
+        self.frames
+            .call_stack
+            .last()
+            .map(|frame| frame.program_counter)
+    }
+
+    /// Returns the gas of the current frame.
+    #[inline]
+    pub fn gas(&self) -> Option<&Gas> {
+        self.frames.call_stack.last().map(|frame| &frame.gas)
+    }
+}
+
+/// Call stack of [ExecutionFrames] captured by [ExecutionSnapshot].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct FramesSnapshot {
+    call_stack: Vec<FrameSnapshot>,
+    shared_memory: SharedMemory,
+    running: bool,
+    created_address: Option<Address>,
+    requested_gas_limit: Option<u64>,
+    result: Option<InterpreterResult>,
+    output: Option<ResultAndState>,
+}
+
+impl FramesSnapshot {
+    pub(crate) fn capture(frames: &ExecutionFrames) -> Self {
+        Self {
+            call_stack: frames
+                .call_stack
+                .iter()
+                .map(|frame| FrameSnapshot::capture(frame))
+                .collect(),
+            shared_memory: match (frames.running, frames.call_stack.last()) {
+                (true, Some(frame)) => frame.interpreter.shared_memory.clone(),
+                _ => frames.shared_memory.clone(),
+            },
+            running: frames.running,
+            created_address: frames.created_address,
+            requested_gas_limit: frames.requested_gas_limit,
+            result: frames.result.clone(),
+            output: frames.output.clone(),
+        }
+    }
+
+    /// Rebuilds the call stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytecode or the program counter of a frame is invalid.
+    pub(crate) fn restore(self) -> ExecutionFrames {
+        let mut frames = ExecutionFrames::new(self.shared_memory, self.requested_gas_limit);
+        frames
+            .call_stack
+            .extend(self.call_stack.into_iter().map(FrameSnapshot::restore));
+        if let (true, Some(frame)) = (self.running, frames.call_stack.last_mut()) {
+            frame.interpreter.shared_memory =
+                core::mem::replace(&mut frames.shared_memory, EMPTY_SHARED_MEMORY);
+            frames.running = true;
+        }
+        frames.created_address = self.created_address;
+        frames.result = self.result;
+        frames.output = self.output;
+        frames
+    }
+}
+
+/// State of a frame on the call stack, see [ExecutionSnapshot].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct FrameSnapshot {
+    is_create: bool,
+    checkpoint: JournalCheckpoint,
+    created_address: Option<Address>,
+    subcall_return_memory_range: Range<usize>,
+    input: Bytes,
+    bytecode: Bytecode,
+    hash: B256,
+    address: Address,
+    caller: Address,
+    value: U256,
+    program_counter: usize,
+    instruction_result: InstructionResult,
+    gas: Gas,
+    stack: Vec<U256>,
+    return_data_buffer: Bytes,
+    is_static: bool,
+    return_stack: Vec<usize>,
+    #[cfg(feature = "instruction-count")]
+    instruction_counts: Vec<u64>,
+}
+
+impl FrameSnapshot {
+    fn capture(frame: &CallStackFrame) -> Self {
+        let interpreter = &frame.interpreter;
+        let contract = interpreter.contract();
+        Self {
+            is_create: frame.is_create,
+            checkpoint: frame.checkpoint,
+            created_address: frame.created_address,
+            subcall_return_memory_range: frame.subcall_return_memory_range.clone(),
+            input: contract.input.clone(),
+            bytecode: contract.bytecode.clone().unlock(),
+            hash: contract.hash,
+            address: contract.address,
+            caller: contract.caller,
+            value: contract.value,
+            program_counter: interpreter.program_counter(),
+            instruction_result: interpreter.instruction_result,
+            gas: interpreter.gas,
+            stack: interpreter.stack.data().to_vec(),
+            return_data_buffer: interpreter.return_data_buffer.clone(),
+            is_static: interpreter.is_static,
+            return_stack: interpreter.return_stack.clone(),
+            #[cfg(feature = "instruction-count")]
+            instruction_counts: interpreter.instruction_counts.to_vec(),
+        }
+    }
+
+    fn restore(self) -> Box<CallStackFrame> {
+        let bytecode = BytecodeLocked::try_from(self.bytecode).expect("bytecode is analysed");
+        assert!(
+            bytecode.len() <= bytecode.bytecode().len()
+                && self.program_counter < bytecode.bytecode().len(),
+            "program counter is out of the bytecode"
+        );
+        let contract = Contract {
+            input: self.input,
+            bytecode,
+            hash: self.hash,
+            address: self.address,
+            caller: self.caller,
+            value: self.value,
+        };
+        let mut interpreter =
+            Interpreter::new(Box::new(contract), self.gas.limit(), self.is_static);
+        // SAFETY: program counter is checked to be inside the bytecode.
+        interpreter.instruction_pointer = unsafe {
+            interpreter
+                .contract
+                .bytecode
+                .as_ptr()
+                .add(self.program_counter)
+        };
+        interpreter.instruction_result = self.instruction_result;
+        interpreter.gas = self.gas;
+        for word in self.stack {
+            interpreter
+                .stack
+                .push(word)
+                .expect("stack of the snapshot is within the limit");
+        }
+        interpreter.return_data_buffer = self.return_data_buffer;
+        interpreter.return_stack = self.return_stack;
+        #[cfg(feature = "instruction-count")]
+        {
+            let counts = self
+                .instruction_counts
+                .get(..256)
+                .expect("count of every opcode");
+            interpreter.instruction_counts.copy_from_slice(counts);
+        }
+        Box::new(CallStackFrame {
+            is_create: self.is_create,
+            checkpoint: self.checkpoint,
+            created_address: self.created_address,
+            subcall_return_memory_range: self.subcall_return_memory_range,
+            interpreter,
+        })
+    }
+}
+
+/// Statistical profile of the transaction, see [ExecutionHandle::resume_profiled].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SamplingProfile {
+    /// Number of executed instructions between two samples.
+    pub interval: usize,
+    /// Number of samples taken at the contract address and program counter.
+    pub samples: HashMap<(Address, usize), u64>,
+    /// Number of all taken samples.
+    pub total: u64,
+}
+
+impl SamplingProfile {
+    /// Returns up to `n` locations with the most samples, most sampled first.
+    pub fn hottest(&self, n: usize) -> Vec<((Address, usize), u64)> {
+        let mut samples = self
+            .samples
+            .iter()
+            .map(|(location, count)| (*location, *count))
+            .collect::<Vec<_>>();
+        samples.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        samples.truncate(n);
+        samples
+    }
+}
+
+/// Transaction execution that is driven one instruction at a time.
+///
+/// Created by [crate::EVM::start_transact]. Execution is advanced with [ExecutionHandle::step],
+/// [ExecutionHandle::step_n] and [ExecutionHandle::run_until], in between the state of the
+/// current frame can be examined. [ExecutionHandle::resume] runs the rest of the transaction and
+/// returns its output.
+pub struct ExecutionHandle<'a, DBError> {
+    evm: Box<dyn TransactExt<DBError> + 'a>,
+    frames: ExecutionFrames,
+}
+
+impl<'a, DBError> ExecutionHandle<'a, DBError> {
+    pub(crate) fn new(evm: Box<dyn TransactExt<DBError> + 'a>, frames: ExecutionFrames) -> Self {
+        Self { evm, frames }
+    }
+
+    /// Executes a single instruction.
+    ///
+    /// Returns false if there are no more instructions to execute.
+    pub fn step(&mut self) -> bool {
+        self.evm.step_transact(&mut self.frames)
+    }
+
+    /// Executes up to `n` instructions and returns the number of executed ones.
+    pub fn step_n(&mut self, n: usize) -> usize {
+        self.evm.advance_transact(&mut self.frames, n)
+    }
+
+    /// Executes instructions until the next one matches the breakpoint.
+    ///
+    /// Returns false if execution finished without hitting the breakpoint.
+    pub fn run_until(&mut self, breakpoint: Breakpoint) -> bool {
+        while self.step() {
+            if self.frames.is_hit(breakpoint) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns true if there are no more instructions to execute.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.frames.is_finished()
+    }
+
+    /// Returns the number of frames on the call stack.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.frames.depth()
+    }
+
+    /// Returns the interpreter of the current frame.
+    #[inline]
+    pub fn interpreter(&self) -> Option<&Interpreter> {
+        self.frames.interpreter()
+    }
+
+    /// Captures the state of the execution, see [ExecutionSnapshot].
+    pub fn snapshot(&self) -> ExecutionSnapshot {
+        self.evm.snapshot_transact(&self.frames)
+    }
+
+    /// Continues the execution from the snapshot taken by an execution of the same EVM
+    /// configuration, discarding the current state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot contains invalid bytecode or program counter.
+    pub fn restore(&mut self, snapshot: ExecutionSnapshot) {
+        self.frames = self.evm.restore_transact(snapshot);
+    }
+
+    /// Executes the rest of the transaction and returns its output.
+    pub fn resume(mut self) -> EVMResult<DBError> {
+        self.evm.finish_transact(self.frames)
+    }
+
+    /// Executes the rest of the transaction like [ExecutionHandle::resume] and samples the
+    /// contract address and program counter of the current frame every `interval` instructions.
+    ///
+    /// Sampling does not go through an [Inspector](crate::Inspector): instructions are executed
+    /// in batches of `interval` with the instruction table of the EVM and the location is read
+    /// between the batches, so the overhead is negligible compared to step tracing even for
+    /// small intervals. Hooks of an inspector the EVM was created with are still called.
+    pub fn resume_profiled(
+        mut self,
+        interval: usize,
+    ) -> Result<(ResultAndState, SamplingProfile), EVMError<DBError>> {
+        assert!(interval > 0, "sampling interval must be positive");
+        let mut profile = SamplingProfile {
+            interval,
+            ..Default::default()
+        };
+        while self.step_n(interval) == interval {
+            if let Some(interpreter) = self.interpreter() {
+                let location = (
+                    interpreter.contract().address,
+                    interpreter.program_counter(),
+                );
+                *profile.samples.entry(location).or_default() += 1;
+                profile.total += 1;
+            }
+        }
+        Ok((self.resume()?, profile))
+    }
+}
@@ -3,88 +3,290 @@ pub mod mainnet;
 pub mod optimism;
 
 use crate::{
-    interpreter::{Gas, InstructionResult},
-    primitives::{db::Database, EVMError, EVMResultGeneric, Env, Output, ResultAndState, Spec},
-    EvmContext,
+    interpreter::{
+        CallContext, CallInputs, CreateInputs, Gas, InstructionResult, InterpreterResult, Transfer,
+    },
+    precompile::{PrecompileProvider, Precompiles, SpecId as PrecompileSpecId},
+    primitives::{
+        db::Database, Account, Address, EVMError, EVMResultGeneric, Env, GasBreakdown,
+        InvalidTransaction, Output, ResultAndState, Spec, SpecId::LONDON, B256, U256,
+    },
+    CallStackFrame, EvmContext, Inspector, Journal, JournaledState,
 };
+use alloc::{boxed::Box, sync::Arc};
 
 /// Handle call return and return final gas value.
 type CallReturnHandle = fn(&Env, InstructionResult, Gas) -> Gas;
 
+/// Returns the caller that is seen by the called contract, e.g. to apply address aliasing of
+/// cross-domain messages.
+type AliasCallerHandle = fn(&Env, &CallContext) -> Address;
+
+/// Builds inputs of the first frame from the transaction and the gas left after the initial
+/// transaction gas.
+type FirstFrameInputsHandle = fn(&Env, u64) -> FirstFrameInputs;
+
+/// Called with the inputs of the first frame if inspector is set. Returning a result skips
+/// execution of the frame.
+type InspectFirstFrameHandle<DB, J, EXT> = fn(
+    &mut EvmContext<'_, DB, J, EXT>,
+    &mut dyn Inspector<DB, J, EXT>,
+    &mut FirstFrameInputs,
+) -> Option<InterpreterResult>;
+
+/// Creates the first frame, or returns the result if the frame was not needed.
+type MakeFirstFrameHandle<DB, J, EXT> = fn(
+    &mut EvmContext<'_, DB, J, EXT>,
+    &FirstFrameInputs,
+) -> Result<Box<CallStackFrame>, InterpreterResult>;
+
+/// Applies the value transfer of a call or create inside of its checkpoint.
+pub(crate) type TransferHandle<DB, J, EXT> =
+    fn(&mut EvmContext<'_, DB, J, EXT>, &Transfer) -> Result<(), InstructionResult>;
+
+/// Derives the address of a created contract from the create inputs, the nonce of the caller
+/// before the create and the hash of the init code.
+pub(crate) type CreateAddressHandle = fn(&Env, &CreateInputs, u64, &B256) -> Address;
+
+/// Deducts the gas cost of the transaction from the caller before execution.
+type DeductCallerHandle<DB, J, EXT> =
+    fn(&mut EvmContext<'_, DB, J, EXT>, U256) -> EVMResultGeneric<(), <DB as Database>::Error>;
+
 /// Reimburse the caller with ethereum it didn't spent.
-type ReimburseCallerHandle<DB> =
-    fn(&mut EvmContext<'_, DB>, &Gas) -> EVMResultGeneric<(), <DB as Database>::Error>;
+type ReimburseCallerHandle<DB, J, EXT> = fn(
+    &mut EvmContext<'_, DB, J, EXT>,
+    &GasAccounting,
+) -> EVMResultGeneric<(), <DB as Database>::Error>;
 
 /// Reward beneficiary with transaction rewards.
-type RewardBeneficiaryHandle<DB> = ReimburseCallerHandle<DB>;
+type RewardBeneficiaryHandle<DB, J, EXT> = ReimburseCallerHandle<DB, J, EXT>;
 
 /// Calculate gas refund for transaction.
+///
+/// Receives the final transaction gas, with the refund counter in [`Gas::refunded`], and
+/// returns the refund given back to the caller. The spec is in [`crate::primitives::CfgEnv`].
 type CalculateGasRefundHandle = fn(&Env, &Gas) -> u64;
 
 /// Main return handle, takes state from journal and transforms internal result to external.
-type MainReturnHandle<DB> = fn(
-    &mut EvmContext<'_, DB>,
+type MainReturnHandle<DB, J, EXT> = fn(
+    &mut EvmContext<'_, DB, J, EXT>,
     InstructionResult,
     Output,
-    &Gas,
-) -> Result<ResultAndState, EVMError<<DB as Database>::Error>>;
+    GasBreakdown,
+)
+    -> Result<ResultAndState, EVMError<<DB as Database>::Error>>;
 
 /// End handle, takes result and state and returns final result.
 /// This will be called after all the other handlers.
 ///
 /// It is useful for catching errors and returning them in a different way.
-type EndHandle<DB> = fn(
-    &mut EvmContext<'_, DB>,
+type EndHandle<DB, J, EXT> = fn(
+    &mut EvmContext<'_, DB, J, EXT>,
     evm_output: Result<ResultAndState, EVMError<<DB as Database>::Error>>,
 ) -> Result<ResultAndState, EVMError<<DB as Database>::Error>>;
 
+/// Returns precompiles of the spec.
+fn spec_precompiles<SPEC: Spec>() -> Arc<dyn PrecompileProvider> {
+    Arc::new(Precompiles::new(PrecompileSpecId::from_spec_id(
+        SPEC::SPEC_ID,
+    )))
+}
+
+/// Extension point for chain specific transaction kinds, e.g. system or retryable transactions
+/// of an L2.
+///
+/// Transactions with [`TxEnv::chain_tx_kind`](crate::primitives::TxEnv::chain_tx_kind) set are
+/// validated and turned into the first frame by [`Handler::chain_tx`] instead of the mainnet
+/// rules. Block validation, intrinsic gas and fee handling stay the same for all kinds.
+pub trait ChainTx: Send + Sync {
+    /// Returns `true` if the transaction kind is handled by this extension.
+    fn supports(&self, kind: u8) -> bool;
+
+    /// Validates the transaction against the environment, in place of [`Env::validate_tx`].
+    fn validate_tx(&self, env: &Env, kind: u8) -> Result<(), InvalidTransaction>;
+
+    /// Validates the transaction against the caller account, in place of
+    /// [`Env::validate_tx_against_state`]. Defaults to the mainnet checks.
+    fn validate_tx_against_state(
+        &self,
+        env: &Env,
+        kind: u8,
+        caller: &mut Account,
+    ) -> Result<(), InvalidTransaction> {
+        let _ = kind;
+        env.validate_tx_against_state(caller)
+    }
+
+    /// Builds inputs of the first frame. Defaults to the mainnet call or create.
+    fn first_frame_inputs(&self, env: &Env, kind: u8, gas_limit: u64) -> FirstFrameInputs {
+        let _ = kind;
+        mainnet::first_frame_inputs(env, gas_limit)
+    }
+}
+
+/// Inputs of the transaction's first frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FirstFrameInputs {
+    Call(Box<CallInputs>),
+    Create(Box<CreateInputs>),
+}
+
+/// Gas accounting of the finished transaction.
+///
+/// It is passed to the reimburse caller and reward beneficiary handles so that chains
+/// can redirect fees (e.g. to a fee vault) without recalculating them from [`Gas`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GasAccounting {
+    /// Gas spent by the transaction with refund already subtracted.
+    pub spent: u64,
+    /// Gas refunded to the caller.
+    pub refunded: u64,
+    /// Fee per gas that is paid to the beneficiary.
+    pub priority_fee: U256,
+    /// Base fee per gas of the block. Zero before London.
+    pub base_fee: U256,
+}
+
+impl GasAccounting {
+    /// Creates gas accounting from the final transaction gas.
+    ///
+    /// Refund in `gas` is expected to be already capped by the refund handle.
+    pub fn new<SPEC: Spec>(env: &Env, gas: &Gas) -> Self {
+        let refunded = gas.refunded() as u64;
+        let effective_gas_price = env.effective_gas_price();
+        // EIP-1559 discard basefee for coinbase transfer.
+        let (priority_fee, base_fee) = if SPEC::enabled(LONDON) {
+            (
+                effective_gas_price.saturating_sub(env.block.basefee),
+                env.block.basefee,
+            )
+        } else {
+            (effective_gas_price, U256::ZERO)
+        };
+        Self {
+            spent: gas.spend() - refunded,
+            refunded,
+            priority_fee,
+            base_fee,
+        }
+    }
+
+    /// Returns the amount that the beneficiary receives.
+    #[inline]
+    pub fn beneficiary_fee(&self) -> U256 {
+        self.priority_fee * U256::from(self.spent)
+    }
+
+    /// Returns the amount of base fee paid by the transaction.
+    #[inline]
+    pub fn base_fee_amount(&self) -> U256 {
+        self.base_fee * U256::from(self.spent)
+    }
+}
+
 /// Handler acts as a proxy and allow to define different behavior for different
 /// sections of the code. This allows nice integration of different chains or
 /// to disable some mainnet behavior.
-pub struct Handler<DB: Database> {
+pub struct Handler<DB: Database, J: Journal = JournaledState, EXT = ()> {
     // Uses env, call result and returned gas from the call to determine the gas
     // that is returned from transaction execution..
     pub call_return: CallReturnHandle,
+    /// Caller of the transaction's call frame. Value is still transferred from and gas is still
+    /// paid by the transaction caller. Not applied to contract creation.
+    pub alias_caller: AliasCallerHandle,
+    /// Caller of the sub calls, applied to all call schemes if set.
+    pub alias_call_caller: Option<AliasCallerHandle>,
+    /// Builds inputs of the first frame, caller of the call is aliased afterwards.
+    pub first_frame_inputs: FirstFrameInputsHandle,
+    /// Lets the inspector see and override the first frame.
+    pub inspect_first_frame: InspectFirstFrameHandle<DB, J, EXT>,
+    /// Creates the first frame from its inputs.
+    pub make_first_frame: MakeFirstFrameHandle<DB, J, EXT>,
+    /// Applies the value transfers of calls and creates, including the ones of the transaction.
+    ///
+    /// Mainnet moves the balance with the journal. Chains with native token rules, e.g. minting
+    /// on deposits, fees on transfers or balance caps, replace it instead of the journal. An
+    /// error fails the frame, changes are reverted with it.
+    pub transfer: TransferHandle<DB, J, EXT>,
+    /// Deducts the gas cost, including the blob fee, from the caller before execution.
+    pub deduct_caller: DeductCallerHandle<DB, J, EXT>,
+    /// Derives the addresses of contracts created by `CREATE`, `CREATE2` and create
+    /// transactions. Mainnet hashes the caller with its nonce, or with the salt and the init
+    /// code hash.
+    pub create_address: CreateAddressHandle,
     /// Reimburse the caller with ethereum it didn't spent.
-    pub reimburse_caller: ReimburseCallerHandle<DB>,
+    pub reimburse_caller: ReimburseCallerHandle<DB, J, EXT>,
     /// Reward the beneficiary with caller fee.
-    pub reward_beneficiary: RewardBeneficiaryHandle<DB>,
+    pub reward_beneficiary: RewardBeneficiaryHandle<DB, J, EXT>,
     /// Calculate gas refund for transaction.
-    /// Some chains have it disabled.
+    ///
+    /// Mainnet caps the refund counter to a part of the spent gas, see [`Gas::capped_refund`].
+    /// Some chains have it disabled or use their own rules.
     pub calculate_gas_refund: CalculateGasRefundHandle,
     /// Main return handle, returns the output of the transact.
-    pub main_return: MainReturnHandle<DB>,
+    pub main_return: MainReturnHandle<DB, J, EXT>,
     /// End handle.
-    pub end: EndHandle<DB>,
+    pub end: EndHandle<DB, J, EXT>,
+    /// Precompiles available to the EVM. Replacing them takes effect from the next
+    /// transaction.
+    pub precompiles: Arc<dyn PrecompileProvider>,
+    /// Chain specific transaction kinds. Transactions of other kinds than Ethereum ones are
+    /// rejected if not set.
+    pub chain_tx: Option<Arc<dyn ChainTx>>,
 }
 
-impl<DB: Database> Handler<DB> {
+impl<DB: Database, J: Journal, EXT> Clone for Handler<DB, J, EXT> {
+    fn clone(&self) -> Self {
+        Self {
+            call_return: self.call_return,
+            alias_caller: self.alias_caller,
+            alias_call_caller: self.alias_call_caller,
+            first_frame_inputs: self.first_frame_inputs,
+            inspect_first_frame: self.inspect_first_frame,
+            make_first_frame: self.make_first_frame,
+            transfer: self.transfer,
+            deduct_caller: self.deduct_caller,
+            create_address: self.create_address,
+            reimburse_caller: self.reimburse_caller,
+            reward_beneficiary: self.reward_beneficiary,
+            calculate_gas_refund: self.calculate_gas_refund,
+            main_return: self.main_return,
+            end: self.end,
+            precompiles: self.precompiles.clone(),
+            chain_tx: self.chain_tx.clone(),
+        }
+    }
+}
+
+impl<DB: Database, J: Journal, EXT> Handler<DB, J, EXT> {
     /// Handler for the mainnet
     pub fn mainnet<SPEC: Spec>() -> Self {
         Self {
             call_return: mainnet::handle_call_return::<SPEC>,
+            alias_caller: mainnet::alias_caller,
+            alias_call_caller: None,
+            first_frame_inputs: mainnet::first_frame_inputs,
+            inspect_first_frame: mainnet::inspect_first_frame::<DB, J, EXT>,
+            make_first_frame: mainnet::make_first_frame::<SPEC, DB, J, EXT>,
+            transfer: mainnet::transfer::<DB, J, EXT>,
+            deduct_caller: mainnet::deduct_caller::<DB, J, EXT>,
+            create_address: mainnet::create_address,
             calculate_gas_refund: mainnet::calculate_gas_refund::<SPEC>,
-            reimburse_caller: mainnet::handle_reimburse_caller::<SPEC, DB>,
-            reward_beneficiary: mainnet::reward_beneficiary::<SPEC, DB>,
-            main_return: mainnet::main_return::<DB>,
-            end: mainnet::end_handle::<DB>,
+            reimburse_caller: mainnet::handle_reimburse_caller::<SPEC, DB, J, EXT>,
+            reward_beneficiary: mainnet::reward_beneficiary::<SPEC, DB, J, EXT>,
+            main_return: mainnet::main_return::<DB, J, EXT>,
+            end: mainnet::end_handle::<DB, J, EXT>,
+            precompiles: spec_precompiles::<SPEC>(),
+            chain_tx: None,
         }
     }
 
-    /// Handler for the optimism
+    /// Handler for the optimism, see [optimism::optimism_handle_register].
     #[cfg(feature = "optimism")]
     pub fn optimism<SPEC: Spec>() -> Self {
-        Self {
-            call_return: optimism::handle_call_return::<SPEC>,
-            // we reinburse caller the same was as in mainnet.
-            // Refund is calculated differently then mainnet.
-            reimburse_caller: mainnet::handle_reimburse_caller::<SPEC, DB>,
-            calculate_gas_refund: optimism::calculate_gas_refund::<SPEC>,
-            reward_beneficiary: optimism::reward_beneficiary::<SPEC, DB>,
-            // In case of halt of deposit transaction return Error.
-            main_return: optimism::main_return::<SPEC, DB>,
-            end: optimism::end_handle::<SPEC, DB>,
-        }
+        let mut handler = Self::mainnet::<SPEC>();
+        optimism::optimism_handle_register::<SPEC, DB, J, EXT>(&mut handler);
+        handler
     }
 
     /// Handle call return, depending on instruction result gas will be reimbursed or not.
@@ -92,13 +294,68 @@ impl<DB: Database> Handler<DB> {
         (self.call_return)(env, call_result, returned_gas)
     }
 
+    /// Returns caller of the call frame, see [Handler::alias_caller].
+    pub fn alias_caller(&self, env: &Env, context: &CallContext) -> Address {
+        (self.alias_caller)(env, context)
+    }
+
+    /// Returns the extension handling the chain specific transaction kind of `env`, or `None`
+    /// for Ethereum transactions.
+    pub fn chain_tx(&self, env: &Env) -> Result<Option<(&dyn ChainTx, u8)>, InvalidTransaction> {
+        let Some(kind) = env.tx.chain_tx_kind else {
+            return Ok(None);
+        };
+        match &self.chain_tx {
+            Some(chain_tx) if chain_tx.supports(kind) => Ok(Some((&**chain_tx, kind))),
+            _ => Err(InvalidTransaction::UnsupportedChainTxKind(kind)),
+        }
+    }
+
+    /// Builds inputs of the first frame.
+    ///
+    /// Chain specific transactions are built by [`ChainTx::first_frame_inputs`].
+    pub fn first_frame_inputs(&self, env: &Env, gas_limit: u64) -> FirstFrameInputs {
+        if let Ok(Some((chain_tx, kind))) = self.chain_tx(env) {
+            return chain_tx.first_frame_inputs(env, kind, gas_limit);
+        }
+        (self.first_frame_inputs)(env, gas_limit)
+    }
+
+    /// Calls inspector with the first frame inputs, returns result if the frame is overridden.
+    pub fn inspect_first_frame(
+        &self,
+        context: &mut EvmContext<'_, DB, J, EXT>,
+        inspector: &mut dyn Inspector<DB, J, EXT>,
+        inputs: &mut FirstFrameInputs,
+    ) -> Option<InterpreterResult> {
+        (self.inspect_first_frame)(context, inspector, inputs)
+    }
+
+    /// Creates the first frame.
+    pub fn make_first_frame(
+        &self,
+        context: &mut EvmContext<'_, DB, J, EXT>,
+        inputs: &FirstFrameInputs,
+    ) -> Result<Box<CallStackFrame>, InterpreterResult> {
+        (self.make_first_frame)(context, inputs)
+    }
+
+    /// Deducts the gas cost from the caller.
+    pub fn deduct_caller(
+        &self,
+        context: &mut EvmContext<'_, DB, J, EXT>,
+        gas_cost: U256,
+    ) -> Result<(), EVMError<DB::Error>> {
+        (self.deduct_caller)(context, gas_cost)
+    }
+
     /// Reimburse the caller with gas that were not spend.
     pub fn reimburse_caller(
         &self,
-        context: &mut EvmContext<'_, DB>,
-        gas: &Gas,
+        context: &mut EvmContext<'_, DB, J, EXT>,
+        accounting: &GasAccounting,
     ) -> Result<(), EVMError<DB::Error>> {
-        (self.reimburse_caller)(context, gas)
+        (self.reimburse_caller)(context, accounting)
     }
 
     /// Calculate gas refund for transaction. Some chains have it disabled.
@@ -109,19 +366,19 @@ impl<DB: Database> Handler<DB> {
     /// Reward beneficiary
     pub fn reward_beneficiary(
         &self,
-        context: &mut EvmContext<'_, DB>,
-        gas: &Gas,
+        context: &mut EvmContext<'_, DB, J, EXT>,
+        accounting: &GasAccounting,
     ) -> Result<(), EVMError<DB::Error>> {
-        (self.reward_beneficiary)(context, gas)
+        (self.reward_beneficiary)(context, accounting)
     }
 
     /// Main return.
     pub fn main_return(
         &self,
-        context: &mut EvmContext<'_, DB>,
+        context: &mut EvmContext<'_, DB, J, EXT>,
         call_result: InstructionResult,
         output: Output,
-        gas: &Gas,
+        gas: GasBreakdown,
     ) -> Result<ResultAndState, EVMError<DB::Error>> {
         (self.main_return)(context, call_result, output, gas)
     }
@@ -129,7 +386,7 @@ impl<DB: Database> Handler<DB> {
     /// End handler.
     pub fn end(
         &self,
-        context: &mut EvmContext<'_, DB>,
+        context: &mut EvmContext<'_, DB, J, EXT>,
         end_output: Result<ResultAndState, EVMError<DB::Error>>,
     ) -> Result<ResultAndState, EVMError<DB::Error>> {
         (self.end)(context, end_output)
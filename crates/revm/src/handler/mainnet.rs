@@ -1,13 +1,18 @@
 //! Mainnet related handlers.
 
 use crate::{
-    interpreter::{return_ok, return_revert, Gas, InstructionResult, SuccessOrHalt},
+    handler::{FirstFrameInputs, GasAccounting},
+    interpreter::{
+        return_ok, return_revert, CallContext, CallInputs, CallScheme, CreateInputs, Gas,
+        InstructionResult, InterpreterResult, SuccessOrHalt, Transfer, TxKind,
+    },
     primitives::{
-        db::Database, EVMError, Env, ExecutionResult, Output, ResultAndState, Spec, SpecId::LONDON,
-        U256,
+        db::Database, Address, EVMError, Env, ExecutionResult, GasBreakdown, Output,
+        ResultAndState, Spec, SpecId::LONDON, TransactTo, TxFees, B256, U256,
     },
-    EvmContext,
+    CallStackFrame, EvmContext, Inspector, Journal,
 };
+use alloc::boxed::Box;
 
 /// Handle output of the transaction
 #[inline]
@@ -34,13 +39,126 @@ pub fn handle_call_return<SPEC: Spec>(
     gas
 }
 
+/// Mainnet doesn't alias callers.
+#[inline]
+pub fn alias_caller(_env: &Env, context: &CallContext) -> Address {
+    context.caller
+}
+
+/// Call or create of the transaction with the given gas limit.
+#[inline]
+pub fn first_frame_inputs(env: &Env, gas_limit: u64) -> FirstFrameInputs {
+    let tx = &env.tx;
+    match tx.transact_to {
+        TransactTo::Call(address) => FirstFrameInputs::Call(Box::new(CallInputs {
+            contract: address,
+            transfer: Transfer {
+                source: tx.caller,
+                target: address,
+                value: tx.value,
+            },
+            input: tx.data.clone(),
+            gas_limit,
+            context: CallContext {
+                caller: tx.caller,
+                address,
+                code_address: address,
+                apparent_value: tx.value,
+                scheme: CallScheme::Call,
+            },
+            is_static: false,
+            tx_kind: Some(TxKind::from_env(env)),
+        })),
+        TransactTo::Create(scheme) => FirstFrameInputs::Create(Box::new(CreateInputs {
+            caller: tx.caller,
+            scheme,
+            value: tx.value,
+            init_code: tx.data.clone(),
+            gas_limit,
+            tx_kind: Some(TxKind::from_env(env)),
+        })),
+    }
+}
+
+/// Calls [Inspector::call] or [Inspector::create] the same way as for the sub calls.
+#[inline]
+pub fn inspect_first_frame<DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
+    inspector: &mut dyn Inspector<DB, J, EXT>,
+    inputs: &mut FirstFrameInputs,
+) -> Option<InterpreterResult> {
+    match inputs {
+        FirstFrameInputs::Call(inputs) => inspector.call(context, inputs).map(|o| o.result),
+        FirstFrameInputs::Create(inputs) => inspector.create(context, inputs).map(|o| o.result),
+    }
+}
+
+/// Creates call or create frame.
 #[inline]
-pub fn handle_reimburse_caller<SPEC: Spec, DB: Database>(
-    context: &mut EvmContext<'_, DB>,
-    gas: &Gas,
+pub fn make_first_frame<SPEC: Spec, DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
+    inputs: &FirstFrameInputs,
+) -> Result<Box<CallStackFrame>, InterpreterResult> {
+    match inputs {
+        FirstFrameInputs::Call(inputs) => context.make_call_frame(inputs, 0..0),
+        FirstFrameInputs::Create(inputs) => context.make_create_frame::<SPEC>(inputs),
+    }
+}
+
+/// Moves the value of the call or create between the accounts.
+#[inline]
+pub fn transfer<DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
+    transfer: &Transfer,
+) -> Result<(), InstructionResult> {
+    context.journaled_state.transfer(
+        &transfer.source,
+        &transfer.target,
+        transfer.value,
+        context.db,
+    )
+}
+
+/// Address of the created contract derived from the caller and its nonce, or from the caller,
+/// the salt and the init code hash for `CREATE2`.
+#[inline]
+pub fn create_address(
+    _env: &Env,
+    inputs: &CreateInputs,
+    nonce: u64,
+    init_code_hash: &B256,
+) -> Address {
+    inputs.created_address_with_hash(nonce, init_code_hash)
+}
+
+/// Subtracts the gas cost from the balance of the caller.
+#[inline]
+pub fn deduct_caller<DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
+    gas_cost: U256,
+) -> Result<(), EVMError<DB::Error>> {
+    let (caller_account, _) = context
+        .journaled_state
+        .load_account(context.env.tx.caller, context.db)
+        .map_err(EVMError::Database)?;
+
+    // We need to saturate the gas cost to prevent underflow in case that `disable_balance_check`
+    // is enabled.
+    caller_account.info.balance = caller_account.info.balance.saturating_sub(gas_cost);
+
+    // touch account so we know it is changed.
+    caller_account.mark_touch();
+    Ok(())
+}
+
+#[inline]
+pub fn handle_reimburse_caller<SPEC: Spec, DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
+    accounting: &GasAccounting,
 ) -> Result<(), EVMError<DB::Error>> {
     let caller = context.env.tx.caller;
     let effective_gas_price = context.env.effective_gas_price();
+    let reimbursed_gas = context.env.tx.gas_limit - accounting.spent;
 
     // return balance of not spend gas.
     let (caller_account, _) = context
@@ -51,28 +169,21 @@ pub fn handle_reimburse_caller<SPEC: Spec, DB: Database>(
     caller_account.info.balance = caller_account
         .info
         .balance
-        .saturating_add(effective_gas_price * U256::from(gas.remaining() + gas.refunded() as u64));
+        .saturating_add(effective_gas_price * U256::from(reimbursed_gas));
 
     Ok(())
 }
 
 /// Reward beneficiary with gas fee.
 #[inline]
-pub fn reward_beneficiary<SPEC: Spec, DB: Database>(
-    context: &mut EvmContext<'_, DB>,
-    gas: &Gas,
+pub fn reward_beneficiary<SPEC: Spec, DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
+    accounting: &GasAccounting,
 ) -> Result<(), EVMError<DB::Error>> {
     let beneficiary = context.env.block.coinbase;
-    let effective_gas_price = context.env.effective_gas_price();
 
     // transfer fee to coinbase/beneficiary.
     // EIP-1559 discard basefee for coinbase transfer. Basefee amount of gas is discarded.
-    let coinbase_gas_price = if SPEC::enabled(LONDON) {
-        effective_gas_price.saturating_sub(context.env.block.basefee)
-    } else {
-        effective_gas_price
-    };
-
     let (coinbase_account, _) = context
         .journaled_state
         .load_account(beneficiary, context.db)
@@ -82,7 +193,7 @@ pub fn reward_beneficiary<SPEC: Spec, DB: Database>(
     coinbase_account.info.balance = coinbase_account
         .info
         .balance
-        .saturating_add(coinbase_gas_price * U256::from(gas.spend() - gas.refunded() as u64));
+        .saturating_add(accounting.beneficiary_fee());
 
     Ok(())
 }
@@ -99,8 +210,7 @@ pub fn calculate_gas_refund<SPEC: Spec>(env: &Env, gas: &Gas) -> u64 {
         0
     } else {
         // EIP-3529: Reduction in refunds
-        let max_refund_quotient = if SPEC::enabled(LONDON) { 5 } else { 2 };
-        (gas.refunded() as u64).min(gas.spend() / max_refund_quotient)
+        gas.capped_refund(SPEC::enabled(LONDON))
     }
 }
 
@@ -108,38 +218,29 @@ pub fn calculate_gas_refund<SPEC: Spec>(env: &Env, gas: &Gas) -> u64 {
 
 /// Main return handle, returns the output of the transaction.
 #[inline]
-pub fn main_return<DB: Database>(
-    context: &mut EvmContext<'_, DB>,
+pub fn main_return<DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
     call_result: InstructionResult,
     output: Output,
-    gas: &Gas,
+    gas: GasBreakdown,
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
-    // used gas with refund calculated.
-    let gas_refunded = gas.refunded() as u64;
-    let final_gas_used = gas.spend() - gas_refunded;
-
     // reset journal and return present state.
+    let access = context.journaled_state.access_summary();
+    #[cfg(feature = "journal-metrics")]
+    let journal_metrics = Some(context.journaled_state.metrics());
+    #[cfg(not(feature = "journal-metrics"))]
+    let journal_metrics = None;
     let (state, logs) = context.journaled_state.finalize();
 
     let result = match call_result.into() {
         SuccessOrHalt::Success(reason) => ExecutionResult::Success {
             reason,
-            gas_used: final_gas_used,
-            gas_refunded,
+            gas,
             logs,
             output,
         },
-        SuccessOrHalt::Revert => ExecutionResult::Revert {
-            gas_used: final_gas_used,
-            output: match output {
-                Output::Call(return_value) => return_value,
-                Output::Create(return_value, _) => return_value,
-            },
-        },
-        SuccessOrHalt::Halt(reason) => ExecutionResult::Halt {
-            reason,
-            gas_used: final_gas_used,
-        },
+        SuccessOrHalt::Revert => ExecutionResult::revert(gas, output.into_data()),
+        SuccessOrHalt::Halt(reason) => ExecutionResult::Halt { reason, gas },
         SuccessOrHalt::FatalExternalError => {
             return Err(EVMError::Database(context.error.take().unwrap()));
         }
@@ -149,13 +250,22 @@ pub fn main_return<DB: Database>(
         }
     };
 
-    Ok(ResultAndState { result, state })
+    Ok(ResultAndState {
+        result,
+        state,
+        requested_gas_limit: None,
+        fees: TxFees::default(),
+        halt_snapshot: None,
+        instruction_counts: None,
+        access,
+        journal_metrics,
+    })
 }
 
 /// Mainnet end handle does not change the output.
 #[inline]
-pub fn end_handle<DB: Database>(
-    _context: &mut EvmContext<'_, DB>,
+pub fn end_handle<DB: Database, J: Journal, EXT>(
+    _context: &mut EvmContext<'_, DB, J, EXT>,
     evm_output: Result<ResultAndState, EVMError<DB::Error>>,
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
     evm_output
@@ -163,7 +273,7 @@ pub fn end_handle<DB: Database>(
 
 #[cfg(test)]
 mod tests {
-    use revm_interpreter::primitives::CancunSpec;
+    use revm_interpreter::primitives::{BerlinSpec, CancunSpec};
 
     use super::*;
 
@@ -207,4 +317,30 @@ mod tests {
         assert_eq!(gas.spend(), 10);
         assert_eq!(gas.refunded(), 0);
     }
+
+    #[test]
+    fn test_gas_accounting() {
+        let mut env = Env::default();
+        env.tx.gas_limit = 100;
+        env.tx.gas_price = U256::from(10);
+        env.block.basefee = U256::from(7);
+
+        let mut gas = Gas::new(100);
+        gas.record_cost(40);
+        gas.set_refund(4);
+
+        let accounting = GasAccounting::new::<CancunSpec>(&env, &gas);
+        assert_eq!(accounting.spent, 36);
+        assert_eq!(accounting.refunded, 4);
+        assert_eq!(accounting.priority_fee, U256::from(3));
+        assert_eq!(accounting.base_fee, U256::from(7));
+        assert_eq!(accounting.beneficiary_fee(), U256::from(108));
+        assert_eq!(accounting.base_fee_amount(), U256::from(252));
+
+        // Before London whole gas price goes to the beneficiary.
+        let accounting = GasAccounting::new::<BerlinSpec>(&env, &gas);
+        assert_eq!(accounting.priority_fee, U256::from(10));
+        assert_eq!(accounting.base_fee, U256::ZERO);
+        assert_eq!(accounting.beneficiary_fee(), U256::from(360));
+    }
 }
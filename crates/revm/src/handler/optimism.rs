@@ -1,16 +1,47 @@
 //! Handler related to Optimism chain
 
-use super::mainnet;
+use super::{mainnet, GasAccounting, Handler};
 use crate::{
     interpreter::{return_ok, return_revert, Gas, InstructionResult},
     optimism,
     primitives::{
-        db::Database, Account, EVMError, Env, ExecutionResult, Halt, HashMap, InvalidTransaction,
-        Output, ResultAndState, Spec, SpecId::REGOLITH, U256,
+        db::Database,
+        Account, Address, EVMError, Env, ExecutionResult, GasBreakdown, Halt, HashMap,
+        InvalidTransaction, Output, ResultAndState, Spec,
+        SpecId::{ISTHMUS, REGOLITH},
+        TxFees, U256,
     },
-    EvmContext,
+    EvmContext, Journal,
 };
-use core::ops::Mul;
+use alloc::boxed::Box;
+
+/// Registers the Optimism handles on top of the given handler.
+///
+/// Covers deposit transactions (minting, gas reporting, refunds, mint persistence on failure),
+/// the L1 data fee and the operator fee charged to the caller of regular transactions, and the
+/// fee vaults, crediting them with the L1 data fee, the base fee and the operator fee. The L1
+/// data fee follows the Bedrock, Ecotone or Fjord formula depending on `SPEC`, see
+/// [crate::optimism::L1BlockInfo::calculate_tx_l1_cost]. The operator fee is charged as of
+/// Isthmus, see [crate::optimism::L1BlockInfo::operator_fee_charge].
+///
+/// Callers are not aliased: the `from` of a deposit transaction is already aliased during
+/// derivation when it is sent by an L1 contract, so [Handler::alias_caller] keeps the mainnet
+/// behavior. Use [crate::optimism::apply_l1_to_l2_alias] in it only to simulate messages from
+/// unaliased L1 senders.
+pub fn optimism_handle_register<SPEC: Spec, DB: Database, J: Journal, EXT>(
+    handler: &mut Handler<DB, J, EXT>,
+) {
+    handler.call_return = handle_call_return::<SPEC>;
+    // we reinburse caller the same was as in mainnet.
+    // Refund is calculated differently then mainnet.
+    handler.calculate_gas_refund = calculate_gas_refund::<SPEC>;
+    handler.deduct_caller = deduct_caller::<SPEC, DB, J, EXT>;
+    handler.reimburse_caller = reimburse_caller::<SPEC, DB, J, EXT>;
+    handler.reward_beneficiary = reward_beneficiary::<SPEC, DB, J, EXT>;
+    // In case of halt of deposit transaction return Error.
+    handler.main_return = main_return::<SPEC, DB, J, EXT>;
+    handler.end = end_handle::<SPEC, DB, J, EXT>;
+}
 
 /// Handle output of the transaction
 #[inline]
@@ -89,18 +120,145 @@ pub fn calculate_gas_refund<SPEC: Spec>(env: &Env, gas: &Gas) -> u64 {
     }
 }
 
+/// If the transaction is not a deposit transaction, subtract the L1 data fee and the operator
+/// fee (`l1_cost`) from the caller's balance directly after minting the requested amount of ETH.
+pub fn remove_l1_cost<DB: Database, J: Journal>(
+    is_deposit: bool,
+    tx_caller: Address,
+    l1_cost: U256,
+    db: &mut DB,
+    journal: &mut J,
+) -> Result<(), EVMError<DB::Error>> {
+    if is_deposit {
+        return Ok(());
+    }
+    let acc = journal
+        .load_account(tx_caller, db)
+        .map_err(EVMError::Database)?
+        .0;
+    if l1_cost.gt(&acc.info.balance) {
+        return Err(EVMError::Transaction(
+            InvalidTransaction::LackOfFundForMaxFee {
+                fee: Box::new(l1_cost),
+                balance: Box::new(acc.info.balance),
+            },
+        ));
+    }
+    acc.info.balance = acc.info.balance.saturating_sub(l1_cost);
+    Ok(())
+}
+
+/// If the transaction is a deposit with a `mint` value, add the mint value
+/// in wei to the caller's balance. This should be persisted to the database
+/// prior to the rest of execution.
+pub fn commit_mint_value<DB: Database, J: Journal>(
+    tx_caller: Address,
+    tx_mint: Option<u128>,
+    db: &mut DB,
+    journal: &mut J,
+) -> Result<(), EVMError<DB::Error>> {
+    if let Some(mint) = tx_mint {
+        // Minted value can't overflow the balance as it is bounded by the total supply.
+        journal
+            .mint(tx_caller, U256::from(mint), db)
+            .map_err(EVMError::Database)?;
+        journal.checkpoint();
+    }
+    Ok(())
+}
+
+/// Deducts the gas cost from the caller like mainnet.
+///
+/// Before it, the mint value of a deposit transaction is added to the caller. A regular
+/// transaction is charged the L1 data fee and the operator fee of its gas limit, which are added
+/// to [EvmContext::fees]. The L1 block info is stored in [EvmContext::l1_block_info].
+#[inline]
+pub fn deduct_caller<SPEC: Spec, DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
+    gas_cost: U256,
+) -> Result<(), EVMError<DB::Error>> {
+    if context.env.cfg.optimism {
+        let tx_caller = context.env.tx.caller;
+        commit_mint_value(
+            tx_caller,
+            context.env.tx.optimism.mint,
+            context.db,
+            &mut context.journaled_state,
+        )?;
+
+        // The L1 cost is only computed for non-deposit transactions.
+        let is_deposit = context.env.tx.optimism.source_hash.is_some();
+        if !is_deposit {
+            let l1_block_info = optimism::L1BlockInfo::try_fetch(context.db, SPEC::SPEC_ID)
+                .map_err(EVMError::Database)?;
+
+            let Some(enveloped_tx) = &context.env.tx.optimism.enveloped_tx else {
+                panic!("[OPTIMISM] Failed to load enveloped transaction.");
+            };
+            let l1_cost = l1_block_info.calculate_tx_l1_cost::<SPEC>(enveloped_tx);
+            context.fees.l1_gas_used = l1_block_info.data_gas::<SPEC>(enveloped_tx);
+            context.fees.l1_fee = l1_cost;
+            // The operator fee of the unspent gas is reimbursed after execution.
+            let operator_fee = l1_block_info.operator_fee_charge(context.env.tx.gas_limit);
+            context.fees.operator_fee = operator_fee;
+
+            remove_l1_cost(
+                is_deposit,
+                tx_caller,
+                l1_cost.saturating_add(operator_fee),
+                context.db,
+                &mut context.journaled_state,
+            )?;
+
+            // storage l1 block info for later use.
+            context.l1_block_info = Some(l1_block_info);
+        }
+    }
+    mainnet::deduct_caller(context, gas_cost)
+}
+
+/// Reimburse the caller like mainnet, together with the operator fee of the gas that was not
+/// spent by a regular transaction. [TxFees::operator_fee] is set to the fee of the spent gas.
+#[inline]
+pub fn reimburse_caller<SPEC: Spec, DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
+    accounting: &GasAccounting,
+) -> Result<(), EVMError<DB::Error>> {
+    mainnet::handle_reimburse_caller::<SPEC, DB, J, EXT>(context, accounting)?;
+
+    let is_deposit = context.env.tx.optimism.source_hash.is_some();
+    if context.env.cfg.optimism && !is_deposit && SPEC::enabled(ISTHMUS) {
+        let Some(l1_block_info) = &context.l1_block_info else {
+            panic!("[OPTIMISM] Failed to load L1 block information.");
+        };
+        let operator_fee = l1_block_info.operator_fee_charge(accounting.spent);
+        let operator_fee_refund = context.fees.operator_fee.saturating_sub(operator_fee);
+        context.fees.operator_fee = operator_fee;
+
+        let (caller_account, _) = context
+            .journaled_state
+            .load_account(context.env.tx.caller, context.db)
+            .map_err(EVMError::Database)?;
+        caller_account.info.balance = caller_account
+            .info
+            .balance
+            .saturating_add(operator_fee_refund);
+    }
+    Ok(())
+}
+
 /// Reward beneficiary with gas fee.
 #[inline]
-pub fn reward_beneficiary<SPEC: Spec, DB: Database>(
-    context: &mut EvmContext<'_, DB>,
-    gas: &Gas,
+pub fn reward_beneficiary<SPEC: Spec, DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
+    accounting: &GasAccounting,
 ) -> Result<(), EVMError<DB::Error>> {
     let is_deposit = context.env.cfg.optimism && context.env.tx.optimism.source_hash.is_some();
     let disable_coinbase_tip = context.env.cfg.optimism && is_deposit;
 
     // transfer fee to coinbase/beneficiary.
     if !disable_coinbase_tip {
-        mainnet::reward_beneficiary::<SPEC, DB>(context, gas)?;
+        mainnet::reward_beneficiary::<SPEC, DB, J, EXT>(context, accounting)?;
     }
 
     if context.env.cfg.optimism && !is_deposit {
@@ -134,24 +292,33 @@ pub fn reward_beneficiary<SPEC: Spec, DB: Database>(
             panic!("[OPTIMISM] Failed to load Base Fee Vault account");
         };
         base_fee_vault_account.mark_touch();
-        base_fee_vault_account.info.balance += context
-            .env
-            .block
-            .basefee
-            .mul(U256::from(gas.spend() - gas.refunded() as u64));
+        base_fee_vault_account.info.balance += accounting.base_fee_amount();
+
+        // Send the operator fee of the spent gas to the Operator Fee Vault.
+        if SPEC::enabled(ISTHMUS) {
+            let operator_fee = l1_block_info.operator_fee_charge(accounting.spent);
+            let Ok((operator_fee_vault_account, _)) = context
+                .journaled_state
+                .load_account(optimism::OPERATOR_FEE_RECIPIENT, context.db)
+            else {
+                panic!("[OPTIMISM] Failed to load Operator Fee Vault account");
+            };
+            operator_fee_vault_account.mark_touch();
+            operator_fee_vault_account.info.balance += operator_fee;
+        }
     }
     Ok(())
 }
 
 /// Main return handle, returns the output of the transaction.
 #[inline]
-pub fn main_return<SPEC: Spec, DB: Database>(
-    context: &mut EvmContext<'_, DB>,
+pub fn main_return<SPEC: Spec, DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
     call_result: InstructionResult,
     output: Output,
-    gas: &Gas,
+    gas: GasBreakdown,
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
-    let result = mainnet::main_return::<DB>(context, call_result, output, gas)?;
+    let result = mainnet::main_return::<DB, J, EXT>(context, call_result, output, gas)?;
 
     if result.result.is_halt() {
         // Post-regolith, if the transaction is a deposit transaction and it haults,
@@ -170,8 +337,8 @@ pub fn main_return<SPEC: Spec, DB: Database>(
 /// Optimism end handle changes output if the transaction is a deposit transaction.
 /// Deposit transaction can't be reverted and is always successful.
 #[inline]
-pub fn end_handle<SPEC: Spec, DB: Database>(
-    context: &mut EvmContext<'_, DB>,
+pub fn end_handle<SPEC: Spec, DB: Database, J: Journal, EXT>(
+    context: &mut EvmContext<'_, DB, J, EXT>,
     evm_output: Result<ResultAndState, EVMError<DB::Error>>,
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
     evm_output.or_else(|err| {
@@ -217,18 +384,31 @@ pub fn end_handle<SPEC: Spec, DB: Database>(
                 .optimism
                 .is_system_transaction
                 .unwrap_or(false);
-            let gas_used = if SPEC::enabled(REGOLITH) || !is_system_tx {
-                context.env().tx.gas_limit
+            let gas_limit = context.env().tx.gas_limit;
+            let gas = if SPEC::enabled(REGOLITH) || !is_system_tx {
+                GasBreakdown {
+                    execution: gas_limit,
+                    ..Default::default()
+                }
             } else {
-                0
+                GasBreakdown {
+                    returned: gas_limit,
+                    ..Default::default()
+                }
             };
 
             Ok(ResultAndState {
                 result: ExecutionResult::Halt {
                     reason: Halt::FailedDeposit,
-                    gas_used,
+                    gas,
                 },
                 state,
+                requested_gas_limit: None,
+                fees: TxFees::default(),
+                halt_snapshot: None,
+                instruction_counts: None,
+                access: Default::default(),
+                journal_metrics: None,
             })
         } else {
             Err(err)
@@ -238,10 +418,108 @@ pub fn end_handle<SPEC: Spec, DB: Database>(
 
 #[cfg(test)]
 mod tests {
-    use crate::primitives::{BedrockSpec, RegolithSpec, B256};
+    use crate::{
+        db::InMemoryDB,
+        primitives::{state::AccountInfo, BedrockSpec, RegolithSpec, SpecId, B256},
+        JournaledState,
+    };
 
     use super::*;
 
+    #[test]
+    fn test_commit_mint_value() {
+        let caller = Address::ZERO;
+        let mint_value = Some(1u128);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 0,
+                balance: U256::from(100),
+                code_hash: B256::ZERO,
+                code: None,
+            },
+        );
+        let mut journal = JournaledState::new(SpecId::BERLIN, vec![]);
+        journal
+            .initial_account_load(caller, &[U256::from(100)], &mut db)
+            .unwrap();
+        assert!(commit_mint_value(caller, mint_value, &mut db, &mut journal).is_ok(),);
+
+        // Check the account balance is updated.
+        let (account, _) = journal.load_account(caller, &mut db).unwrap();
+        assert_eq!(account.info.balance, U256::from(101));
+
+        // No mint value should be a no-op.
+        assert!(commit_mint_value(caller, None, &mut db, &mut journal).is_ok(),);
+        let (account, _) = journal.load_account(caller, &mut db).unwrap();
+        assert_eq!(account.info.balance, U256::from(101));
+    }
+
+    #[test]
+    fn test_remove_l1_cost_non_deposit() {
+        let caller = Address::ZERO;
+        let mut db = InMemoryDB::default();
+        let mut journal = JournaledState::new(SpecId::BERLIN, vec![]);
+        let slots = &[U256::from(100)];
+        journal
+            .initial_account_load(caller, slots, &mut db)
+            .unwrap();
+        assert!(remove_l1_cost(true, caller, U256::ZERO, &mut db, &mut journal).is_ok(),);
+    }
+
+    #[test]
+    fn test_remove_l1_cost() {
+        let caller = Address::ZERO;
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 0,
+                balance: U256::from(100),
+                code_hash: B256::ZERO,
+                code: None,
+            },
+        );
+        let mut journal = JournaledState::new(SpecId::BERLIN, vec![]);
+        journal
+            .initial_account_load(caller, &[U256::from(100)], &mut db)
+            .unwrap();
+        assert!(remove_l1_cost(false, caller, U256::from(1), &mut db, &mut journal).is_ok(),);
+
+        // Check the account balance is updated.
+        let (account, _) = journal.load_account(caller, &mut db).unwrap();
+        assert_eq!(account.info.balance, U256::from(99));
+    }
+
+    #[test]
+    fn test_remove_l1_cost_lack_of_funds() {
+        let caller = Address::ZERO;
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 0,
+                balance: U256::from(100),
+                code_hash: B256::ZERO,
+                code: None,
+            },
+        );
+        let mut journal = JournaledState::new(SpecId::BERLIN, vec![]);
+        journal
+            .initial_account_load(caller, &[U256::from(100)], &mut db)
+            .unwrap();
+        assert_eq!(
+            remove_l1_cost(false, caller, U256::from(101), &mut db, &mut journal),
+            Err(EVMError::Transaction(
+                InvalidTransaction::LackOfFundForMaxFee {
+                    fee: Box::new(U256::from(101)),
+                    balance: Box::new(U256::from(100)),
+                },
+            ))
+        );
+    }
+
     #[test]
     fn test_revert_gas() {
         let mut env = Env::default();
@@ -315,4 +593,23 @@ mod tests {
         assert_eq!(gas.spend(), 100);
         assert_eq!(gas.refunded(), 0);
     }
+
+    #[test]
+    fn test_deposit_first_frame_tx_kind() {
+        use crate::{handler::FirstFrameInputs, interpreter::TxKind};
+
+        let mut env = Env::default();
+        env.cfg.optimism = true;
+        env.tx.optimism.source_hash = Some(B256::ZERO);
+        let FirstFrameInputs::Call(inputs) = mainnet::first_frame_inputs(&env, 100) else {
+            panic!("expected call");
+        };
+        assert_eq!(inputs.tx_kind, Some(TxKind::Deposit));
+
+        env.tx.optimism.source_hash = None;
+        let FirstFrameInputs::Call(inputs) = mainnet::first_frame_inputs(&env, 100) else {
+            panic!("expected call");
+        };
+        assert_eq!(inputs.tx_kind, Some(TxKind::Ethereum));
+    }
 }
@@ -1,9 +1,7 @@
-use core::ops::Range;
-
 use crate::{
     interpreter::{CallInputs, CreateInputs, Interpreter},
-    primitives::{db::Database, Address, Bytes, B256, U256},
-    EvmContext,
+    primitives::{db::Database, Address, BlockSummary, Bytes, Env, ResultAndState, B256, U256},
+    EvmContext, Journal, JournaledState,
 };
 use auto_impl::auto_impl;
 
@@ -16,7 +14,7 @@ mod instruction;
 mod noop;
 
 pub use instruction::inspector_instruction;
-use revm_interpreter::InterpreterResult;
+use revm_interpreter::{CallOutcome, CreateOutcome, InterpreterResult};
 /// [Inspector] implementations.
 pub mod inspectors {
     #[cfg(feature = "std")]
@@ -29,13 +27,17 @@ pub mod inspectors {
 
 /// EVM [Interpreter] callbacks.
 #[auto_impl(&mut, Box)]
-pub trait Inspector<DB: Database> {
+pub trait Inspector<DB: Database, J: Journal = JournaledState, EXT = ()> {
     /// Called before the interpreter is initialized.
     ///
     /// If `interp.instruction_result` is set to anything other than [crate::interpreter::InstructionResult::Continue] then the execution of the interpreter
     /// is skipped.
     #[inline]
-    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB>) {
+    fn initialize_interp(
+        &mut self,
+        interp: &mut Interpreter,
+        context: &mut EvmContext<'_, DB, J, EXT>,
+    ) {
         let _ = interp;
         let _ = context;
     }
@@ -49,7 +51,7 @@ pub trait Inspector<DB: Database> {
     ///
     /// To get the current opcode, use `interp.current_opcode()`.
     #[inline]
-    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB>) {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB, J, EXT>) {
         let _ = interp;
         let _ = context;
     }
@@ -58,7 +60,7 @@ pub trait Inspector<DB: Database> {
     #[inline]
     fn log(
         &mut self,
-        context: &mut EvmContext<'_, DB>,
+        context: &mut EvmContext<'_, DB, J, EXT>,
         address: &Address,
         topics: &[B256],
         data: &Bytes,
@@ -74,20 +76,44 @@ pub trait Inspector<DB: Database> {
     /// Setting `interp.instruction_result` to anything other than [crate::interpreter::InstructionResult::Continue] alters the execution
     /// of the interpreter.
     #[inline]
-    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB>) {
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB, J, EXT>) {
         let _ = interp;
         let _ = context;
     }
 
+    /// Called before JUMP or JUMPI at `pc` of `address` is executed, `condition` is `None` for
+    /// JUMP.
+    ///
+    /// Can be used to record control-flow edges. Destination and condition can be modified,
+    /// and returning `false` forbids the jump, halting the frame with
+    /// [crate::interpreter::InstructionResult::InvalidJump].
+    #[inline]
+    fn jump(
+        &mut self,
+        context: &mut EvmContext<'_, DB, J, EXT>,
+        address: Address,
+        pc: usize,
+        dest: &mut U256,
+        condition: Option<&mut U256>,
+    ) -> bool {
+        let _ = context;
+        let _ = address;
+        let _ = pc;
+        let _ = dest;
+        let _ = condition;
+        true
+    }
+
     /// Called whenever a call to a contract is about to start.
     ///
-    /// InstructionResulting anything other than [crate::interpreter::InstructionResult::Continue] overrides the result of the call.
+    /// Also called for the call of the transaction itself, before the first frame is created.
+    /// Returning a [CallOutcome] skips the call and uses the outcome as its result.
     #[inline]
     fn call(
         &mut self,
-        context: &mut EvmContext<'_, DB>,
+        context: &mut EvmContext<'_, DB, J, EXT>,
         inputs: &mut CallInputs,
-    ) -> Option<(InterpreterResult, Range<usize>)> {
+    ) -> Option<CallOutcome> {
         let _ = context;
         let _ = inputs;
         None
@@ -100,7 +126,7 @@ pub trait Inspector<DB: Database> {
     #[inline]
     fn call_end(
         &mut self,
-        context: &mut EvmContext<'_, DB>,
+        context: &mut EvmContext<'_, DB, J, EXT>,
         result: InterpreterResult,
     ) -> InterpreterResult {
         let _ = context;
@@ -109,13 +135,14 @@ pub trait Inspector<DB: Database> {
 
     /// Called when a contract is about to be created.
     ///
-    /// InstructionResulting anything other than [crate::interpreter::InstructionResult::Continue] overrides the result of the creation.
+    /// Also called for create transactions, before the first frame is created.
+    /// Returning a [CreateOutcome] skips the creation and uses the outcome as its result.
     #[inline]
     fn create(
         &mut self,
-        context: &mut EvmContext<'_, DB>,
+        context: &mut EvmContext<'_, DB, J, EXT>,
         inputs: &mut CreateInputs,
-    ) -> Option<(InterpreterResult, Option<Address>)> {
+    ) -> Option<CreateOutcome> {
         let _ = context;
         let _ = inputs;
         None
@@ -128,7 +155,7 @@ pub trait Inspector<DB: Database> {
     #[inline]
     fn create_end(
         &mut self,
-        context: &mut EvmContext<'_, DB>,
+        context: &mut EvmContext<'_, DB, J, EXT>,
         result: InterpreterResult,
         address: Option<Address>,
     ) -> (InterpreterResult, Option<Address>) {
@@ -143,4 +170,39 @@ pub trait Inspector<DB: Database> {
         let _ = target;
         let _ = value;
     }
+
+    /// Called before anything in the block is executed.
+    ///
+    /// Block level hooks are only called by [crate::EVM::inspect_multiple] and
+    /// [crate::EVM::inspect_block].
+    #[inline]
+    fn begin_block(&mut self, env: &Env) {
+        let _ = env;
+    }
+
+    /// Called before the transaction is executed, `env.tx` is set to it.
+    #[inline]
+    fn begin_tx(&mut self, env: &Env) {
+        let _ = env;
+    }
+
+    /// Called after the transaction is executed, before its state is committed.
+    ///
+    /// `summary` contains cumulative results of the block up to and including this transaction.
+    #[inline]
+    fn end_tx(&mut self, env: &Env, result: &ResultAndState, summary: &BlockSummary) {
+        let _ = env;
+        let _ = result;
+        let _ = summary;
+    }
+
+    /// Called after all transactions and block operations are applied.
+    ///
+    /// Also called if execution of the block failed, `summary` then contains the transactions
+    /// executed before the failure.
+    #[inline]
+    fn end_block(&mut self, env: &Env, summary: &BlockSummary) {
+        let _ = env;
+        let _ = summary;
+    }
 }
@@ -1,10 +1,12 @@
 use crate::{
     inspectors::GasInspector,
-    interpreter::{opcode, CallInputs, CreateInputs, Interpreter, InterpreterResult},
+    interpreter::{
+        opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+        InterpreterResult,
+    },
     primitives::{db::Database, hex, Address, U256},
-    EvmContext, Inspector,
+    EvmContext, Inspector, Journal,
 };
-use core::ops::Range;
 use serde_json::json;
 use std::io::Write;
 
@@ -43,14 +45,18 @@ impl TracerEip3155 {
     }
 }
 
-impl<DB: Database> Inspector<DB> for TracerEip3155 {
-    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB>) {
+impl<DB: Database, J: Journal, EXT> Inspector<DB, J, EXT> for TracerEip3155 {
+    fn initialize_interp(
+        &mut self,
+        interp: &mut Interpreter,
+        context: &mut EvmContext<'_, DB, J, EXT>,
+    ) {
         self.gas_inspector.initialize_interp(interp, context);
     }
 
     // get opcode by calling `interp.contract.opcode(interp.program_counter())`.
     // all other information can be obtained from interp.
-    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB>) {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB, J, EXT>) {
         self.gas_inspector.step(interp, context);
         self.stack = interp.stack.data().clone();
         self.pc = interp.program_counter();
@@ -59,7 +65,7 @@ impl<DB: Database> Inspector<DB> for TracerEip3155 {
         self.gas = interp.gas.remaining();
     }
 
-    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB>) {
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<'_, DB, J, EXT>) {
         self.gas_inspector.step_end(interp, context);
         if self.skip {
             self.skip = false;
@@ -74,15 +80,15 @@ impl<DB: Database> Inspector<DB> for TracerEip3155 {
 
     fn call(
         &mut self,
-        _context: &mut EvmContext<'_, DB>,
+        _context: &mut EvmContext<'_, DB, J, EXT>,
         _inputs: &mut CallInputs,
-    ) -> Option<(InterpreterResult, Range<usize>)> {
+    ) -> Option<CallOutcome> {
         None
     }
 
     fn call_end(
         &mut self,
-        context: &mut EvmContext<'_, DB>,
+        context: &mut EvmContext<'_, DB, J, EXT>,
         result: InterpreterResult,
     ) -> InterpreterResult {
         let result = self.gas_inspector.call_end(context, result);
@@ -103,15 +109,15 @@ impl<DB: Database> Inspector<DB> for TracerEip3155 {
 
     fn create(
         &mut self,
-        _context: &mut EvmContext<'_, DB>,
+        _context: &mut EvmContext<'_, DB, J, EXT>,
         _inputs: &mut CreateInputs,
-    ) -> Option<(InterpreterResult, Option<Address>)> {
+    ) -> Option<CreateOutcome> {
         None
     }
 
     fn create_end(
         &mut self,
-        context: &mut EvmContext<'_, DB>,
+        context: &mut EvmContext<'_, DB, J, EXT>,
         result: InterpreterResult,
         address: Option<Address>,
     ) -> (InterpreterResult, Option<Address>) {
@@ -3,7 +3,7 @@
 use crate::{
     interpreter::InterpreterResult,
     primitives::{db::Database, Address},
-    EvmContext, Inspector,
+    EvmContext, Inspector, Journal,
 };
 
 /// Helper [Inspector] that keeps track of gas.
@@ -24,11 +24,11 @@ impl GasInspector {
     }
 }
 
-impl<DB: Database> Inspector<DB> for GasInspector {
+impl<DB: Database, J: Journal, EXT> Inspector<DB, J, EXT> for GasInspector {
     fn initialize_interp(
         &mut self,
         interp: &mut crate::interpreter::Interpreter,
-        _context: &mut EvmContext<'_, DB>,
+        _context: &mut EvmContext<'_, DB, J, EXT>,
     ) {
         self.gas_remaining = interp.gas.limit();
     }
@@ -36,7 +36,7 @@ impl<DB: Database> Inspector<DB> for GasInspector {
     fn step_end(
         &mut self,
         interp: &mut crate::interpreter::Interpreter,
-        _context: &mut EvmContext<'_, DB>,
+        _context: &mut EvmContext<'_, DB, J, EXT>,
     ) {
         let last_gas = core::mem::replace(&mut self.gas_remaining, interp.gas.remaining());
         self.last_gas_cost = last_gas.saturating_sub(self.last_gas_cost);
@@ -44,7 +44,7 @@ impl<DB: Database> Inspector<DB> for GasInspector {
 
     fn call_end(
         &mut self,
-        _context: &mut EvmContext<'_, DB>,
+        _context: &mut EvmContext<'_, DB, J, EXT>,
         mut result: InterpreterResult,
     ) -> InterpreterResult {
         if result.result.is_error() {
@@ -56,7 +56,7 @@ impl<DB: Database> Inspector<DB> for GasInspector {
 
     fn create_end(
         &mut self,
-        _context: &mut EvmContext<'_, DB>,
+        _context: &mut EvmContext<'_, DB, J, EXT>,
         result: InterpreterResult,
         address: Option<Address>,
     ) -> (InterpreterResult, Option<Address>) {
@@ -66,11 +66,11 @@ impl<DB: Database> Inspector<DB> for GasInspector {
 
 #[cfg(test)]
 mod tests {
-    use core::ops::Range;
-
     use crate::{
         inspectors::GasInspector,
-        interpreter::{CallInputs, CreateInputs, Interpreter, InterpreterResult},
+        interpreter::{
+            CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, InterpreterResult,
+        },
         primitives::{Address, Bytes, B256},
         Database, EvmContext, Inspector,
     };
@@ -116,7 +116,7 @@ mod tests {
             &mut self,
             context: &mut EvmContext<'_, DB>,
             call: &mut CallInputs,
-        ) -> Option<(InterpreterResult, Range<usize>)> {
+        ) -> Option<CallOutcome> {
             self.gas_inspector.call(context, call)
         }
 
@@ -132,7 +132,7 @@ mod tests {
             &mut self,
             context: &mut EvmContext<'_, DB>,
             call: &mut CreateInputs,
-        ) -> Option<(InterpreterResult, Option<Address>)> {
+        ) -> Option<CreateOutcome> {
             self.gas_inspector.create(context, call);
             None
         }
@@ -1,17 +1,17 @@
-use crate::EVMImpl;
+use crate::{EVMImpl, Journal};
 use alloc::boxed::Box;
 use revm_interpreter::{
-    opcode::{BoxedInstruction, Instruction},
+    opcode::BoxedInstruction,
     primitives::{db::Database, Spec},
     InstructionResult, Interpreter,
 };
 
 /// Outer closure that calls Inspector for every instruction.
-pub fn inspector_instruction<'a, SPEC: Spec + 'static, DB: Database>(
-    instruction: Instruction<EVMImpl<'a, SPEC, DB>>,
-) -> BoxedInstruction<'a, EVMImpl<'a, SPEC, DB>> {
+pub fn inspector_instruction<'a, SPEC: Spec + 'static, DB: Database, J: Journal, EXT>(
+    instruction: impl Fn(&mut Interpreter, &mut EVMImpl<'a, SPEC, DB, J, EXT>) + 'a,
+) -> BoxedInstruction<'a, EVMImpl<'a, SPEC, DB, J, EXT>> {
     Box::new(
-        move |interpreter: &mut Interpreter, host: &mut EVMImpl<'a, SPEC, DB>| {
+        move |interpreter: &mut Interpreter, host: &mut EVMImpl<'a, SPEC, DB, J, EXT>| {
             if let Some(inspector) = host.inspector.as_mut() {
                 // SAFETY: as the PC was already incremented we need to subtract 1 to preserve the
                 // old Inspector behavior.
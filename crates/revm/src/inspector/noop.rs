@@ -1,7 +1,7 @@
-use crate::{Database, Inspector};
+use crate::{Database, Inspector, Journal};
 
 /// Dummy [Inspector], helpful as standalone replacement.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct NoOpInspector;
 
-impl<DB: Database> Inspector<DB> for NoOpInspector {}
+impl<DB: Database, J: Journal, EXT> Inspector<DB, J, EXT> for NoOpInspector {}
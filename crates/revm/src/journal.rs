@@ -0,0 +1,614 @@
+use crate::{
+    interpreter::{InstructionResult, SelfDestructResult},
+    primitives::{
+        db::Database, AccessSummary, Account, Address, Bytecode, Log, Spec, SpecId, State, U256,
+    },
+    JournalCheckpoint, JournaledState,
+};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// State of the transaction that changes can be reverted to checkpoints of.
+///
+/// [JournaledState] is the default implementation. Another one can be plugged into
+/// [crate::EVMImpl] to keep the state in different data structures, for example persistent maps
+/// that are cheap to snapshot. Accounts and storage slots need to be loaded from the database
+/// before they are accessed, loaded ones are warm.
+pub trait Journal: Clone + fmt::Debug {
+    /// Creates an empty journal for the spec with the precompile addresses warm.
+    ///
+    /// Precompile addresses are sorted.
+    fn new(spec: SpecId, precompile_addresses: Vec<Address>) -> Self;
+
+    /// Returns the spec the journal was created with.
+    fn spec(&self) -> SpecId;
+
+    /// Sets the precompile addresses that are warm, addresses are sorted.
+    fn set_precompile_addresses(&mut self, precompile_addresses: Vec<Address>);
+
+    /// Sets whether touched empty accounts don't exist, by default as of Spurious Dragon.
+    fn set_state_clear(&mut self, state_clear: bool);
+
+    /// Returns the loaded accounts.
+    fn state(&mut self) -> &mut State;
+
+    /// Returns the loaded account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the account is not loaded.
+    fn account(&self, address: Address) -> &Account;
+
+    /// Returns the number of open checkpoints.
+    fn depth(&self) -> u64;
+
+    /// Opens a checkpoint that changes can be reverted to.
+    fn checkpoint(&mut self) -> JournalCheckpoint;
+
+    /// Commits the changes made since the last checkpoint.
+    fn checkpoint_commit(&mut self);
+
+    /// Reverts the changes made since the checkpoint.
+    fn checkpoint_revert(&mut self, checkpoint: JournalCheckpoint);
+
+    /// Opens a checkpoint for the creation of the account at `address`, transferring `balance`
+    /// to it from `caller`.
+    fn create_account_checkpoint<SPEC: Spec>(
+        &mut self,
+        caller: Address,
+        address: Address,
+        balance: U256,
+    ) -> Result<JournalCheckpoint, InstructionResult>;
+
+    /// Marks the loaded account as touched.
+    fn touch(&mut self, address: &Address);
+
+    /// Loads the account and the storage slots without journaling the load, they are warm for
+    /// the rest of the transaction.
+    fn initial_account_load<DB: Database>(
+        &mut self,
+        address: Address,
+        slots: &[U256],
+        db: &mut DB,
+    ) -> Result<&mut Account, DB::Error>;
+
+    /// Loads the accounts and storage slots of the access list like
+    /// [Journal::initial_account_load].
+    fn load_warm<DB: Database>(
+        &mut self,
+        access_list: &[(Address, Vec<U256>)],
+        db: &mut DB,
+    ) -> Result<(), DB::Error> {
+        for (address, slots) in access_list {
+            self.initial_account_load(*address, slots, db)?;
+        }
+        Ok(())
+    }
+
+    /// Loads the account, returns it and whether it was cold.
+    fn load_account<DB: Database>(
+        &mut self,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<(&mut Account, bool), DB::Error>;
+
+    /// Loads the account, returns whether it was cold and whether it exists.
+    fn load_account_exist<DB: Database>(
+        &mut self,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<(bool, bool), DB::Error>;
+
+    /// Loads the account with its code, returns it and whether it was cold.
+    fn load_code<DB: Database>(
+        &mut self,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<(&mut Account, bool), DB::Error>;
+
+    /// Loads the storage slot of the loaded account, returns its value and whether it was cold.
+    fn sload<DB: Database>(
+        &mut self,
+        address: Address,
+        key: U256,
+        db: &mut DB,
+    ) -> Result<(U256, bool), DB::Error>;
+
+    /// Stores the storage slot of the loaded account, returns its original, present and new
+    /// values and whether it was cold.
+    fn sstore<DB: Database>(
+        &mut self,
+        address: Address,
+        key: U256,
+        new: U256,
+        db: &mut DB,
+    ) -> Result<(U256, U256, U256, bool), DB::Error>;
+
+    /// Reads the transient storage slot.
+    fn tload(&mut self, address: Address, key: U256) -> U256;
+
+    /// Stores the transient storage slot.
+    fn tstore(&mut self, address: Address, key: U256, new: U256);
+
+    /// Sets the code of the loaded account.
+    fn set_code(&mut self, address: Address, code: Bytecode);
+
+    /// Increments the nonce of the loaded account, returns the new nonce or `None` if it would
+    /// overflow.
+    fn inc_nonce(&mut self, address: Address) -> Option<u64>;
+
+    /// Transfers the balance between the accounts.
+    fn transfer<DB: Database>(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        balance: U256,
+        db: &mut DB,
+    ) -> Result<(), InstructionResult>;
+
+    /// Adds the amount to the balance of the account, returns false if it would overflow.
+    fn mint<DB: Database>(
+        &mut self,
+        address: Address,
+        amount: U256,
+        db: &mut DB,
+    ) -> Result<bool, DB::Error>;
+
+    /// Subtracts the amount from the balance of the account, returns false if it is too low.
+    fn burn<DB: Database>(
+        &mut self,
+        address: Address,
+        amount: U256,
+        db: &mut DB,
+    ) -> Result<bool, DB::Error>;
+
+    /// Destroys the account and transfers its balance to the target.
+    fn selfdestruct<DB: Database>(
+        &mut self,
+        address: Address,
+        target: Address,
+        db: &mut DB,
+    ) -> Result<SelfDestructResult, DB::Error>;
+
+    /// Adds the log.
+    fn log(&mut self, log: Log);
+
+    /// Returns the accounts and storage slots read and written since the last finalize.
+    fn access_summary(&self) -> AccessSummary;
+
+    /// Returns the counters of the loads, stores and creations since the last finalize.
+    ///
+    /// Journals that don't count them return zeros.
+    #[cfg(feature = "journal-metrics")]
+    fn metrics(&self) -> crate::primitives::JournalMetrics {
+        crate::primitives::JournalMetrics::default()
+    }
+
+    /// Returns the changed state and the logs, and clears the journal for the next
+    /// transaction.
+    fn finalize(&mut self) -> (State, Vec<Log>);
+}
+
+impl Journal for JournaledState {
+    fn new(spec: SpecId, precompile_addresses: Vec<Address>) -> Self {
+        JournaledState::new(spec, precompile_addresses)
+    }
+
+    fn spec(&self) -> SpecId {
+        self.spec
+    }
+
+    fn set_precompile_addresses(&mut self, precompile_addresses: Vec<Address>) {
+        self.precompile_addresses = precompile_addresses;
+    }
+
+    fn set_state_clear(&mut self, state_clear: bool) {
+        self.state_clear = state_clear;
+    }
+
+    fn state(&mut self) -> &mut State {
+        JournaledState::state(self)
+    }
+
+    fn account(&self, address: Address) -> &Account {
+        JournaledState::account(self, address)
+    }
+
+    fn depth(&self) -> u64 {
+        JournaledState::depth(self)
+    }
+
+    fn checkpoint(&mut self) -> JournalCheckpoint {
+        JournaledState::checkpoint(self)
+    }
+
+    fn checkpoint_commit(&mut self) {
+        JournaledState::checkpoint_commit(self)
+    }
+
+    fn checkpoint_revert(&mut self, checkpoint: JournalCheckpoint) {
+        JournaledState::checkpoint_revert(self, checkpoint)
+    }
+
+    fn create_account_checkpoint<SPEC: Spec>(
+        &mut self,
+        caller: Address,
+        address: Address,
+        balance: U256,
+    ) -> Result<JournalCheckpoint, InstructionResult> {
+        JournaledState::create_account_checkpoint::<SPEC>(self, caller, address, balance)
+    }
+
+    fn touch(&mut self, address: &Address) {
+        JournaledState::touch(self, address)
+    }
+
+    fn initial_account_load<DB: Database>(
+        &mut self,
+        address: Address,
+        slots: &[U256],
+        db: &mut DB,
+    ) -> Result<&mut Account, DB::Error> {
+        JournaledState::initial_account_load(self, address, slots, db)
+    }
+
+    fn load_account<DB: Database>(
+        &mut self,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<(&mut Account, bool), DB::Error> {
+        JournaledState::load_account(self, address, db)
+    }
+
+    fn load_account_exist<DB: Database>(
+        &mut self,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<(bool, bool), DB::Error> {
+        JournaledState::load_account_exist(self, address, db)
+    }
+
+    fn load_code<DB: Database>(
+        &mut self,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<(&mut Account, bool), DB::Error> {
+        JournaledState::load_code(self, address, db)
+    }
+
+    fn sload<DB: Database>(
+        &mut self,
+        address: Address,
+        key: U256,
+        db: &mut DB,
+    ) -> Result<(U256, bool), DB::Error> {
+        JournaledState::sload(self, address, key, db)
+    }
+
+    fn sstore<DB: Database>(
+        &mut self,
+        address: Address,
+        key: U256,
+        new: U256,
+        db: &mut DB,
+    ) -> Result<(U256, U256, U256, bool), DB::Error> {
+        JournaledState::sstore(self, address, key, new, db)
+    }
+
+    fn tload(&mut self, address: Address, key: U256) -> U256 {
+        JournaledState::tload(self, address, key)
+    }
+
+    fn tstore(&mut self, address: Address, key: U256, new: U256) {
+        JournaledState::tstore(self, address, key, new)
+    }
+
+    fn set_code(&mut self, address: Address, code: Bytecode) {
+        JournaledState::set_code(self, address, code)
+    }
+
+    fn inc_nonce(&mut self, address: Address) -> Option<u64> {
+        JournaledState::inc_nonce(self, address)
+    }
+
+    fn transfer<DB: Database>(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        balance: U256,
+        db: &mut DB,
+    ) -> Result<(), InstructionResult> {
+        JournaledState::transfer(self, from, to, balance, db)
+    }
+
+    fn mint<DB: Database>(
+        &mut self,
+        address: Address,
+        amount: U256,
+        db: &mut DB,
+    ) -> Result<bool, DB::Error> {
+        JournaledState::mint(self, address, amount, db)
+    }
+
+    fn burn<DB: Database>(
+        &mut self,
+        address: Address,
+        amount: U256,
+        db: &mut DB,
+    ) -> Result<bool, DB::Error> {
+        JournaledState::burn(self, address, amount, db)
+    }
+
+    fn selfdestruct<DB: Database>(
+        &mut self,
+        address: Address,
+        target: Address,
+        db: &mut DB,
+    ) -> Result<SelfDestructResult, DB::Error> {
+        JournaledState::selfdestruct(self, address, target, db)
+    }
+
+    fn log(&mut self, log: Log) {
+        JournaledState::log(self, log)
+    }
+
+    fn access_summary(&self) -> AccessSummary {
+        JournaledState::access_summary(self)
+    }
+
+    #[cfg(feature = "journal-metrics")]
+    fn metrics(&self) -> crate::primitives::JournalMetrics {
+        self.metrics
+    }
+
+    fn finalize(&mut self) -> (State, Vec<Log>) {
+        JournaledState::finalize(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{AccountInfo, Bytes, ExecutionResult, LatestSpec, TransactTo, KECCAK_EMPTY},
+        EVMImpl, Handler, Transact,
+    };
+
+    /// Journal that counts the checkpoints opened during the execution.
+    #[derive(Clone, Debug)]
+    struct CountingJournal {
+        inner: JournaledState,
+        checkpoints: usize,
+    }
+
+    impl Journal for CountingJournal {
+        fn new(spec: SpecId, precompile_addresses: Vec<Address>) -> Self {
+            Self {
+                inner: JournaledState::new(spec, precompile_addresses),
+                checkpoints: 0,
+            }
+        }
+
+        fn spec(&self) -> SpecId {
+            self.inner.spec()
+        }
+
+        fn set_precompile_addresses(&mut self, precompile_addresses: Vec<Address>) {
+            self.inner.set_precompile_addresses(precompile_addresses)
+        }
+
+        fn set_state_clear(&mut self, state_clear: bool) {
+            self.inner.set_state_clear(state_clear)
+        }
+
+        fn state(&mut self) -> &mut State {
+            self.inner.state()
+        }
+
+        fn account(&self, address: Address) -> &Account {
+            self.inner.account(address)
+        }
+
+        fn depth(&self) -> u64 {
+            self.inner.depth()
+        }
+
+        fn checkpoint(&mut self) -> JournalCheckpoint {
+            self.checkpoints += 1;
+            self.inner.checkpoint()
+        }
+
+        fn checkpoint_commit(&mut self) {
+            self.inner.checkpoint_commit()
+        }
+
+        fn checkpoint_revert(&mut self, checkpoint: JournalCheckpoint) {
+            self.inner.checkpoint_revert(checkpoint)
+        }
+
+        fn create_account_checkpoint<SPEC: Spec>(
+            &mut self,
+            caller: Address,
+            address: Address,
+            balance: U256,
+        ) -> Result<JournalCheckpoint, InstructionResult> {
+            self.checkpoints += 1;
+            self.inner
+                .create_account_checkpoint::<SPEC>(caller, address, balance)
+        }
+
+        fn touch(&mut self, address: &Address) {
+            self.inner.touch(address)
+        }
+
+        fn initial_account_load<DB: Database>(
+            &mut self,
+            address: Address,
+            slots: &[U256],
+            db: &mut DB,
+        ) -> Result<&mut Account, DB::Error> {
+            self.inner.initial_account_load(address, slots, db)
+        }
+
+        fn load_account<DB: Database>(
+            &mut self,
+            address: Address,
+            db: &mut DB,
+        ) -> Result<(&mut Account, bool), DB::Error> {
+            self.inner.load_account(address, db)
+        }
+
+        fn load_account_exist<DB: Database>(
+            &mut self,
+            address: Address,
+            db: &mut DB,
+        ) -> Result<(bool, bool), DB::Error> {
+            self.inner.load_account_exist(address, db)
+        }
+
+        fn load_code<DB: Database>(
+            &mut self,
+            address: Address,
+            db: &mut DB,
+        ) -> Result<(&mut Account, bool), DB::Error> {
+            self.inner.load_code(address, db)
+        }
+
+        fn sload<DB: Database>(
+            &mut self,
+            address: Address,
+            key: U256,
+            db: &mut DB,
+        ) -> Result<(U256, bool), DB::Error> {
+            self.inner.sload(address, key, db)
+        }
+
+        fn sstore<DB: Database>(
+            &mut self,
+            address: Address,
+            key: U256,
+            new: U256,
+            db: &mut DB,
+        ) -> Result<(U256, U256, U256, bool), DB::Error> {
+            self.inner.sstore(address, key, new, db)
+        }
+
+        fn tload(&mut self, address: Address, key: U256) -> U256 {
+            self.inner.tload(address, key)
+        }
+
+        fn tstore(&mut self, address: Address, key: U256, new: U256) {
+            self.inner.tstore(address, key, new)
+        }
+
+        fn set_code(&mut self, address: Address, code: Bytecode) {
+            self.inner.set_code(address, code)
+        }
+
+        fn inc_nonce(&mut self, address: Address) -> Option<u64> {
+            self.inner.inc_nonce(address)
+        }
+
+        fn transfer<DB: Database>(
+            &mut self,
+            from: &Address,
+            to: &Address,
+            balance: U256,
+            db: &mut DB,
+        ) -> Result<(), InstructionResult> {
+            self.inner.transfer(from, to, balance, db)
+        }
+
+        fn mint<DB: Database>(
+            &mut self,
+            address: Address,
+            amount: U256,
+            db: &mut DB,
+        ) -> Result<bool, DB::Error> {
+            self.inner.mint(address, amount, db)
+        }
+
+        fn burn<DB: Database>(
+            &mut self,
+            address: Address,
+            amount: U256,
+            db: &mut DB,
+        ) -> Result<bool, DB::Error> {
+            self.inner.burn(address, amount, db)
+        }
+
+        fn selfdestruct<DB: Database>(
+            &mut self,
+            address: Address,
+            target: Address,
+            db: &mut DB,
+        ) -> Result<SelfDestructResult, DB::Error> {
+            self.inner.selfdestruct(address, target, db)
+        }
+
+        fn log(&mut self, log: Log) {
+            self.inner.log(log)
+        }
+
+        fn access_summary(&self) -> AccessSummary {
+            self.inner.access_summary()
+        }
+
+        fn finalize(&mut self) -> (State, Vec<Log>) {
+            self.inner.finalize()
+        }
+    }
+
+    #[test]
+    fn executes_with_custom_journal() {
+        let callee = Address::with_last_byte(0x20);
+        let contract = Address::repeat_byte(0x10);
+        // Calls the callee and stores the result in slot 0.
+        let code = Bytecode::new_raw(Bytes::from(vec![
+            opcode::PUSH1,
+            0,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::PUSH1,
+            0x20,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::PUSH1,
+            0,
+            opcode::SSTORE,
+            opcode::STOP,
+        ]));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+        );
+        db.insert_account_info(
+            callee,
+            AccountInfo::new(U256::ZERO, 0, KECCAK_EMPTY, Bytecode::new()),
+        );
+
+        let mut env = crate::primitives::Env::default();
+        env.tx.transact_to = TransactTo::Call(contract);
+        env.tx.gas_limit = 100_000;
+
+        let mut evm = EVMImpl::<LatestSpec, _, CountingJournal>::new_with_handler(
+            &mut db,
+            &mut env,
+            None,
+            Handler::mainnet::<LatestSpec>(),
+        );
+        let result = evm.transact().unwrap();
+
+        assert!(matches!(result.result, ExecutionResult::Success { .. }));
+        assert_eq!(
+            result.state[&contract].storage[&U256::ZERO].present_value,
+            U256::from(1)
+        );
+        assert_eq!(evm.context.journaled_state.checkpoints, 2);
+    }
+}
@@ -1,10 +1,12 @@
 use crate::interpreter::{InstructionResult, SelfDestructResult};
 use crate::primitives::{
-    db::Database, hash_map::Entry, Account, Address, Bytecode, HashMap, Log, Spec, SpecId::*,
-    State, StorageSlot, TransientStorage, KECCAK_EMPTY, PRECOMPILE3, U256,
+    db::Database, hash_map::Entry, AccessSummary, Account, AccountInfo, Address, Bytecode, HashMap,
+    Log, Spec, SpecId::*, State, StorageSlot, TransientStorage, KECCAK_EMPTY, PRECOMPILE3, U256,
 };
 use alloc::vec::Vec;
 use core::mem;
+#[cfg(feature = "journal-metrics")]
+use revm_interpreter::primitives::JournalMetrics;
 use revm_interpreter::primitives::SpecId;
 
 /// JournalState is internal EVM state that is used to contain state and track changes to that state.
@@ -32,6 +34,21 @@ pub struct JournaledState {
     ///
     /// Note that addresses are sorted.
     pub precompile_addresses: Vec<Address>,
+    /// Whether touched empty accounts don't exist, set as of Spurious Dragon by [EIP-161].
+    /// Chains without state clearing unset it, see
+    /// [crate::primitives::CfgEnv::disable_state_clear].
+    ///
+    /// [EIP-161]: https://eips.ethereum.org/EIPS/eip-161
+    pub state_clear: bool,
+    /// Accounts and storage slots read since the last finalize, these are not reverted.
+    /// Writes are filled by [JournaledState::access_summary].
+    pub access: AccessSummary,
+    /// Accounts as they were loaded from the database, without code.
+    pub loaded_accounts: HashMap<Address, AccountInfo>,
+    /// Counters of the loads, stores and creations since the last finalize, these are not
+    /// reverted.
+    #[cfg(feature = "journal-metrics")]
+    pub metrics: JournalMetrics,
 }
 
 impl JournaledState {
@@ -54,6 +71,11 @@ impl JournaledState {
             depth: 0,
             spec,
             precompile_addresses,
+            state_clear: SpecId::enabled(spec, SPURIOUS_DRAGON),
+            access: AccessSummary::default(),
+            loaded_accounts: HashMap::new(),
+            #[cfg(feature = "journal-metrics")]
+            metrics: JournalMetrics::default(),
         }
     }
 
@@ -90,9 +112,55 @@ impl JournaledState {
         let logs = mem::take(&mut self.logs);
         self.journal = vec![vec![]];
         self.depth = 0;
+        self.access = AccessSummary::default();
+        self.loaded_accounts.clear();
+        #[cfg(feature = "journal-metrics")]
+        {
+            self.metrics = JournalMetrics::default();
+        }
         (state, logs)
     }
 
+    /// Returns the accounts and storage slots read since the last finalize, and the ones whose
+    /// present values differ from the loaded ones.
+    pub fn access_summary(&self) -> AccessSummary {
+        let mut access = self.access.clone();
+        for (address, account) in self.state.iter() {
+            if !account.is_touched() {
+                continue;
+            }
+            let changed = self.loaded_accounts.get(address).is_none_or(|loaded| {
+                loaded.balance != account.info.balance
+                    || loaded.nonce != account.info.nonce
+                    || loaded.code_hash != account.info.code_hash
+            });
+            if changed || account.is_created() || account.is_selfdestructed() {
+                access.accounts_written.insert(*address);
+            }
+            access.slots_written.extend(
+                account
+                    .storage
+                    .iter()
+                    .filter(|(_, slot)| slot.is_changed())
+                    .map(|(key, _)| (*address, *key)),
+            );
+        }
+        access
+    }
+
+    /// Loads the account from the database, remembering how it was loaded.
+    fn load_from_db<DB: Database>(
+        loaded_accounts: &mut HashMap<Address, AccountInfo>,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<Account, DB::Error> {
+        let info = db.basic(address)?;
+        loaded_accounts
+            .entry(address)
+            .or_insert_with(|| info.clone().unwrap_or_default().without_code());
+        Ok(info.map_or_else(Account::new_not_existing, Into::into))
+    }
+
     /// Returns the _loaded_ [Account] for the given address.
     ///
     /// This assumes that the account has already been loaded.
@@ -191,6 +259,52 @@ impl JournaledState {
         Ok(())
     }
 
+    /// Increases balance of the account by `amount`, creating new supply.
+    ///
+    /// The change is journaled and reverted together with the checkpoint it was made in.
+    /// Returns false and leaves the balance unchanged if it would overflow.
+    #[inline]
+    pub fn mint<DB: Database>(
+        &mut self,
+        address: Address,
+        amount: U256,
+        db: &mut DB,
+    ) -> Result<bool, DB::Error> {
+        self.load_account(address, db)?;
+        let account = self.state.get_mut(&address).unwrap();
+        let Some(balance) = account.info.balance.checked_add(amount) else {
+            return Ok(false);
+        };
+        account.info.balance = balance;
+        let journal = self.journal.last_mut().unwrap();
+        Self::touch_account(journal, &address, account);
+        journal.push(JournalEntry::BalanceMinted { address, amount });
+        Ok(true)
+    }
+
+    /// Decreases balance of the account by `amount`, removing it from supply.
+    ///
+    /// The change is journaled and reverted together with the checkpoint it was made in.
+    /// Returns false and leaves the balance unchanged if the balance is lower than `amount`.
+    #[inline]
+    pub fn burn<DB: Database>(
+        &mut self,
+        address: Address,
+        amount: U256,
+        db: &mut DB,
+    ) -> Result<bool, DB::Error> {
+        self.load_account(address, db)?;
+        let account = self.state.get_mut(&address).unwrap();
+        let Some(balance) = account.info.balance.checked_sub(amount) else {
+            return Ok(false);
+        };
+        account.info.balance = balance;
+        let journal = self.journal.last_mut().unwrap();
+        Self::touch_account(journal, &address, account);
+        journal.push(JournalEntry::BalanceBurned { address, amount });
+        Ok(true)
+    }
+
     /// Create account or return false if collision is detected.
     ///
     /// There are few steps done:
@@ -232,6 +346,11 @@ impl JournaledState {
             return Err(InstructionResult::CreateCollision);
         }
 
+        #[cfg(feature = "journal-metrics")]
+        {
+            self.metrics.account_creations += 1;
+        }
+
         // set account status to created.
         account.mark_created();
 
@@ -286,7 +405,7 @@ impl JournaledState {
         state: &mut State,
         transient_storage: &mut TransientStorage,
         journal_entries: Vec<JournalEntry>,
-        is_spurious_dragon_enabled: bool,
+        state_clear: bool,
     ) {
         for entry in journal_entries.into_iter().rev() {
             match entry {
@@ -294,7 +413,7 @@ impl JournaledState {
                     state.remove(&address);
                 }
                 JournalEntry::AccountTouched { address } => {
-                    if is_spurious_dragon_enabled && address == PRECOMPILE3 {
+                    if state_clear && address == PRECOMPILE3 {
                         continue;
                     }
                     // remove touched status
@@ -330,6 +449,12 @@ impl JournaledState {
                     let to = state.get_mut(&to).unwrap();
                     to.info.balance -= balance;
                 }
+                JournalEntry::BalanceMinted { address, amount } => {
+                    state.get_mut(&address).unwrap().info.balance -= amount;
+                }
+                JournalEntry::BalanceBurned { address, amount } => {
+                    state.get_mut(&address).unwrap().info.balance += amount;
+                }
                 JournalEntry::NonceChange { address } => {
                     state.get_mut(&address).unwrap().info.nonce -= 1;
                 }
@@ -394,7 +519,7 @@ impl JournaledState {
     /// Reverts all changes to state until given checkpoint.
     #[inline]
     pub fn checkpoint_revert(&mut self, checkpoint: JournalCheckpoint) {
-        let is_spurious_dragon_enabled = SpecId::enabled(self.spec, SPURIOUS_DRAGON);
+        let state_clear = self.state_clear;
         let state = &mut self.state;
         let transient_storage = &mut self.transient_storage;
         self.depth -= 1;
@@ -405,12 +530,7 @@ impl JournaledState {
             .rev()
             .take(leng - checkpoint.journal_i)
             .for_each(|cs| {
-                Self::journal_revert(
-                    state,
-                    transient_storage,
-                    mem::take(cs),
-                    is_spurious_dragon_enabled,
-                )
+                Self::journal_revert(state, transient_storage, mem::take(cs), state_clear)
             });
 
         self.logs.truncate(checkpoint.log_i);
@@ -500,11 +620,9 @@ impl JournaledState {
         // load or get account.
         let account = match self.state.entry(address) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(vac) => vac.insert(
-                db.basic(address)?
-                    .map(|i| i.into())
-                    .unwrap_or(Account::new_not_existing()),
-            ),
+            Entry::Vacant(vac) => {
+                vac.insert(Self::load_from_db(&mut self.loaded_accounts, address, db)?)
+            }
         };
         // preload storages.
         for slot in slots {
@@ -523,14 +641,11 @@ impl JournaledState {
         address: Address,
         db: &mut DB,
     ) -> Result<(&mut Account, bool), DB::Error> {
-        Ok(match self.state.entry(address) {
+        self.access.accounts_read.insert(address);
+        let (account, is_cold) = match self.state.entry(address) {
             Entry::Occupied(entry) => (entry.into_mut(), false),
             Entry::Vacant(vac) => {
-                let account = if let Some(account) = db.basic(address)? {
-                    account.into()
-                } else {
-                    Account::new_not_existing()
-                };
+                let account = Self::load_from_db(&mut self.loaded_accounts, address, db)?;
 
                 // journal loading of account. AccessList touch.
                 self.journal
@@ -543,7 +658,14 @@ impl JournaledState {
 
                 (vac.insert(account), is_cold)
             }
-        })
+        };
+        #[cfg(feature = "journal-metrics")]
+        if is_cold {
+            self.metrics.cold_account_loads += 1;
+        } else {
+            self.metrics.warm_account_loads += 1;
+        }
+        Ok((account, is_cold))
     }
 
     /// Load account from database to JournaledState.
@@ -555,10 +677,10 @@ impl JournaledState {
         address: Address,
         db: &mut DB,
     ) -> Result<(bool, bool), DB::Error> {
-        let is_spurious_dragon_enabled = SpecId::enabled(self.spec, SPURIOUS_DRAGON);
+        let state_clear = self.state_clear;
         let (acc, is_cold) = self.load_account(address, db)?;
 
-        let exist = if is_spurious_dragon_enabled {
+        let exist = if state_clear {
             !acc.is_empty()
         } else {
             let is_existing = !acc.is_loaded_as_not_existing();
@@ -600,6 +722,7 @@ impl JournaledState {
         key: U256,
         db: &mut DB,
     ) -> Result<(U256, bool), DB::Error> {
+        self.access.slots_read.insert((address, key));
         let account = self.state.get_mut(&address).unwrap(); // assume acc is warm
                                                              // only if account is created in this tx we can assume that storage is empty.
         let is_newly_created = account.is_created();
@@ -627,6 +750,12 @@ impl JournaledState {
                 (value, true)
             }
         };
+        #[cfg(feature = "journal-metrics")]
+        if load.1 {
+            self.metrics.cold_storage_loads += 1;
+        } else {
+            self.metrics.warm_storage_loads += 1;
+        }
         Ok(load)
     }
 
@@ -666,6 +795,14 @@ impl JournaledState {
             });
         // insert value into present state.
         slot.present_value = new;
+        #[cfg(feature = "journal-metrics")]
+        if present == U256::ZERO {
+            self.metrics.sstore_sets += 1;
+        } else if new == U256::ZERO {
+            self.metrics.sstore_clears += 1;
+        } else {
+            self.metrics.sstore_resets += 1;
+        }
         Ok((slot.previous_or_original_value, present, new, is_cold))
     }
 
@@ -759,6 +896,14 @@ pub enum JournalEntry {
         to: Address,
         balance: U256,
     },
+    /// Balance increased without a counterpart
+    /// Action: Add amount to the balance
+    /// Revert: Subtract amount from the balance
+    BalanceMinted { address: Address, amount: U256 },
+    /// Balance decreased without a counterpart
+    /// Action: Subtract amount from the balance
+    /// Revert: Add amount to the balance
+    BalanceBurned { address: Address, amount: U256 },
     /// Increment nonce
     /// Action: Increment nonce by one
     /// Revert: Decrement nonce by one
@@ -794,7 +939,29 @@ pub enum JournalEntry {
 
 /// SubRoutine checkpoint that will help us to go back from this
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JournalCheckpoint {
     log_i: usize,
     journal_i: usize,
 }
+
+impl JournalCheckpoint {
+    /// Creates a checkpoint at the given number of logs and journal entries, for
+    /// implementations of [crate::Journal].
+    #[inline]
+    pub fn new(log_i: usize, journal_i: usize) -> Self {
+        Self { log_i, journal_i }
+    }
+
+    /// Returns the number of logs at the checkpoint.
+    #[inline]
+    pub fn log_i(&self) -> usize {
+        self.log_i
+    }
+
+    /// Returns the number of journal entries at the checkpoint.
+    #[inline]
+    pub fn journal_i(&self) -> usize {
+        self.journal_i
+    }
+}
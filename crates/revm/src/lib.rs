@@ -10,29 +10,57 @@ compile_error!("`with-serde` feature has been renamed to `serde`.");
 #[macro_use]
 extern crate alloc;
 
+mod chain_evm;
+mod chain_spec;
+mod context_precompiles;
 pub mod db;
 mod evm;
 mod evm_context;
+mod evm_factory;
 mod evm_impl;
+mod execution_handle;
 mod frame;
 pub mod handler;
 mod inspector;
+mod journal;
 mod journaled_state;
+mod orderings;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod pending;
+mod rpc_error;
+mod validation;
 
 #[cfg(feature = "optimism")]
 pub mod optimism;
 
 pub type DummyStateDB = InMemoryDB;
+pub use chain_spec::ChainSpec;
+pub use context_precompiles::{ContextPrecompile, ContextStatefulPrecompile};
 #[cfg(feature = "std")]
 pub use db::{
     CacheState, DBBox, State, StateBuilder, StateDBBox, TransitionAccount, TransitionState,
 };
 pub use db::{Database, DatabaseCommit, DatabaseRef, InMemoryDB};
-pub use evm::{new, EVM};
+pub use evm::{new, BatchResult, ShadowMismatch, EVM};
 pub use evm_context::EvmContext;
-pub use evm_impl::{new_evm, EVMImpl, Transact, CALL_STACK_LIMIT};
+pub use evm_factory::{EvmFactory, Execution};
+pub use evm_impl::{
+    new_evm, new_evm_with_chain_spec, new_evm_with_external, EVMImpl, Transact, TransactExt,
+    CALL_STACK_LIMIT,
+};
+pub use execution_handle::{
+    Breakpoint, ExecutionFrames, ExecutionHandle, ExecutionSnapshot, SamplingProfile,
+};
 pub use frame::CallStackFrame;
+pub use journal::Journal;
 pub use journaled_state::{JournalCheckpoint, JournalEntry, JournaledState};
+pub use orderings::{shuffled_orders, InvalidOrder, OrderingOutcome};
+#[cfg(feature = "parallel")]
+pub use parallel::{transact_parallel, ParallelOutput};
+pub use pending::{PendingState, PendingTx};
+pub use rpc_error::RpcError;
+pub use validation::{validate_env, validate_env_against_state};
 
 // reexport `revm_precompiles`
 #[doc(inline)]
@@ -51,6 +79,9 @@ pub use inspector::{inspector_instruction, inspectors, Inspector};
 
 // export Optimism types, helpers, and constants
 #[cfg(feature = "optimism")]
-pub use optimism::{L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT};
+pub use optimism::{
+    apply_l1_to_l2_alias, undo_l1_to_l2_alias, L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT,
+    L1_FEE_RECIPIENT,
+};
 
-pub use handler::Handler;
+pub use handler::{ChainTx, FirstFrameInputs, GasAccounting, Handler};
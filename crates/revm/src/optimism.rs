@@ -1,5 +1,7 @@
 //! Optimism-specific constants, types, and helpers.
 
+mod fast_lz;
+
 use crate::primitives::{address, db::Database, Address, Bytes, Spec, SpecId, U256};
 use core::ops::Mul;
 
@@ -10,15 +12,57 @@ const L1_BASE_FEE_SLOT: U256 = U256::from_limbs([1u64, 0, 0, 0]);
 const L1_OVERHEAD_SLOT: U256 = U256::from_limbs([5u64, 0, 0, 0]);
 const L1_SCALAR_SLOT: U256 = U256::from_limbs([6u64, 0, 0, 0]);
 
+/// As of Ecotone, the base fee scalar and the blob base fee scalar are packed into slot 3.
+const ECOTONE_L1_FEE_SCALARS_SLOT: U256 = U256::from_limbs([3u64, 0, 0, 0]);
+const ECOTONE_L1_BLOB_BASE_FEE_SLOT: U256 = U256::from_limbs([7u64, 0, 0, 0]);
+
+/// Byte offsets of the packed scalars inside [ECOTONE_L1_FEE_SCALARS_SLOT].
+const BASE_FEE_SCALAR_OFFSET: usize = 16;
+const BLOB_BASE_FEE_SCALAR_OFFSET: usize = 20;
+
+/// As of Isthmus, the operator fee scalar and constant are packed into slot 8.
+const ISTHMUS_OPERATOR_FEE_PARAMS_SLOT: U256 = U256::from_limbs([8u64, 0, 0, 0]);
+
+/// Byte offsets of the packed parameters inside [ISTHMUS_OPERATOR_FEE_PARAMS_SLOT].
+const OPERATOR_FEE_SCALAR_OFFSET: usize = 20;
+const OPERATOR_FEE_CONSTANT_OFFSET: usize = 24;
+
+/// Parameters of the Fjord transaction size estimation, scaled by 1e6.
+const FJORD_MIN_TRANSACTION_SIZE_SCALED: u64 = 100 * 1_000_000;
+const FJORD_L1_COST_INTERCEPT: u64 = 42_585_600;
+const FJORD_L1_COST_FASTLZ_COEF: u64 = 836_500;
+
 /// The address of L1 fee recipient.
 pub const L1_FEE_RECIPIENT: Address = address!("420000000000000000000000000000000000001A");
 
 /// The address of the base fee recipient.
 pub const BASE_FEE_RECIPIENT: Address = address!("4200000000000000000000000000000000000019");
 
+/// The address of the operator fee recipient.
+pub const OPERATOR_FEE_RECIPIENT: Address = address!("420000000000000000000000000000000000001B");
+
 /// The address of the L1Block contract.
 pub const L1_BLOCK_CONTRACT: Address = address!("4200000000000000000000000000000000000015");
 
+/// Offset added to addresses of L1 contracts that send messages to L2.
+pub const L1_TO_L2_ALIAS_OFFSET: Address = address!("1111000000000000000000000000000000001111");
+
+/// Applies the L1 to L2 address aliasing to the address of an L1 contract.
+///
+/// It can be used in [crate::Handler::alias_caller] to simulate cross-domain messages.
+pub fn apply_l1_to_l2_alias(address: Address) -> Address {
+    let aliased = U256::from_be_slice(address.as_slice())
+        .wrapping_add(U256::from_be_slice(L1_TO_L2_ALIAS_OFFSET.as_slice()));
+    Address::from_word(aliased.to_be_bytes().into())
+}
+
+/// Reverts the L1 to L2 address aliasing, see [apply_l1_to_l2_alias].
+pub fn undo_l1_to_l2_alias(address: Address) -> Address {
+    let original = U256::from_be_slice(address.as_slice())
+        .wrapping_sub(U256::from_be_slice(L1_TO_L2_ALIAS_OFFSET.as_slice()));
+    Address::from_word(original.to_be_bytes().into())
+}
+
 /// L1 block info
 ///
 /// We can extract L1 epoch data from each L2 block, by looking at the `setL1BlockValues`
@@ -29,29 +73,82 @@ pub const L1_BLOCK_CONTRACT: Address = address!("4200000000000000000000000000000
 /// setL1BlockValues(uint64 _number, uint64 _timestamp, uint256 _basefee, bytes32 _hash,
 /// uint64 _sequenceNumber, bytes32 _batcherHash, uint256 _l1FeeOverhead, uint256 _l1FeeScalar)
 ///
+/// As of Ecotone, `setL1BlockValuesEcotone` replaces the overhead and the scalar with a
+/// base fee scalar and a blob base fee scalar, and adds the blob base fee of the L1 block.
+/// As of Isthmus, the contract also holds the operator fee scalar and constant.
+///
 /// For now, we only care about the fields necessary for L1 cost calculation.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct L1BlockInfo {
     /// The base fee of the L1 origin block.
     pub l1_base_fee: U256,
-    /// The current L1 fee overhead.
+    /// The current L1 fee overhead. Only used before Ecotone.
     pub l1_fee_overhead: U256,
-    /// The current L1 fee scalar.
+    /// The current L1 fee scalar. Only used before Ecotone.
     pub l1_fee_scalar: U256,
+    /// The blob base fee of the L1 origin block, as of Ecotone.
+    pub l1_blob_base_fee: U256,
+    /// The current L1 base fee scalar, as of Ecotone.
+    pub l1_base_fee_scalar: U256,
+    /// The current L1 blob base fee scalar, as of Ecotone.
+    pub l1_blob_base_fee_scalar: U256,
+    /// The current operator fee scalar, scaled by 1e6, as of Isthmus.
+    pub operator_fee_scalar: U256,
+    /// The current operator fee constant, as of Isthmus.
+    pub operator_fee_constant: U256,
 }
 
 impl L1BlockInfo {
     /// Try to fetch the L1 block info from the database.
-    pub fn try_fetch<DB: Database>(db: &mut DB) -> Result<L1BlockInfo, DB::Error> {
+    ///
+    /// Ecotone and Isthmus fields are only loaded if `spec_id` enables the fork.
+    pub fn try_fetch<DB: Database>(db: &mut DB, spec_id: SpecId) -> Result<L1BlockInfo, DB::Error> {
         let l1_base_fee = db.storage(L1_BLOCK_CONTRACT, L1_BASE_FEE_SLOT)?;
-        let l1_fee_overhead = db.storage(L1_BLOCK_CONTRACT, L1_OVERHEAD_SLOT)?;
-        let l1_fee_scalar = db.storage(L1_BLOCK_CONTRACT, L1_SCALAR_SLOT)?;
-
-        Ok(L1BlockInfo {
+        let mut info = L1BlockInfo {
             l1_base_fee,
-            l1_fee_overhead,
-            l1_fee_scalar,
-        })
+            ..Default::default()
+        };
+
+        if SpecId::enabled(spec_id, SpecId::ECOTONE) {
+            info.l1_blob_base_fee = db.storage(L1_BLOCK_CONTRACT, ECOTONE_L1_BLOB_BASE_FEE_SLOT)?;
+            let l1_fee_scalars = db
+                .storage(L1_BLOCK_CONTRACT, ECOTONE_L1_FEE_SCALARS_SLOT)?
+                .to_be_bytes::<32>();
+            info.l1_base_fee_scalar = U256::from_be_slice(
+                &l1_fee_scalars[BASE_FEE_SCALAR_OFFSET..BASE_FEE_SCALAR_OFFSET + 4],
+            );
+            info.l1_blob_base_fee_scalar = U256::from_be_slice(
+                &l1_fee_scalars[BLOB_BASE_FEE_SCALAR_OFFSET..BLOB_BASE_FEE_SCALAR_OFFSET + 4],
+            );
+        }
+
+        // The first Ecotone block still carries the Bedrock values, as the L1Block contract
+        // is upgraded within it.
+        if !SpecId::enabled(spec_id, SpecId::ECOTONE) || info.has_empty_ecotone_scalars() {
+            info.l1_fee_overhead = db.storage(L1_BLOCK_CONTRACT, L1_OVERHEAD_SLOT)?;
+            info.l1_fee_scalar = db.storage(L1_BLOCK_CONTRACT, L1_SCALAR_SLOT)?;
+        }
+
+        if SpecId::enabled(spec_id, SpecId::ISTHMUS) {
+            let operator_fee_params = db
+                .storage(L1_BLOCK_CONTRACT, ISTHMUS_OPERATOR_FEE_PARAMS_SLOT)?
+                .to_be_bytes::<32>();
+            info.operator_fee_scalar = U256::from_be_slice(
+                &operator_fee_params[OPERATOR_FEE_SCALAR_OFFSET..OPERATOR_FEE_CONSTANT_OFFSET],
+            );
+            info.operator_fee_constant =
+                U256::from_be_slice(&operator_fee_params[OPERATOR_FEE_CONSTANT_OFFSET..]);
+        }
+
+        Ok(info)
+    }
+
+    /// Returns `true` if none of the Ecotone fee parameters are set.
+    pub fn has_empty_ecotone_scalars(&self) -> bool {
+        self.l1_blob_base_fee.is_zero()
+            && self.l1_base_fee_scalar.is_zero()
+            && self.l1_blob_base_fee_scalar.is_zero()
     }
 
     /// Calculate the data gas for posting the transaction on L1. Calldata costs 16 gas per non-zero
@@ -59,7 +156,15 @@ impl L1BlockInfo {
     ///
     /// Prior to regolith, an extra 68 non-zero bytes were included in the rollup data costs to
     /// account for the empty signature.
+    ///
+    /// As of Fjord, the data gas is derived from the estimated compressed size of the transaction.
     pub fn data_gas<SPEC: Spec>(&self, input: &Bytes) -> U256 {
+        if SPEC::enabled(SpecId::FJORD) {
+            return Self::tx_estimated_size_fjord(input)
+                .saturating_mul(U256::from(NON_ZERO_BYTE_COST))
+                / U256::from(1_000_000);
+        }
+
         let mut rollup_data_gas_cost = U256::from(input.iter().fold(0, |acc, byte| {
             acc + if *byte == 0x00 {
                 ZERO_BYTE_COST
@@ -83,6 +188,17 @@ impl L1BlockInfo {
             return U256::ZERO;
         }
 
+        if SPEC::enabled(SpecId::FJORD) {
+            self.calculate_tx_l1_cost_fjord(input)
+        } else if SPEC::enabled(SpecId::ECOTONE) {
+            self.calculate_tx_l1_cost_ecotone::<SPEC>(input)
+        } else {
+            self.calculate_tx_l1_cost_bedrock::<SPEC>(input)
+        }
+    }
+
+    /// Bedrock L1 cost: `(data_gas + overhead) * base_fee * scalar / 1e6`.
+    fn calculate_tx_l1_cost_bedrock<SPEC: Spec>(&self, input: &Bytes) -> U256 {
         let rollup_data_gas_cost = self.data_gas::<SPEC>(input);
         rollup_data_gas_cost
             .saturating_add(self.l1_fee_overhead)
@@ -90,6 +206,57 @@ impl L1BlockInfo {
             .saturating_mul(self.l1_fee_scalar)
             / U256::from(1_000_000)
     }
+
+    /// Ecotone L1 cost: `data_gas * (16 * base_fee * base_fee_scalar + blob_base_fee *
+    /// blob_base_fee_scalar) / (16 * 1e6)`.
+    ///
+    /// Falls back to the Bedrock formula in the Ecotone activation block, where the L1Block
+    /// contract doesn't hold the new scalars yet.
+    fn calculate_tx_l1_cost_ecotone<SPEC: Spec>(&self, input: &Bytes) -> U256 {
+        if self.has_empty_ecotone_scalars() {
+            return self.calculate_tx_l1_cost_bedrock::<SPEC>(input);
+        }
+
+        let rollup_data_gas_cost = self.data_gas::<SPEC>(input);
+        rollup_data_gas_cost.saturating_mul(self.l1_fee_scaled_ecotone())
+            / U256::from(NON_ZERO_BYTE_COST * 1_000_000)
+    }
+
+    /// Fjord L1 cost: `estimated_size * (16 * base_fee * base_fee_scalar + blob_base_fee *
+    /// blob_base_fee_scalar) / 1e12`.
+    fn calculate_tx_l1_cost_fjord(&self, input: &Bytes) -> U256 {
+        Self::tx_estimated_size_fjord(input).saturating_mul(self.l1_fee_scaled_ecotone())
+            / U256::from(1_000_000_000_000u64)
+    }
+
+    /// Weighted sum of the L1 base fee and the L1 blob base fee.
+    fn l1_fee_scaled_ecotone(&self) -> U256 {
+        self.l1_base_fee
+            .saturating_mul(U256::from(NON_ZERO_BYTE_COST))
+            .saturating_mul(self.l1_base_fee_scalar)
+            .saturating_add(
+                self.l1_blob_base_fee
+                    .saturating_mul(self.l1_blob_base_fee_scalar),
+            )
+    }
+
+    /// Calculate the operator fee for `gas` execution gas: `gas * operator_fee_scalar / 1e6 +
+    /// operator_fee_constant`.
+    ///
+    /// It is zero before Isthmus, where the parameters are not loaded.
+    pub fn operator_fee_charge(&self, gas: u64) -> U256 {
+        (U256::from(gas).saturating_mul(self.operator_fee_scalar) / U256::from(1_000_000))
+            .saturating_add(self.operator_fee_constant)
+    }
+
+    /// Estimated size of the transaction once compressed in a batch, scaled by 1e6.
+    fn tx_estimated_size_fjord(input: &Bytes) -> U256 {
+        let fastlz_size = U256::from(fast_lz::flz_compress_len(input));
+        fastlz_size
+            .saturating_mul(U256::from(FJORD_L1_COST_FASTLZ_COEF))
+            .saturating_sub(U256::from(FJORD_L1_COST_INTERCEPT))
+            .max(U256::from(FJORD_MIN_TRANSACTION_SIZE_SCALED))
+    }
 }
 
 #[cfg(test)]
@@ -97,12 +264,24 @@ mod tests {
     use super::*;
     use crate::primitives::{bytes, specification::*};
 
+    #[test]
+    fn test_l1_to_l2_alias() {
+        let l1 = address!("ffffffffffffffffffffffffffffffffffffffff");
+        let aliased = apply_l1_to_l2_alias(l1);
+        assert_eq!(
+            aliased,
+            address!("1111000000000000000000000000000000001110")
+        );
+        assert_eq!(undo_l1_to_l2_alias(aliased), l1);
+    }
+
     #[test]
     fn test_data_gas_non_zero_bytes() {
         let l1_block_info = L1BlockInfo {
             l1_base_fee: U256::from(1_000_000),
             l1_fee_overhead: U256::from(1_000_000),
             l1_fee_scalar: U256::from(1_000_000),
+            ..Default::default()
         };
 
         // 0xFACADE = 6 nibbles = 3 bytes
@@ -127,6 +306,7 @@ mod tests {
             l1_base_fee: U256::from(1_000_000),
             l1_fee_overhead: U256::from(1_000_000),
             l1_fee_scalar: U256::from(1_000_000),
+            ..Default::default()
         };
 
         // 0xFA00CA00DE = 10 nibbles = 5 bytes
@@ -151,6 +331,7 @@ mod tests {
             l1_base_fee: U256::from(1_000),
             l1_fee_overhead: U256::from(1_000),
             l1_fee_scalar: U256::from(1_000),
+            ..Default::default()
         };
 
         let input = bytes!("FACADE");
@@ -167,4 +348,115 @@ mod tests {
         let gas_cost = l1_block_info.calculate_tx_l1_cost::<RegolithSpec>(&input);
         assert_eq!(gas_cost, U256::ZERO);
     }
+
+    #[test]
+    fn test_calculate_tx_l1_cost_ecotone() {
+        let mut l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_blob_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee_scalar: U256::from(1_000),
+            l1_fee_overhead: U256::from(1_000),
+            l1_fee_scalar: U256::from(1_000),
+            ..Default::default()
+        };
+
+        // 48 data gas * (16 * 1e6 + 1e6) / 16e6 = 51
+        let input = bytes!("FACADE");
+        let gas_cost = l1_block_info.calculate_tx_l1_cost::<EcotoneSpec>(&input);
+        assert_eq!(gas_cost, U256::from(51));
+
+        // Empty scalars in the activation block fall back to the Bedrock formula.
+        l1_block_info.l1_blob_base_fee = U256::ZERO;
+        l1_block_info.l1_base_fee_scalar = U256::ZERO;
+        l1_block_info.l1_blob_base_fee_scalar = U256::ZERO;
+        let gas_cost = l1_block_info.calculate_tx_l1_cost::<EcotoneSpec>(&input);
+        assert_eq!(gas_cost, U256::from(1048));
+
+        let input = bytes!("7FFACADE");
+        let gas_cost = l1_block_info.calculate_tx_l1_cost::<EcotoneSpec>(&input);
+        assert_eq!(gas_cost, U256::ZERO);
+    }
+
+    #[test]
+    fn test_calculate_tx_l1_cost_fjord() {
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_blob_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee_scalar: U256::from(1_000),
+            ..Default::default()
+        };
+
+        // Small transactions are charged for the minimum size:
+        // 100e6 * (16 * 1e6 + 1e6) / 1e12 = 1700
+        let input = bytes!("FACADE");
+        let gas_cost = l1_block_info.calculate_tx_l1_cost::<FjordSpec>(&input);
+        assert_eq!(gas_cost, U256::from(1700));
+        assert_eq!(
+            l1_block_info.data_gas::<FjordSpec>(&input),
+            U256::from(1600)
+        );
+
+        let input = bytes!("7FFACADE");
+        let gas_cost = l1_block_info.calculate_tx_l1_cost::<FjordSpec>(&input);
+        assert_eq!(gas_cost, U256::ZERO);
+    }
+
+    #[test]
+    fn test_try_fetch_ecotone() {
+        use crate::db::InMemoryDB;
+
+        let mut db = InMemoryDB::default();
+        let mut scalars = [0u8; 32];
+        scalars[BASE_FEE_SCALAR_OFFSET + 3] = 7;
+        scalars[BLOB_BASE_FEE_SCALAR_OFFSET + 3] = 9;
+        for (slot, value) in [
+            (L1_BASE_FEE_SLOT, U256::from(10)),
+            (ECOTONE_L1_BLOB_BASE_FEE_SLOT, U256::from(11)),
+            (ECOTONE_L1_FEE_SCALARS_SLOT, U256::from_be_bytes(scalars)),
+            (L1_OVERHEAD_SLOT, U256::from(12)),
+            (L1_SCALAR_SLOT, U256::from(13)),
+        ] {
+            db.insert_account_storage(L1_BLOCK_CONTRACT, slot, value)
+                .unwrap();
+        }
+
+        let info = L1BlockInfo::try_fetch(&mut db, SpecId::ECOTONE).unwrap();
+        assert_eq!(info.l1_base_fee, U256::from(10));
+        assert_eq!(info.l1_blob_base_fee, U256::from(11));
+        assert_eq!(info.l1_base_fee_scalar, U256::from(7));
+        assert_eq!(info.l1_blob_base_fee_scalar, U256::from(9));
+        assert_eq!(info.l1_fee_overhead, U256::ZERO);
+
+        let info = L1BlockInfo::try_fetch(&mut db, SpecId::CANYON).unwrap();
+        assert_eq!(info.l1_fee_overhead, U256::from(12));
+        assert_eq!(info.l1_fee_scalar, U256::from(13));
+        assert!(info.has_empty_ecotone_scalars());
+    }
+
+    #[test]
+    fn test_try_fetch_isthmus_operator_fee() {
+        use crate::db::InMemoryDB;
+
+        let mut db = InMemoryDB::default();
+        let mut params = [0u8; 32];
+        params[OPERATOR_FEE_SCALAR_OFFSET + 3] = 5;
+        params[31] = 7;
+        db.insert_account_storage(
+            L1_BLOCK_CONTRACT,
+            ISTHMUS_OPERATOR_FEE_PARAMS_SLOT,
+            U256::from_be_bytes(params),
+        )
+        .unwrap();
+
+        let info = L1BlockInfo::try_fetch(&mut db, SpecId::ISTHMUS).unwrap();
+        assert_eq!(info.operator_fee_scalar, U256::from(5));
+        assert_eq!(info.operator_fee_constant, U256::from(7));
+        // 1_000_000 * 5 / 1e6 + 7
+        assert_eq!(info.operator_fee_charge(1_000_000), U256::from(12));
+
+        let info = L1BlockInfo::try_fetch(&mut db, SpecId::FJORD).unwrap();
+        assert_eq!(info.operator_fee_charge(1_000_000), U256::ZERO);
+    }
 }
@@ -0,0 +1,165 @@
+//! Execution of the same transaction set in multiple orders.
+
+use crate::{
+    db::{CacheDB, Database, DatabaseRef},
+    primitives::{
+        AccountInfo, Address, BlockSummary, Bytecode, EVMError, ExecutionResult, ResultAndState,
+        B256, U256,
+    },
+};
+use alloc::vec::Vec;
+use core::{cell::RefCell, fmt};
+
+/// Outcome of executing transactions in one order, see [crate::EVM::transact_orderings].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderingOutcome<DBError> {
+    /// Order of execution, as indices into the transaction set.
+    pub order: Vec<usize>,
+    /// Results of the executed transactions, in order of execution.
+    pub results: Vec<ResultAndState>,
+    /// Summary of the executed transactions.
+    pub summary: BlockSummary,
+    /// Error of the transaction that stopped the execution of this order.
+    pub error: Option<EVMError<DBError>>,
+    /// Transactions whose result differs from their result in the first order, as indices into
+    /// the transaction set.
+    pub conflicts: Vec<usize>,
+}
+
+impl<DBError> OrderingOutcome<DBError> {
+    /// Returns result of the transaction with the given index in the transaction set.
+    pub fn result_of(&self, tx_index: usize) -> Option<&ExecutionResult> {
+        let position = self.order.iter().position(|&index| index == tx_index)?;
+        self.results.get(position).map(|output| &output.result)
+    }
+
+    /// Marks transactions whose result differs from the result in `first` outcome.
+    pub(crate) fn mark_conflicts(&mut self, first: &Self) {
+        self.conflicts = self
+            .order
+            .iter()
+            .zip(&self.results)
+            .filter(|(&index, output)| {
+                first
+                    .result_of(index)
+                    .is_some_and(|result| *result != output.result)
+            })
+            .map(|(&index, _)| index)
+            .collect();
+    }
+}
+
+/// Order passed to [crate::EVM::transact_orderings] refers to a transaction that is not in the
+/// transaction set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidOrder {
+    /// Position of the order in the orders.
+    pub order: usize,
+    /// Out of range index in the order.
+    pub index: usize,
+    /// Number of transactions in the set.
+    pub len: usize,
+}
+
+impl InvalidOrder {
+    /// Returns the first out of range index of `orders` for a set of `len` transactions.
+    pub(crate) fn check(orders: &[Vec<usize>], len: usize) -> Result<(), Self> {
+        for (order, indices) in orders.iter().enumerate() {
+            if let Some(&index) = indices.iter().find(|&&index| index >= len) {
+                return Err(Self { order, index, len });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidOrder {}
+
+impl fmt::Display for InvalidOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "order {} refers to transaction {} of {} transactions",
+            self.order, self.index, self.len
+        )
+    }
+}
+
+/// Deterministically generates `count` orders of `len` transactions from `seed`.
+///
+/// First order is always the original one, so conflicts are reported against it.
+pub fn shuffled_orders(len: usize, count: usize, seed: u64) -> Vec<Vec<usize>> {
+    // SplitMix64, good enough for order experiments and reproducible across platforms.
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    };
+
+    (0..count)
+        .map(|i| {
+            let mut order: Vec<usize> = (0..len).collect();
+            if i != 0 {
+                // Fisher-Yates shuffle.
+                for j in (1..len).rev() {
+                    order.swap(j, (next() % (j as u64 + 1)) as usize);
+                }
+            }
+            order
+        })
+        .collect()
+}
+
+/// Pre-state that is shared between orders.
+///
+/// It caches everything loaded from the underlying database and is never committed to, so
+/// data warmed by one order is reused by the others.
+pub(crate) struct SharedPreState<ExtDB: DatabaseRef>(RefCell<CacheDB<ExtDB>>);
+
+impl<ExtDB: DatabaseRef> SharedPreState<ExtDB> {
+    pub(crate) fn new(db: ExtDB) -> Self {
+        Self(RefCell::new(CacheDB::new(db)))
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for SharedPreState<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0.borrow_mut().basic(address)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0.borrow_mut().code_by_hash(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0.borrow_mut().storage(address, index)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.0.borrow_mut().block_hash(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shuffled_orders_are_deterministic_permutations() {
+        let orders = shuffled_orders(10, 4, 7);
+        assert_eq!(orders, shuffled_orders(10, 4, 7));
+        assert_eq!(orders[0], (0..10).collect::<Vec<_>>());
+        for order in &orders {
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+        }
+        assert_ne!(orders[1], orders[0]);
+    }
+}
@@ -0,0 +1,386 @@
+//! Optimistic parallel execution of the transactions of a block.
+//!
+//! Transactions are first executed in parallel against the state before the block, recording
+//! the accounts and storage slots they read. They are then validated in block order: a
+//! transaction is accepted if nothing it read was written by an earlier transaction, otherwise
+//! it is executed again on top of the earlier ones. Results are the same as if the
+//! transactions were executed and committed one by one.
+//!
+//! Fees paid to the block beneficiary are not counted as conflicts. The balance increase of
+//! the beneficiary is reapplied on top of the earlier transactions, unless the transaction
+//! could observe the beneficiary's balance.
+
+use crate::{
+    db::{CacheDB, Database, DatabaseCommit, DatabaseRef},
+    interpreter::{opcode, Interpreter},
+    new_evm,
+    primitives::{
+        AccountInfo, Address, Bytecode, EVMError, Env, ResultAndState, TransactTo, TxEnv, B256,
+        U256,
+    },
+    EvmContext, Inspector,
+};
+use std::{collections::HashSet, thread, vec::Vec};
+
+/// Output of [transact_parallel].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParallelOutput {
+    /// Results of the transactions in block order. State of every result is to be committed
+    /// after the states of the previous ones.
+    pub results: Vec<ResultAndState>,
+    /// Indices of the transactions that were executed again because of a conflict.
+    pub reexecuted: Vec<usize>,
+}
+
+/// Executes the transactions of a block on `db` using up to `threads` threads.
+///
+/// Block and config are taken from `env`. Execution stops at the first transaction that
+/// fails, as it would if the transactions were executed one by one.
+pub fn transact_parallel<DB>(
+    db: &DB,
+    env: &Env,
+    txs: &[TxEnv],
+    threads: usize,
+) -> Result<ParallelOutput, EVMError<DB::Error>>
+where
+    DB: DatabaseRef + Sync,
+    DB::Error: Send,
+{
+    let speculative = execute_speculatively(db, env, txs, threads.max(1));
+
+    let coinbase = env.block.coinbase;
+    let coinbase_before = db.basic_ref(coinbase).map_err(EVMError::Database)?;
+    let mut overlay = CacheDB::new(db);
+    let mut written = HashSet::new();
+    let mut output = ParallelOutput {
+        results: Vec::with_capacity(txs.len()),
+        reexecuted: Vec::new(),
+    };
+
+    for (index, (tx, speculative)) in txs.iter().zip(speculative).enumerate() {
+        let coinbase_now = overlay.basic(coinbase).map_err(EVMError::Database)?;
+        // Reads of the beneficiary are ignored only if just its balance was increased.
+        let fee_only_coinbase = !speculative.observed_coinbase
+            && tx.caller != coinbase
+            && tx.transact_to != TransactTo::Call(coinbase)
+            && same_except_balance(coinbase_before.as_ref(), coinbase_now.as_ref());
+        let conflict = speculative.reads.iter().any(|read| {
+            !(fee_only_coinbase && read.address() == coinbase) && written.contains(read)
+        });
+
+        let result = match (conflict, speculative.result) {
+            (false, Ok(mut result)) if fee_only_coinbase => {
+                if rebase_coinbase(&mut result, coinbase, &coinbase_before, &coinbase_now) {
+                    Ok(result)
+                } else {
+                    output.reexecuted.push(index);
+                    transact_on(&mut overlay, env, tx)
+                }
+            }
+            (false, result) => result,
+            (true, _) => {
+                output.reexecuted.push(index);
+                transact_on(&mut overlay, env, tx)
+            }
+        }?;
+
+        for (address, account) in result.state.iter() {
+            if !account.is_touched() {
+                continue;
+            }
+            let info = overlay.basic(*address).map_err(EVMError::Database)?;
+            if account.is_created()
+                || account.is_selfdestructed()
+                || !info.is_some_and(|info| same_info(&info, &account.info))
+            {
+                written.insert(Read::Account(*address));
+            }
+            for (slot, value) in account.storage.iter() {
+                if value.is_changed() {
+                    written.insert(Read::Storage(*address, *slot));
+                }
+            }
+        }
+        overlay.commit(result.state.clone());
+        output.results.push(result);
+    }
+    Ok(output)
+}
+
+/// Item of the state read by a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Read {
+    Account(Address),
+    Storage(Address, U256),
+}
+
+impl Read {
+    fn address(&self) -> Address {
+        match self {
+            Read::Account(address) | Read::Storage(address, _) => *address,
+        }
+    }
+}
+
+/// Transaction executed against the state before the block.
+struct Speculative<E> {
+    result: Result<ResultAndState, EVMError<E>>,
+    reads: HashSet<Read>,
+    observed_coinbase: bool,
+}
+
+fn execute_speculatively<DB>(
+    db: &DB,
+    env: &Env,
+    txs: &[TxEnv],
+    threads: usize,
+) -> Vec<Speculative<DB::Error>>
+where
+    DB: DatabaseRef + Sync,
+    DB::Error: Send,
+{
+    let mut executed: Vec<(usize, Speculative<DB::Error>)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                scope.spawn(move || {
+                    (worker..txs.len())
+                        .step_by(threads)
+                        .map(|index| (index, execute_recorded(db, env, &txs[index])))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("parallel execution panicked"))
+            .collect()
+    });
+    executed.sort_unstable_by_key(|(index, _)| *index);
+    executed
+        .into_iter()
+        .map(|(_, speculative)| speculative)
+        .collect()
+}
+
+fn execute_recorded<DB: DatabaseRef>(db: &DB, env: &Env, tx: &TxEnv) -> Speculative<DB::Error> {
+    let mut env = env.clone();
+    env.tx = tx.clone();
+    let mut recorder = ReadRecorder {
+        db,
+        reads: HashSet::new(),
+    };
+    let mut observer = CoinbaseObserver {
+        coinbase: env.block.coinbase,
+        observed: false,
+    };
+    let result = new_evm(&mut env, &mut recorder, Some(&mut observer)).transact();
+    Speculative {
+        result,
+        reads: recorder.reads,
+        observed_coinbase: observer.observed,
+    }
+}
+
+fn transact_on<DB: DatabaseRef>(
+    overlay: &mut CacheDB<&DB>,
+    env: &Env,
+    tx: &TxEnv,
+) -> Result<ResultAndState, EVMError<DB::Error>> {
+    let mut env = env.clone();
+    env.tx = tx.clone();
+    let result = new_evm(&mut env, overlay, None).transact();
+    result
+}
+
+/// Moves the balance increase of the beneficiary on top of its current balance.
+///
+/// Returns `false` if the beneficiary balance decreased.
+fn rebase_coinbase(
+    result: &mut ResultAndState,
+    coinbase: Address,
+    before: &Option<AccountInfo>,
+    now: &Option<AccountInfo>,
+) -> bool {
+    let Some(account) = result.state.get_mut(&coinbase) else {
+        return true;
+    };
+    let (Some(before), Some(now)) = (before, now) else {
+        return true;
+    };
+    let Some(increase) = account.info.balance.checked_sub(before.balance) else {
+        return false;
+    };
+    account.info.balance = now.balance.saturating_add(increase);
+    true
+}
+
+/// Returns `true` if both accounts exist, aren't empty and differ at most in balance.
+fn same_except_balance(before: Option<&AccountInfo>, now: Option<&AccountInfo>) -> bool {
+    match (before, now) {
+        (Some(before), Some(now)) => {
+            !before.is_empty()
+                && !now.is_empty()
+                && before.nonce == now.nonce
+                && before.code_hash == now.code_hash
+        }
+        _ => false,
+    }
+}
+
+fn same_info(a: &AccountInfo, b: &AccountInfo) -> bool {
+    a.balance == b.balance && a.nonce == b.nonce && a.code_hash == b.code_hash
+}
+
+/// Database that records the accounts and storage slots read from it.
+struct ReadRecorder<'a, DB> {
+    db: &'a DB,
+    reads: HashSet<Read>,
+}
+
+impl<DB: DatabaseRef> Database for ReadRecorder<'_, DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.reads.insert(Read::Account(address));
+        self.db.basic_ref(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.reads.insert(Read::Storage(address, index));
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+/// Detects instructions whose outcome depends on the balance of the beneficiary.
+struct CoinbaseObserver {
+    coinbase: Address,
+    observed: bool,
+}
+
+impl<DB: crate::Database> Inspector<DB> for CoinbaseObserver {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<'_, DB>) {
+        if self.observed {
+            return;
+        }
+        // Code of the beneficiary can spend its balance.
+        if interp.contract.address == self.coinbase {
+            self.observed = true;
+            return;
+        }
+        let address_index = match interp.current_opcode() {
+            opcode::BALANCE | opcode::EXTCODEHASH => 0,
+            // Account creation cost depends on the beneficiary being empty.
+            opcode::CALL | opcode::CALLCODE => 1,
+            _ => return,
+        };
+        self.observed = interp
+            .stack
+            .peek(address_index)
+            .is_ok_and(|word| Address::from_word(B256::from(word.to_be_bytes())) == self.coinbase);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{EmptyDB, InMemoryDB},
+        primitives::{Bytes, SpecId},
+    };
+
+    fn transfer(caller: Address, to: Address, nonce: u64) -> TxEnv {
+        TxEnv {
+            caller,
+            transact_to: TransactTo::Call(to),
+            value: U256::from(1_000),
+            gas_limit: 100_000,
+            gas_price: U256::from(10),
+            nonce: Some(nonce),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parallel_matches_serial_execution() {
+        let coinbase = Address::with_last_byte(0xC0);
+        let counter = Address::with_last_byte(0xAA);
+        let balance_reader = Address::with_last_byte(0xBB);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(coinbase, AccountInfo::from_balance(U256::from(1)));
+        // PUSH1 0 SLOAD PUSH1 1 ADD PUSH1 0 SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x00, 0x54, 0x60, 0x01, 0x01, 0x60, 0x00, 0x55,
+        ]));
+        db.insert_account_info(
+            counter,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+        );
+        // COINBASE BALANCE PUSH1 0 SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x41, 0x31, 0x60, 0x00, 0x55]));
+        db.insert_account_info(
+            balance_reader,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+        );
+        let senders: Vec<_> = (1..=6).map(Address::with_last_byte).collect();
+        for sender in &senders {
+            db.insert_account_info(
+                *sender,
+                AccountInfo::from_balance(U256::from(10u64.pow(18))),
+            );
+        }
+
+        let txs = vec![
+            transfer(senders[0], Address::with_last_byte(0x71), 0),
+            transfer(senders[1], Address::with_last_byte(0x72), 0),
+            // Same sender, depends on the first transaction.
+            transfer(senders[0], Address::with_last_byte(0x73), 1),
+            transfer(senders[2], counter, 0),
+            // Reads the counter written by the previous transaction.
+            transfer(senders[3], counter, 0),
+            // Reads the beneficiary balance including the earlier fees.
+            transfer(senders[4], balance_reader, 0),
+            transfer(senders[5], Address::with_last_byte(0x74), 0),
+        ];
+
+        let mut env = Env::default();
+        env.cfg.spec_id = SpecId::CANCUN;
+        env.block.coinbase = coinbase;
+        env.block.basefee = U256::from(7);
+
+        let parallel = transact_parallel(&db, &env, &txs, 3).unwrap();
+
+        let mut serial_db = CacheDB::new(&db);
+        let serial: Vec<_> = txs
+            .iter()
+            .map(|tx| {
+                let result = transact_on(&mut serial_db, &env, tx).unwrap();
+                serial_db.commit(result.state.clone());
+                result
+            })
+            .collect();
+
+        assert_eq!(parallel.results, serial);
+        assert_eq!(parallel.reexecuted, [2, 4, 5]);
+    }
+
+    #[test]
+    fn parallel_stops_at_invalid_transaction() {
+        let db = CacheDB::new(EmptyDB::default());
+        let mut env = Env::default();
+        env.block.coinbase = Address::with_last_byte(0xC0);
+        let caller = Address::with_last_byte(1);
+        let txs = [transfer(caller, Address::with_last_byte(2), 0)];
+        assert!(matches!(
+            transact_parallel(&db, &env, &txs, 2),
+            Err(EVMError::Transaction(_))
+        ));
+    }
+}
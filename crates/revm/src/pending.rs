@@ -0,0 +1,203 @@
+//! Pending state, latest state with pending transactions executed on top of it.
+
+use crate::{
+    db::{CacheDB, DatabaseCommit, DatabaseRef, SnapshotId},
+    evm_impl::new_evm,
+    primitives::{
+        db::WrapDatabaseRef, BlockEnv, EVMError, EVMResult, Env, ExecutionResult, ResultAndState,
+        TxEnv,
+    },
+};
+use alloc::vec::Vec;
+
+/// Pending transaction included in the [PendingState].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingTx {
+    /// The transaction.
+    pub tx: TxEnv,
+    /// Result of the transaction executed on top of the previous pending transactions.
+    pub result: ExecutionResult,
+    /// Snapshot of the state before the transaction.
+    snapshot: SnapshotId,
+}
+
+/// Latest state with pending transactions executed on top of it, similar to the `pending`
+/// block tag of nodes.
+///
+/// Pending transactions are committed to a [CacheDB] over the latest state as they arrive, so
+/// simulations against the pending state don't re-execute the pool. Removing a transaction
+/// re-executes only the transactions after it.
+#[derive(Debug)]
+pub struct PendingState<ExtDB: DatabaseRef> {
+    db: CacheDB<ExtDB>,
+    env: Env,
+    txs: Vec<PendingTx>,
+}
+
+impl<ExtDB: DatabaseRef> PendingState<ExtDB> {
+    /// Creates pending state over the latest state. Block of `env` is the pending block.
+    pub fn new(latest: ExtDB, env: Env) -> Self {
+        Self {
+            db: CacheDB::new(latest),
+            env,
+            txs: Vec::new(),
+        }
+    }
+
+    /// Returns the pending state.
+    pub fn db(&self) -> &CacheDB<ExtDB> {
+        &self.db
+    }
+
+    /// Returns the environment of the pending block.
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    /// Returns the included pending transactions, in execution order.
+    pub fn txs(&self) -> &[PendingTx] {
+        &self.txs
+    }
+
+    /// Returns gas used by the included pending transactions.
+    pub fn gas_used(&self) -> u64 {
+        self.txs.iter().map(|tx| tx.result.gas_used()).sum()
+    }
+
+    /// Executes the transaction on top of the pending state and includes it.
+    ///
+    /// Transaction that fails validation is not included and its error is returned.
+    pub fn push_tx(&mut self, tx: TxEnv) -> Result<&ExecutionResult, EVMError<ExtDB::Error>> {
+        let snapshot = self.db.snapshot();
+        self.env.tx = tx;
+        let result = new_evm(&mut self.env, &mut self.db, None).transact();
+        match result {
+            Ok(ResultAndState { result, state, .. }) => {
+                self.db.commit(state);
+                self.txs.push(PendingTx {
+                    tx: self.env.tx.clone(),
+                    result,
+                    snapshot,
+                });
+                Ok(&self.txs.last().unwrap().result)
+            }
+            Err(e) => {
+                self.db.revert_to(snapshot);
+                Err(e)
+            }
+        }
+    }
+
+    /// Keeps only the pending transactions for which `f` returns true.
+    ///
+    /// Transactions after the first removed one are re-executed. Returns transactions that
+    /// are no longer valid on top of the new pending state, they are not included anymore.
+    pub fn retain(
+        &mut self,
+        mut f: impl FnMut(&TxEnv) -> bool,
+    ) -> Result<Vec<TxEnv>, EVMError<ExtDB::Error>> {
+        let Some(first_removed) = self.txs.iter().position(|pending| !f(&pending.tx)) else {
+            return Ok(Vec::new());
+        };
+        self.db.revert_to(self.txs[first_removed].snapshot);
+        let removed = self.txs.split_off(first_removed);
+
+        let mut invalid = Vec::new();
+        for pending in removed.into_iter().skip(1) {
+            if !f(&pending.tx) {
+                continue;
+            }
+            match self.push_tx(pending.tx.clone()) {
+                Ok(_) => {}
+                Err(EVMError::Database(e)) => return Err(EVMError::Database(e)),
+                Err(_) => invalid.push(pending.tx),
+            }
+        }
+        Ok(invalid)
+    }
+
+    /// Rebases the pending state on the new latest state and pending block, for example after
+    /// a new block is imported.
+    ///
+    /// Returns the previously included transactions, so the ones that are still pending can be
+    /// pushed again.
+    pub fn reset(&mut self, latest: ExtDB, block: BlockEnv) -> Vec<TxEnv> {
+        self.db = CacheDB::new(latest);
+        self.env.block = block;
+        self.txs.drain(..).map(|pending| pending.tx).collect()
+    }
+
+    /// Executes the transaction against the pending state without including it.
+    pub fn simulate(&self, tx: TxEnv) -> EVMResult<ExtDB::Error> {
+        let mut env = self.env.clone();
+        env.tx = tx;
+        let mut db = WrapDatabaseRef(&self.db);
+        let result = new_evm(&mut env, &mut db, None).transact();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::EmptyDB,
+        primitives::{AccountInfo, Address, TransactTo, U256},
+    };
+
+    fn transfer(caller: Address, value: u64) -> TxEnv {
+        TxEnv {
+            caller,
+            transact_to: TransactTo::Call(Address::with_last_byte(0x20)),
+            value: U256::from(value),
+            gas_limit: 21_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pending_txs_are_applied_incrementally() {
+        let alice = Address::with_last_byte(0x10);
+        let bob = Address::with_last_byte(0x11);
+        let recipient = Address::with_last_byte(0x20);
+        let mut latest = CacheDB::new(EmptyDB::default());
+        latest.insert_account_info(alice, AccountInfo::from_balance(U256::from(100)));
+        latest.insert_account_info(bob, AccountInfo::from_balance(U256::from(10)));
+
+        let mut pending = PendingState::new(latest.clone(), Env::default());
+        assert!(pending.push_tx(transfer(alice, 60)).unwrap().is_success());
+        assert!(pending.push_tx(transfer(alice, 30)).unwrap().is_success());
+        assert!(pending.push_tx(transfer(bob, 10)).unwrap().is_success());
+        // Not enough balance left on top of pending state.
+        assert!(pending.push_tx(transfer(alice, 20)).is_err());
+        assert_eq!(pending.txs().len(), 3);
+        assert_eq!(pending.gas_used(), 63_000);
+
+        let balance = |pending: &PendingState<_>, address| {
+            pending.db().basic_ref(address).unwrap().unwrap().balance
+        };
+        assert_eq!(balance(&pending, recipient), U256::from(100));
+
+        // Simulation sees pending state and doesn't change it.
+        let result = pending.simulate(transfer(alice, 10)).unwrap();
+        assert!(result.result.is_success());
+        assert!(pending.simulate(transfer(alice, 11)).is_err());
+        assert_eq!(pending.txs().len(), 3);
+
+        // Removing the first transaction re-executes the rest.
+        let invalid = pending.retain(|tx| tx.value != U256::from(60)).unwrap();
+        assert!(invalid.is_empty());
+        assert_eq!(pending.txs().len(), 2);
+        assert_eq!(balance(&pending, alice), U256::from(70));
+        assert_eq!(balance(&pending, recipient), U256::from(40));
+
+        // New block includes some of the transactions.
+        latest.insert_account_info(alice, AccountInfo::from_balance(U256::from(20)));
+        let previous = pending.reset(latest, BlockEnv::default());
+        assert_eq!(previous.len(), 2);
+        assert!(pending.txs().is_empty());
+        assert!(pending.push_tx(previous[0].clone()).is_err());
+        assert!(pending.push_tx(previous[1].clone()).is_ok());
+        assert_eq!(balance(&pending, recipient), U256::from(10));
+    }
+}
@@ -0,0 +1,226 @@
+//! Errors as returned by geth and erigon for `eth_call` and `eth_estimateGas`.
+
+use crate::primitives::{
+    Bytes, EVMError, ExecutionResult, Halt, InvalidTransaction, OutOfGasError, RevertReason,
+};
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// JSON-RPC error with the code and message geth and erigon return for `eth_call` and
+/// `eth_estimateGas`, so clients matching on them work with revm-backed simulation.
+///
+/// Messages are the error strings of geth without the context it appends to some of them,
+/// for example the sender address after `nonce too low`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RpcError {
+    /// JSON-RPC error code.
+    pub code: i64,
+    /// Error message.
+    pub message: String,
+    /// Revert output, set for reverted executions.
+    pub data: Option<Bytes>,
+}
+
+impl RpcError {
+    /// Code of reverted executions.
+    pub const EXECUTION_REVERTED: i64 = 3;
+    /// Code of invalid transactions and halted executions.
+    pub const SERVER_ERROR: i64 = -32000;
+    /// Code of errors not caused by the transaction, like database errors.
+    pub const INTERNAL_ERROR: i64 = -32603;
+
+    fn server_error(message: &str) -> Self {
+        Self {
+            code: Self::SERVER_ERROR,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+
+    /// Error of the execution result, `None` if the execution succeeded.
+    pub fn from_result(result: &ExecutionResult) -> Option<Self> {
+        match result {
+            ExecutionResult::Success { .. } => None,
+            ExecutionResult::Revert { output, .. } => Some(Self::reverted(output.clone())),
+            ExecutionResult::Halt { reason, .. } => Some(Self::from_halt(*reason)),
+        }
+    }
+
+    /// Error of the reverted execution with its output. `Error(string)` reason is appended to
+    /// the message.
+    pub fn reverted(output: Bytes) -> Self {
+        let message = match RevertReason::decode(&output) {
+            Some(RevertReason::Error(reason)) => format!("execution reverted: {reason}"),
+            _ => "execution reverted".to_string(),
+        };
+        Self {
+            code: Self::EXECUTION_REVERTED,
+            message,
+            data: Some(output),
+        }
+    }
+
+    /// Error of the halted execution.
+    pub fn from_halt(halt: Halt) -> Self {
+        let message = match halt {
+            Halt::OutOfGas(OutOfGasError::InvalidOperand) => "gas uint64 overflow",
+            Halt::OutOfGas(_) => "out of gas",
+            Halt::OpcodeNotFound | Halt::NotActivated => "invalid opcode",
+            Halt::InvalidFEOpcode => "invalid opcode: INVALID",
+            Halt::InvalidJump => "invalid jump destination",
+            Halt::StackUnderflow => "stack underflow",
+            Halt::StackOverflow | Halt::EOFFunctionStackOverflow => "stack limit reached",
+            Halt::OutOfOffset => "return data out of bounds",
+            Halt::CreateCollision => "contract address collision",
+            Halt::PrecompileError => "precompile failed",
+            Halt::PrecompileInputTooLarge => "precompile input too large",
+            Halt::NonceOverflow => "nonce uint64 overflow",
+            Halt::CreateContractSizeLimit => "max code size exceeded",
+            Halt::CreateContractStartingWithEF => "invalid code: must not begin with 0xef",
+            Halt::CreateInitcodeSizeLimit => "max initcode size exceeded",
+            Halt::InvalidEOFInitCode => "invalid eof code",
+            Halt::OverflowPayment | Halt::OutOfFund => "insufficient balance for transfer",
+            Halt::StateChangeDuringStaticCall | Halt::CallNotAllowedInsideStatic => {
+                "write protection"
+            }
+            Halt::CallTooDeep => "max call depth exceeded",
+            #[cfg(feature = "optimism")]
+            Halt::FailedDeposit => "failed deposit",
+        };
+        Self::server_error(message)
+    }
+
+    /// Error of the transaction that failed validation.
+    pub fn from_invalid_transaction(invalid: &InvalidTransaction) -> Self {
+        let message = match invalid {
+            InvalidTransaction::PriorityFeeGreaterThanMaxFee => {
+                "max priority fee per gas higher than max fee per gas"
+            }
+            InvalidTransaction::GasPriceLessThanBasefee => {
+                "max fee per gas less than block base fee"
+            }
+            InvalidTransaction::CallerGasLimitMoreThanBlock => "gas limit reached",
+            InvalidTransaction::CallGasCostMoreThanGasLimit => "intrinsic gas too low",
+            InvalidTransaction::RejectCallerWithCode => "sender not an eoa",
+            InvalidTransaction::LackOfFundForMaxFee { .. } => {
+                "insufficient funds for gas * price + value"
+            }
+            InvalidTransaction::OverflowPaymentInTransaction => "gas uint64 overflow",
+            InvalidTransaction::NonceOverflowInTransaction => "nonce has max value",
+            InvalidTransaction::NonceTooHigh { .. } => "nonce too high",
+            InvalidTransaction::NonceTooLow { .. } => "nonce too low",
+            InvalidTransaction::CreateInitcodeSizeLimit => "max initcode size exceeded",
+            InvalidTransaction::InvalidChainId => "invalid chain id for signer",
+            InvalidTransaction::AccessListNotSupported
+            | InvalidTransaction::MaxFeePerBlobGasNotSupported
+            | InvalidTransaction::BlobVersionedHashesNotSupported
+            | InvalidTransaction::UnsupportedChainTxKind(_) => "transaction type not supported",
+            InvalidTransaction::BlobGasPriceGreaterThanMax => {
+                "max fee per blob gas less than block blob gas fee"
+            }
+            InvalidTransaction::EmptyBlobs => "blobless blob transaction",
+            InvalidTransaction::BlobCreateTransaction => "blob transaction of type create",
+            InvalidTransaction::TooManyBlobs => "too many blobs",
+            InvalidTransaction::BlobVersionNotSupported => "invalid blob hash version",
+            #[cfg(feature = "optimism")]
+            InvalidTransaction::DepositSystemTxPostRegolith => {
+                "deposit system transactions post regolith hardfork are not supported"
+            }
+            #[cfg(feature = "optimism")]
+            InvalidTransaction::HaltedDepositPostRegolith => "failed deposit",
+        };
+        Self::server_error(message)
+    }
+
+    /// Error of the EVM that failed before execution. Header and database errors are internal
+    /// errors, as they are not caused by the transaction.
+    pub fn from_evm_error<DBError: fmt::Display>(error: &EVMError<DBError>) -> Self {
+        match error {
+            EVMError::Transaction(invalid) => Self::from_invalid_transaction(invalid),
+            EVMError::Header(invalid) => Self::internal_error(invalid),
+            EVMError::Database(e) => Self::internal_error(e),
+        }
+    }
+
+    /// Error of `eth_estimateGas` when execution fails even with the gas `cap`.
+    pub fn gas_required_exceeds_allowance(cap: u64) -> Self {
+        Self::server_error(&format!("gas required exceeds allowance ({cap})"))
+    }
+
+    fn internal_error(error: &dyn fmt::Display) -> Self {
+        Self {
+            code: Self::INTERNAL_ERROR,
+            message: error.to_string(),
+            data: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RpcError {}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{hex, GasBreakdown, U256};
+
+    #[test]
+    fn revert_reason_is_appended() {
+        // Error("insufficient balance")
+        let output = Bytes::from(hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000014"
+            "696e73756666696369656e742062616c616e6365000000000000000000000000"
+        ));
+        let gas = GasBreakdown {
+            intrinsic: 21_000,
+            ..Default::default()
+        };
+        let error = RpcError::from_result(&ExecutionResult::revert(gas, output.clone())).unwrap();
+        assert_eq!(error.code, RpcError::EXECUTION_REVERTED);
+        assert_eq!(error.message, "execution reverted: insufficient balance");
+        assert_eq!(error.data, Some(output));
+
+        // Custom errors are returned only as data.
+        let error = RpcError::reverted(Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(error.message, "execution reverted");
+        // Truncated reason.
+        let error = RpcError::reverted(Bytes::from(hex!("08c379a0ffffffff")));
+        assert_eq!(error.message, "execution reverted");
+    }
+
+    #[test]
+    fn errors_match_geth() {
+        let halt = RpcError::from_halt(Halt::OutOfGas(OutOfGasError::Memory));
+        assert_eq!((halt.code, halt.message.as_str()), (-32000, "out of gas"));
+
+        let error: EVMError<String> = InvalidTransaction::NonceTooLow { tx: 1, state: 2 }.into();
+        assert_eq!(RpcError::from_evm_error(&error).message, "nonce too low");
+        let error = InvalidTransaction::LackOfFundForMaxFee {
+            fee: Box::new(U256::from(2)),
+            balance: Box::new(U256::from(1)),
+        };
+        assert_eq!(
+            RpcError::from_invalid_transaction(&error).to_string(),
+            "insufficient funds for gas * price + value"
+        );
+
+        let error: EVMError<String> = EVMError::Database("missing trie node".to_string());
+        let error = RpcError::from_evm_error(&error);
+        assert_eq!(error.code, RpcError::INTERNAL_ERROR);
+        assert_eq!(error.message, "missing trie node");
+
+        assert_eq!(
+            RpcError::gas_required_exceeds_allowance(30_000_000).message,
+            "gas required exceeds allowance (30000000)"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Validation of the environment independent of the execution, e.g. for transaction pools.
+
+use crate::{
+    db::Database,
+    interpreter::gas::calculate_intrinsic_gas,
+    primitives::{Account, EVMError, Env, SpecId},
+};
+
+/// Validates the block and the transaction of the environment in the given spec.
+///
+/// These are the checks done before executing the transaction that don't need the state: fee
+/// cap against the base fee, chain id, gas limits, access list and blob rules, and the intrinsic
+/// gas. The checks of the caller account, such as its nonce and balance, are done by
+/// [`validate_env_against_state`].
+///
+/// Transactions of a [`ChainTx`](crate::handler::ChainTx) are validated with the mainnet rules.
+pub fn validate_env<DBError>(env: &Env, spec_id: SpecId) -> Result<(), EVMError<DBError>> {
+    // Important: validate block before tx.
+    env.validate_block_env_with_spec_id(spec_id)?;
+    env.validate_tx_with_spec_id(spec_id)?;
+    calculate_intrinsic_gas(&env.tx, spec_id)?;
+    Ok(())
+}
+
+/// Validates the environment like [`validate_env`] and the caller account loaded from the
+/// database: the nonce, the balance for the maximum fee and that it has no code.
+pub fn validate_env_against_state<DB: Database>(
+    env: &Env,
+    spec_id: SpecId,
+    db: &mut DB,
+) -> Result<(), EVMError<DB::Error>> {
+    validate_env(env, spec_id)?;
+    let info = db
+        .basic(env.tx.caller)
+        .map_err(EVMError::Database)?
+        .unwrap_or_default();
+    env.validate_tx_against_state(&mut Account::from(info))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{
+            AccountInfo, Address, InvalidHeader, InvalidTransaction, TransactTo, B256, U256,
+        },
+        Transact,
+    };
+    use core::convert::Infallible;
+
+    fn env() -> Env {
+        let mut env = Env::default();
+        env.cfg.chain_id = 1;
+        env.block.basefee = U256::from(10);
+        env.tx.caller = Address::repeat_byte(0x11);
+        env.tx.transact_to = TransactTo::Call(Address::repeat_byte(0x22));
+        env.tx.gas_limit = 21_000;
+        env.tx.gas_price = U256::from(10);
+        env.tx.chain_id = Some(1);
+        env
+    }
+
+    #[test]
+    fn validates_env_without_state() {
+        assert_eq!(validate_env::<Infallible>(&env(), SpecId::CANCUN), Ok(()));
+
+        let mut low_fee = env();
+        low_fee.tx.gas_price = U256::from(9);
+        assert_eq!(
+            validate_env::<Infallible>(&low_fee, SpecId::CANCUN),
+            Err(InvalidTransaction::GasPriceLessThanBasefee.into())
+        );
+        // There is no base fee before London.
+        assert_eq!(validate_env::<Infallible>(&low_fee, SpecId::BERLIN), Ok(()));
+
+        let mut other_chain = env();
+        other_chain.tx.chain_id = Some(5);
+        assert_eq!(
+            validate_env::<Infallible>(&other_chain, SpecId::CANCUN),
+            Err(InvalidTransaction::InvalidChainId.into())
+        );
+
+        let mut blob = env();
+        blob.tx.blob_hashes = vec![B256::repeat_byte(0x01)];
+        blob.tx.max_fee_per_blob_gas = Some(U256::from(1));
+        assert_eq!(
+            validate_env::<Infallible>(&blob, SpecId::SHANGHAI),
+            Err(InvalidTransaction::BlobVersionedHashesNotSupported.into())
+        );
+
+        let mut no_prevrandao = env();
+        no_prevrandao.block.prevrandao = None;
+        assert_eq!(
+            validate_env::<Infallible>(&no_prevrandao, SpecId::MERGE),
+            Err(InvalidHeader::PrevrandaoNotSet.into())
+        );
+
+        let mut low_gas = env();
+        low_gas.tx.gas_limit = 20_999;
+        assert_eq!(
+            validate_env::<Infallible>(&low_gas, SpecId::CANCUN),
+            Err(InvalidTransaction::CallGasCostMoreThanGasLimit.into())
+        );
+    }
+
+    #[test]
+    fn validates_env_like_transact() {
+        let mut env = env();
+        env.cfg.spec_id = SpecId::CANCUN;
+        env.tx.nonce = Some(1);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            env.tx.caller,
+            AccountInfo {
+                balance: U256::from(1_000_000),
+                nonce: 2,
+                ..Default::default()
+            },
+        );
+
+        let expected = Err(InvalidTransaction::NonceTooLow { tx: 1, state: 2 }.into());
+        assert_eq!(
+            validate_env_against_state(&env, SpecId::CANCUN, &mut db),
+            expected
+        );
+        assert_eq!(
+            crate::new_evm(&mut env.clone(), &mut db, None).preverify_transaction(),
+            expected
+        );
+
+        env.tx.nonce = Some(2);
+        assert_eq!(
+            validate_env_against_state(&env, SpecId::CANCUN, &mut db),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! # revm-wasm
+//!
+//! wasm-bindgen wrapper of revm for in-browser simulators.
+//!
+//! [`transact_json`] executes a transaction on a JSON environment and pre-state and returns the
+//! result and the changed accounts as JSON. On wasm it is exported to JavaScript as
+//! `transact(envJson, stateJson)`, which throws on invalid input or transactions.
+//!
+//! The environment uses the serde encoding of [`Env`], fields that are left out keep their
+//! default value. The state maps addresses to accounts:
+//!
+//! ```json
+//! { "0x1000000000000000000000000000000000000001": { "balance": "0x100", "nonce": 1, "code": "0x", "storage": { "0x0": "0x1" } } }
+//! ```
+#![warn(unreachable_pub, unused_crate_dependencies)]
+#![deny(unused_must_use, rust_2018_idioms)]
+
+use revm::{
+    primitives::{AccountInfo, Address, Bytecode, Bytes, Env, HashMap, KECCAK_EMPTY, U256},
+    InMemoryDB, EVM,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Account of the pre-state, all fields are optional.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+struct AccountState {
+    balance: U256,
+    nonce: u64,
+    code: Bytes,
+    storage: HashMap<U256, U256>,
+}
+
+/// Output of [`transact_json`].
+#[derive(Serialize)]
+struct Output {
+    result: revm::primitives::ExecutionResult,
+    state: revm::primitives::State,
+}
+
+/// Executes the transaction of the environment on the pre-state and returns the result and the
+/// changed accounts, `{ "result": ..., "state": ... }`.
+///
+/// Returns an error message if the input can't be decoded or the transaction is invalid.
+pub fn transact_json(env_json: &str, state_json: &str) -> Result<String, String> {
+    let mut env = serde_json::to_value(Env::default()).map_err(|e| e.to_string())?;
+    let patch: Value = serde_json::from_str(env_json).map_err(|e| format!("invalid env: {e}"))?;
+    merge(&mut env, patch);
+    let env: Env = serde_json::from_value(env).map_err(|e| format!("invalid env: {e}"))?;
+    let state: HashMap<Address, AccountState> =
+        serde_json::from_str(state_json).map_err(|e| format!("invalid state: {e}"))?;
+
+    let mut db = InMemoryDB::default();
+    for (address, account) in state {
+        let code = Bytecode::new_raw(account.code);
+        let info = AccountInfo::new(account.balance, account.nonce, KECCAK_EMPTY, code);
+        db.insert_account_info(address, info);
+        for (slot, value) in account.storage {
+            let Ok(()) = db.insert_account_storage(address, slot, value);
+        }
+    }
+
+    let mut evm = EVM::with_env(env);
+    evm.database(db);
+    let result_and_state = evm.transact().map_err(|e| e.to_string())?;
+    serde_json::to_string(&Output {
+        result: result_and_state.result,
+        state: result_and_state.state,
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Executes a transaction, see [`transact_json`].
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+pub fn transact(env_json: &str, state_json: &str) -> Result<String, wasm_bindgen::JsError> {
+    transact_json(env_json, state_json).map_err(|e| wasm_bindgen::JsError::new(&e))
+}
+
+/// Merges the fields of `patch` into `base`, recursing into objects.
+fn merge(base: &mut Value, patch: Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transacts_json() {
+        // SSTORE(0, CALLVALUE)
+        let state = r#"{
+            "0x1000000000000000000000000000000000000001": { "balance": "0x100" },
+            "0x2000000000000000000000000000000000000002": { "code": "0x3460005500", "storage": { "0x1": "0x7" } }
+        }"#;
+        let env = r#"{
+            "cfg": { "spec_id": "SHANGHAI" },
+            "tx": {
+                "caller": "0x1000000000000000000000000000000000000001",
+                "transact_to": { "Call": "0x2000000000000000000000000000000000000002" },
+                "value": "0x5",
+                "gas_limit": 100000
+            }
+        }"#;
+        let output: Value = serde_json::from_str(&transact_json(env, state).unwrap()).unwrap();
+        assert!(output["result"]["Success"].is_object(), "{output}");
+        let contract = &output["state"]["0x2000000000000000000000000000000000000002"];
+        assert_eq!(contract["info"]["balance"], "0x5");
+        assert_eq!(contract["storage"]["0x0"]["present_value"], "0x5");
+
+        let error = transact_json(r#"{ "tx": { "gas_limit": 1 } }"#, state).unwrap_err();
+        assert!(error.contains("CallGasCostMoreThanGasLimit"), "{error}");
+        assert!(transact_json("[]", "{}")
+            .unwrap_err()
+            .starts_with("invalid env"));
+    }
+}
@@ -0,0 +1,42 @@
+//! Executes a transfer on a chain whose native token has 6 decimals.
+//!
+//! Balances are stored in native units, [NativeDecimalsDB] scales them to 18 decimals for the
+//! EVM so that gas prices, fees and transferred value are all accounted in wei.
+
+use revm::{
+    db::{CacheDB, EmptyDB, NativeDecimalsDB},
+    primitives::{address, AccountInfo, TransactTo, U256},
+    Database, EVM,
+};
+
+/// Decimals of the native token.
+const DECIMALS: u8 = 6;
+
+fn main() {
+    let alice = address!("0000000000000000000000000000000000000a11");
+    let bob = address!("0000000000000000000000000000000000000b0b");
+
+    // Alice holds 10 native tokens, in the smallest native unit.
+    let mut db = CacheDB::new(EmptyDB::default());
+    db.insert_account_info(alice, AccountInfo::from_balance(U256::from(10_000_000)));
+
+    let mut evm = EVM::new();
+    evm.database(NativeDecimalsDB::new(db, DECIMALS));
+
+    // Alice sends 2.5 tokens to Bob, value and gas price are in 18 decimals.
+    evm.env.tx.caller = alice;
+    evm.env.tx.transact_to = TransactTo::Call(bob);
+    evm.env.tx.value = U256::from(2_500_000_000_000_000_000u128);
+    evm.env.tx.gas_price = U256::from(1_000_000_000);
+    evm.env.tx.gas_limit = 21_000;
+
+    let result = evm.transact_commit().expect("transfer is valid");
+    println!("Transfer result: {result:?}");
+
+    let db = evm.db().unwrap();
+    for (name, address) in [("Alice", alice), ("Bob", bob)] {
+        let native = db.db.accounts[&address].info.balance;
+        let evm_balance = db.basic(address).unwrap().unwrap().balance;
+        println!("{name}: {native} native units, {evm_balance} wei seen by the EVM");
+    }
+}